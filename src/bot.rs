@@ -0,0 +1,260 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle};
+use crate::config::{Config, Network};
+use crate::logging::Logger;
+use crate::mempool::solana::SolanaMempool;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::metrics_collector::MetricsCollector;
+use crate::utils::mev_strategies::MevStrategyType;
+use crate::utils::preflight::{self, PreflightReport};
+use crate::utils::risk_controls::RiskManager;
+use crate::utils::supervisor::{supervise, RestartPolicy, RestartTracker};
+
+// A Config (Config::default() unless one is given) plus overrides, which win over it in whatever
+// order they're set
+#[derive(Debug, Default)]
+pub struct BotBuilder {
+    config: Option<Config>,
+    network: Option<Network>,
+    strategies: Option<Vec<MevStrategyType>>,
+    dry_run: Option<bool>,
+    metrics_addr: Option<SocketAddr>,
+    keypair_path: Option<String>,
+    mainnet_confirmed: bool,
+}
+
+impl BotBuilder {
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    pub fn with_strategies(mut self, strategies: Vec<MevStrategyType>) -> Self {
+        self.strategies = Some(strategies);
+        self
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    // Where /metrics and /healthz are served; without one the metrics stay in-process
+    pub fn with_metrics_addr(mut self, addr: SocketAddr) -> Self {
+        self.metrics_addr = Some(addr);
+        self
+    }
+
+    pub fn with_keypair_path(mut self, path: &str) -> Self {
+        self.keypair_path = Some(path.to_string());
+        self
+    }
+
+    // Live trading on mainnet refuses to build without it, as CONFIRM_MAINNET=yes does for the binary
+    pub fn with_mainnet_confirmed(mut self, confirmed: bool) -> Self {
+        self.mainnet_confirmed = confirmed;
+        self
+    }
+
+    // Applies the overrides and validates the result the way startup does
+    pub fn build(self) -> Result<Bot, Box<dyn std::error::Error + Send + Sync>> {
+        let mut config = self.config.unwrap_or_default();
+        if let Some(network) = self.network {
+            config.set_network(network);
+        }
+        if let Some(strategies) = self.strategies {
+            config.strategies.enabled = strategies.iter().map(|strategy| strategy.name().to_string()).collect();
+        }
+        if let Some(dry_run) = self.dry_run {
+            config.dry_run = dry_run;
+        }
+        if let Some(addr) = self.metrics_addr {
+            config.metrics.addr = Some(addr);
+        }
+        if let Some(path) = self.keypair_path {
+            config.keypair_path = path;
+        }
+
+        config.validate()?;
+        let errors = config.requirement_errors();
+        if !errors.is_empty() {
+            return Err(format!("Invalid configuration:\n  - {}", errors.join("\n  - ")).into());
+        }
+        config.check_network_safety(self.mainnet_confirmed)?;
+        Ok(Bot { config })
+    }
+}
+
+// The whole bot behind one validated Config: every component start() builds (RPC manager,
+// mempool, executor, risk controls) is built from it
+#[derive(Debug)]
+pub struct Bot {
+    config: Config,
+}
+
+impl Bot {
+    pub fn builder() -> BotBuilder {
+        BotBuilder::default()
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // Probes endpoints, keypair and balance without starting anything
+    pub async fn preflight(&self) -> PreflightReport {
+        preflight::run(&self.config).await
+    }
+
+    // Starts the metrics server (with an address) and the supervised mempool, and returns once
+    // they're running
+    pub async fn start(self) -> Result<BotHandle, Box<dyn std::error::Error + Send + Sync>> {
+        let config = Arc::new(self.config);
+
+        let rpc_manager = Arc::new(RpcManager::from_config(&config).await?);
+        let mempool = SolanaMempool::with_rpc_manager(Arc::clone(&config), rpc_manager).await
+            .map_err(|e| format!("Failed to initialize Solana mempool: {}", e))?;
+        let metrics = mempool.metrics_collector().ok_or("metrics collector is not initialized")?;
+        let risk = mempool.risk_manager().ok_or("risk controls are not initialized")?;
+        Logger::solana_monitor_start();
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let restarts = RestartTracker::new();
+        // Shares the mempool's metrics collector and risk manager, and stops with it
+        let (metrics_addr, metrics_server) = match (config.metrics.addr, mempool.metrics_server()) {
+            (Some(addr), Some(server)) => {
+                let (addr, handle) = server.with_restarts(restarts.clone()).start(addr, shutdown_rx.clone())?;
+                (Some(addr), Some(handle))
+            }
+            _ => (None, None),
+        };
+
+        // A panicking or exiting mempool is restarted with backoff; every attempt is a clone of the
+        // same mempool, so the collector and risk manager handed out below carry over
+        let policy = RestartPolicy::from_config(&config.supervisor);
+        let tracker = restarts.clone();
+        let supervisor = tokio::spawn(async move {
+            supervise("Solana mempool", &policy, &tracker, shutdown_rx, move |attempt_shutdown| {
+                let mempool = mempool.clone();
+                async move { mempool.start(attempt_shutdown).await }
+            }).await
+        });
+
+        Ok(BotHandle {
+            shutdown_tx,
+            supervisor: Some(supervisor),
+            outcome: None,
+            metrics_server,
+            metrics_addr,
+            metrics,
+            risk,
+            restarts,
+        })
+    }
+}
+
+// A running bot
+pub struct BotHandle {
+    shutdown_tx: watch::Sender<bool>,
+    supervisor: Option<JoinHandle<Result<(), String>>>,
+    outcome: Option<Result<(), String>>, // Once the supervisor has been joined
+    metrics_server: Option<JoinHandle<()>>,
+    metrics_addr: Option<SocketAddr>,
+    metrics: Arc<MetricsCollector>,
+    risk: Arc<RiskManager>,
+    restarts: RestartTracker,
+}
+
+impl BotHandle {
+    pub fn metrics(&self) -> Arc<MetricsCollector> {
+        Arc::clone(&self.metrics)
+    }
+
+    pub fn risk(&self) -> Arc<RiskManager> {
+        Arc::clone(&self.risk)
+    }
+
+    pub fn restarts(&self) -> RestartTracker {
+        self.restarts.clone()
+    }
+
+    // The address the metrics server is bound to; None when it isn't running
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics_addr
+    }
+
+    // Resolves only if the mempool stops for good on its own, i.e. it kept crashing past the
+    // restart limit; stop() still has to be called for the rest. Safe to use in select!
+    pub async fn finished(&mut self) -> Result<(), String> {
+        if let Some(supervisor) = self.supervisor.as_mut() {
+            let outcome = supervisor_outcome(supervisor.await);
+            self.supervisor = None;
+            self.outcome = Some(outcome);
+        }
+        self.outcome.clone().unwrap_or(Ok(()))
+    }
+
+    // Drains in-flight executions, flushes state and stops the metrics server. Err when the
+    // mempool had already given up
+    pub async fn stop(mut self) -> Result<(), String> {
+        let _ = self.shutdown_tx.send(true);
+        let outcome = self.finished().await;
+        if let Some(metrics_server) = self.metrics_server.take() {
+            let _ = metrics_server.await;
+        }
+        outcome
+    }
+}
+
+fn supervisor_outcome(result: Result<Result<(), String>, JoinError>) -> Result<(), String> {
+    match result {
+        Ok(outcome) => outcome,
+        Err(e) => Err(format!("Mempool supervisor ended abnormally: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_win_over_the_config() {
+        let mut config = Config::default();
+        config.rpc.helius = Some("https://helius.example".to_string());
+        config.strategies.enabled = vec!["sandwich".to_string()];
+
+        let bot = Bot::builder()
+            .with_dry_run(true)
+            .with_strategies(vec![MevStrategyType::Arbitrage, MevStrategyType::Snipe])
+            .with_keypair_path("wallet.json")
+            .with_config(config)
+            .build()
+            .unwrap();
+        assert_eq!(bot.config().enabled_strategies().unwrap(), vec![MevStrategyType::Arbitrage, MevStrategyType::Snipe]);
+        assert!(bot.config().dry_run);
+        assert_eq!(bot.config().keypair_path, "wallet.json");
+    }
+
+    #[test]
+    fn test_build_validates_like_startup() {
+        let error = Bot::builder().build().unwrap_err().to_string();
+        assert!(error.contains("HELIUS is not set"), "{}", error);
+
+        let mut config = Config::default();
+        config.rpc.helius = Some("https://helius.example".to_string());
+        config.rpc.drpc = Some("https://drpc.example".to_string());
+        let error = Bot::builder().with_config(config.clone()).with_network(Network::Mainnet).build().unwrap_err().to_string();
+        assert!(error.contains("Refusing to trade real funds on MAINNET"), "{}", error);
+
+        let bot = Bot::builder().with_config(config).with_network(Network::Mainnet).with_mainnet_confirmed(true).build().unwrap();
+        // Tightened as if NETWORK=mainnet had been set when loading
+        assert_eq!(bot.config().risk.global_daily_spending_limit, 2.0);
+    }
+}
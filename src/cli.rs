@@ -0,0 +1,189 @@
+use anyhow::{anyhow, bail, Result};
+use clap::{Args, Parser, Subcommand};
+use colored::Colorize;
+use solana_sdk::signature::{read_keypair_file, Signer};
+
+use rust_mev_hybrid_bot::config::{self, redact_url, Config, CONFIRM_MAINNET_VAR};
+use rust_mev_hybrid_bot::logging::Logger;
+use rust_mev_hybrid_bot::rpc::rpc_manager::RpcManager;
+use rust_mev_hybrid_bot::utils::metrics_state::MetricsState;
+use rust_mev_hybrid_bot::utils::mev_strategies::StrategyThresholds;
+use rust_mev_hybrid_bot::utils::preflight;
+use rust_mev_hybrid_bot::utils::simulation_effects::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+#[derive(Debug, Parser)]
+#[command(name = "mev-bot", version, about = "Solana MEV bot")]
+pub struct Cli {
+    /// TOML config file; env vars and flags override its values [default: bot.toml if present]
+    #[arg(long, global = true, env = "BOT_CONFIG")]
+    pub config: Option<String>,
+
+    #[command(flatten)]
+    pub overrides: Overrides,
+
+    // No subcommand runs the bot, as a plain `cargo run` always has
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the bot (the default)
+    Run,
+    /// Validate the configuration, run the preflight checks, print the resolved settings and exit
+    Check,
+    /// Print the wallet's SOL and token balances
+    Balance {
+        /// Keypair file of the wallet [default: keypair_path from the config]
+        #[arg(long)]
+        keypair: Option<String>,
+    },
+    /// Print the metrics saved in the metrics state file as JSON
+    Metrics {
+        /// Metrics state file [default: metrics.state_file from the config]
+        #[arg(long)]
+        file: Option<String>,
+    },
+}
+
+// Flags that take precedence over the env var of the same name, over .env and over the config file
+#[derive(Debug, Args)]
+pub struct Overrides {
+    /// Network to run on (NETWORK)
+    #[arg(long, global = true, value_parser = ["mainnet", "testnet", "devnet"], ignore_case = true)]
+    pub network: Option<String>,
+    /// Comma separated strategies, e.g. arbitrage,sandwich (STRATEGY)
+    #[arg(long, global = true)]
+    pub strategy: Option<String>,
+    /// Run everything but never submit a transaction or bundle (DRY_RUN=true)
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Allow trading real funds on mainnet (CONFIRM_MAINNET=yes)
+    #[arg(long, global = true)]
+    pub i_understand_mainnet: bool,
+}
+
+impl Overrides {
+    // Written into the environment, where Config::load picks them up as overrides; called before
+    // the config is loaded
+    pub fn apply(&self) {
+        if let Some(network) = &self.network {
+            std::env::set_var("NETWORK", network.to_lowercase());
+        }
+        if let Some(strategy) = &self.strategy {
+            std::env::set_var("STRATEGY", strategy);
+        }
+        if self.dry_run {
+            std::env::set_var("DRY_RUN", "true");
+        }
+        if self.i_understand_mainnet {
+            std::env::set_var(CONFIRM_MAINNET_VAR, "yes");
+        }
+    }
+}
+
+// The parsed STRATEGY list, as the banner and check show it
+pub fn strategy_names(config: &Config) -> Result<String> {
+    let strategies = config.enabled_strategies().map_err(|e| anyhow!(e))?;
+    Ok(strategies.iter().map(|strategy| strategy.name()).collect::<Vec<_>>().join(", "))
+}
+
+// Out-of-range thresholds stop the bot at startup instead of quietly trading on them
+pub fn thresholds(config: &Config) -> Result<StrategyThresholds> {
+    StrategyThresholds::from_config(&config.strategies).map_err(|e| anyhow!(e))
+}
+
+// Prints the resolved settings, then runs the same preflight as `run`. Fails when any check
+// does, so it can gate a deploy
+pub async fn check(config: &Config) -> Result<()> {
+    println!("Network: {}", config.network().label());
+    println!("Strategies: {}", strategy_names(config)?);
+    println!("Dry run: {}", if config.dry_run { "yes" } else { "no" });
+    println!("Thresholds: {}", thresholds(config)?);
+    println!("Metrics server: {}", config.metrics.addr.map(|addr| addr.to_string()).unwrap_or_else(|| "disabled".to_string()));
+    let urls = [("HELIUS", &config.rpc.helius), ("DRPC", &config.rpc.drpc), ("JITO_RPC_URL", &config.jito.rpc_url)];
+    for (var, url) in urls {
+        println!("{}: {}", var, url.as_deref().map(redact_url).unwrap_or_else(|| "not set".to_string()));
+    }
+    println!("\nResolved configuration:\n{}\n", config);
+    config.check_network_safety(config::mainnet_confirmed()).map_err(|e| anyhow!(e))?;
+
+    preflight(config).await?;
+    println!("Configuration OK");
+    Ok(())
+}
+
+// RPC endpoints, WebSocket subscription, Jito, keypair and balance, one line each; a failed check
+// comes with what to change, and any failure stops the caller
+pub async fn preflight(config: &Config) -> Result<()> {
+    let report = preflight::run(config).await;
+    for check in &report.checks {
+        match &check.outcome {
+            Ok(detail) => println!("{}: {} ({})", check.name, "ok".green(), detail),
+            Err(failure) => println!("{}: {} ({})\n  -> {}", check.name, "FAILED".red(), failure.error, failure.hint),
+        }
+    }
+
+    let failures = report.failures();
+    if !failures.is_empty() {
+        let hints: Vec<String> = failures.iter()
+            .filter_map(|check| check.outcome.as_ref().err().map(|failure| format!("{}: {}", check.name, failure.hint)))
+            .collect();
+        bail!("{} preflight check(s) failed:\n  - {}", failures.len(), hints.join("\n  - "));
+    }
+    Ok(())
+}
+
+// Shown before live trading on mainnet starts. A balance that can't be fetched doesn't stop the
+// bot here; the wallet file has to be readable, the executor needs it anyway
+pub async fn mainnet_warning(config: &Config) -> Result<()> {
+    let wallet = wallet_address(&config.keypair_path)?;
+    let balance = match RpcManager::from_config(config).await {
+        Ok(rpc_manager) => rpc_manager.get_balance(&wallet).await.ok(),
+        Err(_) => None,
+    };
+    Logger::mainnet_warning(&wallet, balance.map(|lamports| lamports as f64 / 1_000_000_000.0));
+    Ok(())
+}
+
+fn wallet_address(keypair_path: &str) -> Result<String> {
+    let keypair = read_keypair_file(keypair_path)
+        .map_err(|e| anyhow!("Failed to read keypair {}: {}", keypair_path, e))?;
+    Ok(keypair.pubkey().to_string())
+}
+
+// SOL and every non-empty token account of the wallet, under both token programs
+pub async fn balance(config: &Config, keypair_path: &str) -> Result<()> {
+    let wallet = wallet_address(keypair_path)?;
+    println!("Wallet: {}", wallet);
+
+    let rpc_manager = RpcManager::from_config(config).await.map_err(|e| anyhow!(e))?;
+    let lamports = rpc_manager.get_balance(&wallet).await
+        .map_err(|e| anyhow!("Failed to fetch the SOL balance: {}", e))?;
+    println!("SOL: {:.9}", lamports as f64 / 1_000_000_000.0);
+
+    for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+        let accounts = rpc_manager.get_token_accounts_by_owner(&wallet, program_id).await
+            .map_err(|e| anyhow!("Failed to fetch token accounts: {}", e))?;
+        for account in accounts {
+            let info = &account["data"]["parsed"]["info"];
+            let amount = &info["tokenAmount"];
+            if amount["amount"].as_str().unwrap_or("0") == "0" {
+                continue;
+            }
+            println!(
+                "{}: {}",
+                info["mint"].as_str().unwrap_or("unknown mint"),
+                amount["uiAmountString"].as_str().unwrap_or("?")
+            );
+        }
+    }
+    Ok(())
+}
+
+// The MetricsCollector totals from the persisted state, as pretty JSON on stdout
+pub fn metrics(path: &str) -> Result<()> {
+    let state = MetricsState::read(path).map_err(|e| anyhow!(e))?;
+    println!("{}", serde_json::to_string_pretty(&state.metrics)?);
+    Ok(())
+}
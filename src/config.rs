@@ -1,3 +1,19 @@
+use std::fmt;
+use std::net::SocketAddr;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use crate::utils::mev_strategies::MevStrategyType;
+use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+// Read when neither --config nor BOT_CONFIG names a file; running without it is fine
+pub const DEFAULT_CONFIG_PATH: &str = "bot.toml";
+
+// Must be "yes" (or --i-understand-mainnet given) before the bot trades real funds on mainnet.
+// Deliberately not a config file key
+pub const CONFIRM_MAINNET_VAR: &str = "CONFIRM_MAINNET";
+
+const REDACTED: &str = "<redacted>";
+
 #[derive(Clone, Debug)]
 pub enum Network {
     Mainnet,
@@ -6,18 +22,1567 @@ pub enum Network {
 }
 
 impl Network {
-    pub fn rpc_url_sol(&self) -> String {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "mainnet" => Some(Network::Mainnet),
+            "testnet" => Some(Network::Testnet),
+            "devnet" => Some(Network::Devnet),
+            _ => None,
+        }
+    }
+
+    // As shown in the startup banner
+    pub fn label(&self) -> &'static str {
         match self {
-            Network::Testnet => std::env::var("SOL_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
-            Network::Devnet => std::env::var("SOL_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
-            Network::Mainnet => {
-                let api_key = std::env::var("HELIUS_API_KEY").unwrap_or_else(|_| "TU_KEY".to_string());
-                if api_key == "TU_KEY" {
-                    std::env::var("SOL_RPC_URL").unwrap_or_else(|_| format!("https://mainnet.helius-rpc.com/?api-key={}", api_key))
-                } else {
-                    format!("https://mainnet.helius-rpc.com/?api-key={}", api_key)
-                }
+            Network::Mainnet => "MAINNET",
+            Network::Testnet => "TESTNET",
+            Network::Devnet => "DEVNET",
+        }
+    }
+}
+
+// The bot's settings: the defaults below, then bot.toml, then env vars, validated once at
+// startup and shared by Arc. Each field keeps the env var it has always been read from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub network: String,                // NETWORK: mainnet, testnet or devnet
+    pub dry_run: bool,                  // DRY_RUN
+    pub shutdown_timeout_ms: u64,       // SHUTDOWN_TIMEOUT_MS: bounded wait for in-flight executions
+    pub skip_preflight: bool,           // SKIP_PREFLIGHT: start without probing endpoints, keypair and balance
+    pub keypair_path: String,           // KEYPAIR_PATH: the wallet the executor signs with
+    pub wallet_address: Option<String>, // WALLET_ADDRESS: our own transactions, left out of the competition counts
+    pub rpc: RpcConfig,
+    pub jito: JitoConfig,
+    pub bundles: BundlesConfig,
+    pub strategies: StrategiesConfig,
+    pub snipe: SnipeConfig,
+    pub liquidation: LiquidationConfig,
+    pub simulation: SimulationConfig,
+    pub token_safety: TokenSafetyConfig,
+    pub filters: FiltersConfig,
+    pub pools: PoolsConfig,
+    pub prices: PricesConfig,
+    pub congestion: CongestionConfig,
+    pub risk: RiskConfig,
+    pub fees: FeesConfig,
+    pub metrics: MetricsConfig,
+    pub alerts: AlertsConfig,
+    pub supervisor: SupervisorConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    pub helius: Option<String>,         // HELIUS: reads and simulation
+    pub drpc: Option<String>,           // DRPC: fallback
+    pub solana_rpc_url: Option<String>, // SOLANA_RPC_URL; the network's public endpoint when unset
+    pub solana_ws_url: Option<String>,  // SOLANA_WS_URL; likewise
+    pub solana_ws_urls: Vec<String>,    // SOLANA_WS_URLS: several endpoints to race, comma separated in the env
+    pub health_check_ms: u64,           // RPC_HEALTH_CHECK_MS
+    pub degraded_after_failures: u32,   // RPC_DEGRADED_AFTER_FAILURES
+    pub degraded_latency_ms: f64,       // RPC_DEGRADED_LATENCY_MS
+    pub routing_policy: Vec<String>,    // RPC_ROUTING_POLICY: task=policy entries
+    pub latency_report_secs: u64,       // RPC_LATENCY_REPORT_SECS; 0 turns the report off
+    pub retry_budget: u32,              // RPC_RETRY_BUDGET
+    pub retry_base_ms: u64,             // RPC_RETRY_BASE_MS
+    pub max_retry_wait_ms: u64,         // RPC_MAX_RETRY_WAIT_MS
+    pub shed_task_types: Vec<String>,   // RPC_SHED_TASK_TYPES: turned away while an endpoint throttles, e.g. read,simulate
+    pub max_batch_size: Vec<String>,    // RPC_MAX_BATCH_SIZE: endpoint=size entries
+    pub rate_limits: Vec<String>,       // RPC_RATE_LIMITS: endpoint=requests per second entries
+    pub max_in_flight: Vec<String>,     // RPC_MAX_IN_FLIGHT: endpoint=requests entries
+    pub limit_max_wait_ms: u64,         // RPC_LIMIT_MAX_WAIT_MS
+    pub breaker_failures: u32,          // RPC_BREAKER_FAILURES; 0 disables the circuit breaker
+    pub breaker_window_ms: u64,         // RPC_BREAKER_WINDOW_MS
+    pub breaker_cooldown_ms: u64,       // RPC_BREAKER_COOLDOWN_MS
+    pub tx_fetch_batch_window_ms: u64,  // TX_FETCH_BATCH_WINDOW_MS
+    pub tx_fetch_batch_max: usize,      // TX_FETCH_BATCH_MAX
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JitoConfig {
+    pub use_jito: bool,                    // USE_JITO
+    pub rpc_url: Option<String>,           // JITO_RPC_URL
+    pub tip_account: Option<String>,       // JITO_TIP_ACCOUNT
+    pub auth_header: Option<String>,       // JITO_AUTH_HEADER
+    pub auth_keypair_path: Option<String>, // JITO_AUTH_KEYPAIR_PATH: searcher auth, replaces auth_header
+    pub auth_url: Option<String>,          // JITO_AUTH_URL: the block engine serving the auth service
+    pub simulation_url: Option<String>,    // JITO_SIMULATION_URL: simulateBundle; rpc_url when unset
+    pub tip_percentile: u32,               // JITO_TIP_PERCENTILE: 25, 50, 75 or 95
+    pub tip_target_landing_rate: f64,      // JITO_TIP_TARGET_LANDING_RATE
+    pub tip_history_file: String,          // TIP_HISTORY_FILE
+    pub tip_history_half_life_hours: f64,  // TIP_HISTORY_HALF_LIFE_HOURS
+    pub tip_history_flush_secs: u64,       // TIP_HISTORY_FLUSH_SECS
+    pub tip_floor_url: String,             // JITO_TIP_FLOOR_URL
+    pub tip_floor_poll_ms: u64,            // JITO_TIP_FLOOR_POLL_MS
+    pub tip_floor_max_age_secs: u64,       // JITO_TIP_FLOOR_MAX_AGE_SECS
+    pub leader_max_wait_ms: u64,           // JITO_LEADER_MAX_WAIT_MS
+    pub leader_schedule_poll_ms: u64,      // LEADER_SCHEDULE_POLL_MS
+    pub validators_url: Option<String>,    // JITO_VALIDATORS_URL
+    pub validators: Vec<String>,           // JITO_VALIDATORS: identities known to run Jito
+    pub max_bundles_per_second: f64,       // JITO_MAX_BUNDLES_PER_SECOND
+    pub max_queued_bundles: usize,         // JITO_MAX_QUEUED_BUNDLES
+    pub max_queue_wait_ms: u64,            // JITO_MAX_QUEUE_WAIT_MS
+}
+
+// How a submitted bundle is watched, retried and tipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BundlesConfig {
+    pub landing_slots: u64,      // BUNDLE_LANDING_SLOTS
+    pub max_retries: u32,        // BUNDLE_MAX_RETRIES
+    pub tip_multiplier: f64,     // BUNDLE_TIP_MULTIPLIER
+    pub max_tip_share: f64,      // BUNDLE_MAX_TIP_SHARE: of the estimated profit
+    pub status_poll_ms: u64,     // BUNDLE_STATUS_POLL_MS
+    pub status_expiry_secs: u64, // BUNDLE_STATUS_EXPIRY_SECS
+    pub rpc_fallback: bool,      // JITO_RPC_FALLBACK: send over RPC when the block engine fails
+    pub simulate: bool,          // SIMULATE_BUNDLES
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategiesConfig {
+    pub enabled: Vec<String>,               // STRATEGY, comma separated in the env
+    pub opportunity_workers: usize,         // OPPORTUNITY_WORKERS
+    pub max_notification_age_ms: u64,       // MAX_NOTIFICATION_AGE_MS
+    pub triangular_arb_interval_ms: u64,    // TRIANGULAR_ARB_INTERVAL_MS; 0 disables the search
+    pub liquidation_scan_interval_ms: u64,  // LIQUIDATION_SCAN_INTERVAL_MS
+    pub min_arbitrage_profit_sol: f64,      // MIN_ARBITRAGE_PROFIT_SOL
+    pub min_sandwich_profit_sol: f64,       // MIN_SANDWICH_PROFIT_SOL
+    pub min_frontrun_profit_sol: f64,       // MIN_FRONTRUN_PROFIT_SOL
+    pub max_slippage_bps: u32,              // MAX_SLIPPAGE_BPS
+    pub max_price_impact_bps: u32,          // MAX_PRICE_IMPACT_BPS
+    pub position_size_limit_sol: f64,       // POSITION_SIZE_LIMIT: largest front-run profit estimates are sized for
+    pub triangular_min_liquidity_sol: f64,  // TRIANGULAR_MIN_LIQUIDITY_SOL
+    pub opportunity_ttl_ms: u64,            // OPPORTUNITY_TTL_MS
+    pub opportunity_queue_capacity: usize,  // OPPORTUNITY_QUEUE_CAPACITY
+    pub max_concurrent_analyses: usize,     // MAX_CONCURRENT_ANALYSES
+    pub analysis_permit_timeout_ms: u64,    // ANALYSIS_PERMIT_TIMEOUT_MS
+    pub competition_window_secs: u64,       // COMPETITION_WINDOW_SECS
+    pub pumpfun_min_buy_sol: f64,           // PUMPFUN_MIN_BUY_SOL
+    pub pumpfun_max_frontrun_sol: f64,      // PUMPFUN_MAX_FRONTRUN_SOL
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnipeConfig {
+    pub quote_mints: Vec<String>,    // SNIPE_QUOTE_MINTS
+    pub buy_amount_sol: f64,         // SNIPE_BUY_AMOUNT_SOL
+    pub target_return: f64,          // SNIPE_TARGET_RETURN
+    pub min_pool_age_slots: u64,     // SNIPE_MIN_POOL_AGE_SLOTS
+    pub max_pool_age_slots: u64,     // SNIPE_MAX_POOL_AGE_SLOTS; 0 removes the cap
+    pub min_liquidity_sol: f64,      // SNIPE_MIN_LIQUIDITY_SOL
+    pub min_lp_locked_share: f64,    // SNIPE_MIN_LP_LOCKED_SHARE
+    pub lp_lockers: Vec<String>,     // SNIPE_LP_LOCKERS
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LiquidationConfig {
+    pub lending_market: Option<String>, // SOLEND_LENDING_MARKET; Solend's main market when unset
+    pub max_capital_sol: f64,           // LIQUIDATION_MAX_CAPITAL_SOL
+    pub min_profit_sol: f64,            // LIQUIDATION_MIN_PROFIT_SOL
+    pub near_threshold: f64,            // LIQUIDATION_NEAR_THRESHOLD
+}
+
+// What a simulation has to show before a bundle is sent, and how it's cross-checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub min_arbitrage_profit_sol: f64,        // SIMULATION_MIN_ARBITRAGE_PROFIT_SOL
+    pub min_sandwich_profit_sol: f64,         // SIMULATION_MIN_SANDWICH_PROFIT_SOL
+    pub min_frontrun_profit_sol: f64,         // SIMULATION_MIN_FRONTRUN_PROFIT_SOL
+    pub min_backrun_profit_sol: f64,          // SIMULATION_MIN_BACKRUN_PROFIT_SOL
+    pub min_other_profit_sol: f64,            // SIMULATION_MIN_OTHER_PROFIT_SOL
+    pub max_variance: f64,                    // SIMULATION_MAX_VARIANCE
+    pub strong_profit_sol: f64,               // SIMULATION_STRONG_PROFIT_SOL
+    pub low_variance: f64,                    // SIMULATION_LOW_VARIANCE
+    pub confidence_profit_weight: f64,        // SIMULATION_CONFIDENCE_PROFIT_WEIGHT
+    pub confidence_variance_weight: f64,      // SIMULATION_CONFIDENCE_VARIANCE_WEIGHT
+    pub confidence_profitability_weight: f64, // SIMULATION_CONFIDENCE_PROFITABILITY_WEIGHT
+    pub cache_ttl_ms: u64,                    // SIMULATION_CACHE_TTL_MS
+    pub reserve_perturbation: f64,            // SIMULATION_RESERVE_PERTURBATION
+    pub trade_size_perturbation: f64,         // SIMULATION_TRADE_SIZE_PERTURBATION
+    pub variance_budget_ms: u64,              // SIMULATION_VARIANCE_BUDGET_MS
+    pub consensus_endpoints: usize,           // SIMULATION_CONSENSUS_ENDPOINTS
+    pub consensus_tolerance: f64,             // SIMULATION_CONSENSUS_TOLERANCE
+    pub consensus_deadline_ms: u64,           // SIMULATION_CONSENSUS_DEADLINE_MS
+    pub consensus_penalty: f64,               // SIMULATION_CONSENSUS_PENALTY
+    pub accuracy_window: usize,               // SIMULATION_ACCURACY_WINDOW
+    pub accuracy_min_samples: usize,          // SIMULATION_ACCURACY_MIN_SAMPLES
+    pub accuracy_alert_floor: f64,            // SIMULATION_ACCURACY_ALERT_FLOOR
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenSafetyConfig {
+    pub allow_mint_authority: bool,     // TOKEN_SAFETY_ALLOW_MINT_AUTHORITY
+    pub allow_freeze_authority: bool,   // TOKEN_SAFETY_ALLOW_FREEZE_AUTHORITY
+    pub max_transfer_fee_bps: u16,      // TOKEN_SAFETY_MAX_TRANSFER_FEE_BPS
+    pub allow_transfer_hook: bool,      // TOKEN_SAFETY_ALLOW_TRANSFER_HOOK
+    pub allow_permanent_delegate: bool, // TOKEN_SAFETY_ALLOW_PERMANENT_DELEGATE
+    pub max_top_holder_share: f64,      // TOKEN_SAFETY_MAX_TOP_HOLDER_SHARE; 0 skips the holder check
+    pub failure_ttl_secs: u64,          // TOKEN_SAFETY_FAILURE_TTL_SECS
+}
+
+// Liquidity floors per strategy, each falling back to the default floor, and the filter recalibration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FiltersConfig {
+    pub min_pool_liquidity_sol: f64,                    // MIN_POOL_LIQUIDITY_SOL
+    pub min_volume_24h_usd: f64,                        // MIN_VOLUME_24H_USD
+    pub max_trade_to_liquidity: f64,                    // MAX_TRADE_TO_LIQUIDITY
+    pub arbitrage_min_pool_liquidity_sol: Option<f64>,  // ARBITRAGE_MIN_POOL_LIQUIDITY_SOL
+    pub arbitrage_min_volume_24h_usd: Option<f64>,      // ARBITRAGE_MIN_VOLUME_24H_USD
+    pub arbitrage_max_trade_to_liquidity: Option<f64>,  // ARBITRAGE_MAX_TRADE_TO_LIQUIDITY
+    pub sandwich_min_pool_liquidity_sol: Option<f64>,   // SANDWICH_MIN_POOL_LIQUIDITY_SOL
+    pub sandwich_min_volume_24h_usd: Option<f64>,       // SANDWICH_MIN_VOLUME_24H_USD
+    pub sandwich_max_trade_to_liquidity: Option<f64>,   // SANDWICH_MAX_TRADE_TO_LIQUIDITY
+    pub frontrun_min_pool_liquidity_sol: Option<f64>,   // FRONTRUN_MIN_POOL_LIQUIDITY_SOL
+    pub frontrun_min_volume_24h_usd: Option<f64>,       // FRONTRUN_MIN_VOLUME_24H_USD
+    pub frontrun_max_trade_to_liquidity: Option<f64>,   // FRONTRUN_MAX_TRADE_TO_LIQUIDITY
+    pub target_precision: f64,                          // FILTER_TARGET_PRECISION
+    pub target_recall: f64,                             // FILTER_TARGET_RECALL
+    pub shadow_rejections: bool,                        // FILTER_SHADOW_REJECTIONS; bundle-simulate rejections to label them
+    pub recalibration_min_samples: usize,               // FILTER_RECALIBRATION_MIN_SAMPLES
+    pub recalibration_secs: u64,                        // FILTER_RECALIBRATION_SECS; 0 disables recalibration
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolsConfig {
+    pub registry_file: Option<String>,      // POOLS_FILE; pools.json is read if it exists when unset
+    pub registry_auto_add: bool,            // POOL_REGISTRY_AUTO_ADD
+    pub dex_registry_file: Option<String>,  // DEX_REGISTRY_FILE
+    pub watcher_max_pools: usize,           // POOL_WATCHER_MAX_POOLS; 0 disables the watcher
+    pub dex_monitor_max_pool_age_ms: u64,   // DEX_MONITOR_MAX_POOL_AGE_MS
+    pub cache_ttl_ms: u64,                  // POOL_CACHE_TTL_MS
+    pub cache_capacity: usize,              // POOL_CACHE_CAPACITY
+    pub price_cache_ttl_ms: u64,            // PRICE_CACHE_TTL_MS
+    pub price_cache_capacity: usize,        // PRICE_CACHE_CAPACITY
+    pub lock_timeout_ms: u64,               // POOL_LOCK_TIMEOUT_MS
+    pub failure_cooldown_ms: u64,           // POOL_FAILURE_COOLDOWN_MS
+    pub blacklist_file: String,             // POOL_BLACKLIST_FILE
+    pub blacklist_config: Option<String>,   // POOL_BLACKLIST_CONFIG: manual entries; must exist when set
+    pub blacklist_max_loss_sol: f64,        // POOL_MAX_LOSS_SOL
+    pub blacklist_failure_window: usize,    // POOL_FAILURE_WINDOW
+    pub blacklist_max_failure_rate: f64,    // POOL_MAX_FAILURE_RATE
+    pub blacklist_secs: u64,                // POOL_BLACKLIST_SECS
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PricesConfig {
+    pub birdeye_api_url: String,         // BIRDEYE_API_URL
+    pub birdeye_api_key: Option<String>, // BIRDEYE_API_KEY
+    pub birdeye_timeout_ms: u64,         // BIRDEYE_TIMEOUT_MS
+    pub jupiter_api_url: String,         // JUPITER_API_URL
+    pub jupiter_timeout_ms: u64,         // JUPITER_QUOTE_TIMEOUT_MS
+    pub jupiter_slippage_bps: u16,       // JUPITER_SLIPPAGE_BPS
+    pub pyth_price_feeds: Vec<String>,   // PYTH_PRICE_FEEDS: extra or overriding TOKEN=ACCOUNT entries
+    pub pyth_max_slot_age: u64,          // PYTH_MAX_SLOT_AGE
+}
+
+// How the congestion score weighs TPS, fees and latency, and where each counts as high
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CongestionConfig {
+    pub tps_weight: f64,              // CONGESTION_WEIGHT_TPS
+    pub fee_weight: f64,              // CONGESTION_WEIGHT_FEES
+    pub latency_weight: f64,          // CONGESTION_WEIGHT_LATENCY
+    pub tps_capacity: f64,            // CONGESTION_TPS_CAPACITY
+    pub fee_high_micro_lamports: u64, // CONGESTION_FEE_HIGH_MICRO_LAMPORTS
+    pub latency_high_ms: u64,         // CONGESTION_LATENCY_HIGH_MS
+    pub cache_ms: u64,                // CONGESTION_CACHE_MS
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    pub min_balance: f64,                    // MIN_BALANCE_THRESHOLD
+    pub max_loss_per_bundle: f64,            // MAX_LOSS_PER_BUNDLE: the executor's own check
+    pub global_loss_per_bundle: f64,         // GLOBAL_LOSS_PER_BUNDLE
+    pub global_daily_spending_limit: f64,    // GLOBAL_DAILY_SPENDING_LIMIT
+    pub max_consecutive_failures: u32,       // MAX_CONSECUTIVE_FAILURES
+    pub max_strategy_failures: u32,          // MAX_STRATEGY_FAILURES
+    pub strategy_disable_secs: u64,          // STRATEGY_DISABLE_SECS
+    pub session_timeout_minutes: u64,        // SESSION_TIMEOUT_MINUTES; 0 means no timeout
+    pub max_exposure_per_token_sol: f64,     // MAX_EXPOSURE_PER_TOKEN_SOL
+    pub daily_reset_utc_offset_minutes: i32, // DAILY_RESET_UTC_OFFSET_MINUTES
+    pub token_exposure_window_secs: u64,     // TOKEN_EXPOSURE_WINDOW_SECS
+    pub max_drawdown_percent: f64,           // MAX_DRAWDOWN_PERCENT; 0 is off
+    pub drawdown_cooloff_secs: u64,          // DRAWDOWN_COOLOFF_SECS
+    pub drawdown_resume_percent: f64,        // DRAWDOWN_RESUME_PERCENT
+    pub circuit_breaker_cooldown_secs: u64,  // CIRCUIT_BREAKER_COOLDOWN_SECS
+    pub state_file: String,                  // RISK_STATE_FILE
+    pub state_flush_ms: u64,                 // RISK_STATE_FLUSH_MS: how often the high-water mark and re-enables are written out
+    pub high_water_mark_max_age_secs: u64,   // HIGH_WATER_MARK_MAX_AGE_SECS; older marks aren't restored
+    pub kill_switch_path: Option<String>,    // KILL_SWITCH_PATH: trading halts while this file exists
+    pub kill_switch_poll_ms: u64,            // KILL_SWITCH_POLL_MS
+    pub balance_poll_secs: u64,              // BALANCE_POLL_SECS
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeesConfig {
+    pub priority_fee_percentile: f64, // PRIORITY_FEE_PERCENTILE
+    pub arbitrage_multiplier: f64,    // PRIORITY_FEE_MULTIPLIER_ARBITRAGE
+    pub sandwich_multiplier: f64,     // PRIORITY_FEE_MULTIPLIER_SANDWICH
+    pub frontrun_multiplier: f64,     // PRIORITY_FEE_MULTIPLIER_FRONTRUN
+    pub backrun_multiplier: f64,      // PRIORITY_FEE_MULTIPLIER_BACKRUN
+    pub other_multiplier: f64,        // PRIORITY_FEE_MULTIPLIER_OTHER
+    pub cache_ms: u64,                // PRIORITY_FEE_CACHE_MS
+    pub ema_alpha: f64,               // FEE_EMA_ALPHA
+    pub spike_ratio: f64,             // FEE_SPIKE_RATIO
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    pub addr: Option<SocketAddr>,                // METRICS_ADDR; unset leaves the Prometheus server off
+    pub state_file: String,                      // METRICS_STATE_FILE
+    pub state_flush_secs: u64,                   // METRICS_STATE_FLUSH_SECS; 0 flushes at shutdown only
+    pub restore_state: bool,                     // RESTORE_METRICS_STATE
+    pub metrics_file: String,                    // METRICS_FILE: JSON export at shutdown
+    pub analytics_file: String,                  // ANALYTICS_FILE
+    pub profit_calibration_window: usize,        // PROFIT_CALIBRATION_WINDOW
+    pub profit_calibration_min_samples: usize,   // PROFIT_CALIBRATION_MIN_SAMPLES
+    pub profit_calibration_alert_floor: f64,     // PROFIT_CALIBRATION_ALERT_FLOOR
+    pub profit_calibration_half_life_hours: f64, // PROFIT_CALIBRATION_HALF_LIFE_HOURS
+    pub token_pnl_max_mints: usize,              // TOKEN_PNL_MAX_MINTS
+    pub performance_summary_mins: u64,           // PERFORMANCE_SUMMARY_MINS; 0 turns the summary off
+    pub summary_color: bool,                     // Off under NO_COLOR
+    pub health_stream_stale_secs: u64,           // HEALTH_STREAM_STALE_SECS
+}
+
+// Where risk events are delivered besides the log; no sink configured leaves alerting off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    pub discord_webhook_url: Option<String>, // DISCORD_WEBHOOK_URL
+    pub telegram_bot_token: Option<String>,  // TELEGRAM_BOT_TOKEN; set together with telegram_chat_id
+    pub telegram_chat_id: Option<String>,    // TELEGRAM_CHAT_ID
+    pub cooldown_secs: u64,                  // ALERT_COOLDOWN_SECS
+}
+
+// Restarts of the mempool task after a panic or an unexpected stop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SupervisorConfig {
+    pub max_restarts: u32,           // MEMPOOL_MAX_RESTARTS; one more failure exits nonzero
+    pub restart_backoff_ms: u64,     // MEMPOOL_RESTART_BACKOFF_MS, doubled with each restart
+    pub max_restart_backoff_ms: u64, // MEMPOOL_RESTART_BACKOFF_MAX_MS
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            network: "devnet".to_string(),
+            dry_run: false,
+            shutdown_timeout_ms: 5000,
+            skip_preflight: false,
+            keypair_path: "solana-keypair.json".to_string(),
+            wallet_address: None,
+            rpc: RpcConfig::default(),
+            jito: JitoConfig::default(),
+            bundles: BundlesConfig::default(),
+            strategies: StrategiesConfig::default(),
+            snipe: SnipeConfig::default(),
+            liquidation: LiquidationConfig::default(),
+            simulation: SimulationConfig::default(),
+            token_safety: TokenSafetyConfig::default(),
+            filters: FiltersConfig::default(),
+            pools: PoolsConfig::default(),
+            prices: PricesConfig::default(),
+            congestion: CongestionConfig::default(),
+            risk: RiskConfig::default(),
+            fees: FeesConfig::default(),
+            metrics: MetricsConfig::default(),
+            alerts: AlertsConfig::default(),
+            supervisor: SupervisorConfig::default(),
+        }
+    }
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            helius: None,
+            drpc: None,
+            solana_rpc_url: None,
+            solana_ws_url: None,
+            solana_ws_urls: Vec::new(),
+            health_check_ms: 5000,
+            degraded_after_failures: 3,
+            degraded_latency_ms: 2000.0,
+            routing_policy: Vec::new(),
+            latency_report_secs: 60,
+            retry_budget: 3,
+            retry_base_ms: 100,
+            max_retry_wait_ms: 2000,
+            shed_task_types: Vec::new(),
+            max_batch_size: Vec::new(),
+            rate_limits: Vec::new(), // Unlimited
+            max_in_flight: Vec::new(),
+            limit_max_wait_ms: 500,
+            breaker_failures: 5,
+            breaker_window_ms: 10_000,
+            breaker_cooldown_ms: 5000,
+            tx_fetch_batch_window_ms: 5,
+            tx_fetch_batch_max: 20,
+        }
+    }
+}
+
+impl Default for JitoConfig {
+    fn default() -> Self {
+        Self {
+            use_jito: false,
+            rpc_url: None,
+            tip_account: None,
+            auth_header: None,
+            auth_keypair_path: None,
+            auth_url: None,
+            simulation_url: None,
+            tip_percentile: 50,
+            tip_target_landing_rate: 0.8,
+            tip_history_file: "tip_history.json".to_string(),
+            tip_history_half_life_hours: 24.0,
+            tip_history_flush_secs: 300,
+            tip_floor_url: "https://bundles.jito.wtf/api/v1/bundles/tip_floor".to_string(),
+            tip_floor_poll_ms: 10_000,
+            tip_floor_max_age_secs: 60,
+            leader_max_wait_ms: 1200, // Past that a held-back bundle is too stale to send
+            leader_schedule_poll_ms: 2000,
+            validators_url: None,
+            validators: Vec::new(),
+            max_bundles_per_second: 5.0, // Default unauthenticated allowance
+            max_queued_bundles: 10,
+            max_queue_wait_ms: 400, // About a slot; later than that the opportunity is gone
+        }
+    }
+}
+
+impl Default for BundlesConfig {
+    fn default() -> Self {
+        Self {
+            landing_slots: 8,
+            max_retries: 2,
+            tip_multiplier: 1.5,
+            max_tip_share: 0.5,
+            status_poll_ms: 2000,
+            status_expiry_secs: 60, // Well past blockhash expiry
+            rpc_fallback: true,
+            simulate: false,
+        }
+    }
+}
+
+impl Default for StrategiesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: vec!["arbitrage".to_string()],
+            opportunity_workers: 4,
+            max_notification_age_ms: 1500, // Older notifications rarely land in time to be worth the fees
+            triangular_arb_interval_ms: 2000,
+            liquidation_scan_interval_ms: 30_000, // getProgramAccounts over a whole market is heavy
+            min_arbitrage_profit_sol: 0.005,
+            min_sandwich_profit_sol: 0.01,
+            min_frontrun_profit_sol: 0.005,
+            max_slippage_bps: 300,
+            max_price_impact_bps: 1000,
+            position_size_limit_sol: 5.0,
+            triangular_min_liquidity_sol: 50.0,
+            opportunity_ttl_ms: 800, // ~2 slots at 400ms each
+            opportunity_queue_capacity: 256,
+            max_concurrent_analyses: 8,
+            analysis_permit_timeout_ms: 100, // Past this the notification is likely too stale to act on
+            competition_window_secs: 600,
+            pumpfun_min_buy_sol: 1.0,
+            pumpfun_max_frontrun_sol: 0.5,
+        }
+    }
+}
+
+impl Default for SnipeConfig {
+    fn default() -> Self {
+        Self {
+            quote_mints: vec![WSOL_MINT.to_string(), USDC_MINT.to_string()],
+            buy_amount_sol: 0.1,
+            target_return: 0.2, // 20% take-profit target
+            min_pool_age_slots: 0,
+            max_pool_age_slots: 150, // About a minute
+            min_liquidity_sol: 10.0,
+            min_lp_locked_share: 0.0,
+            lp_lockers: Vec::new(),
+        }
+    }
+}
+
+impl Default for LiquidationConfig {
+    fn default() -> Self {
+        Self {
+            lending_market: None,
+            max_capital_sol: 1.0,
+            min_profit_sol: 0.005,
+            near_threshold: 0.95, // Prices move between on-chain refreshes
+        }
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            min_arbitrage_profit_sol: 0.01,
+            min_sandwich_profit_sol: 0.01,
+            min_frontrun_profit_sol: 0.01,
+            min_backrun_profit_sol: 0.01,
+            min_other_profit_sol: 0.01,
+            max_variance: 0.1,
+            strong_profit_sol: 0.05,
+            low_variance: 0.01,
+            confidence_profit_weight: 0.4,
+            confidence_variance_weight: 0.3,
+            confidence_profitability_weight: 0.3,
+            cache_ttl_ms: 500,
+            reserve_perturbation: 0.02,
+            trade_size_perturbation: 0.1,
+            variance_budget_ms: 200,
+            consensus_endpoints: 3,
+            consensus_tolerance: 0.1,
+            consensus_deadline_ms: 150,
+            consensus_penalty: 0.5,
+            accuracy_window: 50,
+            accuracy_min_samples: 10,
+            accuracy_alert_floor: 0.7,
+        }
+    }
+}
+
+impl Default for TokenSafetyConfig {
+    fn default() -> Self {
+        Self {
+            allow_mint_authority: false,
+            allow_freeze_authority: false,
+            max_transfer_fee_bps: 0,
+            allow_transfer_hook: false,
+            allow_permanent_delegate: false,
+            max_top_holder_share: 0.0,
+            failure_ttl_secs: 3600,
+        }
+    }
+}
+
+impl Default for FiltersConfig {
+    fn default() -> Self {
+        Self {
+            min_pool_liquidity_sol: 10.0,
+            min_volume_24h_usd: 10_000.0,
+            max_trade_to_liquidity: 0.1, // Pool at least 10x the trade
+            arbitrage_min_pool_liquidity_sol: None,
+            arbitrage_min_volume_24h_usd: None,
+            arbitrage_max_trade_to_liquidity: None,
+            sandwich_min_pool_liquidity_sol: None,
+            sandwich_min_volume_24h_usd: None,
+            sandwich_max_trade_to_liquidity: None,
+            frontrun_min_pool_liquidity_sol: None,
+            frontrun_min_volume_24h_usd: None,
+            frontrun_max_trade_to_liquidity: None,
+            target_precision: 0.8,
+            target_recall: 0.5,
+            shadow_rejections: false,
+            recalibration_min_samples: 50,
+            recalibration_secs: 900,
+        }
+    }
+}
+
+impl Default for PoolsConfig {
+    fn default() -> Self {
+        Self {
+            registry_file: None,
+            registry_auto_add: false,
+            dex_registry_file: None,
+            watcher_max_pools: 20,
+            dex_monitor_max_pool_age_ms: 2000, // About five slots
+            cache_ttl_ms: 1000, // Reserves move every slot
+            cache_capacity: 2000,
+            price_cache_ttl_ms: 5000,
+            price_cache_capacity: 2000,
+            lock_timeout_ms: 5000, // Longer than a submission ever takes
+            failure_cooldown_ms: 2000,
+            blacklist_file: "pool_blacklist.json".to_string(),
+            blacklist_config: None,
+            blacklist_max_loss_sol: 0.05,
+            blacklist_failure_window: 10,
+            blacklist_max_failure_rate: 0.8,
+            blacklist_secs: 86_400,
+        }
+    }
+}
+
+impl Default for PricesConfig {
+    fn default() -> Self {
+        Self {
+            birdeye_api_url: "https://public-api.birdeye.so".to_string(),
+            birdeye_api_key: None,
+            birdeye_timeout_ms: 800,
+            jupiter_api_url: "https://quote-api.jup.ag/v6".to_string(),
+            jupiter_timeout_ms: 800,
+            jupiter_slippage_bps: 50,
+            pyth_price_feeds: Vec::new(),
+            pyth_max_slot_age: 25,
+        }
+    }
+}
+
+impl Default for CongestionConfig {
+    fn default() -> Self {
+        Self {
+            tps_weight: 0.4,
+            fee_weight: 0.4,
+            latency_weight: 0.2,
+            tps_capacity: 5000.0, // Votes included, as the samples count them
+            fee_high_micro_lamports: 1_000_000,
+            latency_high_ms: 2000,
+            cache_ms: 3000, // Assessed on every execution
+        }
+    }
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            min_balance: 0.5,
+            max_loss_per_bundle: 0.1,
+            global_loss_per_bundle: 0.01,
+            global_daily_spending_limit: 10.0,
+            max_consecutive_failures: 5,
+            max_strategy_failures: 3,
+            strategy_disable_secs: 3600,
+            session_timeout_minutes: 0,
+            max_exposure_per_token_sol: 0.5,
+            daily_reset_utc_offset_minutes: 0,
+            token_exposure_window_secs: 3600,
+            max_drawdown_percent: 20.0,
+            drawdown_cooloff_secs: 3600,
+            drawdown_resume_percent: 10.0,
+            circuit_breaker_cooldown_secs: 600,
+            state_file: "risk_state.json".to_string(),
+            state_flush_ms: 1000,
+            high_water_mark_max_age_secs: 86_400,
+            kill_switch_path: None,
+            kill_switch_poll_ms: 250,
+            balance_poll_secs: 10,
+        }
+    }
+}
+
+impl RiskConfig {
+    // Devnet-tuned defaults are too loose for real funds: on mainnet, limits the operator set
+    // neither in the file nor in the environment get these instead
+    fn apply_mainnet_defaults(&mut self, is_set: impl Fn(&str, &str) -> bool) {
+        let limits = [
+            ("global_daily_spending_limit", "GLOBAL_DAILY_SPENDING_LIMIT", &mut self.global_daily_spending_limit, 2.0),
+            ("global_loss_per_bundle", "GLOBAL_LOSS_PER_BUNDLE", &mut self.global_loss_per_bundle, 0.005),
+            ("max_loss_per_bundle", "MAX_LOSS_PER_BUNDLE", &mut self.max_loss_per_bundle, 0.05),
+        ];
+        for (key, var, limit, mainnet_default) in limits {
+            if !is_set(key, var) {
+                *limit = mainnet_default;
+            }
+        }
+    }
+}
+
+impl Default for FeesConfig {
+    fn default() -> Self {
+        Self {
+            priority_fee_percentile: 75.0,
+            arbitrage_multiplier: 1.0,
+            sandwich_multiplier: 1.2,
+            frontrun_multiplier: 1.2,
+            backrun_multiplier: 1.0,
+            other_multiplier: 1.0,
+            cache_ms: 2000,
+            ema_alpha: 0.2,
+            spike_ratio: 3.0,
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            state_file: "metrics_state.json".to_string(),
+            state_flush_secs: 60,
+            restore_state: false,
+            metrics_file: "mev_metrics.json".to_string(),
+            analytics_file: "mev_analytics.json".to_string(),
+            profit_calibration_window: 100,
+            profit_calibration_min_samples: 20,
+            profit_calibration_alert_floor: 0.5,
+            profit_calibration_half_life_hours: 6.0,
+            token_pnl_max_mints: 50,
+            performance_summary_mins: 15,
+            summary_color: true,
+            health_stream_stale_secs: 30,
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            discord_webhook_url: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            restart_backoff_ms: 1000,
+            max_restart_backoff_ms: 60_000,
+        }
+    }
+}
+
+impl Config {
+    // `path` is --config or BOT_CONFIG; without one, bot.toml is used only if it exists
+    pub fn load(path: Option<&str>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load_with(path, |name| std::env::var(name).ok())
+    }
+
+    // load() with the environment behind `lookup`
+    pub fn load_with(path: Option<&str>, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.or_else(|| std::path::Path::new(DEFAULT_CONFIG_PATH).exists().then_some(DEFAULT_CONFIG_PATH));
+        let (mut config, file_risk_keys) = match path {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+                let config = Self::from_toml(&contents)
+                    .map_err(|e| format!("Invalid config file {}: {}", path, e))?;
+                (config, risk_keys(&contents))
+            }
+            None => (Self::default(), Vec::new()),
+        };
+
+        config.apply_overrides(&lookup)?;
+        // The NO_COLOR convention: set and non-empty turns color off
+        if lookup("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            config.metrics.summary_color = false;
+        }
+        if matches!(config.network(), Network::Mainnet) {
+            config.risk.apply_mainnet_defaults(|key, var| file_risk_keys.iter().any(|k| k == key) || lookup(var).is_some());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::load(std::env::var("BOT_CONFIG").ok().as_deref())
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    // Every set variable replaces the file's value; all unparseable ones are reported together
+    pub fn apply_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let errors: Vec<String> = self.fields().into_iter()
+            .filter_map(|(name, field)| {
+                let value = lookup(name)?;
+                field.set_from(&value).err().map(|e| format!("Invalid {}: {}", name, e))
+            })
+            .collect();
+        if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if Network::parse(&self.network).is_none() {
+            errors.push(format!("network (NETWORK) must be mainnet, testnet or devnet, not '{}'", self.network));
+        }
+        if let Err(e) = self.enabled_strategies() {
+            errors.push(e);
+        }
+        if self.strategies.opportunity_workers == 0 {
+            errors.push("strategies.opportunity_workers (OPPORTUNITY_WORKERS) must be at least 1".to_string());
+        }
+
+        let risk = &self.risk;
+        let amounts = [
+            ("risk.min_balance (MIN_BALANCE_THRESHOLD)", risk.min_balance),
+            ("risk.max_loss_per_bundle (MAX_LOSS_PER_BUNDLE)", risk.max_loss_per_bundle),
+            ("risk.global_loss_per_bundle (GLOBAL_LOSS_PER_BUNDLE)", risk.global_loss_per_bundle),
+            ("risk.global_daily_spending_limit (GLOBAL_DAILY_SPENDING_LIMIT)", risk.global_daily_spending_limit),
+            ("risk.max_exposure_per_token_sol (MAX_EXPOSURE_PER_TOKEN_SOL)", risk.max_exposure_per_token_sol),
+        ];
+        for (name, amount) in amounts {
+            if !amount.is_finite() || amount < 0.0 {
+                errors.push(format!("{} must be a non-negative amount of SOL", name));
+            }
+        }
+        if risk.daily_reset_utc_offset_minutes.abs() > 14 * 60 {
+            errors.push(format!(
+                "risk.daily_reset_utc_offset_minutes (DAILY_RESET_UTC_OFFSET_MINUTES): {} is more than 14 hours from UTC",
+                risk.daily_reset_utc_offset_minutes
+            ));
+        }
+        if !(0.0..=100.0).contains(&risk.max_drawdown_percent) || !(0.0..=100.0).contains(&risk.drawdown_resume_percent) {
+            errors.push("risk.max_drawdown_percent and risk.drawdown_resume_percent must be in [0, 100]".to_string());
+        }
+
+        let fees = &self.fees;
+        if !(0.0..=100.0).contains(&fees.priority_fee_percentile) {
+            errors.push("fees.priority_fee_percentile (PRIORITY_FEE_PERCENTILE) must be in [0, 100]".to_string());
+        }
+        let multipliers = [
+            fees.arbitrage_multiplier, fees.sandwich_multiplier, fees.frontrun_multiplier,
+            fees.backrun_multiplier, fees.other_multiplier,
+        ];
+        if multipliers.iter().any(|multiplier| !multiplier.is_finite() || *multiplier <= 0.0) {
+            errors.push("fees.*_multiplier (PRIORITY_FEE_MULTIPLIER_*) must be positive".to_string());
+        }
+        if !(fees.ema_alpha > 0.0 && fees.ema_alpha <= 1.0 && fees.spike_ratio >= 1.0) {
+            errors.push("fees.ema_alpha (FEE_EMA_ALPHA) must be in (0, 1] and fees.spike_ratio (FEE_SPIKE_RATIO) at least 1".to_string());
+        }
+
+        if self.supervisor.max_restart_backoff_ms < self.supervisor.restart_backoff_ms {
+            errors.push("supervisor.max_restart_backoff_ms (MEMPOOL_RESTART_BACKOFF_MAX_MS) must be at least supervisor.restart_backoff_ms".to_string());
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(format!("Invalid configuration: {}", errors.join("; "))) }
+    }
+
+    // What the selected mode needs on top of validate(): the endpoints it will actually use, set
+    // and well formed. Empty when nothing is missing; every problem is listed otherwise
+    pub fn requirement_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let live = !self.dry_run;
+        let mut require = |name: &str, value: &Option<String>, needed: bool, why: &str| {
+            if needed && value.is_none() {
+                errors.push(format!("{} is not set ({})", name, why));
+            }
+        };
+        // A dry run may point SOLANA_RPC_URL at a local validator instead
+        require("HELIUS", &self.rpc.helius, live || self.rpc.solana_rpc_url.is_none(), "reads and simulation");
+        require("DRPC", &self.rpc.drpc, live && matches!(self.network(), Network::Mainnet), "fallback RPC for live trading on mainnet");
+        require("JITO_RPC_URL", &self.jito.rpc_url, self.jito.use_jito, "bundle submission with USE_JITO=true");
+        require("JITO_TIP_ACCOUNT", &self.jito.tip_account, self.jito.use_jito, "bundle tips with USE_JITO=true");
+
+        let http_urls = [
+            ("HELIUS", &self.rpc.helius),
+            ("DRPC", &self.rpc.drpc),
+            ("SOLANA_RPC_URL", &self.rpc.solana_rpc_url),
+            ("JITO_RPC_URL", &self.jito.rpc_url),
+            ("JITO_AUTH_URL", &self.jito.auth_url),
+        ];
+        for (name, url) in http_urls {
+            if let Some(error) = url.as_deref().and_then(|url| url_error(name, url, &["http", "https"])) {
+                errors.push(error);
+            }
+        }
+        let ws_urls = self.rpc.solana_ws_url.iter().map(|url| ("SOLANA_WS_URL", url))
+            .chain(self.rpc.solana_ws_urls.iter().map(|url| ("SOLANA_WS_URLS", url)));
+        for (name, url) in ws_urls {
+            if let Some(error) = url_error(name, url, &["ws", "wss"]) {
+                errors.push(error);
+            }
+        }
+
+        if let Some(tip_accounts) = &self.jito.tip_account {
+            let invalid = tip_accounts.split(',').filter(|account| account.trim().parse::<Pubkey>().is_err()).count();
+            if invalid > 0 {
+                errors.push(format!("JITO_TIP_ACCOUNT has {} entry(s) that aren't valid public keys", invalid));
             }
         }
+        // Prometheus needs to know where to scrape
+        if self.metrics.addr.is_some_and(|addr| addr.port() == 0) {
+            errors.push("METRICS_ADDR needs a fixed port, not 0".to_string());
+        }
+        errors
+    }
+
+    // Refuses the combinations that put funds at risk or can't work: live trading on mainnet without
+    // the operator's explicit confirmation, and Jito bundles anywhere but mainnet
+    pub fn check_network_safety(&self, mainnet_confirmed: bool) -> Result<(), String> {
+        let network = self.network();
+        if self.jito.use_jito && !matches!(network, Network::Mainnet) {
+            return Err(format!(
+                "USE_JITO=true doesn't work on {}: the Jito block engine only lands bundles on mainnet. Set USE_JITO=false",
+                network.label()
+            ));
+        }
+        if matches!(network, Network::Mainnet) && !self.dry_run && !mainnet_confirmed {
+            return Err(format!(
+                "Refusing to trade real funds on MAINNET without confirmation: set {}=yes or pass --i-understand-mainnet (or use --dry-run)",
+                CONFIRM_MAINNET_VAR
+            ));
+        }
+        Ok(())
+    }
+
+    // Validated by validate(), so only a Config that skipped it can fall back to devnet here
+    pub fn network(&self) -> Network {
+        Network::parse(&self.network).unwrap_or(Network::Devnet)
+    }
+
+    // Switching onto mainnet after loading tightens the risk limits still at their defaults, as
+    // loading with NETWORK=mainnet would have
+    pub fn set_network(&mut self, network: Network) {
+        if matches!(network, Network::Mainnet) && !matches!(self.network(), Network::Mainnet) {
+            let current = toml::Value::try_from(&self.risk).ok();
+            let defaults = toml::Value::try_from(RiskConfig::default()).ok();
+            self.risk.apply_mainnet_defaults(|key, _| {
+                current.as_ref().and_then(|risk| risk.get(key)) != defaults.as_ref().and_then(|risk| risk.get(key))
+            });
+        }
+        self.network = network.label().to_lowercase();
+    }
+
+    pub fn enabled_strategies(&self) -> Result<Vec<MevStrategyType>, String> {
+        MevStrategyType::parse_strategy_list(&self.strategies.enabled.join(","))
+    }
+
+    // The WebSocket endpoints the mempool races: SOLANA_WS_URLS, else SOLANA_WS_URL, else the
+    // network's public endpoint
+    pub fn ws_urls(&self) -> Vec<String> {
+        if !self.rpc.solana_ws_urls.is_empty() {
+            return self.rpc.solana_ws_urls.clone();
+        }
+        let ws_url = self.rpc.solana_ws_url.clone().unwrap_or_else(|| match self.network() {
+            Network::Devnet => "wss://api.devnet.solana.com".to_string(),
+            Network::Testnet => "wss://api.testnet.solana.com".to_string(),
+            Network::Mainnet => "wss://api.mainnet-beta.solana.com".to_string(),
+        });
+        vec![ws_url]
+    }
+
+    // RPC URLs usually carry an API key in the path or query, and the auth header is a credential
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let redact = |url: &mut Option<String>| {
+            if let Some(url) = url.as_mut() {
+                *url = redact_url(url);
+            }
+        };
+        redact(&mut config.rpc.helius);
+        redact(&mut config.rpc.drpc);
+        redact(&mut config.rpc.solana_rpc_url);
+        redact(&mut config.rpc.solana_ws_url);
+        redact(&mut config.jito.rpc_url);
+        redact(&mut config.jito.auth_url);
+        redact(&mut config.jito.simulation_url);
+        redact(&mut config.alerts.discord_webhook_url);
+        config.rpc.solana_ws_urls = config.rpc.solana_ws_urls.iter().map(|url| redact_url(url)).collect();
+        let hide = |secret: &mut Option<String>| {
+            if secret.is_some() {
+                *secret = Some(REDACTED.to_string());
+            }
+        };
+        hide(&mut config.jito.auth_header);
+        hide(&mut config.alerts.telegram_bot_token);
+        hide(&mut config.prices.birdeye_api_key);
+        config
+    }
+
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn EnvValue)> {
+        let Config {
+            network, dry_run, shutdown_timeout_ms, skip_preflight, keypair_path, wallet_address, rpc, jito, bundles, strategies,
+            snipe, liquidation, simulation, token_safety, filters, pools, prices, congestion, risk, fees, metrics, alerts, supervisor,
+        } = self;
+        vec![
+            ("NETWORK", network as &mut dyn EnvValue),
+            ("DRY_RUN", dry_run),
+            ("SHUTDOWN_TIMEOUT_MS", shutdown_timeout_ms),
+            ("SKIP_PREFLIGHT", skip_preflight),
+            ("KEYPAIR_PATH", keypair_path),
+            ("WALLET_ADDRESS", wallet_address),
+            ("HELIUS", &mut rpc.helius),
+            ("DRPC", &mut rpc.drpc),
+            ("SOLANA_RPC_URL", &mut rpc.solana_rpc_url),
+            ("SOLANA_WS_URL", &mut rpc.solana_ws_url),
+            ("SOLANA_WS_URLS", &mut rpc.solana_ws_urls),
+            ("RPC_HEALTH_CHECK_MS", &mut rpc.health_check_ms),
+            ("RPC_DEGRADED_AFTER_FAILURES", &mut rpc.degraded_after_failures),
+            ("RPC_DEGRADED_LATENCY_MS", &mut rpc.degraded_latency_ms),
+            ("RPC_ROUTING_POLICY", &mut rpc.routing_policy),
+            ("RPC_LATENCY_REPORT_SECS", &mut rpc.latency_report_secs),
+            ("RPC_RETRY_BUDGET", &mut rpc.retry_budget),
+            ("RPC_RETRY_BASE_MS", &mut rpc.retry_base_ms),
+            ("RPC_MAX_RETRY_WAIT_MS", &mut rpc.max_retry_wait_ms),
+            ("RPC_SHED_TASK_TYPES", &mut rpc.shed_task_types),
+            ("RPC_MAX_BATCH_SIZE", &mut rpc.max_batch_size),
+            ("RPC_RATE_LIMITS", &mut rpc.rate_limits),
+            ("RPC_MAX_IN_FLIGHT", &mut rpc.max_in_flight),
+            ("RPC_LIMIT_MAX_WAIT_MS", &mut rpc.limit_max_wait_ms),
+            ("RPC_BREAKER_FAILURES", &mut rpc.breaker_failures),
+            ("RPC_BREAKER_WINDOW_MS", &mut rpc.breaker_window_ms),
+            ("RPC_BREAKER_COOLDOWN_MS", &mut rpc.breaker_cooldown_ms),
+            ("TX_FETCH_BATCH_WINDOW_MS", &mut rpc.tx_fetch_batch_window_ms),
+            ("TX_FETCH_BATCH_MAX", &mut rpc.tx_fetch_batch_max),
+            ("USE_JITO", &mut jito.use_jito),
+            ("JITO_RPC_URL", &mut jito.rpc_url),
+            ("JITO_TIP_ACCOUNT", &mut jito.tip_account),
+            ("JITO_AUTH_HEADER", &mut jito.auth_header),
+            ("JITO_AUTH_KEYPAIR_PATH", &mut jito.auth_keypair_path),
+            ("JITO_AUTH_URL", &mut jito.auth_url),
+            ("JITO_SIMULATION_URL", &mut jito.simulation_url),
+            ("JITO_TIP_PERCENTILE", &mut jito.tip_percentile),
+            ("JITO_TIP_TARGET_LANDING_RATE", &mut jito.tip_target_landing_rate),
+            ("TIP_HISTORY_FILE", &mut jito.tip_history_file),
+            ("TIP_HISTORY_HALF_LIFE_HOURS", &mut jito.tip_history_half_life_hours),
+            ("TIP_HISTORY_FLUSH_SECS", &mut jito.tip_history_flush_secs),
+            ("JITO_TIP_FLOOR_URL", &mut jito.tip_floor_url),
+            ("JITO_TIP_FLOOR_POLL_MS", &mut jito.tip_floor_poll_ms),
+            ("JITO_TIP_FLOOR_MAX_AGE_SECS", &mut jito.tip_floor_max_age_secs),
+            ("JITO_LEADER_MAX_WAIT_MS", &mut jito.leader_max_wait_ms),
+            ("LEADER_SCHEDULE_POLL_MS", &mut jito.leader_schedule_poll_ms),
+            ("JITO_VALIDATORS_URL", &mut jito.validators_url),
+            ("JITO_VALIDATORS", &mut jito.validators),
+            ("JITO_MAX_BUNDLES_PER_SECOND", &mut jito.max_bundles_per_second),
+            ("JITO_MAX_QUEUED_BUNDLES", &mut jito.max_queued_bundles),
+            ("JITO_MAX_QUEUE_WAIT_MS", &mut jito.max_queue_wait_ms),
+            ("BUNDLE_LANDING_SLOTS", &mut bundles.landing_slots),
+            ("BUNDLE_MAX_RETRIES", &mut bundles.max_retries),
+            ("BUNDLE_TIP_MULTIPLIER", &mut bundles.tip_multiplier),
+            ("BUNDLE_MAX_TIP_SHARE", &mut bundles.max_tip_share),
+            ("BUNDLE_STATUS_POLL_MS", &mut bundles.status_poll_ms),
+            ("BUNDLE_STATUS_EXPIRY_SECS", &mut bundles.status_expiry_secs),
+            ("JITO_RPC_FALLBACK", &mut bundles.rpc_fallback),
+            ("SIMULATE_BUNDLES", &mut bundles.simulate),
+            ("STRATEGY", &mut strategies.enabled),
+            ("OPPORTUNITY_WORKERS", &mut strategies.opportunity_workers),
+            ("MAX_NOTIFICATION_AGE_MS", &mut strategies.max_notification_age_ms),
+            ("TRIANGULAR_ARB_INTERVAL_MS", &mut strategies.triangular_arb_interval_ms),
+            ("LIQUIDATION_SCAN_INTERVAL_MS", &mut strategies.liquidation_scan_interval_ms),
+            ("MIN_ARBITRAGE_PROFIT_SOL", &mut strategies.min_arbitrage_profit_sol),
+            ("MIN_SANDWICH_PROFIT_SOL", &mut strategies.min_sandwich_profit_sol),
+            ("MIN_FRONTRUN_PROFIT_SOL", &mut strategies.min_frontrun_profit_sol),
+            ("MAX_SLIPPAGE_BPS", &mut strategies.max_slippage_bps),
+            ("MAX_PRICE_IMPACT_BPS", &mut strategies.max_price_impact_bps),
+            ("POSITION_SIZE_LIMIT", &mut strategies.position_size_limit_sol),
+            ("TRIANGULAR_MIN_LIQUIDITY_SOL", &mut strategies.triangular_min_liquidity_sol),
+            ("OPPORTUNITY_TTL_MS", &mut strategies.opportunity_ttl_ms),
+            ("OPPORTUNITY_QUEUE_CAPACITY", &mut strategies.opportunity_queue_capacity),
+            ("MAX_CONCURRENT_ANALYSES", &mut strategies.max_concurrent_analyses),
+            ("ANALYSIS_PERMIT_TIMEOUT_MS", &mut strategies.analysis_permit_timeout_ms),
+            ("COMPETITION_WINDOW_SECS", &mut strategies.competition_window_secs),
+            ("PUMPFUN_MIN_BUY_SOL", &mut strategies.pumpfun_min_buy_sol),
+            ("PUMPFUN_MAX_FRONTRUN_SOL", &mut strategies.pumpfun_max_frontrun_sol),
+            ("SNIPE_QUOTE_MINTS", &mut snipe.quote_mints),
+            ("SNIPE_BUY_AMOUNT_SOL", &mut snipe.buy_amount_sol),
+            ("SNIPE_TARGET_RETURN", &mut snipe.target_return),
+            ("SNIPE_MIN_POOL_AGE_SLOTS", &mut snipe.min_pool_age_slots),
+            ("SNIPE_MAX_POOL_AGE_SLOTS", &mut snipe.max_pool_age_slots),
+            ("SNIPE_MIN_LIQUIDITY_SOL", &mut snipe.min_liquidity_sol),
+            ("SNIPE_MIN_LP_LOCKED_SHARE", &mut snipe.min_lp_locked_share),
+            ("SNIPE_LP_LOCKERS", &mut snipe.lp_lockers),
+            ("SOLEND_LENDING_MARKET", &mut liquidation.lending_market),
+            ("LIQUIDATION_MAX_CAPITAL_SOL", &mut liquidation.max_capital_sol),
+            ("LIQUIDATION_MIN_PROFIT_SOL", &mut liquidation.min_profit_sol),
+            ("LIQUIDATION_NEAR_THRESHOLD", &mut liquidation.near_threshold),
+            ("SIMULATION_MIN_ARBITRAGE_PROFIT_SOL", &mut simulation.min_arbitrage_profit_sol),
+            ("SIMULATION_MIN_SANDWICH_PROFIT_SOL", &mut simulation.min_sandwich_profit_sol),
+            ("SIMULATION_MIN_FRONTRUN_PROFIT_SOL", &mut simulation.min_frontrun_profit_sol),
+            ("SIMULATION_MIN_BACKRUN_PROFIT_SOL", &mut simulation.min_backrun_profit_sol),
+            ("SIMULATION_MIN_OTHER_PROFIT_SOL", &mut simulation.min_other_profit_sol),
+            ("SIMULATION_MAX_VARIANCE", &mut simulation.max_variance),
+            ("SIMULATION_STRONG_PROFIT_SOL", &mut simulation.strong_profit_sol),
+            ("SIMULATION_LOW_VARIANCE", &mut simulation.low_variance),
+            ("SIMULATION_CONFIDENCE_PROFIT_WEIGHT", &mut simulation.confidence_profit_weight),
+            ("SIMULATION_CONFIDENCE_VARIANCE_WEIGHT", &mut simulation.confidence_variance_weight),
+            ("SIMULATION_CONFIDENCE_PROFITABILITY_WEIGHT", &mut simulation.confidence_profitability_weight),
+            ("SIMULATION_CACHE_TTL_MS", &mut simulation.cache_ttl_ms),
+            ("SIMULATION_RESERVE_PERTURBATION", &mut simulation.reserve_perturbation),
+            ("SIMULATION_TRADE_SIZE_PERTURBATION", &mut simulation.trade_size_perturbation),
+            ("SIMULATION_VARIANCE_BUDGET_MS", &mut simulation.variance_budget_ms),
+            ("SIMULATION_CONSENSUS_ENDPOINTS", &mut simulation.consensus_endpoints),
+            ("SIMULATION_CONSENSUS_TOLERANCE", &mut simulation.consensus_tolerance),
+            ("SIMULATION_CONSENSUS_DEADLINE_MS", &mut simulation.consensus_deadline_ms),
+            ("SIMULATION_CONSENSUS_PENALTY", &mut simulation.consensus_penalty),
+            ("SIMULATION_ACCURACY_WINDOW", &mut simulation.accuracy_window),
+            ("SIMULATION_ACCURACY_MIN_SAMPLES", &mut simulation.accuracy_min_samples),
+            ("SIMULATION_ACCURACY_ALERT_FLOOR", &mut simulation.accuracy_alert_floor),
+            ("TOKEN_SAFETY_ALLOW_MINT_AUTHORITY", &mut token_safety.allow_mint_authority),
+            ("TOKEN_SAFETY_ALLOW_FREEZE_AUTHORITY", &mut token_safety.allow_freeze_authority),
+            ("TOKEN_SAFETY_MAX_TRANSFER_FEE_BPS", &mut token_safety.max_transfer_fee_bps),
+            ("TOKEN_SAFETY_ALLOW_TRANSFER_HOOK", &mut token_safety.allow_transfer_hook),
+            ("TOKEN_SAFETY_ALLOW_PERMANENT_DELEGATE", &mut token_safety.allow_permanent_delegate),
+            ("TOKEN_SAFETY_MAX_TOP_HOLDER_SHARE", &mut token_safety.max_top_holder_share),
+            ("TOKEN_SAFETY_FAILURE_TTL_SECS", &mut token_safety.failure_ttl_secs),
+            ("MIN_POOL_LIQUIDITY_SOL", &mut filters.min_pool_liquidity_sol),
+            ("MIN_VOLUME_24H_USD", &mut filters.min_volume_24h_usd),
+            ("MAX_TRADE_TO_LIQUIDITY", &mut filters.max_trade_to_liquidity),
+            ("ARBITRAGE_MIN_POOL_LIQUIDITY_SOL", &mut filters.arbitrage_min_pool_liquidity_sol),
+            ("ARBITRAGE_MIN_VOLUME_24H_USD", &mut filters.arbitrage_min_volume_24h_usd),
+            ("ARBITRAGE_MAX_TRADE_TO_LIQUIDITY", &mut filters.arbitrage_max_trade_to_liquidity),
+            ("SANDWICH_MIN_POOL_LIQUIDITY_SOL", &mut filters.sandwich_min_pool_liquidity_sol),
+            ("SANDWICH_MIN_VOLUME_24H_USD", &mut filters.sandwich_min_volume_24h_usd),
+            ("SANDWICH_MAX_TRADE_TO_LIQUIDITY", &mut filters.sandwich_max_trade_to_liquidity),
+            ("FRONTRUN_MIN_POOL_LIQUIDITY_SOL", &mut filters.frontrun_min_pool_liquidity_sol),
+            ("FRONTRUN_MIN_VOLUME_24H_USD", &mut filters.frontrun_min_volume_24h_usd),
+            ("FRONTRUN_MAX_TRADE_TO_LIQUIDITY", &mut filters.frontrun_max_trade_to_liquidity),
+            ("FILTER_TARGET_PRECISION", &mut filters.target_precision),
+            ("FILTER_TARGET_RECALL", &mut filters.target_recall),
+            ("FILTER_SHADOW_REJECTIONS", &mut filters.shadow_rejections),
+            ("FILTER_RECALIBRATION_MIN_SAMPLES", &mut filters.recalibration_min_samples),
+            ("FILTER_RECALIBRATION_SECS", &mut filters.recalibration_secs),
+            ("POOLS_FILE", &mut pools.registry_file),
+            ("POOL_REGISTRY_AUTO_ADD", &mut pools.registry_auto_add),
+            ("DEX_REGISTRY_FILE", &mut pools.dex_registry_file),
+            ("POOL_WATCHER_MAX_POOLS", &mut pools.watcher_max_pools),
+            ("DEX_MONITOR_MAX_POOL_AGE_MS", &mut pools.dex_monitor_max_pool_age_ms),
+            ("POOL_CACHE_TTL_MS", &mut pools.cache_ttl_ms),
+            ("POOL_CACHE_CAPACITY", &mut pools.cache_capacity),
+            ("PRICE_CACHE_TTL_MS", &mut pools.price_cache_ttl_ms),
+            ("PRICE_CACHE_CAPACITY", &mut pools.price_cache_capacity),
+            ("POOL_LOCK_TIMEOUT_MS", &mut pools.lock_timeout_ms),
+            ("POOL_FAILURE_COOLDOWN_MS", &mut pools.failure_cooldown_ms),
+            ("POOL_BLACKLIST_FILE", &mut pools.blacklist_file),
+            ("POOL_BLACKLIST_CONFIG", &mut pools.blacklist_config),
+            ("POOL_MAX_LOSS_SOL", &mut pools.blacklist_max_loss_sol),
+            ("POOL_FAILURE_WINDOW", &mut pools.blacklist_failure_window),
+            ("POOL_MAX_FAILURE_RATE", &mut pools.blacklist_max_failure_rate),
+            ("POOL_BLACKLIST_SECS", &mut pools.blacklist_secs),
+            ("BIRDEYE_API_URL", &mut prices.birdeye_api_url),
+            ("BIRDEYE_API_KEY", &mut prices.birdeye_api_key),
+            ("BIRDEYE_TIMEOUT_MS", &mut prices.birdeye_timeout_ms),
+            ("JUPITER_API_URL", &mut prices.jupiter_api_url),
+            ("JUPITER_QUOTE_TIMEOUT_MS", &mut prices.jupiter_timeout_ms),
+            ("JUPITER_SLIPPAGE_BPS", &mut prices.jupiter_slippage_bps),
+            ("PYTH_PRICE_FEEDS", &mut prices.pyth_price_feeds),
+            ("PYTH_MAX_SLOT_AGE", &mut prices.pyth_max_slot_age),
+            ("CONGESTION_WEIGHT_TPS", &mut congestion.tps_weight),
+            ("CONGESTION_WEIGHT_FEES", &mut congestion.fee_weight),
+            ("CONGESTION_WEIGHT_LATENCY", &mut congestion.latency_weight),
+            ("CONGESTION_TPS_CAPACITY", &mut congestion.tps_capacity),
+            ("CONGESTION_FEE_HIGH_MICRO_LAMPORTS", &mut congestion.fee_high_micro_lamports),
+            ("CONGESTION_LATENCY_HIGH_MS", &mut congestion.latency_high_ms),
+            ("CONGESTION_CACHE_MS", &mut congestion.cache_ms),
+            ("MIN_BALANCE_THRESHOLD", &mut risk.min_balance),
+            ("MAX_LOSS_PER_BUNDLE", &mut risk.max_loss_per_bundle),
+            ("GLOBAL_LOSS_PER_BUNDLE", &mut risk.global_loss_per_bundle),
+            ("GLOBAL_DAILY_SPENDING_LIMIT", &mut risk.global_daily_spending_limit),
+            ("MAX_CONSECUTIVE_FAILURES", &mut risk.max_consecutive_failures),
+            ("MAX_STRATEGY_FAILURES", &mut risk.max_strategy_failures),
+            ("STRATEGY_DISABLE_SECS", &mut risk.strategy_disable_secs),
+            ("SESSION_TIMEOUT_MINUTES", &mut risk.session_timeout_minutes),
+            ("MAX_EXPOSURE_PER_TOKEN_SOL", &mut risk.max_exposure_per_token_sol),
+            ("DAILY_RESET_UTC_OFFSET_MINUTES", &mut risk.daily_reset_utc_offset_minutes),
+            ("TOKEN_EXPOSURE_WINDOW_SECS", &mut risk.token_exposure_window_secs),
+            ("MAX_DRAWDOWN_PERCENT", &mut risk.max_drawdown_percent),
+            ("DRAWDOWN_COOLOFF_SECS", &mut risk.drawdown_cooloff_secs),
+            ("DRAWDOWN_RESUME_PERCENT", &mut risk.drawdown_resume_percent),
+            ("CIRCUIT_BREAKER_COOLDOWN_SECS", &mut risk.circuit_breaker_cooldown_secs),
+            ("RISK_STATE_FILE", &mut risk.state_file),
+            ("RISK_STATE_FLUSH_MS", &mut risk.state_flush_ms),
+            ("HIGH_WATER_MARK_MAX_AGE_SECS", &mut risk.high_water_mark_max_age_secs),
+            ("KILL_SWITCH_PATH", &mut risk.kill_switch_path),
+            ("KILL_SWITCH_POLL_MS", &mut risk.kill_switch_poll_ms),
+            ("BALANCE_POLL_SECS", &mut risk.balance_poll_secs),
+            ("PRIORITY_FEE_PERCENTILE", &mut fees.priority_fee_percentile),
+            ("PRIORITY_FEE_MULTIPLIER_ARBITRAGE", &mut fees.arbitrage_multiplier),
+            ("PRIORITY_FEE_MULTIPLIER_SANDWICH", &mut fees.sandwich_multiplier),
+            ("PRIORITY_FEE_MULTIPLIER_FRONTRUN", &mut fees.frontrun_multiplier),
+            ("PRIORITY_FEE_MULTIPLIER_BACKRUN", &mut fees.backrun_multiplier),
+            ("PRIORITY_FEE_MULTIPLIER_OTHER", &mut fees.other_multiplier),
+            ("PRIORITY_FEE_CACHE_MS", &mut fees.cache_ms),
+            ("FEE_EMA_ALPHA", &mut fees.ema_alpha),
+            ("FEE_SPIKE_RATIO", &mut fees.spike_ratio),
+            ("METRICS_ADDR", &mut metrics.addr),
+            ("METRICS_STATE_FILE", &mut metrics.state_file),
+            ("METRICS_STATE_FLUSH_SECS", &mut metrics.state_flush_secs),
+            ("RESTORE_METRICS_STATE", &mut metrics.restore_state),
+            ("METRICS_FILE", &mut metrics.metrics_file),
+            ("ANALYTICS_FILE", &mut metrics.analytics_file),
+            ("PROFIT_CALIBRATION_WINDOW", &mut metrics.profit_calibration_window),
+            ("PROFIT_CALIBRATION_MIN_SAMPLES", &mut metrics.profit_calibration_min_samples),
+            ("PROFIT_CALIBRATION_ALERT_FLOOR", &mut metrics.profit_calibration_alert_floor),
+            ("PROFIT_CALIBRATION_HALF_LIFE_HOURS", &mut metrics.profit_calibration_half_life_hours),
+            ("TOKEN_PNL_MAX_MINTS", &mut metrics.token_pnl_max_mints),
+            ("PERFORMANCE_SUMMARY_MINS", &mut metrics.performance_summary_mins),
+            ("HEALTH_STREAM_STALE_SECS", &mut metrics.health_stream_stale_secs),
+            ("DISCORD_WEBHOOK_URL", &mut alerts.discord_webhook_url),
+            ("TELEGRAM_BOT_TOKEN", &mut alerts.telegram_bot_token),
+            ("TELEGRAM_CHAT_ID", &mut alerts.telegram_chat_id),
+            ("ALERT_COOLDOWN_SECS", &mut alerts.cooldown_secs),
+            ("MEMPOOL_MAX_RESTARTS", &mut supervisor.max_restarts),
+            ("MEMPOOL_RESTART_BACKOFF_MS", &mut supervisor.restart_backoff_ms),
+            ("MEMPOOL_RESTART_BACKOFF_MAX_MS", &mut supervisor.max_restart_backoff_ms),
+        ]
+    }
+}
+
+// The resolved config as TOML, secrets redacted, for the startup log and `check`
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let toml = toml::to_string(&self.redacted()).map_err(|_| fmt::Error)?;
+        write!(f, "{}", toml.trim_end())
+    }
+}
+
+// Keys of the file's [risk] table, which the mainnet defaults leave alone
+fn risk_keys(contents: &str) -> Vec<String> {
+    toml::from_str::<toml::Table>(contents).ok()
+        .and_then(|table| Some(table.get("risk")?.as_table()?.keys().cloned().collect()))
+        .unwrap_or_default()
+}
+
+pub fn mainnet_confirmed() -> bool {
+    std::env::var(CONFIRM_MAINNET_VAR).is_ok_and(|value| value.trim().eq_ignore_ascii_case("yes"))
+}
+
+// The URL itself stays out of the message, it may carry an API key
+fn url_error(name: &str, url: &str, schemes: &[&str]) -> Option<String> {
+    match url::Url::parse(url) {
+        Ok(parsed) if schemes.contains(&parsed.scheme()) && parsed.host_str().is_some() => None,
+        Ok(parsed) if !schemes.contains(&parsed.scheme()) => Some(format!(
+            "{} must be a {} URL, not {}",
+            name, schemes.join("/"), parsed.scheme()
+        )),
+        Ok(_) => Some(format!("{} has no host", name)),
+        Err(e) => Some(format!("{} is not a valid URL: {}", name, e)),
+    }
+}
+
+// Scheme, host and port only
+pub fn redact_url(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", parsed.scheme(), host, port),
+            (Some(host), None) => format!("{}://{}", parsed.scheme(), host),
+            _ => REDACTED.to_string(),
+        },
+        Err(_) => REDACTED.to_string(),
     }
-}
\ No newline at end of file
+}
+
+// A config field as an env var
+trait EnvValue {
+    fn set_from(&mut self, value: &str) -> Result<(), String>;
+}
+
+macro_rules! env_value {
+    ($($ty:ty),*) => {$(
+        impl EnvValue for $ty {
+            fn set_from(&mut self, value: &str) -> Result<(), String> {
+                *self = value.trim().parse::<$ty>().map_err(|e| e.to_string())?;
+                Ok(())
+            }
+        }
+    )*};
+}
+
+env_value!(u16, u32, u64, usize, i32, f64, String);
+
+// Unset or empty leaves the option off
+macro_rules! optional_env_value {
+    ($($ty:ty),*) => {$(
+        impl EnvValue for Option<$ty> {
+            fn set_from(&mut self, value: &str) -> Result<(), String> {
+                let value = value.trim();
+                *self = if value.is_empty() { None } else { Some(value.parse::<$ty>().map_err(|e| e.to_string())?) };
+                Ok(())
+            }
+        }
+    )*};
+}
+
+optional_env_value!(String, SocketAddr, f64);
+
+// "true" in any case is on, as DRY_RUN and USE_JITO have always been read
+impl EnvValue for bool {
+    fn set_from(&mut self, value: &str) -> Result<(), String> {
+        *self = value.trim().to_lowercase().parse::<bool>().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// Comma separated
+impl EnvValue for Vec<String> {
+    fn set_from(&mut self, value: &str) -> Result<(), String> {
+        *self = value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn full_config() -> Config {
+        let mut config = Config::default();
+        config.network = "mainnet".to_string();
+        config.rpc.helius = Some("https://mainnet.helius-rpc.com/?api-key=secret".to_string());
+        config.rpc.solana_ws_urls = vec!["wss://a.example".to_string(), "wss://b.example".to_string()];
+        config.jito.use_jito = true;
+        config.jito.auth_header = Some("Bearer secret".to_string());
+        config.alerts.telegram_bot_token = Some("secret".to_string());
+        config.prices.birdeye_api_key = Some("secret".to_string());
+        config.strategies.enabled = vec!["arbitrage".to_string(), "sandwich".to_string()];
+        config.risk.min_balance = 1.25;
+        config.fees.sandwich_multiplier = 1.5;
+        config.metrics.addr = Some("0.0.0.0:9100".parse().unwrap());
+        config
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let config = full_config();
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(Config::from_toml(&toml).unwrap(), config);
+
+        // Sections and fields left out keep their defaults
+        let partial = Config::from_toml("network = \"testnet\"\n[risk]\nmin_balance = 2.0\n").unwrap();
+        assert_eq!(partial.network, "testnet");
+        assert_eq!(partial.risk.min_balance, 2.0);
+        assert_eq!(partial.risk.max_drawdown_percent, RiskConfig::default().max_drawdown_percent);
+        assert_eq!(partial.fees, FeesConfig::default());
+    }
+
+    #[test]
+    fn test_example_file_is_valid() {
+        let config = Config::from_toml(include_str!("../bot.toml.example")).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.strategies, StrategiesConfig::default());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(Config::from_toml("[risk]\nmin_balanse = 2.0\n").unwrap_err().contains("min_balanse"));
+        assert!(Config::from_toml("[riks]\n").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_file_values() {
+        let mut config = Config::from_toml("network = \"devnet\"\n[risk]\nmin_balance = 2.0\nmax_drawdown_percent = 30.0\n").unwrap();
+        config.apply_overrides(env(&[
+            ("NETWORK", "MAINNET"),
+            ("MIN_BALANCE_THRESHOLD", "1.5"),
+            ("STRATEGY", " arbitrage , sandwich "),
+            ("DRY_RUN", "TRUE"),
+            ("METRICS_ADDR", ""),
+        ])).unwrap();
+
+        assert!(matches!(config.network(), Network::Mainnet));
+        assert_eq!(config.risk.min_balance, 1.5);
+        assert_eq!(config.risk.max_drawdown_percent, 30.0); // Not overridden
+        assert_eq!(config.strategies.enabled, vec!["arbitrage", "sandwich"]);
+        assert!(config.dry_run);
+        assert_eq!(config.metrics.addr, None);
+    }
+
+    #[test]
+    fn test_only_min_balance_threshold_sets_the_minimum_balance() {
+        let mut config = Config::default();
+        config.apply_overrides(env(&[("MIN_BALANCE", "3.0")])).unwrap();
+        assert_eq!(config.risk.min_balance, RiskConfig::default().min_balance);
+
+        config.apply_overrides(env(&[("MIN_BALANCE_THRESHOLD", "0.7")])).unwrap();
+        assert_eq!(config.risk.min_balance, 0.7);
+    }
+
+    #[test]
+    fn test_module_settings_come_from_the_same_overrides() {
+        let mut config = Config::default();
+        config.apply_overrides(env(&[
+            ("TIP_HISTORY_FILE", "/tmp/tips.json"),
+            ("POOL_BLACKLIST_FILE", "/tmp/blacklist.json"),
+            ("RPC_RATE_LIMITS", "helius=50, drpc=20"),
+            ("JITO_MAX_BUNDLES_PER_SECOND", "2.5"),
+            ("SANDWICH_MIN_POOL_LIQUIDITY_SOL", "25"),
+            ("ARBITRAGE_MIN_POOL_LIQUIDITY_SOL", ""),
+        ])).unwrap();
+        assert_eq!(config.jito.tip_history_file, "/tmp/tips.json");
+        assert_eq!(config.pools.blacklist_file, "/tmp/blacklist.json");
+        assert_eq!(config.rpc.rate_limits, vec!["helius=50", "drpc=20"]);
+        assert_eq!(config.jito.max_bundles_per_second, 2.5);
+        assert_eq!(config.filters.sandwich_min_pool_liquidity_sol, Some(25.0));
+        assert_eq!(config.filters.arbitrage_min_pool_liquidity_sol, None);
+    }
+
+    #[test]
+    fn test_no_color_turns_the_summary_color_off() {
+        assert!(Config::load_with(None, env(&[])).unwrap().metrics.summary_color);
+        assert!(Config::load_with(None, env(&[("NO_COLOR", "")])).unwrap().metrics.summary_color);
+        assert!(!Config::load_with(None, env(&[("NO_COLOR", "1")])).unwrap().metrics.summary_color);
+    }
+
+    #[test]
+    fn test_unparseable_overrides_are_reported_together() {
+        let error = Config::default()
+            .apply_overrides(env(&[("OPPORTUNITY_WORKERS", "four"), ("METRICS_ADDR", "localhost"), ("MAX_DRAWDOWN_PERCENT", "20")]))
+            .unwrap_err();
+        assert!(error.contains("Invalid OPPORTUNITY_WORKERS"));
+        assert!(error.contains("Invalid METRICS_ADDR"));
+        assert!(!error.contains("MAX_DRAWDOWN_PERCENT"));
+    }
+
+    #[test]
+    fn test_switching_to_mainnet_tightens_default_limits() {
+        let mut config = Config::default();
+        config.risk.max_loss_per_bundle = 0.02;
+        config.set_network(Network::Mainnet);
+        assert_eq!(config.network, "mainnet");
+        assert_eq!(config.risk.global_daily_spending_limit, 2.0);
+        assert_eq!(config.risk.max_loss_per_bundle, 0.02);
+
+        // Already on mainnet, nothing changes
+        config.risk.global_daily_spending_limit = 10.0;
+        config.set_network(Network::Mainnet);
+        assert_eq!(config.risk.global_daily_spending_limit, 10.0);
+    }
+
+    #[test]
+    fn test_ws_urls_fall_back_to_the_network_endpoint() {
+        let mut config = Config::default();
+        assert_eq!(config.ws_urls(), vec!["wss://api.devnet.solana.com".to_string()]);
+        config.rpc.solana_ws_url = Some("wss://single.example".to_string());
+        assert_eq!(config.ws_urls(), vec!["wss://single.example".to_string()]);
+        assert_eq!(full_config().ws_urls(), vec!["wss://a.example".to_string(), "wss://b.example".to_string()]);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Config::default().validate().is_ok());
+        assert!(full_config().validate().is_ok());
+
+        let mut config = Config::default();
+        config.network = "localnet".to_string();
+        config.strategies.enabled = vec!["arbitrge".to_string()];
+        config.risk.daily_reset_utc_offset_minutes = 15 * 60;
+        config.risk.min_balance = -1.0;
+        config.fees.priority_fee_percentile = 101.0;
+        config.fees.ema_alpha = 0.0;
+        config.supervisor.max_restart_backoff_ms = 500;
+        let error = config.validate().unwrap_err();
+        for expected in ["NETWORK", "arbitrge", "DAILY_RESET_UTC_OFFSET_MINUTES", "MIN_BALANCE_THRESHOLD", "PRIORITY_FEE_PERCENTILE", "FEE_EMA_ALPHA", "MEMPOOL_RESTART_BACKOFF_MAX_MS"] {
+            assert!(error.contains(expected), "{} missing from: {}", expected, error);
+        }
+    }
+
+    // The endpoints every mode needs, with nothing else set
+    fn missing(config: &Config) -> Vec<String> {
+        config.requirement_errors().into_iter()
+            .filter(|error| error.contains("is not set"))
+            .map(|error| error.split(' ').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_requirements_by_mode() {
+        // Live on devnet: only the read RPC
+        let mut config = Config::default();
+        assert_eq!(missing(&config), vec!["HELIUS"]);
+
+        // A dry run against a local validator needs no hosted RPC at all
+        config.dry_run = true;
+        config.rpc.solana_rpc_url = Some("http://127.0.0.1:8899".to_string());
+        assert!(config.requirement_errors().is_empty());
+
+        // ...but without a validator it still reads through Helius
+        config.rpc.solana_rpc_url = None;
+        assert_eq!(missing(&config), vec!["HELIUS"]);
+
+        // Live on mainnet adds the fallback RPC; a mainnet dry run doesn't
+        let mut config = Config::default();
+        config.network = "mainnet".to_string();
+        assert_eq!(missing(&config), vec!["HELIUS", "DRPC"]);
+        config.dry_run = true;
+        assert_eq!(missing(&config), vec!["HELIUS"]);
+
+        // Jito settings only matter with USE_JITO
+        let mut config = Config::default();
+        config.rpc.helius = Some("https://devnet.helius-rpc.com/?api-key=secret".to_string());
+        assert!(config.requirement_errors().is_empty());
+        config.jito.use_jito = true;
+        assert_eq!(missing(&config), vec!["JITO_RPC_URL", "JITO_TIP_ACCOUNT"]);
+        config.jito.rpc_url = Some("https://mainnet.block-engine.jito.wtf/api/v1/bundles".to_string());
+        config.jito.tip_account = Some("96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx".to_string());
+        assert!(config.requirement_errors().is_empty());
+
+        // The metrics server needs a port Prometheus can find
+        config.metrics.addr = Some("0.0.0.0:0".parse().unwrap());
+        assert_eq!(config.requirement_errors(), vec!["METRICS_ADDR needs a fixed port, not 0"]);
+        config.metrics.addr = Some("0.0.0.0:9100".parse().unwrap());
+        assert!(config.requirement_errors().is_empty());
+    }
+
+    #[test]
+    fn test_urls_and_tip_accounts_are_validated() {
+        let mut config = Config::default();
+        config.rpc.helius = Some("mainnet.helius-rpc.com/?api-key=secret".to_string());
+        config.rpc.drpc = Some("wss://lb.drpc.org".to_string());
+        config.rpc.solana_ws_urls = vec!["wss://a.example".to_string(), "https://b.example".to_string()];
+        config.jito.tip_account = Some("96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx,not-a-key".to_string());
+        let errors = config.requirement_errors();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors[0].starts_with("HELIUS is not a valid URL"));
+        assert_eq!(errors[1], "DRPC must be a http/https URL, not wss");
+        assert_eq!(errors[2], "SOLANA_WS_URLS must be a ws/wss URL, not https");
+        assert!(errors[3].contains("1 entry(s)"));
+        assert!(errors.iter().all(|error| !error.contains("secret")));
+    }
+
+    #[test]
+    fn test_mainnet_tightens_only_unset_risk_limits() {
+        let devnet = Config::load_with(None, env(&[])).unwrap();
+        assert_eq!(devnet.risk, RiskConfig::default());
+
+        let path = std::env::temp_dir().join(format!("bot_config_mainnet_{}.toml", std::process::id()));
+        std::fs::write(&path, "network = \"mainnet\"\n[risk]\nglobal_daily_spending_limit = 5.0\n").unwrap();
+        let mainnet = Config::load_with(path.to_str(), env(&[("MAX_LOSS_PER_BUNDLE", "0.08")])).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(mainnet.risk.global_daily_spending_limit, 5.0); // From the file
+        assert_eq!(mainnet.risk.max_loss_per_bundle, 0.08); // From the environment
+        assert_eq!(mainnet.risk.global_loss_per_bundle, 0.005); // Mainnet default
+        assert_eq!(mainnet.risk.min_balance, RiskConfig::default().min_balance);
+    }
+
+    #[test]
+    fn test_mainnet_needs_confirmation_and_jito_needs_mainnet() {
+        let mut config = Config::default();
+        config.network = "mainnet".to_string();
+        let error = config.check_network_safety(false).unwrap_err();
+        assert!(error.contains("CONFIRM_MAINNET=yes") && error.contains("--i-understand-mainnet"), "{}", error);
+        assert!(config.check_network_safety(true).is_ok());
+        config.jito.use_jito = true;
+        assert!(config.check_network_safety(true).is_ok());
+
+        // A dry run submits nothing, so it needs no confirmation
+        config.dry_run = true;
+        assert!(config.check_network_safety(false).is_ok());
+
+        for network in ["devnet", "testnet"] {
+            config.network = network.to_string();
+            let error = config.check_network_safety(true).unwrap_err();
+            assert!(error.starts_with("USE_JITO=true doesn't work on"), "{}", error);
+        }
+        config.jito.use_jito = false;
+        assert!(config.check_network_safety(false).is_ok());
+    }
+
+    #[test]
+    fn test_display_redacts_secrets() {
+        let shown = full_config().to_string();
+        assert!(!shown.contains("secret"));
+        assert!(shown.contains("helius = \"https://mainnet.helius-rpc.com\""));
+        assert!(shown.contains("auth_header = \"<redacted>\""));
+        assert!(shown.contains("[risk]"));
+    }
+}
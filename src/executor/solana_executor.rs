@@ -1,7 +1,9 @@
+use crate::config::Config;
 use crate::logging::Logger;
 use reqwest;
 use serde_json::{json, Value};
 use crate::utils::jito::JitoClient;
+use crate::rpc::types::{self, LatestBlockhash, RpcResponse};
 use crate::utils::profit_calculator::ProfitCalculator;
 use solana_sdk::{
     signature::{Keypair, Signer},
@@ -13,8 +15,10 @@ use solana_sdk::{
 };
 use std::str::FromStr;
 use std::sync::Arc;
-use crate::utils::risk_manager::RiskManager;
+use crate::utils::risk_controls::{RiskError, RiskManager};
 use crate::utils::analytics::Analytics;
+use crate::utils::dry_run;
+use crate::utils::bundle_tracker::ResolvedBundle;
 
 
 #[derive(Clone)]
@@ -27,14 +31,16 @@ pub struct SolanaExecutor {
     profit_calculator: ProfitCalculator,
     max_loss_per_bundle: f64,  // Máxima pérdida aceptable por bundle
     min_balance: f64,          // Saldo mínimo para continuar operaciones
-    risk_manager: Arc<RiskManager>,  // Wrap in Arc for shared access
+    risk_manager: Arc<RiskManager>,  // Shared with the mempool so both paths spend from one daily budget
     analytics: Arc<tokio::sync::Mutex<Analytics>>,
+    dry_run: bool,             // Todo se ejecuta salvo el envío de transacciones
+    jito_client: Option<JitoClient>, // The bot's block engine client; None sends through RPC only
 }
 
 impl SolanaExecutor {
-    pub fn new(rpc_url: String, ws_url: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        // Leer la clave privada desde el archivo
-        let keypair_data_str = std::fs::read_to_string("solana-keypair.json")
+    pub fn new(rpc_url: String, ws_url: String, risk_manager: Arc<RiskManager>, config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // Leer la clave privada desde el archivo (KEYPAIR_PATH)
+        let keypair_data_str = std::fs::read_to_string(&config.keypair_path)
             .map_err(|e| {
                 let error_msg = format!("Failed to read keypair file: {}. Make sure the file exists and has correct permissions.", e);
                 Logger::error_occurred(&error_msg);
@@ -47,39 +53,30 @@ impl SolanaExecutor {
                 error_msg
             })?;
 
-        // Verificar si se debe usar Jito
-        let use_jito = std::env::var("USE_JITO")
-            .unwrap_or_else(|_| "false".to_string())
-            .to_lowercase() == "true";
-            
-        // Configurar límites de riesgo desde variables de entorno o valores por defecto
-        let max_loss_per_bundle = std::env::var("MAX_LOSS_PER_BUNDLE")
-            .unwrap_or_else(|_| "0.1".to_string()) // 0.1 SOL por bundle como máximo de pérdida
-            .parse::<f64>()
-            .unwrap_or(0.1);
-            
-        let min_balance = std::env::var("MIN_BALANCE")
-            .unwrap_or_else(|_| "0.5".to_string()) // 0.5 SOL como saldo mínimo
-            .parse::<f64>()
-            .unwrap_or(0.5);
-
-        let risk_manager = Arc::new(RiskManager::new());
-        let analytics = Arc::new(tokio::sync::Mutex::new(Analytics::new()));
+        let analytics = Arc::new(tokio::sync::Mutex::new(Analytics::new(config.dry_run)));
 
         Ok(Self {
             client: Arc::new(reqwest::Client::new()),
             keypair_data,
             rpc_url,
             ws_url,
-            use_jito,
+            use_jito: config.jito.use_jito,
             profit_calculator: ProfitCalculator::new(),
-            max_loss_per_bundle,
-            min_balance,
+            max_loss_per_bundle: config.risk.max_loss_per_bundle,
+            min_balance: config.risk.min_balance, // The same floor the risk manager pauses at
             risk_manager,
             analytics,
+            dry_run: config.dry_run,
+            jito_client: None,
         })
     }
 
+    // Shares the bot's block engine client, with its searcher auth and rate limit
+    pub fn with_jito_client(mut self, jito_client: Option<JitoClient>) -> Self {
+        self.jito_client = jito_client;
+        self
+    }
+
     // Fix the fees issue in the frontrun function
     async fn record_transaction_analytics(&self, strategy: &str, success: bool, profit: f64, fees: f64) {
         let mut analytics = self.analytics.lock().await;
@@ -90,6 +87,25 @@ impl SolanaExecutor {
         let mut analytics = self.analytics.lock().await;
         analytics.record_opportunity(opportunity_type, executed, profitable, profit, execution_time_ms);
     }
+    
+    // Books a tracked bundle's real outcome; its strategy result was never recorded here
+    pub async fn record_bundle_outcome(&self, bundle: &ResolvedBundle) {
+        let strategy = format!("{:?}", bundle.strategy_type).to_lowercase();
+        self.record_transaction_analytics(&strategy, bundle.landed(), bundle.profit, bundle.fees).await;
+        self.analytics.lock().await.record_token_trade(&bundle.mints, bundle.landed(), bundle.profit, bundle.fees);
+    }
+    
+    // Shared with MetricsPersistence, which saves and restores the aggregates
+    pub fn analytics(&self) -> Arc<tokio::sync::Mutex<Analytics>> {
+        Arc::clone(&self.analytics)
+    }
+    
+    // Persists the session analytics, used on shutdown so nothing recorded is lost
+    pub async fn flush_analytics(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let analytics = self.analytics.lock().await;
+        analytics.print_summary();
+        analytics.save_to_file(file_path)
+    }
 } // Close first impl block
 
 impl SolanaExecutor {
@@ -99,15 +115,34 @@ impl SolanaExecutor {
     }
 
     pub fn get_keypair_public_key(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.keypair()?.pubkey().to_string())
+    }
+    
+    pub fn keypair(&self) -> Result<Keypair, Box<dyn std::error::Error + Send + Sync>> {
         if self.keypair_data.is_empty() {
             return Err("Keypair data is empty".into());
         }
         
-        let keypair = Keypair::from_bytes(&self.keypair_data)
-            .map_err(|e| format!("Invalid keypair data: {}", e))?;
-        let pubkey = keypair.pubkey();
-        
-        Ok(pubkey.to_string())
+        Ok(Keypair::from_bytes(&self.keypair_data)
+            .map_err(|e| format!("Invalid keypair data: {}", e))?)
+    }
+    
+    // Daily budget, balance, failure streak and per-bundle loss, against the shared risk controls
+    async fn check_risk(&self, estimated_profit: f64, total_cost: f64) -> Result<(), RiskError> {
+        self.risk_manager.should_allow_operation(total_cost).await?;
+        self.risk_manager.check_bundle_risk((total_cost - estimated_profit).max(0.0), total_cost).await
+    }
+    
+    // A sent transaction has paid its fees and tip whatever it earns, so they count as spent
+    async fn record_risk_outcome(&self, result: &Result<String, Box<dyn std::error::Error + Send + Sync>>, total_cost: f64) {
+        match result {
+            Ok(_) => self.risk_manager.record_successful_operation(-total_cost).await,
+            Err(_) => {
+                if let Err(e) = self.risk_manager.record_failed_operation().await {
+                    Logger::error_occurred(&format!("Risk controls: {}", e));
+                }
+            }
+        }
     }
     
     // Método para verificar si debemos continuar operando según los parámetros de riesgo
@@ -170,7 +205,7 @@ impl SolanaExecutor {
     }
     
     // Método para obtener el saldo actual de la billetera
-    async fn get_balance(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_balance(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
         // Derivar la clave pública del par de claves
         let keypair = Keypair::from_bytes(&self.keypair_data)
             .map_err(|e| format!("Invalid keypair data: {}", e))?;
@@ -198,13 +233,9 @@ impl SolanaExecutor {
             return Err(format!("Get balance failed: {}", error).into());
         }
 
-        if let Some(value) = response["result"]["value"].as_f64() {
-            // Convertir de lamports a SOL (1 SOL = 1000000000 lamports)
-            let sol_balance = value / 1_000_000_000.0;
-            Ok(sol_balance)
-        } else {
-            Err("Failed to parse balance result".into())
-        }
+        let lamports = types::parse_result::<RpcResponse<u64>>("getBalance", &response)?.value;
+        // Convertir de lamports a SOL (1 SOL = 1000000000 lamports)
+        Ok(lamports as f64 / 1_000_000_000.0)
     }
 
     pub async fn execute_frontrun(
@@ -238,10 +269,10 @@ impl SolanaExecutor {
         let total_cost = fees + tip_amount;
         
         // Check with risk manager if this transaction should be allowed
-        if !self.risk_manager.should_allow_transaction(estimated_profit, total_cost) {
-            Logger::status_update("Transaction rejected by risk manager");
+        if let Err(e) = self.check_risk(estimated_profit, total_cost).await {
+            Logger::status_update(&format!("Transaction rejected by risk manager: {}", e));
             self.record_transaction_analytics("frontrun", false, -total_cost, total_cost).await;
-            return Err("Transaction rejected by risk manager".into());
+            return Err(format!("Transaction rejected by risk manager: {}", e).into());
         }
         
         let analysis = self.profit_calculator.calculate_profitability(estimated_profit, fees, tip_amount);
@@ -332,6 +363,7 @@ impl SolanaExecutor {
         
         // Registrar resultados de la ejecución
         let execution_time = start_time.elapsed().as_millis() as f64;
+        self.record_risk_outcome(&result, total_cost).await;
         match &result {
             Ok(signature) => {
                 Logger::status_update(&format!("Frontrun successful: {}", signature));
@@ -383,7 +415,7 @@ impl SolanaExecutor {
         let transactions = vec![main_transaction_data.clone(), tip_transaction_data];
         
         // Usar Jito para enviar el bundle si está disponible
-        match JitoClient::new() {
+        match &self.jito_client {
             Some(jito_client) => {
                 Logger::status_update("Sending bundle via Jito");
                 match jito_client.send_bundle(&transactions).await {
@@ -449,12 +481,11 @@ impl SolanaExecutor {
             return Err(error_msg.into());
         }
 
-        match response["result"]["value"]["blockhash"].as_str() {
-            Some(blockhash) => Ok(blockhash.to_string()),
-            None => {
-                let error_msg = "Failed to parse blockhash result from response".to_string();
-                Logger::error_occurred(&error_msg);
-                Err(error_msg.into())
+        match types::parse_result::<RpcResponse<LatestBlockhash>>("getLatestBlockhash", &response) {
+            Ok(latest) => Ok(latest.value.blockhash),
+            Err(e) => {
+                Logger::error_occurred(&e.to_string());
+                Err(e.into())
             }
         }
     }
@@ -737,7 +768,7 @@ impl SolanaExecutor {
             .map_err(|e| format!("Invalid keypair data: {}", e))?;
         
         // Get a Jito tip account from the JitoClient
-        let jito_client = JitoClient::new().ok_or("Jito client not initialized")?;
+        let jito_client = self.jito_client.as_ref().ok_or("Jito client not initialized")?;
         let tip_recipient = jito_client.get_random_tip_account();
         
         Logger::status_update(&format!("Using tip account: {}", tip_recipient));
@@ -775,6 +806,12 @@ impl SolanaExecutor {
     }
 
     async fn send_transaction(&self, transaction_data: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        if self.dry_run {
+            let signature = dry_run::synthetic_signature(&[transaction_data.to_string()]);
+            Logger::status_update(&format!("DRY RUN: transaction not sent ({})", signature));
+            return Ok(signature);
+        }
+        
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -802,11 +839,7 @@ impl SolanaExecutor {
             return Err(format!("Transaction failed: {}", error).into());
         }
 
-        if let Some(result) = response["result"].as_str() {
-            Ok(result.to_string())
-        } else {
-            Err("Failed to parse transaction result".into())
-        }
+        Ok(types::parse_result::<String>("sendTransaction", &response)?)
     }
 
     pub async fn execute_sandwich(
@@ -846,10 +879,10 @@ impl SolanaExecutor {
         let total_cost = fees + tip_amount;
         
         // Check with risk manager if this transaction should be allowed
-        if !self.risk_manager.should_allow_transaction(estimated_profit, total_cost) {
-            Logger::status_update("Transaction rejected by risk manager");
+        if let Err(e) = self.check_risk(estimated_profit, total_cost).await {
+            Logger::status_update(&format!("Transaction rejected by risk manager: {}", e));
             self.record_transaction_analytics("sandwich", false, -total_cost, total_cost).await;
-            return Err("Transaction rejected by risk manager".into());
+            return Err(format!("Transaction rejected by risk manager: {}", e).into());
         }
         
         let analysis = self.profit_calculator.calculate_profitability(estimated_profit, fees, tip_amount);
@@ -940,6 +973,7 @@ impl SolanaExecutor {
         
         // Registrar resultados de la ejecución
         let execution_time = start_time.elapsed().as_millis() as f64;
+        self.record_risk_outcome(&result, total_cost).await;
         match &result {
             Ok(signature) => {
                 Logger::status_update(&format!("Sandwich successful: {}", signature));
@@ -989,7 +1023,7 @@ impl SolanaExecutor {
         let transactions = vec![main_transaction_data.clone(), tip_transaction_data];
         
         // Usar Jito para enviar el bundle si está disponible
-        match JitoClient::new() {
+        match &self.jito_client {
             Some(jito_client) => {
                 Logger::status_update("Sending sandwich bundle via Jito");
                 match jito_client.send_bundle(&transactions).await {
@@ -1059,10 +1093,10 @@ impl SolanaExecutor {
         let total_cost = fees + tip_amount;
         
         // Check with risk manager if this transaction should be allowed
-        if !self.risk_manager.should_allow_transaction(estimated_profit, total_cost) {
-            Logger::status_update("Transaction rejected by risk manager");
+        if let Err(e) = self.check_risk(estimated_profit, total_cost).await {
+            Logger::status_update(&format!("Transaction rejected by risk manager: {}", e));
             self.record_transaction_analytics("arbitrage", false, -total_cost, total_cost).await;
-            return Err("Transaction rejected by risk manager".into());
+            return Err(format!("Transaction rejected by risk manager: {}", e).into());
         }
         
         let analysis = self.profit_calculator.calculate_profitability(estimated_profit, fees, tip_amount);
@@ -1153,6 +1187,7 @@ impl SolanaExecutor {
         
         // Registrar resultados de la ejecución
         let execution_time = start_time.elapsed().as_millis() as f64;
+        self.record_risk_outcome(&result, total_cost).await;
         match &result {
             Ok(signature) => {
                 Logger::status_update(&format!("Arbitrage successful: {}", signature));
@@ -1202,7 +1237,7 @@ impl SolanaExecutor {
         let transactions = vec![main_transaction_data.clone(), tip_transaction_data];
         
         // Usar Jito para enviar el bundle si está disponible
-        match JitoClient::new() {
+        match &self.jito_client {
             Some(jito_client) => {
                 Logger::status_update("Sending arbitrage bundle via Jito");
                 match jito_client.send_bundle(&transactions).await {
@@ -1250,6 +1285,12 @@ impl SolanaExecutor {
         
         let fees = self.calculate_transaction_fees().await?;
         let tip_amount = if self.use_jito { 0.001 } else { 0.0 }; // 0.001 SOL como propina para Jito
+        let total_cost = fees + tip_amount;
+        
+        if let Err(e) = self.check_risk(estimated_profit, total_cost).await {
+            Logger::status_update(&format!("Snipe rejected by risk manager: {}", e));
+            return Err(format!("Snipe rejected by risk manager: {}", e).into());
+        }
         
         // Additional safety check: prevent execution if estimated profit is non-positive
         if estimated_profit <= 0.0 {
@@ -1333,6 +1374,7 @@ impl SolanaExecutor {
         };
         
         // Registrar resultados de la ejecución
+        self.record_risk_outcome(&result, total_cost).await;
         match &result {
             Ok(signature) => {
                 Logger::status_update(&format!("Snipe successful: {}", signature));
@@ -1377,7 +1419,7 @@ impl SolanaExecutor {
         let transactions = vec![main_transaction_data.clone(), tip_transaction_data];
         
         // Usar Jito para enviar el bundle si está disponible
-        match JitoClient::new() {
+        match &self.jito_client {
             Some(jito_client) => {
                 Logger::status_update("Sending snipe bundle via Jito");
                 match jito_client.send_bundle(&transactions).await {
@@ -1410,3 +1452,48 @@ impl SolanaExecutor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use crate::utils::mock_http::{self, MockResponse};
+    use crate::utils::risk_controls::RiskLimits;
+
+    #[tokio::test]
+    async fn test_dry_run_never_posts_send_transaction() {
+        let (url, connections) = mock_http::serve(|_| MockResponse::new(200, String::new())).await;
+
+        let executor = SolanaExecutor {
+            dry_run: true,
+            ..SolanaExecutor::new(url, "ws://127.0.0.1:0".to_string(), Arc::new(RiskManager::new(&Config::default()).unwrap()), &Config::default()).unwrap()
+        };
+        let signature = executor.send_transaction("signed-transaction").await.unwrap();
+
+        assert!(dry_run::is_synthetic(&signature));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(connections.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_executor_spend_counts_against_the_shared_daily_limit() {
+        let limits = RiskLimits { global_daily_spending_limit: 0.01, ..RiskLimits::from_config(&Config::default().risk) };
+        let risk_manager = Arc::new(RiskManager::with_limits(limits));
+        risk_manager.initialize_balance(2.0).await;
+        let executor = SolanaExecutor::new("http://127.0.0.1:0".to_string(), "ws://127.0.0.1:0".to_string(), Arc::clone(&risk_manager), &Config::default()).unwrap();
+
+        assert!(executor.check_risk(0.05, 0.004).await.is_ok());
+        executor.record_risk_outcome(&Ok("5Sig".to_string()), 0.004).await;
+        executor.record_risk_outcome(&Ok("6Sig".to_string()), 0.004).await;
+        // A send that failed paid nothing
+        executor.record_risk_outcome(&Err("send failed".into()), 0.004).await;
+        assert_eq!(risk_manager.get_risk_metrics().await.daily_spending, 0.008);
+
+        // The strategy path asks the same instance, so the executor's spend leaves no room for another bundle
+        assert!(matches!(
+            risk_manager.should_allow_operation(0.004).await,
+            Err(RiskError::DailySpendingLimitExceeded)
+        ));
+        assert!(matches!(executor.check_risk(0.05, 0.004).await, Err(RiskError::DailySpendingLimitExceeded)));
+    }
+}
@@ -1,19 +1,15 @@
 // Integration test for the Jito bundle functionality
 #[cfg(test)]
 mod integration_tests {
-    use crate::executor::solana_executor::SolanaExecutor;
+    use crate::config::Config;
     use crate::utils::jito::JitoClient;
-    use std::env;
 
     #[tokio::test]
     async fn test_jito_client_tip_account_selection() {
-        // Set up the environment for testing
-        env::set_var("JITO_RPC_URL", "https://mainnet.block-engine.jito.wtf/api/v1/bundles");
+        let mut config = Config::default();
+        config.jito.rpc_url = Some("https://mainnet.block-engine.jito.wtf/api/v1/bundles".to_string());
         
-        let jito_client = JitoClient::new();
-        assert!(jito_client.is_some(), "JitoClient should initialize when JITO_RPC_URL is set");
-        
-        let client = jito_client.unwrap();
+        let client = JitoClient::from_config(&config);
         let tip_account = client.get_random_tip_account();
         
         // Verify that we get a valid tip account
@@ -3,4 +3,7 @@ pub mod executor;
 pub mod utils;
 pub mod logging;
 pub mod mempool;
-pub mod rpc;
\ No newline at end of file
+pub mod rpc;
+pub mod bot;
+
+pub use bot::{Bot, BotBuilder, BotHandle};
@@ -1,19 +1,41 @@
 use colored::*;
 
+#[cfg(test)]
+thread_local! {
+    // Status lines logged on this thread, so tests can check what was reported
+    static STATUS_LINES: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
 /// Professional CLI logging for the MEV bot
 pub struct Logger;
 
 impl Logger {
-    pub fn startup(network: &str, strategies: &str) {
+    pub fn startup(network: &str, strategies: &str, thresholds: &str, dry_run: bool) {
         println!("{}", "=".repeat(60).blue());
         println!("{} {}", "Solana MEV Bot".bold().green(), "v0.1.0".dimmed());
         println!("{}", "=".repeat(60).blue());
         println!("{} {}", "Network:".bold().yellow(), network);
         println!("{} {}", "Strategies:".bold().yellow(), strategies);
-        println!("{} {}", "Status:".bold().yellow(), "Running".green());
+        println!("{} {}", "Min profit:".bold().yellow(), thresholds);
+        if dry_run {
+            println!("{} {}", "Status:".bold().yellow(), "DRY RUN - nothing will be submitted".black().on_yellow().bold());
+        } else {
+            println!("{} {}", "Status:".bold().yellow(), "Running".green());
+        }
         println!("{}", "=".repeat(60).blue());
     }
 
+    pub fn mainnet_warning(wallet: &str, balance_sol: Option<f64>) {
+        println!("{}", "!".repeat(60).red().bold());
+        println!("{}", " MAINNET - trading with real funds ".white().on_red().bold());
+        println!("{} {}", "Wallet:".bold().red(), wallet);
+        match balance_sol {
+            Some(balance) => println!("{} {:.4} SOL", "Balance:".bold().red(), balance),
+            None => println!("{} {}", "Balance:".bold().red(), "unavailable".dimmed()),
+        }
+        println!("{}", "!".repeat(60).red().bold());
+    }
+
     pub fn eth_monitor_start() {
         println!("{} {}", "".cyan(), "Ethereum mempool monitor started".cyan());
     }
@@ -39,9 +61,17 @@ impl Logger {
     }
 
     pub fn status_update(status: &str) {
+        #[cfg(test)]
+        STATUS_LINES.with(|lines| lines.borrow_mut().push(status.to_string()));
         println!("{} {}", "".blue(), status);
     }
 
+    // Status lines this thread has logged so far
+    #[cfg(test)]
+    pub fn status_lines() -> Vec<String> {
+        STATUS_LINES.with(|lines| lines.borrow().clone())
+    }
+
     pub fn shutdown() {
         println!();
         println!("{}", "=".repeat(60).blue());
@@ -1,75 +1,89 @@
+mod cli;
+
 use dotenv::dotenv;
-use std::env;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use clap::Parser;
 use tokio;
-use colored::Colorize;
 
-use rust_mev_hybrid_bot::config::Network;
+use rust_mev_hybrid_bot::config::{self, Config, Network};
 use rust_mev_hybrid_bot::logging::Logger;
-use rust_mev_hybrid_bot::mempool::solana::SolanaMempool;
+use rust_mev_hybrid_bot::Bot;
+
+use cli::{Cli, Command};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
+    let args = Cli::parse();
+    // --network, --strategy and --dry-run win over the environment and .env
+    args.overrides.apply();
+    // Defaults, then the config file, then env vars; a bad value in any of them stops startup here
+    let config = Config::load(args.config.as_deref()).map_err(|e| anyhow::anyhow!(e))?;
     
+    match args.command.unwrap_or(Command::Run) {
+        Command::Run => run(config).await,
+        Command::Check => {
+            validate_environment_variables(&config)?;
+            cli::check(&config).await
+        }
+        Command::Balance { keypair } => cli::balance(&config, keypair.as_deref().unwrap_or(&config.keypair_path)).await,
+        Command::Metrics { file } => cli::metrics(file.as_deref().unwrap_or(&config.metrics.state_file)),
+    }
+}
+
+async fn run(config: Config) -> Result<()> {
     // NEW ARCHITECTURE: Validate required environment variables
-    validate_environment_variables()?;
-    
-    let network_env = env::var("NETWORK").unwrap_or_else(|_| "devnet".to_string()).to_lowercase();
-    let network = match network_env.as_str() {
-        "mainnet" => Network::Mainnet,
-        "testnet" => Network::Testnet,
-        "devnet" => Network::Devnet,
-        _ => Network::Devnet, // Default to devnet
-    };
+    validate_environment_variables(&config)?;
+    // Live trading on mainnet needs CONFIRM_MAINNET=yes or --i-understand-mainnet
+    let bot = Bot::builder()
+        .with_config(config)
+        .with_mainnet_confirmed(config::mainnet_confirmed())
+        .build()
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let config = bot.config();
     
-    let network_str = match network {
-        Network::Mainnet => "MAINNET",
-        Network::Testnet => "TESTNET", 
-        Network::Devnet => "DEVNET",
-    };
-    
-    let strategy = env::var("STRATEGY").unwrap_or_else(|_| "arbitrage".to_string());
-    println!("Debug: Strategy value read from env: {}", strategy);  // Debug line
-
-    Logger::startup(network_str, &strategy);
-
-    // Solana thread - now the only network we support
-    if strategy.contains("snipe") || strategy.contains("frontrun") || strategy.contains("sandwich") || strategy.contains("arbitrage") {
-        println!("Debug: Starting Solana mempool...");
-        let sol_mempool = SolanaMempool::new(&network);
-        Logger::solana_monitor_start();
-        tokio::spawn(async move {
-            sol_mempool.start().await
-        });
+    let thresholds = cli::thresholds(config)?;
+    Logger::startup(config.network().label(), &cli::strategy_names(config)?, &thresholds.to_string(), config.dry_run);
+    Logger::status_update(&format!("Resolved configuration:\n{}", config));
+    // Endpoints, keypair and balance are probed before anything starts; SKIP_PREFLIGHT=true is for
+    // air-gapped test setups
+    if config.skip_preflight {
+        Logger::status_update("Preflight checks skipped (SKIP_PREFLIGHT=true)");
     } else {
-        println!("Debug: No Solana strategies enabled");
+        cli::preflight(config).await?;
+    }
+    if matches!(config.network(), Network::Mainnet) && !config.dry_run {
+        cli::mainnet_warning(config).await?;
     }
 
+    let mut handle = bot.start().await.map_err(|e| anyhow::anyhow!(e))?;
+
     // Espera indefinida (bot corre forever)
-    println!("{} Press Ctrl+C to stop", "".cyan());
-    tokio::signal::ctrl_c().await?;
-    Logger::shutdown();
-    Ok(())
+    Logger::status_update("Press Ctrl+C to stop");
+    tokio::select! {
+        signal = tokio::signal::ctrl_c() => {
+            signal?;
+            Logger::shutdown();
+        }
+        // Only when the mempool kept crashing; stop() reports why
+        _ = handle.finished() => {}
+    }
+
+    // Let the mempool drain in-flight executions and flush metrics before exiting
+    handle.stop().await.map_err(|e| anyhow::anyhow!("Mempool kept crashing: {}", e))
 }
 
-fn validate_environment_variables() -> Result<()> {
-    // NEW ARCHITECTURE: Check that all required environment variables are set
-    let required_vars = vec![
-        "HELIUS",      // For read/simulation calls
-        "JITO_RPC_URL", // For execution
-        "JITO_TIP_ACCOUNT", // For Jito tips
-        "DRPC",        // Fallback RPC
-    ];
-    
-    for var in required_vars {
-        if std::env::var(var).is_err() {
-            eprintln!("ERROR: Environment variable {} is not set", var);
-            eprintln!("Please check your .env file and ensure all required variables are present");
-            std::process::exit(1);
-        }
+// Which variables are required depends on the mode (network, USE_JITO, DRY_RUN, metrics); all
+// missing or malformed ones come back in one error, which main prints before exiting nonzero
+fn validate_environment_variables(config: &Config) -> Result<()> {
+    let errors = config.requirement_errors();
+    if !errors.is_empty() {
+        bail!(
+            "Missing or invalid environment variables:\n  - {}\nPlease check your .env file (or bot.toml)",
+            errors.join("\n  - ")
+        );
     }
     
-    println!("All required environment variables are present");
+    Logger::status_update("All required environment variables are present");
     Ok(())
 }
\ No newline at end of file
@@ -1,34 +1,127 @@
-use crate::config::Network;
+use crate::config::{Config, Network};
 use crate::logging::Logger;
 use reqwest;
 use serde_json::{json, Value};
+use serde::Deserialize;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use futures_util::StreamExt;
 use futures::SinkExt;
-use std::env;
+use std::collections::HashSet;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use crate::executor::solana_executor::SolanaExecutor;
 use crate::utils::profitability_calculator::OpportunityAnalysis;
 use crate::utils::dex_monitor::DEXMonitor;
 use crate::utils::dex_api::DexApi;
 use crate::utils::transaction_simulator::TransactionSimulator;
-use crate::rpc::rpc_manager::RpcManager;
+use crate::rpc::error as rpc_error;
+use crate::rpc::rpc_manager::{RpcManager, RpcTaskType};
+use crate::rpc::types::{self, EncodedTransaction, TransactionMeta};
 use crate::utils::opportunity_evaluator::OpportunityEvaluator;
-use crate::utils::enhanced_transaction_simulator::EnhancedTransactionSimulator;
-use crate::utils::mev_simulation_pipeline::MevSimulationPipeline;
+use crate::utils::enhanced_transaction_simulator::{EnhancedTransactionSimulator, OpportunityDetails};
+use crate::utils::mev_simulation_pipeline::{MevSimulationPipeline, TargetTransaction};
+use crate::utils::simulation_config::SimulationConfig;
+use crate::utils::simulation_consensus::ConsensusConfig;
+use crate::utils::jito::JitoClient;
 use crate::utils::fee_calculator::FeeCalculator;
 use crate::utils::false_positive_reducer::FalsePositiveReducer;
+use crate::utils::filter_decisions::OutcomeSource;
+use crate::utils::filter_decisions::FilterCalibrationConfig;
+use crate::utils::token_safety::{TokenSafetyChecker, TokenSafetyPolicy};
+use crate::utils::launch_filters::{LaunchFilterConfig, LaunchFilters};
 use crate::utils::jito_optimizer::JitoOptimizer;
-use crate::utils::mev_strategies::MevStrategyExecutor;
-use crate::utils::metrics_collector::MetricsCollector;
-use crate::utils::risk_controls::RiskManager as NewRiskManager;
+use crate::utils::mev_strategies::{MevStrategyExecutor, MevStrategyType};
+use crate::utils::bundle_tracker::ResolvedBundle;
+use crate::utils::metrics_collector::{MetricsCollector, OpportunityTiming};
+use crate::utils::alert_sinks::AlertDispatcher;
+use crate::utils::opportunity_outcomes::OpportunityStage;
+use crate::utils::metrics_server::{MetricsServer, StreamLiveness};
+use crate::utils::risk_controls::{RiskError, RiskManager as NewRiskManager};
+use crate::utils::balance_poller::{BalancePoller, RpcBalanceSource};
+use crate::utils::performance_summary::PerformanceSummarizer;
+use crate::utils::metrics_state::MetricsPersistence;
+use crate::utils::pool_blacklist::PoolBlacklist;
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::opportunity_queue::{OpportunityQueue, QueuedOpportunity};
+use crate::utils::analysis_limiter::AnalysisLimiter;
+use crate::utils::signature_dedup::{Delivery, SignatureDedupCache};
+use crate::utils::dex_registry::DexRegistry;
+use crate::utils::instruction_parser;
+use crate::utils::transaction_fetcher::{self, TransactionFetcher};
+
+// Metric label for a WebSocket endpoint: host and port only, so API keys in the path or
+// query string never end up in logs or Prometheus output
+pub fn ws_endpoint_label(ws_url: &str) -> String {
+    match url::Url::parse(ws_url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => ws_url.to_string(),
+        },
+        Err(_) => ws_url.to_string(),
+    }
+}
+
+// Risk check run before any per-opportunity RPC work; a missing risk manager never pauses
+pub async fn risk_allows_analysis(risk_manager: Option<&NewRiskManager>) -> bool {
+    match risk_manager {
+        Some(risk_manager) => !risk_manager.check_pause_state().await,
+        None => true,
+    }
+}
+
+// A pending bundle's verdict, booked with the risk manager the way execute_queued_opportunity
+// books a result whose landing was known on submission
+async fn record_bundle_risk(risk_manager: &NewRiskManager, bundle: &ResolvedBundle) {
+    if bundle.landed() {
+        risk_manager.record_strategy_success(&bundle.strategy_type).await;
+        risk_manager.record_successful_operation(-(bundle.fees + bundle.tip)).await;
+    } else {
+        risk_manager.record_strategy_failure(&bundle.strategy_type).await;
+        let _ = risk_manager.record_failed_operation().await;
+    }
+    risk_manager.record_pool_outcome(&bundle.pools, bundle.profit, !bundle.landed()).await;
+}
+
+// A logsNotification handed from the WebSocket reader to an analysis task
+#[derive(Debug, Clone)]
+pub struct PendingNotification {
+    pub signature: String,
+    pub received_at: Instant,
+}
+
+impl PendingNotification {
+    pub fn new(signature: &str) -> Self {
+        Self {
+            signature: signature.to_string(),
+            received_at: Instant::now(),
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+}
+
+// Whether the strategy that would execute this opportunity was enabled by the operator
+pub fn strategy_enabled(enabled_strategies: &HashSet<MevStrategyType>, opportunity: &OpportunityDetails) -> bool {
+    enabled_strategies.contains(&MevStrategyType::for_opportunity(&opportunity.opportunity_type))
+}
 
 #[derive(Clone)]
 pub struct SolanaMempool {
     client: Arc<reqwest::Client>,
     rpc_url: String,
     ws_url: String,
+    ws_urls: Vec<String>, // All endpoints raced for notifications; ws_url is the first
+    signature_dedup: SignatureDedupCache,
+    dex_registry: Arc<DexRegistry>, // Only activity of registered programs is fetched and evaluated
     network: Network,
     dex_api: Arc<DexApi>,
     dex_monitor: Arc<tokio::sync::RwLock<DEXMonitor>>,
@@ -36,6 +129,7 @@ pub struct SolanaMempool {
     
     // NEW ARCHITECTURE COMPONENTS - Optional until initialized
     rpc_manager: Option<Arc<RpcManager>>,
+    transaction_fetcher: Option<Arc<TransactionFetcher>>, // Batches target lookups that arrive together
     opportunity_evaluator: Option<Arc<OpportunityEvaluator>>,
     enhanced_simulator: Option<Arc<EnhancedTransactionSimulator>>,
     mev_simulation_pipeline: Option<Arc<MevSimulationPipeline>>,
@@ -45,44 +139,94 @@ pub struct SolanaMempool {
     mev_strategy_executor: Option<Arc<MevStrategyExecutor>>,
     metrics_collector: Option<Arc<MetricsCollector>>,
     new_risk_manager: Option<Arc<NewRiskManager>>,
+    opportunity_queue: OpportunityQueue,
+    analysis_limiter: AnalysisLimiter,
+    max_notification_age: Duration,
+    execution_workers: usize,
+    enabled_strategies: Arc<HashSet<MevStrategyType>>,
+    shutdown_timeout: Duration,
+    triangular_interval: Option<Duration>, // None disables the background route search
+    balance_poll_interval: Duration,
+    performance_summarizer: Arc<PerformanceSummarizer>,
+    metrics_persistence: Arc<MetricsPersistence>, // Session totals that survive restarts
+    stream_liveness: StreamLiveness, // What /healthz reports for the notification stream
+    config: Arc<Config>, // Settings for the executor and the liquidation scan it starts
+    jito_client: JitoClient, // Shared with the executor so both paths submit under one auth and rate limit
 }
 
 impl SolanaMempool {
-    pub async fn new(network: &Network) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        // Use devnet RPC endpoint by default
-        let rpc_url = match network {
-            Network::Devnet => std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
-            Network::Testnet => std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.testnet.solana.com".to_string()),
-            Network::Mainnet => std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
-        };
-
-        let ws_url = match network {
-            Network::Devnet => std::env::var("SOLANA_WS_URL").unwrap_or_else(|_| "wss://api.devnet.solana.com".to_string()),
-            Network::Testnet => std::env::var("SOLANA_WS_URL").unwrap_or_else(|_| "wss://api.testnet.solana.com".to_string()),
-            Network::Mainnet => std::env::var("SOLANA_WS_URL").unwrap_or_else(|_| "wss://api.mainnet-beta.solana.com".to_string()),
-        };
+    // The mempool only executes the strategies config enables
+    pub async fn new(config: Arc<Config>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let rpc_manager = Arc::new(RpcManager::from_config(&config).await?);
+        Self::with_rpc_manager(config, rpc_manager).await
+    }
+    
+    // Everything else is built from config around the given RPC manager
+    pub async fn with_rpc_manager(config: Arc<Config>, rpc_manager: Arc<RpcManager>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let network = config.network();
+        let enabled_strategies = config.enabled_strategies()?;
+        
+        // Use the network's public endpoints unless one is configured
+        let rpc_url = config.rpc.solana_rpc_url.clone().unwrap_or_else(|| match network {
+            Network::Devnet => "https://api.devnet.solana.com".to_string(),
+            Network::Testnet => "https://api.testnet.solana.com".to_string(),
+            Network::Mainnet => "https://api.mainnet-beta.solana.com".to_string(),
+        });
 
-        let dex_api = Arc::new(DexApi::new(rpc_url.clone()));
-        let dex_monitor = Arc::new(tokio::sync::RwLock::new(DEXMonitor::new()));
+        // Several endpoints to race; ws_url is the first
+        let ws_urls = config.ws_urls();
+        let ws_url = ws_urls[0].clone();
         
+        let dex_api = Arc::new(DexApi::new(rpc_url.clone()));
         let transaction_simulator = Arc::new(TransactionSimulator::new(rpc_url.clone())?);
-
-        // NEW ARCHITECTURE - initialize with proper initialization
-        let rpc_manager = Arc::new(RpcManager::new().await?);
         
-        let opportunity_evaluator = Arc::new(OpportunityEvaluator::new(rpc_manager.clone()).await?);
+        let mut metrics_collector = MetricsCollector::new(&config)?;
+        if let Some(dispatcher) = AlertDispatcher::from_config(&config.alerts)? {
+            metrics_collector = metrics_collector.with_alert_dispatcher(dispatcher);
+        }
+        let metrics_collector = Arc::new(metrics_collector);
+        rpc_manager.set_metrics_collector(metrics_collector.clone());
+        
+        // One blacklist: the risk manager books realized pool results into it, the evaluator skips what it lists
+        let pool_blacklist = PoolBlacklist::from_config(&config.pools)?;
         
-        let enhanced_simulator = Arc::new(EnhancedTransactionSimulator::new(rpc_manager.clone()).await?);
+        let fee_calculator = Arc::new(FeeCalculator::new(rpc_manager.clone(), &config).await?);
         
-        let mev_simulation_pipeline = Arc::new(MevSimulationPipeline::new(rpc_manager.clone()).await?);
+        let opportunity_evaluator = Arc::new(
+            OpportunityEvaluator::new(rpc_manager.clone(), &config).await?
+                .with_pool_blacklist(pool_blacklist.clone())
+                .with_profit_calibration(metrics_collector.profit_calibration())
+                .with_fee_calculator(fee_calculator.clone())
+        );
         
-        let fee_calculator = Arc::new(FeeCalculator::new(rpc_manager.clone()).await?);
+        // The DEX monitor tracks the same configured pools as the evaluator, with reserves kept
+        // current by the pool watcher's account subscriptions
+        let mut monitor = DEXMonitor::from_config(&config.pools);
+        monitor.update_pools(opportunity_evaluator.pool_registry().pool_infos().await).await;
+        let dex_monitor = Arc::new(tokio::sync::RwLock::new(monitor));
+        DEXMonitor::spawn_ingestion(dex_monitor.clone(), opportunity_evaluator.pool_watcher().subscribe_updates());
         
-        let jito_optimizer = Arc::new(JitoOptimizer::new(rpc_manager.clone()).await?);
+        let enhanced_simulator = Arc::new(EnhancedTransactionSimulator::new(rpc_manager.clone()).await?
+            .with_consensus(ConsensusConfig::from_config(&config.simulation)?)
+            .with_metrics_collector(metrics_collector.clone())
+            .with_pool_states(opportunity_evaluator.clone()));
         
-        let metrics_collector = Arc::new(MetricsCollector::new()?);
+        // One block engine client for the whole bot: submissions, simulateBundle and status polls
+        // share its searcher auth and rate limit
+        let jito_client = JitoClient::from_config(&config);
         
-        let new_risk_manager = Arc::new(NewRiskManager::new()?);
+        let mev_simulation_pipeline = Arc::new(
+            MevSimulationPipeline::new(rpc_manager.clone(), opportunity_evaluator.clone(), SimulationConfig::from_config(&config.simulation)?).await?
+                .with_jito_client(Some(jito_client.clone()))
+        );
+        
+        let jito_optimizer = Arc::new(JitoOptimizer::new(rpc_manager.clone(), &config).await?);
+        
+        let transaction_fetcher = Arc::new(TransactionFetcher::from_config(rpc_manager.clone(), &config.rpc));
+        
+        let new_risk_manager = Arc::new(
+            NewRiskManager::new(&config)?.with_state_file(&config.risk.state_file).with_pool_blacklist(pool_blacklist)
+        );
         
         let mev_strategy_executor = Arc::new(MevStrategyExecutor::new(
             rpc_manager.clone(),
@@ -90,21 +234,55 @@ impl SolanaMempool {
             fee_calculator.clone(),
             opportunity_evaluator.clone(),
             mev_simulation_pipeline.clone(),
-        ).await?);
-        
-        let false_positive_reducer = Arc::new(FalsePositiveReducer::new());
+            jito_client.clone(),
+            &config,
+        ).await?.with_profit_calibration(metrics_collector.profit_calibration()));
+        metrics_collector.record_strategy_thresholds(mev_strategy_executor.thresholds().clone()).await;
+        
+        let false_positive_reducer = Arc::new(
+            FalsePositiveReducer::new()
+                .with_token_safety(Arc::new(TokenSafetyChecker::new(
+                    opportunity_evaluator.mint_info_cache(),
+                    rpc_manager.clone(),
+                    TokenSafetyPolicy::from_config(&config.token_safety)?,
+                )))
+                .with_launch_filters(Arc::new(LaunchFilters::new(LaunchFilterConfig::from_config(&config.snipe)?, rpc_manager.clone())))
+                .with_pool_states(opportunity_evaluator.clone())
+                .with_outcomes(metrics_collector.opportunity_outcomes())
+                .with_decisions(metrics_collector.filter_decisions(), FilterCalibrationConfig::from_config(&config.filters)?)
+        );
+        
+        let opportunity_queue = OpportunityQueue::from_config(&config.strategies);
+        
+        let analysis_limiter = AnalysisLimiter::from_config(&config.strategies);
+        
+        let balance_poll_interval = BalancePoller::interval(&config.risk);
+        
+        let performance_summarizer = Arc::new(PerformanceSummarizer::from_config(
+            Arc::clone(&metrics_collector),
+            Some(Arc::clone(&new_risk_manager)),
+            &config.metrics,
+        ));
+        
+        let metrics_persistence = Arc::new(MetricsPersistence::from_config(Arc::clone(&metrics_collector), &config.metrics));
+        
+        let stream_liveness = StreamLiveness::from_config(&config.metrics);
 
         Ok(Self {
             client: Arc::new(reqwest::Client::new()),
             rpc_url,
             ws_url,
-            network: network.clone(),
+            ws_urls,
+            signature_dedup: SignatureDedupCache::new(10_000), // Several slots worth of signatures
+            dex_registry: Arc::new(DexRegistry::from_config(&config.pools)?),
+            network,
             dex_api,
             dex_monitor,
             transaction_simulator,
             
             // NEW ARCHITECTURE COMPONENTS
             rpc_manager: Some(rpc_manager),
+            transaction_fetcher: Some(transaction_fetcher),
             opportunity_evaluator: Some(opportunity_evaluator),
             enhanced_simulator: Some(enhanced_simulator),
             mev_simulation_pipeline: Some(mev_simulation_pipeline),
@@ -114,44 +292,477 @@ impl SolanaMempool {
             mev_strategy_executor: Some(mev_strategy_executor),
             metrics_collector: Some(metrics_collector),
             new_risk_manager: Some(new_risk_manager),
+            opportunity_queue,
+            analysis_limiter,
+            max_notification_age: Duration::from_millis(config.strategies.max_notification_age_ms),
+            execution_workers: config.strategies.opportunity_workers,
+            enabled_strategies: Arc::new(enabled_strategies.into_iter().collect()),
+            shutdown_timeout: Duration::from_millis(config.shutdown_timeout_ms),
+            triangular_interval: (config.strategies.triangular_arb_interval_ms > 0)
+                .then(|| Duration::from_millis(config.strategies.triangular_arb_interval_ms)),
+            balance_poll_interval,
+            performance_summarizer,
+            metrics_persistence,
+            stream_liveness,
+            config,
+            jito_client,
         })
     }
 
-    pub async fn start(&self) {
+    pub fn metrics_collector(&self) -> Option<Arc<MetricsCollector>> {
+        self.metrics_collector.clone()
+    }
+    
+    pub fn risk_manager(&self) -> Option<Arc<NewRiskManager>> {
+        self.new_risk_manager.clone()
+    }
+    
+    // Serves this mempool's metrics and health; None without a metrics collector
+    pub fn metrics_server(&self) -> Option<MetricsServer> {
+        let metrics_collector = self.metrics_collector.clone()?;
+        Some(MetricsServer::new(metrics_collector, self.stream_liveness.clone(), self.new_risk_manager.clone()))
+    }
+    
+    // Runs until `shutdown` flips to true, then drains in-flight executions and flushes metrics
+    pub async fn start(&self, shutdown: watch::Receiver<bool>) {
         Logger::status_update(&format!("Solana mempool monitoring active on {:?}", self.network));
         
-        // Initialize Solana Executor
-        let executor = match SolanaExecutor::new(self.rpc_url.clone(), self.ws_url.clone()) {
+        // Initialize Solana Executor; it spends from the same risk controls as the strategy path
+        let executor = match &self.new_risk_manager {
+            Some(risk_manager) => SolanaExecutor::new(self.rpc_url.clone(), self.ws_url.clone(), Arc::clone(risk_manager), &self.config)
+                .map(|executor| executor.with_jito_client(Some(self.jito_client.clone()))),
+            None => Err("risk controls are not initialized".into()),
+        };
+        let executor = match executor {
             Ok(exec) => exec,
             Err(e) => {
                 Logger::error_occurred(&format!("Failed to initialize Solana Executor: {}", e));
                 return;
             }
         };
+        
+        // RPC and Jito health checks, tip floor and leader schedule polling and the auth refresh stop
+        // with this attempt; a restart starts them again
+        if let Some(ref rpc_manager) = self.rpc_manager {
+            rpc_manager.start_background_tasks(shutdown.clone());
+        }
+        if let Some(ref jito_optimizer) = self.jito_optimizer {
+            jito_optimizer.start_background_tasks(shutdown.clone());
+        }
+        self.jito_client.start_auth_refresh(shutdown.clone());
+        
+        // Simulated transactions are signed by, and balance snapshots follow, the executor's wallet
+        if let Some(pipeline) = &self.mev_simulation_pipeline {
+            match executor.keypair() {
+                Ok(keypair) => pipeline.set_signer(Arc::new(keypair)),
+                Err(e) => Logger::error_occurred(&format!("Failed to load the wallet keypair for simulations: {}", e)),
+            }
+        }
+        
+        // Continue the previous process's session totals (RESTORE_METRICS_STATE) and keep saving them
+        self.metrics_persistence.restore(&executor.analytics()).await;
+        Arc::clone(&self.metrics_persistence).spawn(executor.analytics(), shutdown.clone());
+        // Risk state changes are written out every RISK_STATE_FLUSH_MS; finish_shutdown writes the last
+        if let Some(ref risk_manager) = self.new_risk_manager {
+            risk_manager.spawn_state_flush(Duration::from_millis(self.config.risk.state_flush_ms.max(1)), shutdown.clone());
+        }
+        
+        // Seed risk controls with the wallet balance before any opportunity is processed, then keep
+        // it current so the minimum balance, drawdown and balance-drop alert see real numbers
+        if let (Some(risk_manager), Some(rpc_manager)) = (&self.new_risk_manager, &self.rpc_manager) {
+            match executor.get_keypair_public_key() {
+                Ok(wallet) => {
+                    let poller = Arc::new(BalancePoller::new(
+                        Arc::new(RpcBalanceSource::new(Arc::clone(rpc_manager), wallet)),
+                        Arc::clone(risk_manager),
+                        self.metrics_collector.clone(),
+                        self.balance_poll_interval,
+                    ));
+                    match poller.initialize().await {
+                        Ok(balance) => Logger::status_update(&format!("Wallet balance: {:.4} SOL", balance)),
+                        Err(e) => Logger::error_occurred(&format!("Failed to read wallet balance for risk controls: {}", e)),
+                    }
+                    poller.spawn(shutdown.clone());
+                }
+                Err(e) => Logger::error_occurred(&format!("Failed to derive the wallet address for balance polling: {}", e)),
+            }
+        }
+        
+        // Kill switch: SIGUSR1/SIGUSR2 toggle it, and a periodic check logs pause changes and keeps the
+        // kill switch, drawdown and circuit breaker gauges current even while no notifications arrive
+        if let Some(ref risk_manager) = self.new_risk_manager {
+            if let Err(e) = risk_manager.kill_switch().listen_for_signals(shutdown.clone()) {
+                Logger::error_occurred(&format!("Kill switch signals unavailable: {}", e));
+            }
+            let risk_manager = Arc::clone(risk_manager);
+            let metrics_collector = self.metrics_collector.clone();
+            let mut shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(Duration::from_secs(1));
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {},
+                        _ = shutdown.changed() => return,
+                    }
+                    risk_manager.check_pause_state().await;
+                    if let Some(ref metrics_collector) = metrics_collector {
+                        metrics_collector.record_kill_switch(risk_manager.kill_switch().is_engaged()).await;
+                        metrics_collector.record_drawdown(risk_manager.drawdown_percent().await, risk_manager.is_drawdown_halted().await).await;
+                        metrics_collector.record_circuit_breaker(risk_manager.circuit_state().await).await;
+                    }
+                }
+            });
+        }
+        
+        // Workers drain the opportunity queue so the most profitable validated opportunity runs first
+        let workers = self.start_execution_workers(shutdown.clone());
+        
+        // Log per-stage latency percentiles every minute
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            metrics_collector.start_latency_summary(std::time::Duration::from_secs(60), shutdown.clone());
+        }
+        
+        // Log a performance summary every PERFORMANCE_SUMMARY_MINS; finish_shutdown logs the last one
+        Arc::clone(&self.performance_summarizer).spawn(shutdown.clone());
+        
+        // Move the false positive filters toward FILTER_TARGET_PRECISION/RECALL as their decisions
+        // resolve, including those restored with the metrics state
+        Arc::clone(&self.false_positive_reducer).spawn_recalibration(shutdown.clone());
+        
+        // Keep the most active pools' reserves current from account subscriptions
+        if let Some(ref evaluator) = self.opportunity_evaluator {
+            evaluator.pool_watcher().start(self.ws_url.clone(), shutdown.clone());
+        }
+        
+        // Sweep expired evaluator cache entries and publish the cache counters, competition, disabled
+        // strategies, blacklisted pools, tip and leader landing rates, RPC failovers and filter rejections
+        if let Some(ref evaluator) = self.opportunity_evaluator {
+            let evaluator = evaluator.clone();
+            let metrics_collector = self.metrics_collector.clone();
+            let false_positive_reducer = Arc::clone(&self.false_positive_reducer);
+            let strategy_executor = self.mev_strategy_executor.clone();
+            let jito_optimizer = self.jito_optimizer.clone();
+            let rpc_manager = self.rpc_manager.clone();
+            let risk_manager = self.new_risk_manager.clone();
+            let simulation_pipeline = self.mev_simulation_pipeline.clone();
+            let mut shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(std::time::Duration::from_secs(30));
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {},
+                        _ = shutdown.changed() => return,
+                    }
+                    evaluator.sweep_caches().await;
+                    if let Some(ref metrics_collector) = metrics_collector {
+                        for (cache, stats) in evaluator.cache_stats().await {
+                            metrics_collector.record_cache_stats(cache, stats).await;
+                        }
+                        if let Some(ref simulation_pipeline) = simulation_pipeline {
+                            if let Some(stats) = simulation_pipeline.cache_stats().await {
+                                metrics_collector.record_cache_stats("simulations", stats).await;
+                            }
+                        }
+                        for age_ms in evaluator.take_pool_state_ages().await {
+                            metrics_collector.record_pool_state_age(age_ms).await;
+                        }
+                    }
+                    // Also where expired blacklist entries are dropped
+                    if let Some(ref risk_manager) = risk_manager {
+                        let blacklisted_pools = risk_manager.pool_blacklist().entries().await;
+                        if let Some(ref metrics_collector) = metrics_collector {
+                            metrics_collector.record_blacklisted_pools(blacklisted_pools).await;
+                        }
+                    }
+                    if let (Some(metrics_collector), Some(strategy_executor)) = (&metrics_collector, &strategy_executor) {
+                        metrics_collector.record_competition(strategy_executor.competition_tracker().snapshot().await).await;
+                        metrics_collector.record_disabled_strategies(strategy_executor.disabled_strategies().await).await;
+                    }
+                    if let (Some(metrics_collector), Some(jito_optimizer)) = (&metrics_collector, &jito_optimizer) {
+                        metrics_collector.record_tip_bucket_rates(jito_optimizer.tip_bucket_rates().await).await;
+                        metrics_collector.record_leader_landing_rates(jito_optimizer.leader_landing_rates().await).await;
+                    }
+                    if let (Some(metrics_collector), Some(rpc_manager)) = (&metrics_collector, &rpc_manager) {
+                        metrics_collector.record_rpc_failovers(rpc_manager.failover_count()).await;
+                        metrics_collector.record_rpc_rate_limits(rpc_manager.rate_limited_count(), rpc_manager.shed_count(), rpc_manager.limited_locally_count()).await;
+                        metrics_collector.record_rpc_rate_budgets(rpc_manager.rate_budgets()).await;
+                        metrics_collector.record_rpc_breakers(rpc_manager.breaker_statuses().await, rpc_manager.breaker_open_count()).await;
+                    }
+                    for (opportunity_type, rejection) in evaluator.take_filter_rejections().await {
+                        false_positive_reducer.record_filter_rejection(&opportunity_type, rejection.kind()).await;
+                        if let Some(ref metrics_collector) = metrics_collector {
+                            metrics_collector.record_filter_rejection(&format!("{:?}", opportunity_type), rejection.kind()).await;
+                        }
+                    }
+                }
+            });
+        }
 
-        // Keep trying to connect to WebSocket with reconnection logic
+        // Book the real landing outcome of bundles that were only known to be accepted
+        if let Some(ref strategy_executor) = self.mev_strategy_executor {
+            let strategy_executor = Arc::clone(strategy_executor);
+            let metrics_collector = self.metrics_collector.clone();
+            let risk_manager = self.new_risk_manager.clone();
+            let executor = executor.clone();
+            let mut shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(strategy_executor.bundle_poll_interval());
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {},
+                        _ = shutdown.changed() => return,
+                    }
+                    for bundle in strategy_executor.poll_bundle_outcomes().await {
+                        Logger::status_update(&format!("Bundle {} resolved as {:?}", bundle.bundle_id, bundle.state));
+                        if let Some(ref risk_manager) = risk_manager {
+                            record_bundle_risk(risk_manager, &bundle).await;
+                        }
+                        if let Some(ref metrics_collector) = metrics_collector {
+                            metrics_collector.record_bundle_outcome(&bundle).await;
+                        }
+                        executor.record_bundle_outcome(&bundle).await;
+                    }
+                    if let Some(ref metrics_collector) = metrics_collector {
+                        metrics_collector.record_bundles_throttled(strategy_executor.throttled_bundles()).await;
+                    }
+                }
+            });
+        }
+
+        // Triangular routes come from pool state alone, so they're searched independently of
+        // the mempool and fed into the same queue
+        self.start_triangular_search(shutdown.clone());
+        
+        #[cfg(feature = "liquidation")]
+        if let Err(e) = self.start_liquidation_scan(shutdown.clone()) {
+            Logger::error_occurred(&format!("Liquidation scanner not started: {}", e));
+        }
+
+        // One connection per endpoint; each reconnects on its own so the others keep the
+        // bot fed while it's down, and the dedup cache lets the fastest delivery win
+        let endpoints: Vec<JoinHandle<()>> = self.ws_urls.iter()
+            .map(|ws_url| {
+                let mempool_clone = self.clone();
+                let ws_url = ws_url.clone();
+                let shutdown = shutdown.clone();
+                tokio::spawn(async move {
+                    mempool_clone.run_ws_endpoint(ws_url, shutdown).await;
+                })
+            })
+            .collect();
+        
+        futures::future::join_all(endpoints).await;
+        Logger::status_update("WebSocket monitoring stopped");
+        
+        self.finish_shutdown(&executor, workers).await;
+    }
+    
+    fn start_triangular_search(&self, mut shutdown: watch::Receiver<bool>) {
+        let (Some(interval), Some(evaluator)) = (self.triangular_interval, self.opportunity_evaluator.clone()) else {
+            return;
+        };
+        if !self.enabled_strategies.contains(&MevStrategyType::Arbitrage) {
+            return;
+        }
+        
+        let mempool = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if *shutdown.borrow() {
+                    return;
+                }
+                
+                let started = Instant::now();
+                let opportunities = match evaluator.find_triangular_opportunities().await {
+                    Ok(opportunities) => opportunities,
+                    Err(e) => {
+                        Logger::error_occurred(&format!("Triangular route search failed: {}", e));
+                        continue;
+                    }
+                };
+                for arbitrage in opportunities {
+                    let route = arbitrage.route.iter()
+                        .map(|step| step.pool_address.as_str())
+                        .collect::<Vec<_>>()
+                        .join(">");
+                    Logger::status_update(&format!(
+                        "Triangular route {} estimated at {:.6} SOL", route, arbitrage.estimated_profit
+                    ));
+                    let opportunity = arbitrage.to_opportunity_details();
+                    let timing = OpportunityTiming::new(started);
+                    mempool.record_stage(&timing, &opportunity, OpportunityStage::Queued).await;
+                    mempool.enqueue(
+                        format!("triangular:{}", route),
+                        opportunity,
+                        Value::Null, // No target transaction
+                        timing,
+                    ).await;
+                }
+            }
+        });
+    }
+    
+    // Periodically re-prices Solend obligations near their liquidation threshold and queues the
+    // ones that can be liquidated at a profit
+    #[cfg(feature = "liquidation")]
+    fn start_liquidation_scan(&self, mut shutdown: watch::Receiver<bool>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use crate::utils::solend::SolendLiquidator;
+        
+        let (Some(rpc_manager), Some(evaluator)) = (self.rpc_manager.clone(), self.opportunity_evaluator.clone()) else {
+            return Ok(());
+        };
+        if !self.enabled_strategies.contains(&MevStrategyType::Liquidation) {
+            return Ok(());
+        }
+        
+        let interval_ms = self.config.strategies.liquidation_scan_interval_ms;
+        let liquidator = SolendLiquidator::from_config(rpc_manager, evaluator, &self.config.liquidation);
+        Logger::status_update(&format!(
+            "Scanning Solend market {} for liquidations every {}ms (max {:.3} SOL per liquidation)",
+            liquidator.config().lending_market, interval_ms, liquidator.config().max_capital_sol
+        ));
+        
+        let mempool = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms.max(1)));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if *shutdown.borrow() {
+                    return;
+                }
+                
+                let started = Instant::now();
+                let opportunities = match liquidator.scan().await {
+                    Ok(opportunities) => opportunities,
+                    Err(e) => {
+                        Logger::error_occurred(&format!("Liquidation scan failed: {}", e));
+                        continue;
+                    }
+                };
+                for opportunity in opportunities {
+                    let obligation = opportunity.pool_address.clone().unwrap_or_default();
+                    Logger::status_update(&format!(
+                        "Liquidatable obligation {} estimated at {:.6} SOL", obligation, opportunity.estimated_profit
+                    ));
+                    let timing = OpportunityTiming::new(started);
+                    mempool.record_stage(&timing, &opportunity, OpportunityStage::Queued).await;
+                    mempool.enqueue(
+                        format!("liquidation:{}", obligation),
+                        opportunity,
+                        Value::Null, // No target transaction
+                        timing,
+                    ).await;
+                }
+            }
+        });
+        Ok(())
+    }
+    
+    // Keeps a single endpoint connected, backing off exponentially between failed attempts
+    async fn run_ws_endpoint(&self, ws_url: String, mut shutdown: watch::Receiver<bool>) {
+        let label = ws_endpoint_label(&ws_url);
+        let mut backoff = Duration::from_secs(1);
+        let max_backoff = Duration::from_secs(30);
+        
         loop {
-            Logger::status_update("Attempting to connect to WebSocket...");
-            // Convert executor to Arc for safe sharing across tasks
-            let executor_arc = Arc::new(executor.clone());
-            match self.connect_ws_with_reconnect(executor_arc.clone()).await {
-                Ok(_) => {
-                    Logger::status_update("WebSocket connection was successful");
-                    // If connect_ws_with_reconnect returns normally, it means it was intentionally stopped
-                    break;
-                },
+            if *shutdown.borrow() {
+                return;
+            }
+            
+            Logger::status_update(&format!("Attempting to connect to WebSocket {}...", label));
+            let connected_at = Instant::now();
+            match self.connect_ws_with_reconnect(&ws_url, shutdown.clone()).await {
+                // connect_ws_with_reconnect only returns normally when shutdown was requested
+                Ok(_) => return,
                 Err(e) => {
-                    Logger::error_occurred(&format!("WebSocket connection failed: {}, falling back to slot monitoring: {}", e, self.ws_url));
-                    // If WebSocket connection fails, fall back to slot monitoring
-                    // This will automatically try to reconnect to WebSocket when it encounters too many errors
-                    self.start_slot_monitoring(&executor).await;
+                    // A connection that stayed up for a while resets the backoff
+                    if connected_at.elapsed() > max_backoff {
+                        backoff = Duration::from_secs(1);
+                    }
+                    Logger::error_occurred(&format!(
+                        "WebSocket {} failed: {}, reconnecting in {}s", label, e, backoff.as_secs()
+                    ));
                 }
             }
+            
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {},
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+            }
+            backoff = (backoff * 2).min(max_backoff);
+        }
+    }
+    
+    async fn finish_shutdown(&self, executor: &SolanaExecutor, workers: Vec<JoinHandle<()>>) {
+        Logger::status_update(&format!(
+            "Shutdown requested, waiting up to {}ms for in-flight executions", 
+            self.shutdown_timeout.as_millis()
+        ));
+        
+        if tokio::time::timeout(self.shutdown_timeout, futures::future::join_all(workers)).await.is_err() {
+            Logger::error_occurred("In-flight executions did not finish before the shutdown timeout");
+        }
+        
+        self.performance_summarizer.log_summary().await;
+        
+        match self.metrics_persistence.flush(&executor.analytics()).await {
+            Ok(_) => Logger::status_update("Metrics state flushed"),
+            Err(e) => Logger::error_occurred(&format!("Failed to flush metrics state: {}", e)),
+        }
+        
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            let metrics_file = &self.config.metrics.metrics_file;
+            match metrics_collector.export_to_storage(metrics_file).await {
+                Ok(_) => Logger::status_update(&format!("Metrics flushed to {}", metrics_file)),
+                Err(e) => Logger::error_occurred(&format!("Failed to flush metrics: {}", e)),
+            }
         }
+        
+        let analytics_file = &self.config.metrics.analytics_file;
+        match executor.flush_analytics(analytics_file).await {
+            Ok(_) => Logger::status_update(&format!("Analytics flushed to {}", analytics_file)),
+            Err(e) => Logger::error_occurred(&format!("Failed to flush analytics: {}", e)),
+        }
+        
+        if let Some(ref risk_manager) = self.new_risk_manager {
+            match risk_manager.flush_state().await {
+                Ok(_) => Logger::status_update("Risk state flushed"),
+                Err(e) => Logger::error_occurred(&format!("Failed to flush risk state: {}", e)),
+            }
+            match risk_manager.pool_blacklist().flush().await {
+                Ok(_) => Logger::status_update("Pool blacklist flushed"),
+                Err(e) => Logger::error_occurred(&format!("Failed to flush pool blacklist: {}", e)),
+            }
+        }
+        
+        if let Some(ref jito_optimizer) = self.jito_optimizer {
+            match jito_optimizer.flush_tip_history().await {
+                Ok(_) => Logger::status_update("Tip history flushed"),
+                Err(e) => Logger::error_occurred(&format!("Failed to flush tip history: {}", e)),
+            }
+        }
+        
+        Logger::status_update("Solana mempool stopped");
     }
     
-    async fn connect_ws_with_reconnect(&self, executor: Arc<SolanaExecutor>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let (ws_stream, _) = connect_async(&self.ws_url).await
+    async fn connect_ws_with_reconnect(&self, ws_url: &str, mut shutdown: watch::Receiver<bool>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let label = ws_endpoint_label(ws_url);
+        let (ws_stream, _) = connect_async(ws_url).await
             .map_err(|e| format!("WebSocket connection failed: {}", e))?;
         
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -172,27 +783,88 @@ impl SolanaMempool {
         ws_sender.send(Message::Text(subscription_request.to_string())).await
             .map_err(|e| format!("Failed to send subscription: {}", e))?;
         
-        Logger::status_update("Subscribed to Solana transaction logs");
+        Logger::status_update(&format!("Subscription request sent to {}", label));
+        
+        // Set once this connection's subscribe request is confirmed; every endpoint answers
+        // with its own id, so confirmations are tracked per connection
+        let mut subscription_id: Option<u64> = None;
+        // Holds this endpoint in the health check's live set until the connection ends
+        let mut _live_subscription = None;
         
         // Process incoming messages with concurrent handling
         loop {
-            match ws_receiver.next().await {
+            if *shutdown.borrow() {
+                let _ = ws_sender.close().await;
+                return Ok(());
+            }
+            
+            let next_message = tokio::select! {
+                message = ws_receiver.next() => message,
+                changed = shutdown.changed() => {
+                    // A dropped sender means the owner is gone, which is treated as shutdown
+                    if changed.is_err() {
+                        let _ = ws_sender.close().await;
+                        return Ok(());
+                    }
+                    continue; // Re-checked at the top of the loop
+                }
+            };
+            
+            match next_message {
                 Some(Ok(msg)) => {
                     if let Message::Text(text) = msg {
                         if let Ok(value) = serde_json::from_str::<Value>(&text) {
+                            // Subscribe confirmation: {"jsonrpc":"2.0","result":<id>,"id":1}
+                            if value["id"].as_u64() == Some(1) {
+                                if let Some(id) = value["result"].as_u64() {
+                                    if subscription_id.is_none() {
+                                        Logger::status_update(&format!("Subscribed to Solana transaction logs on {} (subscription {})", label, id));
+                                        _live_subscription = Some(self.stream_liveness.subscribed(&label));
+                                    }
+                                    subscription_id = Some(id);
+                                } else if let Some(error) = value.get("error") {
+                                    return Err(format!("Subscription rejected by {}: {}", label, error).into());
+                                }
+                                continue;
+                            }
                             if let Some(method) = value["method"].as_str() {
                                 if method == "logsNotification" {
                                     if let Some(params) = value["params"].as_object() {
+                                        // Ignore notifications for any subscription other than ours
+                                        if let (Some(ours), Some(theirs)) = (subscription_id, params.get("subscription").and_then(|s| s.as_u64())) {
+                                            if ours != theirs {
+                                                continue;
+                                            }
+                                        }
+                                        self.stream_liveness.record_message();
                                         if let Some(result) = params["result"].as_object() {
+                                            let logs = result["value"]["logs"].as_array().cloned().unwrap_or_default();
+                                            if !self.dex_registry.mentions_known_program(&logs) {
+                                                continue;
+                                            }
                                             if let Some(signature) = result["value"]["signature"].as_str() {
-                                                Logger::status_update(&format!("Transaction detected: {}", signature));
+                                                // Only the first endpoint to deliver a signature triggers analysis
+                                                match self.signature_dedup.observe(signature, &label).await {
+                                                    Delivery::First => {
+                                                        if let Some(ref metrics_collector) = self.metrics_collector {
+                                                            metrics_collector.record_ws_first_delivery(&label).await;
+                                                        }
+                                                    }
+                                                    Delivery::Duplicate { behind, .. } => {
+                                                        if let Some(ref metrics_collector) = self.metrics_collector {
+                                                            metrics_collector.record_ws_late_delivery(&label, behind.as_millis() as u64).await;
+                                                        }
+                                                        continue;
+                                                    }
+                                                }
+                                                
+                                                Logger::status_update(&format!("Transaction detected via {}: {}", label, signature));
+                                                let notification = PendingNotification::new(signature);
                                                 // Spawn a new task for each transaction to process concurrently
-                                                let executor_clone = executor.clone();
                                                 let mempool_clone = self.clone();
-                                                let sig = signature.to_string();
                                                 
                                                 tokio::spawn(async move {
-                                                    mempool_clone.analyze_and_execute_opportunity(&executor_clone, &sig).await;
+                                                    mempool_clone.analyze_and_execute_opportunity(notification).await;
                                                 });
                                             }
                                         }
@@ -203,78 +875,28 @@ impl SolanaMempool {
                     }
                 }
                 Some(Err(e)) => {
-                    Logger::error_occurred(&format!("WebSocket error: {}", e));
+                    Logger::error_occurred(&format!("WebSocket error on {}: {}", label, e));
                     return Err(Box::new(e));
                 }
                 None => {
-                    Logger::error_occurred("WebSocket stream ended unexpectedly");
+                    Logger::error_occurred(&format!("WebSocket stream from {} ended unexpectedly", label));
                     return Err("WebSocket stream ended".into());
                 }
             }
         }
     }
 
-    async fn connect_ws(&self, executor: &SolanaExecutor) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let (ws_stream, _) = connect_async(&self.ws_url).await
-            .map_err(|e| format!("WebSocket connection failed: {}", e))?;
+    pub(crate) async fn analyze_and_execute_opportunity(&self, notification: PendingNotification) {
+        let signature = notification.signature.as_str();
         
-        let (mut ws_sender, ws_receiver) = ws_stream.split();
-        
-        // Subscribe to all transactions (this is a simplified approach)
-        let subscription_request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "logsSubscribe",
-            "params": [
-                "all",
-                {
-                    "commitment": "processed"
-                }
-            ]
-        });
-        
-        ws_sender.send(Message::Text(subscription_request.to_string())).await
-            .map_err(|e| format!("Failed to send subscription: {}", e))?;
-        
-        Logger::status_update("Subscribed to Solana transaction logs");
-        
-        // Process incoming messages
-        let mut ws_receiver = ws_receiver;
-        loop {
-            match ws_receiver.next().await {
-                Some(Ok(msg)) => {
-                    if let Message::Text(text) = msg {
-                        if let Ok(value) = serde_json::from_str::<Value>(&text) {
-                            if let Some(method) = value["method"].as_str() {
-                                if method == "logsNotification" {
-                                    if let Some(params) = value["params"].as_object() {
-                                        if let Some(result) = params["result"].as_object() {
-                                            if let Some(signature) = result["value"]["signature"].as_str() {
-                                                Logger::status_update(&format!("Transaction detected: {}", signature));
-                                                self.analyze_and_execute_opportunity(executor, signature).await;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                Some(Err(e)) => {
-                    Logger::error_occurred(&format!("WebSocket error: {}", e));
-                    break;
-                }
-                None => {
-                    Logger::error_occurred("WebSocket stream ended unexpectedly");
-                    break;
-                }
+        // Bail out before fetching anything if risk controls have paused trading
+        if !risk_allows_analysis(self.new_risk_manager.as_deref()).await {
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_risk_paused().await;
             }
+            return;
         }
-
-        Ok(())
-    }
-
-    async fn analyze_and_execute_opportunity(&self, executor: &SolanaExecutor, signature: &str) {
+        
         // NEW ARCHITECTURE: Use the new opportunity evaluator to analyze transaction
         // Check if new architecture is properly initialized
         if self.rpc_manager.is_none() {
@@ -282,12 +904,57 @@ impl SolanaMempool {
             return;
         }
         
+        // Held through fetch, evaluation and simulation; released before the opportunity is enqueued
+        let permit = match self.analysis_limiter.acquire().await {
+            Some(permit) => permit,
+            None => {
+                Logger::status_update(&format!(
+                    "Skipping {}: {} analyses already in flight", 
+                    signature, self.analysis_limiter.max_concurrent
+                ));
+                if let Some(ref metrics_collector) = self.metrics_collector {
+                    metrics_collector.record_analysis_skipped().await;
+                }
+                return;
+            }
+        };
+        
+        // Checked after waiting for a permit and before any RPC work, since both reconnect
+        // storms and a saturated limiter can leave a notification too old to act on
+        let notification_age = notification.age();
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            metrics_collector.record_notification_age(notification_age.as_millis() as u64).await;
+        }
+        if notification.is_stale(self.max_notification_age) {
+            Logger::status_update(&format!(
+                "Dropping stale notification {} ({}ms old, max {}ms)", 
+                signature, notification_age.as_millis(), self.max_notification_age.as_millis()
+            ));
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_stale_notification_dropped().await;
+            }
+            return;
+        }
+        
         Logger::opportunity_detected("Solana", signature);
         
+        let mut timing = OpportunityTiming::new(notification.received_at);
+        
         // Fetch target transaction details with timeout
         let target_tx_details_result = self.fetch_transaction_details_with_timeout(signature, 1000).await; // 1000ms timeout
+        timing.mark_fetched();
         let target_tx_details = target_tx_details_result.as_ref().ok();
         
+        // A throttled provider would only be hit harder by retries; drop the notification quietly
+        if let Err(ref e) = target_tx_details_result {
+            if rpc_error::is_rate_limited(&**e) {
+                if let Some(ref metrics_collector) = self.metrics_collector {
+                    metrics_collector.record_notification_rate_limited().await;
+                }
+                return;
+            }
+        }
+        
         if target_tx_details.is_none() {
             Logger::status_update(&format!("Could not fetch target transaction details for: {}", signature));
             return;
@@ -297,23 +964,84 @@ impl SolanaMempool {
         
         // NEW ARCHITECTURE: Evaluate the opportunity using the new evaluator
         if let Some(ref evaluator) = self.opportunity_evaluator {
-            if let Some(opportunity) = evaluator.evaluate_opportunity(target_tx_details).await.ok().flatten() {
+            let evaluation = evaluator.evaluate_opportunity(target_tx_details).await.ok().flatten();
+            timing.mark_evaluated();
+            if let Some(opportunity) = evaluation {
+                self.record_stage(&timing, &opportunity, OpportunityStage::Detected).await;
+                if !strategy_enabled(&self.enabled_strategies, &opportunity) {
+                    Logger::status_update(&format!(
+                        "Skipping {:?} opportunity for {}: strategy not enabled",
+                        opportunity.opportunity_type, signature
+                    ));
+                    if let Some(ref metrics_collector) = self.metrics_collector {
+                        metrics_collector.record_opportunity_strategy_disabled().await;
+                    }
+                    return;
+                }
+                
+                // Snipes target pools with no trading history, so the swap-impact simulation and
+                // false positive heuristics don't apply; only the token safety policy and the launch
+                // filters are checked here
+                if !self.within_token_exposure(&opportunity).await {
+                    return;
+                }
+                
+                if matches!(opportunity.opportunity_type, crate::utils::enhanced_transaction_simulator::OpportunityType::Snipe) {
+                    if let Some(reason) = self.false_positive_reducer.check_token_safety(&opportunity).await {
+                        Logger::status_update(&format!("Snipe filtered out: {}", reason));
+                        self.record_stage(&timing, &opportunity, OpportunityStage::Filtered(reason)).await;
+                        return;
+                    }
+                    let launch = match opportunity.pool_address {
+                        Some(ref pool) => evaluator.pool_launch(pool).await,
+                        None => None,
+                    };
+                    if let Some(launch) = launch {
+                        if let Some(rejection) = self.false_positive_reducer.check_launch(&launch).await {
+                            Logger::status_update(&format!("Snipe filtered out: pool {} {}", launch.pool_address, rejection));
+                            if let Some(ref metrics_collector) = self.metrics_collector {
+                                metrics_collector.record_filter_rejection("Snipe", rejection.kind()).await;
+                            }
+                            self.record_stage(&timing, &opportunity, OpportunityStage::Filtered(rejection.to_string())).await;
+                            return;
+                        }
+                    }
+                    timing.mark_simulated();
+                    self.record_stage(&timing, &opportunity, OpportunityStage::Queued).await;
+                    drop(permit);
+                    self.enqueue(
+                        signature.to_string(),
+                        opportunity,
+                        target_tx_details.clone(),
+                        timing,
+                    ).await;
+                    return;
+                }
+                
                 // NEW ARCHITECTURE: Run enhanced simulation to validate opportunity
                 if let Some(ref simulator) = self.enhanced_simulator {
-                    let simulation_result = match simulator.simulate_and_validate(&opportunity).await {
+                    let target = TargetTransaction::from_details(target_tx_details);
+                    let encoded_target = target.as_ref().map(|target| target.to_base64());
+                    let simulation_result = match simulator.simulate_and_validate(&opportunity, encoded_target.as_deref()).await {
                         Ok(result) => result,
                         Err(e) => {
                             Logger::error_occurred(&format!("Failed to simulate opportunity: {}", e));
                             return;
                         }
                     };
+                    timing.mark_simulated();
                     
                     // NEW ARCHITECTURE: Apply false positive reduction
                     let filtering_result = self.false_positive_reducer.evaluate_opportunity(&opportunity, &simulation_result.simulation_results).await;
+                    if let Some(ref metrics_collector) = self.metrics_collector {
+                        metrics_collector.record_filter_decision(timing.correlation_id, &opportunity, &filtering_result).await;
+                    }
                     
                     if !filtering_result.should_execute {
-                        Logger::status_update(&format!("Opportunity filtered out by false positive reducer: {}", 
-                                                     filtering_result.filtered_reason.unwrap_or("Unknown reason".to_string())));
+                        let reason = filtering_result.filtered_reason.unwrap_or("Unknown reason".to_string());
+                        Logger::status_update(&format!("Opportunity filtered out by false positive reducer: {}", reason));
+                        self.record_stage(&timing, &opportunity, OpportunityStage::Filtered(reason)).await;
+                        self.label_rejection(timing.correlation_id, &opportunity, simulation_result.is_profitable, target).await;
                         return;
                     }
                     
@@ -333,39 +1061,301 @@ impl SolanaMempool {
                         avg_confidence * 100.0
                     ));
                     
-                    // NEW ARCHITECTURE: Execute the appropriate strategy based on opportunity type
-                    if let Some(ref strategy_executor) = self.mev_strategy_executor {
-                        let strategy_result = match strategy_executor.execute_strategy(&opportunity, Some(target_tx_details)).await {
-                            Ok(result) => result,
-                            Err(e) => {
-                                Logger::error_occurred(&format!("Strategy execution failed: {}", e));
-                                return;
+                    // Enqueue instead of executing directly; workers pick the best non-expired entry
+                    self.record_stage(&timing, &opportunity, OpportunityStage::Queued).await;
+                    drop(permit);
+                    self.enqueue(
+                        signature.to_string(),
+                        opportunity,
+                        target_tx_details.clone(),
+                        timing,
+                    ).await;
+                }
+            } else {
+                Logger::status_update(&format!("No profitable opportunity detected for transaction: {}", signature));
+            }
+        }
+    }
+    
+    // Queues an opportunity for the workers; a full queue first purges what expired
+    async fn enqueue(&self, signature: String, opportunity: OpportunityDetails, target_tx_details: Value, timing: OpportunityTiming) {
+        let outcome = self.opportunity_queue.push(signature, opportunity, target_tx_details, timing).await;
+        if outcome.expired > 0 {
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_opportunities_expired(outcome.expired).await;
+            }
+        }
+        if let Some(dropped) = outcome.dropped {
+            Logger::status_update(&format!(
+                "Opportunity queue full ({}), dropped {} estimated at {:.6} SOL", self.opportunity_queue.capacity, dropped.signature, dropped.estimated_profit
+            ));
+        }
+    }
+    
+    // A rejection never goes out, so its filter decision is labeled by the simulation it was judged
+    // on and, with FILTER_SHADOW_REJECTIONS, by simulating its bundle as if it were being submitted
+    async fn label_rejection(&self, correlation_id: u64, opportunity: &OpportunityDetails, simulated_profitable: bool, target: Option<TargetTransaction>) {
+        let Some(ref metrics_collector) = self.metrics_collector else { return };
+        metrics_collector.record_rejection_outcome(correlation_id, simulated_profitable, OutcomeSource::Simulated).await;
+        if !self.config.filters.shadow_rejections {
+            return;
+        }
+        let Some(pipeline) = self.mev_simulation_pipeline.clone() else { return };
+        let metrics_collector = Arc::clone(metrics_collector);
+        let opportunity = opportunity.clone();
+        tokio::spawn(async move {
+            match pipeline.run_bundle_simulation(&opportunity, target.as_ref()).await {
+                Ok(simulation) => metrics_collector.record_rejection_outcome(correlation_id, simulation.is_profitable, OutcomeSource::Shadow).await,
+                Err(e) => Logger::status_update(&format!("Shadow simulation of a rejected opportunity failed: {}", e)),
+            }
+        });
+    }
+    
+    // Followed through to execution and bundle reconciliation for the false positive rate
+    async fn record_stage(&self, timing: &OpportunityTiming, opportunity: &OpportunityDetails, stage: OpportunityStage) {
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            metrics_collector.record_opportunity_stage(timing.correlation_id, opportunity, stage).await;
+        }
+    }
+    
+    // Cap how much SOL is committed to any single mint, whatever the strategy; the executor checks
+    // again at dequeue, when executions in flight hold their share
+    async fn within_token_exposure(&self, opportunity: &OpportunityDetails) -> bool {
+        if let Some(ref risk_manager) = self.new_risk_manager {
+            let amount_sol = match self.trade_size_in_sol(opportunity).await {
+                Ok(amount_sol) => amount_sol,
+                Err(e) => {
+                    Logger::status_update(&format!("Skipping {} opportunity: can't value its trade size ({})", opportunity.dex, e));
+                    return false;
+                }
+            };
+            let tokens = [opportunity.token_a.as_str(), opportunity.token_b.as_str()];
+            if let Err(e) = risk_manager.check_tokens_exposure(&tokens, amount_sol).await {
+                Logger::status_update(&format!("Skipping {} opportunity: {}", opportunity.dex, e));
+                if let (RiskError::TokenExposureExceeded(mint), Some(metrics_collector)) = (&e, &self.metrics_collector) {
+                    metrics_collector.record_token_exposure_rejection(mint).await;
+                }
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    // trade_size is a raw amount of token_a; what it's worth in SOL is what the exposure cap counts
+    async fn trade_size_in_sol(&self, opportunity: &OpportunityDetails) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        match &self.opportunity_evaluator {
+            Some(evaluator) => evaluator.value_in_sol(&opportunity.token_a, opportunity.trade_size as i128).await,
+            None if opportunity.token_a == WSOL_MINT => Ok(opportunity.trade_size as f64 / 1_000_000_000.0),
+            None => Err(format!("no price source for {}", opportunity.token_a).into()),
+        }
+    }
+    
+    fn start_execution_workers(&self, shutdown: watch::Receiver<bool>) -> Vec<JoinHandle<()>> {
+        let mut workers = Vec::with_capacity(self.execution_workers);
+        for _ in 0..self.execution_workers {
+            let mempool_clone = self.clone();
+            let mut shutdown = shutdown.clone();
+            workers.push(tokio::spawn(async move {
+                loop {
+                    if *shutdown.borrow() {
+                        break;
+                    }
+                    
+                    // pop() only removes an entry in the poll that returns it, so losing the race is safe
+                    let (queued, expired) = tokio::select! {
+                        popped = mempool_clone.opportunity_queue.pop() => popped,
+                        changed = shutdown.changed() => {
+                            if changed.is_err() {
+                                break;
                             }
-                        };
-                        
-                        // NEW ARCHITECTURE: Record the execution result
-                        if let Some(ref metrics_collector) = self.metrics_collector {
-                            metrics_collector.record_strategy_execution(&strategy_result).await;
+                            continue;
                         }
-                        
-                        if strategy_result.success {
-                            Logger::bundle_sent("Solana", true);
-                            Logger::status_update(&format!(
-                                "Strategy executed successfully: type {:?}, net profit: {:.6} SOL", 
-                                strategy_result.strategy_type, 
-                                strategy_result.profit
-                            ));
-                        } else {
-                            Logger::status_update(&format!(
-                                "Strategy execution failed: type {:?}, loss: {:.6} SOL", 
-                                strategy_result.strategy_type, 
-                                strategy_result.profit
-                            ));
+                    };
+                    if expired > 0 {
+                        Logger::status_update(&format!("Dropped {} expired opportunities from queue", expired));
+                        if let Some(ref metrics_collector) = mempool_clone.metrics_collector {
+                            metrics_collector.record_opportunities_expired(expired).await;
                         }
                     }
+                    
+                    // Don't start new submissions once shutdown was requested
+                    if *shutdown.borrow() {
+                        Logger::status_update(&format!("Shutdown requested, discarding queued opportunity {}", queued.signature));
+                        break;
+                    }
+                    mempool_clone.execute_queued_opportunity(queued).await;
                 }
+            }));
+        }
+        
+        Logger::status_update(&format!(
+            "Started {} opportunity execution workers (TTL: {}ms)", 
+            self.execution_workers, 
+            self.opportunity_queue.ttl.as_millis()
+        ));
+        workers
+    }
+    
+    pub(crate) async fn execute_queued_opportunity(&self, queued: QueuedOpportunity) {
+        // Opportunities queued before a pause (e.g. the kill switch) are dropped, not submitted
+        if !risk_allows_analysis(self.new_risk_manager.as_deref()).await {
+            Logger::status_update(&format!("Risk controls paused trading, discarding queued opportunity {}", queued.signature));
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_risk_paused().await;
+            }
+            return;
+        }
+        
+        // The route search and the liquidation scan queue opportunities too; whatever queued
+        // it, only an enabled strategy gets executed
+        if !strategy_enabled(&self.enabled_strategies, &queued.opportunity) {
+            Logger::status_update(&format!(
+                "Discarding queued {:?} opportunity for {}: strategy not enabled",
+                queued.opportunity.opportunity_type, queued.signature
+            ));
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_opportunity_strategy_disabled().await;
+            }
+            return;
+        }
+        
+        let mut timing = queued.timing.clone();
+        timing.mark_dequeued();
+        
+        // NEW ARCHITECTURE: Execute the appropriate strategy based on opportunity type
+        if let Some(ref strategy_executor) = self.mev_strategy_executor {
+            let strategy_type = MevStrategyType::for_opportunity(&queued.opportunity.opportunity_type);
+            if strategy_executor.is_strategy_disabled(&strategy_type).await {
+                Logger::status_update(&format!(
+                    "Skipping {:?} opportunity for {}: strategy disabled after repeated failures",
+                    strategy_type, queued.signature
+                ));
+                if let Some(ref metrics_collector) = self.metrics_collector {
+                    metrics_collector.record_opportunity_strategy_disabled().await;
+                }
+                return;
+            }
+            
+            // Spending booked by either execution path counts against the one daily budget
+            let tokens = [queued.opportunity.token_a.as_str(), queued.opportunity.token_b.as_str()];
+            let amount_sol = match self.trade_size_in_sol(&queued.opportunity).await {
+                Ok(amount_sol) => amount_sol,
+                Err(e) => {
+                    Logger::status_update(&format!("Skipping {:?} opportunity for {}: can't value its trade size ({})", strategy_type, queued.signature, e));
+                    return;
+                }
+            };
+            if let Some(ref risk_manager) = self.new_risk_manager {
+                // Held while the bundle is in flight so concurrent workers see it; checked and booked
+                // in one step so two workers can't both fit under the cap
+                let allowed = match risk_manager.should_allow_strategy(&strategy_type, 0.0).await {
+                    Ok(()) => risk_manager.try_reserve_exposure(&tokens, amount_sol).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = allowed {
+                    Logger::status_update(&format!("Skipping {:?} opportunity for {}: {}", strategy_type, queued.signature, e));
+                    if let Some(ref metrics_collector) = self.metrics_collector {
+                        // The opportunity was still evaluated; only its execution is held back
+                        match e {
+                            RiskError::MaxConsecutiveFailures => metrics_collector.record_circuit_breaker_skip().await,
+                            RiskError::TokenExposureExceeded(ref mint) => metrics_collector.record_token_exposure_rejection(mint).await,
+                            _ => metrics_collector.record_risk_paused().await,
+                        }
+                    }
+                    return;
+                }
+            }
+            
+            let execution = strategy_executor.execute_strategy(&queued.opportunity, Some(&queued.target_tx_details), &mut timing).await;
+            
+            // Frontruns, backruns, sandwiches and arbitrage buy and sell in the same bundle, so even a
+            // landed one leaves nothing in the token; only a landed snipe keeps its position open
+            let holds_position = strategy_type == MevStrategyType::Snipe
+                && matches!(execution, Ok(ref result) if result.success);
+            if !holds_position {
+                if let Some(ref risk_manager) = self.new_risk_manager {
+                    risk_manager.release_tokens_exposure(&tokens, amount_sol).await;
+                }
+            }
+            
+            // Record stage latencies even when execution fails so slow stages stay visible
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_opportunity_timing(&timing).await;
+            }
+            
+            let strategy_result = match execution {
+                Ok(result) => result,
+                // The provider throttled a read the strategy needed; that's no mark against the strategy
+                Err(e) if rpc_error::is_rate_limited(&*e) => {
+                    Logger::status_update(&format!("Skipping {}: RPC rate limited ({})", queued.signature, e));
+                    if let Some(ref metrics_collector) = self.metrics_collector {
+                        metrics_collector.record_filter_rejection(&format!("{:?}", queued.opportunity.opportunity_type), "rpc_rate_limited").await;
+                    }
+                    return;
+                }
+                Err(e) => {
+                    Logger::error_occurred(&format!("Strategy execution failed for {}: {}", queued.signature, e));
+                    if let Some(ref risk_manager) = self.new_risk_manager {
+                        risk_manager.record_strategy_failure(&strategy_type).await;
+                    }
+                    return;
+                }
+            };
+            
+            // Another execution already holds the pool or target, or Jito throttled the bundle; nothing was sent
+            if let Some(reason) = strategy_result.skip_reason {
+                if let Some(ref metrics_collector) = self.metrics_collector {
+                    metrics_collector.record_filter_rejection(&format!("{:?}", queued.opportunity.opportunity_type), reason).await;
+                }
+                return;
+            }
+            
+            // Keep the risk manager's per-strategy streak in step with the executor's; a pending
+            // bundle is booked once it resolves, through record_bundle_risk
+            if let Some(risk_manager) = self.new_risk_manager.as_ref().filter(|_| !strategy_result.pending) {
+                if strategy_result.success {
+                    risk_manager.record_strategy_success(&strategy_type).await;
+                    // A bundle that didn't land cost nothing; one that did paid its fees and tip
+                    risk_manager.record_successful_operation(-(strategy_result.fees_paid + strategy_result.tip_paid)).await;
+                } else if strategy_result.submitted() {
+                    risk_manager.record_strategy_failure(&strategy_type).await;
+                    // Counts toward the circuit breaker; reaching the limit is logged when it opens
+                    let _ = risk_manager.record_failed_operation().await;
+                }
+                // What the pools actually returned decides whether they stay tradable
+                if strategy_result.success || strategy_result.submitted() {
+                    risk_manager.record_pool_outcome(&queued.opportunity.pools(), strategy_result.profit, !strategy_result.success).await;
+                }
+            }
+            
+            // NEW ARCHITECTURE: Record the execution result
+            if let Some(ref metrics_collector) = self.metrics_collector {
+                metrics_collector.record_strategy_execution(&strategy_result).await;
+                metrics_collector.record_execution_outcome(queued.timing.correlation_id, &strategy_result).await;
+                metrics_collector.record_token_outcome(&queued.opportunity, &strategy_result).await;
+            }
+            
+            if strategy_result.success {
+                Logger::bundle_sent("Solana", true);
+                Logger::status_update(&format!(
+                    "Strategy executed successfully: type {:?}, net profit: {:.6} SOL", 
+                    strategy_result.strategy_type, 
+                    strategy_result.profit
+                ));
+            } else if strategy_result.pending {
+                Logger::status_update(&format!(
+                    "Strategy bundle accepted: type {:?}, expected profit {:.6} SOL once bundle {} lands",
+                    strategy_result.strategy_type,
+                    strategy_result.profit,
+                    strategy_result.bundle_id.as_deref().unwrap_or("unknown")
+                ));
             } else {
-                Logger::status_update(&format!("No profitable opportunity detected for transaction: {}", signature));
+                Logger::status_update(&format!(
+                    "Strategy execution failed: type {:?}, loss: {:.6} SOL", 
+                    strategy_result.strategy_type, 
+                    strategy_result.profit
+                ));
             }
         }
     }
@@ -373,53 +1363,31 @@ impl SolanaMempool {
     async fn classify_transaction_opportunity(&self, tx_details: &Value) -> OpportunityType {
         // Analyze the transaction to determine the best MEV strategy
         
-        // Check for swap instructions (common in arbitrage and frontrun opportunities)
-        if let Some(transaction) = tx_details.get("transaction") {
-            if let Some(message) = transaction.get("message") {
-                if let Some(instructions) = message.get("instructions") {
-                    if let Some(instr_array) = instructions.as_array() {
-                        for instruction in instr_array {
-                            if let Some(accounts) = instruction.get("accounts").and_then(|v| v.as_array()) {
-                                // More than 3 accounts often indicates a DEX swap
-                                if accounts.len() >= 4 {
-                                    // Check for high-value token transfers that might indicate arbitrage
-                                    if let Some(meta) = tx_details.get("meta") {
-                                        if let Some(post_balances) = meta.get("postTokenBalances").and_then(|v| v.as_array()) {
-                                            // If there are significant changes, it might be an arbitrage opportunity
-                                            for balance in post_balances {
-                                                if let Some(ui_amount) = balance.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64()) {
-                                                    if ui_amount > 1000.0 { // Threshold for significant amount
-                                                        return OpportunityType::Arbitrage;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    return OpportunityType::Frontrun;
-                                }
-                            }
-                        }
-                    }
+        // Check for swap instructions (common in arbitrage and frontrun opportunities); only
+        // instructions of a registered DEX program count, including CPIs from routers
+        let dex_instruction = instruction_parser::all_instructions(tx_details)
+            .iter()
+            .any(|instruction| self.dex_registry.is_known(&instruction.program_id));
+        let meta = TransactionMeta::deserialize(&tx_details["meta"]).ok();
+        if dex_instruction {
+            // Check for high-value token transfers that might indicate arbitrage
+            if let Some(meta) = &meta {
+                // If there are significant changes, it might be an arbitrage opportunity
+                if meta.post_token_balances.iter().any(|balance| balance.ui_token_amount.ui_amount() > 1000.0) { // Threshold for significant amount
+                    return OpportunityType::Arbitrage;
                 }
             }
+            return OpportunityType::Frontrun;
         }
         
         // Check for token balance changes that indicate swaps
-        if let Some(meta) = tx_details.get("meta") {
-            if let Some(post_token_balances) = meta.get("postTokenBalances").and_then(|v| v.as_array()) {
-                if let Some(pre_token_balances) = meta.get("preTokenBalances").and_then(|v| v.as_array()) {
-                    let significant_changes = post_token_balances.iter().zip(pre_token_balances.iter())
-                        .filter(|(post, pre)| {
-                            let post_amount = post.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64()).unwrap_or(0.0);
-                            let pre_amount = pre.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64()).unwrap_or(0.0);
-                            (post_amount - pre_amount).abs() > 100.0
-                        })
-                        .count();
-                    
-                    if significant_changes >= 2 {
-                        return OpportunityType::Arbitrage;
-                    }
-                }
+        if let Some(meta) = &meta {
+            let significant_changes = meta.post_token_balances.iter().zip(meta.pre_token_balances.iter())
+                .filter(|(post, pre)| (post.ui_token_amount.ui_amount() - pre.ui_token_amount.ui_amount()).abs() > 100.0)
+                .count();
+            
+            if significant_changes >= 2 {
+                return OpportunityType::Arbitrage;
             }
         }
         
@@ -658,7 +1626,7 @@ impl SolanaMempool {
         }
     }
     
-    async fn estimate_profitability(&self, signature: &str) -> OpportunityAnalysis {
+    pub(crate) async fn estimate_profitability(&self, signature: &str) -> OpportunityAnalysis {
         Logger::status_update(&format!("Analyzing profitability for transaction: {}", signature));
         
         // Fetch the actual transaction details to analyze if there are real MEV opportunities
@@ -704,37 +1672,19 @@ impl SolanaMempool {
     }
     
     async fn fetch_transaction_details(&self, signature: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        let request_body = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getTransaction",
-            "params": [
-                signature,
-                {
-                    "encoding": "json",
-                    "maxSupportedTransactionVersion": 0
-                }
-            ]
-        });
-
-        let response: Value = self.client
-            .post(&self.rpc_url)
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-        if let Some(error) = response.get("error") {
-            return Err(format!("Get transaction failed: {}", error).into());
-        }
+        // Batched with other lookups through the RPC manager, so provider rate limits and our own
+        // come back as RpcError::RateLimited / RateLimitedLocally
+        let response = match (&self.transaction_fetcher, &self.rpc_manager) {
+            (Some(fetcher), _) => fetcher.fetch(signature).await?,
+            (None, Some(rpc_manager)) => rpc_manager.make_request(RpcTaskType::Read, transaction_fetcher::get_transaction_request(signature)).await?,
+            (None, None) => return Err("RPC manager not initialized".into()),
+        };
 
-        if let Some(result) = response.get("result") {
-            Ok(result.clone())
-        } else {
-            Err("Failed to get transaction result".into())
+        // Checked against the typed shape up front, so a malformed answer fails here naming the
+        // missing field rather than reading as an empty transaction further down
+        match types::parse_result::<Option<EncodedTransaction>>("getTransaction", &response)? {
+            Some(_) => Ok(response["result"].clone()),
+            None => Err(format!("Transaction {} not found", signature).into()),
         }
     }
     
@@ -780,17 +1730,9 @@ impl SolanaMempool {
         }
         
         // Check for potential sandwich opportunities
-        if let Some(meta) = tx_details.get("meta") {
-            if let Some(post_token_balances) = meta.get("postTokenBalances").and_then(|v| v.as_array()) {
-                if let Some(pre_token_balances) = meta.get("preTokenBalances").and_then(|v| v.as_array()) {
-                    // Compare token balances before and after to detect swaps
-                    // This can indicate potential for sandwich attacks
-                    if post_token_balances.len() > 0 && pre_token_balances.len() > 0 {
-                        estimated_profit += self.analyze_token_balance_changes(post_token_balances, pre_token_balances).await;
-                    }
-                }
-            }
-        }
+        // Compare token balances before and after to detect swaps
+        // This can indicate potential for sandwich attacks
+        estimated_profit += self.analyze_token_balance_changes(tx_details).await;
         
         // Perform real arbitrage analysis by comparing with current pool states
         if let Some(arb_profit) = self.check_arbitrage_against_transaction(tx_details).await {
@@ -867,20 +1809,13 @@ impl SolanaMempool {
         0.0
     }
     
-    async fn analyze_token_balance_changes(&self, post_balances: &[Value], pre_balances: &[Value]) -> f64 {
-        // Analyze changes in token balances to identify swaps and potential MEV opportunities
+    async fn analyze_token_balance_changes(&self, tx_details: &Value) -> f64 {
+        // Analyze changes in the swapping wallet's token balances to identify swaps and potential MEV opportunities
         let mut mev_potential = 0.0;
         
-        // Compare pre and post balances to identify token swaps
-        for (pre, post) in pre_balances.iter().zip(post_balances.iter()) {
-            if let (Some(pre_amount), Some(post_amount)) = (
-                pre.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64()),
-                post.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64())
-            ) {
-                let change = post_amount - pre_amount;
-                if change.abs() > 0.001 {  // Significant change threshold
-                    mev_potential += 0.002; // Potential MEV opportunity
-                }
+        for balance_change in instruction_parser::fee_payer_balance_changes(tx_details) {
+            if balance_change.ui_change().abs() > 0.001 {  // Significant change threshold
+                mev_potential += 0.002; // Potential MEV opportunity
             }
         }
         
@@ -900,7 +1835,7 @@ impl SolanaMempool {
     }
 
     // Fallback method using slot monitoring
-    async fn start_slot_monitoring(&self, executor: &SolanaExecutor) {
+    async fn start_slot_monitoring(&self, executor: &SolanaExecutor, mut shutdown: watch::Receiver<bool>) {
         Logger::status_update("Starting slot-based monitoring as fallback");
         
         let mut last_slot = 0;
@@ -908,6 +1843,11 @@ impl SolanaMempool {
         let max_errors_before_reset = 10;
         
         loop {
+            if *shutdown.borrow() {
+                Logger::status_update("Slot monitoring stopped");
+                return;
+            }
+            
             match self.get_slot().await {
                 Ok(current_slot) => {
                     if current_slot > last_slot {
@@ -948,8 +1888,15 @@ impl SolanaMempool {
                 }
             }
             
-            // Sleep for a short time before checking again
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            // Sleep for a short time before checking again, waking early on shutdown
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(500)) => {},
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+            }
         }
     }
     
@@ -971,10 +1918,6 @@ impl SolanaMempool {
             .await
             .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-        if let Some(result) = response["result"].as_u64() {
-            Ok(result)
-        } else {
-            Err("Failed to get slot".into())
-        }
+        Ok(types::parse_result::<u64>("getSlot", &response)?)
     }
 } // End of impl SolanaMempool
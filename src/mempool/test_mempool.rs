@@ -1,12 +1,29 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mempool::solana::SolanaMempool;
-    use crate::config::Network;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use serde_json::{json, Value};
+    use crate::logging::Logger;
+    use crate::mempool::solana::{risk_allows_analysis, strategy_enabled, ws_endpoint_label, PendingNotification, SolanaMempool};
+    use crate::utils::risk_controls::RiskManager;
+    use crate::utils::kill_switch::KillSwitch;
+    use crate::config::Config;
+    use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
+    use crate::utils::metrics_collector::OpportunityTiming;
+    use crate::utils::mev_strategies::MevStrategyType;
+    use crate::utils::mock_http::{self, MockResponse};
+    use crate::utils::opportunity_queue::OpportunityQueue;
 
     #[tokio::test]
     async fn test_transaction_analysis() {
-        let mempool = SolanaMempool::new(&Network::Devnet);
+        let mut config = Config::default();
+        config.network = "devnet".to_string();
+        config.strategies.enabled = vec!["arbitrage".to_string()];
+        // Nothing listens on the discard port, so every fetch fails fast
+        config.rpc.helius = Some("http://127.0.0.1:9".to_string());
+        let mempool = SolanaMempool::new(std::sync::Arc::new(config)).await
+            .expect("mempool initializes");
         
         // Test with a dummy signature to ensure it doesn't use fake profit estimates
         let analysis = mempool.estimate_profitability("dummy_signature_123456789").await;
@@ -39,4 +56,159 @@ mod tests {
         let analysis = OpportunityAnalysis::new(0.02, 0.006, 0.1); // 0.02 profit, 0.006 fees = 0.014 net
         assert!(ProfitabilityCalculator::should_execute(&analysis));
     }
-}
\ No newline at end of file
+
+    // Devnet config against the given RPC node, with risk state kept out of the working directory
+    fn test_config(rpc_url: String, name: &str) -> Config {
+        let mut config = Config::default();
+        config.network = "devnet".to_string();
+        config.rpc.helius = Some(rpc_url);
+        config.risk.state_file = std::env::temp_dir()
+            .join(format!("mempool_{}_{}.json", name, std::process::id()))
+            .to_string_lossy().to_string();
+        config
+    }
+
+    fn opportunity(opportunity_type: OpportunityType) -> OpportunityDetails {
+        OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000_000,
+            estimated_profit: 0.05,
+            dex: "Raydium".to_string(),
+            opportunity_type,
+            pool_address: None,
+            route: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_arbitrage_only_filters_out_other_strategies() {
+        let enabled = MevStrategyType::parse_strategy_list("arbitrage").unwrap().into_iter().collect();
+
+        assert!(strategy_enabled(&enabled, &opportunity(OpportunityType::Arbitrage)));
+        assert!(!strategy_enabled(&enabled, &opportunity(OpportunityType::Sandwich)));
+        assert!(!strategy_enabled(&enabled, &opportunity(OpportunityType::Frontrun)));
+        assert!(!strategy_enabled(&enabled, &opportunity(OpportunityType::Snipe)));
+        assert!(!strategy_enabled(&enabled, &opportunity(OpportunityType::Other)));
+    }
+
+    #[tokio::test]
+    async fn test_arbitrage_only_never_executes_sandwich() {
+        let (rpc_url, _) = mock_http::serve(|request| MockResponse::rpc_result(request, Value::Null)).await;
+        // Block engine counting the bundles sent to it
+        let bundles_sent = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&bundles_sent);
+        let (jito_url, _) = mock_http::serve(move |request| {
+            if request.json()["method"] == "sendBundle" {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+            MockResponse::rpc_result(request, Value::from("bundle-id"))
+        }).await;
+
+        let mut config = test_config(rpc_url, "arbitrage_only");
+        config.strategies.enabled = vec!["arbitrage".to_string()];
+        config.jito.rpc_url = Some(jito_url);
+        let mempool = SolanaMempool::new(Arc::new(config)).await.expect("mempool initializes");
+        mempool.risk_manager().unwrap().initialize_balance(2.0).await;
+
+        let queue = OpportunityQueue::new(std::time::Duration::from_secs(5), 16);
+        queue.push("sandwich_sig".to_string(), opportunity(OpportunityType::Sandwich), json!({}), OpportunityTiming::new(std::time::Instant::now())).await;
+        let (queued, _) = queue.pop().await;
+        mempool.execute_queued_opportunity(queued).await;
+
+        assert_eq!(bundles_sent.load(Ordering::SeqCst), 0);
+        let metrics = mempool.metrics_collector().unwrap().get_system_metrics().await;
+        assert_eq!(metrics.total_opportunities_strategy_disabled, 1);
+    }
+
+    #[test]
+    fn test_strategy_list_parsing() {
+        let enabled = MevStrategyType::parse_strategy_list("arbitrage, Sandwich").unwrap();
+        assert_eq!(enabled, vec![MevStrategyType::Arbitrage, MevStrategyType::Sandwich]);
+
+        let err = MevStrategyType::parse_strategy_list("arbitrage,arbitrge").unwrap_err();
+        assert!(err.contains("arbitrge"));
+        assert!(err.contains("valid values: arbitrage, sandwich, frontrun, backrun, liquidation, snipe"));
+
+        assert!(MevStrategyType::parse_strategy_list("").is_err());
+    }
+
+    #[test]
+    fn test_stale_notifications_are_detected() {
+        let fresh = PendingNotification::new("fresh_sig");
+        assert!(!fresh.is_stale(std::time::Duration::from_millis(1500)));
+
+        let stale = PendingNotification {
+            signature: "stale_sig".to_string(),
+            received_at: std::time::Instant::now() - std::time::Duration::from_millis(3000),
+        };
+        assert!(stale.is_stale(std::time::Duration::from_millis(1500)));
+    }
+
+    #[tokio::test]
+    async fn test_low_balance_skips_transaction_fetches() {
+        // RPC node counting the getTransaction calls it gets, batched or not
+        let get_transaction_calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&get_transaction_calls);
+        let (url, _) = mock_http::serve(move |request| {
+            let body = request.json();
+            let calls = body.as_array().cloned().unwrap_or_else(|| vec![body]);
+            counter.fetch_add(calls.iter().filter(|call| call["method"] == "getTransaction").count(), Ordering::SeqCst);
+            MockResponse::rpc_result(request, Value::Null)
+        }).await;
+
+        let mempool = SolanaMempool::new(Arc::new(test_config(url, "low_balance"))).await.expect("mempool initializes");
+        let risk_manager = mempool.risk_manager().unwrap();
+        let metrics_collector = mempool.metrics_collector().unwrap();
+        risk_manager.initialize_balance(0.1).await; // Below MIN_BALANCE_THRESHOLD
+
+        for i in 0..10 {
+            mempool.analyze_and_execute_opportunity(PendingNotification::new(&format!("low_balance_sig_{}", i))).await;
+        }
+        assert_eq!(get_transaction_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(metrics_collector.get_system_metrics().await.total_risk_paused, 10);
+
+        // Topping up resumes fetching; each state change is logged once, not per notification
+        risk_manager.initialize_balance(2.0).await;
+        for i in 0..3 {
+            mempool.analyze_and_execute_opportunity(PendingNotification::new(&format!("funded_sig_{}", i))).await;
+        }
+        assert!(get_transaction_calls.load(Ordering::SeqCst) > 0);
+        assert_eq!(metrics_collector.get_system_metrics().await.total_risk_paused, 10);
+
+        let lines = Logger::status_lines();
+        assert_eq!(lines.iter().filter(|line| line.starts_with("Risk controls paused operations")).count(), 1);
+        assert_eq!(lines.iter().filter(|line| *line == "Risk controls resumed operations").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_pauses_and_resumes_processing() {
+        let path = std::env::temp_dir().join(format!("mempool_kill_switch_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let kill_switch = std::sync::Arc::new(KillSwitch::new(Some(path.clone()), std::time::Duration::ZERO));
+        let risk_manager = RiskManager::new(&Config::default()).unwrap().with_kill_switch(kill_switch.clone());
+        risk_manager.initialize_balance(2.0).await;
+        assert!(risk_allows_analysis(Some(&risk_manager)).await);
+
+        // Dropping the file in stops new opportunities; removing it resumes
+        std::fs::write(&path, "").unwrap();
+        assert!(!risk_allows_analysis(Some(&risk_manager)).await);
+        std::fs::remove_file(&path).unwrap();
+        assert!(risk_allows_analysis(Some(&risk_manager)).await);
+
+        // Same through the signal latch (SIGUSR1 / SIGUSR2)
+        kill_switch.engage();
+        assert!(!risk_allows_analysis(Some(&risk_manager)).await);
+        kill_switch.release();
+        assert!(risk_allows_analysis(Some(&risk_manager)).await);
+    }
+
+    #[test]
+    fn test_ws_endpoint_label_hides_credentials() {
+        assert_eq!(
+            ws_endpoint_label("wss://mainnet.helius-rpc.com/?api-key=secret"),
+            "mainnet.helius-rpc.com"
+        );
+        assert_eq!(ws_endpoint_label("ws://127.0.0.1:8900"), "127.0.0.1:8900");
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use crate::rpc::error::RpcError;
+use crate::rpc::rpc_manager::RpcEndpointType;
+
+// Requests per batch for endpoints without a configured limit
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 50;
+
+// Request i gets id first_id + i, so answers can be matched back whatever order they come in
+pub fn with_ids(requests: Vec<Value>, first_id: usize) -> Vec<Value> {
+    requests.into_iter().enumerate()
+        .map(|(index, mut request)| {
+            request["id"] = json!(first_id + index);
+            request
+        })
+        .collect()
+}
+
+// One result per request, in request order. Each answer carries its own result or error; a
+// request the node didn't answer is an InvalidResponse
+pub fn match_responses(response: &Value, first_id: usize, count: usize) -> Vec<Result<Value, RpcError>> {
+    let Some(answers) = response.as_array() else {
+        return vec![Err(RpcError::InvalidResponse("Batch response is not an array".to_string())); count];
+    };
+    let mut by_id: HashMap<u64, &Value> = answers.iter()
+        .filter_map(|answer| answer["id"].as_u64().map(|id| (id, answer)))
+        .collect();
+    (first_id..first_id + count)
+        .map(|id| match by_id.remove(&(id as u64)) {
+            Some(answer) => match answer.get("error") {
+                Some(error) => Err(RpcError::from_json_rpc(error)),
+                None => Ok(answer.clone()),
+            },
+            None => Err(RpcError::InvalidResponse(format!("No answer for batch request {}", id))),
+        })
+        .collect()
+}
+
+// Overrides in the form "helius=100,drpc=25"
+pub fn parse_batch_limits(value: &str) -> Result<HashMap<RpcEndpointType, usize>, String> {
+    let mut limits = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (endpoint, limit) = entry.split_once('=')
+            .ok_or_else(|| format!("expected endpoint=size, got {}", entry))?;
+        let endpoint_type = RpcEndpointType::parse(endpoint)
+            .ok_or_else(|| format!("unknown endpoint {}", endpoint.trim()))?;
+        let limit = limit.trim().parse::<usize>()
+            .map_err(|e| format!("invalid batch size for {}: {}", endpoint.trim(), e))?;
+        limits.insert(endpoint_type, limit.max(1));
+    }
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_out_of_order_answers_and_keeps_item_errors() {
+        let requests = with_ids(vec![json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot" }); 3], 10);
+        assert_eq!(requests[2]["id"], json!(12));
+
+        let response = json!([
+            { "jsonrpc": "2.0", "id": 12, "result": "c" },
+            { "jsonrpc": "2.0", "id": 10, "result": "a" },
+            { "jsonrpc": "2.0", "id": 11, "error": { "code": -32602, "message": "Invalid params" } }
+        ]);
+        let results = match_responses(&response, 10, 3);
+        assert_eq!(results[0].as_ref().unwrap()["result"], json!("a"));
+        assert!(matches!(results[1], Err(RpcError::Rpc { code: -32602, .. })));
+        assert_eq!(results[2].as_ref().unwrap()["result"], json!("c"));
+    }
+
+    #[test]
+    fn test_unanswered_requests_fail_individually() {
+        let response = json!([{ "jsonrpc": "2.0", "id": 0, "result": 1 }]);
+        let results = match_responses(&response, 0, 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(RpcError::InvalidResponse(_))));
+
+        let results = match_responses(&json!({ "jsonrpc": "2.0", "id": 0, "result": 1 }), 0, 2);
+        assert!(results.iter().all(|result| result.is_err()));
+    }
+
+    #[test]
+    fn test_parses_batch_limits() {
+        let limits = parse_batch_limits("helius=100, drpc=0").unwrap();
+        assert_eq!(limits.get(&RpcEndpointType::Helius), Some(&100));
+        assert_eq!(limits.get(&RpcEndpointType::Drpc), Some(&1));
+        assert!(parse_batch_limits("quicknode=10").is_err());
+        assert!(parse_batch_limits("helius=many").is_err());
+    }
+}
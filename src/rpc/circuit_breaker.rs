@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::rpc::rpc_manager::RpcEndpointType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,   // Requests flow
+    Open,     // Too many recent failures: skipped until the cool-down is over
+    HalfOpen, // Cool-down over: one probe request decides whether to close again
+}
+
+impl BreakerState {
+    // Gauge value: 0 closed, 1 half-open, 2 open
+    pub fn as_gauge(&self) -> u8 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakerConfig {
+    pub failure_threshold: u32, // Failures within the window that open the breaker; 0 disables it
+    pub window: Duration,
+    pub cool_down: Duration,    // Time open before a probe is let through
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, window: Duration::from_secs(10), cool_down: Duration::from_secs(5) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakerStatus {
+    pub endpoint: RpcEndpointType,
+    pub state: BreakerState,
+}
+
+// State machine of one endpoint's breaker. Every method takes the current time so tests can walk
+// it with a fake clock; each returns the transition it made, if any, for logging
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    state: BreakerState,
+    failures: VecDeque<Instant>, // Within the window, while closed
+    opened_at: Instant,
+    probe_started: Option<Instant>, // While half-open and the probe is out
+}
+
+pub type Transition = (BreakerState, BreakerState);
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig, now: Instant) -> Self {
+        Self { config, state: BreakerState::Closed, failures: VecDeque::new(), opened_at: now, probe_started: None }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    // Whether a request may go to the endpoint now. Past the cool-down the first caller becomes
+    // the probe; a probe that never reported back (its caller gave up) is replaced after another
+    // cool-down
+    pub fn allow_at(&mut self, now: Instant) -> (bool, Option<Transition>) {
+        match self.state {
+            BreakerState::Closed => (true, None),
+            BreakerState::Open if now.saturating_duration_since(self.opened_at) >= self.config.cool_down => {
+                self.state = BreakerState::HalfOpen;
+                self.probe_started = Some(now);
+                (true, Some((BreakerState::Open, BreakerState::HalfOpen)))
+            }
+            BreakerState::Open => (false, None),
+            BreakerState::HalfOpen => match self.probe_started {
+                Some(started) if now.saturating_duration_since(started) < self.config.cool_down => (false, None),
+                _ => {
+                    self.probe_started = Some(now);
+                    (true, None)
+                }
+            },
+        }
+    }
+
+    pub fn record_success_at(&mut self, now: Instant) -> Option<Transition> {
+        match self.state {
+            BreakerState::HalfOpen => {
+                self.close();
+                Some((BreakerState::HalfOpen, BreakerState::Closed))
+            }
+            _ => {
+                self.prune(now);
+                None
+            }
+        }
+    }
+
+    pub fn record_failure_at(&mut self, now: Instant) -> Option<Transition> {
+        match self.state {
+            BreakerState::Closed if self.config.failure_threshold > 0 => {
+                self.failures.push_back(now);
+                self.prune(now);
+                if self.failures.len() >= self.config.failure_threshold as usize {
+                    self.open(now);
+                    return Some((BreakerState::Closed, BreakerState::Open));
+                }
+                None
+            }
+            BreakerState::HalfOpen => {
+                self.open(now);
+                Some((BreakerState::HalfOpen, BreakerState::Open))
+            }
+            _ => None,
+        }
+    }
+
+    // The probe was let through but never sent (e.g. held back by a local rate limit)
+    pub fn abandon_probe(&mut self) {
+        if self.state == BreakerState::HalfOpen {
+            self.probe_started = None;
+        }
+    }
+
+    fn open(&mut self, now: Instant) {
+        self.state = BreakerState::Open;
+        self.opened_at = now;
+        self.probe_started = None;
+        self.failures.clear();
+    }
+
+    fn close(&mut self) {
+        self.state = BreakerState::Closed;
+        self.probe_started = None;
+        self.failures.clear();
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self.failures.front().is_some_and(|failure| now.saturating_duration_since(*failure) > self.config.window) {
+            self.failures.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(start: Instant) -> CircuitBreaker {
+        CircuitBreaker::new(BreakerConfig {
+            failure_threshold: 3,
+            window: Duration::from_secs(10),
+            cool_down: Duration::from_secs(5),
+        }, start)
+    }
+
+    #[test]
+    fn test_walks_closed_open_half_open_and_back() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut breaker = breaker(start);
+
+        // Scripted outage: failures spread past the window don't add up, three within it do
+        assert_eq!(breaker.record_failure_at(at(0)), None);
+        assert_eq!(breaker.record_failure_at(at(11)), None);
+        assert_eq!(breaker.record_failure_at(at(12)), None);
+        assert_eq!(breaker.allow_at(at(12)), (true, None));
+        assert_eq!(breaker.record_failure_at(at(13)), Some((BreakerState::Closed, BreakerState::Open)));
+
+        // Open: skipped until the cool-down is over
+        assert_eq!(breaker.allow_at(at(14)), (false, None));
+        assert_eq!(breaker.allow_at(at(17)), (false, None));
+
+        // Half-open: one probe goes through, everything else keeps skipping the endpoint
+        assert_eq!(breaker.allow_at(at(18)), (true, Some((BreakerState::Open, BreakerState::HalfOpen))));
+        assert_eq!(breaker.allow_at(at(18)), (false, None));
+        // The probe failed: open for another cool-down
+        assert_eq!(breaker.record_failure_at(at(19)), Some((BreakerState::HalfOpen, BreakerState::Open)));
+        assert_eq!(breaker.allow_at(at(23)), (false, None));
+
+        // The next probe answers and the breaker closes with a clean slate
+        assert!(breaker.allow_at(at(24)).0);
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert_eq!(breaker.record_success_at(at(24)), Some((BreakerState::HalfOpen, BreakerState::Closed)));
+        assert_eq!(breaker.record_failure_at(at(25)), None);
+        assert_eq!(breaker.record_failure_at(at(25)), None);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_lost_probes_are_replaced() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut breaker = breaker(start);
+        for _ in 0..3 {
+            breaker.record_failure_at(at(0));
+        }
+
+        assert!(breaker.allow_at(at(5)).0);
+        // The probe's caller gave up without reporting; after another cool-down a new probe goes
+        assert!(!breaker.allow_at(at(9)).0);
+        assert!(breaker.allow_at(at(10)).0);
+
+        // A probe that was never sent frees the slot straight away
+        breaker.abandon_probe();
+        assert!(breaker.allow_at(at(10)).0);
+    }
+
+    #[test]
+    fn test_zero_threshold_never_opens() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(BreakerConfig { failure_threshold: 0, ..BreakerConfig::default() }, start);
+        for _ in 0..100 {
+            assert_eq!(breaker.record_failure_at(start), None);
+        }
+        assert!(breaker.allow_at(start).0);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+use serde_json::Value;
+
+// JSON-RPC error codes providers answer with when a client exceeds its request rate
+const RATE_LIMIT_CODES: [i64; 3] = [-32005, -32097, -32429];
+
+// Why an RpcManager request failed, so callers can tell a throttled provider from a broken
+// one or from the node rejecting the request itself
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    RateLimited { retry_after: Option<Duration> }, // HTTP 429 or a provider rate-limit error
+    RateLimitedLocally,                             // Over our own budget for the endpoint; never sent
+    Timeout,
+    Rpc { code: i64, message: String },             // JSON-RPC error returned by the node
+    Transport(String),                              // Connection failures, 5xx, unreadable bodies
+    InvalidResponse(String),                        // Answered, but without what the call expects
+}
+
+impl RpcError {
+    // A JSON-RPC error object; rate-limit codes and messages are told apart from node errors
+    pub fn from_json_rpc(error: &Value) -> Self {
+        let code = error["code"].as_i64().unwrap_or(0);
+        let message = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+        let lowercase = message.to_lowercase();
+        if RATE_LIMIT_CODES.contains(&code) || lowercase.contains("rate limit") || lowercase.contains("too many requests") {
+            RpcError::RateLimited { retry_after: None }
+        } else {
+            RpcError::Rpc { code, message }
+        }
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, RpcError::RateLimited { .. } | RpcError::RateLimitedLocally)
+    }
+
+    // Worth trying again on another endpoint or after a wait; node errors would just repeat
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, RpcError::RateLimited { .. } | RpcError::RateLimitedLocally | RpcError::Timeout | RpcError::Transport(_))
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::RateLimited { retry_after: Some(retry_after) } => write!(f, "RPC rate limited, retry after {}ms", retry_after.as_millis()),
+            RpcError::RateLimited { retry_after: None } => write!(f, "RPC rate limited"),
+            RpcError::RateLimitedLocally => write!(f, "RPC request over the local rate limit"),
+            RpcError::Timeout => write!(f, "RPC request timed out"),
+            RpcError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            RpcError::Transport(detail) => write!(f, "RPC transport error: {}", detail),
+            RpcError::InvalidResponse(detail) => write!(f, "{}", detail),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+// Whether a boxed error is an RpcManager rate limit that made it up through `?`
+pub fn is_rate_limited(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    error.downcast_ref::<RpcError>().is_some_and(RpcError::is_rate_limited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_classifies_json_rpc_errors() {
+        assert!(RpcError::from_json_rpc(&json!({ "code": -32005, "message": "Request limit exceeded" })).is_rate_limited());
+        assert!(RpcError::from_json_rpc(&json!({ "code": -32097, "message": "busy" })).is_rate_limited());
+        assert!(RpcError::from_json_rpc(&json!({ "code": -32000, "message": "Too many requests for a specific RPC call" })).is_rate_limited());
+
+        let error = RpcError::from_json_rpc(&json!({ "code": -32602, "message": "Invalid params" }));
+        assert_eq!(error, RpcError::Rpc { code: -32602, message: "Invalid params".to_string() });
+        assert!(!error.is_retryable());
+        assert_eq!(error.to_string(), "RPC error -32602: Invalid params");
+    }
+
+    #[test]
+    fn test_detects_rate_limits_behind_boxed_errors() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> = RpcError::RateLimited { retry_after: None }.into();
+        assert!(is_rate_limited(&*boxed));
+        let boxed: Box<dyn std::error::Error + Send + Sync> = RpcError::RateLimitedLocally.into();
+        assert!(is_rate_limited(&*boxed));
+        let boxed: Box<dyn std::error::Error + Send + Sync> = RpcError::Timeout.into();
+        assert!(!is_rate_limited(&*boxed));
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "RPC rate limited".into();
+        assert!(!is_rate_limited(&*boxed));
+    }
+}
@@ -1 +1,7 @@
-pub mod rpc_manager;
\ No newline at end of file
+pub mod rpc_manager;
+pub mod routing;
+pub mod error;
+pub mod batch;
+pub mod rate_limit;
+pub mod circuit_breaker;
+pub mod types;
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::rpc::error::RpcError;
+use crate::rpc::rpc_manager::RpcEndpointType;
+use crate::utils::token_bucket::TokenBucket;
+
+// Our own request budget for one endpoint, kept under the provider's plan so a busy stretch
+// can't burn through it: requests per second and requests in flight, each optional
+#[derive(Debug)]
+pub struct EndpointLimiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+// Held while the request is out; frees its in-flight slot when dropped
+#[derive(Debug)]
+pub struct Permit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+// What's left of an endpoint's budget right now; None where no limit is configured
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateBudget {
+    pub endpoint: RpcEndpointType,
+    pub tokens: Option<u64>,
+    pub in_flight_available: Option<usize>,
+}
+
+impl EndpointLimiter {
+    // Bursts up to one second's worth of requests
+    pub fn new(requests_per_sec: Option<f64>, max_in_flight: Option<usize>) -> Self {
+        Self {
+            bucket: requests_per_sec.map(|rate| Mutex::new(TokenBucket::new(rate, rate.ceil(), Instant::now()))),
+            in_flight: max_in_flight.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+        }
+    }
+
+    // Waits up to max_wait for an in-flight slot and then for a token; with no wait allowed, only
+    // takes what is free right now. A request that can't get both in time is never sent
+    pub async fn acquire(&self, max_wait: Duration) -> Result<Permit, RpcError> {
+        let deadline = tokio::time::Instant::now() + max_wait;
+        let in_flight = match &self.in_flight {
+            Some(semaphore) if max_wait.is_zero() => Some(Arc::clone(semaphore).try_acquire_owned()
+                .map_err(|_| RpcError::RateLimitedLocally)?),
+            Some(semaphore) => Some(tokio::time::timeout_at(deadline, Arc::clone(semaphore).acquire_owned()).await
+                .ok()
+                .and_then(Result::ok)
+                .ok_or(RpcError::RateLimitedLocally)?),
+            None => None,
+        };
+
+        if let Some(bucket) = &self.bucket {
+            let delay = {
+                let mut bucket = bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let now = Instant::now();
+                // Only reserve a token we can wait for, so a rejected request costs nothing
+                if bucket.wait_at(now) > deadline.saturating_duration_since(tokio::time::Instant::now()) {
+                    return Err(RpcError::RateLimitedLocally);
+                }
+                bucket.reserve_at(now)
+            };
+            if delay > Duration::ZERO {
+                tokio::time::sleep(delay).await;
+            }
+        }
+        Ok(Permit { _in_flight: in_flight })
+    }
+
+    pub fn budget(&self, endpoint: RpcEndpointType) -> RateBudget {
+        RateBudget {
+            endpoint,
+            tokens: self.bucket.as_ref()
+                .map(|bucket| bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).available_at(Instant::now())),
+            in_flight_available: self.in_flight.as_ref().map(|semaphore| semaphore.available_permits()),
+        }
+    }
+}
+
+// Per-endpoint values in the form "helius=50,drpc=20"
+pub fn parse_endpoint_limits<T: FromStr>(value: &str) -> Result<HashMap<RpcEndpointType, T>, String>
+where
+    T::Err: std::fmt::Display,
+{
+    let mut limits = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (endpoint, limit) = entry.split_once('=')
+            .ok_or_else(|| format!("expected endpoint=limit, got {}", entry))?;
+        let endpoint_type = RpcEndpointType::parse(endpoint)
+            .ok_or_else(|| format!("unknown endpoint {}", endpoint.trim()))?;
+        let limit = limit.trim().parse::<T>()
+            .map_err(|e| format!("invalid limit for {}: {}", endpoint.trim(), e))?;
+        limits.insert(endpoint_type, limit);
+    }
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_waits_for_tokens_within_the_bound_and_fails_fast_without_one() {
+        let limiter = EndpointLimiter::new(Some(20.0), None);
+        for _ in 0..20 {
+            limiter.acquire(Duration::ZERO).await.unwrap();
+        }
+        assert_eq!(limiter.budget(RpcEndpointType::Helius).tokens, Some(0));
+
+        // Out of burst: no wait allowed fails at once, a 50ms wait covers the next token
+        assert!(matches!(limiter.acquire(Duration::ZERO).await, Err(RpcError::RateLimitedLocally)));
+        let started = Instant::now();
+        limiter.acquire(Duration::from_millis(200)).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        // A wait past the bound isn't reserved, so it doesn't push later requests back
+        assert!(matches!(limiter.acquire(Duration::from_millis(1)).await, Err(RpcError::RateLimitedLocally)));
+    }
+
+    #[tokio::test]
+    async fn test_caps_requests_in_flight() {
+        let limiter = EndpointLimiter::new(None, Some(1));
+        let permit = limiter.acquire(Duration::ZERO).await.unwrap();
+        assert_eq!(limiter.budget(RpcEndpointType::Drpc).in_flight_available, Some(0));
+        assert!(matches!(limiter.acquire(Duration::from_millis(20)).await, Err(RpcError::RateLimitedLocally)));
+
+        drop(permit);
+        assert!(limiter.acquire(Duration::ZERO).await.is_ok());
+        assert_eq!(limiter.budget(RpcEndpointType::Drpc), RateBudget {
+            endpoint: RpcEndpointType::Drpc,
+            tokens: None,
+            in_flight_available: Some(1),
+        });
+    }
+
+    #[test]
+    fn test_parses_endpoint_limits() {
+        let rates = parse_endpoint_limits::<f64>("helius=50, drpc=12.5").unwrap();
+        assert_eq!(rates.get(&RpcEndpointType::Helius), Some(&50.0));
+        assert_eq!(rates.get(&RpcEndpointType::Drpc), Some(&12.5));
+        assert!(parse_endpoint_limits::<usize>("helius=-1").is_err());
+        assert!(parse_endpoint_limits::<usize>("quicknode=5").is_err());
+        assert!(parse_endpoint_limits::<usize>("").unwrap().is_empty());
+    }
+}
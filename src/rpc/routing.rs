@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use crate::rpc::rpc_manager::{RpcEndpointType, RpcTaskType};
+
+// Weight of the newest sample in the latency averages
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingPolicy {
+    Preferred,  // Fixed preference order
+    Fastest,    // Lowest average latency for the task type
+    RoundRobin, // Spread requests evenly
+}
+
+impl RoutingPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "preferred" => Some(RoutingPolicy::Preferred),
+            "fastest" => Some(RoutingPolicy::Fastest),
+            "round_robin" => Some(RoutingPolicy::RoundRobin),
+            _ => None,
+        }
+    }
+
+    // Latency-critical task types take the fastest endpoint; bulk ones are spread around
+    pub fn default_for(task_type: RpcTaskType) -> Self {
+        match task_type {
+            RpcTaskType::CriticalRead | RpcTaskType::Execute => RoutingPolicy::Fastest,
+            RpcTaskType::Read | RpcTaskType::Simulate => RoutingPolicy::RoundRobin,
+        }
+    }
+}
+
+// Overrides in the form "read=round_robin,simulate=preferred,critical_read=fastest"
+pub fn parse_routing_policies(value: &str) -> Result<HashMap<RpcTaskType, RoutingPolicy>, String> {
+    let mut policies = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (task, policy) = entry.split_once('=')
+            .ok_or_else(|| format!("expected task=policy, got {}", entry))?;
+        let task_type = RpcTaskType::parse(task)
+            .ok_or_else(|| format!("unknown task type {}", task.trim()))?;
+        let policy = RoutingPolicy::parse(policy)
+            .ok_or_else(|| format!("unknown routing policy {} (expected preferred, fastest or round_robin)", policy.trim()))?;
+        policies.insert(task_type, policy);
+    }
+    Ok(policies)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub endpoint_type: RpcEndpointType,
+    pub healthy: bool,
+    pub latency_ms: f64,
+}
+
+// Order to try the candidates in, given in preference order. Degraded endpoints always go last,
+// in preference order, as a last resort; turn picks the starting point for round robin
+pub fn order_candidates(policy: RoutingPolicy, candidates: Vec<Candidate>, turn: usize) -> Vec<RpcEndpointType> {
+    let (mut healthy, degraded): (Vec<Candidate>, Vec<Candidate>) = candidates.into_iter().partition(|candidate| candidate.healthy);
+    match policy {
+        RoutingPolicy::Preferred => {}
+        RoutingPolicy::Fastest => healthy.sort_by(|a, b| a.latency_ms.total_cmp(&b.latency_ms)),
+        RoutingPolicy::RoundRobin => {
+            if !healthy.is_empty() {
+                let len = healthy.len();
+                healthy.rotate_left(turn % len);
+            }
+        }
+    }
+    healthy.into_iter().chain(degraded).map(|candidate| candidate.endpoint_type).collect()
+}
+
+// Average response latency per endpoint and task type
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTable {
+    averages: HashMap<(RpcEndpointType, RpcTaskType), (f64, u64)>, // (ewma_ms, samples)
+}
+
+impl LatencyTable {
+    pub fn record(&mut self, endpoint_type: RpcEndpointType, task_type: RpcTaskType, latency_ms: f64) {
+        let entry = self.averages.entry((endpoint_type, task_type)).or_insert((latency_ms, 0));
+        entry.0 = LATENCY_EWMA_ALPHA * latency_ms + (1.0 - LATENCY_EWMA_ALPHA) * entry.0;
+        entry.1 += 1;
+    }
+
+    pub fn get(&self, endpoint_type: RpcEndpointType, task_type: RpcTaskType) -> Option<f64> {
+        self.averages.get(&(endpoint_type, task_type)).map(|(ewma, _)| *ewma)
+    }
+
+    // (endpoint, task type, average ms, samples), sorted for stable output
+    pub fn rows(&self) -> Vec<(RpcEndpointType, RpcTaskType, f64, u64)> {
+        let mut rows: Vec<_> = self.averages.iter()
+            .map(|((endpoint_type, task_type), (ewma, samples))| (*endpoint_type, *task_type, *ewma, *samples))
+            .collect();
+        rows.sort_by_key(|(endpoint_type, task_type, _, _)| (format!("{:?}", task_type), format!("{:?}", endpoint_type)));
+        rows
+    }
+
+    // One line per task type: "Read: Helius 85ms (120), Drpc 140ms (118)"
+    pub fn report(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        for task_type in RpcTaskType::ALL {
+            let entries: Vec<String> = self.rows().into_iter()
+                .filter(|(_, task, _, _)| *task == task_type)
+                .map(|(endpoint_type, _, ewma, samples)| format!("{:?} {:.0}ms ({})", endpoint_type, ewma, samples))
+                .collect();
+            if !entries.is_empty() {
+                lines.push(format!("{:?}: {}", task_type, entries.join(", ")));
+            }
+        }
+        if lines.is_empty() { "no RPC requests yet".to_string() } else { lines.join("; ") }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(endpoint_type: RpcEndpointType, healthy: bool, latency_ms: f64) -> Candidate {
+        Candidate { endpoint_type, healthy, latency_ms }
+    }
+
+    #[test]
+    fn test_policies_order_candidates() {
+        let candidates = vec![
+            candidate(RpcEndpointType::Helius, true, 120.0),
+            candidate(RpcEndpointType::Drpc, true, 40.0),
+            candidate(RpcEndpointType::Jito, false, 10.0),
+        ];
+
+        assert_eq!(order_candidates(RoutingPolicy::Preferred, candidates.clone(), 0),
+            vec![RpcEndpointType::Helius, RpcEndpointType::Drpc, RpcEndpointType::Jito]);
+        // Degraded endpoints stay last however fast they were
+        assert_eq!(order_candidates(RoutingPolicy::Fastest, candidates.clone(), 0),
+            vec![RpcEndpointType::Drpc, RpcEndpointType::Helius, RpcEndpointType::Jito]);
+        assert_eq!(order_candidates(RoutingPolicy::RoundRobin, candidates.clone(), 1),
+            vec![RpcEndpointType::Drpc, RpcEndpointType::Helius, RpcEndpointType::Jito]);
+        assert_eq!(order_candidates(RoutingPolicy::RoundRobin, candidates, 2)[0], RpcEndpointType::Helius);
+    }
+
+    #[test]
+    fn test_parses_policy_overrides() {
+        let policies = parse_routing_policies("read=fastest, simulate=preferred").unwrap();
+        assert_eq!(policies.get(&RpcTaskType::Read), Some(&RoutingPolicy::Fastest));
+        assert_eq!(policies.get(&RpcTaskType::Simulate), Some(&RoutingPolicy::Preferred));
+        assert!(parse_routing_policies("read=cheapest").is_err());
+        assert!(parse_routing_policies("writes=fastest").is_err());
+        assert!(parse_routing_policies("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_latency_table_averages_per_task_type() {
+        let mut table = LatencyTable::default();
+        table.record(RpcEndpointType::Helius, RpcTaskType::Read, 100.0);
+        table.record(RpcEndpointType::Helius, RpcTaskType::Read, 200.0);
+        table.record(RpcEndpointType::Helius, RpcTaskType::Simulate, 50.0);
+
+        assert!((table.get(RpcEndpointType::Helius, RpcTaskType::Read).unwrap() - 120.0).abs() < 1e-9);
+        assert_eq!(table.get(RpcEndpointType::Drpc, RpcTaskType::Read), None);
+        assert_eq!(table.report(), "Read: Helius 120ms (2); Simulate: Helius 50ms (1)");
+    }
+}
@@ -1,31 +1,78 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
-use reqwest::Client;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, StatusCode};
 use serde_json::{json, Value};
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
+use crate::config::{Config, RpcConfig};
 use crate::logging::Logger;
+use crate::rpc::batch;
+use crate::rpc::circuit_breaker::{BreakerConfig, BreakerState, BreakerStatus, CircuitBreaker, Transition};
+use crate::rpc::error::RpcError;
+use crate::rpc::rate_limit::{self, EndpointLimiter, RateBudget};
+use crate::rpc::routing::{self, Candidate, LatencyTable, RoutingPolicy};
+use crate::rpc::types::{self, EncodedTransaction, LatestBlockhash, RpcResponse, SignatureStatus};
+use crate::utils::jito_rate_limit;
+use crate::utils::metrics_collector::MetricsCollector;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RpcTaskType {
-    Read,      // getAccountInfo, getMultipleAccounts, getBlock, etc.
-    Simulate,  // simulateTransaction
-    Execute,   // sendTransaction, sendBundle via Jito
+    Read,         // getAccountInfo, getMultipleAccounts, getBlock, etc.
+    CriticalRead, // getLatestBlockhash, getSlot, getSignatureStatuses: on the submission path
+    Simulate,     // simulateTransaction
+    Execute,      // sendTransaction, sendBundle via Jito
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+impl RpcTaskType {
+    pub const ALL: [RpcTaskType; 4] = [RpcTaskType::Read, RpcTaskType::CriticalRead, RpcTaskType::Simulate, RpcTaskType::Execute];
+    
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "read" => Some(RpcTaskType::Read),
+            "critical_read" => Some(RpcTaskType::CriticalRead),
+            "simulate" => Some(RpcTaskType::Simulate),
+            "execute" => Some(RpcTaskType::Execute),
+            _ => None,
+        }
+    }
+    
+    // Endpoints in order of preference for the task
+    fn preference(&self) -> &'static [RpcEndpointType] {
+        match self {
+            RpcTaskType::Read | RpcTaskType::CriticalRead | RpcTaskType::Simulate => &[RpcEndpointType::Helius, RpcEndpointType::Drpc, RpcEndpointType::Jito],
+            RpcTaskType::Execute => &[RpcEndpointType::Jito, RpcEndpointType::Drpc],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RpcEndpointType {
     Helius,
     Jito,
     Drpc,
 }
 
+impl RpcEndpointType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "helius" => Some(RpcEndpointType::Helius),
+            "jito" => Some(RpcEndpointType::Jito),
+            "drpc" => Some(RpcEndpointType::Drpc),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RpcHealthStatus {
     pub latency_ms: f64,
     pub success_rate: f64,
     pub last_check: Instant,
     pub is_healthy: bool,
+    pub consecutive_failures: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -36,171 +83,643 @@ pub struct RpcEndpoint {
     pub weight: f64,  // For load balancing, higher weight = more requests
 }
 
-#[derive(Debug)]
+impl RpcEndpoint {
+    // Endpoints start out healthy so requests flow before the first probe has run
+    pub fn new(url: String, endpoint_type: RpcEndpointType, weight: f64) -> Self {
+        Self {
+            url,
+            endpoint_type,
+            health: RpcHealthStatus {
+                latency_ms: 0.0,
+                success_rate: 1.0,
+                last_check: Instant::now(),
+                is_healthy: true,
+                consecutive_failures: 0,
+            },
+            weight,
+        }
+    }
+}
+
+// Cool-down an endpoint is in after answering with a rate limit
+#[derive(Debug, Clone, Copy)]
+struct Throttle {
+    until: Instant,
+    strikes: u32, // Rate limits in a row, for the backoff when no Retry-After is given
+}
+
+// Doubles with every rate limit in a row, up to the cap
+fn backoff_delay(base: Duration, strikes: u32, max: Duration) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(strikes.saturating_sub(1))).min(max)
+}
+
+fn simulate_transaction_request(transaction_data: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "simulateTransaction",
+        "params": [
+            transaction_data,
+            {
+                "encoding": "base64",
+                "sigVerify": false,
+                "replaceRecentBlockhash": true
+            }
+        ]
+    })
+}
+
+fn simulate_with_accounts_request(transaction_data: &str, accounts: &[String]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "simulateTransaction",
+        "params": [
+            transaction_data,
+            {
+                "encoding": "base64",
+                "commitment": "processed",
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+                "innerInstructions": true,
+                "accounts": {
+                    "encoding": "jsonParsed",
+                    "addresses": accounts
+                }
+            }
+        ]
+    })
+}
+
 pub struct RpcManager {
     client: Arc<Client>,
     endpoints: Arc<RwLock<HashMap<RpcEndpointType, RpcEndpoint>>>,
     health_check_interval: Duration,
+    degraded_after_failures: u32, // Consecutive failures before an endpoint is marked degraded
+    degraded_latency_ms: f64,     // Average latency above which an endpoint is marked degraded
+    failed_over: Arc<RwLock<HashSet<RpcEndpointType>>>, // Endpoints traffic moved off, until they answer again
+    failovers: Arc<AtomicU64>,
+    policies: HashMap<RpcTaskType, RoutingPolicy>, // Overrides of RoutingPolicy::default_for
+    latencies: Arc<RwLock<LatencyTable>>,
+    round_robin: Arc<AtomicUsize>,
+    latency_report_interval: Duration, // Zero disables the periodic latency table log line
+    metrics_collector: Arc<OnceLock<Arc<MetricsCollector>>>,
+    throttles: Arc<RwLock<HashMap<RpcEndpointType, Throttle>>>,
+    retry_budget: u32,          // Retries a request may spend on other endpoints or on waiting out a rate limit
+    retry_base_delay: Duration, // Back-off after a rate limit without Retry-After; doubles on each one in a row
+    max_retry_wait: Duration,   // Longest a request waits for a rate limit to clear before giving up
+    shed_task_types: HashSet<RpcTaskType>, // Low priority: rejected outright while an endpoint is rate limited or over budget
+    rate_limited: Arc<AtomicU64>,
+    shed: Arc<AtomicU64>,
+    max_batch_sizes: HashMap<RpcEndpointType, usize>, // Overrides of batch::DEFAULT_MAX_BATCH_SIZE
+    limiters: HashMap<RpcEndpointType, Arc<EndpointLimiter>>, // Our own per-endpoint request budgets
+    limit_max_wait: Duration, // Longest a request waits on a local budget unless the caller gives a timeout
+    limited_locally: Arc<AtomicU64>,
+    breaker_config: BreakerConfig,
+    breakers: Arc<RwLock<HashMap<RpcEndpointType, CircuitBreaker>>>,
+    breaker_opens: Arc<AtomicU64>,
 }
 
 impl RpcManager {
-    pub async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let client = Arc::new(Client::new());
-        let endpoints = Arc::new(RwLock::new(HashMap::new()));
-        
-        let mut rpc_manager = Self {
-            client,
-            endpoints,
-            health_check_interval: Duration::from_secs(30), // Check every 30 seconds
-        };
+    // HELIUS falls back to SOLANA_RPC_URL, so a dry run against a local validator can stand in for it
+    pub async fn from_config(config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let rpc_manager = Self::tuned(&config.rpc)?;
         
-        // Initialize endpoints from environment variables
-        rpc_manager.load_endpoints_from_env().await?;
-        
-        // Start health checks
-        rpc_manager.start_health_checks().await;
+        {
+            let mut endpoints = rpc_manager.endpoints.write().await;
+            let helius_url = config.rpc.helius.clone().or_else(|| config.rpc.solana_rpc_url.clone())
+                .ok_or("HELIUS is not set")?;
+            endpoints.insert(RpcEndpointType::Helius, RpcEndpoint::new(helius_url, RpcEndpointType::Helius, 1.0));
+            if let Some(jito_url) = config.jito.rpc_url.clone() {
+                endpoints.insert(RpcEndpointType::Jito, RpcEndpoint::new(jito_url, RpcEndpointType::Jito, 1.0));
+            }
+            if let Some(drpc_url) = config.rpc.drpc.clone() {
+                endpoints.insert(RpcEndpointType::Drpc, RpcEndpoint::new(drpc_url, RpcEndpointType::Drpc, 0.5)); // Lower weight as fallback
+            }
+        }
         
         Ok(rpc_manager)
     }
     
-    async fn load_endpoints_from_env(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut endpoints = self.endpoints.write().await;
+    // Health checks and the latency log line, both running until `shutdown` flips to true
+    pub fn start_background_tasks(&self, shutdown: watch::Receiver<bool>) {
+        self.start_health_checks(shutdown.clone());
+        self.start_latency_report(shutdown);
+    }
+    
+    // Health, routing, retry, rate limit and circuit breaker settings; no endpoints yet
+    fn tuned(config: &RpcConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let policies = routing::parse_routing_policies(&config.routing_policy.join(","))
+            .map_err(|e| format!("Invalid RPC_ROUTING_POLICY: {}", e))?;
         
-        // Load HELIUS endpoint
-        let helius_url = std::env::var("HELIUS")
-            .map_err(|_| "HELIUS environment variable not set")?;
-        endpoints.insert(
-            RpcEndpointType::Helius,
-            RpcEndpoint {
-                url: helius_url,
-                endpoint_type: RpcEndpointType::Helius,
-                health: RpcHealthStatus {
-                    latency_ms: 0.0,
-                    success_rate: 1.0,
-                    last_check: Instant::now(),
-                    is_healthy: false,
-                },
-                weight: 1.0,
-            }
-        );
+        let shed_task_types = config.shed_task_types.iter()
+            .map(|task| RpcTaskType::parse(task).ok_or_else(|| format!("Invalid RPC_SHED_TASK_TYPES: unknown task type {}", task)))
+            .collect::<Result<HashSet<RpcTaskType>, String>>()?;
         
-        // Load JITO RPC endpoint
-        let jito_url = std::env::var("JITO_RPC_URL")
-            .map_err(|_| "JITO_RPC_URL environment variable not set")?;
-        endpoints.insert(
-            RpcEndpointType::Jito,
-            RpcEndpoint {
-                url: jito_url,
-                endpoint_type: RpcEndpointType::Jito,
-                health: RpcHealthStatus {
-                    latency_ms: 0.0,
-                    success_rate: 1.0,
-                    last_check: Instant::now(),
-                    is_healthy: false,
-                },
-                weight: 1.0,
-            }
-        );
+        let max_batch_sizes = batch::parse_batch_limits(&config.max_batch_size.join(","))
+            .map_err(|e| format!("Invalid RPC_MAX_BATCH_SIZE: {}", e))?;
         
-        // Load DRPC endpoint
-        let drpc_url = std::env::var("DRPC")
-            .map_err(|_| "DRPC environment variable not set")?;
-        endpoints.insert(
-            RpcEndpointType::Drpc,
-            RpcEndpoint {
-                url: drpc_url,
-                endpoint_type: RpcEndpointType::Drpc,
-                health: RpcHealthStatus {
-                    latency_ms: 0.0,
-                    success_rate: 1.0,
-                    last_check: Instant::now(),
-                    is_healthy: false,
-                },
-                weight: 0.5, // Lower weight as fallback
-            }
-        );
+        let rate_limits = rate_limit::parse_endpoint_limits::<f64>(&config.rate_limits.join(","))
+            .map_err(|e| format!("Invalid RPC_RATE_LIMITS: {}", e))?;
+        let max_in_flight = rate_limit::parse_endpoint_limits::<usize>(&config.max_in_flight.join(","))
+            .map_err(|e| format!("Invalid RPC_MAX_IN_FLIGHT: {}", e))?;
         
-        Ok(())
+        let mut rpc_manager = Self::with_endpoints(Vec::new());
+        rpc_manager.health_check_interval = Duration::from_millis(config.health_check_ms.max(100));
+        rpc_manager.degraded_after_failures = config.degraded_after_failures.max(1);
+        rpc_manager.degraded_latency_ms = config.degraded_latency_ms;
+        rpc_manager.policies = policies;
+        rpc_manager.latency_report_interval = Duration::from_secs(config.latency_report_secs);
+        rpc_manager.retry_budget = config.retry_budget;
+        rpc_manager.retry_base_delay = Duration::from_millis(config.retry_base_ms.max(1));
+        rpc_manager.max_retry_wait = Duration::from_millis(config.max_retry_wait_ms);
+        rpc_manager.shed_task_types = shed_task_types;
+        rpc_manager.max_batch_sizes = max_batch_sizes;
+        rpc_manager.limit_max_wait = Duration::from_millis(config.limit_max_wait_ms);
+        // Failures within the window that open the breaker, and how long it stays open before a
+        // probe request is let through
+        rpc_manager.breaker_config = BreakerConfig {
+            failure_threshold: config.breaker_failures,
+            window: Duration::from_millis(config.breaker_window_ms),
+            cool_down: Duration::from_millis(config.breaker_cooldown_ms),
+        };
+        for endpoint_type in rate_limits.keys().chain(max_in_flight.keys()).copied().collect::<HashSet<_>>() {
+            rpc_manager = rpc_manager.with_rate_limit(endpoint_type, rate_limits.get(&endpoint_type).copied(), max_in_flight.get(&endpoint_type).copied());
+        }
+        
+        Ok(rpc_manager)
+    }
+    
+    // Manager over the given endpoints with default health settings and no background probing
+    pub fn with_endpoints(endpoints: Vec<RpcEndpoint>) -> Self {
+        Self {
+            client: Arc::new(Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| Client::new())),
+            endpoints: Arc::new(RwLock::new(endpoints.into_iter().map(|endpoint| (endpoint.endpoint_type, endpoint)).collect())),
+            health_check_interval: Duration::from_secs(5),
+            degraded_after_failures: 3,
+            degraded_latency_ms: 2000.0,
+            failed_over: Arc::new(RwLock::new(HashSet::new())),
+            failovers: Arc::new(AtomicU64::new(0)),
+            policies: HashMap::new(),
+            latencies: Arc::new(RwLock::new(LatencyTable::default())),
+            round_robin: Arc::new(AtomicUsize::new(0)),
+            latency_report_interval: Duration::ZERO,
+            metrics_collector: Arc::new(OnceLock::new()),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
+            retry_budget: 3,
+            retry_base_delay: Duration::from_millis(100),
+            max_retry_wait: Duration::from_secs(2),
+            shed_task_types: HashSet::new(),
+            rate_limited: Arc::new(AtomicU64::new(0)),
+            shed: Arc::new(AtomicU64::new(0)),
+            max_batch_sizes: HashMap::new(),
+            limiters: HashMap::new(),
+            limit_max_wait: Duration::from_millis(500),
+            limited_locally: Arc::new(AtomicU64::new(0)),
+            breaker_config: BreakerConfig::default(),
+            breakers: Arc::new(RwLock::new(HashMap::new())),
+            breaker_opens: Arc::new(AtomicU64::new(0)),
+        }
+    }
+    
+    pub fn with_routing_policy(mut self, task_type: RpcTaskType, policy: RoutingPolicy) -> Self {
+        self.policies.insert(task_type, policy);
+        self
+    }
+    
+    pub fn with_load_shedding(mut self, task_type: RpcTaskType) -> Self {
+        self.shed_task_types.insert(task_type);
+        self
+    }
+    
+    pub fn with_max_batch_size(mut self, endpoint_type: RpcEndpointType, max_batch_size: usize) -> Self {
+        self.max_batch_sizes.insert(endpoint_type, max_batch_size.max(1));
+        self
+    }
+    
+    pub fn with_circuit_breaker(mut self, config: BreakerConfig) -> Self {
+        self.breaker_config = config;
+        self
+    }
+    
+    pub fn with_rate_limit(mut self, endpoint_type: RpcEndpointType, requests_per_sec: Option<f64>, max_in_flight: Option<usize>) -> Self {
+        self.limiters.insert(endpoint_type, Arc::new(EndpointLimiter::new(requests_per_sec, max_in_flight)));
+        self
+    }
+    
+    // Every request is reported to the collector once it is set
+    pub fn set_metrics_collector(&self, metrics_collector: Arc<MetricsCollector>) {
+        let _ = self.metrics_collector.set(metrics_collector);
+    }
+    
+    pub fn routing_policy(&self, task_type: RpcTaskType) -> RoutingPolicy {
+        self.policies.get(&task_type).copied().unwrap_or_else(|| RoutingPolicy::default_for(task_type))
     }
     
     pub async fn get_best_rpc(&self, task_type: RpcTaskType) -> Option<RpcEndpoint> {
         let endpoints = self.endpoints.read().await;
-        
-        match task_type {
-            RpcTaskType::Read | RpcTaskType::Simulate => {
-                // Prefer HELIUS for reads and simulations
-                if let Some(helius) = endpoints.get(&RpcEndpointType::Helius) {
-                    if helius.health.is_healthy {
-                        return Some(helius.clone());
-                    }
+        task_type.preference().iter()
+            .filter_map(|endpoint_type| endpoints.get(endpoint_type))
+            .find(|endpoint| endpoint.health.is_healthy)
+            .cloned()
+    }
+    
+    // Configured endpoints from the preference list, ordered by the task type's routing policy;
+    // degraded endpoints stay at the back as a last resort rather than failing the request outright.
+    // An endpoint with no latency for the task yet is judged by its health-check latency
+    async fn candidates(&self, task_type: RpcTaskType, preference: &[RpcEndpointType]) -> Vec<RpcEndpointType> {
+        let candidates: Vec<Candidate> = {
+            let endpoints = self.endpoints.read().await;
+            let latencies = self.latencies.read().await;
+            preference.iter()
+                .filter_map(|endpoint_type| endpoints.get(endpoint_type))
+                .map(|endpoint| Candidate {
+                    endpoint_type: endpoint.endpoint_type,
+                    healthy: endpoint.health.is_healthy,
+                    latency_ms: latencies.get(endpoint.endpoint_type, task_type).unwrap_or(endpoint.health.latency_ms),
+                })
+                .collect()
+        };
+        let turn = self.round_robin.fetch_add(1, Ordering::Relaxed);
+        routing::order_candidates(self.routing_policy(task_type), candidates, turn)
+    }
+    
+    // The configured endpoints that may serve the task, best first
+    pub async fn endpoints_for(&self, task_type: RpcTaskType) -> Vec<RpcEndpointType> {
+        self.candidates(task_type, task_type.preference()).await
+    }
+    
+    // Sends the request to the healthiest endpoint for the task, retrying on the next one when it
+    // fails and waiting out rate limits within the retry budget. JSON-RPC errors from the node come
+    // back as RpcError::Rpc without a retry; they aren't the endpoint's fault
+    pub async fn make_request(&self, task_type: RpcTaskType, request_body: Value) -> Result<Value, RpcError> {
+        self.route(task_type, task_type.preference(), request_body, Instant::now() + self.limit_max_wait).await
+    }
+    
+    // make_request for a caller with its own deadline: waits on local budgets and the request
+    // itself both count against the timeout
+    pub async fn make_request_within(&self, task_type: RpcTaskType, request_body: Value, timeout: Duration) -> Result<Value, RpcError> {
+        tokio::time::timeout(timeout, self.route(task_type, task_type.preference(), request_body, Instant::now() + timeout)).await
+            .unwrap_or(Err(RpcError::Timeout))
+    }
+    
+    // limit_deadline bounds how long a request waits on an endpoint's local budget; low-priority
+    // task types don't wait at all
+    async fn route(&self, task_type: RpcTaskType, preference: &[RpcEndpointType], request_body: Value, limit_deadline: Instant) -> Result<Value, RpcError> {
+        let mut attempts: u32 = 0;
+        let mut failed: HashSet<RpcEndpointType> = HashSet::new();
+        let mut last_error: Option<(RpcEndpointType, RpcError)> = None;
+        loop {
+            let candidates = self.candidates(task_type, preference).await;
+            if candidates.is_empty() {
+                return Err(RpcError::Transport(format!("No RPC endpoint configured for {:?} requests", task_type)));
+            }
+            let throttled = self.throttled_endpoints().await;
+            
+            // Low-priority work steps aside while a provider is throttling, leaving it the headroom
+            // for the requests that can't wait
+            if self.shed_task_types.contains(&task_type) {
+                if let Some(retry_after) = candidates.iter().filter_map(|endpoint_type| throttled.get(endpoint_type)).max() {
+                    self.shed.fetch_add(1, Ordering::Relaxed);
+                    return Err(RpcError::RateLimited { retry_after: Some(*retry_after) });
                 }
-                
-                // Fallback to DRPC for reads/simulations
-                if let Some(drpc) = endpoints.get(&RpcEndpointType::Drpc) {
-                    if drpc.health.is_healthy {
-                        return Some(drpc.clone());
-                    }
+            }
+            
+            // Endpoints whose breaker is open are skipped without paying for another timeout
+            let mut next = None;
+            let mut open_circuits = Vec::new();
+            for endpoint_type in candidates.iter().copied().filter(|endpoint_type| !failed.contains(endpoint_type) && !throttled.contains_key(endpoint_type)) {
+                if self.breaker_allows(endpoint_type).await {
+                    next = Some(endpoint_type);
+                    break;
                 }
-                
-                // If HELIUS is down, try JITO as last resort for reads
-                if let Some(jito) = endpoints.get(&RpcEndpointType::Jito) {
-                    if jito.health.is_healthy {
-                        return Some(jito.clone());
+                open_circuits.push(endpoint_type);
+            }
+            let endpoint_type = match next {
+                Some(endpoint_type) => endpoint_type,
+                None => {
+                    // Whatever hasn't failed outright is cooling down: wait for the first one back,
+                    // if the budget allows another attempt and the wait is short enough
+                    let wait = candidates.iter()
+                        .filter(|endpoint_type| !failed.contains(endpoint_type))
+                        .filter_map(|endpoint_type| throttled.get(endpoint_type))
+                        .min()
+                        .copied();
+                    match wait {
+                        Some(wait) if attempts <= self.retry_budget && wait <= self.max_retry_wait => {
+                            tokio::time::sleep(wait).await;
+                            continue;
+                        }
+                        Some(wait) => return Err(RpcError::RateLimited { retry_after: Some(wait) }),
+                        None if last_error.is_none() && !open_circuits.is_empty() => {
+                            return Err(RpcError::Transport(format!("Circuit breaker open for {:?}", open_circuits)));
+                        }
+                        None => return Err(last_error.map(|(_, e)| e)
+                            .unwrap_or_else(|| RpcError::Transport("RPC request failed on every endpoint".to_string()))),
                     }
                 }
-            },
-            RpcTaskType::Execute => {
-                // Prefer JITO for execution
-                if let Some(jito) = endpoints.get(&RpcEndpointType::Jito) {
-                    if jito.health.is_healthy {
-                        return Some(jito.clone());
-                    }
+            };
+            
+            if let Some((failed_endpoint, e)) = &last_error {
+                if attempts > self.retry_budget {
+                    return Err(e.clone());
+                }
+                if *failed_endpoint != endpoint_type {
+                    self.note_failover(task_type, *failed_endpoint, endpoint_type, &e.to_string()).await;
                 }
-                
-                // Fallback to DRPC for execution only if JITO unavailable
-                if let Some(drpc) = endpoints.get(&RpcEndpointType::Drpc) {
-                    if drpc.health.is_healthy {
-                        return Some(drpc.clone());
+            }
+            attempts += 1;
+            
+            // The budget is the chosen endpoint's, so a request over it moves on to the next one
+            let limit_wait = if self.shed_task_types.contains(&task_type) {
+                Duration::ZERO
+            } else {
+                limit_deadline.saturating_duration_since(Instant::now())
+            };
+            match self.request_to(endpoint_type, Some(task_type), request_body.clone(), limit_wait).await {
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_retryable() => {
+                    // Rate-limited endpoints come back once their cool-down is over
+                    if !matches!(e, RpcError::RateLimited { .. }) {
+                        failed.insert(endpoint_type);
                     }
+                    last_error = Some((endpoint_type, e));
                 }
-            },
+                Err(e) => return Err(e),
+            }
         }
-        
-        None
     }
     
-    pub async fn make_request(&self, endpoint_type: RpcEndpointType, request_body: Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        let endpoint = {
+    // Several requests sent as JSON-RPC batches, routed like make_request; one result per request,
+    // in order, with each request's own error kept. Batches are split to the smallest limit among
+    // the endpoints that may serve the task, so a failover can't overflow the next endpoint. Each
+    // batch takes one request from the endpoint's local budget
+    pub async fn make_batch_request(&self, task_type: RpcTaskType, requests: Vec<Value>) -> Vec<Result<Value, RpcError>> {
+        let preference = task_type.preference();
+        let max_batch_size = {
             let endpoints = self.endpoints.read().await;
-            match endpoints.get(&endpoint_type) {
-                Some(ep) => ep.clone(),
-                None => return Err(format!("RPC endpoint {:?} not configured", endpoint_type).into()),
+            preference.iter()
+                .filter(|endpoint_type| endpoints.contains_key(endpoint_type))
+                .map(|endpoint_type| self.max_batch_size(*endpoint_type))
+                .min()
+                .unwrap_or(batch::DEFAULT_MAX_BATCH_SIZE)
+        };
+        let limit_deadline = Instant::now() + self.limit_max_wait;
+        Self::send_batches(requests, max_batch_size, |body| self.route(task_type, preference, body, limit_deadline)).await
+    }
+    
+    // Batches pinned to one endpoint, without retries
+    pub async fn make_batch_request_to(&self, endpoint_type: RpcEndpointType, requests: Vec<Value>) -> Vec<Result<Value, RpcError>> {
+        Self::send_batches(requests, self.max_batch_size(endpoint_type), |body| self.pinned_request(endpoint_type, body)).await
+    }
+    
+    fn max_batch_size(&self, endpoint_type: RpcEndpointType) -> usize {
+        self.max_batch_sizes.get(&endpoint_type).copied().unwrap_or(batch::DEFAULT_MAX_BATCH_SIZE)
+    }
+    
+    // Chunks go out concurrently; a chunk that fails as a whole fails each of its requests
+    async fn send_batches<F, Fut>(requests: Vec<Value>, max_batch_size: usize, send: F) -> Vec<Result<Value, RpcError>>
+    where
+        F: Fn(Value) -> Fut,
+        Fut: Future<Output = Result<Value, RpcError>>,
+    {
+        let max_batch_size = max_batch_size.max(1);
+        let requests = batch::with_ids(requests, 0);
+        let chunks = requests.chunks(max_batch_size).enumerate().map(|(index, chunk)| {
+            let (first_id, count) = (index * max_batch_size, chunk.len());
+            let response = send(Value::Array(chunk.to_vec()));
+            async move {
+                match response.await {
+                    Ok(response) => batch::match_responses(&response, first_id, count),
+                    Err(e) => vec![Err(e); count],
+                }
+            }
+        });
+        futures::future::join_all(chunks).await.into_iter().flatten().collect()
+    }
+    
+    // Counts every retry on an alternate endpoint, but logs an endpoint's failover only once
+    // until it answers again
+    async fn note_failover(&self, task_type: RpcTaskType, failed: RpcEndpointType, alternate: RpcEndpointType, error: &str) {
+        self.failovers.fetch_add(1, Ordering::Relaxed);
+        if self.failed_over.write().await.insert(failed) {
+            Logger::error_occurred(&format!("RPC failover: {:?} request failed on {:?} ({}), retrying on {:?}", task_type, failed, error, alternate));
+        }
+    }
+    
+    // Requests retried on another endpoint after their first choice failed, since startup
+    pub fn failover_count(&self) -> u64 {
+        self.failovers.load(Ordering::Relaxed)
+    }
+    
+    // Rate-limit answers (429s and provider rate-limit errors) received since startup
+    pub fn rate_limited_count(&self) -> u64 {
+        self.rate_limited.load(Ordering::Relaxed)
+    }
+    
+    // Requests turned away without being sent because their endpoints were throttling
+    pub fn shed_count(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+    
+    // Requests held back because their endpoint was over its local budget
+    pub fn limited_locally_count(&self) -> u64 {
+        self.limited_locally.load(Ordering::Relaxed)
+    }
+    
+    // Remaining local budget of every rate-limited endpoint
+    pub fn rate_budgets(&self) -> Vec<RateBudget> {
+        let mut budgets: Vec<RateBudget> = self.limiters.iter()
+            .map(|(endpoint_type, limiter)| limiter.budget(*endpoint_type))
+            .collect();
+        budgets.sort_by_key(|budget| format!("{:?}", budget.endpoint));
+        budgets
+    }
+    
+    // Times an endpoint's circuit breaker opened since startup
+    pub fn breaker_open_count(&self) -> u64 {
+        self.breaker_opens.load(Ordering::Relaxed)
+    }
+    
+    // Breaker state of every endpoint that has handled a request
+    pub async fn breaker_statuses(&self) -> Vec<BreakerStatus> {
+        let mut statuses: Vec<BreakerStatus> = self.breakers.read().await.iter()
+            .map(|(endpoint_type, breaker)| BreakerStatus { endpoint: *endpoint_type, state: breaker.state() })
+            .collect();
+        statuses.sort_by_key(|status| format!("{:?}", status.endpoint));
+        statuses
+    }
+    
+    // Lets the request through the endpoint's breaker, making it the probe when the breaker is
+    // ready to try the endpoint again; false while it is open
+    async fn breaker_allows(&self, endpoint_type: RpcEndpointType) -> bool {
+        let now = Instant::now();
+        let (allowed, transition) = self.breakers.write().await
+            .entry(endpoint_type)
+            .or_insert_with(|| CircuitBreaker::new(self.breaker_config, now))
+            .allow_at(now);
+        if let Some(transition) = transition {
+            self.log_breaker_transition(endpoint_type, transition);
+        }
+        allowed
+    }
+    
+    async fn record_breaker_outcome(&self, endpoint_type: RpcEndpointType, success: bool) {
+        let now = Instant::now();
+        let transition = {
+            let mut breakers = self.breakers.write().await;
+            let breaker = breakers.entry(endpoint_type).or_insert_with(|| CircuitBreaker::new(self.breaker_config, now));
+            if success { breaker.record_success_at(now) } else { breaker.record_failure_at(now) }
+        };
+        if let Some(transition) = transition {
+            if transition.1 == BreakerState::Open {
+                self.breaker_opens.fetch_add(1, Ordering::Relaxed);
             }
+            self.log_breaker_transition(endpoint_type, transition);
+        }
+    }
+    
+    fn log_breaker_transition(&self, endpoint_type: RpcEndpointType, (from, to): Transition) {
+        match to {
+            BreakerState::Open => Logger::error_occurred(&format!(
+                "RPC circuit breaker for {:?} opened ({:?} -> Open); skipping it for {}ms",
+                endpoint_type, from, self.breaker_config.cool_down.as_millis()
+            )),
+            BreakerState::HalfOpen => Logger::status_update(&format!("RPC circuit breaker for {:?} half-open, sending a probe", endpoint_type)),
+            BreakerState::Closed => Logger::status_update(&format!("RPC circuit breaker for {:?} closed, probe answered", endpoint_type)),
+        }
+    }
+    
+    // Endpoints still cooling down from a rate limit, and for how much longer
+    async fn throttled_endpoints(&self) -> HashMap<RpcEndpointType, Duration> {
+        let now = Instant::now();
+        self.throttles.read().await.iter()
+            .filter(|(_, throttle)| throttle.until > now)
+            .map(|(endpoint_type, throttle)| (*endpoint_type, throttle.until - now))
+            .collect()
+    }
+    
+    // Keeps requests off the endpoint for its Retry-After, or an exponential back-off without one
+    async fn throttle(&self, endpoint_type: RpcEndpointType, retry_after: Option<Duration>) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let mut throttles = self.throttles.write().await;
+        let throttle = throttles.entry(endpoint_type).or_insert(Throttle { until: now, strikes: 0 });
+        throttle.strikes += 1;
+        let cool_down = retry_after.unwrap_or_else(|| backoff_delay(self.retry_base_delay, throttle.strikes, self.max_retry_wait));
+        throttle.until = now + cool_down;
+        if throttle.strikes == 1 {
+            Logger::status_update(&format!("RPC endpoint {:?} rate limited, backing off for {}ms", endpoint_type, cool_down.as_millis()));
+        }
+    }
+    
+    // Sends the request to one specific endpoint, without retries; transport errors, 5xx responses
+    // and unparsable bodies count against its health
+    pub async fn make_request_to(&self, endpoint_type: RpcEndpointType, request_body: Value) -> Result<Value, RpcError> {
+        self.pinned_request(endpoint_type, request_body).await
+    }
+    
+    async fn pinned_request(&self, endpoint_type: RpcEndpointType, request_body: Value) -> Result<Value, RpcError> {
+        if !self.breaker_allows(endpoint_type).await {
+            return Err(RpcError::Transport(format!("Circuit breaker open for {:?}", endpoint_type)));
+        }
+        self.request_to(endpoint_type, None, request_body, self.limit_max_wait).await
+    }
+    
+    async fn request_to(&self, endpoint_type: RpcEndpointType, task_type: Option<RpcTaskType>, request_body: Value, limit_wait: Duration) -> Result<Value, RpcError> {
+        // Held until the answer is in, so it counts against the endpoint's in-flight cap
+        let _permit = match self.limiters.get(&endpoint_type) {
+            Some(limiter) => match limiter.acquire(limit_wait).await {
+                Ok(permit) => Some(permit),
+                Err(e) => {
+                    self.limited_locally.fetch_add(1, Ordering::Relaxed);
+                    if let Some(breaker) = self.breakers.write().await.get_mut(&endpoint_type) {
+                        breaker.abandon_probe();
+                    }
+                    return Err(e);
+                }
+            },
+            None => None,
         };
         
         let start_time = Instant::now();
+        let result = self.send(endpoint_type, &request_body).await;
+        let elapsed = start_time.elapsed().as_secs_f64() * 1000.0;
+        
+        let (result, bytes_received) = match result {
+            Ok((response, bytes)) => match response.get("error") {
+                Some(error) => (Err(RpcError::from_json_rpc(error)), bytes),
+                None => (Ok(response), bytes),
+            },
+            Err(e) => (Err(e), 0),
+        };
+        
+        // A node error is still an answer; a rate limit says nothing about the endpoint's health
+        match &result {
+            Ok(_) | Err(RpcError::Rpc { .. }) => {
+                self.update_health(endpoint_type, elapsed, true).await;
+                if let Some(task_type) = task_type {
+                    self.latencies.write().await.record(endpoint_type, task_type, elapsed);
+                }
+                if self.throttles.read().await.contains_key(&endpoint_type) {
+                    self.throttles.write().await.remove(&endpoint_type);
+                }
+                if self.failed_over.read().await.contains(&endpoint_type) {
+                    self.failed_over.write().await.remove(&endpoint_type);
+                    Logger::status_update(&format!("RPC endpoint {:?} answering again", endpoint_type));
+                }
+            }
+            Err(RpcError::RateLimited { retry_after }) => self.throttle(endpoint_type, *retry_after).await,
+            Err(_) => self.update_health(endpoint_type, elapsed, false).await,
+        }
+        // Any answer, even a rate limit, shows the endpoint is up; only timeouts and transport
+        // failures count towards opening its breaker
+        self.record_breaker_outcome(endpoint_type, !matches!(result, Err(RpcError::Timeout) | Err(RpcError::Transport(_)))).await;
+        if let Some(metrics_collector) = self.metrics_collector.get() {
+            metrics_collector.record_rpc_call(&format!("{:?}", endpoint_type), result.is_ok(), elapsed, request_body.to_string().len() as u64, bytes_received as u64).await;
+        }
+        
+        result.map_err(|e| match e {
+            RpcError::Transport(detail) => RpcError::Transport(format!("{:?}: {}", endpoint_type, detail)),
+            e => e,
+        })
+    }
+    
+    // Parsed response and its size in bytes
+    async fn send(&self, endpoint_type: RpcEndpointType, request_body: &Value) -> Result<(Value, usize), RpcError> {
+        let url = match self.endpoints.read().await.get(&endpoint_type) {
+            Some(endpoint) => endpoint.url.clone(),
+            None => return Err(RpcError::Transport(format!("RPC endpoint {:?} not configured", endpoint_type))),
+        };
         
         let response = self.client
-            .post(&endpoint.url)
-            .json(&request_body)
+            .post(&url)
+            .json(request_body)
             .send()
             .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .map_err(|e| if e.is_timeout() { RpcError::Timeout } else { RpcError::Transport(format!("HTTP request failed: {}", e)) })?;
         
-        let elapsed = start_time.elapsed().as_millis() as f64;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = jito_rate_limit::parse_retry_after(response.headers().get(RETRY_AFTER).and_then(|value| value.to_str().ok()));
+            return Err(RpcError::RateLimited { retry_after });
+        }
+        if status.is_server_error() {
+            return Err(RpcError::Transport(format!("HTTP request failed with status {}", status)));
+        }
         
         let response_text = response.text().await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
-        
-        let response_value: Value = serde_json::from_str(&response_text)
-            .map_err(|e| format!("Failed to parse response as JSON: {}", e))?;
-        
-        // Update health metrics based on success
-        self.update_health(endpoint_type, elapsed, true).await;
+            .map_err(|e| if e.is_timeout() { RpcError::Timeout } else { RpcError::Transport(format!("Failed to read response: {}", e)) })?;
         
-        Ok(response_value)
+        serde_json::from_str(&response_text)
+            .map(|response| (response, response_text.len()))
+            .map_err(|e| RpcError::Transport(format!("Failed to parse response as JSON: {}", e)))
     }
     
+    // getHealth, falling back to getSlot for endpoints that don't implement it
     pub async fn health_check(&self, endpoint_type: RpcEndpointType) -> Result<RpcHealthStatus, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = Instant::now();
         
@@ -211,12 +730,13 @@ impl RpcManager {
             "params": []
         });
         
-        let success = match self.make_request(endpoint_type.clone(), test_request).await {
-            Ok(response) => {
-                // Check if response indicates healthy status
-                response["result"]["health"].as_str().map(|s| s == "ok").unwrap_or(false)
-            },
-            Err(_) => false,
+        let (response, _) = self.send(endpoint_type, &test_request).await?;
+        let success = if response["error"]["code"].as_i64() == Some(-32601) {
+            let slot_request = json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot", "params": [] });
+            self.send(endpoint_type, &slot_request).await?.0["result"].as_u64().is_some()
+        } else {
+            // A node that is behind answers with an error instead of "ok"
+            response["result"].as_str() == Some("ok")
         };
         
         let latency = start_time.elapsed().as_millis() as f64;
@@ -226,6 +746,7 @@ impl RpcManager {
             success_rate: if success { 1.0 } else { 0.0 },
             last_check: Instant::now(),
             is_healthy: success,
+            consecutive_failures: if success { 0 } else { 1 },
         })
     }
     
@@ -233,51 +754,78 @@ impl RpcManager {
         let mut endpoints = self.endpoints.write().await;
         
         if let Some(endpoint) = endpoints.get_mut(&endpoint_type) {
+            let health = &mut endpoint.health;
             // Update rolling average for success rate (simple exponentially weighted)
-            endpoint.health.success_rate = 0.9 * endpoint.health.success_rate + 0.1 * if success { 1.0 } else { 0.0 };
+            health.success_rate = 0.9 * health.success_rate + 0.1 * if success { 1.0 } else { 0.0 };
             
             // Update latency (simple average of recent measurements)
-            endpoint.health.latency_ms = (endpoint.health.latency_ms + latency_ms) / 2.0;
-            endpoint.health.last_check = Instant::now();
-            endpoint.health.is_healthy = success && latency_ms < 2000.0; // Healthy if under 2s latency
+            health.latency_ms = (health.latency_ms + latency_ms) / 2.0;
+            health.last_check = Instant::now();
+            health.consecutive_failures = if success { 0 } else { health.consecutive_failures + 1 };
+            
+            let was_healthy = health.is_healthy;
+            health.is_healthy = health.consecutive_failures < self.degraded_after_failures
+                && health.latency_ms < self.degraded_latency_ms;
+            if was_healthy && !health.is_healthy {
+                Logger::error_occurred(&format!(
+                    "RPC endpoint {:?} degraded ({} consecutive failures, {:.0}ms average latency)",
+                    endpoint_type, health.consecutive_failures, health.latency_ms
+                ));
+            } else if !was_healthy && health.is_healthy {
+                Logger::status_update(&format!("RPC endpoint {:?} healthy again", endpoint_type));
+            }
         }
     }
     
-    async fn start_health_checks(&self) {
+    fn start_health_checks(&self, mut shutdown: watch::Receiver<bool>) {
         let self_clone = self.clone_for_spawn();
         
         tokio::spawn(async move {
             loop {
                 self_clone.run_health_checks().await;
-                tokio::time::sleep(Duration::from_secs(30)).await; // Check every 30 seconds
+                tokio::select! {
+                    _ = tokio::time::sleep(self_clone.health_check_interval) => {},
+                    _ = shutdown.changed() => return,
+                }
+            }
+        });
+    }
+    
+    // Current average latency per endpoint and task type, as one line
+    pub async fn latency_report(&self) -> String {
+        self.latencies.read().await.report()
+    }
+    
+    fn start_latency_report(&self, mut shutdown: watch::Receiver<bool>) {
+        if self.latency_report_interval.is_zero() {
+            return;
+        }
+        let self_clone = self.clone_for_spawn();
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self_clone.latency_report_interval);
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                Logger::status_update(&format!("RPC latency: {}", self_clone.latency_report().await));
             }
         });
     }
     
     async fn run_health_checks(&self) {
-        let endpoint_types = vec![
-            RpcEndpointType::Helius,
-            RpcEndpointType::Jito,
-            RpcEndpointType::Drpc,
-        ];
+        let endpoint_types: Vec<RpcEndpointType> = self.endpoints.read().await.keys().copied().collect();
         
         for endpoint_type in endpoint_types {
-            match self.health_check(endpoint_type.clone()).await {
+            match self.health_check(endpoint_type).await {
                 Ok(health_status) => {
-                    let mut endpoints = self.endpoints.write().await;
-                    if let Some(endpoint) = endpoints.get_mut(&endpoint_type) {
-                        endpoint.health = health_status;
-                    }
+                    self.update_health(endpoint_type, health_status.latency_ms, health_status.is_healthy).await;
                 },
                 Err(e) => {
-                    Logger::error_occurred(&format!("Health check failed for {:?}: {}", endpoint_type, e));
-                    
-                    // Mark as unhealthy
-                    let mut endpoints = self.endpoints.write().await;
-                    if let Some(endpoint) = endpoints.get_mut(&endpoint_type) {
-                        endpoint.health.is_healthy = false;
-                        endpoint.health.last_check = Instant::now();
-                    }
+                    Logger::status_update(&format!("Health check failed for {:?}: {}", endpoint_type, e));
+                    self.update_health(endpoint_type, 0.0, false).await;
                 }
             }
         }
@@ -289,11 +837,34 @@ impl RpcManager {
             client: Arc::clone(&self.client),
             endpoints: Arc::clone(&self.endpoints),
             health_check_interval: self.health_check_interval,
+            degraded_after_failures: self.degraded_after_failures,
+            degraded_latency_ms: self.degraded_latency_ms,
+            failed_over: Arc::clone(&self.failed_over),
+            failovers: Arc::clone(&self.failovers),
+            policies: self.policies.clone(),
+            latencies: Arc::clone(&self.latencies),
+            round_robin: Arc::clone(&self.round_robin),
+            latency_report_interval: self.latency_report_interval,
+            metrics_collector: Arc::clone(&self.metrics_collector),
+            throttles: Arc::clone(&self.throttles),
+            retry_budget: self.retry_budget,
+            retry_base_delay: self.retry_base_delay,
+            max_retry_wait: self.max_retry_wait,
+            shed_task_types: self.shed_task_types.clone(),
+            rate_limited: Arc::clone(&self.rate_limited),
+            shed: Arc::clone(&self.shed),
+            max_batch_sizes: self.max_batch_sizes.clone(),
+            limiters: self.limiters.clone(),
+            limit_max_wait: self.limit_max_wait,
+            limited_locally: Arc::clone(&self.limited_locally),
+            breaker_config: self.breaker_config,
+            breakers: Arc::clone(&self.breakers),
+            breaker_opens: Arc::clone(&self.breaker_opens),
         }
     }
     
     // Convenience methods for specific RPC calls
-    pub async fn get_account_info(&self, account: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_account_info(&self, account: &str) -> Result<Value, RpcError> {
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -306,46 +877,246 @@ impl RpcManager {
             ]
         });
         
-        let endpoint = self.get_best_rpc(RpcTaskType::Read).await
-            .ok_or("No healthy read endpoint available")?;
+        self.make_request(RpcTaskType::Read, request_body).await
+    }
+    
+    // Raw account bytes for programs the RPC can't jsonParse (AMM pools, bonding curves, etc.)
+    pub async fn get_account_data(&self, account: &str) -> Result<Vec<u8>, RpcError> {
+        use base64::Engine;
         
-        let response = self.make_request(endpoint.endpoint_type, request_body).await?;
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [
+                account,
+                {
+                    "encoding": "base64"
+                }
+            ]
+        });
+        
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        
+        let encoded = response["result"]["value"]["data"][0].as_str()
+            .ok_or_else(|| RpcError::InvalidResponse(format!("Account {} not found or has no data", account)))?;
         
-        if let Some(error) = response.get("error") {
-            return Err(format!("getAccountInfo failed: {}", error).into());
+        base64::engine::general_purpose::STANDARD.decode(encoded)
+            .map_err(|e| RpcError::InvalidResponse(format!("Failed to decode account data for {}: {}", account, e)))
+    }
+    
+    // Raw bytes of several accounts in one round trip; None for accounts that don't exist
+    pub async fn get_multiple_accounts_data(&self, accounts: &[String]) -> Result<Vec<Option<Vec<u8>>>, RpcError> {
+        use base64::Engine;
+        
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [
+                accounts,
+                {
+                    "encoding": "base64"
+                }
+            ]
+        });
+        
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        
+        let values = response["result"]["value"].as_array()
+            .ok_or_else(|| RpcError::InvalidResponse("getMultipleAccounts returned no account list".to_string()))?;
+        if values.len() != accounts.len() {
+            return Err(RpcError::InvalidResponse(format!("getMultipleAccounts returned {} accounts, expected {}", values.len(), accounts.len())));
         }
         
-        Ok(response)
+        values.iter().zip(accounts)
+            .map(|(value, account)| match value["data"][0].as_str() {
+                Some(encoded) => base64::engine::general_purpose::STANDARD.decode(encoded)
+                    .map(Some)
+                    .map_err(|e| RpcError::InvalidResponse(format!("Failed to decode account data for {}: {}", account, e))),
+                None => Ok(None),
+            })
+            .collect()
     }
     
-    pub async fn simulate_transaction(&self, transaction_data: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    // jsonParsed state of several accounts in one round trip, at processed; null for accounts that
+    // don't exist
+    pub async fn get_multiple_accounts_parsed(&self, accounts: &[String]) -> Result<Vec<Value>, RpcError> {
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "simulateTransaction",
+            "method": "getMultipleAccounts",
             "params": [
-                transaction_data,
+                accounts,
+                {
+                    "encoding": "jsonParsed",
+                    "commitment": "processed"
+                }
+            ]
+        });
+        
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        
+        let values = response["result"]["value"].as_array()
+            .ok_or_else(|| RpcError::InvalidResponse("getMultipleAccounts returned no account list".to_string()))?;
+        if values.len() != accounts.len() {
+            return Err(RpcError::InvalidResponse(format!("getMultipleAccounts returned {} accounts, expected {}", values.len(), accounts.len())));
+        }
+        Ok(values.clone())
+    }
+    
+    // Addresses and raw balances of a mint's largest token accounts (up to 20), largest first
+    pub async fn get_token_largest_accounts(&self, mint: &str) -> Result<Vec<(String, u64)>, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTokenLargestAccounts",
+            "params": [mint]
+        });
+        
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        
+        let accounts = response["result"]["value"].as_array()
+            .ok_or_else(|| RpcError::InvalidResponse(format!("getTokenLargestAccounts returned no accounts for {}", mint)))?;
+        accounts.iter()
+            .map(|account| {
+                let address = account["address"].as_str().unwrap_or_default().to_string();
+                account["amount"].as_str()
+                    .and_then(|amount| amount.parse::<u64>().ok())
+                    .map(|amount| (address, amount))
+                    .ok_or_else(|| RpcError::InvalidResponse(format!("Invalid token account amount for {}", mint)))
+            })
+            .collect()
+    }
+    
+    // jsonParsed token accounts the owner holds under one token program, at processed
+    pub async fn get_token_accounts_by_owner(&self, owner: &str, program_id: &str) -> Result<Vec<Value>, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTokenAccountsByOwner",
+            "params": [
+                owner,
+                { "programId": program_id },
+                {
+                    "encoding": "jsonParsed",
+                    "commitment": "processed"
+                }
+            ]
+        });
+        
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        
+        let values = response["result"]["value"].as_array()
+            .ok_or_else(|| RpcError::InvalidResponse("getTokenAccountsByOwner returned no account list".to_string()))?;
+        Ok(values.iter().map(|entry| entry["account"].clone()).collect())
+    }
+    
+    // Address and raw bytes of every account owned by a program that matches the filters
+    // (dataSize / memcmp objects as the RPC expects them)
+    pub async fn get_program_accounts_data(&self, program_id: &str, filters: Value) -> Result<Vec<(String, Vec<u8>)>, RpcError> {
+        use base64::Engine;
+        
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getProgramAccounts",
+            "params": [
+                program_id,
                 {
                     "encoding": "base64",
-                    "sigVerify": false,
-                    "replaceRecentBlockhash": true
+                    "filters": filters
                 }
             ]
         });
         
-        let endpoint = self.get_best_rpc(RpcTaskType::Simulate).await
-            .ok_or("No healthy simulation endpoint available")?;
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
         
-        let response = self.make_request(endpoint.endpoint_type, request_body).await?;
+        let accounts = response["result"].as_array()
+            .ok_or_else(|| RpcError::InvalidResponse("getProgramAccounts returned no account list".to_string()))?;
         
-        if let Some(error) = response.get("error") {
-            return Err(format!("simulateTransaction failed: {}", error).into());
-        }
+        accounts.iter()
+            .map(|account| {
+                let address = account["pubkey"].as_str()
+                    .ok_or_else(|| RpcError::InvalidResponse("getProgramAccounts entry without pubkey".to_string()))?;
+                let encoded = account["account"]["data"][0].as_str()
+                    .ok_or_else(|| RpcError::InvalidResponse(format!("Account {} has no data", address)))?;
+                let data = base64::engine::general_purpose::STANDARD.decode(encoded)
+                    .map_err(|e| RpcError::InvalidResponse(format!("Failed to decode account data for {}: {}", address, e)))?;
+                Ok((address.to_string(), data))
+            })
+            .collect()
+    }
+    
+    pub async fn simulate_transaction(&self, transaction_data: &str) -> Result<Value, RpcError> {
+        self.make_request(RpcTaskType::Simulate, simulate_transaction_request(transaction_data)).await
+    }
+    
+    // The same simulation on one specific endpoint, for comparing what several of them see
+    pub async fn simulate_transaction_on(&self, endpoint_type: RpcEndpointType, transaction_data: &str) -> Result<Value, RpcError> {
+        self.make_request_to(endpoint_type, simulate_transaction_request(transaction_data)).await
+    }
+    
+    // Also returns the jsonParsed state of `accounts` after the transaction and its inner
+    // instructions. Read at processed, like get_multiple_accounts_parsed, so the two compare
+    pub async fn simulate_transaction_with_accounts(&self, transaction_data: &str, accounts: &[String]) -> Result<Value, RpcError> {
+        self.make_request(RpcTaskType::Simulate, simulate_with_accounts_request(transaction_data, accounts)).await
+    }
+    
+    pub async fn simulate_transaction_with_accounts_on(&self, endpoint_type: RpcEndpointType, transaction_data: &str, accounts: &[String]) -> Result<Value, RpcError> {
+        self.make_request_to(endpoint_type, simulate_with_accounts_request(transaction_data, accounts)).await
+    }
+    
+    pub async fn get_slot(&self) -> Result<u64, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getSlot",
+            "params": [{ "commitment": "processed" }]
+        });
+        
+        let response = self.make_request(RpcTaskType::CriticalRead, request_body).await?;
+        types::parse_result("getSlot", &response)
+    }
+    
+    // Current epoch, the absolute slot and its index within the epoch
+    pub async fn get_epoch_info(&self) -> Result<Value, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getEpochInfo",
+            "params": [{ "commitment": "processed" }]
+        });
+        
+        self.make_request(RpcTaskType::Read, request_body).await
+    }
+    
+    // Leader schedule of the epoch containing the slot: identity -> slot indexes relative to the epoch start
+    pub async fn get_leader_schedule(&self, slot: u64) -> Result<Value, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getLeaderSchedule",
+            "params": [slot]
+        });
+        
+        self.make_request(RpcTaskType::Read, request_body).await
+    }
+    
+    // Lamports held by an account
+    pub async fn get_balance(&self, address: &str) -> Result<u64, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getBalance",
+            "params": [address, { "commitment": "confirmed" }]
+        });
         
-        Ok(response)
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        Ok(types::parse_result::<RpcResponse<u64>>("getBalance", &response)?.value)
     }
     
-    pub async fn get_recent_blockhash(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_recent_blockhash(&self) -> Result<LatestBlockhash, RpcError> {
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -353,45 +1124,448 @@ impl RpcManager {
             "params": []
         });
         
-        let endpoint = self.get_best_rpc(RpcTaskType::Read).await
-            .ok_or("No healthy read endpoint available")?;
-        
-        let response = self.make_request(endpoint.endpoint_type, request_body).await?;
+        let response = self.make_request(RpcTaskType::CriticalRead, request_body).await?;
+        Ok(types::parse_result::<RpcResponse<LatestBlockhash>>("getLatestBlockhash", &response)?.value)
+    }
+    
+    // None while the node doesn't know the signature, e.g. before it has landed
+    pub async fn get_transaction(&self, signature: &str) -> Result<Option<EncodedTransaction>, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTransaction",
+            "params": [
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0
+                }
+            ]
+        });
         
-        if let Some(error) = response.get("error") {
-            return Err(format!("getLatestBlockhash failed: {}", error).into());
-        }
+        let response = self.make_request(RpcTaskType::Read, request_body).await?;
+        types::parse_result("getTransaction", &response)
+    }
+    
+    // Plain sendTransaction through a standard RPC rather than the block engine; returns the signature
+    pub async fn send_transaction(&self, transaction_data: &str) -> Result<String, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendTransaction",
+            "params": [
+                transaction_data,
+                {
+                    "encoding": "base58",
+                    "skipPreflight": true,
+                    "maxRetries": 0
+                }
+            ]
+        });
         
-        Ok(response)
+        // Standard RPCs only; the block engine isn't a place for plain transactions
+        let response = self.route(RpcTaskType::Execute, &[RpcEndpointType::Drpc, RpcEndpointType::Helius], request_body, Instant::now() + self.limit_max_wait).await?;
+        types::parse_result("sendTransaction", &response)
     }
     
-    pub async fn get_recent_prioritization_fees(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    // Confirmation status of each signature, in order; None for signatures unknown to the node so far
+    pub async fn get_signature_statuses(&self, signatures: &[String]) -> Result<Vec<Option<SignatureStatus>>, RpcError> {
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "getRecentPrioritizationFees",
-            "params": []
+            "method": "getSignatureStatuses",
+            "params": [signatures]
         });
         
-        let endpoint = self.get_best_rpc(RpcTaskType::Read).await
-            .ok_or("No healthy read endpoint available")?;
+        let response = self.make_request(RpcTaskType::CriticalRead, request_body).await?;
+        Ok(types::parse_result::<RpcResponse<Vec<Option<SignatureStatus>>>>("getSignatureStatuses", &response)?.value)
+    }
+    
+    // Block with each transaction's signatures and account keys (signer/writable flags), in order
+    pub async fn get_block_accounts(&self, slot: u64) -> Result<Value, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getBlock",
+            "params": [
+                slot,
+                {
+                    "encoding": "json",
+                    "transactionDetails": "accounts",
+                    "rewards": false,
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0
+                }
+            ]
+        });
         
-        let response = self.make_request(endpoint.endpoint_type, request_body).await?;
+        self.make_request(RpcTaskType::Read, request_body).await
+    }
+    
+    // Most recent per-minute performance samples (transactions and slots per sample period)
+    pub async fn get_recent_performance_samples(&self, limit: usize) -> Result<Value, RpcError> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getRecentPerformanceSamples",
+            "params": [limit]
+        });
         
-        if let Some(error) = response.get("error") {
-            return Err(format!("getRecentPrioritizationFees failed: {}", error).into());
-        }
+        self.make_request(RpcTaskType::Read, request_body).await
+    }
+    
+    pub async fn get_recent_prioritization_fees(&self) -> Result<Value, RpcError> {
+        self.get_recent_prioritization_fees_for(&[]).await
+    }
+    
+    // Per-slot fees paid by transactions that write-locked any of `accounts` (at most 128);
+    // network-wide without accounts
+    pub async fn get_recent_prioritization_fees_for(&self, accounts: &[String]) -> Result<Value, RpcError> {
+        let params = if accounts.is_empty() { json!([]) } else { json!([accounts]) };
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getRecentPrioritizationFees",
+            "params": params
+        });
         
-        Ok(response)
+        self.make_request(RpcTaskType::Read, request_body).await
     }
 }
 
 impl Clone for RpcManager {
     fn clone(&self) -> Self {
-        RpcManager {
-            client: Arc::clone(&self.client),
-            endpoints: Arc::clone(&self.endpoints),
-            health_check_interval: self.health_check_interval,
+        self.clone_for_spawn()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use crate::utils::mock_http::{self, MockResponse};
+
+    async fn mock_rpc(status: u16, body: Value) -> (String, Arc<AtomicUsize>) {
+        mock_rpc_with_delay(status, body, Duration::ZERO).await
+    }
+
+    // RPC node answering every request with the same status and body after the delay; counts the
+    // requests it gets
+    async fn mock_rpc_with_delay(status: u16, body: Value, delay: Duration) -> (String, Arc<AtomicUsize>) {
+        mock_http::serve(move |_| MockResponse::new(status, body.to_string()).with_delay(delay)).await
+    }
+
+    // RPC node replaying (status, Retry-After, body) responses in order, then repeating the last one
+    async fn mock_rpc_script(script: Vec<(u16, Option<&'static str>, Value)>) -> (String, Arc<AtomicUsize>) {
+        mock_http::serve(move |request| {
+            let (status, retry_after, body) = &script[request.index.min(script.len() - 1)];
+            let response = MockResponse::new(*status, body.to_string());
+            match retry_after {
+                Some(seconds) => response.with_header("Retry-After", seconds),
+                None => response,
+            }
+        }).await
+    }
+
+    fn too_many_requests() -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 429, "message": "Too many requests" } })
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_when_primary_returns_500s() {
+        let (primary_url, primary_hits) = mock_rpc(500, json!({ "error": "internal" })).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "result": 42 })).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(primary_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ]).with_routing_policy(RpcTaskType::CriticalRead, RoutingPolicy::Preferred);
+
+        for _ in 0..5 {
+            let slot = manager.get_slot().await.unwrap();
+            assert_eq!(slot, 42);
+        }
+
+        // The primary is tried until it is marked degraded, then skipped; every request still succeeds
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 3);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 5);
+        assert_eq!(manager.failover_count(), 3);
+        assert_eq!(manager.get_best_rpc(RpcTaskType::Read).await.unwrap().endpoint_type, RpcEndpointType::Drpc);
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_errors_do_not_fail_over() {
+        let (primary_url, primary_hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "Invalid params" } })).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "result": 1 })).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(primary_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ]);
+
+        assert!(matches!(manager.get_slot().await, Err(RpcError::Rpc { code: -32602, .. })));
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 0);
+        assert_eq!(manager.failover_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_critical_reads_move_to_the_fastest_endpoint() {
+        let slot = json!({ "jsonrpc": "2.0", "id": 1, "result": 7 });
+        let (slow_url, slow_hits) = mock_rpc_with_delay(200, slot.clone(), Duration::from_millis(80)).await;
+        let (fast_url, fast_hits) = mock_rpc(200, slot).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(slow_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fast_url, RpcEndpointType::Drpc, 0.5),
+        ]);
+
+        for _ in 0..5 {
+            manager.get_slot().await.unwrap();
+        }
+
+        // The preferred endpoint goes first until its latency is known, then the faster one takes over
+        assert_eq!(slow_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(fast_hits.load(Ordering::SeqCst), 4);
+        assert!(manager.latency_report().await.starts_with("CriticalRead: Drpc"));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_reads_round_robin() {
+        let account = json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": null } });
+        let (first_url, first_hits) = mock_rpc(200, account.clone()).await;
+        let (second_url, second_hits) = mock_rpc(200, account).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(first_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(second_url, RpcEndpointType::Drpc, 0.5),
+        ]);
+
+        for _ in 0..4 {
+            manager.get_account_info("11111111111111111111111111111111").await.unwrap();
+        }
+
+        assert_eq!(first_hits.load(Ordering::SeqCst), 2);
+        assert_eq!(second_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_waits_out_a_429_burst_within_the_retry_budget() {
+        let slot = json!({ "jsonrpc": "2.0", "id": 1, "result": 42 });
+        let (url, hits) = mock_rpc_script(vec![
+            (429, None, too_many_requests()),
+            (429, None, too_many_requests()),
+            (200, None, slot),
+        ]).await;
+        let mut manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]);
+        manager.retry_base_delay = Duration::from_millis(10);
+
+        let started = Instant::now();
+        assert_eq!(manager.get_slot().await.unwrap(), 42);
+
+        // Without Retry-After the back-off doubles: 10ms, then 20ms
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        assert_eq!(manager.rate_limited_count(), 2);
+        assert_eq!(manager.failover_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_once_the_burst_outlasts_the_budget() {
+        let (url, hits) = mock_rpc_script(vec![(429, Some("0"), too_many_requests())]).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]);
+
+        assert!(matches!(manager.get_slot().await, Err(RpcError::RateLimited { .. })));
+        // The first attempt and three retries
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+
+        // A Retry-After beyond the longest wait fails straight away, and later requests don't send at all
+        let (url, hits) = mock_rpc_script(vec![(429, Some("30"), json!({}))]).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]);
+        for _ in 0..2 {
+            match manager.get_slot().await {
+                Err(RpcError::RateLimited { retry_after: Some(retry_after) }) => assert!(retry_after > Duration::from_secs(25)),
+                other => panic!("expected a rate limit, got {:?}", other),
+            }
         }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_endpoint_is_skipped_until_retry_after() {
+        let (throttled_url, throttled_hits) = mock_rpc_script(vec![(429, Some("30"), json!({}))]).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "result": 9 })).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(throttled_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ]).with_routing_policy(RpcTaskType::CriticalRead, RoutingPolicy::Preferred);
+
+        for _ in 0..3 {
+            assert_eq!(manager.get_slot().await.unwrap(), 9);
+        }
+
+        assert_eq!(throttled_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 3);
+        assert_eq!(manager.failover_count(), 1);
+        // Throttling isn't an outage: the endpoint keeps its health
+        assert_eq!(manager.get_best_rpc(RpcTaskType::Read).await.unwrap().endpoint_type, RpcEndpointType::Helius);
+    }
+
+    #[tokio::test]
+    async fn test_sheds_low_priority_reads_while_an_endpoint_throttles() {
+        let (throttled_url, throttled_hits) = mock_rpc_script(vec![(429, Some("30"), json!({}))]).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "result": 9 })).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(throttled_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ])
+            .with_routing_policy(RpcTaskType::CriticalRead, RoutingPolicy::Preferred)
+            .with_load_shedding(RpcTaskType::Read);
+
+        assert_eq!(manager.get_slot().await.unwrap(), 9);
+        assert!(matches!(
+            manager.get_account_info("11111111111111111111111111111111").await,
+            Err(RpcError::RateLimited { retry_after: Some(_) })
+        ));
+        assert_eq!(manager.get_slot().await.unwrap(), 9);
+
+        assert_eq!(throttled_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 2);
+        assert_eq!(manager.shed_count(), 1);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_requests_wait_for_the_local_budget() {
+        let (url, hits) = mock_rpc(200, json!({ "jsonrpc": "2.0", "id": 1, "result": 42 })).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)])
+            .with_rate_limit(RpcEndpointType::Helius, Some(5.0), Some(2));
+
+        let started = Instant::now();
+        for _ in 0..6 {
+            assert_eq!(manager.get_slot().await.unwrap(), 42);
+        }
+
+        // The burst covers five; the sixth waits for the next token instead of being sent early
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(hits.load(Ordering::SeqCst), 6);
+        assert_eq!(manager.limited_locally_count(), 0);
+        assert_eq!(manager.rate_budgets(), vec![RateBudget {
+            endpoint: RpcEndpointType::Helius,
+            tokens: Some(0),
+            in_flight_available: Some(2),
+        }]);
+
+        // A caller whose timeout is shorter than the wait is turned away without sending
+        assert_eq!(
+            manager.make_request_within(RpcTaskType::CriticalRead, json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot" }), Duration::from_millis(20)).await,
+            Err(RpcError::RateLimitedLocally)
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 6);
+        assert_eq!(manager.limited_locally_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_low_priority_reads_over_budget_fail_fast_or_move_on() {
+        let account = json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": null } });
+        let (limited_url, limited_hits) = mock_rpc(200, account.clone()).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, account.clone()).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(limited_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ])
+            .with_routing_policy(RpcTaskType::Read, RoutingPolicy::Preferred)
+            .with_load_shedding(RpcTaskType::Read)
+            .with_rate_limit(RpcEndpointType::Helius, Some(1.0), None);
+
+        for _ in 0..2 {
+            manager.get_account_info("11111111111111111111111111111111").await.unwrap();
+        }
+        assert_eq!(limited_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 1);
+
+        // With nowhere else to go the read fails at once rather than waiting out the second
+        let (url, hits) = mock_rpc(200, account).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)])
+            .with_load_shedding(RpcTaskType::Read)
+            .with_rate_limit(RpcEndpointType::Helius, Some(1.0), None);
+        manager.get_account_info("11111111111111111111111111111111").await.unwrap();
+        let started = Instant::now();
+        assert_eq!(manager.get_account_info("11111111111111111111111111111111").await, Err(RpcError::RateLimitedLocally));
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_skips_a_failing_endpoint_until_a_probe_answers() {
+        let slot = |slot: u64| json!({ "jsonrpc": "2.0", "id": 1, "result": slot });
+        let (primary_url, primary_hits) = mock_rpc_script(vec![
+            (500, None, json!({})),
+            (500, None, json!({})),
+            (200, None, slot(42)),
+        ]).await;
+        let (fallback_url, fallback_hits) = mock_rpc(200, slot(9)).await;
+        let manager = RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(primary_url, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(fallback_url, RpcEndpointType::Drpc, 0.5),
+        ])
+            .with_routing_policy(RpcTaskType::CriticalRead, RoutingPolicy::Preferred)
+            .with_circuit_breaker(BreakerConfig {
+                failure_threshold: 2,
+                window: Duration::from_secs(10),
+                cool_down: Duration::from_millis(100),
+            });
+
+        // Two failures open the breaker; after that the primary isn't tried at all
+        for _ in 0..3 {
+            assert_eq!(manager.get_slot().await.unwrap(), 9);
+        }
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 2);
+        assert_eq!(manager.breaker_open_count(), 1);
+        assert_eq!(manager.breaker_statuses().await, vec![
+            BreakerStatus { endpoint: RpcEndpointType::Drpc, state: BreakerState::Closed },
+            BreakerStatus { endpoint: RpcEndpointType::Helius, state: BreakerState::Open },
+        ]);
+        let pinned = manager.make_request_to(RpcEndpointType::Helius, json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot" })).await;
+        assert!(matches!(pinned, Err(RpcError::Transport(_))));
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 2);
+
+        // Past the cool-down one probe goes to the primary, and its answer closes the breaker
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(manager.get_slot().await.unwrap(), 42);
+        assert_eq!(manager.get_slot().await.unwrap(), 42);
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 4);
+        assert_eq!(fallback_hits.load(Ordering::SeqCst), 3);
+        assert!(manager.breaker_statuses().await.iter().all(|status| status.state == BreakerState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_batches_split_at_the_endpoint_limit_and_keep_item_errors() {
+        // Answers out of order, one of them an error
+        let answers = json!([
+            { "jsonrpc": "2.0", "id": 2, "result": "c" },
+            { "jsonrpc": "2.0", "id": 0, "result": "a" },
+            { "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "Invalid params" } }
+        ]);
+        let (url, hits) = mock_rpc(200, answers).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)])
+            .with_max_batch_size(RpcEndpointType::Helius, 2);
+
+        let requests = vec![json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot", "params": [] }); 3];
+        let results = manager.make_batch_request(RpcTaskType::Read, requests).await;
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()["result"], json!("a"));
+        assert!(matches!(results[1], Err(RpcError::Rpc { code: -32602, .. })));
+        assert_eq!(results[2].as_ref().unwrap()["result"], json!("c"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_fails_each_request() {
+        let (url, _) = mock_rpc(500, json!({})).await;
+        let manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Jito, 1.0)]);
+
+        let requests = vec![json!({ "jsonrpc": "2.0", "id": 1, "method": "getBundleStatuses", "params": [[]] }); 2];
+        let results = manager.make_batch_request_to(RpcEndpointType::Jito, requests).await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| matches!(result, Err(RpcError::Transport(_)))));
+        assert!(manager.make_batch_request_to(RpcEndpointType::Jito, Vec::new()).await.is_empty());
+    }
+}
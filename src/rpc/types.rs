@@ -0,0 +1,276 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use crate::rpc::error::RpcError;
+
+// Typed views of the RPC answers the bot reads. Fields we don't use are ignored, so providers
+// adding to a response doesn't break parsing; a field we rely on that is missing or of the wrong
+// type fails with an error naming it
+
+// The "result" of a JSON-RPC answer; node errors have already been split off by RpcManager
+pub fn parse_result<T: DeserializeOwned>(method: &str, response: &Value) -> Result<T, RpcError> {
+    let result = response.get("result")
+        .ok_or_else(|| RpcError::InvalidResponse(format!("{} response has no result", method)))?;
+    T::deserialize(result).map_err(|e| RpcError::InvalidResponse(format!("Malformed {} response: {}", method, e)))
+}
+
+// Arrays the node sends as null when it has nothing to report
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcResponseContext {
+    pub slot: u64,
+}
+
+// Results wrapped as { context, value }: getBalance, getLatestBlockhash, getSignatureStatuses
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcResponse<T> {
+    pub context: RpcResponseContext,
+    pub value: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestBlockhash {
+    pub blockhash: String,
+    pub last_valid_block_height: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    #[serde(default)]
+    pub confirmations: Option<u64>, // None once rooted
+    #[serde(default)]
+    pub err: Option<Value>,
+    #[serde(default)]
+    pub confirmation_status: Option<String>, // processed, confirmed or finalized
+}
+
+impl SignatureStatus {
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self.confirmation_status.as_deref(), Some("confirmed") | Some("finalized"))
+    }
+}
+
+// getTransaction with "json" encoding
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedTransaction {
+    pub slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    pub transaction: UiTransaction,
+    #[serde(default)]
+    pub meta: Option<TransactionMeta>,
+}
+
+impl EncodedTransaction {
+    pub fn signature(&self) -> Option<&str> {
+        self.transaction.signatures.first().map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UiTransaction {
+    pub signatures: Vec<String>,
+    pub message: UiMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMessage {
+    pub account_keys: Vec<String>, // Static keys only; lookup-table keys are in meta.loaded_addresses
+    pub recent_blockhash: String,
+    pub instructions: Vec<UiCompiledInstruction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiCompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: String, // base58
+    #[serde(default)]
+    pub stack_height: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMeta {
+    #[serde(default)]
+    pub err: Option<Value>,
+    pub fee: u64,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub pre_token_balances: Vec<TokenBalance>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub post_token_balances: Vec<TokenBalance>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub inner_instructions: Vec<InnerInstructions>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub log_messages: Vec<String>,
+    #[serde(default)]
+    pub loaded_addresses: Option<LoadedAddresses>,
+    #[serde(default)]
+    pub compute_units_consumed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub account_index: u8,
+    pub mint: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub ui_token_amount: UiTokenAmount,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTokenAmount {
+    pub amount: String, // Raw amount; u64 as a string
+    pub decimals: u8,
+    #[serde(default)]
+    pub ui_amount: Option<f64>,
+}
+
+impl UiTokenAmount {
+    // uiAmount when the node sends it, otherwise worked out from the raw amount
+    pub fn ui_amount(&self) -> f64 {
+        self.ui_amount.unwrap_or_else(|| {
+            self.amount.parse::<u64>().map_or(0.0, |raw| raw as f64 / 10f64.powi(self.decimals as i32))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InnerInstructions {
+    pub index: u8,
+    pub instructions: Vec<UiCompiledInstruction>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LoadedAddresses {
+    #[serde(default)]
+    pub writable: Vec<String>,
+    #[serde(default)]
+    pub readonly: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transaction_fixture() -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "slot": 301_234_567u64,
+            "blockTime": 1_718_000_000,
+            "version": 0,
+            "transaction": {
+                "signatures": ["5Sig"],
+                "message": {
+                    "accountKeys": ["Payer111", "Pool2222", "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"],
+                    "recentBlockhash": "Hash3333",
+                    "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": 1 },
+                    "instructions": [{ "programIdIndex": 2, "accounts": [0, 1], "data": "3Bxs4", "stackHeight": null }],
+                    "addressTableLookups": []
+                }
+            },
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 2_039_280, 1],
+                "postBalances": [999_995_000u64, 2_039_280, 1],
+                "preTokenBalances": [{
+                    "accountIndex": 1, "mint": "So11111111111111111111111111111111111111112", "owner": "Payer111",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "uiTokenAmount": { "amount": "1500000000", "decimals": 9, "uiAmount": 1.5, "uiAmountString": "1.5" }
+                }],
+                "postTokenBalances": [{
+                    "accountIndex": 1, "mint": "So11111111111111111111111111111111111111112", "owner": "Payer111",
+                    "uiTokenAmount": { "amount": "500000000", "decimals": 9, "uiAmount": null, "uiAmountString": "0.5" }
+                }],
+                "innerInstructions": null,
+                "logMessages": ["Program log: Instruction: Swap"],
+                "loadedAddresses": { "writable": [], "readonly": ["Lookup444"] },
+                "rewards": [],
+                "computeUnitsConsumed": 41_200
+            }
+        }})
+    }
+
+    #[test]
+    fn test_parses_get_transaction_with_meta_and_token_balances() {
+        let tx: Option<EncodedTransaction> = parse_result("getTransaction", &transaction_fixture()).unwrap();
+        let tx = tx.unwrap();
+        assert_eq!(tx.slot, 301_234_567);
+        assert_eq!(tx.signature(), Some("5Sig"));
+        assert_eq!(tx.transaction.message.instructions[0].program_id_index, 2);
+        assert_eq!(tx.transaction.message.instructions[0].stack_height, None);
+
+        let meta = tx.meta.unwrap();
+        assert_eq!(meta.fee, 5000);
+        assert!(meta.inner_instructions.is_empty());
+        assert_eq!(meta.pre_token_balances[0].ui_token_amount.ui_amount(), 1.5);
+        // A null uiAmount falls back to the raw amount
+        assert_eq!(meta.post_token_balances[0].ui_token_amount.ui_amount(), 0.5);
+        assert_eq!(meta.loaded_addresses.unwrap().readonly, vec!["Lookup444".to_string()]);
+        assert_eq!(meta.compute_units_consumed, Some(41_200));
+
+        // Unknown signatures come back as a null result
+        let missing: Option<EncodedTransaction> = parse_result("getTransaction", &json!({ "jsonrpc": "2.0", "id": 1, "result": null })).unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn test_parses_context_wrapped_results() {
+        let blockhash: RpcResponse<LatestBlockhash> = parse_result("getLatestBlockhash", &json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "context": { "apiVersion": "2.0.3", "slot": 300 },
+            "value": { "blockhash": "Hash3333", "lastValidBlockHeight": 280 }
+        }})).unwrap();
+        assert_eq!(blockhash.context.slot, 300);
+        assert_eq!(blockhash.value, LatestBlockhash { blockhash: "Hash3333".to_string(), last_valid_block_height: 280 });
+
+        let balance: RpcResponse<u64> = parse_result("getBalance", &json!({ "result": { "context": { "slot": 1 }, "value": 42 } })).unwrap();
+        assert_eq!(balance.value, 42);
+
+        let statuses: RpcResponse<Vec<Option<SignatureStatus>>> = parse_result("getSignatureStatuses", &json!({ "result": {
+            "context": { "slot": 2 },
+            "value": [null, { "slot": 1, "confirmations": null, "err": null, "status": { "Ok": null }, "confirmationStatus": "finalized" }]
+        }})).unwrap();
+        assert_eq!(statuses.value[0], None);
+        assert!(statuses.value[1].as_ref().unwrap().is_confirmed());
+
+        assert_eq!(parse_result::<u64>("getSlot", &json!({ "result": 77 })).unwrap(), 77);
+        assert_eq!(parse_result::<String>("sendTransaction", &json!({ "result": "5Sig" })).unwrap(), "5Sig");
+    }
+
+    #[test]
+    fn test_malformed_responses_name_the_missing_field() {
+        let error = parse_result::<RpcResponse<LatestBlockhash>>("getLatestBlockhash", &json!({ "result": {
+            "context": { "slot": 300 },
+            "value": { "lastValidBlockHeight": 280 }
+        }})).unwrap_err();
+        assert!(matches!(&error, RpcError::InvalidResponse(detail) if detail.contains("getLatestBlockhash") && detail.contains("`blockhash`")));
+
+        let mut fixture = transaction_fixture();
+        fixture["result"]["meta"].as_object_mut().unwrap().remove("fee");
+        let error = parse_result::<Option<EncodedTransaction>>("getTransaction", &fixture).unwrap_err();
+        assert!(error.to_string().contains("`fee`"));
+
+        assert_eq!(
+            parse_result::<u64>("getSlot", &json!({ "jsonrpc": "2.0", "id": 1 })).unwrap_err(),
+            RpcError::InvalidResponse("getSlot response has no result".to_string())
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures::future::BoxFuture;
+use serde_json::json;
+use tokio::sync::mpsc;
+use crate::config::AlertsConfig;
+use crate::logging::Logger;
+use crate::utils::metrics_collector::{AlertEvent, AlertSeverity};
+
+const QUEUE_SIZE: usize = 64;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const TELEGRAM_API: &str = "https://api.telegram.org";
+
+type SendResult<'a> = BoxFuture<'a, Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+
+// Somewhere an alert is delivered to besides the log
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+    fn send<'a>(&'a self, alert: &'a AlertEvent) -> SendResult<'a>;
+}
+
+pub fn format_alert(alert: &AlertEvent) -> String {
+    let severity = format!("{:?}", alert.severity).to_uppercase();
+    format!("[{}] {:?}: {}", severity, alert.alert_type, alert.message)
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+// Errors leave the URL out: Telegram's carries the bot token and Discord's is a secret itself
+async fn post_json(client: &reqwest::Client, url: &str, body: serde_json::Value) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let response = client.post(url).json(&body).send().await.map_err(|e| e.without_url())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()).into());
+    }
+    Ok(())
+}
+
+// Posts to a Discord channel webhook
+pub struct DiscordSink {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl DiscordSink {
+    pub fn new(webhook_url: String) -> Self {
+        Self { client: http_client(), webhook_url }
+    }
+}
+
+impl AlertSink for DiscordSink {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    fn send<'a>(&'a self, alert: &'a AlertEvent) -> SendResult<'a> {
+        Box::pin(async move {
+            post_json(&self.client, &self.webhook_url, json!({ "content": format_alert(alert) })).await
+        })
+    }
+}
+
+// Sends through a Telegram bot's sendMessage to one chat
+pub struct TelegramSink {
+    client: reqwest::Client,
+    api_base: String,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl TelegramSink {
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        Self { client: http_client(), api_base: TELEGRAM_API.to_string(), bot_token, chat_id }
+    }
+
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+impl AlertSink for TelegramSink {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send<'a>(&'a self, alert: &'a AlertEvent) -> SendResult<'a> {
+        Box::pin(async move {
+            let url = format!("{}/bot{}/sendMessage", self.api_base, self.bot_token);
+            post_json(&self.client, &url, json!({ "chat_id": self.chat_id, "text": format_alert(alert) })).await
+        })
+    }
+}
+
+// Hands Warning-and-above alerts to a sender task that delivers them to every sink, so a slow
+// or unreachable webhook never holds up the caller. An alert type that fired less than
+// `cooldown` ago is dropped, so a flapping condition sends one message per cooldown
+pub struct AlertDispatcher {
+    queue: mpsc::Sender<AlertEvent>,
+    cooldown: Duration,
+    last_sent: Mutex<HashMap<String, Instant>>, // Alert type -> last time it was queued
+}
+
+impl AlertDispatcher {
+    // Must be called inside the runtime: the sender task is spawned here and runs until the
+    // dispatcher is dropped
+    pub fn new(sinks: Vec<Arc<dyn AlertSink>>, cooldown: Duration) -> Self {
+        let (queue, mut alerts) = mpsc::channel::<AlertEvent>(QUEUE_SIZE);
+        tokio::spawn(async move {
+            while let Some(alert) = alerts.recv().await {
+                for sink in &sinks {
+                    if let Err(e) = sink.send(&alert).await {
+                        Logger::error_occurred(&format!("Failed to deliver alert to {}: {}", sink.name(), e));
+                    }
+                }
+            }
+        });
+        Self { queue, cooldown, last_sent: Mutex::new(HashMap::new()) }
+    }
+
+    // DISCORD_WEBHOOK_URL and/or TELEGRAM_BOT_TOKEN with TELEGRAM_CHAT_ID; None when neither is set
+    pub fn from_config(config: &AlertsConfig) -> Result<Option<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut sinks: Vec<Arc<dyn AlertSink>> = Vec::new();
+        if let Some(webhook_url) = non_empty(&config.discord_webhook_url) {
+            sinks.push(Arc::new(DiscordSink::new(webhook_url)));
+        }
+        match (non_empty(&config.telegram_bot_token), non_empty(&config.telegram_chat_id)) {
+            (Some(bot_token), Some(chat_id)) => sinks.push(Arc::new(TelegramSink::new(bot_token, chat_id))),
+            (None, None) => {}
+            _ => return Err("TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together".into()),
+        }
+        if sinks.is_empty() {
+            return Ok(None);
+        }
+
+        let names: Vec<&str> = sinks.iter().map(|sink| sink.name()).collect();
+        Logger::status_update(&format!("Delivering alerts to {}", names.join(", ")));
+        Ok(Some(Self::new(sinks, Duration::from_secs(config.cooldown_secs))))
+    }
+
+    pub fn dispatch(&self, alert: &AlertEvent) -> bool {
+        self.dispatch_at(alert, Instant::now())
+    }
+
+    // True if the alert was queued for delivery
+    pub fn dispatch_at(&self, alert: &AlertEvent, now: Instant) -> bool {
+        if matches!(alert.severity, AlertSeverity::Info) {
+            return false;
+        }
+
+        let key = format!("{:?}", alert.alert_type);
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if last_sent.get(&key).is_some_and(|last| now.saturating_duration_since(*last) < self.cooldown) {
+            return false;
+        }
+
+        // Only a queued alert starts the cooldown, so one dropped on a full queue goes out next time
+        match self.queue.try_send(alert.clone()) {
+            Ok(()) => {
+                last_sent.insert(key, now);
+                true
+            }
+            Err(e) => {
+                Logger::error_occurred(&format!("Dropping alert delivery ({}): {}", e, alert.message));
+                false
+            }
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use crate::config::Config;
+    use std::net::SocketAddr;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, StatusCode};
+    use crate::utils::metrics_collector::{AlertType, MetricsCollector};
+
+    type Received = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+    // Records every request's path and JSON body, answering with `status`
+    fn mock_server(status: StatusCode) -> (SocketAddr, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&received);
+        let make_service = make_service_fn(move |_| {
+            let recorder = Arc::clone(&recorder);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let recorder = Arc::clone(&recorder);
+                    async move {
+                        let path = request.uri().path().to_string();
+                        let bytes = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+                        let body = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+                        recorder.lock().unwrap().push((path, body));
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = status;
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, received)
+    }
+
+    async fn wait_for(received: &Received, count: usize) {
+        for _ in 0..100 {
+            if received.lock().unwrap().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    fn alert(alert_type: AlertType, severity: AlertSeverity) -> AlertEvent {
+        AlertEvent {
+            timestamp: std::time::SystemTime::now(),
+            alert_type,
+            message: "Balance dropped by 12.00%".to_string(),
+            severity,
+            value: Some(0.12),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_suppresses_repeats_of_an_alert_type() {
+        let (addr, received) = mock_server(StatusCode::NO_CONTENT);
+        let discord: Arc<dyn AlertSink> = Arc::new(DiscordSink::new(format!("http://{}/webhook", addr)));
+        let dispatcher = AlertDispatcher::new(vec![discord], Duration::from_secs(300));
+        let start = Instant::now();
+
+        assert!(dispatcher.dispatch_at(&alert(AlertType::BalanceDrop, AlertSeverity::Warning), start));
+        // Flapping: the same type within the cooldown is dropped, another type isn't
+        assert!(!dispatcher.dispatch_at(&alert(AlertType::BalanceDrop, AlertSeverity::Warning), start + Duration::from_secs(5)));
+        assert!(!dispatcher.dispatch_at(&alert(AlertType::BalanceDrop, AlertSeverity::Critical), start + Duration::from_secs(299)));
+        assert!(dispatcher.dispatch_at(&alert(AlertType::DrawdownHalt, AlertSeverity::Critical), start + Duration::from_secs(5)));
+        // Info stays in the log
+        assert!(!dispatcher.dispatch_at(&alert(AlertType::HighLatency, AlertSeverity::Info), start));
+        // Past the cooldown it goes out again
+        assert!(dispatcher.dispatch_at(&alert(AlertType::BalanceDrop, AlertSeverity::Warning), start + Duration::from_secs(300)));
+
+        wait_for(&received, 3).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[0].0, "/webhook");
+        assert_eq!(received[0].1["content"], "[WARNING] BalanceDrop: Balance dropped by 12.00%");
+        assert_eq!(received[1].1["content"].as_str().unwrap(), "[CRITICAL] DrawdownHalt: Balance dropped by 12.00%");
+    }
+
+    #[tokio::test]
+    async fn test_collector_alerts_reach_telegram_and_survive_a_failing_sink() {
+        let (telegram_addr, telegram) = mock_server(StatusCode::OK);
+        let (discord_addr, discord) = mock_server(StatusCode::INTERNAL_SERVER_ERROR);
+        let sinks: Vec<Arc<dyn AlertSink>> = vec![
+            Arc::new(DiscordSink::new(format!("http://{}/webhook", discord_addr))),
+            Arc::new(TelegramSink::new("123:abc".to_string(), "-100".to_string()).with_api_base(&format!("http://{}/", telegram_addr))),
+        ];
+        let collector = MetricsCollector::new(&Config::default()).unwrap()
+            .with_alert_dispatcher(AlertDispatcher::new(sinks, Duration::from_secs(300)));
+
+        // A 50% drop, twice: logged both times, delivered once
+        collector.check_and_trigger_alerts(1.0, 2.0).await;
+        collector.check_and_trigger_alerts(0.5, 1.0).await;
+        assert_eq!(collector.get_recent_alerts(10).await.len(), 2);
+
+        wait_for(&telegram, 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Discord answering 500 didn't stop delivery to Telegram
+        assert_eq!(discord.lock().unwrap().len(), 1);
+        let telegram = telegram.lock().unwrap();
+        assert_eq!(telegram.len(), 1);
+        assert_eq!(telegram[0].0, "/bot123:abc/sendMessage");
+        assert_eq!(telegram[0].1["chat_id"], "-100");
+        assert!(telegram[0].1["text"].as_str().unwrap().starts_with("[WARNING] BalanceDrop"));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_error_leaves_the_bot_token_out() {
+        // Nothing listens on the port once the listener is dropped
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let sink = TelegramSink::new("123:secret".to_string(), "-100".to_string()).with_api_base(&format!("http://{}/", addr));
+
+        let error = sink.send(&alert(AlertType::BalanceDrop, AlertSeverity::Warning)).await.unwrap_err();
+        assert!(!error.to_string().contains("123:secret"));
+    }
+}
@@ -0,0 +1,374 @@
+// Constant-product (x * y = k) swap math. Fees are taken from the input, as Raydium AMM v4
+// does; for concentrated liquidity pools the virtual reserves give the same results while
+// the trade stays within the current tick range
+
+const FEE_DENOMINATOR: u128 = 1_000_000;
+
+fn fee_numerator(fee_rate: f64) -> u128 {
+    (fee_rate.clamp(0.0, 1.0) * FEE_DENOMINATOR as f64).round() as u128
+}
+
+// Output received for amount_in after the pool fee
+pub fn get_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64) -> u64 {
+    if amount_in == 0 || reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+    let amount_in_with_fee = amount_in as u128 * (FEE_DENOMINATOR - fee_numerator(fee_rate));
+    let denominator = reserve_in as u128 * FEE_DENOMINATOR + amount_in_with_fee;
+    match amount_in_with_fee.checked_mul(reserve_out as u128) {
+        Some(numerator) => (numerator / denominator) as u64,
+        // Only reachable with reserves near u64::MAX (e.g. virtual CLMM reserves)
+        None => (amount_in_with_fee as f64 * reserve_out as f64 / denominator as f64) as u64,
+    }
+}
+
+// Input required to receive amount_out, rounded up; None when the pool can't provide it
+pub fn get_amount_in(amount_out: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64) -> Option<u64> {
+    if amount_out == 0 {
+        return Some(0);
+    }
+    if reserve_in == 0 || amount_out >= reserve_out {
+        return None;
+    }
+    let fee_multiplier = FEE_DENOMINATOR - fee_numerator(fee_rate);
+    if fee_multiplier == 0 {
+        return None;
+    }
+    let numerator = (reserve_in as u128 * amount_out as u128).checked_mul(FEE_DENOMINATOR)?;
+    let denominator = (reserve_out - amount_out) as u128 * fee_multiplier;
+    u64::try_from(numerator / denominator + 1).ok()
+}
+
+// Price movement caused by the trade itself, excluding the fee: 1 - execution / spot price
+pub fn price_impact(amount_in: u64, reserve_in: u64) -> f64 {
+    if amount_in == 0 {
+        return 0.0;
+    }
+    amount_in as f64 / (reserve_in as f64 + amount_in as f64)
+}
+
+// Shortfall of the output against the spot price, fee included
+pub fn effective_slippage(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64) -> f64 {
+    if amount_in == 0 || reserve_in == 0 || reserve_out == 0 {
+        return 0.0;
+    }
+    let spot_output = amount_in as f64 * reserve_out as f64 / reserve_in as f64;
+    let amount_out = get_amount_out(amount_in, reserve_in, reserve_out, fee_rate) as f64;
+    (1.0 - amount_out / spot_output).max(0.0)
+}
+
+// Profit in input tokens of buying front_in before a victim swap of victim_in in the same
+// direction and selling everything back right after it
+pub fn sandwich_profit(front_in: u64, victim_in: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64) -> i128 {
+    let front_out = get_amount_out(front_in, reserve_in, reserve_out, fee_rate);
+    let (reserve_in, reserve_out) = (reserve_in.saturating_add(front_in), reserve_out - front_out);
+
+    let victim_out = get_amount_out(victim_in, reserve_in, reserve_out, fee_rate);
+    let (reserve_in, reserve_out) = (reserve_in.saturating_add(victim_in), reserve_out - victim_out);
+
+    let back_out = get_amount_out(front_out, reserve_out, reserve_in, fee_rate);
+    back_out as i128 - front_in as i128
+}
+
+// Profit in input tokens we can capture around a victim swap with at most max_front_in of our
+// own; the front-run never exceeds the victim's size. 0 when the curve fees eat the movement
+pub fn capturable_profit(victim_in: u64, max_front_in: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64) -> u64 {
+    let front_in = max_front_in.min(victim_in);
+    if front_in == 0 {
+        return 0;
+    }
+    sandwich_profit(front_in, victim_in, reserve_in, reserve_out, fee_rate).max(0) as u64
+}
+
+// Largest input whose effective slippage, fee included, stays within max_slippage. The output
+// falls short of spot by 1 - γ·x / (x + γ·dx), solved here for dx
+pub fn max_input_for_slippage(reserve_in: u64, fee_rate: f64, max_slippage: f64) -> u64 {
+    let gamma = 1.0 - fee_rate.clamp(0.0, 1.0);
+    if max_slippage >= 1.0 {
+        return u64::MAX;
+    }
+    if gamma == 0.0 || max_slippage <= 1.0 - gamma {
+        return 0;
+    }
+    (reserve_in as f64 * (gamma - (1.0 - max_slippage)) / (gamma * (1.0 - max_slippage))) as u64
+}
+
+// Our side of a sandwich: what goes in, what it buys, and where it leaves the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandwichFront {
+    pub front_in: u64,
+    pub front_out: u64,
+    pub profit: u64,            // Input tokens, after the fees of both legs
+    pub price_after_front: f64, // Marginal price (input per output) once our frontrun executes
+    pub expected_back_out: u64, // Selling front_out right after the victim
+}
+
+// Frontrun size that maximizes sandwich profit around a victim swap, with at most max_front_in,
+// our own slippage within max_slippage and the victim still getting victim_min_out (0 when
+// unknown). Profit is unimodal in the frontrun size, so a ternary search over the feasible range
+// finds the optimum. None when no feasible size profits
+pub fn optimal_sandwich_front(
+    victim_in: u64,
+    victim_min_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: f64,
+    max_front_in: u64,
+    max_slippage: f64,
+) -> Option<SandwichFront> {
+    if victim_in == 0 || reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+    let victim_out_after = |front_in: u64| {
+        let front_out = get_amount_out(front_in, reserve_in, reserve_out, fee_rate);
+        get_amount_out(victim_in, reserve_in.saturating_add(front_in), reserve_out - front_out, fee_rate)
+    };
+    if victim_out_after(0) < victim_min_out {
+        return None;
+    }
+
+    // Largest size that still lets the victim's swap through; its output only falls as we grow
+    let (mut lo, mut hi) = (0u64, max_front_in.min(max_input_for_slippage(reserve_in, fee_rate, max_slippage)));
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        if victim_out_after(mid) >= victim_min_out {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    let profit = |front_in: u64| sandwich_profit(front_in, victim_in, reserve_in, reserve_out, fee_rate);
+    let (mut lo, mut hi) = (1u64, hi);
+    if hi < lo {
+        return None;
+    }
+    while hi - lo > 2 {
+        let (m1, m2) = (lo + (hi - lo) / 3, hi - (hi - lo) / 3);
+        if profit(m1) < profit(m2) {
+            lo = m1 + 1;
+        } else {
+            hi = m2;
+        }
+    }
+    let front_in = (lo..=hi).max_by_key(|front_in| profit(*front_in))?;
+    let best = profit(front_in);
+    if best <= 0 {
+        return None;
+    }
+
+    let front_out = get_amount_out(front_in, reserve_in, reserve_out, fee_rate);
+    let (reserve_in, reserve_out) = (reserve_in + front_in, reserve_out - front_out);
+    let victim_out = get_amount_out(victim_in, reserve_in, reserve_out, fee_rate);
+    Some(SandwichFront {
+        front_in,
+        front_out,
+        profit: best as u64,
+        price_after_front: reserve_in as f64 / reserve_out as f64,
+        expected_back_out: get_amount_out(front_out, reserve_out - victim_out, reserve_in + victim_in, fee_rate),
+    })
+}
+
+// Input that extracts the most value from a pool whose output is worth output_price input
+// units on other venues, i.e. the swap that moves a displaced pool back to the outside price.
+// Marginal output is γ·x·y / (x + γ·dx)², which equals 1 / output_price at the optimum.
+// 0 when the price gap doesn't cover the fee
+pub fn optimal_backrun_amount(reserve_in: u64, reserve_out: u64, fee_rate: f64, output_price: f64) -> u64 {
+    if reserve_in == 0 || reserve_out == 0 || output_price.is_nan() || output_price <= 0.0 {
+        return 0;
+    }
+    let gamma = 1.0 - fee_rate.clamp(0.0, 1.0);
+    let (x, y) = (reserve_in as f64, reserve_out as f64);
+    let balanced_in = (output_price * gamma * x * y).sqrt();
+    if balanced_in <= x || gamma == 0.0 {
+        return 0;
+    }
+    ((balanced_in - x) / gamma) as u64
+}
+
+// Value of a backrun in input tokens, with the output valued at output_price
+pub fn backrun_profit(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_rate: f64, output_price: f64) -> f64 {
+    get_amount_out(amount_in, reserve_in, reserve_out, fee_rate) as f64 * output_price - amount_in as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_matches_raydium_reference_output() {
+        // 1 SOL into a 100 SOL / 500k token pool at 0.25%
+        let out = get_amount_out(1_000_000_000, 100_000_000_000, 500_000_000_000, 0.0025);
+        assert_eq!(out, 4_938_241_045);
+        assert!((price_impact(1_000_000_000, 100_000_000_000) - 1.0 / 101.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_amount_in_inverts_amount_out() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..1_000 {
+            let reserve_in = rng.gen_range(1_000_000..1_000_000_000_000u64);
+            let reserve_out = rng.gen_range(1_000_000..1_000_000_000_000u64);
+            let amount_out = rng.gen_range(1..reserve_out / 2);
+
+            let amount_in = get_amount_in(amount_out, reserve_in, reserve_out, 0.003).unwrap();
+            assert!(get_amount_out(amount_in, reserve_in, reserve_out, 0.003) >= amount_out);
+        }
+        assert_eq!(get_amount_in(100, 1_000, 100, 0.003), None);
+    }
+
+    #[test]
+    fn test_output_and_impact_are_monotonic() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..1_000 {
+            let reserve_in = rng.gen_range(1_000_000..1_000_000_000_000u64);
+            let reserve_out = rng.gen_range(1_000_000..1_000_000_000_000u64);
+            let smaller = rng.gen_range(1..reserve_in);
+            let larger = smaller + rng.gen_range(1..reserve_in);
+
+            assert!(get_amount_out(larger, reserve_in, reserve_out, 0.0025) >= get_amount_out(smaller, reserve_in, reserve_out, 0.0025));
+            assert!(price_impact(larger, reserve_in) > price_impact(smaller, reserve_in));
+            // A higher fee never pays more
+            assert!(get_amount_out(smaller, reserve_in, reserve_out, 0.01) <= get_amount_out(smaller, reserve_in, reserve_out, 0.0025));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_never_profits() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..1_000 {
+            let reserve_in = rng.gen_range(1_000..1_000_000_000_000u64);
+            let reserve_out = rng.gen_range(1_000..1_000_000_000_000u64);
+            let amount_in = rng.gen_range(1..reserve_in);
+            let fee_rate = [0.0, 0.0001, 0.0025, 0.01][rng.gen_range(0..4)];
+
+            let out = get_amount_out(amount_in, reserve_in, reserve_out, fee_rate);
+            let back = get_amount_out(out, reserve_out - out, reserve_in + amount_in, fee_rate);
+            assert!(back <= amount_in, "round trip of {} returned {}", amount_in, back);
+        }
+    }
+
+    #[test]
+    fn test_sandwich_needs_a_victim() {
+        let (reserve_in, reserve_out) = (100_000_000_000, 500_000_000_000);
+        assert!(sandwich_profit(1_000_000_000, 0, reserve_in, reserve_out, 0.0025) <= 0);
+        assert!(sandwich_profit(1_000_000_000, 20_000_000_000, reserve_in, reserve_out, 0.0025) > 0);
+    }
+
+    #[test]
+    fn test_capturable_profit_depends_on_impact_not_size_alone() {
+        // 5 SOL swap into a 10 SOL pool moves the price a lot
+        let shallow = capturable_profit(5_000_000_000, 5_000_000_000, 10_000_000_000, 50_000_000_000, 0.0025);
+        assert!(shallow > 2_000_000_000);
+
+        // 0.1 SOL swap into a 100k SOL pool barely moves it; fees dominate
+        let deep = capturable_profit(100_000_000, 5_000_000_000, 100_000_000_000_000, 500_000_000_000_000, 0.0025);
+        assert_eq!(deep, 0);
+
+        // Our position size caps the capturable slice
+        let capped = capturable_profit(5_000_000_000, 1_000_000_000, 10_000_000_000, 50_000_000_000, 0.0025);
+        assert!(capped > 0 && capped < shallow);
+        assert_eq!(capturable_profit(5_000_000_000, 0, 10_000_000_000, 50_000_000_000, 0.0025), 0);
+    }
+
+    #[test]
+    fn test_slippage_bound_inverts_effective_slippage() {
+        let (reserve_in, reserve_out) = (100_000_000_000, 500_000_000_000);
+        let amount_in = max_input_for_slippage(reserve_in, 0.0025, 0.03);
+        assert!((effective_slippage(amount_in, reserve_in, reserve_out, 0.0025) - 0.03).abs() < 1e-6);
+        // The fee alone already exceeds the budget
+        assert_eq!(max_input_for_slippage(reserve_in, 0.0025, 0.002), 0);
+    }
+
+    #[test]
+    fn test_sandwich_front_respects_caps() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for _ in 0..500 {
+            let reserve_in = rng.gen_range(1_000_000_000..10_000_000_000_000u64);
+            let reserve_out = rng.gen_range(1_000_000_000..10_000_000_000_000u64);
+            let victim_in = rng.gen_range(1_000_000..reserve_in / 5);
+            let fee_rate = [0.0, 0.0025, 0.003, 0.01][rng.gen_range(0..4)];
+            let tolerance = [0.005, 0.01, 0.05, 0.2][rng.gen_range(0..4)];
+            let victim_min_out = (get_amount_out(victim_in, reserve_in, reserve_out, fee_rate) as f64 * (1.0 - tolerance)) as u64;
+            let max_front_in = rng.gen_range(1_000_000..reserve_in);
+            let max_slippage = [0.03, 0.1, 0.5][rng.gen_range(0..3)];
+
+            let Some(front) = optimal_sandwich_front(victim_in, victim_min_out, reserve_in, reserve_out, fee_rate, max_front_in, max_slippage) else { continue };
+            assert!(front.front_in <= max_front_in);
+            assert!(front.front_in <= max_input_for_slippage(reserve_in, fee_rate, max_slippage));
+            let front_out = get_amount_out(front.front_in, reserve_in, reserve_out, fee_rate);
+            assert!(get_amount_out(victim_in, reserve_in + front.front_in, reserve_out - front_out, fee_rate) >= victim_min_out);
+            assert_eq!(front.profit as i128, sandwich_profit(front.front_in, victim_in, reserve_in, reserve_out, fee_rate));
+            assert_eq!(front.expected_back_out as i128 - front.front_in as i128, front.profit as i128);
+        }
+    }
+
+    #[test]
+    fn test_sandwich_profit_non_increasing_past_optimum() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let mut checked = 0;
+        for _ in 0..2_000 {
+            let reserve_in = rng.gen_range(1_000_000_000..10_000_000_000_000u64);
+            let reserve_out = rng.gen_range(1_000_000_000..10_000_000_000_000u64);
+            let victim_in = rng.gen_range(1_000_000..reserve_in / 5);
+            let fee_rate = [0.0025, 0.003, 0.01][rng.gen_range(0..3)];
+            // A victim that accepts any output and a loose slippage cap, so the optimum can be interior
+            // with valid sizes past it; with tight limits it sits on the limit itself
+            let victim_min_out = 0;
+            let max_front_in = max_input_for_slippage(reserve_in, fee_rate, 0.99);
+
+            let Some(front) = optimal_sandwich_front(victim_in, victim_min_out, reserve_in, reserve_out, fee_rate, u64::MAX, 0.99) else { continue };
+            // Integer rounding of the output adds noise worth about one output unit
+            let noise = 2 + 2 * (reserve_in / reserve_out) as i128;
+            let step = (front.front_in / 100).max(1);
+            let mut previous = front.profit as i128;
+            let mut size = front.front_in + step;
+            for _ in 0..200 {
+                if size > max_front_in {
+                    break;
+                }
+                let profit = sandwich_profit(size, victim_in, reserve_in, reserve_out, fee_rate);
+                assert!(profit <= previous + noise, "profit rose from {} to {} at {}", previous, profit, size);
+                previous = profit;
+                size += step;
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_backrun_restores_displaced_price() {
+        // 1,000 SOL / 150,000 USDC; a 50 SOL buy of USDC leaves USDC expensive in this pool
+        let (sol, usdc) = (1_000_000_000_000u64, 150_000_000_000u64);
+        let victim_out = get_amount_out(50_000_000_000, sol, usdc, 0.0025);
+        let (sol, usdc) = (sol + 50_000_000_000, usdc - victim_out);
+
+        // Other venues still price 1 raw SOL at 0.15 raw USDC; we sell USDC back for SOL
+        let sol_price_in_usdc = 0.15;
+        let amount_in = optimal_backrun_amount(usdc, sol, 0.0025, sol_price_in_usdc);
+        assert!(amount_in > 0);
+
+        let profit = backrun_profit(amount_in, usdc, sol, 0.0025, sol_price_in_usdc);
+        assert!(profit > 0.0);
+        for off_optimum in [amount_in * 9 / 10, amount_in * 11 / 10] {
+            assert!(backrun_profit(off_optimum, usdc, sol, 0.0025, sol_price_in_usdc) < profit);
+        }
+
+        // Afterwards the pool's marginal price sits at the outside price, less the fee
+        let sol_out = get_amount_out(amount_in, usdc, sol, 0.0025);
+        let pool_price = (usdc + amount_in) as f64 / (sol - sol_out) as f64;
+        assert!((pool_price - sol_price_in_usdc * 0.9975).abs() / sol_price_in_usdc < 0.001);
+    }
+
+    #[test]
+    fn test_no_backrun_when_gap_is_within_fee() {
+        let (usdc, sol) = (150_000_000_000u64, 1_000_000_000_000u64);
+        assert_eq!(optimal_backrun_amount(usdc, sol, 0.0025, 0.15), 0);
+        // A gap smaller than the fee isn't worth trading either
+        assert_eq!(optimal_backrun_amount(usdc, sol, 0.0025, 0.15 * 1.002), 0);
+        assert!(optimal_backrun_amount(usdc, sol, 0.0025, 0.15 * 1.01) > 0);
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::config::StrategiesConfig;
+
+// Caps how many opportunity analyses (fetch, evaluation, simulation) run at once so
+// notification bursts don't fan out into more RPC calls than the endpoint tolerates
+pub struct AnalysisLimiter {
+    semaphore: Arc<Semaphore>,
+    in_flight: Arc<AtomicUsize>,
+    skipped_count: Arc<AtomicU64>,
+    pub max_concurrent: usize,
+    pub acquire_timeout: Duration,
+}
+
+// Releases the analysis slot when dropped
+pub struct AnalysisPermit {
+    _permit: OwnedSemaphorePermit,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for AnalysisPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl AnalysisLimiter {
+    pub fn new(max_concurrent: usize, acquire_timeout: Duration) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            skipped_count: Arc::new(AtomicU64::new(0)),
+            max_concurrent,
+            acquire_timeout,
+        }
+    }
+
+    pub fn from_config(config: &StrategiesConfig) -> Self {
+        Self::new(config.max_concurrent_analyses, Duration::from_millis(config.analysis_permit_timeout_ms))
+    }
+
+    // Waits up to acquire_timeout for a slot; None means the analysis should be skipped
+    pub async fn acquire(&self) -> Option<AnalysisPermit> {
+        let acquired = tokio::time::timeout(
+            self.acquire_timeout,
+            Arc::clone(&self.semaphore).acquire_owned(),
+        ).await;
+
+        match acquired {
+            Ok(Ok(permit)) => {
+                self.in_flight.fetch_add(1, Ordering::SeqCst);
+                Some(AnalysisPermit {
+                    _permit: permit,
+                    in_flight: Arc::clone(&self.in_flight),
+                })
+            }
+            _ => {
+                self.skipped_count.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    pub fn skipped_count(&self) -> u64 {
+        self.skipped_count.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for AnalysisLimiter {
+    fn clone(&self) -> Self {
+        AnalysisLimiter {
+            semaphore: Arc::clone(&self.semaphore),
+            in_flight: Arc::clone(&self.in_flight),
+            skipped_count: Arc::clone(&self.skipped_count),
+            max_concurrent: self.max_concurrent,
+            acquire_timeout: self.acquire_timeout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight_never_exceeds_cap() {
+        let limiter = AnalysisLimiter::new(8, Duration::from_secs(5));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = Vec::new();
+        for _ in 0..200 {
+            let limiter = limiter.clone();
+            let peak = Arc::clone(&peak);
+            tasks.push(tokio::spawn(async move {
+                let _permit = limiter.acquire().await.expect("permit within timeout");
+                peak.fetch_max(limiter.in_flight(), Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }));
+        }
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 8);
+        assert_eq!(limiter.in_flight(), 0);
+        assert_eq!(limiter.skipped_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_skips_when_no_permit_within_deadline() {
+        let limiter = AnalysisLimiter::new(1, Duration::from_millis(10));
+        let held = limiter.acquire().await.unwrap();
+
+        assert!(limiter.acquire().await.is_none());
+        assert_eq!(limiter.skipped_count(), 1);
+
+        drop(held);
+        assert!(limiter.acquire().await.is_some());
+    }
+}
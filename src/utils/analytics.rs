@@ -3,6 +3,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use serde_json::Value;
 use serde::{Serialize, Deserialize};
 use crate::logging::Logger;
+use crate::utils::token_pnl::{attributed_mints, TokenPnl};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Analytics {
@@ -15,6 +16,10 @@ pub struct Analytics {
     pub start_time: u64,
     pub strategy_performance: HashMap<String, StrategyStats>,
     pub opportunity_analysis: HashMap<String, OpportunityStats>,
+    #[serde(default)]
+    pub dry_run: bool, // Nothing was submitted; the profit here is hypothetical
+    #[serde(default)]
+    pub token_performance: HashMap<String, TokenPnl>, // By mint, see token_pnl::attributed_mints
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,7 +40,7 @@ pub struct OpportunityStats {
 }
 
 impl Analytics {
-    pub fn new() -> Self {
+    pub fn new(dry_run: bool) -> Self {
         Self {
             total_profit: 0.0,
             total_transactions: 0,
@@ -46,6 +51,8 @@ impl Analytics {
             start_time: Self::current_timestamp(),
             strategy_performance: HashMap::new(),
             opportunity_analysis: HashMap::new(),
+            dry_run,
+            token_performance: HashMap::new(),
         }
     }
 
@@ -87,6 +94,23 @@ impl Analytics {
         }
     }
 
+    // Same booking as record_transaction, split evenly across the mints the trade is attributed to
+    pub fn record_token_trade(&mut self, mints: &[String], success: bool, profit: f64, fees: f64) {
+        let mints = attributed_mints(mints);
+        let share = 1.0 / mints.len().max(1) as f64;
+        for mint in mints {
+            let token = self.token_performance.entry(mint.clone()).or_insert_with(|| TokenPnl::new(&mint));
+            token.trades += 1;
+            if success {
+                token.successes += 1;
+                token.profit += profit * share;
+            } else {
+                token.profit -= fees * share;
+            }
+            token.fees += fees * share;
+        }
+    }
+
     pub fn record_opportunity(&mut self, opportunity_type: &str, executed: bool, profitable: bool, profit: f64, execution_time_ms: f64) {
         let opp_stats = self.opportunity_analysis.entry(opportunity_type.to_string()).or_insert_with(|| {
             OpportunityStats {
@@ -133,7 +157,9 @@ impl Analytics {
             } else { 0.0 },
             "hours_running": hours_running,
             "strategy_performance": self.strategy_performance,
-            "opportunity_analysis": self.opportunity_analysis
+            "opportunity_analysis": self.opportunity_analysis,
+            "token_performance": self.token_performance,
+            "dry_run": self.dry_run
         })
     }
 
@@ -142,6 +168,13 @@ impl Analytics {
         Logger::status_update(&format!("Analytics Summary: {:?}", metrics));
     }
 
+    pub fn save_to_file(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(&self.get_performance_metrics())
+            .map_err(|e| format!("Failed to serialize analytics: {}", e))?;
+        std::fs::write(file_path, json)
+            .map_err(|e| format!("Failed to write analytics to file: {}", e).into())
+    }
+
     fn current_timestamp() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
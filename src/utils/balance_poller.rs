@@ -0,0 +1,200 @@
+use std::sync::Arc;
+use std::time::Duration;
+use futures::future::BoxFuture;
+use tokio::sync::{watch, Mutex};
+use crate::config::RiskConfig;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::metrics_collector::MetricsCollector;
+use crate::utils::risk_controls::RiskManager;
+
+type BalanceResult<'a> = BoxFuture<'a, Result<f64, Box<dyn std::error::Error + Send + Sync>>>;
+
+// Where the wallet balance is read from, in SOL
+pub trait BalanceSource: Send + Sync {
+    fn balance(&self) -> BalanceResult<'_>;
+}
+
+// The wallet's confirmed balance through RpcManager, so polls share its endpoint rotation and limits
+pub struct RpcBalanceSource {
+    rpc_manager: Arc<RpcManager>,
+    wallet: String,
+}
+
+impl RpcBalanceSource {
+    pub fn new(rpc_manager: Arc<RpcManager>, wallet: String) -> Self {
+        Self { rpc_manager, wallet }
+    }
+}
+
+impl BalanceSource for RpcBalanceSource {
+    fn balance(&self) -> BalanceResult<'_> {
+        Box::pin(async move {
+            let lamports = self.rpc_manager.get_balance(&self.wallet).await?;
+            Ok(lamports as f64 / 1_000_000_000.0)
+        })
+    }
+}
+
+// Keeps RiskManager's balance tracker (minimum balance, high-water mark, drawdown) and the
+// balance-drop alert fed with the real wallet balance
+pub struct BalancePoller {
+    source: Arc<dyn BalanceSource>,
+    risk_manager: Arc<RiskManager>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
+    interval: Duration,
+    previous_balance: Mutex<Option<f64>>, // Last successful read
+}
+
+impl BalancePoller {
+    pub fn new(
+        source: Arc<dyn BalanceSource>,
+        risk_manager: Arc<RiskManager>,
+        metrics_collector: Option<Arc<MetricsCollector>>,
+        interval: Duration,
+    ) -> Self {
+        Self { source, risk_manager, metrics_collector, interval, previous_balance: Mutex::new(None) }
+    }
+
+    // BALANCE_POLL_SECS, at least a second
+    pub fn interval(config: &RiskConfig) -> Duration {
+        Duration::from_secs(config.balance_poll_secs.max(1))
+    }
+
+    // First read, awaited before any opportunity is processed; until then the tracker reads zero
+    // and should_pause_operations would pause everything
+    pub async fn initialize(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let balance = self.source.balance().await?;
+        self.risk_manager.initialize_balance(balance).await;
+        *self.previous_balance.lock().await = Some(balance);
+        Ok(balance)
+    }
+
+    // One read: updates the risk tracker and checks the alerts against the previous read. A
+    // poller whose initial read failed initializes on its first successful one
+    pub async fn poll_once(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let balance = self.source.balance().await?;
+        let previous = self.previous_balance.lock().await.replace(balance);
+        let Some(previous) = previous else {
+            self.risk_manager.initialize_balance(balance).await;
+            return Ok(balance);
+        };
+
+        // Below the minimum or in a drawdown halt; the risk manager has already recorded why, so
+        // this is only logged when the balance moves rather than on every poll
+        if let Err(e) = self.risk_manager.update_balance(balance).await {
+            if balance != previous {
+                Logger::status_update(&format!("Wallet balance {:.4} SOL: {}", balance, e));
+            }
+        }
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            metrics_collector.check_and_trigger_alerts(balance, previous).await;
+        }
+        Ok(balance)
+    }
+
+    pub fn spawn(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.tick().await; // The first tick is immediate and initialize() has just read
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if let Err(e) = self.poll_once().await {
+                    Logger::error_occurred(&format!("Failed to poll wallet balance: {}", e));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use crate::config::Config;
+    use crate::utils::metrics_collector::AlertType;
+    use crate::utils::risk_controls::{RiskEventType, RiskLimits};
+
+    // Hands out scripted balances, then keeps repeating the last one
+    struct ScriptedBalance(std::sync::Mutex<VecDeque<f64>>);
+
+    impl ScriptedBalance {
+        fn new(balances: &[f64]) -> Arc<Self> {
+            Arc::new(Self(std::sync::Mutex::new(balances.iter().copied().collect())))
+        }
+    }
+
+    impl BalanceSource for ScriptedBalance {
+        fn balance(&self) -> BalanceResult<'_> {
+            Box::pin(async move {
+                let mut balances = self.0.lock().unwrap();
+                match balances.len() {
+                    0 => Err("no balance scripted".into()),
+                    1 => Ok(balances[0]),
+                    _ => Ok(balances.pop_front().unwrap()),
+                }
+            })
+        }
+    }
+
+    async fn threshold_events(risk_manager: &RiskManager) -> usize {
+        risk_manager.get_recent_risk_events(60).await.iter()
+            .filter(|event| matches!(event.event_type, RiskEventType::BalanceThresholdBreached))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_threshold_crossings_fire_events_and_alerts() {
+        // 0.5 SOL minimum balance and a 10% drop alert between reads; the drawdown halt is off so
+        // only the minimum pauses trading
+        let limits = RiskLimits { min_balance_threshold: 0.5, max_drawdown_percent: 0.0, ..RiskLimits::from_config(&Config::default().risk) };
+        let risk_manager = Arc::new(RiskManager::with_limits(limits));
+        let metrics_collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let source = ScriptedBalance::new(&[2.0, 1.9, 0.4, 0.35, 0.8, 0.3]);
+        let poller = BalancePoller::new(source, Arc::clone(&risk_manager), Some(Arc::clone(&metrics_collector)), Duration::from_secs(1));
+
+        assert_eq!(poller.initialize().await.unwrap(), 2.0);
+        assert!(!risk_manager.should_pause_operations().await);
+
+        // A 5% dip: no alert, no event
+        poller.poll_once().await.unwrap();
+        assert!(metrics_collector.get_recent_alerts(10).await.is_empty());
+        assert_eq!(threshold_events(&risk_manager).await, 0);
+
+        // Crossing below the minimum fires once; staying below doesn't fire again
+        poller.poll_once().await.unwrap();
+        assert_eq!(threshold_events(&risk_manager).await, 1);
+        assert!(risk_manager.should_pause_operations().await);
+        let alerts = metrics_collector.get_recent_alerts(10).await;
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0].alert_type, AlertType::BalanceDrop));
+        poller.poll_once().await.unwrap();
+        assert_eq!(threshold_events(&risk_manager).await, 1);
+
+        // Recovering and dropping back below is a second crossing
+        poller.poll_once().await.unwrap();
+        assert!(!risk_manager.should_pause_operations().await);
+        poller.poll_once().await.unwrap();
+        assert_eq!(threshold_events(&risk_manager).await, 2);
+        assert_eq!(risk_manager.get_risk_metrics().await.current_balance, 0.3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_initial_read_initializes_on_the_first_poll() {
+        let risk_manager = Arc::new(RiskManager::new(&Config::default()).unwrap());
+        let source = ScriptedBalance::new(&[]);
+        let poller = BalancePoller::new(source.clone(), Arc::clone(&risk_manager), None, Duration::from_secs(1));
+
+        assert!(poller.initialize().await.is_err());
+        source.0.lock().unwrap().push_back(3.0);
+        poller.poll_once().await.unwrap();
+
+        let metrics = risk_manager.get_risk_metrics().await;
+        assert_eq!(metrics.initial_balance, 3.0);
+        assert_eq!(metrics.current_balance, 3.0);
+        assert_eq!(threshold_events(&risk_manager).await, 0);
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64, // Entries dropped for capacity or age, not overwrites
+    pub len: usize,
+}
+
+struct CacheState<V> {
+    entries: HashMap<String, (Instant, V)>,
+    order: VecDeque<(String, Instant)>, // Insertion order; stale records are skipped on eviction
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+// String-keyed cache holding at most `capacity` entries, each fresh for `ttl`. Lookups only
+// take the read lock; expired entries are swept and the oldest evicted when an insert
+// would go over capacity
+pub struct BoundedCache<V> {
+    state: Arc<RwLock<CacheState<V>>>,
+    counters: Arc<CacheCounters>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl<V: Clone> BoundedCache<V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(CacheState {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+            counters: Arc::new(CacheCounters::default()),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    // Fresh value for the key; expired entries count as misses
+    pub async fn get(&self, key: &str) -> Option<V> {
+        let value = self.state.read().await.entries.get(key)
+            .filter(|(inserted_at, _)| inserted_at.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone());
+
+        let counter = if value.is_some() { &self.counters.hits } else { &self.counters.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub async fn insert(&self, key: String, value: V) {
+        let now = Instant::now();
+        let mut state = self.state.write().await;
+
+        state.order.push_back((key.clone(), now));
+        state.entries.insert(key, (now, value));
+
+        if state.entries.len() > self.capacity {
+            self.sweep_expired(&mut state);
+        }
+        while state.entries.len() > self.capacity {
+            let Some((oldest, inserted_at)) = state.order.pop_front() else { break };
+            // Skip records superseded by a later insert of the same key
+            if state.entries.get(&oldest).is_some_and(|(current, _)| *current == inserted_at) {
+                state.entries.remove(&oldest);
+                self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // Re-inserting hot keys leaves stale order records behind; compact them
+        if state.order.len() > self.capacity * 2 {
+            let CacheState { entries, order } = &mut *state;
+            order.retain(|(key, inserted_at)| entries.get(key).is_some_and(|(current, _)| current == inserted_at));
+        }
+    }
+
+    // Removes every expired entry; also run on inserts over capacity
+    pub async fn sweep(&self) {
+        let mut state = self.state.write().await;
+        self.sweep_expired(&mut state);
+    }
+
+    fn sweep_expired(&self, state: &mut CacheState<V>) {
+        let ttl = self.ttl;
+        let before = state.entries.len();
+        state.entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < ttl);
+        let CacheState { entries, order } = &mut *state;
+        order.retain(|(key, inserted_at)| entries.get(key).is_some_and(|(current, _)| current == inserted_at));
+        self.counters.evictions.fetch_add((before - state.entries.len()) as u64, Ordering::Relaxed);
+    }
+
+    // Drops the entries `keep` rejects; invalidations aren't counted as evictions
+    pub async fn retain(&self, mut keep: impl FnMut(&str, &V) -> bool) {
+        let mut state = self.state.write().await;
+        state.entries.retain(|key, (_, value)| keep(key, value));
+        let CacheState { entries, order } = &mut *state;
+        order.retain(|(key, inserted_at)| entries.get(key).is_some_and(|(current, _)| current == inserted_at));
+    }
+
+    // First match over all cached values, fresh or not
+    pub async fn find_map<R>(&self, f: impl FnMut(&V) -> Option<R>) -> Option<R> {
+        self.state.read().await.entries.values().map(|(_, value)| value).find_map(f)
+    }
+
+    // Every match over all cached values, fresh or not
+    pub async fn filter_map<R>(&self, f: impl FnMut(&V) -> Option<R>) -> Vec<R> {
+        self.state.read().await.entries.values().map(|(_, value)| value).filter_map(f).collect()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.state.read().await.entries.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.state.read().await.entries.is_empty()
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            len: self.len().await,
+        }
+    }
+}
+
+impl<V> Clone for BoundedCache<V> {
+    fn clone(&self) -> Self {
+        BoundedCache {
+            state: Arc::clone(&self.state),
+            counters: Arc::clone(&self.counters),
+            capacity: self.capacity,
+            ttl: self.ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stays_within_capacity() {
+        let cache = BoundedCache::new(100, Duration::from_secs(60));
+        for i in 0..10_000 {
+            cache.insert(format!("pool{}", i), vec![0u8; 64]).await;
+            // Hot keys are re-inserted constantly on mainnet
+            cache.insert("pool0".to_string(), vec![0u8; 64]).await;
+        }
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.len, 100);
+        assert!(stats.evictions >= 9_900);
+        assert!(cache.state.read().await.order.len() <= 200);
+        // Most recent entries survive, the oldest are gone
+        assert!(cache.get("pool9999").await.is_some());
+        assert!(cache.get("pool1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_miss_and_are_swept() {
+        let cache = BoundedCache::new(10, Duration::from_millis(20));
+        cache.insert("SOL".to_string(), 1.0).await;
+        assert_eq!(cache.get("SOL").await, Some(1.0));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(cache.get("SOL").await, None);
+        cache.sweep().await;
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.evictions, stats.len), (1, 1, 1, 0));
+    }
+}
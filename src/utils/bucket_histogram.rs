@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+// Upper bounds in ms, 1ms to 5s; anything slower lands in the implicit +Inf bucket
+pub const LATENCY_BUCKETS_MS: [f64; 14] = [1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2000.0, 3000.0, 5000.0];
+
+// Fixed-bucket histogram, Prometheus style: a value goes in the first bucket whose upper bound
+// is >= it. Constant memory however many values are recorded, and the sum and count give the
+// exact mean
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BucketHistogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>, // One per bound, then the +Inf bucket; not cumulative
+    sum: f64,
+    count: u64,
+}
+
+impl BucketHistogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self { bounds: bounds.to_vec(), counts: vec![0; bounds.len() + 1], sum: 0.0, count: 0 }
+    }
+
+    pub fn latency_ms() -> Self {
+        Self::new(&LATENCY_BUCKETS_MS)
+    }
+
+    // Negative values count as 0; NaN and infinities are ignored
+    pub fn record(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let value = value.max(0.0);
+        let index = self.bucket_index(value);
+        self.counts[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn bucket_index(&self, value: f64) -> usize {
+        self.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.bounds.len())
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count > 0 { self.sum / self.count as f64 } else { 0.0 }
+    }
+
+    // (upper bound, cumulative count) per bucket, ending with +Inf
+    pub fn cumulative(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        self.bounds.iter().copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(self.counts.iter())
+            .map(|(bound, count)| {
+                total += count;
+                (bound, total)
+            })
+            .collect()
+    }
+
+    // Estimated q-quantile, q in [0.0, 1.0], interpolating linearly inside the bucket it falls in
+    // (as histogram_quantile does); one in the +Inf bucket reads as the largest bound
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = q.clamp(0.0, 1.0) * self.count as f64;
+        let mut below = 0u64;
+        for (index, count) in self.counts.iter().enumerate() {
+            if *count == 0 || ((below + count) as f64) < rank {
+                below += count;
+                continue;
+            }
+            let Some(upper) = self.bounds.get(index) else {
+                return self.bounds.last().copied();
+            };
+            let lower = if index == 0 { 0.0 } else { self.bounds[index - 1] };
+            return Some(lower + (upper - lower) * (rank - below as f64) / *count as f64);
+        }
+        self.bounds.last().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_land_in_the_first_bucket_at_or_above_them() {
+        let histogram = BucketHistogram::latency_ms();
+        assert_eq!(histogram.bucket_index(0.0), 0);
+        assert_eq!(histogram.bucket_index(1.0), 0);
+        assert_eq!(histogram.bucket_index(1.5), 1);
+        assert_eq!(histogram.bucket_index(100.0), 6);
+        assert_eq!(histogram.bucket_index(5000.0), 13);
+        assert_eq!(histogram.bucket_index(5000.1), 14);
+
+        let mut histogram = BucketHistogram::latency_ms();
+        for value in [0.5, 3.0, 3.0, 7000.0, f64::NAN, -2.0] {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.sum(), 7006.5);
+        let cumulative = histogram.cumulative();
+        assert_eq!(cumulative.len(), LATENCY_BUCKETS_MS.len() + 1);
+        assert_eq!(cumulative[0], (1.0, 2));
+        assert_eq!(cumulative[2], (5.0, 4));
+        assert_eq!(cumulative.last().copied(), Some((f64::INFINITY, 5)));
+    }
+
+    #[test]
+    fn test_quantiles_of_a_uniform_distribution() {
+        // 1..=1000ms, one sample each: the true p50 is 500ms, p90 900ms and p99 990ms
+        let mut histogram = BucketHistogram::latency_ms();
+        for value in 1..=1000 {
+            histogram.record(value as f64);
+        }
+        assert_eq!(histogram.mean(), 500.5);
+        assert_eq!(histogram.quantile(0.5), Some(500.0));
+        // 900th sample in the 750-1000ms bucket, which holds 250 of them
+        assert!((histogram.quantile(0.9).unwrap() - 900.0).abs() < 1.0);
+        assert!((histogram.quantile(0.99).unwrap() - 990.0).abs() < 1.0);
+        assert_eq!(histogram.quantile(0.0), Some(0.0));
+        assert_eq!(BucketHistogram::latency_ms().quantile(0.5), None);
+
+        // Everything slower than the largest bound reads as that bound
+        let mut slow = BucketHistogram::latency_ms();
+        slow.record(9000.0);
+        assert_eq!(slow.quantile(0.99), Some(5000.0));
+    }
+}
@@ -0,0 +1,182 @@
+use base64::Engine;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedTransaction {
+    pub err: Option<Value>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+// Why a bundle simulation failed. The kind is a fixed label for metrics; the detail is the
+// engine's error as reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleSimulationFailure {
+    pub kind: &'static str,
+    pub transaction_index: Option<usize>,
+    pub detail: String,
+}
+
+impl std::fmt::Display for BundleSimulationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.transaction_index {
+            Some(index) => write!(f, "Bundle simulation failed at transaction {}: {}", index, self.detail),
+            None => write!(f, "Bundle simulation failed: {}", self.detail),
+        }
+    }
+}
+
+impl std::error::Error for BundleSimulationFailure {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleSimulation {
+    pub transactions: Vec<SimulatedTransaction>,
+    pub failure: Option<BundleSimulationFailure>,
+}
+
+impl BundleSimulation {
+    pub fn succeeded(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    pub fn units_consumed(&self) -> u64 {
+        self.transactions.iter().filter_map(|tx| tx.units_consumed).sum()
+    }
+}
+
+// Metrics label for a transaction error
+pub fn failure_kind(error: &Value) -> &'static str {
+    let error = error.to_string();
+    if error.contains("InstructionError") {
+        "bundle_sim_instruction_error"
+    } else if error.contains("InsufficientFunds") {
+        "bundle_sim_insufficient_funds"
+    } else if error.contains("BlockhashNotFound") {
+        "bundle_sim_blockhash_not_found"
+    } else if error.contains("AccountInUse") || error.contains("AccountLoadedTwice") {
+        "bundle_sim_account_conflict"
+    } else {
+        "bundle_sim_failed"
+    }
+}
+
+fn simulated_transaction(result: &Value) -> SimulatedTransaction {
+    SimulatedTransaction {
+        err: Some(result["err"].clone()).filter(|err| !err.is_null()),
+        units_consumed: result["unitsConsumed"].as_u64(),
+        logs: result["logs"].as_array()
+            .map(|logs| logs.iter().filter_map(|log| log.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+    }
+}
+
+// simulateBundle result: a summary that is either "succeeded" or {"failed": {"error", "tx_signature"}}
+// plus a result per transaction executed, up to and including the one that failed
+pub fn parse_simulate_bundle(response: &Value) -> Option<BundleSimulation> {
+    let value = &response["result"]["value"];
+    let transactions: Vec<SimulatedTransaction> = value["transactionResults"].as_array()?
+        .iter()
+        .map(simulated_transaction)
+        .collect();
+
+    let summary = &value["summary"];
+    let failure = if summary.as_str() == Some("succeeded") {
+        None
+    } else {
+        let error = &summary["failed"]["error"];
+        if error.is_null() && summary["failed"].is_null() {
+            return None;
+        }
+        let failed_index = transactions.iter().position(|tx| tx.err.is_some());
+        let detail = failed_index
+            .and_then(|index| transactions[index].err.as_ref())
+            .unwrap_or(error);
+        Some(BundleSimulationFailure {
+            kind: failure_kind(detail),
+            transaction_index: failed_index,
+            detail: detail.to_string(),
+        })
+    };
+
+    Some(BundleSimulation { transactions, failure })
+}
+
+// Fallback when simulateBundle isn't available: each transaction's simulateTransaction response,
+// in bundle order. Every leg is simulated against current state, so this misses reverts that
+// only show up after an earlier leg has run
+pub fn from_transaction_simulations(responses: &[Value]) -> BundleSimulation {
+    let transactions: Vec<SimulatedTransaction> = responses.iter()
+        .map(|response| simulated_transaction(&response["result"]["value"]))
+        .collect();
+    let failure = transactions.iter().enumerate()
+        .find_map(|(index, tx)| tx.err.as_ref().map(|err| BundleSimulationFailure {
+            kind: failure_kind(err),
+            transaction_index: Some(index),
+            detail: err.to_string(),
+        }));
+    BundleSimulation { transactions, failure }
+}
+
+// JSON-RPC "method not found", or an engine that doesn't know simulateBundle
+pub fn is_method_unsupported(error: &str) -> bool {
+    error.contains("-32601") || error.contains("Method not found")
+}
+
+// Bundles are sent base58-encoded; both simulation methods take base64
+pub fn to_base64(encoded_transaction: &str) -> Option<String> {
+    let bytes = bs58::decode(encoded_transaction).into_vec().ok()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parses_failed_bundle_simulation() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": { "context": { "slot": 1 }, "value": {
+            "summary": { "failed": {
+                "error": { "TransactionFailure": [[1, 2, 3], "Error processing Instruction 0: custom program error: 0x1771"] },
+                "tx_signature": "5xyz"
+            }},
+            "transactionResults": [
+                { "err": null, "logs": ["Program log: swap"], "unitsConsumed": 52_000, "returnData": null },
+                { "err": { "InstructionError": [0, { "Custom": 6001 }] }, "logs": [], "unitsConsumed": 31_000, "returnData": null }
+            ]
+        }}});
+
+        let simulation = parse_simulate_bundle(&response).unwrap();
+        assert!(!simulation.succeeded());
+        assert_eq!(simulation.units_consumed(), 83_000);
+        let failure = simulation.failure.unwrap();
+        assert_eq!(failure.kind, "bundle_sim_instruction_error");
+        assert_eq!(failure.transaction_index, Some(1));
+        assert!(failure.to_string().starts_with("Bundle simulation failed at transaction 1"));
+    }
+
+    #[test]
+    fn test_parses_successful_bundle_simulation() {
+        let response = json!({ "result": { "value": {
+            "summary": "succeeded",
+            "transactionResults": [{ "err": null, "logs": [], "unitsConsumed": 40_000 }]
+        }}});
+        let simulation = parse_simulate_bundle(&response).unwrap();
+        assert!(simulation.succeeded());
+        assert_eq!(simulation.transactions[0].units_consumed, Some(40_000));
+        assert_eq!(parse_simulate_bundle(&json!({ "result": { "value": null } })), None);
+    }
+
+    #[test]
+    fn test_per_transaction_fallback_reports_first_failing_leg() {
+        let ok = json!({ "result": { "value": { "err": null, "logs": [], "unitsConsumed": 10 } } });
+        let failed = json!({ "result": { "value": { "err": "InsufficientFundsForFee", "logs": [], "unitsConsumed": 0 } } });
+        let simulation = from_transaction_simulations(&[ok.clone(), failed, ok]);
+        let failure = simulation.failure.unwrap();
+        assert_eq!(failure.transaction_index, Some(1));
+        assert_eq!(failure.kind, "bundle_sim_insufficient_funds");
+
+        assert!(is_method_unsupported(r#"Jito simulateBundle failed: {"code":-32601,"message":"Method not found"}"#));
+        assert!(!is_method_unsupported("Jito request failed: connection refused"));
+    }
+}
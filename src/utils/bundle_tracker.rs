@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use crate::config::BundlesConfig;
+use crate::logging::Logger;
+use crate::utils::jito::JitoClient;
+use crate::utils::leader_schedule::LeaderType;
+use crate::utils::mev_simulation_pipeline::MevSimulationResult;
+use crate::utils::mev_strategies::MevStrategyType;
+use crate::utils::tip_floor::TipPercentile;
+
+// getBundleStatuses takes at most this many ids per call
+const MAX_IDS_PER_REQUEST: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleState {
+    Pending,
+    Landed,
+    Failed,  // Landed, but a transaction in it errored
+    Expired, // Never showed up within the expiry window
+}
+
+// State of each bundle in a getBundleStatuses response; bundles the engine doesn't report are left out
+pub fn parse_bundle_statuses(response: &Value) -> HashMap<String, BundleState> {
+    let mut states = HashMap::new();
+    let Some(statuses) = response["result"]["value"].as_array() else {
+        return states;
+    };
+    for status in statuses.iter().filter(|status| !status.is_null()) {
+        let Some(bundle_id) = status["bundle_id"].as_str() else { continue };
+        let state = if status["err"].get("Ok").is_none() && !status["err"].is_null() {
+            BundleState::Failed
+        } else {
+            match status["confirmation_status"].as_str() {
+                Some("confirmed") | Some("finalized") => BundleState::Landed,
+                _ => BundleState::Pending,
+            }
+        };
+        states.insert(bundle_id.to_string(), state);
+    }
+    states
+}
+
+// Signatures of each reported bundle's transactions, in bundle order
+pub fn parse_bundle_transactions(response: &Value) -> HashMap<String, Vec<String>> {
+    response["result"]["value"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|status| {
+            let bundle_id = status["bundle_id"].as_str()?;
+            let signatures = status["transactions"].as_array()?.iter().filter_map(Value::as_str).map(str::to_string).collect();
+            Some((bundle_id.to_string(), signatures))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct PendingBundle {
+    strategy_type: MevStrategyType,
+    tip: f64,
+    percentile: Option<TipPercentile>,
+    leader: LeaderType,
+    profit: f64, // As claimed by the strategy result
+    fees: f64,
+    mints: Vec<String>, // Tokens the opportunity traded, for the per-token PnL
+    pools: Vec<String>, // Pools it traded through, for the pool blacklist
+    simulation: Option<MevSimulationResult>, // What the bundle was expected to do, for the accuracy check
+    submitted_at: Instant,
+}
+
+// A tracked bundle's final state, joined back to its strategy result through the bundle id
+#[derive(Debug, Clone)]
+pub struct ResolvedBundle {
+    pub bundle_id: String,
+    pub strategy_type: MevStrategyType,
+    pub state: BundleState,
+    pub tip: f64,
+    pub percentile: Option<TipPercentile>,
+    pub leader: LeaderType,
+    pub profit: f64,
+    pub fees: f64,
+    pub mints: Vec<String>,
+    pub pools: Vec<String>,
+    pub transactions: Vec<String>, // Signatures, as the block engine reported them
+    pub simulation: Option<MevSimulationResult>,
+    pub simulation_accuracy: Option<f64>, // Filled in by the strategy executor for landed bundles
+    pub realized_profit: Option<f64>, // Net SOL the landed transactions made the wallet, likewise
+}
+
+impl ResolvedBundle {
+    pub fn landed(&self) -> bool {
+        self.state == BundleState::Landed
+    }
+}
+
+// Accepted bundles whose landing we couldn't watch at submission time, polled through the
+// block engine until they land, fail or expire
+pub struct BundleTracker {
+    jito_client: Option<JitoClient>, // Same auth and 429 backoff as bundle submission
+    pending: Arc<RwLock<HashMap<String, PendingBundle>>>,
+    poll_interval: Duration,
+    expiry: Duration,
+}
+
+impl BundleTracker {
+    pub fn new(jito_client: Option<JitoClient>, poll_interval: Duration, expiry: Duration) -> Self {
+        Self {
+            jito_client,
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            poll_interval,
+            expiry,
+        }
+    }
+
+    pub fn from_config(jito_client: Option<JitoClient>, config: &BundlesConfig) -> Self {
+        Self::new(
+            jito_client,
+            Duration::from_millis(config.status_poll_ms.max(1)),
+            Duration::from_secs(config.status_expiry_secs),
+        )
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    pub async fn track(&self, bundle_id: &str, strategy_type: MevStrategyType, tip: f64, percentile: Option<TipPercentile>, leader: LeaderType) {
+        self.pending.write().await.insert(bundle_id.to_string(), PendingBundle {
+            strategy_type,
+            tip,
+            percentile,
+            leader,
+            profit: 0.0,
+            fees: 0.0,
+            mints: Vec::new(),
+            pools: Vec::new(),
+            simulation: None,
+            submitted_at: Instant::now(),
+        });
+    }
+
+    // Attach what the strategy expected of the bundle, booked only once it lands. No-op for
+    // bundles that aren't pending
+    pub async fn annotate(&self, bundle_id: &str, profit: f64, fees: f64, mints: Vec<String>, pools: Vec<String>) {
+        if let Some(bundle) = self.pending.write().await.get_mut(bundle_id) {
+            bundle.profit = profit;
+            bundle.fees = fees;
+            bundle.mints = mints;
+            bundle.pools = pools;
+        }
+    }
+
+    // The simulation the bundle was approved on, compared to what it realized once it lands
+    pub async fn attach_simulation(&self, bundle_id: &str, simulation: MevSimulationResult) {
+        if let Some(bundle) = self.pending.write().await.get_mut(bundle_id) {
+            bundle.simulation = Some(simulation);
+        }
+    }
+
+    pub async fn pending_count(&self) -> usize {
+        self.pending.read().await.len()
+    }
+
+    // One round of status checks; returns the bundles that reached a final state
+    pub async fn poll(&self) -> Vec<ResolvedBundle> {
+        let bundle_ids: Vec<String> = self.pending.read().await.keys().cloned().collect();
+        if bundle_ids.is_empty() {
+            return Vec::new();
+        }
+
+        // A getBundleStatuses call per five ids; without a block engine the bundles just expire
+        let mut states = HashMap::new();
+        let mut transactions = HashMap::new();
+        if let Some(ref jito_client) = self.jito_client {
+            for chunk in bundle_ids.chunks(MAX_IDS_PER_REQUEST) {
+                match jito_client.get_bundle_statuses(chunk).await {
+                    Ok(response) => {
+                        states.extend(parse_bundle_statuses(&response));
+                        transactions.extend(parse_bundle_transactions(&response));
+                    }
+                    Err(e) => {
+                        Logger::status_update(&format!("Could not poll bundle statuses: {}", e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let mut resolved = Vec::new();
+        self.pending.write().await.retain(|bundle_id, bundle| {
+            let state = match states.get(bundle_id) {
+                Some(state) if *state != BundleState::Pending => *state,
+                _ if now.saturating_duration_since(bundle.submitted_at) >= self.expiry => BundleState::Expired,
+                _ => return true,
+            };
+            resolved.push(ResolvedBundle {
+                bundle_id: bundle_id.clone(),
+                strategy_type: bundle.strategy_type.clone(),
+                state,
+                tip: bundle.tip,
+                percentile: bundle.percentile,
+                leader: bundle.leader,
+                profit: bundle.profit,
+                fees: bundle.fees,
+                mints: std::mem::take(&mut bundle.mints),
+                pools: std::mem::take(&mut bundle.pools),
+                transactions: transactions.remove(bundle_id).unwrap_or_default(),
+                simulation: bundle.simulation.take(),
+                simulation_accuracy: None,
+                realized_profit: None,
+            });
+            false
+        });
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jito_rate_limit::BundleRateLimiter;
+    use serde_json::json;
+    use crate::utils::mock_http::{self, MockResponse};
+
+    // Block engine answering each request with the next body in the list, repeating the last
+    async fn mock_block_engine(bodies: Vec<Value>) -> String {
+        let (url, _) = mock_http::serve(move |request| MockResponse::json(&bodies[request.index.min(bodies.len() - 1)])).await;
+        url
+    }
+
+    fn statuses(value: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "result": { "context": { "slot": 250_000_000 }, "value": value } })
+    }
+
+    fn tracker(url: String, expiry: Duration) -> BundleTracker {
+        let rate_limiter = Arc::new(BundleRateLimiter::new(100.0, 10, Duration::from_secs(1)));
+        let jito_client = JitoClient::with_url(url).with_rate_limiter(rate_limiter);
+        BundleTracker::new(Some(jito_client), Duration::from_millis(10), expiry)
+    }
+
+    #[test]
+    fn test_parses_bundle_statuses() {
+        let response = statuses(json!([
+            { "bundle_id": "landed", "slot": 1, "confirmation_status": "confirmed", "err": { "Ok": null } },
+            { "bundle_id": "processing", "slot": 1, "confirmation_status": "processed", "err": { "Ok": null } },
+            { "bundle_id": "failed", "slot": 1, "confirmation_status": "confirmed", "err": { "Err": "InstructionError" } },
+            null
+        ]));
+        let states = parse_bundle_statuses(&response);
+        assert_eq!(states.get("landed"), Some(&BundleState::Landed));
+        assert_eq!(states.get("processing"), Some(&BundleState::Pending));
+        assert_eq!(states.get("failed"), Some(&BundleState::Failed));
+        assert_eq!(states.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pending_bundle_resolves_once_landed() {
+        let url = mock_block_engine(vec![
+            statuses(json!([null])),
+            statuses(json!([{ "bundle_id": "bundle-1", "slot": 2, "confirmation_status": "processed", "err": { "Ok": null } }])),
+            statuses(json!([{
+                "bundle_id": "bundle-1", "slot": 2, "confirmation_status": "confirmed", "err": { "Ok": null },
+                "transactions": ["frontSig", "victimSig", "backSig"]
+            }])),
+        ]).await;
+        let tracker = tracker(url, Duration::from_secs(60));
+        tracker.track("bundle-1", MevStrategyType::Arbitrage, 0.001, Some(TipPercentile::P50), LeaderType::Jito).await;
+        tracker.annotate("bundle-1", 0.02, 0.0005, vec!["BONK".to_string()], vec!["PoolA".to_string()]).await;
+
+        assert!(tracker.poll().await.is_empty());
+        assert!(tracker.poll().await.is_empty());
+        let resolved = tracker.poll().await;
+
+        assert_eq!(resolved.len(), 1);
+        assert!(resolved[0].landed());
+        assert_eq!(resolved[0].bundle_id, "bundle-1");
+        assert!((resolved[0].profit - 0.02).abs() < 1e-12);
+        assert_eq!(resolved[0].mints, vec!["BONK".to_string()]);
+        assert_eq!(resolved[0].pools, vec!["PoolA".to_string()]);
+        assert_eq!(resolved[0].transactions, vec!["frontSig", "victimSig", "backSig"]);
+        assert_eq!(resolved[0].percentile, Some(TipPercentile::P50));
+        assert_eq!(resolved[0].leader, LeaderType::Jito);
+        assert_eq!(tracker.pending_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unreported_bundle_expires() {
+        let url = mock_block_engine(vec![statuses(json!([null]))]).await;
+        let tracker = tracker(url, Duration::ZERO);
+        tracker.track("bundle-2", MevStrategyType::Backrun, 0.002, None, LeaderType::Unknown).await;
+
+        let resolved = tracker.poll().await;
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].state, BundleState::Expired);
+        assert!(!resolved[0].landed());
+    }
+
+    #[tokio::test]
+    async fn test_poll_holds_off_while_block_engine_backs_off() {
+        let url = mock_block_engine(vec![statuses(json!([{ "bundle_id": "bundle-3", "slot": 2, "confirmation_status": "confirmed", "err": { "Ok": null } }]))]).await;
+        let rate_limiter = Arc::new(BundleRateLimiter::new(100.0, 10, Duration::from_secs(1)));
+        rate_limiter.record_rate_limited(Some(Duration::from_secs(60)));
+        let jito_client = JitoClient::with_url(url).with_rate_limiter(rate_limiter);
+        let tracker = BundleTracker::new(Some(jito_client), Duration::from_millis(10), Duration::from_secs(60));
+        tracker.track("bundle-3", MevStrategyType::Arbitrage, 0.001, None, LeaderType::Jito).await;
+
+        assert!(tracker.poll().await.is_empty());
+        assert_eq!(tracker.pending_count().await, 1);
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use crate::config::Config;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+
+const MAX_OUTCOMES: usize = 200;
+// Pseudo-observations at the neutral rate, so a handful of results doesn't swing the score
+const PRIOR_WEIGHT: f64 = 5.0;
+const PRIOR_RATE: f64 = 0.5;
+const BUNDLE_FAILURE_WEIGHT: f64 = 0.6;
+const CONTESTED_TARGET_WEIGHT: f64 = 0.4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompetitionSnapshot {
+    pub score: f64,
+    pub bundle_failure_rate: f64,
+    pub contested_target_rate: f64,
+    pub bundles: usize,
+    pub targets: usize,
+}
+
+// Rolling window of (time, lost) outcomes
+#[derive(Debug, Clone)]
+struct OutcomeWindow {
+    outcomes: VecDeque<(Instant, bool)>,
+}
+
+impl OutcomeWindow {
+    fn new() -> Self {
+        Self { outcomes: VecDeque::with_capacity(MAX_OUTCOMES) }
+    }
+
+    fn record(&mut self, lost: bool, now: Instant) {
+        if self.outcomes.len() >= MAX_OUTCOMES {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back((now, lost));
+    }
+
+    fn prune(&mut self, window: Duration, now: Instant) {
+        while self.outcomes.front().is_some_and(|(at, _)| now.saturating_duration_since(*at) > window) {
+            self.outcomes.pop_front();
+        }
+    }
+
+    // Loss rate pulled towards the neutral prior while samples are few
+    fn smoothed_rate(&self) -> f64 {
+        let lost = self.outcomes.iter().filter(|(_, lost)| *lost).count() as f64;
+        (lost + PRIOR_RATE * PRIOR_WEIGHT) / (self.outcomes.len() as f64 + PRIOR_WEIGHT)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompetitionState {
+    bundles: OutcomeWindow, // lost = bundle didn't land
+    targets: OutcomeWindow, // lost = someone else's transaction sat right next to our target
+}
+
+impl CompetitionState {
+    fn snapshot(&mut self, window: Duration, now: Instant) -> CompetitionSnapshot {
+        self.bundles.prune(window, now);
+        self.targets.prune(window, now);
+        let bundle_failure_rate = self.bundles.smoothed_rate();
+        let contested_target_rate = self.targets.smoothed_rate();
+        CompetitionSnapshot {
+            score: (BUNDLE_FAILURE_WEIGHT * bundle_failure_rate + CONTESTED_TARGET_WEIGHT * contested_target_rate).clamp(0.0, 1.0),
+            bundle_failure_rate,
+            contested_target_rate,
+            bundles: self.bundles.outcomes.len(),
+            targets: self.targets.outcomes.len(),
+        }
+    }
+}
+
+// Whether the transaction landed right before the target touched the same pool without being
+// signed by us, i.e. another searcher got there first. None when the target isn't in the block
+pub fn target_was_contested(block: &Value, target_signature: &str, pool_address: &str, our_wallet: Option<&str>) -> Option<bool> {
+    let transactions = block["result"]["transactions"].as_array()?;
+    let index = transactions.iter().position(|tx| {
+        tx["transaction"]["signatures"].as_array()
+            .is_some_and(|signatures| signatures.iter().any(|signature| signature == target_signature))
+    })?;
+    if index == 0 {
+        return Some(false);
+    }
+
+    let keys = transactions[index - 1]["transaction"]["accountKeys"].as_array()?;
+    let touches_pool = keys.iter().any(|key| key["pubkey"] == pool_address);
+    let signed_by_us = our_wallet.is_some_and(|wallet| {
+        keys.iter().any(|key| key["pubkey"] == wallet && key["signer"].as_bool().unwrap_or(false))
+    });
+    Some(touches_pool && !signed_by_us)
+}
+
+// Competition level from our own outcomes: how often our bundles fail to land and how often
+// the targets we go after are already flanked by someone else
+pub struct CompetitionTracker {
+    rpc_manager: Arc<RpcManager>,
+    state: Arc<RwLock<CompetitionState>>,
+    window: Duration,
+    wallet: Option<String>,
+}
+
+impl CompetitionTracker {
+    pub fn new(rpc_manager: Arc<RpcManager>, window: Duration, wallet: Option<String>) -> Self {
+        Self {
+            rpc_manager,
+            state: Arc::new(RwLock::new(CompetitionState {
+                bundles: OutcomeWindow::new(),
+                targets: OutcomeWindow::new(),
+            })),
+            window,
+            wallet,
+        }
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, config: &Config) -> Self {
+        Self::new(
+            rpc_manager,
+            Duration::from_secs(config.strategies.competition_window_secs),
+            config.wallet_address.clone(),
+        )
+    }
+
+    pub async fn record_bundle(&self, landed: bool) {
+        self.state.write().await.bundles.record(!landed, Instant::now());
+    }
+
+    pub async fn record_target(&self, contested: bool) {
+        self.state.write().await.targets.record(contested, Instant::now());
+    }
+
+    pub async fn snapshot(&self) -> CompetitionSnapshot {
+        self.state.write().await.snapshot(self.window, Instant::now())
+    }
+
+    pub async fn score(&self) -> f64 {
+        self.snapshot().await.score
+    }
+
+    // Once the target has had time to land, look at its block and record whether another
+    // searcher's transaction on the same pool preceded it
+    pub fn check_target(&self, target_signature: String, pool_address: String) {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(2)).await; // ~5 slots
+            let slot = match tracker.rpc_manager.get_transaction(&target_signature).await {
+                Ok(Some(tx)) => tx.slot,
+                Ok(None) => return, // Not landed (yet); nothing to learn
+                Err(e) => {
+                    Logger::status_update(&format!("Could not look up target {}: {}", target_signature, e));
+                    return;
+                }
+            };
+            match tracker.rpc_manager.get_block_accounts(slot).await {
+                Ok(block) => {
+                    if let Some(contested) = target_was_contested(&block, &target_signature, &pool_address, tracker.wallet.as_deref()) {
+                        tracker.record_target(contested).await;
+                    }
+                }
+                Err(e) => Logger::status_update(&format!("Could not load block {} for target {}: {}", slot, target_signature, e)),
+            }
+        });
+    }
+}
+
+impl Clone for CompetitionTracker {
+    fn clone(&self) -> Self {
+        Self {
+            rpc_manager: Arc::clone(&self.rpc_manager),
+            state: Arc::clone(&self.state),
+            window: self.window,
+            wallet: self.wallet.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> CompetitionState {
+        CompetitionState { bundles: OutcomeWindow::new(), targets: OutcomeWindow::new() }
+    }
+
+    #[test]
+    fn test_score_rises_as_failures_accumulate() {
+        let mut state = state();
+        let now = Instant::now();
+        let window = Duration::from_secs(600);
+        let neutral = state.snapshot(window, now).score;
+        assert!((neutral - 0.5).abs() < 1e-9);
+
+        let mut previous = neutral;
+        for _ in 0..10 {
+            state.bundles.record(true, now);
+            let score = state.snapshot(window, now).score;
+            assert!(score > previous);
+            previous = score;
+        }
+
+        for _ in 0..10 {
+            state.targets.record(true, now);
+        }
+        assert!(state.snapshot(window, now).score > previous);
+
+        // Outcomes older than the window stop counting
+        let later = now + Duration::from_secs(601);
+        assert!((state.snapshot(window, later).score - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_landed_bundles_lower_the_score() {
+        let mut state = state();
+        let now = Instant::now();
+        for _ in 0..30 {
+            state.bundles.record(false, now);
+        }
+        let snapshot = state.snapshot(Duration::from_secs(600), now);
+        assert!(snapshot.bundle_failure_rate < 0.1);
+        assert!(snapshot.score < 0.5);
+        assert_eq!(snapshot.bundles, 30);
+    }
+
+    #[test]
+    fn test_detects_competing_transaction_before_target() {
+        let tx = |signature: &str, keys: Vec<(&str, bool)>| json!({
+            "transaction": {
+                "signatures": [signature],
+                "accountKeys": keys.iter().map(|(pubkey, signer)| json!({ "pubkey": pubkey, "signer": signer, "writable": true })).collect::<Vec<_>>()
+            }
+        });
+        let block = json!({ "result": { "transactions": [
+            tx("other", vec![("Searcher", true), ("Poo1", false)]),
+            tx("target", vec![("Victim", true), ("Poo1", false)]),
+            tx("ours", vec![("Us", true), ("Poo1", false)]),
+        ]}});
+
+        assert_eq!(target_was_contested(&block, "target", "Poo1", Some("Us")), Some(true));
+        assert_eq!(target_was_contested(&block, "target", "Poo2", Some("Us")), Some(false));
+        assert_eq!(target_was_contested(&block, "target", "Poo1", Some("Searcher")), Some(false));
+        assert_eq!(target_was_contested(&block, "missing", "Poo1", Some("Us")), None);
+    }
+}
@@ -1,6 +1,59 @@
 use reqwest;
 use serde_json::Value;
 use crate::logging::Logger;
+use crate::utils::amm_math;
+use crate::utils::opportunity_evaluator::PoolState;
+
+// Direction of a swap through a pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapSide {
+    AToB, // Sells token_a for token_b
+    BToA,
+}
+
+impl SwapSide {
+    // Side that sells input_mint into the pool; None if the pool doesn't hold it
+    pub fn selling(pool: &PoolState, input_mint: &str) -> Option<Self> {
+        if input_mint == pool.token_a {
+            Some(SwapSide::AToB)
+        } else if input_mint == pool.token_b {
+            Some(SwapSide::BToA)
+        } else {
+            None
+        }
+    }
+
+    fn reserves(self, pool: &PoolState) -> (u64, u64) {
+        match self {
+            SwapSide::AToB => (pool.reserve_a, pool.reserve_b),
+            SwapSide::BToA => (pool.reserve_b, pool.reserve_a),
+        }
+    }
+}
+
+// What a trade does to a pool. Prices are output per input in raw units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceImpact {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub spot_price: f64,
+    pub execution_price: f64,
+    pub post_trade_price: f64, // Marginal price once the trade lands
+    pub impact_bps: f64, // Drop from spot_price to post_trade_price
+    pub reserve_a_after: u64,
+    pub reserve_b_after: u64,
+}
+
+impl PriceImpact {
+    // The pool as the next leg of a route sees it; liquidity is left as it was
+    pub fn pool_after(&self, pool: &PoolState) -> PoolState {
+        PoolState {
+            reserve_a: self.reserve_a_after,
+            reserve_b: self.reserve_b_after,
+            ..pool.clone()
+        }
+    }
+}
 
 pub struct DexApi {
     client: reqwest::Client,
@@ -15,6 +68,64 @@ impl DexApi {
         }
     }
 
+    // Selling `size` into the pool on the given side, the fee staying in the pool as on Raydium.
+    // None when either reserve is empty
+    pub fn price_impact(pool: &PoolState, side: SwapSide, size: u64) -> Option<PriceImpact> {
+        let (reserve_in, reserve_out) = side.reserves(pool);
+        if reserve_in == 0 || reserve_out == 0 {
+            return None;
+        }
+
+        let amount_out = amm_math::get_amount_out(size, reserve_in, reserve_out, pool.fee_rate);
+        let (in_after, out_after) = (reserve_in.saturating_add(size), reserve_out - amount_out);
+        let spot_price = reserve_out as f64 / reserve_in as f64;
+        let post_trade_price = out_after as f64 / in_after as f64;
+        let (reserve_a_after, reserve_b_after) = match side {
+            SwapSide::AToB => (in_after, out_after),
+            SwapSide::BToA => (out_after, in_after),
+        };
+
+        Some(PriceImpact {
+            amount_in: size,
+            amount_out,
+            spot_price,
+            execution_price: if size > 0 { amount_out as f64 / size as f64 } else { spot_price },
+            post_trade_price,
+            impact_bps: (1.0 - post_trade_price / spot_price).max(0.0) * 10_000.0,
+            reserve_a_after,
+            reserve_b_after,
+        })
+    }
+
+    // Largest trade on the given side that moves the pool's price by at most max_bps. The move is
+    // 1 - x² / ((x + dx)(x + γ·dx)); its root brackets a binary search over price_impact, which
+    // rounds the way the pool does
+    pub fn max_size_for_impact(pool: &PoolState, side: SwapSide, max_bps: f64) -> u64 {
+        let (reserve_in, reserve_out) = side.reserves(pool);
+        if reserve_in == 0 || reserve_out == 0 || max_bps.is_nan() || max_bps <= 0.0 {
+            return 0;
+        }
+        if max_bps >= 10_000.0 {
+            return u64::MAX;
+        }
+
+        let (x, gamma) = (reserve_in as f64, (1.0 - pool.fee_rate.clamp(0.0, 1.0)).max(1e-9));
+        let keep = 1.0 - max_bps / 10_000.0;
+        let root = (-x * (1.0 + gamma) + (x * x * (1.0 + gamma).powi(2) + 4.0 * gamma * x * x * (1.0 / keep - 1.0)).sqrt()) / (2.0 * gamma);
+        let within = |size: u64| Self::price_impact(pool, side, size).is_some_and(|impact| impact.impact_bps <= max_bps);
+
+        let (mut low, mut high) = (0u64, (root * 2.0 + 2.0).min(u64::MAX as f64) as u64);
+        while low < high {
+            let mid = low + (high - low) / 2 + 1;
+            if within(mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        low
+    }
+
     pub async fn get_raydium_pools(&self) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
         // Raydium API or direct Solana RPC call to fetch pool data
         // In practice, this would call Raydium's API or query Solana accounts
@@ -104,4 +215,80 @@ impl DexApi {
 
         Ok(response)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    fn pool(reserve_a: u64, reserve_b: u64, fee_rate: f64) -> PoolState {
+        PoolState {
+            pool_address: "Poo1".to_string(),
+            dex: "Raydium".to_string(),
+            token_a: WSOL_MINT.to_string(),
+            token_b: USDC_MINT.to_string(),
+            reserve_a,
+            reserve_b,
+            liquidity: 0.0,
+            fee_rate,
+            vaults: Vec::new(),
+            last_updated: std::time::SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_impact_reports_the_post_trade_pool() {
+        // 1 SOL into 100 SOL / 15,000 USDC
+        let pool = pool(100_000_000_000, 15_000_000_000, 0.0025);
+        assert_eq!(SwapSide::selling(&pool, USDC_MINT), Some(SwapSide::BToA));
+        let impact = DexApi::price_impact(&pool, SwapSide::AToB, 1_000_000_000).unwrap();
+
+        assert_eq!(impact.amount_out, amm_math::get_amount_out(1_000_000_000, 100_000_000_000, 15_000_000_000, 0.0025));
+        assert_eq!((impact.reserve_a_after, impact.reserve_b_after), (101_000_000_000, 15_000_000_000 - impact.amount_out));
+        assert!(impact.post_trade_price < impact.execution_price && impact.execution_price < impact.spot_price);
+        // About 2% for a 1% trade: x² / ((x + dx)(x + γ·dx))
+        assert!((impact.impact_bps - (1.0 - 1.0 / (1.01 * 1.009975)) * 10_000.0).abs() < 0.01);
+
+        // The next leg starts from the moved price
+        let after = impact.pool_after(&pool);
+        assert!((DexApi::price_impact(&after, SwapSide::AToB, 0).unwrap().spot_price - impact.post_trade_price).abs() < 1e-15);
+        assert!(DexApi::price_impact(&self::pool(0, 1, 0.0025), SwapSide::AToB, 1).is_none());
+    }
+
+    #[test]
+    fn test_impact_is_monotone_in_size() {
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..1_000 {
+            let pool = pool(rng.gen_range(1_000_000..1_000_000_000_000u64), rng.gen_range(1_000_000..1_000_000_000_000u64), [0.0, 0.0025, 0.01][rng.gen_range(0..3)]);
+            let side = if rng.gen_bool(0.5) { SwapSide::AToB } else { SwapSide::BToA };
+            let smaller = rng.gen_range(1..1_000_000_000_000u64);
+            let larger = smaller + rng.gen_range(1..1_000_000_000_000u64);
+
+            let (small, large) = (DexApi::price_impact(&pool, side, smaller).unwrap(), DexApi::price_impact(&pool, side, larger).unwrap());
+            assert!(large.impact_bps >= small.impact_bps);
+            assert!(large.post_trade_price <= small.post_trade_price);
+        }
+    }
+
+    #[test]
+    fn test_max_size_inverts_price_impact() {
+        let mut rng = StdRng::seed_from_u64(29);
+        for _ in 0..1_000 {
+            let pool = pool(rng.gen_range(1_000_000_000..1_000_000_000_000u64), rng.gen_range(1_000_000_000..1_000_000_000_000u64), [0.0, 0.0025, 0.01][rng.gen_range(0..3)]);
+            let side = if rng.gen_bool(0.5) { SwapSide::AToB } else { SwapSide::BToA };
+            let max_bps = [1.0, 10.0, 100.0, 1_000.0, 5_000.0][rng.gen_range(0..5)];
+
+            let size = DexApi::max_size_for_impact(&pool, side, max_bps);
+            let at_size = DexApi::price_impact(&pool, side, size).unwrap().impact_bps;
+            assert!(at_size <= max_bps);
+            assert!((at_size - max_bps).abs() < 0.01, "{} bps at {} for a {} bps budget", at_size, size, max_bps);
+            assert!(DexApi::price_impact(&pool, side, size + 1).unwrap().impact_bps > max_bps);
+        }
+
+        let pool = pool(100_000_000_000, 15_000_000_000, 0.0025);
+        assert_eq!(DexApi::max_size_for_impact(&pool, SwapSide::AToB, 0.0), 0);
+        assert_eq!(DexApi::max_size_for_impact(&pool, SwapSide::AToB, 10_000.0), u64::MAX);
+    }
+}
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{broadcast, RwLock};
 use tokio::time::{timeout, Duration};
+use crate::config::PoolsConfig;
 use crate::logging::Logger;
+use crate::utils::opportunity_evaluator::PoolState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolInfo {
@@ -29,6 +34,8 @@ pub struct DEXMonitor {
     pub pools: HashMap<String, PoolInfo>,
     pub token_prices: HashMap<String, f64>, // Price relative to USD
     pub last_update: std::time::Instant,
+    pool_updates: HashMap<String, SystemTime>, // When each pool's reserves were last read
+    max_pool_age: Duration, // Pools not updated for longer are left out of arbitrage
 }
 
 impl DEXMonitor {
@@ -37,16 +44,73 @@ impl DEXMonitor {
             pools: HashMap::new(),
             token_prices: HashMap::new(),
             last_update: std::time::Instant::now(),
+            pool_updates: HashMap::new(),
+            max_pool_age: Duration::from_millis(2000),
         }
     }
 
+    pub fn from_config(config: &PoolsConfig) -> Self {
+        Self::new().with_max_pool_age(Duration::from_millis(config.dex_monitor_max_pool_age_ms))
+    }
+
+    pub fn with_max_pool_age(mut self, max_pool_age: Duration) -> Self {
+        self.max_pool_age = max_pool_age;
+        self
+    }
+
     pub async fn update_pools(&mut self, pools: Vec<PoolInfo>) {
+        let now = SystemTime::now();
         for pool in pools {
+            self.pool_updates.insert(pool.address.clone(), now);
             self.pools.insert(pool.address.clone(), pool);
         }
         self.last_update = std::time::Instant::now();
     }
 
+    // Takes the reserves from a pool state read off chain, adding the pool if it's new. A state
+    // older than the one already applied is ignored
+    pub fn apply_pool_state(&mut self, state: &PoolState) {
+        if self.pool_updates.get(&state.pool_address).is_some_and(|applied| *applied > state.last_updated) {
+            return;
+        }
+        self.pools.insert(state.pool_address.clone(), PoolInfo {
+            address: state.pool_address.clone(),
+            token_a: state.token_a.clone(),
+            token_b: state.token_b.clone(),
+            reserve_a: state.reserve_a,
+            reserve_b: state.reserve_b,
+            pool_type: state.dex.to_lowercase(),
+        });
+        self.pool_updates.insert(state.pool_address.clone(), state.last_updated);
+        self.last_update = std::time::Instant::now();
+    }
+
+    // Applies every pool state the pool watcher streams from its accountSubscribe connection.
+    // Updates missed by falling behind leave those pools to age out until their next change
+    pub fn spawn_ingestion(monitor: Arc<RwLock<DEXMonitor>>, mut updates: broadcast::Receiver<PoolState>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match updates.recv().await {
+                    Ok(pool_state) => monitor.write().await.apply_pool_state(&pool_state),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        Logger::status_update(&format!("DEX monitor missed {} pool updates", missed));
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+
+    // Time since the pool's reserves were last read; None for unknown pools
+    pub fn pool_age(&self, address: &str) -> Option<Duration> {
+        let updated = self.pool_updates.get(address)?;
+        Some(SystemTime::now().duration_since(*updated).unwrap_or(Duration::ZERO))
+    }
+
+    fn is_fresh(&self, address: &str) -> bool {
+        self.pool_age(address).is_some_and(|age| age <= self.max_pool_age)
+    }
+
     pub fn get_pool(&self, address: &str) -> Option<&PoolInfo> {
         self.pools.get(address)
     }
@@ -55,13 +119,15 @@ impl DEXMonitor {
         self.pools.values().collect()
     }
 
-    // Calculate arbitrage opportunity between two pools for the same token pair
+    // Calculate arbitrage opportunity between two pools for the same token pair; pools older
+    // than the staleness bound are not used
     pub fn find_arbitrage_opportunity(&self, token_a: &str, token_b: &str) -> Option<ArbitrageOpportunity> {
         let pools_a_to_b: Vec<&PoolInfo> = self.pools.values()
             .filter(|pool| 
                 (pool.token_a == token_a && pool.token_b == token_b) || 
                 (pool.token_a == token_b && pool.token_b == token_a)
             )
+            .filter(|pool| self.is_fresh(&pool.address))
             .collect();
 
         if pools_a_to_b.len() < 2 {
@@ -225,4 +291,67 @@ pub enum SwapType {
     LiquidityRemove,
     RaydiumSwap,
     OrcaSwap,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    // A SOL/USDC pool state as the pool watcher emits it after a vault update
+    fn pool_state(address: &str, sol_reserve: u64, usdc_reserve: u64, age: Duration) -> PoolState {
+        PoolState {
+            pool_address: address.to_string(),
+            dex: "Raydium".to_string(),
+            token_a: WSOL_MINT.to_string(),
+            token_b: USDC_MINT.to_string(),
+            reserve_a: sol_reserve,
+            reserve_b: usdc_reserve,
+            liquidity: sol_reserve as f64 / 1_000_000_000.0 * 2.0,
+            fee_rate: 0.0025,
+            vaults: Vec::new(),
+            last_updated: SystemTime::now() - age,
+        }
+    }
+
+    #[test]
+    fn test_updates_move_the_arbitrage_and_stale_pools_are_skipped() {
+        let mut monitor = DEXMonitor::new().with_max_pool_age(Duration::from_millis(500));
+        monitor.apply_pool_state(&pool_state("PoolA", 1_000_000_000_000, 150_000_000_000, Duration::ZERO));
+        monitor.apply_pool_state(&pool_state("PoolB", 1_000_000_000_000, 150_000_000_000, Duration::ZERO));
+        assert!(monitor.find_arbitrage_opportunity(WSOL_MINT, USDC_MINT).is_none());
+
+        // A swap on PoolB moves its USDC vault: SOL is now 2% dearer there
+        monitor.apply_pool_state(&pool_state("PoolB", 1_000_000_000_000, 153_000_000_000, Duration::ZERO));
+        let opportunity = monitor.find_arbitrage_opportunity(WSOL_MINT, USDC_MINT).expect("price gap");
+        assert_eq!((opportunity.buy_pool.as_str(), opportunity.sell_pool.as_str()), ("PoolA", "PoolB"));
+        assert!((opportunity.price_ratio - 1.02).abs() < 1e-9);
+
+        // An update that arrives out of order doesn't roll the reserves back
+        monitor.apply_pool_state(&pool_state("PoolB", 1_000_000_000_000, 150_000_000_000, Duration::from_millis(100)));
+        assert_eq!(monitor.get_pool("PoolB").unwrap().reserve_b, 153_000_000_000);
+
+        // PoolA stops updating: its price can't be trusted anymore
+        let mut stale = monitor.clone();
+        stale.pool_updates.insert("PoolA".to_string(), SystemTime::now() - Duration::from_secs(10));
+        assert!(stale.pool_age("PoolA").unwrap() >= Duration::from_secs(10));
+        assert!(stale.find_arbitrage_opportunity(WSOL_MINT, USDC_MINT).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ingests_the_pool_watcher_stream() {
+        let monitor = Arc::new(RwLock::new(DEXMonitor::new()));
+        let (sender, receiver) = broadcast::channel(16);
+        let ingestion = DEXMonitor::spawn_ingestion(Arc::clone(&monitor), receiver);
+
+        sender.send(pool_state("PoolA", 1_000_000_000_000, 150_000_000_000, Duration::ZERO)).unwrap();
+        sender.send(pool_state("PoolB", 1_000_000_000_000, 147_000_000_000, Duration::ZERO)).unwrap();
+        drop(sender);
+        ingestion.await.unwrap();
+
+        let monitor = monitor.read().await;
+        assert_eq!(monitor.get_all_pools().len(), 2);
+        assert_eq!(monitor.get_pool("PoolB").unwrap().pool_type, "raydium");
+        let opportunity = monitor.find_arbitrage_opportunity(USDC_MINT, WSOL_MINT).expect("price gap");
+        assert_eq!((opportunity.buy_pool.as_str(), opportunity.sell_pool.as_str()), ("PoolB", "PoolA"));
+    }
+}
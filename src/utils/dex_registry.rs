@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::config::PoolsConfig;
+use crate::utils::instruction_parser::ParsedInstruction;
+use crate::utils::meteora_dlmm::{self, DlmmSwap, METEORA_DLMM_PROGRAM_ID};
+use crate::utils::orca_whirlpool::{self, WhirlpoolSwap, WHIRLPOOL_PROGRAM_ID};
+use crate::utils::phoenix::PHOENIX_PROGRAM_ID;
+use crate::utils::pool_creation::RAYDIUM_AMM_V4_PROGRAM_ID;
+use crate::utils::pumpfun::PUMPFUN_PROGRAM_ID;
+use crate::utils::raydium_amm::{self, RaydiumSwap};
+
+pub const JUPITER_V6_PROGRAM_ID: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+pub const RAYDIUM_CLMM_PROGRAM_ID: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DexKind {
+    Jupiter,
+    RaydiumAmmV4,
+    RaydiumClmm,
+    OrcaWhirlpool,
+    MeteoraDlmm,
+    Phoenix,
+    PumpFun,
+    Custom, // Added through the registry file; recognized but without a decoder
+}
+
+// Swap decoded by one of the registered decoder hooks
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedSwap {
+    Raydium(RaydiumSwap),
+    Whirlpool(WhirlpoolSwap),
+    Meteora(DlmmSwap),
+}
+
+pub type SwapDecoder = fn(&ParsedInstruction, &Value) -> Option<DecodedSwap>;
+
+#[derive(Debug, Clone)]
+pub struct DexProgram {
+    pub program_id: String,
+    pub kind: DexKind,
+    pub name: String, // Value used for OpportunityDetails.dex
+    pub decoder: Option<SwapDecoder>,
+}
+
+// Entry of the DEX_REGISTRY_FILE JSON array
+#[derive(Debug, Clone, Deserialize)]
+struct DexProgramConfig {
+    program_id: String,
+    name: String,
+    #[serde(default = "default_kind")]
+    kind: DexKind,
+}
+
+fn default_kind() -> DexKind {
+    DexKind::Custom
+}
+
+fn decode_raydium_swap(instruction: &ParsedInstruction, tx_details: &Value) -> Option<DecodedSwap> {
+    raydium_amm::decode_instruction(instruction, tx_details).map(DecodedSwap::Raydium)
+}
+
+fn decode_whirlpool_swap(instruction: &ParsedInstruction, _tx_details: &Value) -> Option<DecodedSwap> {
+    orca_whirlpool::decode_instruction(instruction).map(DecodedSwap::Whirlpool)
+}
+
+fn decode_dlmm_swap(instruction: &ParsedInstruction, tx_details: &Value) -> Option<DecodedSwap> {
+    meteora_dlmm::decode_instruction(instruction, tx_details).map(DecodedSwap::Meteora)
+}
+
+// Exact program ID lookup for the DEXes we monitor
+#[derive(Debug, Clone)]
+pub struct DexRegistry {
+    programs: HashMap<String, DexProgram>,
+}
+
+impl DexRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self { programs: HashMap::new() };
+        registry.register(JUPITER_V6_PROGRAM_ID, DexKind::Jupiter, "Jupiter", None);
+        registry.register(RAYDIUM_AMM_V4_PROGRAM_ID, DexKind::RaydiumAmmV4, "Raydium", Some(decode_raydium_swap));
+        registry.register(RAYDIUM_CLMM_PROGRAM_ID, DexKind::RaydiumClmm, "RaydiumClmm", None);
+        registry.register(WHIRLPOOL_PROGRAM_ID, DexKind::OrcaWhirlpool, "Orca", Some(decode_whirlpool_swap));
+        registry.register(METEORA_DLMM_PROGRAM_ID, DexKind::MeteoraDlmm, "Meteora", Some(decode_dlmm_swap));
+        registry.register(PHOENIX_PROGRAM_ID, DexKind::Phoenix, "Phoenix", None);
+        registry.register(PUMPFUN_PROGRAM_ID, DexKind::PumpFun, "PumpFun", None);
+        registry
+    }
+
+    // Built-in programs plus any listed in DEX_REGISTRY_FILE
+    pub fn from_config(config: &PoolsConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut registry = Self::new();
+        if let Some(path) = &config.dex_registry_file {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read DEX_REGISTRY_FILE {}: {}", path, e))?;
+            registry.load_config(&contents)?;
+        }
+        Ok(registry)
+    }
+
+    // Format: [{"program_id": "...", "name": "...", "kind": "Custom"}]; entries override
+    // built-ins with the same program ID but keep their decoder
+    pub fn load_config(&mut self, contents: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let entries: Vec<DexProgramConfig> = serde_json::from_str(contents)
+            .map_err(|e| format!("Invalid DEX registry config: {}", e))?;
+
+        for entry in entries {
+            if bs58::decode(&entry.program_id).into_vec().map_or(true, |key| key.len() != 32) {
+                return Err(format!("Invalid program ID in DEX registry config: {}", entry.program_id).into());
+            }
+            let decoder = self.programs.get(&entry.program_id).and_then(|program| program.decoder);
+            self.register(&entry.program_id, entry.kind, &entry.name, decoder);
+        }
+        Ok(())
+    }
+
+    pub fn register(&mut self, program_id: &str, kind: DexKind, name: &str, decoder: Option<SwapDecoder>) {
+        self.programs.insert(program_id.to_string(), DexProgram {
+            program_id: program_id.to_string(),
+            kind,
+            name: name.to_string(),
+            decoder,
+        });
+    }
+
+    pub fn lookup(&self, program_id: &str) -> Option<&DexProgram> {
+        self.programs.get(program_id)
+    }
+
+    pub fn dex_name(&self, program_id: &str) -> Option<&str> {
+        self.lookup(program_id).map(|program| program.name.as_str())
+    }
+
+    pub fn is_known(&self, program_id: &str) -> bool {
+        self.programs.contains_key(program_id)
+    }
+
+    pub fn program_ids(&self) -> impl Iterator<Item = &str> {
+        self.programs.keys().map(|id| id.as_str())
+    }
+
+    // Runs the decoder hook of the instruction's program, if it has one
+    pub fn decode_swap(&self, instruction: &ParsedInstruction, tx_details: &Value) -> Option<DecodedSwap> {
+        let decoder = self.lookup(&instruction.program_id)?.decoder?;
+        decoder(instruction, tx_details)
+    }
+
+    // True when the logs show an invocation of any registered program
+    pub fn mentions_known_program(&self, logs: &[Value]) -> bool {
+        logs.iter()
+            .filter_map(|log| log.as_str())
+            .filter_map(|log| log.strip_prefix("Program "))
+            .filter_map(|rest| rest.split_whitespace().next().map(|id| (id, rest)))
+            .any(|(program_id, rest)| rest[program_id.len()..].starts_with(" invoke") && self.is_known(program_id))
+    }
+}
+
+impl Default for DexRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lookup_by_exact_program_id() {
+        let registry = DexRegistry::new();
+        assert_eq!(registry.lookup(RAYDIUM_AMM_V4_PROGRAM_ID).unwrap().kind, DexKind::RaydiumAmmV4);
+        assert_eq!(registry.lookup(JUPITER_V6_PROGRAM_ID).unwrap().kind, DexKind::Jupiter);
+        assert_eq!(registry.dex_name(WHIRLPOOL_PROGRAM_ID), Some("Orca"));
+        assert_eq!(registry.lookup(PUMPFUN_PROGRAM_ID).unwrap().kind, DexKind::PumpFun);
+        assert!(registry.lookup(RAYDIUM_AMM_V4_PROGRAM_ID).unwrap().decoder.is_some());
+        assert!(registry.lookup(METEORA_DLMM_PROGRAM_ID).unwrap().decoder.is_some());
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_lookalike_programs() {
+        let registry = DexRegistry::new();
+        // The old substring matching would have classified these as Jupiter/Raydium
+        assert!(registry.lookup("JUPxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx").is_none());
+        assert!(registry.lookup("SomeRAYProgram1111111111111111111111111111").is_none());
+        assert!(registry.lookup("11111111111111111111111111111111").is_none());
+    }
+
+    #[test]
+    fn test_config_file_adds_programs() {
+        let mut registry = DexRegistry::new();
+        registry.load_config(r#"[
+            {"program_id": "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", "name": "RaydiumCpmm"},
+            {"program_id": "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "name": "RaydiumV4", "kind": "RaydiumAmmV4"}
+        ]"#).unwrap();
+
+        let cpmm = registry.lookup("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C").unwrap();
+        assert_eq!(cpmm.kind, DexKind::Custom);
+        assert!(cpmm.decoder.is_none());
+
+        // Overriding a built-in keeps its decoder
+        let raydium = registry.lookup(RAYDIUM_AMM_V4_PROGRAM_ID).unwrap();
+        assert_eq!(raydium.name, "RaydiumV4");
+        assert!(raydium.decoder.is_some());
+
+        assert!(registry.load_config(r#"[{"program_id": "not-a-pubkey", "name": "Bad"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_mentions_known_program_in_logs() {
+        let registry = DexRegistry::new();
+        let logs = vec![
+            json!("Program ComputeBudget111111111111111111111111111111 invoke [1]"),
+            json!(format!("Program {} invoke [1]", WHIRLPOOL_PROGRAM_ID)),
+        ];
+        assert!(registry.mentions_known_program(&logs));
+
+        let logs = vec![json!(format!("Program {} success", WHIRLPOOL_PROGRAM_ID))];
+        assert!(!registry.mentions_known_program(&logs));
+    }
+}
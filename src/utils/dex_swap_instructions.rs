@@ -1,220 +1,263 @@
+use std::str::FromStr;
+use std::sync::Arc;
 use solana_sdk::{
-    instruction::Instruction,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::VersionedMessage,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     system_instruction,
-    message::Message,
-    transaction::Transaction,
-    hash::Hash,
+    transaction::{Transaction, VersionedTransaction},
 };
-use serde_json::Value;
-use std::str::FromStr;
-use crate::logging::Logger;
-use crate::utils::dex_monitor::ArbitrageOpportunity;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::meteora_dlmm::{self, LbPairState};
+use crate::utils::mev_strategies::RouteLeg;
+use crate::utils::orca_whirlpool::{self, WhirlpoolState};
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::pumpfun::{self, BondingCurveState};
+use crate::utils::raydium_amm::{self, AmmInfo, AmmSwapKeys};
+use crate::utils::simulation_effects::{self, TOKEN_PROGRAM_ID};
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const CREATE_IDEMPOTENT: u8 = 1; // Associated token program instruction
+const SYNC_NATIVE: u8 = 17; // Token program instruction
 
-pub struct DexSwapInstructions;
+// Swap instructions against the pools' current on-chain state, for the DEXes the bot trades
+pub struct DexSwapInstructions {
+    rpc_manager: Arc<RpcManager>,
+}
 
 impl DexSwapInstructions {
-    pub fn create_raydium_swap_instruction(
-        keypair: &Keypair,
-        input_mint: &str,
-        output_mint: &str,
-        input_amount: u64,
-        min_output_amount: u64,
-        pool_info: &Value,
-    ) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
-        // This would create actual Raydium swap instructions
-        // In a real implementation, we'd need the exact Raydium program structure
-        
-        // For now, creating a placeholder - in real implementation:
-        // 1. Get the Raydium program ID
-        // 2. Get the pool's vault accounts
-        // 3. Create the instruction with proper accounts and data
-        
-        let input_token_pubkey = Pubkey::from_str(input_mint)
-            .map_err(|e| format!("Invalid input token mint: {}", e))?;
-        
-        let output_token_pubkey = Pubkey::from_str(output_mint)
-            .map_err(|e| format!("Invalid output token mint: {}", e))?;
-        
-        // Create a basic transfer as a placeholder
-        let instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        Ok(instruction)
+    pub fn new(rpc_manager: Arc<RpcManager>) -> Self {
+        Self { rpc_manager }
     }
 
-    pub fn create_orca_swap_instruction(
-        keypair: &Keypair,
-        input_mint: &str,
-        output_mint: &str,
-        input_amount: u64,
-        min_output_amount: u64,
-        pool_info: &Value,
-    ) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
-        // This would create actual Orca swap instructions
-        // Similar to Raydium, we'd need the exact Orca program structure
-        
-        let input_token_pubkey = Pubkey::from_str(input_mint)
-            .map_err(|e| format!("Invalid input token mint: {}", e))?;
-        
-        let output_token_pubkey = Pubkey::from_str(output_mint)
-            .map_err(|e| format!("Invalid output token mint: {}", e))?;
-        
-        // Create a basic transfer as a placeholder
-        let instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        Ok(instruction)
-    }
+    // One leg paid from and settled to the user's associated token accounts, which are created
+    // first when missing. Wrapped SOL input is funded from the user's lamports; pump.fun trades
+    // SOL directly
+    pub async fn swap_instructions(&self, leg: &RouteLeg, user: &Pubkey) -> Result<Vec<Instruction>, Box<dyn std::error::Error + Send + Sync>> {
+        if leg.dex == "PumpFun" {
+            let mint = if leg.input_token == WSOL_MINT { &leg.output_token } else { &leg.input_token };
+            return Ok(vec![
+                create_token_account_instruction(user, mint)?,
+                self.pumpfun_instruction(leg, user).await?,
+            ]);
+        }
 
-    pub fn create_jupiter_swap_instructions(
-        keypair: &Keypair,
-        jupiter_swap_data: &Value,
-    ) -> Result<Vec<Instruction>, Box<dyn std::error::Error + Send + Sync>> {
-        // Process Jupiter swap transaction data to extract instructions
-        // This would parse the swap transaction and return the actual instructions
-        
-        // For now, return a placeholder
-        let instructions = vec![
-            system_instruction::transfer(
-                &keypair.pubkey(),
-                &keypair.pubkey(), // Placeholder
-                1000, // Placeholder amount
-            )
-        ];
-        
+        let mut instructions = vec![create_token_account_instruction(user, &leg.output_token)?];
+        if leg.input_token == WSOL_MINT {
+            instructions.extend(wrap_sol_instructions(user, leg.amount_in)?);
+        }
+        instructions.push(self.swap_instruction(leg, user).await?);
         Ok(instructions)
     }
 
-    pub fn create_arbitrage_transaction(
-        keypair: &Keypair,
-        opportunity: &ArbitrageOpportunity,
-        input_amount: u64,
-    ) -> Result<Transaction, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a transaction that executes the arbitrage opportunity
-        // This would involve creating two swap instructions back-to-back
-        
-        let buy_instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        let sell_instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        let instructions = vec![buy_instruction, sell_instruction];
-        
-        // Get current blockhash
-        let blockhash = Hash::new(&[0; 32]); // Placeholder - would be real blockhash
-        
-        let message = Message::new(
-            &instructions,
-            Some(&keypair.pubkey()),
-        );
-        
-        let transaction = Transaction::new(
-            &[keypair],
-            message,
-            blockhash,
-        );
-        
-        Ok(transaction)
+    async fn swap_instruction(&self, leg: &RouteLeg, user: &Pubkey) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+        let data = self.rpc_manager.get_account_data(&leg.pool_address).await?;
+        let not_traded = || format!("{} pool {} doesn't trade {}", leg.dex, leg.pool_address, leg.input_token);
+        match leg.dex.as_str() {
+            "Raydium" => {
+                let amm = AmmInfo::decode(&data)?;
+                let market = bs58::encode(data.get(528..560).ok_or("Raydium AMM account too short")?).into_string();
+                let keys = AmmSwapKeys::decode(&data, &self.rpc_manager.get_account_data(&market).await?)?;
+                let coin_to_pc = if leg.input_token == amm.coin_mint {
+                    true
+                } else if leg.input_token == amm.pc_mint {
+                    false
+                } else {
+                    return Err(not_traded().into());
+                };
+                raydium_amm::swap_instruction(&leg.pool_address, &amm, &keys, user, leg.amount_in, leg.min_out, coin_to_pc)
+            }
+            "Orca" | "Whirlpool" => {
+                let pool = WhirlpoolState::decode(&data)?;
+                let a_to_b = if leg.input_token == pool.token_mint_a {
+                    true
+                } else if leg.input_token == pool.token_mint_b {
+                    false
+                } else {
+                    return Err(not_traded().into());
+                };
+                orca_whirlpool::swap_instruction(&leg.pool_address, &pool, user, leg.amount_in, leg.min_out, a_to_b)
+            }
+            "Meteora" => {
+                let pair = LbPairState::decode(&data)?;
+                let swap_for_y = if leg.input_token == pair.token_x_mint {
+                    true
+                } else if leg.input_token == pair.token_y_mint {
+                    false
+                } else {
+                    return Err(not_traded().into());
+                };
+                meteora_dlmm::swap_instruction(&leg.pool_address, &pair, user, leg.amount_in, leg.min_out, swap_for_y)
+            }
+            #[cfg(feature = "orderbook")]
+            "Phoenix" => {
+                let book = crate::utils::phoenix::OrderBook::decode(&leg.pool_address, &data)?;
+                crate::utils::phoenix::ioc_instruction(&book, user, &leg.input_token, leg.amount_in, leg.min_out)
+            }
+            #[cfg(not(feature = "orderbook"))]
+            "Phoenix" => Err("Phoenix legs go out as IOC orders, which only the orderbook build sends".into()),
+            dex => Err(format!("No swap instruction builder for {} pools", dex).into()),
+        }
+    }
+
+    // A buy spends amount_in lamports on the tokens the curve quotes for it, failing unless that
+    // covers min_out; a sell takes min_out as the lamports it must return
+    async fn pumpfun_instruction(&self, leg: &RouteLeg, user: &Pubkey) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+        if leg.input_token == WSOL_MINT {
+            let curve_address = pumpfun::bonding_curve_address(&leg.output_token)?;
+            let curve = BondingCurveState::decode(&self.rpc_manager.get_account_data(&curve_address).await?)?;
+            if curve.complete {
+                return Err(format!("Bonding curve of {} has completed", leg.output_token).into());
+            }
+            let tokens = curve.tokens_out_for_sol(leg.amount_in);
+            if tokens == 0 || tokens < leg.min_out {
+                return Err(format!("{} lamports buy only {} of {}, below the {} required", leg.amount_in, tokens, leg.output_token, leg.min_out).into());
+            }
+            pumpfun::buy_instruction(&leg.output_token, user, tokens, leg.amount_in)
+        } else if leg.output_token == WSOL_MINT {
+            pumpfun::sell_instruction(&leg.input_token, user, leg.amount_in, leg.min_out)
+        } else {
+            Err(format!("pump.fun trades tokens against SOL, not {} for {}", leg.input_token, leg.output_token).into())
+        }
+    }
+}
+
+// SetComputeUnitLimit and SetComputeUnitPrice (micro-lamports per unit), placed ahead of a
+// transaction's other instructions
+pub fn compute_budget_instructions(unit_limit: u32, unit_price: u64) -> Vec<Instruction> {
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(unit_price),
+    ]
+}
+
+// Legacy transaction paid and signed by `signer`, serialized and base58-encoded the way bundles
+// and simulations take it
+pub fn signed_transaction(instructions: &[Instruction], signer: &Keypair, blockhash: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let blockhash = Hash::from_str(blockhash).map_err(|e| format!("Invalid blockhash {}: {}", blockhash, e))?;
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&signer.pubkey()), &[signer], blockhash);
+    let serialized = bincode::serialize(&transaction).map_err(|e| format!("Failed to serialize transaction: {}", e))?;
+    Ok(bs58::encode(serialized).into_string())
+}
+
+// A signed legacy transaction (base58) with its compute budget replaced by `unit_limit` and
+// `unit_price`, re-signed by `signer` under `blockhash`. Only transactions `signer` alone signed
+// can be re-signed, and versioned ones can't be rebuilt without their lookup tables
+pub fn with_compute_budget(transaction: &str, unit_limit: u32, unit_price: u64, signer: &Keypair, blockhash: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let bytes = bs58::decode(transaction).into_vec().map_err(|e| format!("Transaction isn't base58-encoded: {}", e))?;
+    let decoded: VersionedTransaction = bincode::deserialize(&bytes).map_err(|e| format!("Transaction doesn't deserialize: {}", e))?;
+    let VersionedMessage::Legacy(message) = decoded.message else {
+        return Err("Only legacy transactions can have their compute budget replaced".into());
+    };
+    if message.header.num_required_signatures != 1 || message.account_keys.first() != Some(&signer.pubkey()) {
+        return Err(format!("Transaction isn't signed by {} alone", signer.pubkey()).into());
+    }
+    
+    let mut instructions = compute_budget_instructions(unit_limit, unit_price);
+    for compiled in &message.instructions {
+        let program_id = message.account_keys[compiled.program_id_index as usize];
+        if program_id == solana_sdk::compute_budget::id() {
+            continue;
+        }
+        let accounts = compiled.accounts.iter()
+            .map(|&index| {
+                let index = index as usize;
+                AccountMeta { pubkey: message.account_keys[index], is_signer: message.is_signer(index), is_writable: message.is_writable(index) }
+            })
+            .collect();
+        instructions.push(Instruction { program_id, accounts, data: compiled.data.clone() });
+    }
+    signed_transaction(&instructions, signer, blockhash)
+}
+
+// The user's associated token account for a mint, created unless it exists (CreateIdempotent)
+pub fn create_token_account_instruction(user: &Pubkey, mint: &str) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let token_account = simulation_effects::associated_token_address(&user.to_string(), mint)
+        .ok_or_else(|| format!("Invalid mint {}", mint))?;
+    Ok(Instruction {
+        program_id: pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+        accounts: vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pubkey(&token_account)?, false),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new_readonly(pubkey(mint)?, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(pubkey(TOKEN_PROGRAM_ID)?, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT],
+    })
+}
+
+// Moves lamports into the user's wrapped SOL account and syncs its token balance
+fn wrap_sol_instructions(user: &Pubkey, lamports: u64) -> Result<Vec<Instruction>, Box<dyn std::error::Error + Send + Sync>> {
+    let wsol_account = pubkey(&simulation_effects::associated_token_address(&user.to_string(), WSOL_MINT).ok_or("Invalid wallet")?)?;
+    Ok(vec![
+        create_token_account_instruction(user, WSOL_MINT)?,
+        system_instruction::transfer(user, &wsol_account, lamports),
+        Instruction {
+            program_id: pubkey(TOKEN_PROGRAM_ID)?,
+            accounts: vec![AccountMeta::new(wsol_account, false)],
+            data: vec![SYNC_NATIVE],
+        },
+    ])
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCKHASH: &str = "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N";
+
+    #[test]
+    fn test_signed_transaction_verifies_and_carries_the_compute_budget() {
+        let signer = Keypair::new();
+        let mut instructions = compute_budget_instructions(180_000, 25_000);
+        instructions.extend(wrap_sol_instructions(&signer.pubkey(), 1_000_000_000).unwrap());
+
+        let encoded = signed_transaction(&instructions, &signer, BLOCKHASH).unwrap();
+        let bytes = bs58::decode(&encoded).into_vec().unwrap();
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert!(transaction.verify_with_results().iter().all(|verified| *verified));
+        assert_eq!(transaction.message.static_account_keys()[0], signer.pubkey());
+
+        let fees = simulation_effects::fee_parameters(&bytes).unwrap();
+        assert_eq!((fees.signatures, fees.compute_unit_price), (1, 25_000));
     }
 
-    pub fn create_frontrun_transaction(
-        keypair: &Keypair,
-        target_transaction: &Value,
-        opportunity: &ArbitrageOpportunity,
-    ) -> Result<Transaction, Box<dyn std::error::Error + Send + Sync>> {
-        // Analyze the target transaction and create a frontrunning transaction
-        // This would involve replicating the same swap with better parameters
-        
-        // For now, creating a placeholder
-        let instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        let instructions = vec![instruction];
-        
-        // Get current blockhash
-        let blockhash = Hash::new(&[0; 32]); // Placeholder - would be real blockhash
-        
-        let message = Message::new(
-            &instructions,
-            Some(&keypair.pubkey()),
-        );
-        
-        let transaction = Transaction::new(
-            &[keypair],
-            message,
-            blockhash,
-        );
-        
-        Ok(transaction)
+    #[test]
+    fn test_compute_budget_is_replaced_and_re_signed() {
+        let signer = Keypair::new();
+        let mut instructions = compute_budget_instructions(1_400_000, 1);
+        instructions.extend(wrap_sol_instructions(&signer.pubkey(), 1_000_000_000).unwrap());
+        let original = signed_transaction(&instructions, &signer, BLOCKHASH).unwrap();
+
+        let budgeted = with_compute_budget(&original, 96_000, 40_000, &signer, "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM").unwrap();
+        let bytes = bs58::decode(&budgeted).into_vec().unwrap();
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert!(transaction.verify_with_results().iter().all(|verified| *verified));
+        assert_eq!(transaction.message.recent_blockhash().to_string(), "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM");
+        // The old budget is gone and the wrap is untouched
+        assert_eq!(transaction.message.instructions().len(), 2 + 3);
+        assert_eq!(simulation_effects::fee_parameters(&bytes).unwrap().compute_unit_price, 40_000);
+        let limit = &transaction.message.instructions()[0];
+        assert_eq!(limit.data, ComputeBudgetInstruction::set_compute_unit_limit(96_000).data);
+
+        // Someone else's transaction can't be re-signed
+        assert!(with_compute_budget(&original, 96_000, 40_000, &Keypair::new(), BLOCKHASH).is_err());
+        assert!(with_compute_budget("frontrun_placeholder", 96_000, 40_000, &signer, BLOCKHASH).is_err());
     }
 
-    pub fn create_sandwich_transaction(
-        keypair: &Keypair,
-        target_transaction: &Value,
-        opportunity: &ArbitrageOpportunity,
-    ) -> Result<(Transaction, Transaction), Box<dyn std::error::Error + Send + Sync>> { 
-        // Create both frontrun and backrun transactions for sandwich attack
-        
-        // Frontrun transaction
-        let frontrun_instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        let frontrun_instructions = vec![frontrun_instruction];
-        let frontrun_blockhash = Hash::new(&[0; 32]); // Placeholder
-        
-        let frontrun_message = Message::new(
-            &frontrun_instructions,
-            Some(&keypair.pubkey()),
-        );
-        
-        let frontrun_transaction = Transaction::new(
-            &[keypair],
-            frontrun_message,
-            frontrun_blockhash,
-        );
-        
-        // Backrun transaction
-        let backrun_instruction = system_instruction::transfer(
-            &keypair.pubkey(),
-            &keypair.pubkey(), // Placeholder
-            1000, // Placeholder amount
-        );
-        
-        let backrun_instructions = vec![backrun_instruction];
-        let backrun_blockhash = Hash::new(&[0; 32]); // Placeholder
-        
-        let backrun_message = Message::new(
-            &backrun_instructions,
-            Some(&keypair.pubkey()),
-        );
-        
-        let backrun_transaction = Transaction::new(
-            &[keypair],
-            backrun_message,
-            backrun_blockhash,
-        );
-        
-        Ok((frontrun_transaction, backrun_transaction))
+    #[test]
+    fn test_rejects_bad_blockhash_and_mint() {
+        let signer = Keypair::new();
+        assert!(signed_transaction(&[], &signer, "not a hash").is_err());
+        assert!(create_token_account_instruction(&signer.pubkey(), "not a mint").is_err());
     }
-}
\ No newline at end of file
+}
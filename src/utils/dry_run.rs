@@ -0,0 +1,36 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const SYNTHETIC_PREFIX: &str = "dry-run-";
+
+// With DRY_RUN=true (Config::dry_run) detection, simulation and fee/tip calculation run for real
+// but every submission is replaced with a stub, so the bot can be evaluated on mainnet data
+// without risking funds
+
+// Stands in for the signature or bundle id a real submission would have returned; the same
+// payload always gets the same id so log lines can be matched up
+pub fn synthetic_signature(payload: &[String]) -> String {
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    format!("{}{:016x}", SYNTHETIC_PREFIX, hasher.finish())
+}
+
+// Whether a signature came from a stubbed submission and so will never show up on chain
+pub fn is_synthetic(signature: &str) -> bool {
+    signature.starts_with(SYNTHETIC_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_signatures_are_stable_and_recognizable() {
+        let bundle = vec!["tx1".to_string(), "tx2".to_string()];
+        let signature = synthetic_signature(&bundle);
+        assert_eq!(signature, synthetic_signature(&bundle));
+        assert_ne!(signature, synthetic_signature(&bundle[..1]));
+        assert!(is_synthetic(&signature));
+        assert!(!is_synthetic("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"));
+    }
+}
@@ -1,7 +1,12 @@
 use std::sync::Arc;
 use serde_json::Value;
 use crate::logging::Logger;
-use crate::rpc::rpc_manager::RpcManager;
+use crate::rpc::rpc_manager::{RpcManager, RpcTaskType};
+use crate::utils::mev_simulation_pipeline::mev_operations::RouteStep;
+use crate::utils::metrics_collector::MetricsCollector;
+use crate::utils::opportunity_evaluator::{OpportunityEvaluator, PoolState};
+use crate::utils::raydium_amm;
+use crate::utils::simulation_consensus::{self, ConsensusConfig, SimulationConsensus};
 
 #[derive(Debug, Clone)]
 pub struct SimulationResult {
@@ -20,12 +25,16 @@ pub struct OpportunityValidation {
     pub net_profit: f64,
     pub total_costs: f64,
     pub simulation_results: Vec<SimulationResult>,
+    pub consensus: Option<SimulationConsensus>, // The cross-endpoint check, when there was a transaction to run it on
 }
 
 pub struct EnhancedTransactionSimulator {
     pub rpc_manager: Arc<RpcManager>,
     safety_margin: f64,  // Default safety margin of 0.005 SOL
     min_confidence_threshold: f64,  // Minimum confidence score to execute (85%)
+    consensus: ConsensusConfig,
+    metrics_collector: Option<Arc<MetricsCollector>>,
+    pool_states: Option<Arc<OpportunityEvaluator>>, // Reads the opportunity's pool for the cross-endpoint check
 }
 
 impl EnhancedTransactionSimulator {
@@ -34,17 +43,56 @@ impl EnhancedTransactionSimulator {
             rpc_manager,
             safety_margin: 0.005,  // 0.005 SOL safety margin
             min_confidence_threshold: 0.85,  // 85% confidence threshold
+            consensus: ConsensusConfig::default(),
+            metrics_collector: None,
+            pool_states: None,
         })
     }
     
-    pub async fn simulate_and_validate(&self, opportunity: &OpportunityDetails) -> Result<OpportunityValidation, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn with_consensus(mut self, consensus: ConsensusConfig) -> Self {
+        self.consensus = consensus;
+        self
+    }
+    
+    pub fn with_metrics_collector(mut self, metrics_collector: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics_collector);
+        self
+    }
+    
+    pub fn with_pool_states(mut self, evaluator: Arc<OpportunityEvaluator>) -> Self {
+        self.pool_states = Some(evaluator);
+        self
+    }
+    
+    // `transaction` is the base64 transaction the opportunity hinges on (the target's, for
+    // victim-driven types); when there is one, its simulation is cross-checked on several endpoints
+    pub async fn simulate_and_validate(
+        &self,
+        opportunity: &OpportunityDetails,
+        transaction: Option<&str>
+    ) -> Result<OpportunityValidation, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Starting opportunity simulation and validation pipeline");
         
         // Step 1: Run multiple simulation branches with variations
         let simulation_results = self.run_simulation_variations(opportunity).await?;
         
         // Step 2: Validate net profit against all costs
-        let validation = self.validate_net_profit(opportunity, &simulation_results).await?;
+        let mut validation = self.validate_net_profit(opportunity, &simulation_results).await?;
+        
+        // Step 3: A node a slot or two behind can flip the result, so only validate what the
+        // endpoints agree on
+        if let Some(transaction) = transaction {
+            if let Some(consensus) = self.consensus_simulation(transaction, opportunity, &validation).await {
+                if let Some(ref disagreement) = consensus.disagreement {
+                    Logger::status_update(&format!("RPC endpoints disagree on the opportunity's simulation: {}", disagreement));
+                    validation.is_profitable = false;
+                    for result in &mut validation.simulation_results {
+                        result.confidence_score *= self.consensus.disagreement_penalty;
+                    }
+                }
+                validation.consensus = Some(consensus);
+            }
+        }
         
         Logger::status_update(&format!(
             "Opportunity validation completed - profitable: {}, net profit: {:.6} SOL", 
@@ -54,6 +102,59 @@ impl EnhancedTransactionSimulator {
         Ok(validation)
     }
     
+    // Simulates the transaction on up to `consensus.endpoints` endpoints at once, keeping what
+    // answered by the deadline. Each endpoint's profit is the backrun its own state after the
+    // transaction leaves in the opportunity's pool, less the validation's costs; without a
+    // readable SOL pool every endpoint gets the validation's figure and only errors can differ.
+    // None when the check is off or fewer than two endpoints answered
+    async fn consensus_simulation(&self, transaction: &str, opportunity: &OpportunityDetails, validation: &OpportunityValidation) -> Option<SimulationConsensus> {
+        if self.consensus.endpoints < 2 {
+            return None;
+        }
+        let pool = self.pool_before(opportunity).await;
+        let vaults = pool.as_ref().map(|(pool, _)| pool.vaults.clone()).unwrap_or_default();
+        let net_profit = |accounts: &[Value]| {
+            pool.as_ref()
+                .and_then(|(pool, vaults_before)| simulation_consensus::backrun_value(pool, vaults_before, accounts))
+                .map_or(validation.net_profit, |value| value - validation.total_costs)
+        };
+        let simulations = self.rpc_manager.endpoints_for(RpcTaskType::Simulate).await.into_iter()
+            .take(self.consensus.endpoints)
+            .map(|endpoint_type| {
+                let vaults = &vaults;
+                async move {
+                    let response = match vaults.is_empty() {
+                        true => self.rpc_manager.simulate_transaction_on(endpoint_type, transaction).await,
+                        false => self.rpc_manager.simulate_transaction_with_accounts_on(endpoint_type, transaction, vaults).await,
+                    }.ok()?;
+                    Some(simulation_consensus::endpoint_simulation(&format!("{:?}", endpoint_type), &response, net_profit))
+                }
+            })
+            .collect();
+        let answered = simulation_consensus::race(simulations, self.consensus.deadline).await;
+        if answered.len() < 2 {
+            return None;
+        }
+        
+        let consensus = simulation_consensus::compare(answered, self.consensus.tolerance);
+        if let Some(ref metrics_collector) = self.metrics_collector {
+            metrics_collector.record_simulation_consensus(consensus.agrees()).await;
+        }
+        Some(consensus)
+    }
+    
+    // The opportunity's pool and its vault balances as they are now, before the transaction; None
+    // without a pool reader, a pool address or a decodable state
+    async fn pool_before(&self, opportunity: &OpportunityDetails) -> Option<(PoolState, Vec<u64>)> {
+        let evaluator = self.pool_states.as_ref()?;
+        let pool = evaluator.get_pool_state(opportunity.pool_address.as_ref()?, &opportunity.dex).await.ok()??;
+        let vault_data = self.rpc_manager.get_multiple_accounts_data(&pool.vaults).await.ok()?;
+        let vaults_before = vault_data.iter()
+            .map(|data| raydium_amm::token_account_amount(data.as_ref()?).ok())
+            .collect::<Option<Vec<u64>>>()?;
+        Some((pool, vaults_before))
+    }
+    
     async fn run_simulation_variations(&self, opportunity: &OpportunityDetails) -> Result<Vec<SimulationResult>, Box<dyn std::error::Error + Send + Sync>> {
         let mut results = Vec::new();
         
@@ -238,6 +339,7 @@ impl EnhancedTransactionSimulator {
                 net_profit: result.net_profit,
                 total_costs,
                 simulation_results: simulation_results.to_vec(),
+                consensus: None,
             })
         } else {
             // No valid simulation results
@@ -246,6 +348,7 @@ impl EnhancedTransactionSimulator {
                 net_profit: 0.0,
                 total_costs: 0.0,
                 simulation_results: simulation_results.to_vec(),
+                consensus: None,
             })
         }
     }
@@ -308,6 +411,33 @@ pub struct OpportunityDetails {
     pub estimated_profit: f64,
     pub dex: String, // Which DEX (Jupiter, Raydium, Orca, etc.)
     pub opportunity_type: OpportunityType,
+    pub pool_address: Option<String>, // Target pool when known (e.g. newly created pools for snipes)
+    pub route: Vec<RouteStep>, // Ordered legs for multi-hop arbitrage; empty otherwise
+}
+
+impl OpportunityDetails {
+    // Every pool the opportunity trades: the target pool and the route's legs, each once
+    pub fn pools(&self) -> Vec<String> {
+        let mut pools: Vec<String> = Vec::new();
+        for pool in self.pool_address.iter().chain(self.route.iter().map(|step| &step.pool_address)) {
+            if !pools.contains(pool) {
+                pools.push(pool.clone());
+            }
+        }
+        pools
+    }
+    
+    // Every token the opportunity trades: the pair and the route's legs, each once
+    pub fn mints(&self) -> Vec<String> {
+        let mut mints: Vec<String> = Vec::new();
+        let route_mints = self.route.iter().flat_map(|step| [&step.input_token, &step.output_token]);
+        for mint in [&self.token_a, &self.token_b].into_iter().chain(route_mints) {
+            if !mint.is_empty() && !mints.contains(mint) {
+                mints.push(mint.clone());
+            }
+        }
+        mints
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -315,11 +445,70 @@ pub enum OpportunityType {
     Arbitrage,
     Frontrun,
     Sandwich,
+    Backrun,
     Liquidation,
+    Snipe,
     Other,
 }
 
 struct SimulationScenario {
     slippage_tolerance: f64,
     priority_fee: f64,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::rpc::rpc_manager::{RpcEndpoint, RpcEndpointType};
+    use crate::utils::mock_http::{self, MockResponse};
+
+    // Node answering every request with `value` as the simulation result
+    async fn mock_node(value: Value) -> String {
+        let (url, _) = mock_http::serve(move |request| {
+            MockResponse::rpc_result(request, json!({ "context": { "slot": 1 }, "value": value }))
+        }).await;
+        url
+    }
+
+    async fn validate(helius: Value, drpc: Value) -> OpportunityValidation {
+        let rpc_manager = Arc::new(RpcManager::with_endpoints(vec![
+            RpcEndpoint::new(mock_node(helius).await, RpcEndpointType::Helius, 1.0),
+            RpcEndpoint::new(mock_node(drpc).await, RpcEndpointType::Drpc, 1.0),
+        ]));
+        let simulator = EnhancedTransactionSimulator::new(rpc_manager).await.unwrap()
+            .with_consensus(ConsensusConfig::default());
+        let opportunity = OpportunityDetails {
+            token_a: "So11111111111111111111111111111111111111112".to_string(),
+            token_b: "EPjFWDDh7cF3ovXV1DEYsdqk6gXdVHDVGg3bQeXjDwLS".to_string(),
+            trade_size: 1,
+            estimated_profit: 0.05,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Backrun,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        simulator.simulate_and_validate(&opportunity, Some("AQID")).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_disagreeing_endpoints_lower_confidence() {
+        let succeeded = json!({ "err": null, "logs": [], "unitsConsumed": 52_000 });
+        let agreed = validate(succeeded.clone(), succeeded.clone()).await;
+        let consensus = agreed.consensus.as_ref().unwrap();
+        assert!(consensus.agrees());
+        assert_eq!(consensus.simulations.len(), 2);
+
+        // One node already sees the victim's swap fail on slippage
+        let slippage = json!({
+            "err": { "InstructionError": [2, { "Custom": 6001 }] },
+            "logs": ["Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 failed: custom program error: 0x1771"],
+            "unitsConsumed": 31_000
+        });
+        let disagreed = validate(succeeded, slippage).await;
+        assert!(!disagreed.consensus.as_ref().unwrap().agrees());
+        assert!(!disagreed.is_profitable);
+        for (agreed, disagreed) in agreed.simulation_results.iter().zip(&disagreed.simulation_results) {
+            assert!((disagreed.confidence_score - agreed.confidence_score * 0.5).abs() < 1e-12);
+        }
+    }
+}
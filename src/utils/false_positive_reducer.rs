@@ -1,9 +1,15 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use serde_json::Value;
 use crate::logging::Logger;
 use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
+use crate::utils::filter_decisions::{self, FilterCalibrationConfig, FilterDecisionLog, FilterEvaluation, FilterFeatures, FilterThresholds};
+use crate::utils::opportunity_outcomes::OpportunityOutcomes;
+use crate::utils::token_safety::TokenSafetyChecker;
+use crate::utils::launch_filters::{LaunchFilters, LaunchRejection, PoolLaunch};
+use crate::utils::dex_api::{DexApi, PriceImpact, SwapSide};
+use crate::utils::opportunity_evaluator::{OpportunityEvaluator, PoolState};
 
 #[derive(Debug, Clone)]
 pub struct ConfidenceFactors {
@@ -29,14 +35,22 @@ pub struct OpportunityFilteringResult {
     pub should_execute: bool,
     pub confidence_score: ConfidenceScore,
     pub filtered_reason: Option<String>,
+    pub features: FilterFeatures, // What the decision was based on, for filter_decisions
+    pub fixed_rejection: bool, // Rejected by a check no threshold controls
 }
 
 pub struct FalsePositiveReducer {
-    min_confidence_threshold: f64,
+    thresholds: Arc<RwLock<FilterThresholds>>, // Confidence, value, pool depth and variance cutoffs; recalibrated from decisions
     slippage_threshold: f64,  // 3% threshold
-    pool_depth_multiplier: f64,
     spam_sender_cache: Arc<RwLock<HashMap<String, SenderHistory>>>,
     opportunity_history: Arc<RwLock<HashMap<String, Vec<HistoricalResult>>>>,
+    token_safety: Option<Arc<TokenSafetyChecker>>, // Honeypot and freezable-token policy
+    launch_filters: Option<Arc<LaunchFilters>>, // Pool age, initial liquidity and LP lock rules for snipes
+    pool_states: Option<Arc<OpportunityEvaluator>>, // Live pool state for depth and impact checks
+    filter_rejections: Arc<RwLock<HashMap<String, u64>>>, // Dropped before simulation, by reason
+    outcomes: Option<OpportunityOutcomes>, // What opportunities we let through went on to do
+    decisions: Option<FilterDecisionLog>, // Our past verdicts and their outcomes
+    calibration: FilterCalibrationConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -57,43 +71,98 @@ struct HistoricalResult {
 impl FalsePositiveReducer {
     pub fn new() -> Self {
         Self {
-            min_confidence_threshold: 0.85, // 85% confidence required
+            thresholds: Arc::new(RwLock::new(FilterThresholds::default())), // 85% confidence, 0.001 SOL, 10x pool depth
             slippage_threshold: 0.03,       // 3% of potential profit
-            pool_depth_multiplier: 10.0,    // Require 10x pool depth
             spam_sender_cache: Arc::new(RwLock::new(HashMap::new())),
             opportunity_history: Arc::new(RwLock::new(HashMap::new())),
+            token_safety: None,
+            launch_filters: None,
+            pool_states: None,
+            filter_rejections: Arc::new(RwLock::new(HashMap::new())),
+            outcomes: None,
+            decisions: None,
+            calibration: FilterCalibrationConfig::default(),
         }
     }
     
+    pub fn with_outcomes(mut self, outcomes: OpportunityOutcomes) -> Self {
+        self.outcomes = Some(outcomes);
+        self
+    }
+    
+    pub fn with_token_safety(mut self, token_safety: Arc<TokenSafetyChecker>) -> Self {
+        self.token_safety = Some(token_safety);
+        self
+    }
+    
+    pub fn with_launch_filters(mut self, launch_filters: Arc<LaunchFilters>) -> Self {
+        self.launch_filters = Some(launch_filters);
+        self
+    }
+    
+    pub fn with_pool_states(mut self, evaluator: Arc<OpportunityEvaluator>) -> Self {
+        self.pool_states = Some(evaluator);
+        self
+    }
+    
+    // Recalibrates the thresholds from these decisions toward the configured precision and recall
+    pub fn with_decisions(mut self, decisions: FilterDecisionLog, calibration: FilterCalibrationConfig) -> Self {
+        self.decisions = Some(decisions);
+        self.calibration = calibration;
+        self
+    }
+    
+    pub async fn thresholds(&self) -> FilterThresholds {
+        *self.thresholds.read().await
+    }
+    
     pub async fn evaluate_opportunity(
         &self, 
         opportunity: &OpportunityDetails,
         simulation_results: &[crate::utils::enhanced_transaction_simulator::SimulationResult]
     ) -> OpportunityFilteringResult {
         Logger::status_update("Evaluating opportunity to reduce false positives");
+        let thresholds = self.thresholds().await;
         
         // Calculate comprehensive confidence score
-        let confidence_score = self.calculate_confidence_score(opportunity, simulation_results).await;
+        let confidence_score = self.calculate_confidence_score(opportunity, simulation_results, &thresholds).await;
+        let features = FilterFeatures {
+            estimated_profit: opportunity.estimated_profit,
+            confidence: confidence_score.score,
+            variance: simulation_variance(simulation_results),
+            dex: opportunity.dex.clone(),
+            liquidity: self.estimate_pool_size(&opportunity.token_a, &opportunity.token_b).await,
+            trade_size: opportunity.trade_size as f64,
+        };
         
         // Apply various filters
         let slippage_check = self.check_slippage_threshold(opportunity, &confidence_score).await;
-        let pool_depth_check = self.check_pool_depth_sufficiency(opportunity).await;
+        let pool_depth_check = self.check_pool_depth_sufficiency(opportunity, &thresholds).await;
         let spam_check = self.detect_spam_transaction(opportunity).await;
-        let value_threshold_check = self.check_value_threshold(opportunity).await;
+        let value_threshold_check = self.check_value_threshold(opportunity, &thresholds).await;
+        let token_issue = self.check_token_safety(opportunity).await;
+        let fixed_rejection = !slippage_check || !spam_check || token_issue.is_some();
         
         // Overall decision
         let mut should_execute = true;
         let mut filtered_reasons = Vec::new();
         
-        if confidence_score.score < self.min_confidence_threshold {
+        if confidence_score.score < thresholds.min_confidence {
             should_execute = false;
             filtered_reasons.push(format!(
                 "Confidence score {:.2}% below threshold {:.2}%", 
                 confidence_score.score * 100.0, 
-                self.min_confidence_threshold * 104.0
+                thresholds.min_confidence * 104.0
             ));
         }
         
+        if let Some(max_variance) = thresholds.max_variance {
+            if features.variance > max_variance {
+                should_execute = false;
+                filtered_reasons.push(format!("Simulation variance {:.6} above threshold {:.6}", features.variance, max_variance));
+            }
+        }
+        
         if !slippage_check {
             should_execute = false;
             filtered_reasons.push("Slippage exceeds acceptable threshold".to_string());
@@ -114,6 +183,11 @@ impl FalsePositiveReducer {
             filtered_reasons.push("Opportunity value below minimum threshold".to_string());
         }
         
+        if let Some(issue) = token_issue {
+            should_execute = false;
+            filtered_reasons.push(issue);
+        }
+        
         OpportunityFilteringResult {
             should_execute,
             confidence_score,
@@ -122,13 +196,16 @@ impl FalsePositiveReducer {
             } else { 
                 None 
             },
+            features,
+            fixed_rejection,
         }
     }
     
     async fn calculate_confidence_score(
         &self,
         opportunity: &OpportunityDetails,
-        simulation_results: &[crate::utils::enhanced_transaction_simulator::SimulationResult]
+        simulation_results: &[crate::utils::enhanced_transaction_simulator::SimulationResult],
+        thresholds: &FilterThresholds,
     ) -> ConfidenceScore {
         // Calculate all confidence factors
         let pool_size_factor = self.calculate_pool_size_factor(opportunity).await;
@@ -162,7 +239,7 @@ impl FalsePositiveReducer {
             transaction_value_factor,
         };
         
-        let reason = if final_score >= self.min_confidence_threshold {
+        let reason = if final_score >= thresholds.min_confidence {
             "Opportunity meets all confidence criteria".to_string()
         } else {
             "Opportunity does not meet minimum confidence threshold".to_string()
@@ -253,9 +330,7 @@ impl FalsePositiveReducer {
         
         // Calculate consistency (variance across results)
         let avg_net_profit_f64 = avg_net_profit;
-        let variance: f64 = valid_results.iter()
-            .map(|r| (r.net_profit - avg_net_profit_f64).powi(2))
-            .sum::<f64>() / valid_results.len() as f64;
+        let variance = simulation_variance(simulation_results);
         
         // High average profit and low variance = high confidence
         let profit_factor = (avg_net_profit_f64 / 0.01).min(0.5); // Cap profit factor at 0.5
@@ -306,12 +381,20 @@ impl FalsePositiveReducer {
         confidence_score.factors.slippage_factor > 0.5
     }
     
-    async fn check_pool_depth_sufficiency(&self, opportunity: &OpportunityDetails) -> bool {
+    async fn check_pool_depth_sufficiency(&self, opportunity: &OpportunityDetails, thresholds: &FilterThresholds) -> bool {
+        // A pool `m` times the trade moves its price by 1 - (m / (m + 1))², fee aside; with live
+        // state the trade has to fit inside that move
+        if let Some((pool, side)) = self.trade_pool(opportunity).await {
+            let multiplier = thresholds.pool_depth_multiplier;
+            let max_bps = (1.0 - (multiplier / (multiplier + 1.0)).powi(2)) * 10_000.0;
+            return opportunity.trade_size <= DexApi::max_size_for_impact(&pool, side, max_bps);
+        }
+        
         // Check if pool depth is sufficient for the trade size
         let pool_size = self.estimate_pool_size(&opportunity.token_a, &opportunity.token_b).await;
         let trade_size = opportunity.trade_size as f64;
         
-        pool_size >= trade_size * self.pool_depth_multiplier
+        pool_size >= trade_size * thresholds.pool_depth_multiplier
     }
     
     async fn detect_spam_transaction(&self, opportunity: &OpportunityDetails) -> bool {
@@ -327,9 +410,31 @@ impl FalsePositiveReducer {
         false
     }
     
-    async fn check_value_threshold(&self, opportunity: &OpportunityDetails) -> bool {
+    // A token that fails the safety policy could trap the position we take or tax it away;
+    // also run ahead of snipes, which skip the rest of the filters
+    pub async fn check_token_safety(&self, opportunity: &OpportunityDetails) -> Option<String> {
+        let token_safety = self.token_safety.as_ref()?;
+        
+        for mint in [&opportunity.token_a, &opportunity.token_b] {
+            if let Some(reason) = token_safety.check(mint).await {
+                return Some(reason);
+            }
+        }
+        None
+    }
+    
+    // Fresh pools are where rugs live too: a snipe's pool has to be inside the configured age
+    // bounds, start with enough liquidity and, when configured, have its LP burned or locked.
+    // Each rejection is counted under its rule
+    pub async fn check_launch(&self, launch: &PoolLaunch) -> Option<LaunchRejection> {
+        let rejection = self.launch_filters.as_ref()?.check(launch).await?;
+        self.record_filter_rejection(&OpportunityType::Snipe, rejection.kind()).await;
+        Some(rejection)
+    }
+    
+    async fn check_value_threshold(&self, opportunity: &OpportunityDetails, thresholds: &FilterThresholds) -> bool {
         // Check if opportunity value meets minimum threshold to be worth pursuing
-        opportunity.estimated_profit >= 0.001 // Minimum 0.001 SOL
+        opportunity.estimated_profit >= thresholds.min_profit // Minimum 0.001 SOL by default
     }
     
     async fn estimate_pool_size(&self, token_a: &str, token_b: &str) -> f64 {
@@ -356,10 +461,29 @@ impl FalsePositiveReducer {
     }
     
     async fn estimate_price_impact(&self, opportunity: &OpportunityDetails) -> f64 {
+        if let Some(impact) = self.trade_impact(opportunity).await {
+            return impact.impact_bps / 10_000.0;
+        }
+        
         // Estimate expected price impact
         self.estimate_slippage(opportunity).await * 0.8 // Price impact is typically less than slippage
     }
     
+    // The pool the opportunity trades in and the side it sells token_a into; None without a
+    // decoded pool or live state for it
+    async fn trade_pool(&self, opportunity: &OpportunityDetails) -> Option<(PoolState, SwapSide)> {
+        let evaluator = self.pool_states.as_ref()?;
+        let pool_address = opportunity.pool_address.as_ref()?;
+        let pool = evaluator.get_pool_state(pool_address, &opportunity.dex).await.ok()??;
+        let side = SwapSide::selling(&pool, &opportunity.token_a)?;
+        Some((pool, side))
+    }
+    
+    async fn trade_impact(&self, opportunity: &OpportunityDetails) -> Option<PriceImpact> {
+        let (pool, side) = self.trade_pool(opportunity).await?;
+        DexApi::price_impact(&pool, side, opportunity.trade_size)
+    }
+    
     // Method to record opportunity results for historical analysis
     pub async fn record_opportunity_result(
         &self,
@@ -382,8 +506,39 @@ impl FalsePositiveReducer {
         }
     }
     
+    // Opportunities the evaluator's liquidity filters or our launch filters dropped, so filter
+    // effectiveness can be compared with the rejections made in evaluate_opportunity
+    pub async fn record_filter_rejection(&self, opportunity_type: &OpportunityType, reason: &str) {
+        *self.filter_rejections.write().await
+            .entry(format!("{:?}/{}", opportunity_type, reason))
+            .or_insert(0) += 1;
+    }
+    
+    pub async fn filter_rejection_counts(&self) -> HashMap<String, u64> {
+        self.filter_rejections.read().await.clone()
+    }
+    
+    // Whether each of the last `limit` opportunities of this type we let through paid, oldest
+    // first; suitable for adjust_confidence_threshold
+    pub async fn recent_performance(&self, opportunity_type: &OpportunityType, limit: usize) -> Vec<bool> {
+        match self.outcomes {
+            Some(ref outcomes) => outcomes.recent_results(&format!("{:?}", opportunity_type), limit).await,
+            None => Vec::new(),
+        }
+    }
+    
     // Method to check historical success rate for similar opportunities
     pub async fn get_historical_success_rate(&self, opportunity_type: &OpportunityType) -> f64 {
+        // Precision of our own past decisions for this type, once any have resolved
+        if let Some(ref outcomes) = self.outcomes {
+            let type_key = format!("{:?}", opportunity_type);
+            if let Some((_, stats)) = outcomes.stats_by_type().await.into_iter().find(|(key, _)| *key == type_key) {
+                if stats.resolved() > 0 {
+                    return stats.filter_precision();
+                }
+            }
+        }
+        
         let history = self.opportunity_history.read().await;
         
         let relevant_results: Vec<&HistoricalResult> = history
@@ -408,7 +563,7 @@ impl FalsePositiveReducer {
     }
     
     // Method to update the confidence threshold based on recent performance
-    pub async fn adjust_confidence_threshold(&mut self, recent_performance: &[bool]) {
+    pub async fn adjust_confidence_threshold(&self, recent_performance: &[bool]) {
         if recent_performance.is_empty() {
             return;
         }
@@ -418,15 +573,49 @@ impl FalsePositiveReducer {
             .count() as f64 / recent_performance.len() as f64;
         
         // Adjust threshold based on success rate
+        let mut thresholds = self.thresholds.write().await;
         if success_rate > 0.85 {
             // If success rate is high, we can afford to be more selective
-            self.min_confidence_threshold = (self.min_confidence_threshold * 1.05).min(0.95);
+            thresholds.min_confidence = (thresholds.min_confidence * 1.05).min(0.95);
         } else if success_rate < 0.7 {
             // If success rate is low, be less selective to catch more opportunities
-            self.min_confidence_threshold = (self.min_confidence_threshold * 0.95).max(0.7);
+            thresholds.min_confidence = (thresholds.min_confidence * 0.95).max(0.7);
         }
     }
     
+    // Moves the thresholds to the ones that, replayed over our stored decisions, best hit the
+    // target precision and recall; see filter_decisions::recalibrate. Returns how they score
+    pub async fn recalibrate(&self) -> Option<FilterEvaluation> {
+        let decisions = self.decisions.as_ref()?.decisions().await;
+        let current = self.thresholds().await;
+        let (thresholds, evaluation) = filter_decisions::recalibrate(&decisions, &current, &self.calibration)?;
+        if thresholds != current {
+            Logger::status_update(&format!(
+                "Recalibrated opportunity filters from {} decisions: confidence {:.2}, min profit {:.4} SOL, pool depth {:.0}x, max variance {:?} (precision {:.2}, recall {:.2})",
+                evaluation.labeled(), thresholds.min_confidence, thresholds.min_profit, thresholds.pool_depth_multiplier,
+                thresholds.max_variance, evaluation.precision(), evaluation.recall()
+            ));
+            *self.thresholds.write().await = thresholds;
+        }
+        Some(evaluation)
+    }
+    
+    // Every FILTER_RECALIBRATION_SECS until shutdown, when there are decisions to learn from
+    pub fn spawn_recalibration(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let (Some(interval), Some(_)) = (self.calibration.interval, &self.decisions) else { return };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                self.recalibrate().await;
+            }
+        });
+    }
+    
     // Method to detect consecutive failures for specific strategy types
     pub async fn check_consecutive_failures(&self, strategy_type: &str) -> u32 {
         // In a real implementation, this would track consecutive failures by strategy type
@@ -435,6 +624,16 @@ impl FalsePositiveReducer {
     }
 }
 
+// Spread of the valid simulations' net profit; 0 without any
+fn simulation_variance(simulation_results: &[crate::utils::enhanced_transaction_simulator::SimulationResult]) -> f64 {
+    let profits: Vec<f64> = simulation_results.iter().filter(|r| r.is_valid).map(|r| r.net_profit).collect();
+    if profits.is_empty() {
+        return 0.0;
+    }
+    let mean = profits.iter().sum::<f64>() / profits.len() as f64;
+    profits.iter().map(|profit| (profit - mean).powi(2)).sum::<f64>() / profits.len() as f64
+}
+
 // Additional utilities for false positive detection
 pub mod fp_detection_utils {
     use super::*;
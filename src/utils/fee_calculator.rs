@@ -1,14 +1,132 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use serde_json::Value;
+use tokio::sync::RwLock;
+use crate::config::{Config, FeesConfig};
 use crate::logging::Logger;
 use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::bounded_cache::BoundedCache;
+use crate::utils::enhanced_transaction_simulator::OpportunityType;
+use crate::utils::mev_strategies::MevStrategyType;
 
-#[derive(Debug, Clone)]
+// Assumed when there is no simulation of the transactions to go by
+pub const DEFAULT_COMPUTE_UNITS: u64 = 200_000;
+// Per-transaction ceiling the runtime enforces
+pub const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+// Headroom over the simulated units; the pool can move between simulation and landing
+const COMPUTE_UNIT_MARGIN_PERCENT: u64 = 20;
+// Paid when no recent slot reports a fee for the accounts
+const FALLBACK_COMPUTE_UNIT_PRICE: u64 = 1_000_000;
+// Micro-lamports per unit; a single outlier slot doesn't get to price us out
+const MAX_COMPUTE_UNIT_PRICE: u64 = 100_000_000;
+// Distinct account sets held at once
+const FEE_CACHE_CAPACITY: usize = 256;
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+// Cap on the priority fee of a single transaction
+const MAX_PRIORITY_FEE_SOL: f64 = 0.01;
+
+// How the compute unit price is picked from the fees recently paid to write-lock the same accounts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriorityFeeConfig {
+    pub percentile: f64, // Of the per-slot fees, 0 to 100
+    pub arbitrage_multiplier: f64, // Safety multiplier on the percentile, per strategy
+    pub sandwich_multiplier: f64,
+    pub frontrun_multiplier: f64,
+    pub backrun_multiplier: f64,
+    pub other_multiplier: f64, // Liquidations, snipes and anything else
+    pub cache_ttl: Duration, // Recent fees are reused this long per account set
+    pub ema_alpha: f64, // Weight of the newest realized per-transaction fee in each strategy's average
+    pub spike_ratio: f64, // Readings above this many times the average count as this many
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self::from_config(&FeesConfig::default())
+    }
+}
+
+impl PriorityFeeConfig {
+    // Ranges are checked by Config::validate
+    pub fn from_config(config: &FeesConfig) -> Self {
+        Self {
+            percentile: config.priority_fee_percentile,
+            arbitrage_multiplier: config.arbitrage_multiplier,
+            sandwich_multiplier: config.sandwich_multiplier,
+            frontrun_multiplier: config.frontrun_multiplier,
+            backrun_multiplier: config.backrun_multiplier,
+            other_multiplier: config.other_multiplier,
+            cache_ttl: Duration::from_millis(config.cache_ms),
+            ema_alpha: config.ema_alpha,
+            spike_ratio: config.spike_ratio,
+        }
+    }
+
+    pub fn multiplier(&self, strategy: &MevStrategyType) -> f64 {
+        match strategy {
+            MevStrategyType::Arbitrage => self.arbitrage_multiplier,
+            MevStrategyType::Sandwich => self.sandwich_multiplier,
+            MevStrategyType::Frontrun => self.frontrun_multiplier,
+            MevStrategyType::Backrun => self.backrun_multiplier,
+            MevStrategyType::Liquidation | MevStrategyType::Snipe | MevStrategyType::Other => self.other_multiplier,
+        }
+    }
+}
+
+// What a strategy's bundle costs to land, before the priority fee: how many of our own
+// transactions it carries, what each consumes when there's no simulation to go by and the tip it
+// starts from under low competition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrategyFeeModel {
+    pub legs: usize,
+    pub compute_units_per_leg: u64,
+    pub tip_baseline: f64, // SOL
+}
+
+impl StrategyFeeModel {
+    pub fn for_strategy(strategy: &MevStrategyType) -> Self {
+        let (legs, compute_units_per_leg, tip_baseline) = match strategy {
+            MevStrategyType::Arbitrage => (2, 150_000, 0.0005),
+            MevStrategyType::Sandwich => (2, 120_000, 0.001), // Frontrun and backrun; the victim pays its own
+            MevStrategyType::Frontrun => (1, 150_000, 0.001),
+            MevStrategyType::Backrun => (1, 150_000, 0.0005),
+            MevStrategyType::Liquidation => (1, 300_000, 0.0005),
+            MevStrategyType::Snipe => (1, 100_000, 0.0005),
+            MevStrategyType::Other => (1, DEFAULT_COMPUTE_UNITS, 0.0005),
+        };
+        Self { legs, compute_units_per_leg, tip_baseline }
+    }
+}
+
+// One step of an exponential moving average toward `observed`, a reading above spike_ratio times
+// the average counting as spike_ratio times it, so one congested slot can't whipsaw the average
+pub fn ema_step(average: f64, observed: f64, alpha: f64, spike_ratio: f64) -> f64 {
+    alpha * observed.min(average * spike_ratio) + (1.0 - alpha) * average
+}
+
+// Nearest-rank percentile of the per-slot fees in a getRecentPrioritizationFees response; None
+// when it reports none
+pub fn fee_percentile(fees_data: &Value, percentile: f64) -> Option<u64> {
+    let mut fees: Vec<u64> = fees_data["result"].as_array()?.iter()
+        .filter_map(|entry| entry["prioritizationFee"].as_u64())
+        .collect();
+    if fees.is_empty() {
+        return None;
+    }
+    fees.sort_unstable();
+    let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * fees.len() as f64).ceil() as usize;
+    Some(fees[rank.clamp(1, fees.len()) - 1])
+}
+
+#[derive(Clone)]
 pub struct FeeCalculator {
     rpc_manager: Arc<RpcManager>,
     base_fee: f64,
     jito_tip: f64,
     dynamic_fee_multiplier: f64,
+    config: PriorityFeeConfig,
+    recent_fees: BoundedCache<Value>, // getRecentPrioritizationFees answers, by sorted account set
+    realized_fees: Arc<RwLock<HashMap<MevStrategyType, f64>>>, // EMA of the SOL each landed transaction paid, per strategy
 }
 
 #[derive(Debug, Clone)]
@@ -17,38 +135,78 @@ pub struct FeeEstimation {
     pub jito_tip: f64,
     pub priority_fee: f64,
     pub total_execution_cost: f64,
-    pub compute_unit_price: u64,
-    pub compute_units_consumed: u64,
+    pub safety_multiplier: f64, // The strategy's, already applied to compute_unit_price
+    pub compute_unit_price: u64, // Micro-lamports per compute unit; the ComputeBudget instruction sets this
+    pub compute_units_consumed: u64, // Simulated when known, DEFAULT_COMPUTE_UNITS otherwise
+    pub compute_unit_limit: u32, // What the ComputeBudget instruction requests
+}
+
+// Priority fee in SOL for `compute_units` at `compute_unit_price` micro-lamports each
+pub fn priority_fee_sol(compute_units: u64, compute_unit_price: u64) -> f64 {
+    (compute_units as f64 * compute_unit_price as f64 / 1_000_000.0) / 1_000_000_000.0
+}
+
+// ComputeBudget limit for a transaction that simulated at `simulated_units`: the units plus the
+// margin, or DEFAULT_COMPUTE_UNITS when it wasn't simulated
+pub fn compute_unit_limit(simulated_units: Option<u64>) -> u32 {
+    match simulated_units.filter(|units| *units > 0) {
+        Some(units) => (units.saturating_mul(100 + COMPUTE_UNIT_MARGIN_PERCENT).saturating_add(99) / 100).min(MAX_COMPUTE_UNIT_LIMIT) as u32,
+        None => DEFAULT_COMPUTE_UNITS as u32,
+    }
 }
 
 impl FeeCalculator {
-    pub async fn new(rpc_manager: Arc<RpcManager>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn new(rpc_manager: Arc<RpcManager>, config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = PriorityFeeConfig::from_config(&config.fees);
         Ok(Self {
             rpc_manager,
             base_fee: 0.001, // Base transaction fee
             jito_tip: 0.001, // Default Jito tip
             dynamic_fee_multiplier: 1.0, // Multiplier that can be adjusted based on network conditions
+            recent_fees: BoundedCache::new(FEE_CACHE_CAPACITY, config.cache_ttl),
+            realized_fees: Arc::new(RwLock::new(HashMap::new())),
+            config,
         })
     }
     
-    pub async fn calculate_dynamic_fees(&self, opportunity_value: f64) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
+    // `compute_units` is what our transactions consumed in simulation, summed over the bundle;
+    // None (or 0) falls back to DEFAULT_COMPUTE_UNITS. The compute unit price is what recent
+    // slots paid to write-lock `writable_accounts` (the contested pool and its vaults), at the
+    // configured percentile and times the strategy's multiplier
+    pub async fn calculate_dynamic_fees(
+        &self,
+        opportunity_value: f64,
+        compute_units: Option<u64>,
+        writable_accounts: &[String],
+        opportunity_type: &OpportunityType,
+    ) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Calculating dynamic fees based on recent block analysis");
         
-        // Get recent prioritization fees from the network
-        let recent_fees_data = self.get_recent_prioritization_fees().await?;
+        let recent_fees_data = self.get_recent_prioritization_fees(writable_accounts).await?;
+        
+        self.estimate_fees(&recent_fees_data, opportunity_value, compute_units, opportunity_type).await
+    }
+    
+    async fn estimate_fees(
+        &self,
+        recent_fees_data: &Value,
+        opportunity_value: f64,
+        compute_units: Option<u64>,
+        opportunity_type: &OpportunityType,
+    ) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
+        let simulated_units = compute_units.filter(|units| *units > 0);
+        let compute_units_consumed = simulated_units.unwrap_or(DEFAULT_COMPUTE_UNITS);
         
-        // Calculate priority fee based on recent network activity
-        let priority_fee = self.calculate_priority_fee(&recent_fees_data, opportunity_value).await?;
+        // Choose a compute unit price from what the same accounts recently cost, then pay it on every unit
+        let safety_multiplier = self.config.multiplier(&MevStrategyType::for_opportunity(opportunity_type));
+        let compute_unit_price = self.estimate_compute_unit_price(recent_fees_data, safety_multiplier);
+        let priority_fee = self.calculate_priority_fee(compute_units_consumed, compute_unit_price).await?;
         
         // Calculate Jito tip based on current competition level
-        let jito_tip = self.calculate_dynamic_jito_tip(&recent_fees_data, opportunity_value).await?;
+        let jito_tip = self.calculate_dynamic_jito_tip(recent_fees_data, opportunity_value).await?;
         
         // Calculate base transaction fee with adjustments
-        let transaction_fee = self.calculate_base_transaction_fee(&recent_fees_data).await?;
-        
-        // Calculate compute units and prices
-        let compute_unit_price = self.estimate_compute_unit_price(&recent_fees_data).await?;
-        let compute_units_consumed = self.estimate_compute_units_consumed().await?;
+        let transaction_fee = self.calculate_base_transaction_fee(recent_fees_data).await?;
         
         let total_execution_cost = transaction_fee + priority_fee + jito_tip;
         
@@ -57,43 +215,101 @@ impl FeeCalculator {
             jito_tip,
             priority_fee,
             total_execution_cost,
+            safety_multiplier,
             compute_unit_price,
             compute_units_consumed,
+            compute_unit_limit: compute_unit_limit(simulated_units),
         })
     }
     
-    async fn get_recent_prioritization_fees(&self) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        // Use the RPC manager to get recent prioritization fees
-        self.rpc_manager.get_recent_prioritization_fees().await
+    // Fees for a bundle of `legs` of our transactions (the strategy's usual count when 0) that
+    // simulated at `cu_estimate` units over all of them; without a simulation the model's units
+    // per leg are priced. The compute unit price is the one from calculate_dynamic_fees, and it is
+    // what the ComputeBudget instruction sets. The cost each leg is booked at is that price pulled
+    // toward what this strategy's landed transactions have been paying (see record_realized_fees)
+    pub async fn estimate_for(
+        &self,
+        strategy: &MevStrategyType,
+        legs: usize,
+        cu_estimate: Option<u64>,
+        writable_accounts: &[String],
+    ) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
+        let recent_fees_data = self.get_recent_prioritization_fees(writable_accounts).await?;
+        self.model_estimate(&recent_fees_data, strategy, legs, cu_estimate).await
     }
     
-    async fn calculate_priority_fee(&self, fees_data: &Value, opportunity_value: f64) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Analyze recent fees to determine appropriate priority fee
-        let mut fees_list = Vec::new();
-        
-        if let Some(fees_array) = fees_data["result"].as_array() {
-            for fee_entry in fees_array {
-                if let Some(prioritization_fee) = fee_entry["prioritizationFee"].as_u64() {
-                    fees_list.push(prioritization_fee as f64);
-                }
-            }
-        }
-        
-        if fees_list.is_empty() {
-            // Fallback if no recent fee data available
-            return Ok(0.001); // Conservative estimate
-        }
-        
-        // Calculate average fee and adjust based on opportunity value
-        let avg_fee: f64 = fees_list.iter().sum::<f64>() / fees_list.len() as f64;
+    async fn model_estimate(
+        &self,
+        recent_fees_data: &Value,
+        strategy: &MevStrategyType,
+        legs: usize,
+        cu_estimate: Option<u64>,
+    ) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
+        let model = StrategyFeeModel::for_strategy(strategy);
+        let legs = if legs == 0 { model.legs } else { legs } as u64;
+        let simulated_units = cu_estimate.filter(|units| *units > 0);
+        let compute_units_consumed = simulated_units.unwrap_or(model.compute_units_per_leg * legs);
+        let units_per_leg = compute_units_consumed.div_ceil(legs);
         
-        // For higher value opportunities, we may want to pay higher priority fees to ensure inclusion
-        let multiplier = if opportunity_value > 1.0 { 1.5 } else if opportunity_value > 0.1 { 1.2 } else { 1.0 };
+        let safety_multiplier = self.config.multiplier(strategy);
+        let compute_unit_price = self.estimate_compute_unit_price(recent_fees_data, safety_multiplier);
+        let base_fee_per_leg = LAMPORTS_PER_SIGNATURE as f64 / 1_000_000_000.0;
+        let live_per_leg = base_fee_per_leg + priority_fee_sol(units_per_leg, compute_unit_price).min(MAX_PRIORITY_FEE_SOL);
+        let per_leg = match self.realized_fees.read().await.get(strategy) {
+            Some(average) => ema_step(*average, live_per_leg, self.config.ema_alpha, self.config.spike_ratio),
+            None => live_per_leg,
+        };
         
-        // Convert from lamports to SOL and apply multiplier
-        let priority_fee_sol = (avg_fee / 1_000_000_000.0) * multiplier;
+        let transaction_fee = base_fee_per_leg * legs as f64;
+        let priority_fee = (per_leg - base_fee_per_leg).max(0.0) * legs as f64;
+        let jito_tip = model.tip_baseline * match self.assess_bundle_competition(recent_fees_data).await? {
+            CompetitionLevel::Low => 1.0,
+            CompetitionLevel::Medium => 2.0,
+            CompetitionLevel::High => 4.0,
+            CompetitionLevel::VeryHigh => 6.0,
+        };
         
-        Ok(priority_fee_sol.min(0.01)) // Cap priority fee at 0.01 SOL
+        Ok(FeeEstimation {
+            transaction_fee,
+            jito_tip,
+            priority_fee,
+            total_execution_cost: transaction_fee + priority_fee + jito_tip,
+            safety_multiplier,
+            compute_unit_price,
+            compute_units_consumed,
+            compute_unit_limit: compute_unit_limit(Some(units_per_leg)),
+        })
+    }
+    
+    // Books what a landed bundle of the strategy paid in transaction and priority fees over its
+    // `legs` transactions of ours
+    pub async fn record_realized_fees(&self, strategy: &MevStrategyType, legs: usize, fees: f64) {
+        if legs == 0 || !fees.is_finite() || fees < 0.0 {
+            return;
+        }
+        let per_leg = fees / legs as f64;
+        let mut realized_fees = self.realized_fees.write().await;
+        realized_fees.entry(strategy.clone())
+            .and_modify(|average| *average = ema_step(*average, per_leg, self.config.ema_alpha, self.config.spike_ratio))
+            .or_insert(per_leg);
+    }
+    
+    // Cached per account set, so a burst of opportunities on one pool asks the node once
+    async fn get_recent_prioritization_fees(&self, writable_accounts: &[String]) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut accounts = writable_accounts.to_vec();
+        accounts.sort();
+        accounts.dedup();
+        let key = accounts.join(",");
+        if let Some(cached) = self.recent_fees.get(&key).await {
+            return Ok(cached);
+        }
+        let fees = self.rpc_manager.get_recent_prioritization_fees_for(&accounts).await?;
+        self.recent_fees.insert(key, fees.clone()).await;
+        Ok(fees)
+    }
+    
+    async fn calculate_priority_fee(&self, compute_units: u64, compute_unit_price: u64) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(priority_fee_sol(compute_units, compute_unit_price).min(MAX_PRIORITY_FEE_SOL))
     }
     
     async fn calculate_dynamic_jito_tip(&self, fees_data: &Value, opportunity_value: f64) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
@@ -121,35 +337,13 @@ impl FeeCalculator {
         Ok(0.000005) // Base transaction fee in SOL
     }
     
-    async fn estimate_compute_unit_price(&self, fees_data: &Value) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        // Estimate the optimal compute unit price based on recent fees
-        let mut prices = Vec::new();
-        
-        if let Some(fees_array) = fees_data["result"].as_array() {
-            for fee_entry in fees_array {
-                if let Some(prioritization_fee) = fee_entry["prioritizationFee"].as_u64() {
-                    prices.push(prioritization_fee);
-                }
-            }
-        }
-        
-        if prices.is_empty() {
-            return Ok(1_000_000); // Conservative default in micro-lamports
-        }
-        
-        // Calculate average and convert to micro-lamports
-        let avg_price = prices.iter().sum::<u64>() as f64 / prices.len() as f64;
-        
-        // Convert to appropriate units for compute budget
-        Ok((avg_price.max(100_000.0).min(100_000_000.0)) as u64) // Between 0.1 and 100 micro-lamports
-    }
-    
-    async fn estimate_compute_units_consumed(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        // Estimate compute units consumed based on transaction complexity
-        // For MEV transactions, this would depend on the complexity of the DEX operations
-        
-        // Conservative estimate for complex MEV transactions
-        Ok(200_000) // 200k compute units for complex DEX operations
+    // Micro-lamports per compute unit: the configured percentile of the recent per-slot fees
+    // times the safety multiplier, at least 1 and at most MAX_COMPUTE_UNIT_PRICE
+    fn estimate_compute_unit_price(&self, fees_data: &Value, safety_multiplier: f64) -> u64 {
+        let Some(percentile) = fee_percentile(fees_data, self.config.percentile) else {
+            return FALLBACK_COMPUTE_UNIT_PRICE;
+        };
+        ((percentile as f64 * safety_multiplier).round() as u64).clamp(1, MAX_COMPUTE_UNIT_PRICE)
     }
     
     async fn assess_bundle_competition(&self, fees_data: &Value) -> Result<CompetitionLevel, Box<dyn std::error::Error + Send + Sync>> {
@@ -209,7 +403,7 @@ impl FeeCalculator {
         expected_profit: f64,
         opportunity_value: f64
     ) -> Result<ProfitabilityAnalysis, Box<dyn std::error::Error + Send + Sync>> {
-        let fee_estimation = self.calculate_dynamic_fees(opportunity_value).await?;
+        let fee_estimation = self.calculate_dynamic_fees(opportunity_value, None, &[], &OpportunityType::Other).await?;
         
         let total_costs = fee_estimation.total_execution_cost;
         let net_profit = expected_profit - total_costs;
@@ -247,4 +441,147 @@ enum CompetitionLevel {
     Medium,
     High,
     VeryHigh,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use serde_json::json;
+    use crate::utils::mock_http::{self, MockResponse};
+    use crate::rpc::rpc_manager::{RpcEndpoint, RpcEndpointType};
+
+    #[tokio::test]
+    async fn test_fee_scales_with_simulated_compute_units() {
+        let calculator = FeeCalculator::new(Arc::new(RpcManager::with_endpoints(Vec::new())), &Config::default()).await.unwrap();
+        let recent = json!({ "result": [
+            { "slot": 1, "prioritizationFee": 500_000 },
+            { "slot": 2, "prioritizationFee": 1_000_000 },
+            { "slot": 3, "prioritizationFee": 1_000_000 },
+            { "slot": 4, "prioritizationFee": 9_000_000 },
+        ]});
+        let estimate = |units| calculator.estimate_fees(&recent, 0.05, units, &OpportunityType::Arbitrage);
+
+        let light = estimate(Some(50_000)).await.unwrap();
+        let heavy = estimate(Some(400_000)).await.unwrap();
+        // The 75th percentile, 1,000,000 micro-lamports per unit
+        assert_eq!(light.compute_unit_price, 1_000_000);
+        assert!((light.priority_fee - 0.00005).abs() < 1e-12);
+        assert!((heavy.priority_fee - 8.0 * light.priority_fee).abs() < 1e-12);
+        assert!((heavy.total_execution_cost - light.total_execution_cost - 0.00035).abs() < 1e-12);
+        assert_eq!((light.compute_units_consumed, light.compute_unit_limit), (50_000, 60_000));
+        assert_eq!((heavy.compute_units_consumed, heavy.compute_unit_limit), (400_000, 480_000));
+
+        // Without a simulation the default is priced and requested
+        let unsimulated = estimate(Some(0)).await.unwrap();
+        assert_eq!((unsimulated.compute_units_consumed, unsimulated.compute_unit_limit), (DEFAULT_COMPUTE_UNITS, 200_000));
+        assert!((unsimulated.priority_fee - 0.0002).abs() < 1e-12);
+    }
+
+    // Node answering getRecentPrioritizationFees with `hot` when the request names `hot_account`
+    // and `cold` otherwise; counts the requests
+    async fn mock_fee_node(hot_account: &'static str, hot: Value, cold: Value) -> (String, Arc<AtomicUsize>) {
+        mock_http::serve(move |request| {
+            let result = if request.body.contains(hot_account) { &hot } else { &cold };
+            MockResponse::rpc_result(request, result.clone())
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_hot_accounts_cost_more_than_cold_ones() {
+        let slots = |fees: &[u64]| json!(fees.iter().enumerate()
+            .map(|(slot, fee)| json!({ "slot": slot, "prioritizationFee": fee }))
+            .collect::<Vec<_>>());
+        let (url, requests) = mock_fee_node(
+            "HotPoo1",
+            slots(&[200_000, 400_000, 800_000, 1_600_000]),
+            slots(&[0, 0, 1_000, 2_000]),
+        ).await;
+        let rpc_manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]);
+        let calculator = FeeCalculator::new(Arc::new(rpc_manager), &Config::default()).await.unwrap();
+
+        let hot_accounts = vec!["HotPoo1".to_string(), "HotVau1t".to_string()];
+        let hot = calculator.calculate_dynamic_fees(0.05, Some(100_000), &hot_accounts, &OpportunityType::Arbitrage).await.unwrap();
+        let cold = calculator.calculate_dynamic_fees(0.05, Some(100_000), &["ColdPoo1".to_string()], &OpportunityType::Arbitrage).await.unwrap();
+        assert_eq!(hot.compute_unit_price, 800_000);
+        assert_eq!(cold.compute_unit_price, 1_000);
+        assert!(hot.priority_fee > cold.priority_fee);
+        assert_eq!(hot.transaction_fee, cold.transaction_fee);
+
+        // The sandwich multiplier on the same percentile, from the cached answer
+        let sandwich = calculator.calculate_dynamic_fees(0.05, Some(100_000), &["HotVau1t".to_string(), "HotPoo1".to_string()], &OpportunityType::Sandwich).await.unwrap();
+        assert_eq!(sandwich.safety_multiplier, 1.2);
+        assert_eq!(sandwich.compute_unit_price, 960_000);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sandwich_costs_more_than_a_snipe() {
+        let calculator = FeeCalculator::new(Arc::new(RpcManager::with_endpoints(Vec::new())), &Config::default()).await.unwrap();
+        let recent = json!({ "result": [{ "slot": 1, "prioritizationFee": 200_000 }, { "slot": 2, "prioritizationFee": 400_000 }] });
+
+        let sandwich = calculator.model_estimate(&recent, &MevStrategyType::Sandwich, 3, None).await.unwrap();
+        let snipe = calculator.model_estimate(&recent, &MevStrategyType::Snipe, 1, None).await.unwrap();
+        assert!(sandwich.total_execution_cost > snipe.total_execution_cost);
+        assert!(sandwich.transaction_fee > snipe.transaction_fee);
+        assert!(sandwich.priority_fee > snipe.priority_fee);
+        assert!(sandwich.jito_tip > snipe.jito_tip);
+        assert_eq!(sandwich.compute_units_consumed, 360_000);
+        assert_eq!(sandwich.compute_unit_limit, 144_000);
+
+        // Simulated units are split over the legs; 0 legs is the strategy's usual count
+        let arbitrage = calculator.model_estimate(&recent, &MevStrategyType::Arbitrage, 0, Some(100_000)).await.unwrap();
+        assert_eq!((arbitrage.compute_units_consumed, arbitrage.compute_unit_limit), (100_000, 60_000));
+        assert!((arbitrage.transaction_fee - 0.00001).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_realized_fees_smooth_out_spikes() {
+        let calculator = FeeCalculator::new(Arc::new(RpcManager::with_endpoints(Vec::new())), &Config::default()).await.unwrap();
+        let quiet = json!({ "result": [{ "slot": 1, "prioritizationFee": 100_000 }] });
+        let spike = json!({ "result": [{ "slot": 1, "prioritizationFee": 50_000_000 }] });
+        let per_leg = |estimation: &FeeEstimation| (estimation.transaction_fee + estimation.priority_fee) / 2.0;
+
+        // Landed arbitrage bundles have been paying 0.00003 SOL per transaction
+        for _ in 0..5 {
+            calculator.record_realized_fees(&MevStrategyType::Arbitrage, 2, 0.00006).await;
+        }
+        let calm = calculator.model_estimate(&quiet, &MevStrategyType::Arbitrage, 2, Some(300_000)).await.unwrap();
+        // 0.8 of the average plus 0.2 of today's 0.00002
+        assert!((per_leg(&calm) - 0.000028).abs() < 1e-12);
+
+        // A slot at 500x the price moves the estimate by at most alpha * spike_ratio
+        let spiked = calculator.model_estimate(&spike, &MevStrategyType::Arbitrage, 2, Some(300_000)).await.unwrap();
+        assert!((per_leg(&spiked) - 0.000042).abs() < 1e-12);
+        assert_eq!(spiked.compute_unit_price, 50_000_000);
+
+        // The same holds for realized readings, and strategies don't share averages
+        calculator.record_realized_fees(&MevStrategyType::Arbitrage, 2, 1.0).await;
+        let after = calculator.model_estimate(&quiet, &MevStrategyType::Arbitrage, 2, Some(300_000)).await.unwrap();
+        assert!((per_leg(&after) - (0.8 * 0.000042 + 0.2 * 0.00002)).abs() < 1e-12);
+        let snipe = calculator.model_estimate(&quiet, &MevStrategyType::Snipe, 1, Some(150_000)).await.unwrap();
+        assert!((snipe.transaction_fee + snipe.priority_fee - 0.00002).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fee_percentile_is_nearest_rank() {
+        let recent = json!({ "result": [
+            { "slot": 1, "prioritizationFee": 40 },
+            { "slot": 2, "prioritizationFee": 10 },
+            { "slot": 3, "prioritizationFee": 30 },
+            { "slot": 4, "prioritizationFee": 20 },
+        ]});
+        assert_eq!(fee_percentile(&recent, 0.0), Some(10));
+        assert_eq!(fee_percentile(&recent, 50.0), Some(20));
+        assert_eq!(fee_percentile(&recent, 75.0), Some(30));
+        assert_eq!(fee_percentile(&recent, 100.0), Some(40));
+        assert_eq!(fee_percentile(&json!({ "result": [] }), 75.0), None);
+    }
+
+    #[test]
+    fn test_compute_unit_limit_stays_under_the_runtime_ceiling() {
+        assert_eq!(compute_unit_limit(Some(1)), 2);
+        assert_eq!(compute_unit_limit(Some(1_300_000)), MAX_COMPUTE_UNIT_LIMIT as u32);
+        assert_eq!(compute_unit_limit(None), DEFAULT_COMPUTE_UNITS as u32);
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use crate::config::FiltersConfig;
+use crate::utils::opportunity_outcomes::{OpportunityOutcome, OpportunityStage};
+
+const MAX_DECISIONS: usize = 10_000;
+
+// What the false positive reducer saw when it judged an opportunity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterFeatures {
+    pub estimated_profit: f64, // SOL
+    pub confidence: f64, // 0 to 1
+    pub variance: f64, // Of the valid simulations' net profit
+    pub dex: String,
+    pub liquidity: f64, // Estimated pool size
+    pub trade_size: f64,
+}
+
+// The thresholds the reducer applies that recalibration may move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FilterThresholds {
+    pub min_confidence: f64,
+    pub min_profit: f64, // SOL
+    pub pool_depth_multiplier: f64, // Pool must be this many times the trade size
+    pub max_variance: Option<f64>, // None doesn't cap it
+}
+
+impl Default for FilterThresholds {
+    fn default() -> Self {
+        Self { min_confidence: 0.85, min_profit: 0.001, pool_depth_multiplier: 10.0, max_variance: None }
+    }
+}
+
+impl FilterThresholds {
+    pub fn admits(&self, features: &FilterFeatures) -> bool {
+        features.confidence >= self.min_confidence
+            && features.estimated_profit >= self.min_profit
+            && features.liquidity >= features.trade_size * self.pool_depth_multiplier
+            && self.max_variance.is_none_or(|max| features.variance <= max)
+    }
+}
+
+// Where a decision's outcome came from, least trusted first. A rejection never goes out, so it's
+// labeled by simulation instead; a label is only replaced by a more trusted one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OutcomeSource {
+    Simulated, // The simulation the reducer judged the opportunity on
+    Shadow,    // The rejected bundle simulated as if it were going out (FILTER_SHADOW_REJECTIONS)
+    DryRun,    // Executed with the submission stubbed out
+    #[default]
+    Live,      // Submitted and reconciled
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DecisionOutcome {
+    pub executed: bool,
+    pub profitable: bool,
+    #[serde(default)]
+    pub source: OutcomeSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterDecision {
+    pub correlation_id: u64,
+    pub opportunity_type: String,
+    pub should_execute: bool,
+    pub filtered_reason: Option<String>,
+    pub features: FilterFeatures,
+    pub fixed_rejection: bool, // Turned away by a check no threshold controls (slippage, spam, token safety)
+    pub outcome: Option<DecisionOutcome>, // None until reconciled
+    pub decided_at: u64, // Unix seconds
+}
+
+impl FilterDecision {
+    // Whether the reducer would let this opportunity through with `thresholds`
+    pub fn admitted_by(&self, thresholds: &FilterThresholds) -> bool {
+        !self.fixed_rejection && thresholds.admits(&self.features)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilterEvaluation {
+    pub true_positives: u64, // Admitted and paid
+    pub false_positives: u64, // Admitted and made no money
+    pub false_negatives: u64, // Turned away but would have paid
+    pub true_negatives: u64,
+}
+
+impl FilterEvaluation {
+    pub fn labeled(&self) -> u64 {
+        self.true_positives + self.false_positives + self.false_negatives + self.true_negatives
+    }
+
+    pub fn precision(&self) -> f64 {
+        let admitted = self.true_positives + self.false_positives;
+        if admitted > 0 { self.true_positives as f64 / admitted as f64 } else { 0.0 }
+    }
+
+    pub fn recall(&self) -> f64 {
+        let profitable = self.true_positives + self.false_negatives;
+        if profitable > 0 { self.true_positives as f64 / profitable as f64 } else { 0.0 }
+    }
+}
+
+// Replays stored decisions against candidate thresholds. Only decisions with an outcome count;
+// rejections get theirs from simulation, see OutcomeSource
+pub fn evaluate(decisions: &[FilterDecision], thresholds: &FilterThresholds) -> FilterEvaluation {
+    let mut evaluation = FilterEvaluation::default();
+    for decision in decisions {
+        let Some(outcome) = decision.outcome else { continue };
+        match (decision.admitted_by(thresholds), outcome.profitable) {
+            (true, true) => evaluation.true_positives += 1,
+            (true, false) => evaluation.false_positives += 1,
+            (false, true) => evaluation.false_negatives += 1,
+            (false, false) => evaluation.true_negatives += 1,
+        }
+    }
+    evaluation
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterCalibrationConfig {
+    pub target_precision: f64,
+    pub target_recall: f64,
+    pub min_samples: usize, // Labeled decisions needed before thresholds move
+    pub interval: Option<Duration>, // None disables recalibration
+}
+
+impl Default for FilterCalibrationConfig {
+    fn default() -> Self {
+        Self { target_precision: 0.8, target_recall: 0.5, min_samples: 50, interval: Some(Duration::from_secs(900)) }
+    }
+}
+
+impl FilterCalibrationConfig {
+    pub fn from_config(config: &FiltersConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !(0.0..=1.0).contains(&config.target_precision) || !(0.0..=1.0).contains(&config.target_recall) {
+            return Err("FILTER_TARGET_PRECISION and FILTER_TARGET_RECALL must be in [0, 1]".into());
+        }
+
+        let interval_secs = config.recalibration_secs; // 0 disables recalibration
+        Ok(Self {
+            target_precision: config.target_precision,
+            target_recall: config.target_recall,
+            min_samples: config.recalibration_min_samples,
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+        })
+    }
+}
+
+const CONFIDENCE_CANDIDATES: [f64; 10] = [0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95];
+const PROFIT_CANDIDATES: [f64; 5] = [0.0005, 0.001, 0.002, 0.005, 0.01];
+const POOL_DEPTH_CANDIDATES: [f64; 4] = [2.0, 5.0, 10.0, 20.0];
+
+// Nearest-rank percentile of the labeled decisions' variance, as a cap to try
+fn variance_percentile(decisions: &[FilterDecision], percentile: f64) -> Option<f64> {
+    let mut variances: Vec<f64> = decisions.iter()
+        .filter(|decision| decision.outcome.is_some() && decision.features.variance.is_finite())
+        .map(|decision| decision.features.variance)
+        .collect();
+    if variances.is_empty() {
+        return None;
+    }
+    variances.sort_by(|a, b| a.total_cmp(b));
+    let rank = ((percentile * variances.len() as f64).ceil() as usize).clamp(1, variances.len());
+    Some(variances[rank - 1])
+}
+
+// How many of the thresholds a candidate moves away from the current ones
+fn changes(candidate: &FilterThresholds, current: &FilterThresholds) -> usize {
+    [
+        candidate.min_confidence != current.min_confidence,
+        candidate.min_profit != current.min_profit,
+        candidate.pool_depth_multiplier != current.pool_depth_multiplier,
+        candidate.max_variance != current.max_variance,
+    ].iter().filter(|changed| **changed).count()
+}
+
+// The thresholds that best meet the operator's targets on the stored decisions: the highest
+// recall among those reaching the target precision or, failing that, the highest precision that
+// still keeps the target recall. Ties go to the candidate that moves the fewest of `current`'s
+// thresholds, so one the data says nothing about stays put. None when there's too little data
+// or nothing qualifies
+pub fn recalibrate(
+    decisions: &[FilterDecision],
+    current: &FilterThresholds,
+    config: &FilterCalibrationConfig,
+) -> Option<(FilterThresholds, FilterEvaluation)> {
+    let labeled = decisions.iter().filter(|decision| decision.outcome.is_some()).count();
+    if labeled == 0 || labeled < config.min_samples {
+        return None;
+    }
+    let mut pool_depths = POOL_DEPTH_CANDIDATES.to_vec();
+    pool_depths.push(current.pool_depth_multiplier);
+    let mut variance_caps = vec![None, current.max_variance];
+    variance_caps.extend([0.95, 0.75].iter().filter_map(|&percentile| variance_percentile(decisions, percentile)).map(Some));
+
+    // (thresholds, evaluation, what it's ranked by, changes from current)
+    type Candidate = (FilterThresholds, FilterEvaluation, (f64, f64), usize);
+    let mut meeting_precision: Option<Candidate> = None;
+    let mut keeping_recall: Option<Candidate> = None;
+    for min_confidence in CONFIDENCE_CANDIDATES.iter().copied().chain([current.min_confidence]) {
+        for min_profit in PROFIT_CANDIDATES.iter().copied().chain([current.min_profit]) {
+            for &pool_depth_multiplier in &pool_depths {
+                for &max_variance in &variance_caps {
+                    let thresholds = FilterThresholds { min_confidence, min_profit, pool_depth_multiplier, max_variance };
+                    let evaluation = evaluate(decisions, &thresholds);
+                    let (precision, recall) = (evaluation.precision(), evaluation.recall());
+                    if recall < config.target_recall {
+                        continue;
+                    }
+                    let (best, rank) = if precision >= config.target_precision {
+                        (&mut meeting_precision, (recall, precision))
+                    } else {
+                        (&mut keeping_recall, (precision, recall))
+                    };
+                    let moved = changes(&thresholds, current);
+                    let better = best.is_none_or(|(_, _, best_rank, best_moved)| {
+                        rank > best_rank || (rank == best_rank && moved < best_moved)
+                    });
+                    if better {
+                        *best = Some((thresholds, evaluation, rank, moved));
+                    }
+                }
+            }
+        }
+    }
+    meeting_precision.or(keeping_recall).map(|(thresholds, evaluation, _, _)| (thresholds, evaluation))
+}
+
+// Every verdict the false positive reducer reached, with how the opportunity turned out once
+// reconciliation knows. Bounded to the most recent MAX_DECISIONS and saved with the metrics
+// state. Cheap to clone: clones share the same log
+#[derive(Debug, Clone, Default)]
+pub struct FilterDecisionLog {
+    decisions: Arc<RwLock<VecDeque<FilterDecision>>>, // Oldest first
+}
+
+impl FilterDecisionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn record(&self, decision: FilterDecision) {
+        let mut decisions = self.decisions.write().await;
+        decisions.push_back(decision);
+        while decisions.len() > MAX_DECISIONS {
+            decisions.pop_front();
+        }
+    }
+
+    // Labels the decision behind a final opportunity outcome, `source` telling a live execution
+    // from a dry run; anything short of execution is left unlabeled
+    pub async fn record_outcome(&self, outcome: &OpportunityOutcome, source: OutcomeSource) -> bool {
+        let Some(false_positive) = outcome.is_false_positive() else { return false };
+        let executed = matches!(outcome.stage, OpportunityStage::Executed { .. } | OpportunityStage::BundleFailed);
+        self.label(outcome.correlation_id, DecisionOutcome { executed, profitable: !false_positive, source }).await
+    }
+
+    // Labels a decision unless it already has an outcome from a more trusted source
+    pub async fn label(&self, correlation_id: u64, outcome: DecisionOutcome) -> bool {
+        let mut decisions = self.decisions.write().await;
+        let Some(decision) = decisions.iter_mut().rev().find(|decision| decision.correlation_id == correlation_id) else {
+            return false;
+        };
+        if decision.outcome.is_some_and(|labeled| labeled.source > outcome.source) {
+            return false;
+        }
+        decision.outcome = Some(outcome);
+        true
+    }
+
+    pub async fn decisions(&self) -> Vec<FilterDecision> {
+        self.decisions.read().await.iter().cloned().collect()
+    }
+
+    // Continues a previous process's log, ahead of anything recorded since
+    pub async fn restore(&self, restored: Vec<FilterDecision>) {
+        let mut decisions = self.decisions.write().await;
+        let recent: Vec<FilterDecision> = decisions.drain(..).collect();
+        decisions.extend(restored);
+        decisions.extend(recent);
+        while decisions.len() > MAX_DECISIONS {
+            decisions.pop_front();
+        }
+    }
+
+    pub async fn len(&self) -> usize {
+        self.decisions.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.decisions.read().await.is_empty()
+    }
+
+    pub async fn clear(&self) {
+        self.decisions.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(correlation_id: u64, confidence: f64, estimated_profit: f64, profitable: Option<bool>) -> FilterDecision {
+        FilterDecision {
+            correlation_id,
+            opportunity_type: "Arbitrage".to_string(),
+            should_execute: true,
+            filtered_reason: None,
+            features: FilterFeatures {
+                estimated_profit,
+                confidence,
+                variance: 0.0001,
+                dex: "Raydium".to_string(),
+                liquidity: 50_000.0,
+                trade_size: 1_000.0,
+            },
+            fixed_rejection: false,
+            outcome: profitable.map(|profitable| DecisionOutcome { executed: true, profitable, source: OutcomeSource::Live }),
+            decided_at: 1,
+        }
+    }
+
+    // 200 opportunities over a grid of confidence and profit; they pay when confidence is at
+    // least 0.7 and the estimate at least 0.002 SOL, except every tenth paying one, which lost
+    fn labeled_dataset() -> Vec<FilterDecision> {
+        let mut decisions = Vec::new();
+        let mut paying = 0;
+        for i in 0..20 {
+            for j in 0..10 {
+                let confidence = 0.5 + i as f64 * 0.025;
+                let estimated_profit = 0.0005 * (j + 1) as f64;
+                let mut profitable = confidence >= 0.7 - 1e-9 && estimated_profit >= 0.002 - 1e-9;
+                if profitable {
+                    paying += 1;
+                    profitable = paying % 10 != 0;
+                }
+                decisions.push(decision(decisions.len() as u64, confidence, estimated_profit, Some(profitable)));
+            }
+        }
+        decisions
+    }
+
+    #[test]
+    fn test_replay_scores_candidate_thresholds() {
+        let mut decisions = labeled_dataset();
+        // Unlabeled decisions and those turned away for other reasons aren't admitted by any threshold
+        decisions.push(decision(1_000, 0.99, 0.01, None));
+        let mut frozen = decision(1_001, 0.99, 0.01, Some(true));
+        frozen.fixed_rejection = true;
+        decisions.push(frozen);
+
+        // The defaults admit 0.85 confidence and up (6 rows) from 0.001 SOL (9 columns), missing
+        // the paying opportunities between 0.7 and 0.85
+        let current = evaluate(&decisions, &FilterThresholds::default());
+        assert_eq!(current.labeled(), 201);
+        assert_eq!(current, FilterEvaluation { true_positives: 38, false_positives: 16, false_negatives: 39, true_negatives: 108 });
+        assert!(current.recall() < 0.5);
+
+        let loose = evaluate(&decisions, &FilterThresholds { min_confidence: 0.5, min_profit: 0.0005, ..FilterThresholds::default() });
+        assert_eq!(loose, FilterEvaluation { true_positives: 76, false_positives: 124, false_negatives: 1, true_negatives: 0 });
+        assert!((loose.precision() - 0.38).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recalibration_hits_the_target_precision_and_recall() {
+        let decisions = labeled_dataset();
+        let config = FilterCalibrationConfig { target_precision: 0.85, target_recall: 0.9, min_samples: 50, interval: None };
+        let current = FilterThresholds::default();
+        let (thresholds, evaluation) = recalibrate(&decisions, &current, &config).unwrap();
+        // Every opportunity has the same liquidity and variance, so those thresholds stay put
+        assert_eq!(thresholds, FilterThresholds { min_confidence: 0.7, min_profit: 0.002, ..current });
+        assert_eq!(evaluation, FilterEvaluation { true_positives: 76, false_positives: 8, false_negatives: 0, true_negatives: 116 });
+
+        // A precision no threshold reaches falls back to the most precise that keeps the recall
+        let strict = FilterCalibrationConfig { target_precision: 0.99, ..config };
+        let (_, evaluation) = recalibrate(&decisions, &current, &strict).unwrap();
+        assert!(evaluation.recall() >= 0.9);
+        assert!(evaluation.precision() < 0.99);
+
+        // Only what today's thresholds let through went out; the rejections' simulations are
+        // enough to tell the confidence threshold is turning paying opportunities away
+        let simulated_rejections: Vec<FilterDecision> = decisions.iter().cloned()
+            .map(|mut decision| {
+                if !decision.admitted_by(&current) {
+                    decision.outcome = decision.outcome
+                        .map(|outcome| DecisionOutcome { executed: false, source: OutcomeSource::Simulated, ..outcome });
+                }
+                decision
+            })
+            .collect();
+        let (thresholds, _) = recalibrate(&simulated_rejections, &current, &config).unwrap();
+        assert_eq!(thresholds, FilterThresholds { min_confidence: 0.7, min_profit: 0.002, ..current });
+
+        // Too few labeled decisions to move anything
+        assert!(recalibrate(&decisions[..20], &current, &config).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outcomes_label_the_decision_behind_them() {
+        let log = FilterDecisionLog::new();
+        log.record(decision(7, 0.9, 0.01, None)).await;
+        log.record(decision(8, 0.9, 0.01, None)).await;
+
+        let outcome = |correlation_id, stage| OpportunityOutcome {
+            correlation_id,
+            opportunity_type: "Arbitrage".to_string(),
+            dex: "Raydium".to_string(),
+            estimated_profit: 0.01,
+            stage,
+            bundle_id: None,
+        };
+        assert!(log.record_outcome(&outcome(7, OpportunityStage::BundleFailed), OutcomeSource::Live).await);
+        assert!(!log.record_outcome(&outcome(8, OpportunityStage::Queued), OutcomeSource::Live).await);
+        assert!(!log.record_outcome(&outcome(9, OpportunityStage::Executed { profit: 0.01, success: true }), OutcomeSource::Live).await);
+
+        let decisions = log.decisions().await;
+        assert_eq!(decisions[0].outcome, Some(DecisionOutcome { executed: true, profitable: false, source: OutcomeSource::Live }));
+        assert_eq!(decisions[1].outcome, None);
+
+        // A rejection's simulation gives way to a shadow run of its bundle, but never to a live result
+        let simulated = DecisionOutcome { executed: false, profitable: true, source: OutcomeSource::Simulated };
+        let shadow = DecisionOutcome { executed: false, profitable: false, source: OutcomeSource::Shadow };
+        assert!(!log.label(7, simulated).await);
+        assert!(log.label(8, simulated).await);
+        assert!(log.label(8, shadow).await);
+        assert!(!log.label(8, simulated).await);
+        assert_eq!(log.decisions().await[1].outcome, Some(shadow));
+        let decisions = log.decisions().await;
+
+        // A restart picks the saved decisions back up ahead of new ones
+        let restarted = FilterDecisionLog::new();
+        restarted.record(decision(9, 0.9, 0.01, None)).await;
+        let saved: Vec<FilterDecision> = serde_json::from_str(&serde_json::to_string(&decisions).unwrap()).unwrap();
+        restarted.restore(saved).await;
+        let ids: Vec<u64> = restarted.decisions().await.iter().map(|decision| decision.correlation_id).collect();
+        assert_eq!(ids, vec![7, 8, 9]);
+    }
+}
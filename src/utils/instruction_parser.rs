@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use serde_json::Value;
+
+// Helpers for reading instructions out of getTransaction results. Handles both the
+// "json" encoding (programIdIndex + account indices) and "jsonParsed" (pubkey strings).
+
+#[derive(Debug, Clone)]
+pub struct ParsedInstruction {
+    pub program_id: String,
+    pub accounts: Vec<String>,
+    pub data: Option<String>, // base58 encoded instruction data
+}
+
+// All account keys of the transaction, including addresses loaded from lookup tables
+pub fn account_keys(tx_details: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = tx_details["transaction"]["message"]["accountKeys"]
+        .as_array()
+        .map(|keys| {
+            keys.iter()
+                .filter_map(|key| key.as_str().or_else(|| key["pubkey"].as_str()))
+                .map(|key| key.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // v0 transactions append writable then readonly lookup-table addresses
+    for section in ["writable", "readonly"] {
+        if let Some(loaded) = tx_details["meta"]["loadedAddresses"][section].as_array() {
+            keys.extend(loaded.iter().filter_map(|k| k.as_str()).map(|k| k.to_string()));
+        }
+    }
+
+    keys
+}
+
+pub fn parse_instruction(instruction: &Value, keys: &[String]) -> Option<ParsedInstruction> {
+    let program_id = match instruction.get("programId").and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => {
+            let index = instruction.get("programIdIndex")?.as_u64()? as usize;
+            keys.get(index)?.clone()
+        }
+    };
+
+    let accounts = instruction.get("accounts")
+        .and_then(|v| v.as_array())
+        .map(|accounts| {
+            accounts.iter()
+                .filter_map(|account| match account {
+                    Value::String(pubkey) => Some(pubkey.clone()),
+                    Value::Number(index) => index.as_u64().and_then(|i| keys.get(i as usize).cloned()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ParsedInstruction {
+        program_id,
+        accounts,
+        data: instruction.get("data").and_then(|v| v.as_str()).map(|d| d.to_string()),
+    })
+}
+
+// Top-level and inner (CPI) instructions in execution order
+pub fn all_instructions(tx_details: &Value) -> Vec<ParsedInstruction> {
+    let keys = account_keys(tx_details);
+    let mut parsed = Vec::new();
+
+    if let Some(instructions) = tx_details["transaction"]["message"]["instructions"].as_array() {
+        parsed.extend(instructions.iter().filter_map(|ix| parse_instruction(ix, &keys)));
+    }
+
+    if let Some(inner_groups) = tx_details["meta"]["innerInstructions"].as_array() {
+        for group in inner_groups {
+            if let Some(instructions) = group["instructions"].as_array() {
+                parsed.extend(instructions.iter().filter_map(|ix| parse_instruction(ix, &keys)));
+            }
+        }
+    }
+
+    parsed
+}
+
+pub fn log_messages(tx_details: &Value) -> Vec<&str> {
+    tx_details["meta"]["logMessages"]
+        .as_array()
+        .map(|logs| logs.iter().filter_map(|l| l.as_str()).collect())
+        .unwrap_or_default()
+}
+
+pub fn decode_instruction_data(instruction: &ParsedInstruction) -> Option<Vec<u8>> {
+    instruction.data.as_ref().and_then(|data| bs58::decode(data).into_vec().ok())
+}
+
+// Mint of a token account, resolved from the transaction's pre/post token balance metadata
+pub fn token_account_mint(tx_details: &Value, token_account: &str) -> Option<String> {
+    token_balance_entry(tx_details, token_account, "postTokenBalances")
+        .or_else(|| token_balance_entry(tx_details, token_account, "preTokenBalances"))
+        .and_then(|entry| entry["mint"].as_str().map(|mint| mint.to_string()))
+}
+
+// Raw amount change (post - pre) of a token account over the transaction
+pub fn token_balance_change(tx_details: &Value, token_account: &str) -> Option<i128> {
+    let amount = |section: &str| -> Option<i128> {
+        token_balance_entry(tx_details, token_account, section)
+            .and_then(|entry| entry["uiTokenAmount"]["amount"].as_str()?.parse().ok())
+    };
+
+    let post = amount("postTokenBalances");
+    let pre = amount("preTokenBalances");
+    if post.is_none() && pre.is_none() {
+        return None;
+    }
+    // Accounts created or closed by the transaction only show up on one side
+    Some(post.unwrap_or(0) - pre.unwrap_or(0))
+}
+
+// The wallet that signed and paid for the transaction
+pub fn fee_payer(tx_details: &Value) -> Option<String> {
+    account_keys(tx_details).into_iter().next()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnerBalanceChange {
+    pub owner: String,
+    pub mint: String,
+    pub decimals: u8,
+    pub change: i128, // Raw post - pre, summed over the owner's accounts of the mint
+}
+
+impl OwnerBalanceChange {
+    pub fn ui_change(&self) -> f64 {
+        self.change as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+// (accountIndex, mint) -> (owner, decimals, pre, post)
+type TokenAccountBalances = BTreeMap<(u64, String), (Option<String>, u8, i128, i128)>;
+
+// Net token balance changes per (owner, mint). pre/postTokenBalances are keyed by accountIndex
+// and differ in length and order when accounts are created or closed, so entries are matched
+// by (accountIndex, mint) and a missing side counts as zero
+pub fn owner_balance_changes(pre_balances: &[Value], post_balances: &[Value]) -> Vec<OwnerBalanceChange> {
+    let mut accounts: TokenAccountBalances = BTreeMap::new();
+    for (balances, is_post) in [(pre_balances, false), (post_balances, true)] {
+        for entry in balances {
+            let (Some(index), Some(mint)) = (entry["accountIndex"].as_u64(), entry["mint"].as_str()) else { continue };
+            let amount: i128 = entry["uiTokenAmount"]["amount"].as_str()
+                .and_then(|amount| amount.parse().ok())
+                .unwrap_or(0);
+            let account = accounts.entry((index, mint.to_string())).or_insert((None, 0, 0, 0));
+            if let Some(owner) = entry["owner"].as_str() {
+                account.0 = Some(owner.to_string());
+            }
+            if let Some(decimals) = entry["uiTokenAmount"]["decimals"].as_u64() {
+                account.1 = decimals as u8;
+            }
+            if is_post { account.3 = amount } else { account.2 = amount }
+        }
+    }
+
+    let mut by_owner: BTreeMap<(String, String), (u8, i128)> = BTreeMap::new();
+    for ((_, mint), (owner, decimals, pre, post)) in accounts {
+        // Entries without an owner (very old transactions) can't be attributed
+        let Some(owner) = owner else { continue };
+        let total = by_owner.entry((owner, mint)).or_insert((decimals, 0));
+        total.1 += post - pre;
+    }
+
+    by_owner.into_iter()
+        .filter(|(_, (_, change))| *change != 0)
+        .map(|((owner, mint), (decimals, change))| OwnerBalanceChange { owner, mint, decimals, change })
+        .collect()
+}
+
+// Balance changes of the fee payer's own token accounts
+pub fn fee_payer_balance_changes(tx_details: &Value) -> Vec<OwnerBalanceChange> {
+    let Some(payer) = fee_payer(tx_details) else { return Vec::new() };
+    let section = |name: &str| tx_details["meta"][name].as_array().cloned().unwrap_or_default();
+    owner_balance_changes(&section("preTokenBalances"), &section("postTokenBalances"))
+        .into_iter()
+        .filter(|change| change.owner == payer)
+        .collect()
+}
+
+fn token_balance_entry<'a>(tx_details: &'a Value, token_account: &str, section: &str) -> Option<&'a Value> {
+    let keys = account_keys(tx_details);
+    tx_details["meta"][section].as_array()?.iter().find(|entry| {
+        entry["accountIndex"].as_u64()
+            .and_then(|index| keys.get(index as usize))
+            .is_some_and(|key| key == token_account)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const POOL_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn balance(index: u64, mint: &str, owner: &str, amount: u64, decimals: u8) -> Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": owner,
+            "uiTokenAmount": { "amount": amount.to_string(), "decimals": decimals }
+        })
+    }
+
+    // Wallet sells 100 USDC for BONK; its BONK account is created by the transaction and a
+    // temporary USDC account is closed, so the arrays differ in length and order
+    fn swap_transaction() -> Value {
+        json!({
+            "transaction": { "message": { "accountKeys": [WALLET] } },
+            "meta": {
+                "preTokenBalances": [
+                    balance(1, USDC, WALLET, 250_000_000, 6),
+                    balance(4, USDC, POOL_AUTHORITY, 9_000_000_000, 6),
+                    balance(5, BONK, POOL_AUTHORITY, 900_000_000_000, 5),
+                    balance(6, USDC, WALLET, 10_000_000, 6),
+                ],
+                "postTokenBalances": [
+                    balance(5, BONK, POOL_AUTHORITY, 400_000_000_000, 5),
+                    balance(4, USDC, POOL_AUTHORITY, 9_110_000_000, 6),
+                    balance(2, BONK, WALLET, 500_000_000_000, 5),
+                    balance(1, USDC, WALLET, 150_000_000, 6),
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_matches_balances_by_account_not_position() {
+        let tx = swap_transaction();
+        let changes = owner_balance_changes(
+            tx["meta"]["preTokenBalances"].as_array().unwrap(),
+            tx["meta"]["postTokenBalances"].as_array().unwrap(),
+        );
+
+        let change = |owner: &str, mint: &str| changes.iter()
+            .find(|c| c.owner == owner && c.mint == mint)
+            .map(|c| c.change);
+        assert_eq!(change(POOL_AUTHORITY, USDC), Some(110_000_000));
+        assert_eq!(change(POOL_AUTHORITY, BONK), Some(-500_000_000_000));
+        // Created BONK account counts from zero; the closed USDC account counts to zero
+        assert_eq!(change(WALLET, BONK), Some(500_000_000_000));
+        assert_eq!(change(WALLET, USDC), Some(-110_000_000));
+    }
+
+    #[test]
+    fn test_fee_payer_changes_exclude_pool_accounts() {
+        let changes = fee_payer_balance_changes(&swap_transaction());
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|change| change.owner == WALLET));
+
+        let bonk = changes.iter().find(|change| change.mint == BONK).unwrap();
+        assert!((bonk.ui_change() - 5_000_000.0).abs() < 1e-9);
+    }
+}
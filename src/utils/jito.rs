@@ -1,43 +1,101 @@
 use reqwest;
 use serde_json::{json, Value};
 use solana_sdk::pubkey::Pubkey;
+use crate::config::{Config, JitoConfig};
+use crate::logging::Logger;
+use crate::rpc::error::RpcError;
+use crate::utils::bundle_simulation::{self, BundleSimulation};
+use crate::utils::dry_run;
+use crate::utils::jito_auth::JitoAuth;
+use crate::utils::jito_rate_limit::{self, BundleRateLimiter, Throttled};
+use std::sync::Arc;
+use tokio::sync::watch;
 
+// The official tip accounts; the same on mainnet and devnet
+pub const TIP_ACCOUNTS: [&str; 5] = [
+    "96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+];
+
+// Cheap to clone: clones share the searcher auth session and the rate limiter, so one bot's
+// submissions all count against the same allowance
+#[derive(Clone)]
 pub struct JitoClient {
     client: reqwest::Client,
     jito_rpc_url: String,
+    simulation_url: Option<String>, // simulateBundle goes to jito_rpc_url when unset
     auth_header: Option<String>,
+    auth: Option<Arc<JitoAuth>>, // Searcher auth; takes over from the static header when configured
+    rate_limiter: Arc<BundleRateLimiter>,
     // Jito tip accounts (these are the public keys of the tip accounts)
     tip_accounts: Vec<Pubkey>,
+    dry_run: bool, // Bundles are built but never sent
 }
 
 impl JitoClient {
-    pub fn new() -> Option<Self> {
-        // Try to get JITO_RPC_URL from environment, otherwise default to mainnet endpoint
-        let jito_rpc_url = std::env::var("JITO_RPC_URL")
-            .unwrap_or_else(|_| "https://mainnet.block-engine.jito.wtf:443".to_string());
-        
-        // Jito authentication header (if provided)
-        let auth_header = std::env::var("JITO_AUTH_HEADER").ok();
-        
+    pub fn from_config(config: &Config) -> Self {
+        // JITO_RPC_URL, otherwise the mainnet endpoint
+        let jito_rpc_url = config.jito.rpc_url.clone()
+            .unwrap_or_else(|| "https://mainnet.block-engine.jito.wtf:443".to_string());
+
+        Self {
+            simulation_url: config.jito.simulation_url.clone(),
+            auth_header: config.jito.auth_header.clone(),
+            auth: JitoAuth::load(&config.jito),
+            rate_limiter: Arc::new(BundleRateLimiter::from_config(&config.jito)),
+            dry_run: config.dry_run,
+            ..Self::with_url(jito_rpc_url)
+        }
+    }
+
+    // Unauthenticated and with the default rate limits
+    pub fn with_url(jito_rpc_url: String) -> Self {
         // Jito tip accounts - these are the official tip account addresses
-        // These should work for both mainnet and devnet
-        let tip_accounts = vec![
-            "96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx".parse().unwrap(), // Main tip account
-            "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY".parse().unwrap(), // Alternative tip account
-            "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh".parse().unwrap(), // Alternative tip account
-            "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49".parse().unwrap(), // Alternative tip account
-            "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt".parse().unwrap(), // Alternative tip account
-        ];
+        let tip_accounts = TIP_ACCOUNTS.iter().map(|account| account.parse().unwrap()).collect();
         
-        Some(Self {
+        Self {
             client: reqwest::Client::new(),
             jito_rpc_url,
-            auth_header,
+            simulation_url: None,
+            auth_header: None,
+            auth: None,
+            rate_limiter: Arc::new(BundleRateLimiter::from_config(&JitoConfig::default())),
             tip_accounts,
-        })
+            dry_run: false,
+        }
+    }
+
+    // A limiter of its own instead of the one the config sets up
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<BundleRateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    // Bundles held back or dropped by the rate limiter so far
+    pub fn throttled_count(&self) -> u64 {
+        self.rate_limiter.throttled_count()
+    }
+
+    // Keeps the searcher auth token fresh until `shutdown` flips to true; nothing to do without auth
+    pub fn start_auth_refresh(&self, shutdown: watch::Receiver<bool>) {
+        if let Some(auth) = &self.auth {
+            auth.start_refresh(shutdown);
+        }
     }
 
     pub async fn send_bundle(&self, transactions: &[String]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        if self.dry_run {
+            let bundle_id = dry_run::synthetic_signature(transactions);
+            Logger::status_update(&format!("DRY RUN: bundle of {} transactions not sent ({})", transactions.len(), bundle_id));
+            return Ok(bundle_id);
+        }
+        
+        // Wait for a slot under the rate limit, or give up on a bundle that would go out stale
+        self.rate_limiter.admit()?.wait().await;
+        
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -45,10 +103,118 @@ impl JitoClient {
             "params": [transactions]
         });
 
-        let mut request = self.client.post(&self.jito_rpc_url).json(&request_body);
+        let response = self.post(&request_body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("Jito bundle failed: {}", error).into());
+        }
+
+        if let Some(result) = response["result"].as_str() {
+            Ok(result.to_string())
+        } else {
+            Err("Failed to parse Jito response".into())
+        }
+    }
+
+    // Runs the bundle atomically against current state without submitting it; transactions must
+    // be base64-encoded. Unlike sendBundle this is answered by Jito's RPC rather than the block
+    // engine, so it goes to JITO_SIMULATION_URL when set
+    pub async fn simulate_bundle(&self, encoded_transactions: &[String]) -> Result<BundleSimulation, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.simulate_bundle_with_accounts(encoded_transactions, &[]).await?;
+        bundle_simulation::parse_simulate_bundle(&response)
+            .ok_or_else(|| "Failed to parse Jito simulateBundle response".into())
+    }
+
+    // simulateBundle returning, for every transaction executed, the given accounts (jsonParsed)
+    // just before and just after it runs, so each leg's effects are read against the state the
+    // previous legs left behind. No accounts asks for none
+    pub async fn simulate_bundle_with_accounts(&self, encoded_transactions: &[String], accounts: &[String]) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let account_config = if accounts.is_empty() {
+            Value::Null
+        } else {
+            json!({ "addresses": accounts, "encoding": "jsonParsed" })
+        };
+        let account_configs = vec![account_config; encoded_transactions.len()];
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "simulateBundle",
+            "params": [
+                { "encodedTransactions": encoded_transactions },
+                {
+                    "preExecutionAccountsConfigs": account_configs,
+                    "postExecutionAccountsConfigs": account_configs,
+                    "skipSigVerify": true,
+                    "replaceRecentBlockhash": true
+                }
+            ]
+        });
+
+        let url = self.simulation_url.as_deref().unwrap_or(&self.jito_rpc_url);
+        let response = self.post_to(url, &request_body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("Jito simulateBundle failed: {}", error).into());
+        }
+        Ok(response)
+    }
+
+    // Landing status of up to five bundles; ids the block engine doesn't know (yet) come back null.
+    // Held back while the block engine's Retry-After lasts, like bundles are
+    pub async fn get_bundle_statuses(&self, bundle_ids: &[String]) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let backoff = self.rate_limiter.backoff();
+        if backoff > std::time::Duration::ZERO {
+            return Err(Throttled::BlockEngineBackoff(backoff).into());
+        }
+        
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getBundleStatuses",
+            "params": [bundle_ids]
+        });
+
+        let response = self.post(&request_body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("Jito getBundleStatuses failed: {}", error).into());
+        }
+
+        Ok(response)
+    }
+
+    // Tip accounts as the block engine lists them; cheap enough to double as a connectivity probe
+    pub async fn fetch_tip_accounts(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTipAccounts",
+            "params": []
+        });
+
+        let response = self.post(&request_body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("Jito getTipAccounts failed: {}", error).into());
+        }
+
+        response["result"].as_array()
+            .map(|accounts| accounts.iter().filter_map(|account| account.as_str().map(str::to_string)).collect())
+            .ok_or_else(|| "Jito getTipAccounts returned no account list".into())
+    }
+
+    async fn post(&self, request_body: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.post_to(&self.jito_rpc_url, request_body).await
+    }
+
+    async fn post_to(&self, url: &str, request_body: &Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut request = self.client.post(url).json(request_body);
         
         // Add authentication header if available
-        if let Some(auth) = &self.auth_header {
+        if let Some(auth) = &self.auth {
+            let token = auth.access_token().await.map_err(|e| RpcError::Transport(e.to_string()))?;
+            request = request.header("Authorization", format!("Bearer {}", token));
+        } else if let Some(auth) = &self.auth_header {
             request = request.header("Authorization", auth);
         }
 
@@ -57,25 +223,40 @@ impl JitoClient {
             .header("Content-Type", "application/json")
             .timeout(std::time::Duration::from_secs(10)); // Reduce timeout to speed up failed requests
 
-        let response = request.send().await?;
+        let response = request.send().await.map_err(|e| match e.is_timeout() {
+            true => RpcError::Timeout,
+            false => RpcError::Transport(format!("Jito request failed: {}", e)),
+        })?;
         
-        // Check if response status is successful
-        if !response.status().is_success() {
-            return Err(format!("Jito bundle request failed with status: {}", response.status()).into());
+        // Rate limited: back every submission off for as long as the block engine asks
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = jito_rate_limit::parse_retry_after(
+                response.headers().get(reqwest::header::RETRY_AFTER).and_then(|value| value.to_str().ok())
+            );
+            return Err(Throttled::BlockEngineBackoff(self.rate_limiter.record_rate_limited(retry_after)).into());
         }
         
-        let response_text = response.text().await?;
-        let response: Value = serde_json::from_str(&response_text)
-            .map_err(|e| format!("Failed to parse Jito response as JSON: {}", e))?;
-
-        if let Some(error) = response.get("error") {
-            return Err(format!("Jito bundle failed: {}", error).into());
+        // An outage is worth trying elsewhere; the engine refusing the request (a bad or missing
+        // JITO_AUTH_HEADER answers 401/403) is not
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(RpcError::Transport(format!("Jito request failed with status: {}", status)).into());
+        }
+        if !status.is_success() {
+            return Err(RpcError::Rpc { code: status.as_u16() as i64, message: format!("Jito request failed with status: {}", status) }.into());
         }
+        
+        let response_text = response.text().await.map_err(|e| RpcError::Transport(format!("Jito response unreadable: {}", e)))?;
+        serde_json::from_str(&response_text)
+            .map_err(|e| RpcError::InvalidResponse(format!("Failed to parse Jito response as JSON: {}", e)).into())
+    }
 
-        if let Some(result) = response["result"].as_str() {
-            Ok(result.to_string())
-        } else {
-            Err("Failed to parse Jito response".into())
+    // Transport failures, timeouts, rate limiting and block-engine outages; a bundle the engine
+    // rejected on its merits would fare no better anywhere else
+    pub fn is_retryable_error(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+        match error.downcast_ref::<RpcError>() {
+            Some(error) => error.is_retryable(),
+            None => error.is::<Throttled>(),
         }
     }
 
@@ -89,4 +270,38 @@ impl JitoClient {
         let index = rng.gen_range(0..self.tip_accounts.len());
         &self.tip_accounts[index]
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use crate::utils::mock_http::{self, MockResponse};
+
+    #[tokio::test]
+    async fn test_dry_run_never_contacts_block_engine() {
+        let (url, connections) = mock_http::serve(|_| MockResponse::new(200, String::new())).await;
+
+        let client = JitoClient {
+            dry_run: true,
+            ..JitoClient::with_url(url)
+        };
+        let bundle = vec!["front".to_string(), "back".to_string()];
+        let bundle_id = client.send_bundle(&bundle).await.unwrap();
+
+        assert!(dry_run::is_synthetic(&bundle_id));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(connections.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_is_not_retried() {
+        let (url, _) = mock_http::serve(|_| MockResponse::new(401, String::new())).await;
+
+        let client = JitoClient::with_url(url);
+        let error = client.post(&serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "getTipAccounts", "params": [] })).await.unwrap_err();
+
+        assert!(matches!(error.downcast_ref::<RpcError>(), Some(RpcError::Rpc { code: 401, .. })));
+        assert!(!JitoClient::is_retryable_error(&*error));
+    }
+}
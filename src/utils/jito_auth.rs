@@ -0,0 +1,481 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use solana_sdk::signature::{Keypair, Signer};
+use tokio::sync::{watch, RwLock};
+use crate::config::JitoConfig;
+use crate::logging::Logger;
+
+// Refresh the access token this long before it expires
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+const MIN_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+// The block engine serves searcher auth as the gRPC service auth.AuthService
+const AUTH_SERVICE: &str = "auth.AuthService";
+const ROLE_SEARCHER: u64 = 1;
+
+// Every error out of the auth flow carries this prefix, so it never reads as a bundle rejection
+pub const AUTH_ERROR_PREFIX: &str = "Jito auth failed";
+
+fn auth_error(message: impl std::fmt::Display) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("{}: {}", AUTH_ERROR_PREFIX, message).into()
+}
+
+#[derive(Debug, Clone)]
+struct AuthToken {
+    value: String,
+    expires_at: Instant,
+}
+
+impl AuthToken {
+    // Token { string value = 1; google.protobuf.Timestamp expires_at_utc = 2; }
+    fn decode(token: &[u8]) -> Option<Self> {
+        let value = String::from_utf8(proto::bytes_field(token, 1)?.to_vec()).ok()?;
+        let expires_at_unix = proto::varint_field(proto::bytes_field(token, 2)?, 1)?;
+        let now_unix = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Some(Self {
+            value,
+            expires_at: Instant::now() + Duration::from_secs(expires_at_unix.saturating_sub(now_unix)),
+        })
+    }
+
+    fn expires_within(&self, margin: Duration) -> bool {
+        self.expires_at.saturating_duration_since(Instant::now()) <= margin
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct AuthTokens {
+    access: Option<AuthToken>,
+    refresh: Option<AuthToken>,
+}
+
+// Searcher auth against the block engine: sign the engine's challenge with the registered
+// keypair for an access/refresh token pair, then keep the access token fresh. Shared by every
+// clone of the bot's JitoClient
+pub struct JitoAuth {
+    client: reqwest::Client,
+    auth_url: String,
+    keypair: Keypair,
+    tokens: RwLock<AuthTokens>,
+}
+
+impl JitoAuth {
+    // `auth_url` is the block engine's gRPC origin, e.g. https://mainnet.block-engine.jito.wtf
+    pub fn new(keypair: Keypair, auth_url: String) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .http2_prior_knowledge()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            auth_url,
+            keypair,
+            tokens: RwLock::new(AuthTokens::default()),
+        }
+    }
+
+    // None without JITO_AUTH_KEYPAIR_PATH, leaving the client unauthenticated
+    pub fn from_config(config: &JitoConfig) -> Result<Option<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(keypair_path) = &config.auth_keypair_path else {
+            return Ok(None);
+        };
+
+        let keypair_data = std::fs::read_to_string(keypair_path)
+            .map_err(|e| auth_error(format!("could not read {}: {}", keypair_path, e)))?;
+        let keypair_bytes: Vec<u8> = serde_json::from_str(&keypair_data)
+            .map_err(|e| auth_error(format!("{} is not a JSON array of bytes: {}", keypair_path, e)))?;
+        let keypair = Keypair::from_bytes(&keypair_bytes)
+            .map_err(|e| auth_error(format!("invalid keypair in {}: {}", keypair_path, e)))?;
+
+        let auth_url = config.auth_url.clone()
+            .unwrap_or_else(|| "https://mainnet.block-engine.jito.wtf".to_string());
+
+        Ok(Some(Self::new(keypair, auth_url)))
+    }
+
+    // A session for the bot's JitoClient; start_refresh keeps it fresh. A keypair that can't be
+    // loaded is logged and leaves submissions unauthenticated
+    pub fn load(config: &JitoConfig) -> Option<Arc<JitoAuth>> {
+        match Self::from_config(config) {
+            Ok(Some(auth)) => {
+                let auth = Arc::new(auth);
+                Logger::status_update(&format!("Jito searcher auth enabled for {}", auth.keypair.pubkey()));
+                Some(auth)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                Logger::error_occurred(&format!("{}; submitting bundles unauthenticated", e));
+                None
+            }
+        }
+    }
+
+    // Current access token, authenticating or refreshing first when there's no usable one
+    pub async fn access_token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(access) = &self.tokens.read().await.access {
+            if !access.expires_within(Duration::ZERO) {
+                return Ok(access.value.clone());
+            }
+        }
+        self.refresh_if_needed().await?;
+        self.tokens.read().await.access.as_ref()
+            .map(|access| access.value.clone())
+            .ok_or_else(|| auth_error("no access token after authenticating"))
+    }
+
+    // Renews whatever is about to expire and returns how long until the next renewal is due
+    async fn refresh_if_needed(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        let tokens = self.tokens.read().await.clone();
+        let access_fresh = tokens.access.as_ref().is_some_and(|access| !access.expires_within(REFRESH_MARGIN));
+        if !access_fresh {
+            match tokens.refresh.filter(|refresh| !refresh.expires_within(Duration::ZERO)) {
+                Some(refresh) => self.refresh_access_token(&refresh.value).await?,
+                None => self.authenticate().await?,
+            }
+        }
+
+        let tokens = self.tokens.read().await;
+        let access = tokens.access.as_ref().ok_or_else(|| auth_error("no access token after authenticating"))?;
+        Ok(access.expires_at.saturating_duration_since(Instant::now()).saturating_sub(REFRESH_MARGIN).max(MIN_RETRY_BACKOFF))
+    }
+
+    // Challenge/response: the engine hands out a challenge, we sign "<pubkey>-<challenge>"
+    async fn authenticate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let pubkey = self.keypair.pubkey();
+
+        // GenerateAuthChallengeRequest { Role role = 1; bytes pubkey = 2; }
+        let mut request = Vec::new();
+        proto::put_varint_field(&mut request, 1, ROLE_SEARCHER);
+        proto::put_bytes_field(&mut request, 2, pubkey.as_ref());
+        let response = self.call("GenerateAuthChallenge", &request).await?;
+        // GenerateAuthChallengeResponse { string challenge = 1; }
+        let challenge = proto::bytes_field(&response, 1)
+            .and_then(|challenge| std::str::from_utf8(challenge).ok())
+            .ok_or_else(|| auth_error("challenge response has no challenge"))?;
+
+        let signed_message = format!("{}-{}", pubkey, challenge);
+        let signature = self.keypair.sign_message(signed_message.as_bytes());
+        // GenerateAuthTokensRequest { string challenge = 1; bytes client_pubkey = 2; bytes signed_challenge = 3; }
+        let mut request = Vec::new();
+        proto::put_bytes_field(&mut request, 1, signed_message.as_bytes());
+        proto::put_bytes_field(&mut request, 2, pubkey.as_ref());
+        proto::put_bytes_field(&mut request, 3, signature.as_ref());
+        let response = self.call("GenerateAuthTokens", &request).await?;
+
+        // GenerateAuthTokensResponse { Token access_token = 1; Token refresh_token = 2; }
+        let access = proto::bytes_field(&response, 1).and_then(AuthToken::decode)
+            .ok_or_else(|| auth_error("token response has no access token"))?;
+        let refresh = proto::bytes_field(&response, 2).and_then(AuthToken::decode)
+            .ok_or_else(|| auth_error("token response has no refresh token"))?;
+        *self.tokens.write().await = AuthTokens { access: Some(access), refresh: Some(refresh) };
+        Ok(())
+    }
+
+    async fn refresh_access_token(&self, refresh_token: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // RefreshAccessTokenRequest { string refresh_token = 1; } -> { Token access_token = 1; }
+        let mut request = Vec::new();
+        proto::put_bytes_field(&mut request, 1, refresh_token.as_bytes());
+        let response = self.call("RefreshAccessToken", &request).await?;
+        let access = proto::bytes_field(&response, 1).and_then(AuthToken::decode)
+            .ok_or_else(|| auth_error("refresh response has no access token"))?;
+        self.tokens.write().await.access = Some(access);
+        Ok(())
+    }
+
+    // One unary gRPC call: the message goes out length-prefixed over HTTP/2 and the reply's
+    // message comes back the same way. A call the service refused carries a nonzero grpc-status
+    async fn call(&self, method: &str, message: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/{}/{}", self.auth_url.trim_end_matches('/'), AUTH_SERVICE, method);
+        let response = self.client.post(url)
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(grpc_frame(message))
+            .send()
+            .await
+            .map_err(|e| auth_error(format!("{} request failed: {}", method, e)))?;
+        if !response.status().is_success() {
+            return Err(auth_error(format!("{} returned status {}", method, response.status())));
+        }
+        if let Some(status) = response.headers().get("grpc-status").and_then(|status| status.to_str().ok()) {
+            if status != "0" {
+                let message = response.headers().get("grpc-message").and_then(|message| message.to_str().ok()).unwrap_or("");
+                return Err(auth_error(format!("{} returned grpc-status {} {}", method, status, message)));
+            }
+        }
+        let body = response.bytes().await.map_err(|e| auth_error(format!("{} response unreadable: {}", method, e)))?;
+        grpc_message(&body).map(<[u8]>::to_vec).ok_or_else(|| auth_error(format!("{} returned no message", method)))
+    }
+
+    // Keeps the access token ahead of expiry; failures back off on their own schedule without
+    // holding up submissions, which authenticate inline if the token lapses anyway. Runs until
+    // `shutdown` flips to true
+    pub fn start_refresh(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let auth = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut backoff = MIN_RETRY_BACKOFF;
+            loop {
+                let wait = match auth.refresh_if_needed().await {
+                    Ok(next_refresh) => {
+                        backoff = MIN_RETRY_BACKOFF;
+                        next_refresh
+                    }
+                    Err(e) => {
+                        Logger::error_occurred(&format!("{} (retrying in {}s)", e, backoff.as_secs()));
+                        let wait = backoff;
+                        backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                        wait
+                    }
+                };
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {},
+                    _ = shutdown.changed() => return,
+                }
+                if *shutdown.borrow() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+// Uncompressed flag, big-endian length, message
+fn grpc_frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(5 + message.len());
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+fn grpc_message(body: &[u8]) -> Option<&[u8]> {
+    let (header, rest) = (body.get(..5)?, body.get(5..)?);
+    if header[0] != 0 {
+        return None; // Compressed, which we never ask for
+    }
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    rest.get(..len)
+}
+
+// The few protobuf wire-format pieces the auth messages need
+mod proto {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    pub fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    pub fn put_varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+        put_varint(out, (field << 3) | VARINT);
+        put_varint(out, value);
+    }
+
+    pub fn put_bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        put_varint(out, (field << 3) | LENGTH_DELIMITED);
+        put_varint(out, bytes.len() as u64);
+        out.extend_from_slice(bytes);
+    }
+
+    fn varint(input: &[u8]) -> Option<(u64, &[u8])> {
+        let mut value = 0u64;
+        for (index, byte) in input.iter().enumerate().take(10) {
+            value |= u64::from(byte & 0x7f) << (7 * index);
+            if byte & 0x80 == 0 {
+                return Some((value, &input[index + 1..]));
+            }
+        }
+        None
+    }
+
+    enum Value<'a> {
+        Varint(u64),
+        Bytes(&'a [u8]),
+    }
+
+    // The first occurrence of `field`; None when it's absent or the message is malformed
+    fn field(mut message: &[u8], wanted: u64) -> Option<Value<'_>> {
+        while !message.is_empty() {
+            let (key, rest) = varint(message)?;
+            let value = match key & 7 {
+                VARINT => {
+                    let (value, rest) = varint(rest)?;
+                    message = rest;
+                    Value::Varint(value)
+                }
+                LENGTH_DELIMITED => {
+                    let (len, rest) = varint(rest)?;
+                    let len = usize::try_from(len).ok()?;
+                    message = rest.get(len..)?;
+                    Value::Bytes(rest.get(..len)?)
+                }
+                1 => { message = rest.get(8..)?; continue; } // 64-bit
+                5 => { message = rest.get(4..)?; continue; } // 32-bit
+                _ => return None,
+            };
+            if key >> 3 == wanted {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    pub fn varint_field(message: &[u8], wanted: u64) -> Option<u64> {
+        match field(message, wanted)? {
+            Value::Varint(value) => Some(value),
+            Value::Bytes(_) => None,
+        }
+    }
+
+    pub fn bytes_field(message: &[u8], wanted: u64) -> Option<&[u8]> {
+        match field(message, wanted)? {
+            Value::Bytes(bytes) => Some(bytes),
+            Value::Varint(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Signature;
+
+    fn unix_in(secs: u64) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + secs
+    }
+
+    fn token(value: &str, expires_at_unix: u64) -> Vec<u8> {
+        let mut timestamp = Vec::new();
+        proto::put_varint_field(&mut timestamp, 1, expires_at_unix);
+        let mut token = Vec::new();
+        proto::put_bytes_field(&mut token, 1, value.as_bytes());
+        proto::put_bytes_field(&mut token, 2, &timestamp);
+        token
+    }
+
+    // GenerateAuthTokens only answers a correctly signed challenge
+    fn answer(method: &str, request: &[u8], access_lifetime_secs: u64) -> Option<Vec<u8>> {
+        let mut response = Vec::new();
+        match method {
+            "GenerateAuthChallenge" => {
+                if proto::varint_field(request, 1) != Some(ROLE_SEARCHER) || proto::bytes_field(request, 2)?.len() != 32 {
+                    return None;
+                }
+                proto::put_bytes_field(&mut response, 1, b"abc123");
+            }
+            "GenerateAuthTokens" => {
+                let message = std::str::from_utf8(proto::bytes_field(request, 1)?).ok()?;
+                let pubkey = Pubkey::try_from(proto::bytes_field(request, 2)?).ok()?;
+                let signature = Signature::try_from(proto::bytes_field(request, 3)?).ok()?;
+                if message != format!("{}-abc123", pubkey) || !signature.verify(pubkey.as_ref(), message.as_bytes()) {
+                    return None;
+                }
+                proto::put_bytes_field(&mut response, 1, &token("access-1", unix_in(access_lifetime_secs)));
+                proto::put_bytes_field(&mut response, 2, &token("refresh-1", unix_in(86_400)));
+            }
+            "RefreshAccessToken" => {
+                if proto::bytes_field(request, 1)? != b"refresh-1" {
+                    return None;
+                }
+                proto::put_bytes_field(&mut response, 1, &token("access-2", unix_in(1800)));
+            }
+            _ => return None,
+        }
+        Some(response)
+    }
+
+    // gRPC auth service over HTTP/2 counting calls per method: challenge, tokens, refresh
+    async fn mock_auth_service(access_lifetime_secs: u64) -> (String, Arc<[AtomicUsize; 3]>) {
+        let calls = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)]);
+        let counters = Arc::clone(&calls);
+        let make_service = make_service_fn(move |_| {
+            let counters = Arc::clone(&counters);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let counters = Arc::clone(&counters);
+                    async move {
+                        let method = request.uri().path().rsplit('/').next().unwrap_or("").to_string();
+                        let grpc = request.headers().get("content-type").is_some_and(|value| value == "application/grpc");
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        let index = ["GenerateAuthChallenge", "GenerateAuthTokens", "RefreshAccessToken"].iter().position(|name| *name == method);
+                        if let Some(index) = index {
+                            counters[index].fetch_add(1, Ordering::SeqCst);
+                        }
+                        let reply = grpc_message(&body).filter(|_| grpc).and_then(|message| answer(&method, message, access_lifetime_secs));
+                        let response = match reply {
+                            Some(reply) => Response::builder().header("grpc-status", "0").body(Body::from(grpc_frame(&reply))),
+                            None => Response::builder().header("grpc-status", "16").header("grpc-message", "unauthenticated").body(Body::empty()),
+                        };
+                        Ok::<_, Infallible>(response.unwrap())
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).http2_only(true).serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        (url, calls)
+    }
+
+    #[tokio::test]
+    async fn test_challenge_response_yields_reusable_token() {
+        let (url, calls) = mock_auth_service(1800).await;
+        let auth = JitoAuth::new(Keypair::new(), url);
+
+        assert_eq!(auth.access_token().await.unwrap(), "access-1");
+        assert_eq!(auth.access_token().await.unwrap(), "access-1");
+        assert_eq!(calls[0].load(Ordering::SeqCst), 1);
+        assert_eq!(calls[1].load(Ordering::SeqCst), 1);
+        assert_eq!(calls[2].load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expiring_access_token_is_refreshed_not_reauthenticated() {
+        // Access token inside the refresh margin from the start
+        let (url, calls) = mock_auth_service(30).await;
+        let auth = JitoAuth::new(Keypair::new(), url);
+        auth.access_token().await.unwrap();
+
+        let next_refresh = auth.refresh_if_needed().await.unwrap();
+        assert_eq!(auth.access_token().await.unwrap(), "access-2");
+        assert!(next_refresh > Duration::from_secs(1000));
+        assert_eq!(calls[1].load(Ordering::SeqCst), 1);
+        assert_eq!(calls[2].load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auth_errors_are_labelled() {
+        let auth = JitoAuth::new(Keypair::new(), "http://127.0.0.1:9".to_string());
+        let error = auth.access_token().await.unwrap_err().to_string();
+        assert!(error.starts_with(AUTH_ERROR_PREFIX), "{}", error);
+
+        // Refused by the service rather than unreachable
+        let (url, _) = mock_auth_service(1800).await;
+        let auth = JitoAuth::new(Keypair::new(), url);
+        *auth.tokens.write().await = AuthTokens { access: None, refresh: Some(AuthToken { value: "stale".to_string(), expires_at: Instant::now() + Duration::from_secs(60) }) };
+        let error = auth.access_token().await.unwrap_err().to_string();
+        assert!(error.starts_with(AUTH_ERROR_PREFIX) && error.contains("grpc-status 16"), "{}", error);
+    }
+
+    #[test]
+    fn test_protobuf_fields_round_trip() {
+        let mut message = Vec::new();
+        proto::put_varint_field(&mut message, 1, 300);
+        proto::put_bytes_field(&mut message, 3, b"hello");
+        assert_eq!(proto::varint_field(&message, 1), Some(300));
+        assert_eq!(proto::bytes_field(&message, 3), Some(&b"hello"[..]));
+        assert_eq!(proto::bytes_field(&message, 2), None);
+        assert_eq!(proto::bytes_field(&message, 1), None);
+        assert_eq!(grpc_message(&grpc_frame(&message)), Some(&message[..]));
+        assert_eq!(grpc_message(&[0, 0, 0, 0, 9, 1]), None);
+    }
+}
@@ -2,11 +2,17 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use serde_json::{json, Value};
 use solana_sdk::pubkey::Pubkey;
+use crate::config::Config;
 use crate::logging::Logger;
+use crate::utils::jito;
 use crate::rpc::rpc_manager::{RpcManager, RpcEndpointType};
+use crate::rpc::types::{self, LatestBlockhash, RpcResponse};
+use crate::utils::leader_schedule::{LeaderLandingRate, LeaderScheduleTracker, LeaderType, LeaderWindow, SLOT_MS};
+use crate::utils::tip_floor::{self, TipFloorCache, TipPercentile};
+use crate::utils::tip_history::{self, TipBucketRate, TipHistory};
 
 #[derive(Debug, Clone)]
 pub struct JitoHealthStatus {
@@ -23,6 +29,7 @@ pub struct TipOptimizationResult {
     pub recommended_tip_account: String,
     pub confidence: f64,
     pub expected_success_rate: f64,
+    pub percentile: Option<TipPercentile>, // None when the tip came from the heuristic
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +37,8 @@ pub struct BundleTimingStrategy {
     pub delay_micros: u64,
     pub retry_count: u8,
     pub propagation_wait_ms: u64,
+    pub leader: LeaderType, // Leader of the slot the bundle is aimed at
+    pub prefer_rpc: bool,   // No Jito leader within the wait; a bundle sent now would be dropped
 }
 
 pub struct JitoOptimizer {
@@ -38,14 +47,23 @@ pub struct JitoOptimizer {
     tip_accounts: Vec<Pubkey>,
     current_tip: f64,
     health_check_interval: Duration,
-    tip_adjustment_history: Arc<RwLock<Vec<(Instant, f64, bool)>>>, // (time, tip_amount, success)
+    tip_history: Arc<RwLock<TipHistory>>,
+    tip_history_file: String,
+    tip_history_flush_interval: Duration,
+    tip_floor: Arc<TipFloorCache>,
+    tip_percentile: TipPercentile,
+    max_tip_profit_share: f64,
+    target_landing_rate: f64,
+    leader_schedule: Arc<LeaderScheduleTracker>,
+    leader_max_wait: Duration,
 }
 
 impl JitoOptimizer {
-    pub async fn new(rpc_manager: Arc<RpcManager>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        // Load tip accounts from environment variable
-        let tip_accounts_str = std::env::var("JITO_TIP_ACCOUNT")
-            .map_err(|_| "JITO_TIP_ACCOUNT environment variable not set")?;
+    pub async fn new(rpc_manager: Arc<RpcManager>, config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // JITO_TIP_ACCOUNT; without USE_JITO it may be unset, and tips are still priced against the
+        // official accounts
+        let tip_accounts_str = config.jito.tip_account.clone()
+            .unwrap_or_else(|| jito::TIP_ACCOUNTS.join(","));
         
         let tip_accounts: Vec<Pubkey> = tip_accounts_str
             .split(',')
@@ -56,8 +74,18 @@ impl JitoOptimizer {
             return Err("No valid Jito tip accounts provided in JITO_TIP_ACCOUNT".into());
         }
         
+        let tip_percentile = TipPercentile::parse(&config.jito.tip_percentile.to_string())
+            .ok_or_else(|| format!("Invalid JITO_TIP_PERCENTILE: {} (expected 25, 50, 75 or 95)", config.jito.tip_percentile))?;
+        
+        let tip_floor = Arc::new(TipFloorCache::from_config(&config.jito));
+        let leader_schedule = Arc::new(LeaderScheduleTracker::from_config(Arc::clone(&rpc_manager), &config.jito));
+        
+        // Outcomes from previous runs, so calibration doesn't start from scratch after a restart
+        let tip_history_file = config.jito.tip_history_file.clone();
+        let tip_history = TipHistory::load(&tip_history_file, Duration::from_secs_f64(config.jito.tip_history_half_life_hours.max(0.0) * 3600.0));
+        
         let optimizer = Self {
-            rpc_manager: Arc::new(rpc_manager),
+            rpc_manager,
             health_status: Arc::new(RwLock::new(JitoHealthStatus {
                 is_healthy: false,
                 latency_ms: 0.0,
@@ -68,15 +96,30 @@ impl JitoOptimizer {
             tip_accounts,
             current_tip: 0.001, // Start with 0.001 SOL default tip
             health_check_interval: Duration::from_secs(15), // Check every 15 seconds
-            tip_adjustment_history: Arc::new(RwLock::new(Vec::new())),
+            tip_history: Arc::new(RwLock::new(tip_history)),
+            tip_history_file,
+            tip_history_flush_interval: Duration::from_secs(config.jito.tip_history_flush_secs.max(1)),
+            tip_floor,
+            tip_percentile,
+            max_tip_profit_share: config.bundles.max_tip_share, // Same cap the bundle retries escalate up to
+            target_landing_rate: config.jito.tip_target_landing_rate,
+            leader_schedule,
+            // How long a bundle may be held back waiting for a Jito leader before it's too stale to send
+            leader_max_wait: Duration::from_millis(config.jito.leader_max_wait_ms),
         };
         
-        // Start health checks
-        optimizer.start_health_checks().await;
-        
         Ok(optimizer)
     }
     
+    // Tip floor and leader schedule polling, health checks and the tip history flush, all running
+    // until `shutdown` flips to true
+    pub fn start_background_tasks(&self, shutdown: watch::Receiver<bool>) {
+        self.tip_floor.start_polling(shutdown.clone());
+        self.leader_schedule.start_polling(shutdown.clone());
+        self.start_health_checks(shutdown.clone());
+        self.start_tip_history_flush(shutdown);
+    }
+    
     pub async fn check_jito_health(&self) -> Result<JitoHealthStatus, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = Instant::now();
         
@@ -88,10 +131,8 @@ impl JitoOptimizer {
             "params": []
         });
         
-        let success = match self.rpc_manager.make_request(RpcEndpointType::Jito, test_request).await {
-            Ok(response) => {
-                response["result"]["value"]["blockhash"].as_str().is_some()
-            },
+        let success = match self.rpc_manager.make_request_to(RpcEndpointType::Jito, test_request).await {
+            Ok(response) => types::parse_result::<RpcResponse<LatestBlockhash>>("getLatestBlockhash", &response).is_ok(),
             Err(_) => false,
         };
         
@@ -114,7 +155,7 @@ impl JitoOptimizer {
         Ok(health_status)
     }
     
-    async fn start_health_checks(&self) {
+    fn start_health_checks(&self, mut shutdown: watch::Receiver<bool>) {
         let self_clone = self.clone_for_spawn();
         
         tokio::spawn(async move {
@@ -133,7 +174,10 @@ impl JitoOptimizer {
                     }
                 }
                 
-                tokio::time::sleep(self_clone.health_check_interval).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(self_clone.health_check_interval) => {},
+                    _ = shutdown.changed() => return,
+                }
             }
         });
     }
@@ -146,20 +190,16 @@ impl JitoOptimizer {
     ) -> Result<TipOptimizationResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Calculating optimal Jito tip based on opportunity value and network conditions");
         
-        // Calculate base tip based on opportunity value
-        let base_tip = self.calculate_base_tip(opportunity_value).await;
-        
-        // Adjust for network congestion
-        let congestion_adjustment = 1.0 + (network_congestion * 0.5); // Up to 50% increase for high congestion
-        
-        // Adjust for competition level
-        let competition_adjustment = 1.0 + (competition_level * 0.8); // Up to 80% increase for high competition
-        
-        // Calculate final tip
-        let final_tip = base_tip * congestion_adjustment * competition_adjustment;
-        
-        // Ensure tip is within reasonable bounds
-        let optimal_tip = final_tip.clamp(0.0001, 0.01); // Between 0.0001 and 0.01 SOL
+        // Tip at what recently landed when the tip floor is available; our own congestion and
+        // competition guesses are only the fallback
+        let (optimal_tip, percentile) = match self.tip_floor.current().await {
+            Some(floor) => {
+                let percentile = self.calibrated_percentile().await;
+                let tip = tip_floor::bounded_tip(&floor, percentile, opportunity_value, self.max_tip_profit_share);
+                (tip, Some(percentile))
+            }
+            None => (self.heuristic_tip(opportunity_value, network_congestion, competition_level).await, None),
+        };
         
         // Select the best tip account based on load balancing
         let recommended_tip_account = self.select_best_tip_account().await;
@@ -175,11 +215,13 @@ impl JitoOptimizer {
             recommended_tip_account,
             confidence,
             expected_success_rate,
+            percentile,
         };
         
         Logger::status_update(&format!(
-            "Optimal tip: {:.6} SOL, success_rate: {:.1}%, confidence: {:.1}%", 
+            "Optimal tip: {:.6} SOL ({}), success_rate: {:.1}%, confidence: {:.1}%", 
             result.optimal_tip, 
+            result.percentile.map_or("heuristic", |p| p.label()),
             result.expected_success_rate * 100.0, 
             result.confidence * 100.0
         ));
@@ -187,6 +229,29 @@ impl JitoOptimizer {
         Ok(result)
     }
     
+    async fn heuristic_tip(&self, opportunity_value: f64, network_congestion: f64, competition_level: f64) -> f64 {
+        // Calculate base tip based on opportunity value
+        let base_tip = self.calculate_base_tip(opportunity_value).await;
+        
+        // Adjust for network congestion
+        let congestion_adjustment = 1.0 + (network_congestion * 0.5); // Up to 50% increase for high congestion
+        
+        // Adjust for competition level
+        let competition_adjustment = 1.0 + (competition_level * 0.8); // Up to 80% increase for high competition
+        
+        // Calculate final tip
+        let final_tip = base_tip * congestion_adjustment * competition_adjustment;
+        
+        // Ensure tip is within reasonable bounds
+        final_tip.clamp(0.0001, 0.01) // Between 0.0001 and 0.01 SOL
+    }
+    
+    // The configured percentile, moved to whichever one our landing outcomes show is enough
+    async fn calibrated_percentile(&self) -> TipPercentile {
+        let outcomes = self.tip_history.read().await.percentile_outcomes_at(tip_history::now_unix());
+        tip_floor::calibrated_percentile(&outcomes, self.tip_percentile, self.target_landing_rate)
+    }
+    
     async fn calculate_base_tip(&self, opportunity_value: f64) -> f64 {
         // Calculate base tip based on opportunity value
         // Higher value opportunities get higher tips to ensure inclusion
@@ -257,13 +322,33 @@ impl JitoOptimizer {
             NetworkSpeed::Slow => 400,
         };
         
+        // Bundles only land with a Jito leader: send straight away while one is producing, hold the
+        // bundle until just before the next one takes over, and give up on Jito if none is due in time
+        let window = self.leader_schedule.window(self.leader_max_wait).await;
+        let delay_micros = match window {
+            LeaderWindow::JitoNow | LeaderWindow::NoJitoWithin => 0,
+            LeaderWindow::JitoIn(slots) => (slots * SLOT_MS).saturating_sub(propagation_wait_ms) * 1_000,
+            LeaderWindow::Unknown => delay_micros,
+        };
+        
         BundleTimingStrategy {
             delay_micros,
             retry_count,
             propagation_wait_ms,
+            leader: window.leader_type(),
+            prefer_rpc: window == LeaderWindow::NoJitoWithin,
         }
     }
     
+    // Bundle outcome by the leader it was aimed at, for the per-leader landing rate
+    pub async fn record_leader_outcome(&self, leader: LeaderType, landed: bool) {
+        self.leader_schedule.record_outcome(leader, landed).await;
+    }
+    
+    pub async fn leader_landing_rates(&self) -> Vec<LeaderLandingRate> {
+        self.leader_schedule.landing_rates().await
+    }
+    
     async fn assess_network_speed(&self) -> NetworkSpeed {
         // Assess network speed based on recent health checks
         let health = self.health_status.read().await;
@@ -313,32 +398,46 @@ impl JitoOptimizer {
         health.is_healthy
     }
     
-    pub async fn record_tip_result(&self, tip_amount: f64, success: bool) {
+    // The percentile the tip was picked at, if any, lets calibration learn which one lands
+    pub async fn record_tip_result(&self, tip_amount: f64, percentile: Option<TipPercentile>, success: bool) {
         // Record the result of a tip for historical analysis
-        let mut history = self.tip_adjustment_history.write().await;
-        history.push((Instant::now(), tip_amount, success));
+        self.tip_history.write().await.record(tip_amount, percentile, success);
+    }
+    
+    // Decay-weighted landing rate per tip size bucket, for metrics
+    pub async fn tip_bucket_rates(&self) -> Vec<TipBucketRate> {
+        self.tip_history.read().await.bucket_rates_at(tip_history::now_unix())
+    }
+    
+    pub async fn flush_tip_history(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let history = self.tip_history.read().await.clone();
+        history.save(&self.tip_history_file)
+    }
+    
+    fn start_tip_history_flush(&self, mut shutdown: watch::Receiver<bool>) {
+        let self_clone = self.clone_for_spawn();
         
-        // Keep only recent history (last 100 entries)
-        if history.len() > 100 {
-            let to_remove = history.len() - 100;
-            history.drain(0..to_remove);
-        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self_clone.tip_history_flush_interval);
+            ticker.tick().await; // The first tick fires immediately; nothing new to save yet
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if let Err(e) = self_clone.flush_tip_history().await {
+                    Logger::error_occurred(&format!("Failed to flush tip history: {}", e));
+                }
+            }
+        });
     }
     
     pub async fn adjust_tip_based_on_history(&mut self) {
         // Adjust current tip based on historical success/failure patterns
-        let history = self.tip_adjustment_history.read().await;
-        
-        if history.is_empty() {
-            return;
-        }
-        
-        // Calculate recent success rate
-        let recent_successes: usize = history.iter()
-            .filter(|(_, _, success)| *success)
-            .count();
-        
-        let success_rate = recent_successes as f64 / history.len() as f64;
+        let success_rate = match self.tip_history.read().await.success_rate_at(tip_history::now_unix()) {
+            Some(rate) => rate,
+            None => return,
+        };
         
         // Adjust tip based on success rate
         if success_rate < 0.7 { // Low success rate, increase tip
@@ -352,12 +451,13 @@ impl JitoOptimizer {
         &self,
         transactions: Vec<String>,
         tip_amount: f64,
-        tip_account: &str
+        tip_account: &str,
+        recent_blockhash: &str
     ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Preparing bundle with tip transaction for Jito submission");
         
         // Create the tip transaction
-        let tip_tx = self.create_tip_transaction(tip_amount, tip_account).await?;
+        let tip_tx = self.create_tip_transaction(tip_amount, tip_account, recent_blockhash).await?;
         
         // Combine the original transactions with the tip transaction
         let mut bundle_transactions = transactions;
@@ -371,7 +471,7 @@ impl JitoOptimizer {
         Ok(bundle_transactions)
     }
     
-    async fn create_tip_transaction(&self, tip_amount: f64, tip_account: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    async fn create_tip_transaction(&self, tip_amount: f64, tip_account: &str, recent_blockhash: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         // In a real implementation, this would create an actual tip transaction using Solana SDK
         // For now, return a placeholder transaction
         
@@ -380,10 +480,10 @@ impl JitoOptimizer {
         
         // This would be implemented using Solana SDK to create:
         // 1. A transfer from the bot's wallet to the tip account
-        // 2. Properly signed and serialized
+        // 2. Properly signed and serialized against recent_blockhash
         
         // Placeholder implementation
-        Ok(format!("tip_transaction_{}_to_{}", tip_lamports, tip_account))
+        Ok(format!("tip_transaction_{}_to_{}_at_{}", tip_lamports, tip_account, recent_blockhash))
     }
     
     // Method to implement micro-delay strategies
@@ -414,7 +514,15 @@ impl JitoOptimizer {
             tip_accounts: self.tip_accounts.clone(),
             current_tip: self.current_tip,
             health_check_interval: self.health_check_interval,
-            tip_adjustment_history: Arc::clone(&self.tip_adjustment_history),
+            tip_history: Arc::clone(&self.tip_history),
+            tip_history_file: self.tip_history_file.clone(),
+            tip_history_flush_interval: self.tip_history_flush_interval,
+            tip_floor: Arc::clone(&self.tip_floor),
+            tip_percentile: self.tip_percentile,
+            max_tip_profit_share: self.max_tip_profit_share,
+            target_landing_rate: self.target_landing_rate,
+            leader_schedule: Arc::clone(&self.leader_schedule),
+            leader_max_wait: self.leader_max_wait,
         }
     }
 }
@@ -434,7 +542,15 @@ impl Clone for JitoOptimizer {
             tip_accounts: self.tip_accounts.clone(),
             current_tip: self.current_tip,
             health_check_interval: self.health_check_interval,
-            tip_adjustment_history: Arc::clone(&self.tip_adjustment_history),
+            tip_history: Arc::clone(&self.tip_history),
+            tip_history_file: self.tip_history_file.clone(),
+            tip_history_flush_interval: self.tip_history_flush_interval,
+            tip_floor: Arc::clone(&self.tip_floor),
+            tip_percentile: self.tip_percentile,
+            max_tip_profit_share: self.max_tip_profit_share,
+            target_landing_rate: self.target_landing_rate,
+            leader_schedule: Arc::clone(&self.leader_schedule),
+            leader_max_wait: self.leader_max_wait,
         }
     }
 }
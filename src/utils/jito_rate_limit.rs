@@ -0,0 +1,206 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::config::JitoConfig;
+use crate::utils::token_bucket::TokenBucket;
+
+// Every throttling error carries this prefix; throttled bundles were never judged by the engine
+pub const THROTTLED_ERROR_PREFIX: &str = "Jito throttled";
+
+// Backoff after a 429 that didn't say how long to wait
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Throttled {
+    QueueFull,                    // Too many bundles already waiting for a slot
+    TooLate(Duration),            // The slot would come after the bundle is stale
+    BlockEngineBackoff(Duration), // Still inside a Retry-After from the block engine
+}
+
+impl std::fmt::Display for Throttled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Throttled::QueueFull => write!(f, "{}: submission queue full", THROTTLED_ERROR_PREFIX),
+            Throttled::TooLate(wait) => write!(f, "{}: next slot in {}ms, bundle would be stale", THROTTLED_ERROR_PREFIX, wait.as_millis()),
+            Throttled::BlockEngineBackoff(wait) => write!(f, "{}: backing off for {}ms after 429", THROTTLED_ERROR_PREFIX, wait.as_millis()),
+        }
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+struct LimiterState {
+    bucket: TokenBucket,
+    blocked_until: Option<Instant>, // Set by a 429's Retry-After
+    queued: usize,
+}
+
+// Keeps sendBundle within the block engine's per-IP budget. Bundles over the rate wait in line
+// for their slot; ones that would wait past max_wait, or find the line full, are turned away
+// rather than sent stale. Shared by every clone of the bot's JitoClient
+pub struct BundleRateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+    max_queued: usize,
+    max_wait: Duration,
+    throttled: AtomicU64,
+}
+
+// A bundle's place in line: wait out the delay, then send. Leaves the line when dropped
+pub struct Admission {
+    pub delay: Duration,
+    state: Option<Arc<Mutex<LimiterState>>>,
+}
+
+impl Admission {
+    pub async fn wait(self) {
+        if self.delay > Duration::ZERO {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        if let Some(state) = &self.state {
+            if let Ok(mut state) = state.lock() {
+                state.queued = state.queued.saturating_sub(1);
+            }
+        }
+    }
+}
+
+impl BundleRateLimiter {
+    pub fn new(bundles_per_second: f64, max_queued: usize, max_wait: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LimiterState {
+                bucket: TokenBucket::new(bundles_per_second, bundles_per_second.ceil(), Instant::now()),
+                blocked_until: None,
+                queued: 0,
+            })),
+            max_queued,
+            max_wait,
+            throttled: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_config(config: &JitoConfig) -> Self {
+        Self::new(config.max_bundles_per_second, config.max_queued_bundles, Duration::from_millis(config.max_queue_wait_ms))
+    }
+
+    pub fn admit(&self) -> Result<Admission, Throttled> {
+        self.admit_at(Instant::now())
+    }
+
+    pub fn admit_at(&self, now: Instant) -> Result<Admission, Throttled> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let backoff = state.blocked_until.map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+        let rejection = if backoff > self.max_wait {
+            Some(Throttled::BlockEngineBackoff(backoff))
+        } else {
+            let delay = state.bucket.wait_at(now).max(backoff);
+            if delay > self.max_wait {
+                Some(Throttled::TooLate(delay))
+            } else if delay > Duration::ZERO && state.queued >= self.max_queued {
+                Some(Throttled::QueueFull)
+            } else {
+                None
+            }
+        };
+        if let Some(rejection) = rejection {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            return Err(rejection);
+        }
+
+        let delay = state.bucket.reserve_at(now).max(backoff);
+        if delay == Duration::ZERO {
+            return Ok(Admission { delay, state: None });
+        }
+        state.queued += 1;
+        Ok(Admission { delay, state: Some(Arc::clone(&self.state)) })
+    }
+
+    // The block engine answered 429: hold everything back for its Retry-After
+    // Returns how long submissions are now held back for
+    pub fn record_rate_limited(&self, retry_after: Option<Duration>) -> Duration {
+        self.record_rate_limited_at(retry_after, Instant::now())
+    }
+
+    pub fn record_rate_limited_at(&self, retry_after: Option<Duration>, now: Instant) -> Duration {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let until = now + retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+        let until = state.blocked_until.map_or(until, |blocked| blocked.max(until));
+        state.blocked_until = Some(until);
+        until.saturating_duration_since(now)
+    }
+
+    // What's left of the block engine's last Retry-After
+    pub fn backoff(&self) -> Duration {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.blocked_until.map_or(Duration::ZERO, |until| until.saturating_duration_since(Instant::now()))
+    }
+
+    // Submissions turned away locally or by a 429, since startup
+    pub fn throttled_count(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+// Skip reason for a submission the limiter or the block engine's 429 held back, so the strategy
+// isn't charged with a failure for it
+pub fn throttle_skip_reason(error: &str) -> Option<&'static str> {
+    error.starts_with(THROTTLED_ERROR_PREFIX).then_some("jito_throttled")
+}
+
+// Retry-After in its delay-seconds form; HTTP dates fall back to the default backoff
+pub fn parse_retry_after(value: Option<&str>) -> Option<Duration> {
+    value?.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_beyond_rate_queues_then_rejects() {
+        let limiter = BundleRateLimiter::new(2.0, 1, Duration::from_millis(1200));
+        let start = Instant::now();
+
+        let first = limiter.admit_at(start).unwrap();
+        let second = limiter.admit_at(start).unwrap();
+        assert_eq!((first.delay, second.delay), (Duration::ZERO, Duration::ZERO));
+
+        // Third waits half a second in the one queue slot; the fourth finds the queue full
+        let third = limiter.admit_at(start).unwrap();
+        assert_eq!(third.delay, Duration::from_millis(500));
+        assert_eq!(limiter.admit_at(start).err(), Some(Throttled::QueueFull));
+
+        // Once the third is sent the next one can queue, but the one after would go out stale
+        drop(third);
+        let fifth = limiter.admit_at(start).unwrap();
+        assert_eq!(fifth.delay, Duration::from_millis(1000));
+        assert_eq!(limiter.admit_at(start).err(), Some(Throttled::TooLate(Duration::from_millis(1500))));
+        assert_eq!(limiter.throttled_count(), 2);
+
+        // Later on the rate allows another bundle straight away
+        drop(fifth);
+        assert_eq!(limiter.admit_at(start + Duration::from_millis(1500)).unwrap().delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_429_holds_submissions_for_retry_after() {
+        let limiter = BundleRateLimiter::new(10.0, 10, Duration::from_millis(400));
+        let start = Instant::now();
+
+        limiter.record_rate_limited_at(parse_retry_after(Some("2")), start);
+        assert_eq!(limiter.admit_at(start).err(), Some(Throttled::BlockEngineBackoff(Duration::from_secs(2))));
+
+        // Near the end of the backoff bundles queue for it instead
+        let admission = limiter.admit_at(start + Duration::from_millis(1800)).unwrap();
+        assert_eq!(admission.delay, Duration::from_millis(200));
+        assert_eq!(limiter.admit_at(start + Duration::from_secs(3)).unwrap().delay, Duration::ZERO);
+
+        assert_eq!(parse_retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT")), None);
+        assert_eq!(limiter.throttled_count(), 2);
+    }
+}
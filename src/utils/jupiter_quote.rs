@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use crate::config::PricesConfig;
+use crate::logging::Logger;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JupiterQuote {
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub other_amount_threshold: u64, // Minimum output after slippage
+    pub price_impact: f64,           // Fraction (0.01 = 1%)
+    pub slippage_bps: u16,
+    pub route: Vec<String>,          // AMM labels of each hop, e.g. ["Raydium", "Orca"]
+}
+
+type QuoteKey = (String, String, u64);
+// A `None` quote is cached too, so a pair without a route isn't asked for again right away
+type QuoteCache = HashMap<QuoteKey, (Instant, Option<JupiterQuote>)>;
+
+// Client for the Jupiter v6 /quote endpoint. Identical quotes are served from a short-lived
+// cache, and any failure (timeout, rate limit, no route) is reported as no quote
+pub struct JupiterQuoteClient {
+    client: reqwest::Client,
+    api_url: String,
+    slippage_bps: u16,
+    cache_ttl: Duration,
+    cache: Arc<Mutex<QuoteCache>>,
+}
+
+impl JupiterQuoteClient {
+    pub fn new(api_url: &str, timeout: Duration, slippage_bps: u16, cache_ttl: Duration) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to build Jupiter HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            slippage_bps,
+            cache_ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn from_config(config: &PricesConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new(&config.jupiter_api_url, Duration::from_millis(config.jupiter_timeout_ms), config.jupiter_slippage_bps, Duration::from_millis(500))
+    }
+
+    pub async fn quote(&self, input_mint: &str, output_mint: &str, amount: u64) -> Option<JupiterQuote> {
+        let key = (input_mint.to_string(), output_mint.to_string(), amount);
+
+        if let Some((fetched_at, quote)) = self.cache.lock().await.get(&key) {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return quote.clone();
+            }
+        }
+
+        let quote = match self.fetch_quote(input_mint, output_mint, amount).await {
+            Ok(quote) => quote,
+            Err(e) => {
+                // Transient failures aren't cached so the next request retries
+                Logger::status_update(&format!("Jupiter quote {} -> {} unavailable: {}", input_mint, output_mint, e));
+                return None;
+            }
+        };
+
+        let mut cache = self.cache.lock().await;
+        let ttl = self.cache_ttl;
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+        cache.insert(key, (Instant::now(), quote.clone()));
+        quote
+    }
+
+    // Ok(None) when Jupiter has no route for the pair; Err for transport errors and rate limits
+    async fn fetch_quote(&self, input_mint: &str, output_mint: &str, amount: u64) -> Result<Option<JupiterQuote>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.client
+            .get(format!("{}/quote", self.api_url))
+            .query(&[
+                ("inputMint", input_mint.to_string()),
+                ("outputMint", output_mint.to_string()),
+                ("amount", amount.to_string()),
+                ("slippageBps", self.slippage_bps.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Jupiter quote request failed: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err("rate limited by Jupiter".into());
+        }
+
+        let body: Value = response.json().await
+            .map_err(|e| format!("Failed to parse Jupiter response: {}", e))?;
+
+        if !status.is_success() {
+            // Jupiter answers 400 with an error code when no route exists
+            if body["errorCode"].as_str().is_some_and(|code| code.contains("ROUTE")) {
+                return Ok(None);
+            }
+            return Err(format!("Jupiter quote failed with {}: {}", status, body).into());
+        }
+
+        parse_quote(&body)
+            .map(Some)
+            .ok_or_else(|| format!("Unexpected Jupiter quote response: {}", body).into())
+    }
+}
+
+impl Clone for JupiterQuoteClient {
+    fn clone(&self) -> Self {
+        JupiterQuoteClient {
+            client: self.client.clone(),
+            api_url: self.api_url.clone(),
+            slippage_bps: self.slippage_bps,
+            cache_ttl: self.cache_ttl,
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+// Amounts are returned as strings to preserve u64 precision
+pub fn parse_quote(body: &Value) -> Option<JupiterQuote> {
+    let amount = |field: &str| body[field].as_str()?.parse::<u64>().ok();
+    let price_impact_pct = match &body["priceImpactPct"] {
+        Value::String(pct) => pct.parse::<f64>().ok()?,
+        other => other.as_f64()?,
+    };
+
+    let route = body["routePlan"].as_array()?
+        .iter()
+        .map(|step| step["swapInfo"]["label"].as_str().unwrap_or("Unknown").to_string())
+        .collect();
+
+    Some(JupiterQuote {
+        in_amount: amount("inAmount")?,
+        out_amount: amount("outAmount")?,
+        other_amount_threshold: amount("otherAmountThreshold")?,
+        price_impact: price_impact_pct,
+        slippage_bps: body["slippageBps"].as_u64().unwrap_or(0) as u16,
+        route,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::utils::mock_http::{self, MockResponse};
+
+    fn quote_body() -> Value {
+        json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "148250000",
+            "otherAmountThreshold": "147508750",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "priceImpactPct": "0.0012",
+            "routePlan": [
+                { "swapInfo": { "ammKey": "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2", "label": "Raydium" }, "percent": 100 },
+                { "swapInfo": { "ammKey": "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ", "label": "Whirlpool" }, "percent": 100 }
+            ]
+        })
+    }
+
+    // HTTP server answering every request with the given status and body
+    async fn mock_server(status: u16, body: String, delay: Duration) -> (String, Arc<AtomicUsize>) {
+        mock_http::serve(move |_| MockResponse::new(status, body.clone()).with_delay(delay)).await
+    }
+
+    fn client(url: &str) -> JupiterQuoteClient {
+        JupiterQuoteClient::new(url, Duration::from_millis(200), 50, Duration::from_millis(500)).unwrap()
+    }
+
+    #[test]
+    fn test_parses_quote_response() {
+        let quote = parse_quote(&quote_body()).unwrap();
+        assert_eq!(quote.in_amount, 1_000_000_000);
+        assert_eq!(quote.out_amount, 148_250_000);
+        assert_eq!(quote.other_amount_threshold, 147_508_750);
+        assert!((quote.price_impact - 0.0012).abs() < 1e-12);
+        assert_eq!(quote.route, vec!["Raydium", "Whirlpool"]);
+    }
+
+    #[tokio::test]
+    async fn test_identical_quotes_are_cached() {
+        let (url, requests) = mock_server(200, quote_body().to_string(), Duration::ZERO).await;
+        let client = client(&url);
+
+        let first = client.quote("So11111111111111111111111111111111111111112", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 1_000_000_000).await;
+        let second = client.quote("So11111111111111111111111111111111111111112", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 1_000_000_000).await;
+
+        assert_eq!(first.as_ref().unwrap().out_amount, 148_250_000);
+        assert_eq!(first, second);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_and_missing_route_return_no_quote() {
+        let (url, _) = mock_server(429, "{}".to_string(), Duration::ZERO).await;
+        assert!(client(&url).quote("A", "B", 1).await.is_none());
+
+        let no_route = json!({ "error": "Could not find any route", "errorCode": "COULD_NOT_FIND_ANY_ROUTE" });
+        let (url, _) = mock_server(400, no_route.to_string(), Duration::ZERO).await;
+        assert!(client(&url).quote("A", "B", 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_responses_time_out() {
+        let (url, _) = mock_server(200, quote_body().to_string(), Duration::from_secs(2)).await;
+        let started = Instant::now();
+        assert!(client(&url).quote("A", "B", 1).await.is_none());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
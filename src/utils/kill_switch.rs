@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use crate::config::RiskConfig;
+use crate::logging::Logger;
+
+// Operator stop for trading that doesn't need the process killed: engaged while the file at
+// KILL_SWITCH_PATH exists or after SIGUSR1, released by removing the file or SIGUSR2.
+// In-flight executions finish; only new opportunities are held back
+#[derive(Debug)]
+pub struct KillSwitch {
+    path: Option<PathBuf>,
+    poll_interval: Duration, // The file is looked at no more often than this
+    file_check: Mutex<Option<(Instant, bool)>>, // Last look at the file and whether it existed
+    signaled: AtomicBool,
+}
+
+impl KillSwitch {
+    pub fn new(path: Option<PathBuf>, poll_interval: Duration) -> Self {
+        Self { path, poll_interval, file_check: Mutex::new(None), signaled: AtomicBool::new(false) }
+    }
+
+    // Signals only
+    pub fn disabled() -> Self {
+        Self::new(None, Duration::ZERO)
+    }
+
+    pub fn from_config(config: &RiskConfig) -> Self {
+        let path = config.kill_switch_path.as_ref()
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from);
+        Self::new(path, Duration::from_millis(config.kill_switch_poll_ms))
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    // Why trading is stopped, for the pause log line; None while released
+    pub fn reason(&self) -> Option<String> {
+        if self.signaled.load(Ordering::SeqCst) {
+            return Some("SIGUSR1 received".to_string());
+        }
+        let path = self.path.as_ref()?;
+        self.file_exists_at(Instant::now()).then(|| format!("{} exists", path.display()))
+    }
+
+    pub fn engage(&self) {
+        self.signaled.store(true, Ordering::SeqCst);
+    }
+
+    // Clears the signal latch; a kill switch file still present keeps trading stopped
+    pub fn release(&self) {
+        self.signaled.store(false, Ordering::SeqCst);
+    }
+
+    fn file_exists_at(&self, now: Instant) -> bool {
+        let Some(ref path) = self.path else {
+            return false;
+        };
+        let mut file_check = self.file_check.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match *file_check {
+            Some((checked, exists)) if now.saturating_duration_since(checked) < self.poll_interval => exists,
+            _ => {
+                let exists = path.exists();
+                *file_check = Some((now, exists));
+                exists
+            }
+        }
+    }
+
+    // SIGUSR1 engages, SIGUSR2 releases, until `shutdown` flips to true
+    #[cfg(unix)]
+    pub fn listen_for_signals(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut engage = signal(SignalKind::user_defined1())
+            .map_err(|e| format!("Failed to listen for SIGUSR1: {}", e))?;
+        let mut release = signal(SignalKind::user_defined2())
+            .map_err(|e| format!("Failed to listen for SIGUSR2: {}", e))?;
+
+        let kill_switch = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(_) = engage.recv() => {
+                        Logger::status_update("SIGUSR1 received, engaging the kill switch");
+                        kill_switch.engage();
+                    }
+                    Some(_) = release.recv() => {
+                        Logger::status_update("SIGUSR2 received, releasing the kill switch");
+                        kill_switch.release();
+                    }
+                    _ = shutdown.changed() => return,
+                    else => return,
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen_for_signals(self: &Arc<Self>, _shutdown: watch::Receiver<bool>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_is_rechecked_once_the_poll_interval_passes() {
+        let path = std::env::temp_dir().join(format!("kill_switch_poll_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let kill_switch = KillSwitch::new(Some(path.clone()), Duration::from_secs(1));
+        let start = Instant::now();
+
+        assert!(!kill_switch.file_exists_at(start));
+        std::fs::write(&path, "").unwrap();
+        // Cached until the interval is over
+        assert!(!kill_switch.file_exists_at(start + Duration::from_millis(500)));
+        assert!(kill_switch.file_exists_at(start + Duration::from_secs(1)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_signal_latch_engages_and_releases() {
+        let kill_switch = KillSwitch::disabled();
+        assert_eq!(kill_switch.reason(), None);
+        kill_switch.engage();
+        assert_eq!(kill_switch.reason(), Some("SIGUSR1 received".to_string()));
+        kill_switch.release();
+        assert!(!kill_switch.is_engaged());
+    }
+}
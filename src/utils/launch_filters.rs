@@ -0,0 +1,252 @@
+use std::fmt;
+use std::sync::Arc;
+use crate::config::SnipeConfig;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::mint_info::MintInfo;
+
+// A new pool as its creation was detected, for the launch rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolLaunch {
+    pub pool_address: String,
+    pub dex: String,
+    pub creation_slot: Option<u64>, // Slot of the creating transaction
+    pub initial_liquidity_sol: Option<f64>, // Quote side; None where it doesn't apply (bonding curves)
+    pub lp_mint: Option<String>, // None for pools without LP tokens
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchFilterConfig {
+    pub min_age_slots: u64, // Skip pools created fewer slots ago, to see whether they get pulled
+    pub max_age_slots: Option<u64>, // Skip pools created longer ago; the launch has moved on by then
+    pub min_initial_liquidity_sol: f64,
+    pub min_lp_locked_share: f64, // Share of LP supply that must be burned or held by a locker; 0 disables
+    pub lp_lockers: Vec<String>, // Owners whose LP holdings count as locked
+}
+
+impl Default for LaunchFilterConfig {
+    fn default() -> Self {
+        Self {
+            min_age_slots: 0,
+            max_age_slots: Some(150),
+            min_initial_liquidity_sol: 10.0,
+            min_lp_locked_share: 0.0,
+            lp_lockers: Vec::new(),
+        }
+    }
+}
+
+impl LaunchFilterConfig {
+    pub fn from_config(config: &SnipeConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (min_age_slots, max_age_slots) = (config.min_pool_age_slots, config.max_pool_age_slots); // 0 removes the cap
+        if max_age_slots > 0 && max_age_slots < min_age_slots {
+            return Err("SNIPE_MAX_POOL_AGE_SLOTS must not be below SNIPE_MIN_POOL_AGE_SLOTS".into());
+        }
+        if !(0.0..=1.0).contains(&config.min_lp_locked_share) {
+            return Err("SNIPE_MIN_LP_LOCKED_SHARE must be in [0, 1]".into());
+        }
+
+        Ok(Self {
+            min_age_slots,
+            max_age_slots: (max_age_slots > 0).then_some(max_age_slots),
+            min_initial_liquidity_sol: config.min_liquidity_sol,
+            min_lp_locked_share: config.min_lp_locked_share,
+            lp_lockers: config.lp_lockers.clone(),
+        })
+    }
+
+    // The first rule the launch breaks. Pool age needs both slots and the LP rule a resolved share;
+    // each is skipped without them
+    pub fn check(&self, launch: &PoolLaunch, current_slot: Option<u64>, lp_locked_share: Option<f64>) -> Option<LaunchRejection> {
+        if let (Some(created), Some(current)) = (launch.creation_slot, current_slot) {
+            let age_slots = current.saturating_sub(created);
+            if age_slots < self.min_age_slots {
+                return Some(LaunchRejection::TooYoung { age_slots, min_slots: self.min_age_slots });
+            }
+            if let Some(max_slots) = self.max_age_slots.filter(|max_slots| age_slots > *max_slots) {
+                return Some(LaunchRejection::TooOld { age_slots, max_slots });
+            }
+        }
+        if let Some(liquidity_sol) = launch.initial_liquidity_sol {
+            if liquidity_sol < self.min_initial_liquidity_sol {
+                return Some(LaunchRejection::LowLiquidity { liquidity_sol, min_sol: self.min_initial_liquidity_sol });
+            }
+        }
+        if let Some(locked_share) = lp_locked_share {
+            if locked_share < self.min_lp_locked_share {
+                return Some(LaunchRejection::LpUnlocked { locked_share, min_share: self.min_lp_locked_share });
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchRejection {
+    TooYoung { age_slots: u64, min_slots: u64 },
+    TooOld { age_slots: u64, max_slots: u64 },
+    LowLiquidity { liquidity_sol: f64, min_sol: f64 },
+    LpUnlocked { locked_share: f64, min_share: f64 },
+}
+
+impl LaunchRejection {
+    // Stable label for counters
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LaunchRejection::TooYoung { .. } => "pool_too_young",
+            LaunchRejection::TooOld { .. } => "pool_too_old",
+            LaunchRejection::LowLiquidity { .. } => "low_initial_liquidity",
+            LaunchRejection::LpUnlocked { .. } => "lp_unlocked",
+        }
+    }
+}
+
+impl fmt::Display for LaunchRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchRejection::TooYoung { age_slots, min_slots } =>
+                write!(f, "pool created {} slots ago, fewer than {}", age_slots, min_slots),
+            LaunchRejection::TooOld { age_slots, max_slots } =>
+                write!(f, "pool created {} slots ago, more than {}", age_slots, max_slots),
+            LaunchRejection::LowLiquidity { liquidity_sol, min_sol } =>
+                write!(f, "initial liquidity {:.3} SOL below minimum {:.3} SOL", liquidity_sol, min_sol),
+            LaunchRejection::LpUnlocked { locked_share, min_share } =>
+                write!(f, "{:.1}% of LP burned or locked, below {:.1}%", locked_share * 100.0, min_share * 100.0),
+        }
+    }
+}
+
+// Share of the LP tokens that can't be pulled: all of them once the supply is burned to zero,
+// otherwise what locker-owned accounts hold. A partial burn isn't visible in the supply alone and
+// only counts through what's left
+pub fn lp_locked_share(supply: u64, holders: &[(String, u64)], lockers: &[String]) -> f64 {
+    if supply == 0 {
+        return 1.0;
+    }
+    let locked: u64 = holders.iter()
+        .filter(|(owner, _)| lockers.contains(owner))
+        .map(|(_, amount)| *amount)
+        .sum();
+    (locked as f64 / supply as f64).min(1.0)
+}
+
+// The launch rules for snipes, resolving the current slot and LP holdings over RPC
+pub struct LaunchFilters {
+    config: LaunchFilterConfig,
+    rpc_manager: Arc<RpcManager>,
+}
+
+impl LaunchFilters {
+    pub fn new(config: LaunchFilterConfig, rpc_manager: Arc<RpcManager>) -> Self {
+        Self { config, rpc_manager }
+    }
+
+    pub fn config(&self) -> &LaunchFilterConfig {
+        &self.config
+    }
+
+    pub async fn check(&self, launch: &PoolLaunch) -> Option<LaunchRejection> {
+        let age_bounded = self.config.min_age_slots > 0 || self.config.max_age_slots.is_some();
+        let current_slot = match launch.creation_slot {
+            Some(_) if age_bounded => self.rpc_manager.get_slot().await.ok(),
+            _ => None,
+        };
+        let lp_locked_share = match launch.lp_mint {
+            Some(ref lp_mint) if self.config.min_lp_locked_share > 0.0 => self.resolve_lp_locked_share(lp_mint).await,
+            _ => None,
+        };
+        self.config.check(launch, current_slot, lp_locked_share)
+    }
+
+    // The LP mint's current supply and the owners of its largest accounts; None when either
+    // can't be read
+    async fn resolve_lp_locked_share(&self, lp_mint: &str) -> Option<f64> {
+        let supply = MintInfo::decode(&self.rpc_manager.get_account_data(lp_mint).await.ok()?).ok()?.supply;
+        if supply == 0 {
+            return Some(1.0);
+        }
+        let largest = self.rpc_manager.get_token_largest_accounts(lp_mint).await.ok()?;
+        let accounts: Vec<String> = largest.iter().map(|(account, _)| account.clone()).collect();
+        let parsed = self.rpc_manager.get_multiple_accounts_parsed(&accounts).await.ok()?;
+        let holders: Vec<(String, u64)> = parsed.iter().zip(&largest)
+            .filter_map(|(account, (_, amount))| {
+                let owner = account["data"]["parsed"]["info"]["owner"].as_str()?;
+                Some((owner.to_string(), *amount))
+            })
+            .collect();
+        Some(lp_locked_share(supply, &holders, &self.config.lp_lockers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::WSOL_MINT;
+
+    // What detection produces for a Raydium initialize2 landing in `slot`
+    fn raydium_launch(slot: u64, liquidity_sol: f64) -> PoolLaunch {
+        PoolLaunch {
+            pool_address: "NewPoo11111111111111111111111111111111111111".to_string(),
+            dex: "Raydium".to_string(),
+            creation_slot: Some(slot),
+            initial_liquidity_sol: Some(liquidity_sol),
+            lp_mint: Some("LpMint1111111111111111111111111111111111111".to_string()),
+        }
+    }
+
+    fn config() -> LaunchFilterConfig {
+        LaunchFilterConfig {
+            min_age_slots: 2,
+            max_age_slots: Some(150),
+            min_initial_liquidity_sol: 10.0,
+            min_lp_locked_share: 0.9,
+            lp_lockers: vec!["Locker1111111111111111111111111111111111111".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_each_rule_rejects_with_its_own_reason() {
+        let config = config();
+        let kind = |launch: &PoolLaunch, current_slot, locked_share| config.check(launch, current_slot, locked_share).map(|rejection| rejection.kind());
+
+        assert_eq!(kind(&raydium_launch(1_000, 50.0), Some(1_005), Some(1.0)), None);
+        assert_eq!(kind(&raydium_launch(1_000, 50.0), Some(1_001), Some(1.0)), Some("pool_too_young"));
+        assert_eq!(kind(&raydium_launch(1_000, 50.0), Some(1_200), Some(1.0)), Some("pool_too_old"));
+        assert_eq!(kind(&raydium_launch(1_000, 5.0), Some(1_005), Some(1.0)), Some("low_initial_liquidity"));
+        assert_eq!(kind(&raydium_launch(1_000, 50.0), Some(1_005), Some(0.2)), Some("lp_unlocked"));
+        assert_eq!(
+            config.check(&raydium_launch(1_000, 5.0), Some(1_005), None).unwrap().to_string(),
+            "initial liquidity 5.000 SOL below minimum 10.000 SOL"
+        );
+
+        // Without the current slot or an LP share those rules are skipped; a bonding curve launch
+        // has neither liquidity nor LP
+        assert_eq!(kind(&raydium_launch(1_000, 50.0), None, None), None);
+        let pumpfun = PoolLaunch {
+            pool_address: "Curve11111111111111111111111111111111111111".to_string(),
+            dex: "PumpFun".to_string(),
+            creation_slot: Some(1_000),
+            initial_liquidity_sol: None,
+            lp_mint: None,
+        };
+        assert_eq!(kind(&pumpfun, Some(1_010), None), None);
+        assert_eq!(kind(&pumpfun, Some(2_000), None), Some("pool_too_old"));
+
+        let uncapped = LaunchFilterConfig { max_age_slots: None, ..config.clone() };
+        assert_eq!(uncapped.check(&raydium_launch(1_000, 50.0), Some(100_000), Some(1.0)), None);
+    }
+
+    #[test]
+    fn test_lp_counts_as_locked_once_burned_or_with_a_locker() {
+        let lockers = config().lp_lockers;
+        let creator = "Creator111111111111111111111111111111111111".to_string();
+
+        // Fresh pool: the creator holds everything
+        assert_eq!(lp_locked_share(1_000, &[(creator.clone(), 1_000)], &lockers), 0.0);
+        // Most of it sent to a locker
+        let share = lp_locked_share(1_000, &[(lockers[0].clone(), 950), (creator.clone(), 50)], &lockers);
+        assert!((share - 0.95).abs() < 1e-9);
+        // All of it burned
+        assert_eq!(lp_locked_share(0, &[], &lockers), 1.0);
+        assert_eq!(lp_locked_share(1_000, &[(WSOL_MINT.to_string(), 1_000)], &[]), 0.0);
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::{watch, RwLock};
+use crate::config::JitoConfig;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+
+pub const SLOT_MS: u64 = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderType {
+    Jito,    // Runs the Jito-Solana client, so bundles can land
+    NonJito, // Bundles sent for this leader are dropped
+    Unknown, // No schedule or validator set to judge by
+}
+
+impl LeaderType {
+    pub const ALL: [LeaderType; 3] = [LeaderType::Jito, LeaderType::NonJito, LeaderType::Unknown];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LeaderType::Jito => "jito",
+            LeaderType::NonJito => "non_jito",
+            LeaderType::Unknown => "unknown",
+        }
+    }
+}
+
+// When the next Jito leader takes over, as seen from the current slot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeaderWindow {
+    JitoNow,
+    JitoIn(u64), // Slots until the next Jito leader
+    NoJitoWithin,
+    Unknown,
+}
+
+impl LeaderWindow {
+    pub fn leader_type(&self) -> LeaderType {
+        match self {
+            LeaderWindow::JitoNow | LeaderWindow::JitoIn(_) => LeaderType::Jito,
+            LeaderWindow::NoJitoWithin => LeaderType::NonJito,
+            LeaderWindow::Unknown => LeaderType::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LeaderLandingRate {
+    pub leader: &'static str,
+    pub attempts: u64,
+    pub landed: u64,
+}
+
+impl LeaderLandingRate {
+    pub fn landing_rate(&self) -> f64 {
+        if self.attempts > 0 { self.landed as f64 / self.attempts as f64 } else { 0.0 }
+    }
+}
+
+// One epoch's leaders keyed by absolute slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderSchedule {
+    pub epoch: u64,
+    leaders: HashMap<u64, String>,
+}
+
+impl LeaderSchedule {
+    // getLeaderSchedule result: identity -> slot indexes relative to the epoch's first slot
+    pub fn parse(response: &Value, epoch: u64, epoch_start_slot: u64) -> Option<Self> {
+        let schedule = response["result"].as_object()?;
+        let mut leaders = HashMap::new();
+        for (identity, slots) in schedule {
+            for index in slots.as_array().into_iter().flatten().filter_map(Value::as_u64) {
+                leaders.insert(epoch_start_slot + index, identity.clone());
+            }
+        }
+        Some(Self { epoch, leaders })
+    }
+
+    pub fn leader_at(&self, slot: u64) -> Option<&str> {
+        self.leaders.get(&slot).map(String::as_str)
+    }
+
+    // Where the next Jito leader is within max_slots of the current slot
+    pub fn window(&self, current_slot: u64, max_slots: u64, jito_validators: &HashSet<String>) -> LeaderWindow {
+        if jito_validators.is_empty() || self.leader_at(current_slot).is_none() {
+            return LeaderWindow::Unknown;
+        }
+        for offset in 0..=max_slots {
+            match self.leader_at(current_slot + offset) {
+                Some(leader) if jito_validators.contains(leader) => {
+                    return if offset == 0 { LeaderWindow::JitoNow } else { LeaderWindow::JitoIn(offset) };
+                }
+                Some(_) => {}
+                // Past the end of the epoch we can't say
+                None => return LeaderWindow::Unknown,
+            }
+        }
+        LeaderWindow::NoJitoWithin
+    }
+}
+
+// Identities running the Jito client, from either a plain list or a validators API response:
+// {"validators": [{"identity_account": "...", "running_jito": true}, ...]}
+pub fn parse_jito_validators(response: &Value) -> HashSet<String> {
+    let entries = response["validators"].as_array().or_else(|| response.as_array());
+    entries.into_iter().flatten()
+        .filter_map(|entry| match entry {
+            Value::String(identity) => Some(identity.clone()),
+            Value::Object(_) if entry["running_jito"].as_bool() != Some(false) => entry["identity_account"].as_str()
+                .or_else(|| entry["identity"].as_str())
+                .map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+struct ScheduleState {
+    schedule: Option<LeaderSchedule>,
+    jito_validators: HashSet<String>,
+    slot: Option<(u64, Instant)>, // Last observed slot and when
+}
+
+// Epoch leader schedule joined with the Jito validator set, so bundles can be timed for slots
+// where a Jito leader is producing. Without a validator list every slot is Unknown and timing
+// falls back to the network-speed heuristic
+pub struct LeaderScheduleTracker {
+    rpc_manager: Arc<RpcManager>,
+    client: reqwest::Client,
+    validators_url: Option<String>,
+    static_validators: HashSet<String>,
+    state: RwLock<ScheduleState>,
+    poll_interval: Duration,
+    outcomes: RwLock<HashMap<LeaderType, (u64, u64)>>, // (attempts, landed)
+}
+
+impl LeaderScheduleTracker {
+    pub fn new(
+        rpc_manager: Arc<RpcManager>,
+        validators_url: Option<String>,
+        static_validators: HashSet<String>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            rpc_manager,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            validators_url,
+            state: RwLock::new(ScheduleState {
+                schedule: None,
+                jito_validators: static_validators.clone(),
+                slot: None,
+            }),
+            static_validators,
+            poll_interval,
+            outcomes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, config: &JitoConfig) -> Self {
+        let validators_url = config.validators_url.clone().filter(|url| !url.trim().is_empty());
+        let static_validators: HashSet<String> = config.validators.iter().cloned().collect();
+        Self::new(rpc_manager, validators_url, static_validators, Duration::from_millis(config.leader_schedule_poll_ms.max(1)))
+    }
+
+    // Tracks the current slot, and fetches the schedule and validator set again on each new epoch
+    pub async fn refresh(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let epoch_info = self.rpc_manager.get_epoch_info().await?;
+        let info = &epoch_info["result"];
+        let (Some(epoch), Some(slot), Some(slot_index)) = (info["epoch"].as_u64(), info["absoluteSlot"].as_u64(), info["slotIndex"].as_u64()) else {
+            return Err("getEpochInfo returned no slot".into());
+        };
+        self.state.write().await.slot = Some((slot, Instant::now()));
+
+        let current_epoch = self.state.read().await.schedule.as_ref().map(|schedule| schedule.epoch);
+        if current_epoch == Some(epoch) {
+            return Ok(());
+        }
+
+        let response = self.rpc_manager.get_leader_schedule(slot).await?;
+        let schedule = LeaderSchedule::parse(&response, epoch, slot - slot_index)
+            .ok_or("getLeaderSchedule returned no schedule")?;
+
+        let mut jito_validators = self.static_validators.clone();
+        if let Some(ref url) = self.validators_url {
+            match self.fetch_validators(url).await {
+                Ok(validators) => jito_validators.extend(validators),
+                Err(e) => Logger::status_update(&format!("Could not fetch Jito validators: {}", e)),
+            }
+        }
+        Logger::status_update(&format!("Leader schedule for epoch {} loaded, {} Jito validators known", epoch, jito_validators.len()));
+
+        let mut state = self.state.write().await;
+        state.schedule = Some(schedule);
+        if !jito_validators.is_empty() {
+            state.jito_validators = jito_validators;
+        }
+        Ok(())
+    }
+
+    async fn fetch_validators(&self, url: &str) -> Result<HashSet<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response: Value = self.client.get(url).send().await?.error_for_status()?.json().await?;
+        Ok(parse_jito_validators(&response))
+    }
+
+    pub fn start_polling(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let tracker = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = tracker.refresh().await {
+                    Logger::status_update(&format!("Could not refresh leader schedule: {}", e));
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(tracker.poll_interval) => {},
+                    _ = shutdown.changed() => return,
+                }
+            }
+        });
+    }
+
+    // Next Jito leader within the wait, counted from the slot we extrapolate we're in now
+    pub async fn window(&self, max_wait: Duration) -> LeaderWindow {
+        let state = self.state.read().await;
+        let (Some(schedule), Some((slot, observed_at))) = (&state.schedule, state.slot) else {
+            return LeaderWindow::Unknown;
+        };
+        let current_slot = slot + observed_at.elapsed().as_millis() as u64 / SLOT_MS;
+        schedule.window(current_slot, max_wait.as_millis() as u64 / SLOT_MS, &state.jito_validators)
+    }
+
+    pub async fn record_outcome(&self, leader: LeaderType, landed: bool) {
+        let mut outcomes = self.outcomes.write().await;
+        let (attempts, landings) = outcomes.entry(leader).or_insert((0, 0));
+        *attempts += 1;
+        if landed {
+            *landings += 1;
+        }
+    }
+
+    // Bundle landing rate split by the type of leader it was sent for
+    pub async fn landing_rates(&self) -> Vec<LeaderLandingRate> {
+        let outcomes = self.outcomes.read().await;
+        LeaderType::ALL.iter()
+            .filter_map(|leader| outcomes.get(leader).map(|(attempts, landed)| LeaderLandingRate {
+                leader: leader.label(),
+                attempts: *attempts,
+                landed: *landed,
+            }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validators() -> HashSet<String> {
+        parse_jito_validators(&json!({ "validators": [
+            { "identity_account": "JitoA", "running_jito": true },
+            { "identity_account": "Plain", "running_jito": false },
+            { "identity": "JitoB" }
+        ]}))
+    }
+
+    fn schedule() -> LeaderSchedule {
+        // Epoch starting at slot 1000: Plain leads 1000-1003, JitoA 1004-1007, Plain 1008-1011
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": {
+            "Plain": [0, 1, 2, 3, 8, 9, 10, 11],
+            "JitoA": [4, 5, 6, 7]
+        }});
+        LeaderSchedule::parse(&response, 7, 1000).unwrap()
+    }
+
+    #[test]
+    fn test_parses_jito_validators() {
+        let validators = validators();
+        assert_eq!(validators.len(), 2);
+        assert!(validators.contains("JitoA") && validators.contains("JitoB"));
+        assert_eq!(parse_jito_validators(&json!(["X", "Y"])).len(), 2);
+    }
+
+    #[test]
+    fn test_window_finds_next_jito_leader_within_deadline() {
+        let schedule = schedule();
+        let validators = validators();
+        assert_eq!(schedule.leader_at(1005), Some("JitoA"));
+
+        assert_eq!(schedule.window(1005, 3, &validators), LeaderWindow::JitoNow);
+        assert_eq!(schedule.window(1001, 3, &validators), LeaderWindow::JitoIn(3));
+        assert_eq!(schedule.window(1000, 3, &validators), LeaderWindow::NoJitoWithin);
+        assert_eq!(LeaderWindow::NoJitoWithin.leader_type(), LeaderType::NonJito);
+
+        // Running off the end of the epoch, or with no validator set, we can't tell
+        assert_eq!(schedule.window(1009, 5, &validators), LeaderWindow::Unknown);
+        assert_eq!(schedule.window(1001, 3, &HashSet::new()), LeaderWindow::Unknown);
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use crate::config::FiltersConfig;
+use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityFloors {
+    pub min_pool_liquidity_sol: f64,
+    pub min_volume_24h_usd: f64, // Only checked when a volume source is available
+    pub max_trade_to_liquidity: f64,
+}
+
+impl LiquidityFloors {
+    // Each unset per-strategy floor falls back to the shared one
+    fn with_overrides(&self, min_pool_liquidity_sol: Option<f64>, min_volume_24h_usd: Option<f64>, max_trade_to_liquidity: Option<f64>) -> Self {
+        Self {
+            min_pool_liquidity_sol: min_pool_liquidity_sol.unwrap_or(self.min_pool_liquidity_sol),
+            min_volume_24h_usd: min_volume_24h_usd.unwrap_or(self.min_volume_24h_usd),
+            max_trade_to_liquidity: max_trade_to_liquidity.unwrap_or(self.max_trade_to_liquidity),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterRejection {
+    LowLiquidity { liquidity_sol: f64, min_sol: f64 },
+    LowVolume { volume_usd: f64, min_usd: f64 },
+    TradeTooLarge { ratio: f64, max_ratio: f64 },
+}
+
+impl FilterRejection {
+    // Stable label for counters
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FilterRejection::LowLiquidity { .. } => "low_liquidity",
+            FilterRejection::LowVolume { .. } => "low_volume",
+            FilterRejection::TradeTooLarge { .. } => "trade_too_large",
+        }
+    }
+}
+
+impl fmt::Display for FilterRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterRejection::LowLiquidity { liquidity_sol, min_sol } =>
+                write!(f, "pool liquidity {:.3} SOL below minimum {:.3} SOL", liquidity_sol, min_sol),
+            FilterRejection::LowVolume { volume_usd, min_usd } =>
+                write!(f, "24h volume ${:.0} below minimum ${:.0}", volume_usd, min_usd),
+            FilterRejection::TradeTooLarge { ratio, max_ratio } =>
+                write!(f, "trade is {:.1}% of pool liquidity (max {:.1}%)", ratio * 100.0, max_ratio * 100.0),
+        }
+    }
+}
+
+// Floors on pool depth and activity applied before an opportunity is verified. Shallow pools
+// show the widest spreads and the worst fills; sandwiches and arbitrage get separate thresholds
+#[derive(Debug, Clone)]
+pub struct LiquidityFilters {
+    arbitrage: LiquidityFloors,
+    sandwich: LiquidityFloors,
+    frontrun: LiquidityFloors,
+    default: LiquidityFloors,
+}
+
+impl LiquidityFilters {
+    pub fn new(default: LiquidityFloors) -> Self {
+        Self {
+            arbitrage: default.clone(),
+            sandwich: default.clone(),
+            frontrun: default.clone(),
+            default,
+        }
+    }
+
+    pub fn from_config(config: &FiltersConfig) -> Self {
+        let default = LiquidityFloors {
+            min_pool_liquidity_sol: config.min_pool_liquidity_sol,
+            min_volume_24h_usd: config.min_volume_24h_usd,
+            max_trade_to_liquidity: config.max_trade_to_liquidity,
+        };
+
+        Self {
+            arbitrage: default.with_overrides(config.arbitrage_min_pool_liquidity_sol, config.arbitrage_min_volume_24h_usd, config.arbitrage_max_trade_to_liquidity),
+            sandwich: default.with_overrides(config.sandwich_min_pool_liquidity_sol, config.sandwich_min_volume_24h_usd, config.sandwich_max_trade_to_liquidity),
+            frontrun: default.with_overrides(config.frontrun_min_pool_liquidity_sol, config.frontrun_min_volume_24h_usd, config.frontrun_max_trade_to_liquidity),
+            default,
+        }
+    }
+
+    pub fn with_floors(mut self, opportunity_type: &OpportunityType, floors: LiquidityFloors) -> Self {
+        match opportunity_type {
+            OpportunityType::Arbitrage | OpportunityType::Backrun => self.arbitrage = floors,
+            OpportunityType::Sandwich => self.sandwich = floors,
+            OpportunityType::Frontrun => self.frontrun = floors,
+            _ => self.default = floors,
+        }
+        self
+    }
+
+    pub fn floors(&self, opportunity_type: &OpportunityType) -> &LiquidityFloors {
+        match opportunity_type {
+            // A backrun is one leg of a cross-venue arbitrage
+            OpportunityType::Arbitrage | OpportunityType::Backrun => &self.arbitrage,
+            OpportunityType::Sandwich => &self.sandwich,
+            OpportunityType::Frontrun => &self.frontrun,
+            _ => &self.default,
+        }
+    }
+
+    // First floor the opportunity fails; volume is skipped when unknown
+    pub fn check(
+        &self,
+        opportunity_type: &OpportunityType,
+        pool_liquidity_sol: f64,
+        volume_24h_usd: Option<f64>,
+        trade_size_sol: f64,
+    ) -> Result<(), FilterRejection> {
+        let floors = self.floors(opportunity_type);
+
+        if pool_liquidity_sol < floors.min_pool_liquidity_sol {
+            return Err(FilterRejection::LowLiquidity {
+                liquidity_sol: pool_liquidity_sol,
+                min_sol: floors.min_pool_liquidity_sol,
+            });
+        }
+
+        if let Some(volume_usd) = volume_24h_usd {
+            if volume_usd < floors.min_volume_24h_usd {
+                return Err(FilterRejection::LowVolume { volume_usd, min_usd: floors.min_volume_24h_usd });
+            }
+        }
+
+        let ratio = trade_size_sol / pool_liquidity_sol;
+        if ratio > floors.max_trade_to_liquidity {
+            return Err(FilterRejection::TradeTooLarge { ratio, max_ratio: floors.max_trade_to_liquidity });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floors(min_pool_liquidity_sol: f64, min_volume_24h_usd: f64, max_trade_to_liquidity: f64) -> LiquidityFloors {
+        LiquidityFloors { min_pool_liquidity_sol, min_volume_24h_usd, max_trade_to_liquidity }
+    }
+
+    #[test]
+    fn test_reports_the_failing_floor() {
+        let filters = LiquidityFilters::new(floors(10.0, 10_000.0, 0.1));
+        let arbitrage = OpportunityType::Arbitrage;
+
+        assert_eq!(filters.check(&arbitrage, 200.0, Some(50_000.0), 5.0), Ok(()));
+        assert_eq!(filters.check(&arbitrage, 5.0, None, 0.1).unwrap_err().kind(), "low_liquidity");
+        assert_eq!(filters.check(&arbitrage, 200.0, Some(2_000.0), 5.0).unwrap_err().kind(), "low_volume");
+        assert_eq!(filters.check(&arbitrage, 200.0, None, 25.0).unwrap_err().kind(), "trade_too_large");
+        // Unknown volume doesn't reject
+        assert_eq!(filters.check(&arbitrage, 200.0, None, 5.0), Ok(()));
+    }
+
+    #[test]
+    fn test_thresholds_differ_per_strategy() {
+        let filters = LiquidityFilters::new(floors(10.0, 0.0, 0.1))
+            .with_floors(&OpportunityType::Sandwich, floors(50.0, 0.0, 0.02));
+
+        assert_eq!(filters.check(&OpportunityType::Arbitrage, 30.0, None, 2.0), Ok(()));
+        assert_eq!(filters.check(&OpportunityType::Sandwich, 30.0, None, 0.1).unwrap_err().kind(), "low_liquidity");
+        assert_eq!(filters.check(&OpportunityType::Sandwich, 100.0, None, 5.0).unwrap_err().kind(), "trade_too_large");
+        assert_eq!(filters.floors(&OpportunityType::Snipe).min_pool_liquidity_sol, 10.0);
+    }
+}
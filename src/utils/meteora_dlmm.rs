@@ -0,0 +1,604 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::utils::instruction_parser::{self, ParsedInstruction};
+use crate::utils::simulation_effects::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+pub const METEORA_DLMM_PROGRAM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+
+// Anchor discriminators: first 8 bytes of sha256("global:swap"), sha256("global:swap2")
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+const SWAP2_DISCRIMINATOR: [u8; 8] = [65, 75, 63, 76, 235, 91, 91, 136];
+
+// Anchor account discriminators for LbPair and BinArray
+const LB_PAIR_ACCOUNT_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+const BIN_ARRAY_ACCOUNT_DISCRIMINATOR: [u8; 8] = [92, 142, 92, 220, 5, 148, 70, 181];
+
+const SWAP_MIN_ACCOUNTS: usize = 15;
+const LB_PAIR_ACCOUNT_LEN: usize = 904;
+const BINS_PER_ARRAY: i32 = 70;
+const BIN_LEN: usize = 144;
+const BIN_ARRAY_HEADER_LEN: usize = 56;
+const BIN_ARRAY_ACCOUNT_LEN: usize = BIN_ARRAY_HEADER_LEN + BINS_PER_ARRAY as usize * BIN_LEN;
+
+// Fee rates are in billionths; the program caps the total at 10%
+const FEE_PRECISION: u128 = 1_000_000_000;
+const MAX_FEE_RATE: u128 = 100_000_000;
+
+// Bin arrays passed to a swap, starting with the active one; each covers 70 bins
+pub const SWAP_BIN_ARRAYS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DlmmSwap {
+    pub lb_pair: String,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    pub swap_for_y: Option<bool>, // X in, Y out; None when the input side couldn't be resolved
+    pub reserve_x: String,
+    pub reserve_y: String,
+    pub user_token_in: String,
+    pub user_token_out: String,
+    pub token_x_mint: String,
+    pub token_y_mint: String,
+}
+
+impl DlmmSwap {
+    pub fn input_mint(&self) -> Option<&str> {
+        self.swap_for_y.map(|swap_for_y| if swap_for_y { self.token_x_mint.as_str() } else { self.token_y_mint.as_str() })
+    }
+
+    pub fn output_mint(&self) -> Option<&str> {
+        self.swap_for_y.map(|swap_for_y| if swap_for_y { self.token_y_mint.as_str() } else { self.token_x_mint.as_str() })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LbPairState {
+    pub active_id: i32,
+    pub bin_step: u16, // Basis points between neighbouring bin prices
+    pub base_factor: u16,
+    pub base_fee_power_factor: u8,
+    pub variable_fee_control: u32,
+    pub volatility_accumulator: u32,
+    pub token_x_mint: String,
+    pub token_y_mint: String,
+    pub reserve_x: String, // Vault token accounts
+    pub reserve_y: String,
+    pub protocol_fee_x: u64, // Protocol fees still sitting in the vaults
+    pub protocol_fee_y: u64,
+    pub oracle: String,
+    pub token_x_program_flag: u8, // 0 SPL Token, 1 Token-2022
+    pub token_y_program_flag: u8,
+}
+
+impl LbPairState {
+    // Layout (after the 8 byte discriminator): static parameters 32 (base_factor u16 at 8,
+    // variable_fee_control u32 at 16, base_fee_power_factor u8 at 34), variable parameters 32
+    // (volatility_accumulator u32 at 40), bump, bin_step_seed, pair_type, active_id i32 at 76,
+    // bin_step u16 at 80, ..., mint x 88, mint y 120, reserve x 152, reserve y 184, protocol fees
+    // 2x u64 at 216, ..., oracle 552, ..., token program flags at 880/881
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < LB_PAIR_ACCOUNT_LEN {
+            return Err(format!("DLMM lb_pair account too short: {} bytes", data.len()).into());
+        }
+        if data[..8] != LB_PAIR_ACCOUNT_DISCRIMINATOR {
+            return Err("Account is not a Meteora DLMM lb_pair".into());
+        }
+
+        let pubkey = |offset: usize| bs58::encode(&data[offset..offset + 32]).into_string();
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 byte slice"));
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+
+        Ok(Self {
+            active_id: u32_at(76) as i32,
+            bin_step: u16_at(80),
+            base_factor: u16_at(8),
+            base_fee_power_factor: data[34],
+            variable_fee_control: u32_at(16),
+            volatility_accumulator: u32_at(40),
+            token_x_mint: pubkey(88),
+            token_y_mint: pubkey(120),
+            reserve_x: pubkey(152),
+            reserve_y: pubkey(184),
+            protocol_fee_x: u64_at(216),
+            protocol_fee_y: u64_at(224),
+            oracle: pubkey(552),
+            token_x_program_flag: data[880],
+            token_y_program_flag: data[881],
+        })
+    }
+
+    // Base fee plus the volatility-driven variable fee, in billionths
+    pub fn fee_rate(&self) -> u128 {
+        let base = self.base_factor as u128 * self.bin_step as u128 * 10 * 10u128.pow(self.base_fee_power_factor as u32);
+        let volatility = self.volatility_accumulator as u128 * self.bin_step as u128;
+        let variable = (volatility * volatility * self.variable_fee_control as u128).div_ceil(100_000_000_000);
+        (base + variable).min(MAX_FEE_RATE)
+    }
+
+    // Fee as a fraction (0.0025 = 0.25%)
+    pub fn fee_fraction(&self) -> f64 {
+        self.fee_rate() as f64 / FEE_PRECISION as f64
+    }
+
+    // Price of X in Y for a bin, in raw (non decimal-adjusted) units
+    pub fn bin_price(&self, bin_id: i32) -> f64 {
+        (1.0 + self.bin_step as f64 / 10_000.0).powi(bin_id)
+    }
+
+    // Tradable reserves: vault balances minus the protocol fees not yet claimed
+    pub fn reserves(&self, vault_x_amount: u64, vault_y_amount: u64) -> (u64, u64) {
+        (vault_x_amount.saturating_sub(self.protocol_fee_x), vault_y_amount.saturating_sub(self.protocol_fee_y))
+    }
+
+    // Constant-product reserves at the active bin's price holding the pool's total value. Liquidity
+    // sits in discrete bins, so this only approximates trades that stay near the active bin; the
+    // bin walk in `quote` is exact
+    pub fn virtual_reserves(&self, amount_x: u64, amount_y: u64) -> (u64, u64) {
+        let price = self.bin_price(self.active_id);
+        if price <= 0.0 || !price.is_finite() {
+            return (0, 0);
+        }
+        let value_in_y = amount_x as f64 * price + amount_y as f64;
+        ((value_in_y / 2.0 / price) as u64, (value_in_y / 2.0) as u64)
+    }
+
+    fn token_program(flag: u8) -> &'static str {
+        if flag == 1 { TOKEN_2022_PROGRAM_ID } else { TOKEN_PROGRAM_ID }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Bin {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinArray {
+    pub index: i64,
+    pub lb_pair: String,
+    pub bins: Vec<Bin>,
+}
+
+impl BinArray {
+    // Layout (after the 8 byte discriminator): index i64, version u8, padding 7, lb_pair 32, then
+    // 70 bins of 144 bytes starting with amount_x u64, amount_y u64
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < BIN_ARRAY_ACCOUNT_LEN {
+            return Err(format!("DLMM bin array account too short: {} bytes", data.len()).into());
+        }
+        if data[..8] != BIN_ARRAY_ACCOUNT_DISCRIMINATOR {
+            return Err("Account is not a Meteora DLMM bin array".into());
+        }
+
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+        let bins = (0..BINS_PER_ARRAY as usize)
+            .map(|i| {
+                let offset = BIN_ARRAY_HEADER_LEN + i * BIN_LEN;
+                Bin { amount_x: u64_at(offset), amount_y: u64_at(offset + 8) }
+            })
+            .collect();
+
+        Ok(Self {
+            index: u64_at(8) as i64,
+            lb_pair: bs58::encode(&data[24..56]).into_string(),
+            bins,
+        })
+    }
+
+    // Bins keyed by bin id
+    pub fn bins_by_id(&self) -> impl Iterator<Item = (i32, Bin)> + '_ {
+        let first = self.index as i32 * BINS_PER_ARRAY;
+        self.bins.iter().enumerate().map(move |(i, bin)| (first + i as i32, *bin))
+    }
+}
+
+pub fn bin_array_index(bin_id: i32) -> i64 {
+    bin_id.div_euclid(BINS_PER_ARRAY) as i64
+}
+
+// PDA of ["bin_array", lb_pair, index as i64 le]
+pub fn bin_array_address(lb_pair: &str, index: i64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"bin_array", pubkey(lb_pair)?.as_ref(), &index.to_le_bytes()],
+        &pubkey(METEORA_DLMM_PROGRAM_ID)?,
+    );
+    Ok(address.to_string())
+}
+
+// The active bin's array and the next ones in the swap's direction: X in walks down, Y in walks up
+pub fn swap_bin_arrays(lb_pair: &str, active_id: i32, swap_for_y: bool, count: usize) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let start = bin_array_index(active_id);
+    let step = if swap_for_y { -1 } else { 1 };
+    (0..count as i64).map(|i| bin_array_address(lb_pair, start + i * step)).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DlmmQuote {
+    pub amount_in: u64, // Input consumed, fees included; less than asked when the bins run out
+    pub amount_out: u64,
+    pub fee: u64,
+    pub end_bin: i32, // Bin the swap stops in
+}
+
+// Walks bins from the active one in the swap's direction, draining each bin's output-side
+// liquidity at its price until the input is used up. Bins missing from `bins` end the walk, so
+// the caller decides how far ahead liquidity is loaded. The fee rate is held at the current one;
+// the program raises the variable part as bins are crossed, so long walks are slightly optimistic
+pub fn quote(pair: &LbPairState, bins: &BTreeMap<i32, Bin>, amount_in: u64, swap_for_y: bool) -> DlmmQuote {
+    let fee_rate = pair.fee_rate();
+    let mut left = amount_in as u128;
+    let mut amount_out = 0u128;
+    let mut fee = 0u128;
+    let mut bin_id = pair.active_id;
+
+    while left > 0 {
+        let Some(bin) = bins.get(&bin_id) else { break };
+        let price = pair.bin_price(bin_id);
+        let liquidity_out = (if swap_for_y { bin.amount_y } else { bin.amount_x }) as u128;
+
+        if liquidity_out > 0 {
+            // Input that takes the whole bin, then the fee on top of it
+            let max_in = (if swap_for_y { liquidity_out as f64 / price } else { liquidity_out as f64 * price }).ceil() as u128;
+            let max_fee = (max_in * fee_rate).div_ceil(FEE_PRECISION - fee_rate);
+            if left >= max_in + max_fee {
+                left -= max_in + max_fee;
+                amount_out += liquidity_out;
+                fee += max_fee;
+            } else {
+                let bin_fee = (left * fee_rate).div_ceil(FEE_PRECISION);
+                let net_in = (left - bin_fee) as f64;
+                let out = (if swap_for_y { net_in * price } else { net_in / price }) as u128;
+                amount_out += out.min(liquidity_out);
+                fee += bin_fee;
+                left = 0;
+                break;
+            }
+        }
+        bin_id = if swap_for_y { bin_id - 1 } else { bin_id + 1 };
+    }
+
+    DlmmQuote {
+        amount_in: (amount_in as u128 - left) as u64,
+        amount_out: amount_out.min(u64::MAX as u128) as u64,
+        fee: fee as u64,
+        end_bin: bin_id,
+    }
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")?,
+    );
+    Ok(address)
+}
+
+// swap: [lb_pair (w), bitmap extension, reserve x (w), reserve y (w), user token in (w),
+// user token out (w), mint x, mint y, oracle (w), host fee in, user (s), token x program,
+// token y program, event authority, program] followed by the bin arrays (w). The optional
+// bitmap extension and host fee accounts are passed as the program itself, i.e. omitted; the
+// extension is only needed for pools trading beyond bin ±35,840. The wallet's associated token
+// accounts are the swap's source and destination
+pub fn swap_instruction(
+    lb_pair: &str,
+    pair: &LbPairState,
+    user: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    swap_for_y: bool,
+) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let program_id = pubkey(METEORA_DLMM_PROGRAM_ID)?;
+    let mint_x = pubkey(&pair.token_x_mint)?;
+    let mint_y = pubkey(&pair.token_y_mint)?;
+    let token_x_program = pubkey(LbPairState::token_program(pair.token_x_program_flag))?;
+    let token_y_program = pubkey(LbPairState::token_program(pair.token_y_program_flag))?;
+    let user_token_x = associated_token_address(user, &mint_x, &token_x_program)?;
+    let user_token_y = associated_token_address(user, &mint_y, &token_y_program)?;
+    let (user_token_in, user_token_out) = if swap_for_y { (user_token_x, user_token_y) } else { (user_token_y, user_token_x) };
+    let (event_authority, _) = Pubkey::find_program_address(&[b"__event_authority"], &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(pubkey(lb_pair)?, false),
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new(pubkey(&pair.reserve_x)?, false),
+        AccountMeta::new(pubkey(&pair.reserve_y)?, false),
+        AccountMeta::new(user_token_in, false),
+        AccountMeta::new(user_token_out, false),
+        AccountMeta::new_readonly(mint_x, false),
+        AccountMeta::new_readonly(mint_y, false),
+        AccountMeta::new(pubkey(&pair.oracle)?, false),
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new_readonly(token_x_program, false),
+        AccountMeta::new_readonly(token_y_program, false),
+        AccountMeta::new_readonly(event_authority, false),
+        AccountMeta::new_readonly(program_id, false),
+    ];
+    for bin_array in swap_bin_arrays(lb_pair, pair.active_id, swap_for_y, SWAP_BIN_ARRAYS)? {
+        accounts.push(AccountMeta::new(pubkey(&bin_array)?, false));
+    }
+
+    let mut data = SWAP_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    Ok(Instruction { program_id, accounts, data })
+}
+
+// Layout: [discriminator 8][amount_in u64][min_amount_out u64], for swap and swap2 (which
+// appends remaining-accounts info and takes a memo program after the token programs). The
+// input side comes from the user's input account mint or, failing that, the X reserve's
+// balance change
+pub fn decode_instruction(instruction: &ParsedInstruction, tx_details: &Value) -> Option<DlmmSwap> {
+    if instruction.program_id != METEORA_DLMM_PROGRAM_ID || instruction.accounts.len() < SWAP_MIN_ACCOUNTS {
+        return None;
+    }
+
+    let data = instruction_parser::decode_instruction_data(instruction)?;
+    if data.len() < 24 || (data[..8] != SWAP_DISCRIMINATOR && data[..8] != SWAP2_DISCRIMINATOR) {
+        return None;
+    }
+
+    let accounts = &instruction.accounts;
+    let (reserve_x, user_token_in) = (&accounts[2], &accounts[4]);
+    let (token_x_mint, token_y_mint) = (&accounts[6], &accounts[7]);
+    let swap_for_y = match instruction_parser::token_account_mint(tx_details, user_token_in) {
+        Some(mint) if &mint == token_x_mint => Some(true),
+        Some(mint) if &mint == token_y_mint => Some(false),
+        _ => instruction_parser::token_balance_change(tx_details, reserve_x)
+            .filter(|change| *change != 0)
+            .map(|change| change > 0),
+    };
+
+    Some(DlmmSwap {
+        lb_pair: accounts[0].clone(),
+        amount_in: u64::from_le_bytes(data[8..16].try_into().ok()?),
+        min_amount_out: u64::from_le_bytes(data[16..24].try_into().ok()?),
+        swap_for_y,
+        reserve_x: reserve_x.clone(),
+        reserve_y: accounts[3].clone(),
+        user_token_in: user_token_in.clone(),
+        user_token_out: accounts[5].clone(),
+        token_x_mint: token_x_mint.clone(),
+        token_y_mint: token_y_mint.clone(),
+    })
+}
+
+pub fn decode_transaction(tx_details: &Value) -> Vec<DlmmSwap> {
+    instruction_parser::all_instructions(tx_details)
+        .iter()
+        .filter_map(|instruction| decode_instruction(instruction, tx_details))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    // Pubkeys from the registry tests, standing in for the pair's vaults and oracle
+    const RESERVE_X: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+    const RESERVE_Y: &str = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+    const ORACLE: &str = "BJE5MMbqXjVwjAF7oxwPYXnTXDyspzZyt4vwenNw5ruG";
+
+    fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    // SOL/USDC lb_pair account laid out as the program stores it: bin step 25, active bin -760
+    // (about 150 USDC per SOL once decimals are applied)
+    fn lb_pair_account() -> Vec<u8> {
+        let mut data = vec![0u8; LB_PAIR_ACCOUNT_LEN];
+        put(&mut data, 0, &LB_PAIR_ACCOUNT_DISCRIMINATOR);
+        put(&mut data, 8, &10_000u16.to_le_bytes()); // base_factor
+        put(&mut data, 10, &30u16.to_le_bytes()); // filter_period
+        put(&mut data, 12, &600u16.to_le_bytes()); // decay_period
+        put(&mut data, 16, &7_500u32.to_le_bytes()); // variable_fee_control
+        put(&mut data, 20, &150_000u32.to_le_bytes()); // max_volatility_accumulator
+        put(&mut data, 40, &10_000u32.to_le_bytes()); // volatility_accumulator
+        put(&mut data, 73, &25u16.to_le_bytes()); // bin_step_seed
+        put(&mut data, 76, &(-760i32).to_le_bytes());
+        put(&mut data, 80, &25u16.to_le_bytes());
+        put(&mut data, 88, &bs58::decode(WSOL_MINT).into_vec().unwrap());
+        put(&mut data, 120, &bs58::decode(USDC_MINT).into_vec().unwrap());
+        put(&mut data, 152, &bs58::decode(RESERVE_X).into_vec().unwrap());
+        put(&mut data, 184, &bs58::decode(RESERVE_Y).into_vec().unwrap());
+        put(&mut data, 216, &1_000_000u64.to_le_bytes()); // protocol fee x
+        put(&mut data, 224, &2_000u64.to_le_bytes()); // protocol fee y
+        put(&mut data, 552, &bs58::decode(ORACLE).into_vec().unwrap());
+        data
+    }
+
+    fn bin_array_account(lb_pair: &str, index: i64, bins: &[(usize, u64, u64)]) -> Vec<u8> {
+        let mut data = vec![0u8; BIN_ARRAY_ACCOUNT_LEN];
+        put(&mut data, 0, &BIN_ARRAY_ACCOUNT_DISCRIMINATOR);
+        put(&mut data, 8, &index.to_le_bytes());
+        put(&mut data, 24, &bs58::decode(lb_pair).into_vec().unwrap());
+        for (i, amount_x, amount_y) in bins {
+            let offset = BIN_ARRAY_HEADER_LEN + i * BIN_LEN;
+            put(&mut data, offset, &amount_x.to_le_bytes());
+            put(&mut data, offset + 8, &amount_y.to_le_bytes());
+        }
+        data
+    }
+
+    // Trimmed getTransaction ("json" encoding) of a 2 SOL -> USDC swap on the pair
+    fn swap_fixture(lb_pair: &str) -> Value {
+        let mut data = SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&2_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&295_000_000u64.to_le_bytes());
+
+        let keys = vec![
+            "UserWa11et111111111111111111111111111111111".to_string(), // 0 user
+            lb_pair.to_string(),                                        // 1 lb_pair
+            RESERVE_X.to_string(),                                      // 2 reserve x
+            RESERVE_Y.to_string(),                                      // 3 reserve y
+            "UserWsoLAccount1111111111111111111111111111".to_string(),  // 4 user token in
+            "UserUsdcAccount1111111111111111111111111111".to_string(),  // 5 user token out
+            WSOL_MINT.to_string(),                                      // 6
+            USDC_MINT.to_string(),                                      // 7
+            ORACLE.to_string(),                                         // 8
+            TOKEN_PROGRAM_ID.to_string(),                               // 9
+            "EventAuthority11111111111111111111111111111".to_string(),  // 10
+            METEORA_DLMM_PROGRAM_ID.to_string(),                        // 11
+            "BinArray11111111111111111111111111111111111".to_string(),  // 12
+        ];
+
+        json!({
+            "transaction": {
+                "message": {
+                    "accountKeys": keys,
+                    "instructions": [{
+                        "programIdIndex": 11,
+                        "accounts": [1, 11, 2, 3, 4, 5, 6, 7, 8, 11, 0, 9, 9, 10, 11, 12],
+                        "data": bs58::encode(&data).into_string()
+                    }]
+                }
+            },
+            "meta": {
+                "preTokenBalances": [
+                    { "accountIndex": 4, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "3000000000", "decimals": 9 } },
+                    { "accountIndex": 5, "mint": USDC_MINT, "uiTokenAmount": { "amount": "0", "decimals": 6 } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 4, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "1000000000", "decimals": 9 } },
+                    { "accountIndex": 5, "mint": USDC_MINT, "uiTokenAmount": { "amount": "299100000", "decimals": 6 } }
+                ],
+                "logMessages": []
+            }
+        })
+    }
+
+    // Bin step 10 around bin 0 (price 1.0), output liquidity in the active bin and one more
+    fn flat_pair(base_factor: u16) -> LbPairState {
+        let mut pair = LbPairState::decode(&lb_pair_account()).unwrap();
+        pair.active_id = 0;
+        pair.bin_step = 10;
+        pair.base_factor = base_factor;
+        pair.variable_fee_control = 0;
+        pair
+    }
+
+    #[test]
+    fn test_decodes_lb_pair_account() {
+        let pair = LbPairState::decode(&lb_pair_account()).unwrap();
+        assert_eq!(pair.active_id, -760);
+        assert_eq!(pair.bin_step, 25);
+        assert_eq!((pair.token_x_mint.as_str(), pair.token_y_mint.as_str()), (WSOL_MINT, USDC_MINT));
+        assert_eq!((pair.reserve_x.as_str(), pair.reserve_y.as_str()), (RESERVE_X, RESERVE_Y));
+        assert_eq!(pair.oracle, ORACLE);
+
+        // 0.25% base fee; a volatility accumulator of 10,000 adds (10,000 * 25)^2 * 7,500 / 1e11
+        assert_eq!(pair.fee_rate(), 2_500_000 + 4_688);
+        // Raw USDC per raw SOL: 150 USDC / 1e9 lamports * 1e6
+        let price = pair.bin_price(pair.active_id);
+        assert!((price - 0.15).abs() < 0.001, "price {}", price);
+
+        assert_eq!(pair.reserves(101_000_000_000, 15_000_002_000), (100_999_000_000, 15_000_000_000));
+        let (virtual_x, virtual_y) = pair.virtual_reserves(100_000_000_000, 15_000_000_000);
+        assert!((virtual_y as f64 / virtual_x as f64 - price).abs() < 1e-6);
+
+        let mut data = lb_pair_account();
+        data[0] ^= 0xff;
+        assert!(LbPairState::decode(&data).is_err());
+        assert!(LbPairState::decode(&lb_pair_account()[..400]).is_err());
+    }
+
+    #[test]
+    fn test_decodes_bin_array_account() {
+        let lb_pair = Pubkey::new_unique().to_string();
+        let array = BinArray::decode(&bin_array_account(&lb_pair, -11, &[(10, 0, 5_000), (11, 7_000, 0)])).unwrap();
+        assert_eq!((array.index, array.lb_pair.as_str()), (-11, lb_pair.as_str()));
+
+        // Array -11 covers bins -770..=-701
+        let bins: BTreeMap<i32, Bin> = array.bins_by_id().collect();
+        assert_eq!(bins.len(), 70);
+        assert_eq!(bins[&-760], Bin { amount_x: 0, amount_y: 5_000 });
+        assert_eq!(bins[&-759], Bin { amount_x: 7_000, amount_y: 0 });
+        assert_eq!((bin_array_index(-760), bin_array_index(-1), bin_array_index(0), bin_array_index(69)), (-11, -1, 0, 0));
+    }
+
+    #[test]
+    fn test_decoded_swap_matches_balance_meta() {
+        let lb_pair = Pubkey::new_unique().to_string();
+        let tx = swap_fixture(&lb_pair);
+        let swap = decode_transaction(&tx).pop().expect("dlmm swap decoded");
+
+        assert_eq!(swap.lb_pair, lb_pair);
+        assert_eq!(swap.swap_for_y, Some(true));
+        assert_eq!((swap.input_mint(), swap.output_mint()), (Some(WSOL_MINT), Some(USDC_MINT)));
+        assert_eq!(swap.min_amount_out, 295_000_000);
+        let spent = -instruction_parser::token_balance_change(&tx, &swap.user_token_in).unwrap();
+        assert_eq!(spent, swap.amount_in as i128);
+
+        // Without balance metadata the side can't be told
+        let mut bare = tx.clone();
+        bare["meta"] = json!({ "logMessages": [] });
+        assert_eq!(decode_transaction(&bare).pop().unwrap().swap_for_y, None);
+    }
+
+    #[test]
+    fn test_quote_walks_bins_in_the_swap_direction() {
+        let bins: BTreeMap<i32, Bin> = [
+            (-1, Bin { amount_x: 0, amount_y: 1_000 }),
+            (0, Bin { amount_x: 0, amount_y: 1_000 }),
+            (1, Bin { amount_x: 1_000, amount_y: 0 }),
+        ].into_iter().collect();
+
+        // X in: all of bin 0 at 1.0, then 500 at bin -1's price of 1/1.001
+        let pair = flat_pair(0);
+        let quote = quote(&pair, &bins, 1_500, true);
+        assert_eq!((quote.amount_in, quote.amount_out, quote.fee, quote.end_bin), (1_500, 1_499, 0, -1));
+
+        // Y in: bin 0 holds no X, bin 1 sells at 1.001
+        let quote = super::quote(&pair, &bins, 500, false);
+        assert_eq!((quote.amount_out, quote.end_bin), (499, 1));
+
+        // More than the loaded bins hold: everything they have, and the input left over
+        let quote = super::quote(&pair, &bins, 5_000, true);
+        assert_eq!(quote.amount_out, 2_000);
+        assert!(quote.amount_in < 5_000);
+
+        // 0.1% fee, rounded up, comes off the input first
+        let quote = super::quote(&flat_pair(10_000), &bins, 500, true);
+        assert_eq!((quote.fee, quote.amount_out), (1, 499));
+    }
+
+    #[test]
+    fn test_builds_swap_instruction() {
+        let lb_pair = Pubkey::new_unique().to_string();
+        let pair = LbPairState::decode(&lb_pair_account()).unwrap();
+        let user = Pubkey::new_unique();
+        let instruction = swap_instruction(&lb_pair, &pair, &user, 2_000_000_000, 295_000_000, true).unwrap();
+
+        assert_eq!(instruction.program_id.to_string(), METEORA_DLMM_PROGRAM_ID);
+        assert_eq!(instruction.accounts.len(), SWAP_MIN_ACCOUNTS + SWAP_BIN_ARRAYS);
+        assert_eq!(instruction.accounts[0].pubkey.to_string(), lb_pair);
+        assert_eq!(instruction.accounts[2].pubkey.to_string(), RESERVE_X);
+        assert!(instruction.accounts[10].is_signer && instruction.accounts[10].pubkey == user);
+        // Selling SOL walks down from the active bin's array
+        assert_eq!(instruction.accounts[15].pubkey.to_string(), bin_array_address(&lb_pair, -11).unwrap());
+        assert_eq!(instruction.accounts[16].pubkey.to_string(), bin_array_address(&lb_pair, -12).unwrap());
+
+        // The instruction decodes back to the same swap
+        let keys: Vec<String> = instruction.accounts.iter().map(|meta| meta.pubkey.to_string()).collect();
+        let parsed = ParsedInstruction {
+            program_id: METEORA_DLMM_PROGRAM_ID.to_string(),
+            accounts: keys,
+            data: Some(bs58::encode(&instruction.data).into_string()),
+        };
+        let swap = decode_instruction(&parsed, &json!({})).unwrap();
+        assert_eq!((swap.amount_in, swap.min_amount_out), (2_000_000_000, 295_000_000));
+        assert_eq!(swap.token_x_mint, WSOL_MINT);
+    }
+}
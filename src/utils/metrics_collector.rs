@@ -1,9 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, RwLock};
 use serde::{Deserialize, Serialize};
+use crate::config::Config;
 use crate::logging::Logger;
-use crate::utils::mev_strategies::{MevStrategyType, MevStrategyResult};
+use crate::utils::mev_strategies::{MevStrategyType, MevStrategyResult, StrategyThresholds};
+use crate::utils::bounded_cache::CacheStats;
+use crate::utils::competition_tracker::CompetitionSnapshot;
+use crate::utils::leader_schedule::LeaderLandingRate;
+use crate::utils::tip_history::{now_unix, TipBucketRate};
+use crate::utils::windowed_metrics::{WindowTotals, WindowedMetrics};
+use crate::utils::bucket_histogram::BucketHistogram;
+use crate::utils::alert_sinks::AlertDispatcher;
+use crate::utils::opportunity_outcomes::{OpportunityOutcome, OpportunityOutcomes, OpportunityStage, OutcomeStats};
+use crate::utils::false_positive_reducer::OpportunityFilteringResult;
+use crate::utils::filter_decisions::{DecisionOutcome, FilterDecision, FilterDecisionLog, OutcomeSource};
+use crate::utils::profit_calibration::ProfitCalibration;
+use crate::utils::simulation_accuracy::SimulationAccuracy;
+use crate::utils::token_pnl::{TokenPnl, TokenPnlBook, TokenPnlLeaders};
+use crate::utils::pool_blacklist::BlacklistEntry;
+use crate::utils::bundle_tracker::ResolvedBundle;
+use crate::utils::enhanced_transaction_simulator::OpportunityDetails;
+use crate::rpc::rate_limit::RateBudget;
+use crate::rpc::circuit_breaker::BreakerStatus;
+use crate::utils::risk_controls::risk_utils::CircuitState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpportunityMetrics {
@@ -33,6 +55,11 @@ pub struct SystemMetrics {
     pub total_opportunities_detected: u64,
     pub total_opportunities_evaluated: u64,
     pub total_opportunities_executed: u64,
+    pub total_opportunities_expired: u64,
+    pub total_opportunities_strategy_disabled: u64,
+    pub total_analyses_skipped: u64,
+    pub total_stale_notifications_dropped: u64,
+    pub total_risk_paused: u64,
     pub total_successful_executions: u64,
     pub total_profit: f64,
     pub total_fees_paid: f64,
@@ -42,6 +69,99 @@ pub struct SystemMetrics {
     pub avg_profit_per_success: f64,
     pub avg_execution_time_ms: f64,
     pub start_time: std::time::SystemTime,
+    #[serde(default)]
+    pub dry_run: bool, // Results come from stubbed submissions and aren't live PnL
+    #[serde(default)]
+    pub total_bundles_landed: u64,
+    #[serde(default)]
+    pub total_bundles_failed: u64,  // Includes bundles that expired without landing
+    #[serde(default)]
+    pub total_bundles_throttled: u64, // Held back by our rate limit or a 429; never judged by the engine
+    #[serde(default)]
+    pub total_rpc_failovers: u64, // RPC requests retried on another endpoint after their first choice failed
+    #[serde(default)]
+    pub total_rpc_rate_limited: u64, // 429s and provider rate-limit errors received
+    #[serde(default)]
+    pub total_rpc_shed: u64, // Low-priority RPC requests turned away while an endpoint was throttling
+    #[serde(default)]
+    pub total_rpc_limited_locally: u64, // RPC requests held back by our own per-endpoint budgets
+    #[serde(default)]
+    pub total_rpc_breaker_opens: u64, // Times an RPC endpoint's circuit breaker opened
+    #[serde(default)]
+    pub total_notifications_rate_limited: u64, // Notifications dropped because fetching the target was rate limited
+    #[serde(default)]
+    pub kill_switch_engaged: bool,
+    #[serde(default)]
+    pub drawdown_percent: f64, // Below the balance high-water mark
+    #[serde(default)]
+    pub drawdown_halted: bool,
+    #[serde(default)]
+    pub circuit_breaker_state: u8, // 0 closed, 1 half-open, 2 open
+    #[serde(default)]
+    pub total_circuit_breaker_skips: u64, // Queued opportunities not executed while the breaker was open
+    #[serde(default)]
+    pub total_execution_time_ms: u64, // Sum over executions
+    #[serde(default)]
+    pub total_simulation_consensus_checks: u64, // Simulations cross-checked on at least two RPC endpoints
+    #[serde(default)]
+    pub total_simulation_disagreements: u64, // Of those, the ones where the endpoints disagreed
+    #[serde(default = "std::time::SystemTime::now")]
+    pub process_start_time: std::time::SystemTime, // Uptime; start_time carries over restored sessions
+}
+
+impl SystemMetrics {
+    // Zeroed counters from `start_time`, which is also the process start
+    pub fn new(start_time: std::time::SystemTime, dry_run: bool) -> Self {
+        Self {
+            total_opportunities_detected: 0,
+            total_opportunities_evaluated: 0,
+            total_opportunities_executed: 0,
+            total_opportunities_expired: 0,
+            total_opportunities_strategy_disabled: 0,
+            total_analyses_skipped: 0,
+            total_stale_notifications_dropped: 0,
+            total_risk_paused: 0,
+            total_successful_executions: 0,
+            total_profit: 0.0,
+            total_fees_paid: 0.0,
+            total_tips_paid: 0.0,
+            false_positive_rate: 0.0,
+            execution_success_rate: 0.0,
+            avg_profit_per_success: 0.0,
+            avg_execution_time_ms: 0.0,
+            start_time,
+            dry_run,
+            total_bundles_landed: 0,
+            total_bundles_failed: 0,
+            total_bundles_throttled: 0,
+            total_rpc_failovers: 0,
+            total_rpc_rate_limited: 0,
+            total_rpc_shed: 0,
+            total_rpc_limited_locally: 0,
+            total_rpc_breaker_opens: 0,
+            total_notifications_rate_limited: 0,
+            kill_switch_engaged: false,
+            drawdown_percent: 0.0,
+            drawdown_halted: false,
+            circuit_breaker_state: 0,
+            total_circuit_breaker_skips: 0,
+            total_execution_time_ms: 0,
+            total_simulation_consensus_checks: 0,
+            total_simulation_disagreements: 0,
+            process_start_time: start_time,
+        }
+    }
+}
+
+// Averages are derived from a sum and the count that owns it, so they can't divide by zero or
+// depend on which record_* call came first
+fn average(sum: f64, count: u64) -> f64 {
+    if count > 0 { sum / count as f64 } else { 0.0 }
+}
+
+// A NaN or infinite amount from upstream would otherwise stick in every sum it's added to
+fn finite_or_zero(value: f64) -> f64 {
+    if value.is_finite() { value } else { 0.0 }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,7 +173,7 @@ pub struct StrategyMetrics {
     pub total_fees: f64,
     pub total_tips: f64,
     pub avg_profit_per_execution: f64,
-    pub avg_execution_time_ms: f64,
+    pub avg_execution_time_ms: f64, // Mean of the strategy's execution histogram
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,7 +181,7 @@ pub struct RpcMetrics {
     pub endpoint_type: String,
     pub total_requests: u64,
     pub successful_requests: u64,
-    pub avg_response_time_ms: f64,
+    pub avg_response_time_ms: f64, // Mean of the endpoint's response time histogram
     pub error_rate: f64,
     pub total_bytes_sent: u64,
     pub total_bytes_received: u64,
@@ -83,6 +203,9 @@ pub enum AlertType {
     HighLatency,
     LowSuccessRate,
     UnexpectedError,
+    DrawdownHalt,
+    ProfitCalibration, // Realized profit running well under the estimates
+    SimulationAccuracy, // Simulations no longer predicting what landed executions realize
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,12 +216,202 @@ pub enum AlertSeverity {
     Critical,
 }
 
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+// Per-opportunity timestamps from notification to bundle submission
+#[derive(Debug, Clone)]
+pub struct OpportunityTiming {
+    pub correlation_id: u64, // Joins this opportunity's stage outcomes in OpportunityOutcomes
+    pub detected_at: Instant,
+    pub fetched_at: Option<Instant>,
+    pub evaluated_at: Option<Instant>,
+    pub simulated_at: Option<Instant>,
+    pub dequeued_at: Option<Instant>,
+    pub submitted_at: Option<Instant>,
+}
+
+impl OpportunityTiming {
+    pub fn new(detected_at: Instant) -> Self {
+        Self {
+            correlation_id: NEXT_CORRELATION_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            detected_at,
+            fetched_at: None,
+            evaluated_at: None,
+            simulated_at: None,
+            dequeued_at: None,
+            submitted_at: None,
+        }
+    }
+    
+    pub fn mark_fetched(&mut self) {
+        self.fetched_at = Some(Instant::now());
+    }
+    
+    pub fn mark_evaluated(&mut self) {
+        self.evaluated_at = Some(Instant::now());
+    }
+    
+    pub fn mark_simulated(&mut self) {
+        self.simulated_at = Some(Instant::now());
+    }
+    
+    pub fn mark_dequeued(&mut self) {
+        self.dequeued_at = Some(Instant::now());
+    }
+    
+    pub fn mark_submitted(&mut self) {
+        self.submitted_at = Some(Instant::now());
+    }
+    
+    // Durations in ms for each stage whose start and end were both recorded
+    pub fn stage_durations(&self) -> Vec<(&'static str, u64)> {
+        let stages = [
+            ("fetch", Some(self.detected_at), self.fetched_at),
+            ("evaluate", self.fetched_at, self.evaluated_at),
+            ("simulate", self.evaluated_at, self.simulated_at),
+            ("queue_wait", self.simulated_at, self.dequeued_at),
+            ("submit", self.dequeued_at, self.submitted_at),
+            ("detection_to_submission", Some(self.detected_at), self.submitted_at),
+        ];
+        
+        stages.iter()
+            .filter_map(|(name, start, end)| match (start, end) {
+                (Some(start), Some(end)) => Some((*name, end.saturating_duration_since(*start).as_millis() as u64)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// Bounded sample store used to compute latency percentiles
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    samples: VecDeque<u64>,
+    max_samples: usize,
+}
+
+impl LatencyHistogram {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+    
+    pub fn record(&mut self, value_ms: u64) {
+        if self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value_ms);
+    }
+    
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+    
+    // Nearest-rank percentile, p in [0.0, 1.0]
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((p.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize).max(1);
+        Some(sorted[rank - 1])
+    }
+}
+
+// Fixed-bucket latency distributions. Unlike LatencyHistogram they cover every sample since
+// the last reset, and the averages reported elsewhere are their means
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyHistograms {
+    pub detection_to_submission: BucketHistogram,
+    pub execution: BucketHistogram,
+    pub execution_by_strategy: HashMap<String, BucketHistogram>,
+    pub rpc_response: HashMap<String, BucketHistogram>, // By endpoint type
+}
+
+impl LatencyHistograms {
+    pub fn new() -> Self {
+        Self {
+            detection_to_submission: BucketHistogram::latency_ms(),
+            execution: BucketHistogram::latency_ms(),
+            execution_by_strategy: HashMap::new(),
+            rpc_response: HashMap::new(),
+        }
+    }
+}
+
+impl Default for LatencyHistograms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Execution phase durations of one strategy, including the total as phase "total"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseLatencySummary {
+    pub strategy: String,
+    pub phase: String,
+    pub count: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageLatencySummary {
+    pub stage: String,
+    pub count: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+}
+
+// Per WebSocket endpoint delivery stats: how often it delivered a signature first and,
+// when it lost the race, how far behind the winner it was
+#[derive(Debug, Clone)]
+pub struct WsEndpointStats {
+    pub first_deliveries: u64,
+    pub late_deliveries: u64,
+    pub behind_ms: LatencyHistogram,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsEndpointSummary {
+    pub endpoint: String,
+    pub first_deliveries: u64,
+    pub late_deliveries: u64,
+    pub p50_behind_ms: u64,
+    pub p95_behind_ms: u64,
+}
+
 pub struct MetricsCollector {
     system_metrics: Arc<RwLock<SystemMetrics>>,
     strategy_metrics: Arc<RwLock<HashMap<String, StrategyMetrics>>>,
     rpc_metrics: Arc<RwLock<HashMap<String, RpcMetrics>>>,
     opportunity_history: Arc<RwLock<Vec<OpportunityMetrics>>>,
     alert_history: Arc<RwLock<Vec<AlertEvent>>>,
+    stage_latencies: Arc<RwLock<HashMap<String, LatencyHistogram>>>,
+    ws_endpoint_stats: Arc<RwLock<HashMap<String, WsEndpointStats>>>,
+    cache_stats: Arc<RwLock<HashMap<String, CacheStats>>>,
+    filter_rejections: Arc<RwLock<HashMap<String, u64>>>, // "<strategy>/<reason>" -> count
+    phase_latencies: Arc<RwLock<HashMap<(String, String), LatencyHistogram>>>, // (strategy, phase)
+    competition: Arc<RwLock<Option<CompetitionSnapshot>>>,
+    disabled_strategies: Arc<RwLock<Vec<MevStrategyType>>>, // Sitting out a failure cooldown
+    tip_bucket_rates: Arc<RwLock<Vec<TipBucketRate>>>,
+    leader_landing_rates: Arc<RwLock<Vec<LeaderLandingRate>>>,
+    rpc_rate_budgets: Arc<RwLock<Vec<RateBudget>>>,
+    rpc_breakers: Arc<RwLock<Vec<BreakerStatus>>>,
+    token_exposure_rejections: Arc<RwLock<HashMap<String, u64>>>, // mint -> opportunities skipped at the exposure cap
+    blacklisted_pools: Arc<RwLock<Vec<BlacklistEntry>>>,
+    strategy_thresholds: Arc<RwLock<Option<StrategyThresholds>>>, // Effective values the executor runs with
+    windowed_metrics: Arc<RwLock<WindowedMetrics>>, // Per-minute totals behind get_performance_in_window
+    alert_dispatcher: Option<Arc<AlertDispatcher>>, // Discord/Telegram delivery; alerts are only logged without it
+    opportunity_outcomes: OpportunityOutcomes, // Per-opportunity stages behind the false positive rate
+    filter_decisions: FilterDecisionLog, // False positive reducer verdicts and their outcomes, for threshold recalibration
+    latency_histograms: Arc<RwLock<LatencyHistograms>>,
+    token_pnl: Arc<RwLock<TokenPnlBook>>, // Realized PnL per mint traded
+    profit_calibration: ProfitCalibration, // Realized vs estimated profit, per opportunity type and DEX
+    simulation_accuracy: SimulationAccuracy, // Simulated vs realized net profit of landed bundles, per opportunity type and DEX
     
     // Monitoring thresholds
     pub balance_drop_threshold: f64,    // Percentage drop to trigger alert
@@ -108,26 +421,35 @@ pub struct MetricsCollector {
 }
 
 impl MetricsCollector {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         Ok(Self {
-            system_metrics: Arc::new(RwLock::new(SystemMetrics {
-                total_opportunities_detected: 0,
-                total_opportunities_evaluated: 0,
-                total_opportunities_executed: 0,
-                total_successful_executions: 0,
-                total_profit: 0.0,
-                total_fees_paid: 0.0,
-                total_tips_paid: 0.0,
-                false_positive_rate: 0.0,
-                execution_success_rate: 0.0,
-                avg_profit_per_success: 0.0,
-                avg_execution_time_ms: 0.0,
-                start_time: std::time::SystemTime::now(),
-            })),
+            system_metrics: Arc::new(RwLock::new(SystemMetrics::new(std::time::SystemTime::now(), config.dry_run))),
             strategy_metrics: Arc::new(RwLock::new(HashMap::new())),
             rpc_metrics: Arc::new(RwLock::new(HashMap::new())),
             opportunity_history: Arc::new(RwLock::new(Vec::new())),
             alert_history: Arc::new(RwLock::new(Vec::new())),
+            stage_latencies: Arc::new(RwLock::new(HashMap::new())),
+            ws_endpoint_stats: Arc::new(RwLock::new(HashMap::new())),
+            cache_stats: Arc::new(RwLock::new(HashMap::new())),
+            filter_rejections: Arc::new(RwLock::new(HashMap::new())),
+            phase_latencies: Arc::new(RwLock::new(HashMap::new())),
+            competition: Arc::new(RwLock::new(None)),
+            disabled_strategies: Arc::new(RwLock::new(Vec::new())),
+            tip_bucket_rates: Arc::new(RwLock::new(Vec::new())),
+            leader_landing_rates: Arc::new(RwLock::new(Vec::new())),
+            rpc_rate_budgets: Arc::new(RwLock::new(Vec::new())),
+            rpc_breakers: Arc::new(RwLock::new(Vec::new())),
+            token_exposure_rejections: Arc::new(RwLock::new(HashMap::new())),
+            blacklisted_pools: Arc::new(RwLock::new(Vec::new())),
+            strategy_thresholds: Arc::new(RwLock::new(None)),
+            windowed_metrics: Arc::new(RwLock::new(WindowedMetrics::new())),
+            alert_dispatcher: None,
+            opportunity_outcomes: OpportunityOutcomes::new(),
+            filter_decisions: FilterDecisionLog::new(),
+            latency_histograms: Arc::new(RwLock::new(LatencyHistograms::new())),
+            token_pnl: Arc::new(RwLock::new(TokenPnlBook::new(config.metrics.token_pnl_max_mints))),
+            profit_calibration: ProfitCalibration::from_config(&config.metrics),
+            simulation_accuracy: SimulationAccuracy::from_config(&config.simulation)?,
             balance_drop_threshold: 0.1,      // 10% drop
             consecutive_failures_threshold: 5, // 5 consecutive failures
             success_rate_threshold: 0.7,      // 70% success rate
@@ -135,49 +457,336 @@ impl MetricsCollector {
         })
     }
     
+    pub fn with_alert_dispatcher(mut self, dispatcher: AlertDispatcher) -> Self {
+        self.alert_dispatcher = Some(Arc::new(dispatcher));
+        self
+    }
+    
+    // Adds to the current minute's bucket
+    async fn record_in_window(&self, update: impl FnOnce(&mut WindowTotals)) {
+        if let Some(bucket) = self.windowed_metrics.write().await.bucket_at(now_unix()) {
+            update(bucket);
+        }
+    }
+    
+    // Where the opportunity behind `correlation_id` got to before execution
+    // Also counts the opportunity as detected on its first stage and as evaluated once the
+    // filters have queued or rejected it
+    pub async fn record_opportunity_stage(&self, correlation_id: u64, opportunity: &OpportunityDetails, stage: OpportunityStage) {
+        let evaluated = matches!(stage, OpportunityStage::Queued | OpportunityStage::Filtered(_));
+        if self.opportunity_outcomes.record_stage(correlation_id, opportunity, stage).await {
+            self.record_opportunity_detected().await;
+        }
+        if evaluated {
+            self.record_opportunity_evaluated().await;
+        }
+    }
+    
+    // The false positive reducer's verdict on the opportunity behind `correlation_id`; labeled once
+    // the opportunity's outcome is final
+    pub async fn record_filter_decision(&self, correlation_id: u64, opportunity: &OpportunityDetails, result: &OpportunityFilteringResult) {
+        self.filter_decisions.record(FilterDecision {
+            correlation_id,
+            opportunity_type: format!("{:?}", opportunity.opportunity_type),
+            should_execute: result.should_execute,
+            filtered_reason: result.filtered_reason.clone(),
+            features: result.features.clone(),
+            fixed_rejection: result.fixed_rejection,
+            outcome: None,
+            decided_at: now_unix(),
+        }).await;
+    }
+    
+    // How a rejected opportunity would have turned out, by simulation since it never went out
+    pub async fn record_rejection_outcome(&self, correlation_id: u64, profitable: bool, source: OutcomeSource) {
+        self.filter_decisions.label(correlation_id, DecisionOutcome { executed: false, profitable, source }).await;
+    }
+    
+    // Executions under DRY_RUN are labeled as such; their submissions were stubbed out
+    async fn outcome_source(&self) -> OutcomeSource {
+        if self.system_metrics.read().await.dry_run { OutcomeSource::DryRun } else { OutcomeSource::Live }
+    }
+    
+    // Shared with the false positive reducer, which recalibrates its thresholds from them
+    pub fn filter_decisions(&self) -> FilterDecisionLog {
+        self.filter_decisions.clone()
+    }
+    
+    // Attributes an executed opportunity's result to the mints it traded; a pending bundle's
+    // success is added through record_bundle_outcome once it lands
+    pub async fn record_token_outcome(&self, opportunity: &OpportunityDetails, result: &MevStrategyResult) {
+        let (profit, fees, tips) = (finite_or_zero(result.profit), finite_or_zero(result.fees_paid), finite_or_zero(result.tip_paid));
+        self.token_pnl.write().await.record(&opportunity.mints(), result.success, profit, fees, tips);
+    }
+    
+    // Most profitable first
+    pub async fn get_token_pnl(&self) -> Vec<TokenPnl> {
+        self.token_pnl.read().await.tokens()
+    }
+    
+    pub async fn get_token_pnl_leaders(&self, limit: usize) -> TokenPnlLeaders {
+        self.token_pnl.read().await.leaders(limit)
+    }
+    
+    // The execution result of a queued opportunity; a pending one is settled through
+    // record_bundle_outcome, and a bundle that never lands makes it a false positive
+    pub async fn record_execution_outcome(&self, correlation_id: u64, result: &MevStrategyResult) {
+        if let Some(outcome) = self.opportunity_outcomes.record_execution(correlation_id, result).await {
+            self.filter_decisions.record_outcome(&outcome, self.outcome_source().await).await;
+        }
+        self.refresh_false_positive_rate().await;
+    }
+    
+    // Compares a reconciled success against its estimate. The estimate is gross, so the fees and
+    // tip come back onto the realized profit before the two are compared
+    async fn record_profit_calibration(&self, outcome: &OpportunityOutcome, realized_profit: f64, costs: f64) {
+        let OpportunityStage::Executed { success: true, .. } = outcome.stage else { return };
+        let realized = finite_or_zero(realized_profit) + finite_or_zero(costs);
+        let crossed = self.profit_calibration
+            .record(&outcome.opportunity_type, &outcome.dex, outcome.estimated_profit, realized)
+            .await;
+        let config = self.profit_calibration.config();
+        for factor in crossed {
+            self.trigger_alert(AlertType::ProfitCalibration,
+                             AlertSeverity::Warning,
+                             format!("Profit calibration for {} {}: realizing {:.2}x the estimates over the last {} trades, below the {:.2} floor",
+                                     factor.scope, factor.key, factor.factor, factor.samples, config.alert_floor),
+                             Some(factor.factor)).await;
+        }
+    }
+    
+    // Shared with the evaluator and the strategy executor, which scale estimates by it
+    pub fn profit_calibration(&self) -> ProfitCalibration {
+        self.profit_calibration.clone()
+    }
+    
+    // Scores a landed bundle's simulation against what it realized (see
+    // MevStrategyExecutor::poll_bundle_outcomes), under the opportunity's type and DEX
+    async fn record_simulation_accuracy(&self, outcome: &OpportunityOutcome, accuracy: f64) {
+        let crossed = self.simulation_accuracy.record(&outcome.opportunity_type, &outcome.dex, accuracy).await;
+        let config = self.simulation_accuracy.config();
+        for score in crossed {
+            self.trigger_alert(AlertType::SimulationAccuracy,
+                             AlertSeverity::Warning,
+                             format!("Simulation accuracy for {} {}: {:.2} over the last {} landed bundles, below the {:.2} floor",
+                                     score.scope, score.key, score.accuracy, score.samples, config.alert_floor),
+                             Some(score.accuracy)).await;
+        }
+    }
+    
+    pub fn simulation_accuracy(&self) -> SimulationAccuracy {
+        self.simulation_accuracy.clone()
+    }
+    
+    async fn refresh_false_positive_rate(&self) {
+        let false_positive_rate = self.calculate_false_positive_rate().await;
+        self.system_metrics.write().await.false_positive_rate = false_positive_rate;
+    }
+    
     pub async fn record_opportunity_detected(&self) {
+        self.record_in_window(|window| window.opportunities_detected += 1).await;
         let mut metrics = self.system_metrics.write().await;
         metrics.total_opportunities_detected += 1;
     }
     
     pub async fn record_opportunity_evaluated(&self) {
+        self.record_in_window(|window| window.opportunities_evaluated += 1).await;
         let mut metrics = self.system_metrics.write().await;
         metrics.total_opportunities_evaluated += 1;
     }
     
+    // Opportunities that passed validation but missed their deadline in the execution queue
+    pub async fn record_opportunities_expired(&self, count: u64) {
+        self.record_in_window(|window| window.opportunities_expired += count).await;
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_opportunities_expired += count;
+    }
+    
+    // Opportunities dropped because their strategy type isn't enabled via STRATEGY
+    pub async fn record_opportunity_strategy_disabled(&self) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_opportunities_strategy_disabled += 1;
+    }
+    
+    // Notifications skipped because no analysis slot freed up in time
+    pub async fn record_analysis_skipped(&self) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_analyses_skipped += 1;
+    }
+    
+    // Notifications discarded for exceeding MAX_NOTIFICATION_AGE_MS before any RPC work
+    pub async fn record_stale_notification_dropped(&self) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_stale_notifications_dropped += 1;
+    }
+    
+    // Notifications skipped without any RPC work because risk controls paused trading
+    pub async fn record_risk_paused(&self) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_risk_paused += 1;
+    }
+    
+    pub async fn record_kill_switch(&self, engaged: bool) {
+        self.system_metrics.write().await.kill_switch_engaged = engaged;
+    }
+    
+    pub async fn record_circuit_breaker(&self, state: CircuitState) {
+        self.system_metrics.write().await.circuit_breaker_state = state.as_gauge();
+    }
+    
+    pub async fn record_circuit_breaker_skip(&self) {
+        self.system_metrics.write().await.total_circuit_breaker_skips += 1;
+    }
+    
+    // A simulation compared across RPC endpoints; see EnhancedTransactionSimulator
+    pub async fn record_simulation_consensus(&self, agreed: bool) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_simulation_consensus_checks += 1;
+        if !agreed {
+            metrics.total_simulation_disagreements += 1;
+        }
+    }
+    
+    // Alerts when a drawdown halt starts
+    pub async fn record_drawdown(&self, drawdown_percent: f64, halted: bool) {
+        let was_halted = {
+            let mut metrics = self.system_metrics.write().await;
+            metrics.drawdown_percent = finite_or_zero(drawdown_percent);
+            std::mem::replace(&mut metrics.drawdown_halted, halted)
+        };
+        if halted && !was_halted {
+            self.trigger_alert(AlertType::DrawdownHalt,
+                             AlertSeverity::Critical,
+                             format!("Trading halted at a {:.2}% drawdown from the balance high-water mark", drawdown_percent),
+                             Some(drawdown_percent)).await;
+        }
+    }
+    
     pub async fn record_strategy_execution(&self, result: &MevStrategyResult) {
+        let (profit, fees, tips) = (finite_or_zero(result.profit), finite_or_zero(result.fees_paid), finite_or_zero(result.tip_paid));
+        self.record_in_window(|window| {
+            window.executions += 1;
+            if result.success {
+                window.successes += 1;
+                window.profit += profit;
+            }
+            window.fees += fees;
+            window.tips += tips;
+            window.execution_time_ms += result.execution_time_ms;
+        }).await;
+        
+        let strategy_key = format!("{:?}", result.strategy_type);
+        let (avg_execution_time_ms, strategy_avg_execution_time_ms) = {
+            let mut histograms = self.latency_histograms.write().await;
+            let execution_time_ms = result.execution_time_ms as f64;
+            histograms.execution.record(execution_time_ms);
+            let by_strategy = histograms.execution_by_strategy.entry(strategy_key)
+                .or_insert_with(BucketHistogram::latency_ms);
+            by_strategy.record(execution_time_ms);
+            let strategy_mean = by_strategy.mean();
+            (histograms.execution.mean(), strategy_mean)
+        };
+        
         let mut sys_metrics = self.system_metrics.write().await;
         sys_metrics.total_opportunities_executed += 1;
         
         if result.success {
             sys_metrics.total_successful_executions += 1;
-            sys_metrics.total_profit += result.profit;
+            sys_metrics.total_profit += profit;
         }
         
-        sys_metrics.total_fees_paid += result.fees_paid;
-        sys_metrics.total_tips_paid += result.tip_paid;
+        sys_metrics.total_fees_paid += fees;
+        sys_metrics.total_tips_paid += tips;
+        sys_metrics.total_execution_time_ms += result.execution_time_ms;
         
-        // Update success rate
-        if sys_metrics.total_opportunities_executed > 0 {
-            sys_metrics.execution_success_rate = 
-                sys_metrics.total_successful_executions as f64 / 
-                sys_metrics.total_opportunities_executed as f64;
-        }
+        sys_metrics.execution_success_rate =
+            average(sys_metrics.total_successful_executions as f64, sys_metrics.total_opportunities_executed);
+        sys_metrics.avg_profit_per_success = average(sys_metrics.total_profit, sys_metrics.total_successful_executions);
+        sys_metrics.avg_execution_time_ms = avg_execution_time_ms;
+        drop(sys_metrics);
+        
+        // Record strategy-specific metrics
+        self.record_strategy_specific_metrics(result, strategy_avg_execution_time_ms).await;
+        self.record_execution_phases(result).await;
+    }
+    
+    // Final landing verdict for a bundle whose result was booked as pending on acceptance: its
+    // execution, fees and tip were counted then, and a landed one adds its success and profit now
+    pub async fn record_bundle_outcome(&self, bundle: &ResolvedBundle) {
+        let profit = finite_or_zero(bundle.profit);
+        // The success lands in the minute it's learned, not the one the execution was booked in
+        self.record_in_window(|window| {
+            if bundle.landed() {
+                window.bundles_landed += 1;
+                window.successes += 1;
+                window.profit += profit;
+            } else {
+                window.bundles_failed += 1;
+            }
+        }).await;
         
-        // Update average profit per success
-        if sys_metrics.total_successful_executions > 0 {
-            sys_metrics.avg_profit_per_success = 
-                sys_metrics.total_profit / 
-                sys_metrics.total_successful_executions as f64;
+        if let Some(outcome) = self.opportunity_outcomes.record_bundle_resolved(&bundle.bundle_id, bundle.landed()).await {
+            // Only what the landed transactions did to the wallet, not the profit the strategy expected
+            if let Some(realized_profit) = bundle.realized_profit {
+                self.record_profit_calibration(&outcome, realized_profit, bundle.fees + bundle.tip).await;
+            }
+            if let Some(accuracy) = bundle.simulation_accuracy {
+                self.record_simulation_accuracy(&outcome, accuracy).await;
+            }
+            self.filter_decisions.record_outcome(&outcome, self.outcome_source().await).await;
+            self.refresh_false_positive_rate().await;
         }
         
-        // Update to be implemented in record_opportunity_result
+        let mut sys_metrics = self.system_metrics.write().await;
+        if !bundle.landed() {
+            sys_metrics.total_bundles_failed += 1;
+            return;
+        }
+        self.token_pnl.write().await.confirm(&bundle.mints, profit);
+        sys_metrics.total_bundles_landed += 1;
+        sys_metrics.total_successful_executions += 1;
+        sys_metrics.total_profit += profit;
+        sys_metrics.execution_success_rate =
+            average(sys_metrics.total_successful_executions as f64, sys_metrics.total_opportunities_executed);
+        sys_metrics.avg_profit_per_success = average(sys_metrics.total_profit, sys_metrics.total_successful_executions);
+        drop(sys_metrics);
         
-        // Record strategy-specific metrics
-        self.record_strategy_specific_metrics(result).await;
+        if let Some(strategy_metrics) = self.strategy_metrics.write().await.get_mut(&format!("{:?}", bundle.strategy_type)) {
+            strategy_metrics.successes += 1;
+            strategy_metrics.total_profit += profit;
+            strategy_metrics.avg_profit_per_execution = average(strategy_metrics.total_profit, strategy_metrics.executions);
+        }
     }
     
-    async fn record_strategy_specific_metrics(&self, result: &MevStrategyResult) {
+    async fn record_execution_phases(&self, result: &MevStrategyResult) {
+        let strategy = format!("{:?}", result.strategy_type);
+        let mut latencies = self.phase_latencies.write().await;
+        let phases = result.phases.iter()
+            .map(|(phase, duration_ms)| (phase.name(), *duration_ms))
+            .chain(std::iter::once(("total", result.execution_time_ms)));
+        for (phase, duration_ms) in phases {
+            latencies.entry((strategy.clone(), phase.to_string()))
+                .or_insert_with(|| LatencyHistogram::new(1000))
+                .record(duration_ms);
+        }
+    }
+    
+    pub async fn get_phase_latency_summaries(&self) -> Vec<PhaseLatencySummary> {
+        let latencies = self.phase_latencies.read().await;
+        let mut summaries: Vec<PhaseLatencySummary> = latencies.iter()
+            .map(|((strategy, phase), histogram)| PhaseLatencySummary {
+                strategy: strategy.clone(),
+                phase: phase.clone(),
+                count: histogram.count(),
+                p50_ms: histogram.percentile(0.50).unwrap_or(0),
+                p95_ms: histogram.percentile(0.95).unwrap_or(0),
+            })
+            .collect();
+        summaries.sort_by(|a, b| (&a.strategy, &a.phase).cmp(&(&b.strategy, &b.phase)));
+        summaries
+    }
+    
+    async fn record_strategy_specific_metrics(&self, result: &MevStrategyResult, avg_execution_time_ms: f64) {
         let strategy_key = format!("{:?}", result.strategy_type);
         let mut strategy_map = self.strategy_metrics.write().await;
         
@@ -195,19 +804,15 @@ impl MetricsCollector {
         strategy_metrics.executions += 1;
         if result.success {
             strategy_metrics.successes += 1;
-            strategy_metrics.total_profit += result.profit;
+            strategy_metrics.total_profit += finite_or_zero(result.profit);
         }
         
-        strategy_metrics.total_fees += result.fees_paid;
-        strategy_metrics.total_tips += result.tip_paid;
+        strategy_metrics.total_fees += finite_or_zero(result.fees_paid);
+        strategy_metrics.total_tips += finite_or_zero(result.tip_paid);
         
         // Update averages
-        strategy_metrics.avg_profit_per_execution = 
-            strategy_metrics.total_profit / strategy_metrics.executions as f64;
-        
-        strategy_metrics.avg_execution_time_ms = 
-            (strategy_metrics.avg_execution_time_ms * (strategy_metrics.executions as f64 - 1.0) + 
-             result.execution_time_ms as f64) / strategy_metrics.executions as f64;
+        strategy_metrics.avg_profit_per_execution = average(strategy_metrics.total_profit, strategy_metrics.executions);
+        strategy_metrics.avg_execution_time_ms = avg_execution_time_ms;
     }
     
     pub async fn record_opportunity_result(
@@ -243,12 +848,8 @@ impl MetricsCollector {
             let to_remove = history.len() - 10000;
             history.drain(0..to_remove);
         }
-        
-        // Update system metrics
-        let mut sys_metrics = self.system_metrics.write().await;
-        sys_metrics.avg_execution_time_ms = 
-            (sys_metrics.avg_execution_time_ms * (sys_metrics.total_opportunities_executed as f64 - 1.0) + 
-             execution_time_ms as f64) / sys_metrics.total_opportunities_executed as f64;
+        // The system-wide average execution time is kept by record_strategy_execution, which owns
+        // the execution count; this only adds to the history
     }
     
     pub async fn record_rpc_call(
@@ -259,6 +860,14 @@ impl MetricsCollector {
         bytes_sent: u64,
         bytes_received: u64,
     ) {
+        let avg_response_time_ms = {
+            let mut histograms = self.latency_histograms.write().await;
+            let histogram = histograms.rpc_response.entry(endpoint_type.to_string())
+                .or_insert_with(BucketHistogram::latency_ms);
+            histogram.record(response_time_ms);
+            histogram.mean()
+        };
+        
         let mut rpc_map = self.rpc_metrics.write().await;
         
         let key = endpoint_type.to_string();
@@ -280,15 +889,291 @@ impl MetricsCollector {
         rpc_metrics.total_bytes_sent += bytes_sent;
         rpc_metrics.total_bytes_received += bytes_received;
         
-        // Update response time average
-        rpc_metrics.avg_response_time_ms = 
-            (rpc_metrics.avg_response_time_ms * (rpc_metrics.total_requests as f64 - 1.0) + 
-             response_time_ms) / rpc_metrics.total_requests as f64;
+        // Update response time average and error rate
+        rpc_metrics.avg_response_time_ms = avg_response_time_ms;
+        rpc_metrics.error_rate =
+            average((rpc_metrics.total_requests - rpc_metrics.successful_requests) as f64, rpc_metrics.total_requests);
+    }
+    
+    // Record stage durations for a single opportunity's pipeline run
+    pub async fn record_opportunity_timing(&self, timing: &OpportunityTiming) {
+        if let Some(submitted_at) = timing.submitted_at {
+            let elapsed = submitted_at.saturating_duration_since(timing.detected_at);
+            self.latency_histograms.write().await.detection_to_submission.record(elapsed.as_secs_f64() * 1000.0);
+        }
         
-        // Update error rate
-        rpc_metrics.error_rate = 
-            (rpc_metrics.total_requests - rpc_metrics.successful_requests) as f64 / 
-            rpc_metrics.total_requests as f64;
+        let mut latencies = self.stage_latencies.write().await;
+        for (stage, duration_ms) in timing.stage_durations() {
+            latencies.entry(stage.to_string())
+                .or_insert_with(|| LatencyHistogram::new(1000)) // Keep last 1000 samples per stage
+                .record(duration_ms);
+        }
+    }
+    
+    // Notification age when analysis starts, reported alongside the stage latencies so the
+    // staleness threshold can be tuned against the observed distribution
+    pub async fn record_notification_age(&self, age_ms: u64) {
+        let mut latencies = self.stage_latencies.write().await;
+        latencies.entry("notification_age".to_string())
+            .or_insert_with(|| LatencyHistogram::new(1000))
+            .record(age_ms);
+    }
+    
+    // Age of the pool state behind each evaluation; compare p50 with POOL_WATCHER_MAX_POOLS=0
+    // to see how much the subscriptions save over cache-or-fetch
+    pub async fn record_pool_state_age(&self, age_ms: u64) {
+        let mut latencies = self.stage_latencies.write().await;
+        latencies.entry("pool_state_age".to_string())
+            .or_insert_with(|| LatencyHistogram::new(1000))
+            .record(age_ms);
+    }
+    
+    pub async fn get_latency_histograms(&self) -> LatencyHistograms {
+        self.latency_histograms.read().await.clone()
+    }
+    
+    pub async fn get_stage_latency_summaries(&self) -> Vec<StageLatencySummary> {
+        let latencies = self.stage_latencies.read().await;
+        let mut summaries: Vec<StageLatencySummary> = latencies.iter()
+            .map(|(stage, histogram)| StageLatencySummary {
+                stage: stage.clone(),
+                count: histogram.count(),
+                p50_ms: histogram.percentile(0.50).unwrap_or(0),
+                p95_ms: histogram.percentile(0.95).unwrap_or(0),
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.stage.cmp(&b.stage));
+        summaries
+    }
+    
+    pub async fn record_ws_first_delivery(&self, endpoint: &str) {
+        let mut stats = self.ws_endpoint_stats.write().await;
+        Self::ws_endpoint_entry(&mut stats, endpoint).first_deliveries += 1;
+    }
+    
+    pub async fn record_ws_late_delivery(&self, endpoint: &str, behind_ms: u64) {
+        let mut stats = self.ws_endpoint_stats.write().await;
+        let entry = Self::ws_endpoint_entry(&mut stats, endpoint);
+        entry.late_deliveries += 1;
+        entry.behind_ms.record(behind_ms);
+    }
+    
+    fn ws_endpoint_entry<'a>(stats: &'a mut HashMap<String, WsEndpointStats>, endpoint: &str) -> &'a mut WsEndpointStats {
+        stats.entry(endpoint.to_string()).or_insert_with(|| WsEndpointStats {
+            first_deliveries: 0,
+            late_deliveries: 0,
+            behind_ms: LatencyHistogram::new(1000),
+        })
+    }
+    
+    pub async fn get_ws_endpoint_summaries(&self) -> Vec<WsEndpointSummary> {
+        let stats = self.ws_endpoint_stats.read().await;
+        let mut summaries: Vec<WsEndpointSummary> = stats.iter()
+            .map(|(endpoint, stats)| WsEndpointSummary {
+                endpoint: endpoint.clone(),
+                first_deliveries: stats.first_deliveries,
+                late_deliveries: stats.late_deliveries,
+                p50_behind_ms: stats.behind_ms.percentile(0.50).unwrap_or(0),
+                p95_behind_ms: stats.behind_ms.percentile(0.95).unwrap_or(0),
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        summaries
+    }
+    
+    // Latest hit/miss/eviction counters of a named cache; the counters are cumulative
+    pub async fn record_cache_stats(&self, cache: &str, stats: CacheStats) {
+        self.cache_stats.write().await.insert(cache.to_string(), stats);
+    }
+    
+    // Running count of throttled bundle submissions, kept apart from bundles the engine rejected
+    pub async fn record_bundles_throttled(&self, total: u64) {
+        self.system_metrics.write().await.total_bundles_throttled = total;
+    }
+    
+    pub async fn record_rpc_failovers(&self, total: u64) {
+        self.system_metrics.write().await.total_rpc_failovers = total;
+    }
+    
+    // Running totals from the RPC manager
+    pub async fn record_rpc_rate_limits(&self, rate_limited: u64, shed: u64, limited_locally: u64) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_rpc_rate_limited = rate_limited;
+        metrics.total_rpc_shed = shed;
+        metrics.total_rpc_limited_locally = limited_locally;
+    }
+    
+    // Latest view of what's left of each endpoint's local request budget
+    pub async fn record_rpc_rate_budgets(&self, budgets: Vec<RateBudget>) {
+        *self.rpc_rate_budgets.write().await = budgets;
+    }
+    
+    pub async fn get_rpc_rate_budgets(&self) -> Vec<RateBudget> {
+        self.rpc_rate_budgets.read().await.clone()
+    }
+    
+    // Current breaker state per endpoint, and how often one has opened in total
+    pub async fn record_rpc_breakers(&self, statuses: Vec<BreakerStatus>, opens: u64) {
+        *self.rpc_breakers.write().await = statuses;
+        self.system_metrics.write().await.total_rpc_breaker_opens = opens;
+    }
+    
+    pub async fn get_rpc_breakers(&self) -> Vec<BreakerStatus> {
+        self.rpc_breakers.read().await.clone()
+    }
+    
+    pub async fn record_token_exposure_rejection(&self, mint: &str) {
+        *self.token_exposure_rejections.write().await.entry(mint.to_string()).or_insert(0) += 1;
+    }
+    
+    // Sorted by mint
+    pub async fn get_token_exposure_rejections(&self) -> Vec<(String, u64)> {
+        let mut rejections: Vec<(String, u64)> = self.token_exposure_rejections.read().await
+            .iter()
+            .map(|(mint, count)| (mint.clone(), *count))
+            .collect();
+        rejections.sort();
+        rejections
+    }
+    
+    // Notifications dropped because the provider rate limited the target transaction fetch
+    pub async fn record_notification_rate_limited(&self) {
+        let mut metrics = self.system_metrics.write().await;
+        metrics.total_notifications_rate_limited += 1;
+    }
+    
+    // Opportunities dropped by the liquidity filters or the pool locks, per strategy and reason
+    pub async fn record_filter_rejection(&self, strategy: &str, reason: &str) {
+        *self.filter_rejections.write().await
+            .entry(format!("{}/{}", strategy, reason))
+            .or_insert(0) += 1;
+    }
+    
+    pub async fn get_filter_rejections(&self) -> Vec<(String, u64)> {
+        let mut rejections: Vec<(String, u64)> = self.filter_rejections.read().await
+            .iter()
+            .map(|(key, count)| (key.clone(), *count))
+            .collect();
+        rejections.sort();
+        rejections
+    }
+    
+    // Latest competition score and the outcome rates behind it
+    pub async fn record_competition(&self, snapshot: CompetitionSnapshot) {
+        *self.competition.write().await = Some(snapshot);
+    }
+    
+    pub async fn get_competition(&self) -> Option<CompetitionSnapshot> {
+        self.competition.read().await.clone()
+    }
+    
+    pub async fn record_disabled_strategies(&self, strategies: Vec<MevStrategyType>) {
+        *self.disabled_strategies.write().await = strategies;
+    }
+    
+    pub async fn get_disabled_strategies(&self) -> Vec<MevStrategyType> {
+        self.disabled_strategies.read().await.clone()
+    }
+    
+    pub async fn record_blacklisted_pools(&self, entries: Vec<BlacklistEntry>) {
+        *self.blacklisted_pools.write().await = entries;
+    }
+    
+    pub async fn get_blacklisted_pools(&self) -> Vec<BlacklistEntry> {
+        self.blacklisted_pools.read().await.clone()
+    }
+    
+    // Landing rate per tip size bucket from the optimizer's (decayed, persisted) tip history
+    pub async fn record_tip_bucket_rates(&self, rates: Vec<TipBucketRate>) {
+        *self.tip_bucket_rates.write().await = rates;
+    }
+    
+    pub async fn get_tip_bucket_rates(&self) -> Vec<TipBucketRate> {
+        self.tip_bucket_rates.read().await.clone()
+    }
+    
+    // Bundle landing rate by the type of leader each bundle was aimed at
+    pub async fn record_leader_landing_rates(&self, rates: Vec<LeaderLandingRate>) {
+        *self.leader_landing_rates.write().await = rates;
+    }
+    
+    pub async fn get_leader_landing_rates(&self) -> Vec<LeaderLandingRate> {
+        self.leader_landing_rates.read().await.clone()
+    }
+    
+    pub async fn record_strategy_thresholds(&self, thresholds: StrategyThresholds) {
+        *self.strategy_thresholds.write().await = Some(thresholds);
+    }
+    
+    pub async fn get_strategy_thresholds(&self) -> Option<StrategyThresholds> {
+        self.strategy_thresholds.read().await.clone()
+    }
+    
+    pub async fn get_cache_stats(&self) -> Vec<(String, CacheStats)> {
+        let mut stats: Vec<(String, CacheStats)> = self.cache_stats.read().await
+            .iter()
+            .map(|(cache, stats)| (cache.clone(), stats.clone()))
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+    
+    pub async fn log_latency_summary(&self) {
+        let summaries = self.get_stage_latency_summaries().await;
+        if summaries.is_empty() {
+            return;
+        }
+        
+        let line = summaries.iter()
+            .map(|s| format!("{} p50={}ms p95={}ms (n={})", s.stage, s.p50_ms, s.p95_ms, s.count))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        Logger::status_update(&format!("Stage latency: {}", line));
+        
+        let endpoints = self.get_ws_endpoint_summaries().await;
+        if endpoints.len() > 1 {
+            let line = endpoints.iter()
+                .map(|e| format!("{} first={} late={} behind p50={}ms p95={}ms", 
+                                 e.endpoint, e.first_deliveries, e.late_deliveries, e.p50_behind_ms, e.p95_behind_ms))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            Logger::status_update(&format!("WebSocket endpoints: {}", line));
+        }
+        
+        let caches = self.get_cache_stats().await;
+        if !caches.is_empty() {
+            let line = caches.iter()
+                .map(|(cache, s)| format!("{} size={} hits={} misses={} evictions={}", cache, s.len, s.hits, s.misses, s.evictions))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            Logger::status_update(&format!("Caches: {}", line));
+        }
+        
+        let rejections = self.get_filter_rejections().await;
+        if !rejections.is_empty() {
+            let line = rejections.iter()
+                .map(|(key, count)| format!("{}={}", key, count))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            Logger::status_update(&format!("Filter rejections: {}", line));
+        }
+    }
+    
+    // Spawns a background task that logs stage latency percentiles on a fixed interval until
+    // `shutdown` flips to true
+    pub fn start_latency_summary(&self, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                collector.log_latency_summary().await;
+            }
+        });
     }
     
     // Alert system
@@ -330,6 +1215,11 @@ impl MetricsCollector {
         // Log the alert
         Logger::error_occurred(&format!("[ALERT - {:?}] {}", severity, alert.message));
         
+        // Queued for the webhooks; never waits on delivery
+        if let Some(ref dispatcher) = self.alert_dispatcher {
+            dispatcher.dispatch(&alert);
+        }
+        
         // Keep only recent alerts
         if alerts.len() > 1000 { // Keep last 1000 alerts
             let to_remove = alerts.len() - 1000;
@@ -358,6 +1248,12 @@ impl MetricsCollector {
         map.get(endpoint_type).cloned()
     }
     
+    pub async fn get_all_rpc_metrics(&self) -> Vec<RpcMetrics> {
+        let mut metrics: Vec<RpcMetrics> = self.rpc_metrics.read().await.values().cloned().collect();
+        metrics.sort_by(|a, b| a.endpoint_type.cmp(&b.endpoint_type));
+        metrics
+    }
+    
     pub async fn get_recent_alerts(&self, count: usize) -> Vec<AlertEvent> {
         let alerts = self.alert_history.read().await;
         let start = alerts.len().saturating_sub(count);
@@ -369,7 +1265,9 @@ impl MetricsCollector {
         let export = MetricsExport {
             system: self.get_system_metrics().await,
             strategies: self.get_all_strategy_metrics().await,
+            thresholds: self.get_strategy_thresholds().await,
             alerts: self.get_recent_alerts(50).await, // Last 50 alerts
+            tokens: self.get_token_pnl_leaders(10).await, // Top 10 winners and losers by mint
             export_time: std::time::SystemTime::now(),
         };
         
@@ -386,72 +1284,140 @@ impl MetricsCollector {
             .map_err(|e| format!("Failed to write metrics to file: {}", e).into())
     }
     
-    // Calculate false positive rate
-    pub async fn calculate_false_positive_rate(&self) -> f64 {
-        let sys_metrics = self.system_metrics.read().await;
-        
-        if sys_metrics.total_opportunities_evaluated == 0 {
-            0.0
-        } else {
-            // False positive rate is the rate of detected opportunities that were not profitable when executed
-            let total_detected = sys_metrics.total_opportunities_detected;
-            let total_evaluated = sys_metrics.total_opportunities_evaluated;
-            
-            // For now, we'll calculate this as 1 - evaluation_rate as a proxy
-            // In a more complete implementation, we'd track which opportunities were false positives
-            if total_detected > 0 {
-                (total_detected - total_evaluated) as f64 / total_detected as f64
-            } else {
-                0.0
-            }
+    pub async fn persisted_metrics(&self) -> PersistedMetrics {
+        PersistedMetrics {
+            system: self.get_system_metrics().await,
+            strategies: self.get_all_strategy_metrics().await,
+            latency_histograms: self.get_latency_histograms().await,
+            tokens: self.get_token_pnl().await,
+            filter_decisions: self.filter_decisions.decisions().await,
         }
     }
     
+    // Continues a previous process's counters. The session start carries over; process_start_time
+    // stays this process's so uptime is still accurate. State gauges are left to be refreshed
+    pub async fn restore_persisted_metrics(&self, persisted: PersistedMetrics) {
+        {
+            let mut sys_metrics = self.system_metrics.write().await;
+            *sys_metrics = SystemMetrics {
+                process_start_time: sys_metrics.process_start_time,
+                dry_run: sys_metrics.dry_run,
+                kill_switch_engaged: sys_metrics.kill_switch_engaged,
+                drawdown_percent: sys_metrics.drawdown_percent,
+                drawdown_halted: sys_metrics.drawdown_halted,
+                circuit_breaker_state: sys_metrics.circuit_breaker_state,
+                ..persisted.system
+            };
+        }
+        *self.strategy_metrics.write().await = persisted.strategies.into_iter()
+            .map(|strategy| (format!("{:?}", strategy.strategy_type), strategy))
+            .collect();
+        *self.latency_histograms.write().await = persisted.latency_histograms;
+        self.token_pnl.write().await.restore(persisted.tokens);
+        self.filter_decisions.restore(persisted.filter_decisions).await;
+    }
+    
+    // Share of the opportunities that passed the filters and, once executed and reconciled, made
+    // no money; see OpportunityOutcomes
+    pub async fn calculate_false_positive_rate(&self) -> f64 {
+        self.opportunity_outcomes.stats().await.false_positive_rate()
+    }
+    
+    pub fn opportunity_outcomes(&self) -> OpportunityOutcomes {
+        self.opportunity_outcomes.clone()
+    }
+    
+    pub async fn get_outcome_stats(&self) -> OutcomeStats {
+        self.opportunity_outcomes.stats().await
+    }
+    
     // Get performance metrics by time window
     pub async fn get_performance_in_window(&self, minutes: u64) -> SystemMetrics {
-        let start_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-            
-        let window_start = start_time - (minutes * 60 * 1000); // Convert minutes to milliseconds
-        
-        // In a real implementation, we'd filter metrics by time window
-        // For now, return the full system metrics
-        self.get_system_metrics().await
+        self.get_performance_in_window_at(minutes, now_unix()).await
+    }
+    
+    // Counters over the last `minutes` minutes up to `now` (Unix seconds), to the minute and at
+    // most a day back. State gauges (kill switch, drawdown, circuit breaker) are current values;
+    // counters that aren't kept per minute read zero
+    pub async fn get_performance_in_window_at(&self, minutes: u64, now: u64) -> SystemMetrics {
+        let window = self.windowed_metrics.read().await.totals_at(now, minutes);
+        let current = self.get_system_metrics().await;
+        let start_time = std::time::UNIX_EPOCH + Duration::from_secs(now.saturating_sub(minutes * 60));
+        SystemMetrics {
+            total_opportunities_detected: window.opportunities_detected,
+            total_opportunities_evaluated: window.opportunities_evaluated,
+            total_opportunities_executed: window.executions,
+            total_opportunities_expired: window.opportunities_expired,
+            total_successful_executions: window.successes,
+            total_profit: window.profit,
+            total_fees_paid: window.fees,
+            total_tips_paid: window.tips,
+            execution_success_rate: window.success_rate(),
+            avg_profit_per_success: window.avg_profit_per_success(),
+            avg_execution_time_ms: window.avg_execution_time_ms(),
+            total_bundles_landed: window.bundles_landed,
+            total_bundles_failed: window.bundles_failed,
+            total_execution_time_ms: window.execution_time_ms,
+            kill_switch_engaged: current.kill_switch_engaged,
+            drawdown_percent: current.drawdown_percent,
+            drawdown_halted: current.drawdown_halted,
+            circuit_breaker_state: current.circuit_breaker_state,
+            process_start_time: current.process_start_time,
+            ..SystemMetrics::new(start_time, current.dry_run)
+        }
     }
     
     // Reset metrics (for testing or new sessions)
     pub async fn reset_metrics(&self) {
         let mut sys_metrics = self.system_metrics.write().await;
-        *sys_metrics = SystemMetrics {
-            total_opportunities_detected: 0,
-            total_opportunities_evaluated: 0,
-            total_opportunities_executed: 0,
-            total_successful_executions: 0,
-            total_profit: 0.0,
-            total_fees_paid: 0.0,
-            total_tips_paid: 0.0,
-            false_positive_rate: 0.0,
-            execution_success_rate: 0.0,
-            avg_profit_per_success: 0.0,
-            avg_execution_time_ms: 0.0,
-            start_time: std::time::SystemTime::now(),
-        };
+        *sys_metrics = SystemMetrics::new(std::time::SystemTime::now(), sys_metrics.dry_run);
         
         // Clear other metrics
         *self.strategy_metrics.write().await = HashMap::new();
         *self.rpc_metrics.write().await = HashMap::new();
         *self.opportunity_history.write().await = Vec::new();
         *self.alert_history.write().await = Vec::new();
+        *self.stage_latencies.write().await = HashMap::new();
+        *self.ws_endpoint_stats.write().await = HashMap::new();
+        *self.filter_rejections.write().await = HashMap::new();
+        *self.phase_latencies.write().await = HashMap::new();
+        *self.competition.write().await = None;
+        *self.disabled_strategies.write().await = Vec::new();
+        *self.tip_bucket_rates.write().await = Vec::new();
+        *self.leader_landing_rates.write().await = Vec::new();
+        *self.rpc_rate_budgets.write().await = Vec::new();
+        *self.rpc_breakers.write().await = Vec::new();
+        *self.token_exposure_rejections.write().await = HashMap::new();
+        *self.blacklisted_pools.write().await = Vec::new();
+        *self.windowed_metrics.write().await = WindowedMetrics::new();
+        self.opportunity_outcomes.clear().await;
+        self.filter_decisions.clear().await;
+        *self.latency_histograms.write().await = LatencyHistograms::new();
+        self.token_pnl.write().await.clear();
+        self.profit_calibration.clear().await;
+        self.simulation_accuracy.clear().await;
     }
 }
 
+// The counters that continue across restarts; see metrics_state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedMetrics {
+    pub system: SystemMetrics,
+    pub strategies: Vec<StrategyMetrics>,
+    pub latency_histograms: LatencyHistograms, // The averages above are their means
+    #[serde(default)]
+    pub tokens: Vec<TokenPnl>,
+    #[serde(default)]
+    pub filter_decisions: Vec<FilterDecision>, // See filter_decisions
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct MetricsExport {
     system: SystemMetrics,
     strategies: Vec<StrategyMetrics>,
+    thresholds: Option<StrategyThresholds>,
     alerts: Vec<AlertEvent>,
+    tokens: TokenPnlLeaders,
     export_time: std::time::SystemTime,
 }
 
@@ -477,24 +1443,305 @@ pub mod prometheus_exporter {
             let mut output = String::new();
             
             // System metrics
-            output.push_str(&format!("# HELP mev_bot_total_opportunities_detected Total opportunities detected\n"));
+            let now = std::time::SystemTime::now();
+            output.push_str("# HELP mev_bot_uptime_seconds Seconds since this process started\n");
+            output.push_str(&format!("mev_bot_uptime_seconds {}\n", now.duration_since(sys_metrics.process_start_time).unwrap_or_default().as_secs()));
+            
+            output.push_str("# HELP mev_bot_session_seconds Seconds the counters have been accumulating, across restored restarts\n");
+            output.push_str(&format!("mev_bot_session_seconds {}\n", now.duration_since(sys_metrics.start_time).unwrap_or_default().as_secs()));
+            
+            output.push_str("# HELP mev_bot_total_opportunities_detected Total opportunities detected\n");
             output.push_str(&format!("mev_bot_total_opportunities_detected {}\n", sys_metrics.total_opportunities_detected));
             
-            output.push_str(&format!("# HELP mev_bot_total_opportunities_executed Total opportunities executed\n"));
+            output.push_str("# HELP mev_bot_total_opportunities_executed Total opportunities executed\n");
             output.push_str(&format!("mev_bot_total_opportunities_executed {}\n", sys_metrics.total_opportunities_executed));
             
-            output.push_str(&format!("# HELP mev_bot_total_successful_executions Total successful executions\n"));
+            output.push_str("# HELP mev_bot_total_opportunities_expired Opportunities dropped after missing their deadline\n");
+            output.push_str(&format!("mev_bot_total_opportunities_expired {}\n", sys_metrics.total_opportunities_expired));
+            
+            output.push_str("# HELP mev_bot_total_opportunities_strategy_disabled Opportunities dropped because their strategy is not enabled\n");
+            output.push_str(&format!("mev_bot_total_opportunities_strategy_disabled {}\n", sys_metrics.total_opportunities_strategy_disabled));
+            
+            output.push_str("# HELP mev_bot_total_analyses_skipped Notifications skipped because the analysis concurrency cap was reached\n");
+            output.push_str(&format!("mev_bot_total_analyses_skipped {}\n", sys_metrics.total_analyses_skipped));
+            
+            output.push_str("# HELP mev_bot_total_stale_notifications_dropped Notifications dropped for exceeding the maximum age\n");
+            output.push_str(&format!("mev_bot_total_stale_notifications_dropped {}\n", sys_metrics.total_stale_notifications_dropped));
+            
+            output.push_str("# HELP mev_bot_total_risk_paused Notifications skipped while risk controls paused trading\n");
+            output.push_str(&format!("mev_bot_total_risk_paused {}\n", sys_metrics.total_risk_paused));
+            
+            output.push_str("# HELP mev_bot_kill_switch_engaged 1 while the kill switch holds back new opportunities\n");
+            output.push_str(&format!("mev_bot_kill_switch_engaged {}\n", sys_metrics.kill_switch_engaged as u8));
+            
+            output.push_str("# HELP mev_bot_drawdown_percent Balance drawdown from the high-water mark, in percent\n");
+            output.push_str(&format!("mev_bot_drawdown_percent {:.4}\n", sys_metrics.drawdown_percent));
+            output.push_str("# HELP mev_bot_drawdown_halted 1 while a drawdown halt holds back trading\n");
+            output.push_str(&format!("mev_bot_drawdown_halted {}\n", sys_metrics.drawdown_halted as u8));
+            
+            output.push_str("# HELP mev_bot_circuit_breaker_state Execution circuit breaker: 0 closed, 1 half-open, 2 open\n");
+            output.push_str(&format!("mev_bot_circuit_breaker_state {}\n", sys_metrics.circuit_breaker_state));
+            output.push_str("# HELP mev_bot_total_circuit_breaker_skips Queued opportunities not executed while the circuit breaker was open\n");
+            output.push_str(&format!("mev_bot_total_circuit_breaker_skips {}\n", sys_metrics.total_circuit_breaker_skips));
+            
+            output.push_str("# HELP mev_bot_total_simulation_consensus_checks Simulations cross-checked on several RPC endpoints\n");
+            output.push_str(&format!("mev_bot_total_simulation_consensus_checks {}\n", sys_metrics.total_simulation_consensus_checks));
+            output.push_str("# HELP mev_bot_total_simulation_disagreements Cross-checked simulations whose endpoints disagreed\n");
+            output.push_str(&format!("mev_bot_total_simulation_disagreements {}\n", sys_metrics.total_simulation_disagreements));
+            
+            output.push_str("# HELP mev_bot_total_successful_executions Total successful executions\n");
             output.push_str(&format!("mev_bot_total_successful_executions {}\n", sys_metrics.total_successful_executions));
             
-            output.push_str(&format!("# HELP mev_bot_total_profit Total profit in SOL\n"));
+            output.push_str("# HELP mev_bot_dry_run 1 when submissions are stubbed and profit figures are hypothetical\n");
+            output.push_str(&format!("mev_bot_dry_run {}\n", sys_metrics.dry_run as u8));
+            
+            output.push_str("# HELP mev_bot_total_profit Total profit in SOL\n");
             output.push_str(&format!("mev_bot_total_profit {:.6}\n", sys_metrics.total_profit));
             
-            output.push_str(&format!("# HELP mev_bot_execution_success_rate Success rate of executions\n"));
+            output.push_str("# HELP mev_bot_bundles_landed Bundles confirmed on chain by their landing status\n");
+            output.push_str(&format!("mev_bot_bundles_landed {}\n", sys_metrics.total_bundles_landed));
+            
+            output.push_str("# HELP mev_bot_bundles_failed Accepted bundles that failed or expired without landing\n");
+            output.push_str(&format!("mev_bot_bundles_failed {}\n", sys_metrics.total_bundles_failed));
+            
+            output.push_str("# HELP mev_bot_bundles_throttled Bundle submissions held back by the Jito rate limit or a 429\n");
+            output.push_str(&format!("mev_bot_bundles_throttled {}\n", sys_metrics.total_bundles_throttled));
+            
+            output.push_str("# HELP mev_bot_rpc_failovers RPC requests retried on another endpoint\n");
+            output.push_str(&format!("mev_bot_rpc_failovers {}\n", sys_metrics.total_rpc_failovers));
+            
+            output.push_str("# HELP mev_bot_rpc_rate_limited RPC responses that were 429s or provider rate-limit errors\n");
+            output.push_str(&format!("mev_bot_rpc_rate_limited {}\n", sys_metrics.total_rpc_rate_limited));
+            
+            output.push_str("# HELP mev_bot_rpc_shed Low-priority RPC requests turned away while an endpoint was throttling\n");
+            output.push_str(&format!("mev_bot_rpc_shed {}\n", sys_metrics.total_rpc_shed));
+            
+            output.push_str("# HELP mev_bot_rpc_limited_locally RPC requests held back by the per-endpoint rate limits\n");
+            output.push_str(&format!("mev_bot_rpc_limited_locally {}\n", sys_metrics.total_rpc_limited_locally));
+            
+            output.push_str("# HELP mev_bot_rpc_breaker_opens Times an RPC endpoint's circuit breaker opened\n");
+            output.push_str(&format!("mev_bot_rpc_breaker_opens {}\n", sys_metrics.total_rpc_breaker_opens));
+            
+            output.push_str("# HELP mev_bot_notifications_rate_limited Notifications dropped because the target fetch was rate limited\n");
+            output.push_str(&format!("mev_bot_notifications_rate_limited {}\n", sys_metrics.total_notifications_rate_limited));
+            
+            output.push_str("# HELP mev_bot_execution_success_rate Success rate of executions\n");
             output.push_str(&format!("mev_bot_execution_success_rate {:.4}\n", sys_metrics.execution_success_rate));
             
-            output.push_str(&format!("# HELP mev_bot_avg_profit_per_success Average profit per successful execution\n"));
+            // Opportunities that passed the filters and made no money, overall and broken down
+            let outcomes = self.metrics_collector.opportunity_outcomes();
+            let outcome_stats = outcomes.stats().await;
+            output.push_str("# HELP mev_bot_false_positive_rate Share of executed opportunities that passed the filters and made no money\n");
+            output.push_str(&format!("mev_bot_false_positive_rate {:.4}\n", outcome_stats.false_positive_rate()));
+            output.push_str("# HELP mev_bot_filter_precision Share of executed opportunities that passed the filters and paid\n");
+            output.push_str(&format!("mev_bot_filter_precision {:.4}\n", outcome_stats.filter_precision()));
+            output.push_str("# HELP mev_bot_filter_rejection_rate Share of opportunities the false positive reducer rejected\n");
+            output.push_str(&format!("mev_bot_filter_rejection_rate {:.4}\n", outcome_stats.filter_rejection_rate()));
+            for (opportunity_type, stats) in outcomes.stats_by_type().await {
+                let opportunity_type = opportunity_type.to_lowercase();
+                output.push_str(&format!("mev_bot_false_positive_rate_by_type{{opportunity_type=\"{}\"}} {:.4}\n", opportunity_type, stats.false_positive_rate()));
+                output.push_str(&format!("mev_bot_filter_precision_by_type{{opportunity_type=\"{}\"}} {:.4}\n", opportunity_type, stats.filter_precision()));
+            }
+            for (dex, stats) in outcomes.stats_by_dex().await {
+                output.push_str(&format!("mev_bot_false_positive_rate_by_dex{{dex=\"{}\"}} {:.4}\n", dex, stats.false_positive_rate()));
+                output.push_str(&format!("mev_bot_filter_precision_by_dex{{dex=\"{}\"}} {:.4}\n", dex, stats.filter_precision()));
+            }
+            
+            // Median realized/estimated profit per opportunity type and DEX; see ProfitCalibration
+            let calibration = self.metrics_collector.profit_calibration().factors().await;
+            if !calibration.is_empty() {
+                output.push_str("# HELP mev_bot_profit_calibration Median realized share of the estimated profit\n");
+                for factor in &calibration {
+                    output.push_str(&format!("mev_bot_profit_calibration{{scope=\"{}\",key=\"{}\"}} {:.4}\n", factor.scope, factor.key, factor.factor));
+                }
+                output.push_str("# HELP mev_bot_profit_calibration_samples Reconciled trades behind each calibration factor\n");
+                for factor in &calibration {
+                    output.push_str(&format!("mev_bot_profit_calibration_samples{{scope=\"{}\",key=\"{}\"}} {}\n", factor.scope, factor.key, factor.samples));
+                }
+            }
+            
+            // Rolling accuracy of the simulations behind landed bundles; see SimulationAccuracy
+            let accuracy = self.metrics_collector.simulation_accuracy().scores().await;
+            if !accuracy.is_empty() {
+                output.push_str("# HELP mev_bot_simulation_accuracy Mean accuracy of simulated against realized net profit\n");
+                for score in &accuracy {
+                    output.push_str(&format!("mev_bot_simulation_accuracy{{scope=\"{}\",key=\"{}\"}} {:.4}\n", score.scope, score.key, score.accuracy));
+                }
+                output.push_str("# HELP mev_bot_simulation_accuracy_samples Landed bundles behind each accuracy score\n");
+                for score in &accuracy {
+                    output.push_str(&format!("mev_bot_simulation_accuracy_samples{{scope=\"{}\",key=\"{}\"}} {}\n", score.scope, score.key, score.samples));
+                }
+            }
+            
+            // Realized PnL by mint; past TOKEN_PNL_MAX_MINTS mints share mint="other"
+            let tokens = self.metrics_collector.get_token_pnl().await;
+            if !tokens.is_empty() {
+                output.push_str("# HELP mev_bot_token_profit_sol Realized profit attributed to each traded mint\n");
+                for token in &tokens {
+                    output.push_str(&format!("mev_bot_token_profit_sol{{mint=\"{}\"}} {:.6}\n", token.mint, token.profit));
+                }
+                output.push_str("# HELP mev_bot_token_fees_sol Fees and tips attributed to each traded mint\n");
+                for token in &tokens {
+                    output.push_str(&format!("mev_bot_token_fees_sol{{mint=\"{}\"}} {:.6}\n", token.mint, token.fees + token.tips));
+                }
+                output.push_str("# HELP mev_bot_token_trades Executed opportunities that traded each mint\n");
+                for token in &tokens {
+                    output.push_str(&format!("mev_bot_token_trades{{mint=\"{}\"}} {}\n", token.mint, token.trades));
+                }
+            }
+            
+            output.push_str("# HELP mev_bot_avg_profit_per_success Average profit per successful execution\n");
             output.push_str(&format!("mev_bot_avg_profit_per_success {:.6}\n", sys_metrics.avg_profit_per_success));
             
+            // Latency distributions, as Prometheus histograms plus p50/p90/p99 estimated from them
+            let histograms = self.metrics_collector.get_latency_histograms().await;
+            write_histogram_header(&mut output, "mev_bot_detection_to_submission_ms", "Time from detection to bundle submission");
+            write_histogram(&mut output, "mev_bot_detection_to_submission_ms", "", &histograms.detection_to_submission);
+            write_histogram_header(&mut output, "mev_bot_execution_ms", "Strategy execution time");
+            write_histogram(&mut output, "mev_bot_execution_ms", "", &histograms.execution);
+            let mut by_strategy: Vec<_> = histograms.execution_by_strategy.iter().collect();
+            by_strategy.sort_by(|a, b| a.0.cmp(b.0));
+            write_histogram_header(&mut output, "mev_bot_strategy_execution_ms", "Strategy execution time per strategy");
+            for (strategy, histogram) in by_strategy {
+                write_histogram(&mut output, "mev_bot_strategy_execution_ms", &format!("strategy=\"{}\"", strategy.to_lowercase()), histogram);
+            }
+            let mut by_endpoint: Vec<_> = histograms.rpc_response.iter().collect();
+            by_endpoint.sort_by(|a, b| a.0.cmp(b.0));
+            write_histogram_header(&mut output, "mev_bot_rpc_response_ms", "RPC response time per endpoint type");
+            for (endpoint, histogram) in by_endpoint {
+                write_histogram(&mut output, "mev_bot_rpc_response_ms", &format!("endpoint=\"{}\"", endpoint), histogram);
+            }
+            
+            // Stage latency percentiles
+            for summary in self.metrics_collector.get_stage_latency_summaries().await {
+                output.push_str(&format!("mev_bot_stage_latency_ms{{stage=\"{}\",quantile=\"0.5\"}} {}\n", summary.stage, summary.p50_ms));
+                output.push_str(&format!("mev_bot_stage_latency_ms{{stage=\"{}\",quantile=\"0.95\"}} {}\n", summary.stage, summary.p95_ms));
+            }
+            
+            // Per-strategy execution phases
+            for summary in self.metrics_collector.get_phase_latency_summaries().await {
+                let strategy = summary.strategy.to_lowercase();
+                output.push_str(&format!("mev_bot_execution_phase_ms{{strategy=\"{}\",phase=\"{}\",quantile=\"0.5\"}} {}\n", strategy, summary.phase, summary.p50_ms));
+                output.push_str(&format!("mev_bot_execution_phase_ms{{strategy=\"{}\",phase=\"{}\",quantile=\"0.95\"}} {}\n", strategy, summary.phase, summary.p95_ms));
+            }
+            
+            // Competition inferred from our own bundle outcomes
+            if let Some(competition) = self.metrics_collector.get_competition().await {
+                output.push_str("# HELP mev_bot_competition_score Estimated MEV competition (0-1)\n");
+                output.push_str(&format!("mev_bot_competition_score {:.4}\n", competition.score));
+                output.push_str(&format!("mev_bot_competition_bundle_failure_rate {:.4}\n", competition.bundle_failure_rate));
+                output.push_str(&format!("mev_bot_competition_contested_target_rate {:.4}\n", competition.contested_target_rate));
+                output.push_str(&format!("mev_bot_competition_samples{{kind=\"bundles\"}} {}\n", competition.bundles));
+                output.push_str(&format!("mev_bot_competition_samples{{kind=\"targets\"}} {}\n", competition.targets));
+            }
+            
+            // Per-endpoint RPC traffic
+            for rpc in self.metrics_collector.get_all_rpc_metrics().await {
+                let endpoint = rpc.endpoint_type.to_lowercase();
+                output.push_str(&format!("mev_bot_rpc_requests_total{{endpoint=\"{}\"}} {}\n", endpoint, rpc.total_requests));
+                output.push_str(&format!("mev_bot_rpc_error_rate{{endpoint=\"{}\"}} {:.4}\n", endpoint, rpc.error_rate));
+                output.push_str(&format!("mev_bot_rpc_avg_response_ms{{endpoint=\"{}\"}} {:.1}\n", endpoint, rpc.avg_response_time_ms));
+            }
+            
+            // What's left of each endpoint's local budget
+            for budget in self.metrics_collector.get_rpc_rate_budgets().await {
+                let endpoint = format!("{:?}", budget.endpoint).to_lowercase();
+                if let Some(tokens) = budget.tokens {
+                    output.push_str(&format!("mev_bot_rpc_rate_budget{{endpoint=\"{}\"}} {}\n", endpoint, tokens));
+                }
+                if let Some(available) = budget.in_flight_available {
+                    output.push_str(&format!("mev_bot_rpc_in_flight_available{{endpoint=\"{}\"}} {}\n", endpoint, available));
+                }
+            }
+            
+            // Circuit breaker per endpoint: 0 closed, 1 half-open, 2 open
+            for breaker in self.metrics_collector.get_rpc_breakers().await {
+                let endpoint = format!("{:?}", breaker.endpoint).to_lowercase();
+                output.push_str(&format!("mev_bot_rpc_breaker_state{{endpoint=\"{}\"}} {}\n", endpoint, breaker.state.as_gauge()));
+            }
+            
+            // Opportunities skipped at the per-token exposure cap
+            for (mint, count) in self.metrics_collector.get_token_exposure_rejections().await {
+                output.push_str(&format!("mev_bot_token_exposure_rejections_total{{mint=\"{}\"}} {}\n", mint, count));
+            }
+            
+            // Pools the evaluator skips, with what was lost on them
+            let blacklisted_pools = self.metrics_collector.get_blacklisted_pools().await;
+            output.push_str("# HELP mev_bot_blacklisted_pools Pools no opportunities are emitted on\n");
+            output.push_str(&format!("mev_bot_blacklisted_pools {}\n", blacklisted_pools.len()));
+            for entry in blacklisted_pools {
+                output.push_str(&format!("mev_bot_pool_blacklist_loss_sol{{pool=\"{}\",reason=\"{}\"}} {:.6}\n", entry.pool, entry.reason.label(), entry.net_loss));
+            }
+            
+            // Recent performance, so a bad last hour isn't hidden behind a good week
+            let mut windows = Vec::new();
+            for (label, minutes) in [("15m", 15), ("1h", 60), ("24h", 1440)] {
+                windows.push((label, self.metrics_collector.get_performance_in_window(minutes).await));
+            }
+            output.push_str("# HELP mev_bot_window_opportunities_detected Opportunities detected in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_opportunities_detected{{window=\"{}\"}} {}\n", label, window.total_opportunities_detected));
+            }
+            output.push_str("# HELP mev_bot_window_executions Executions in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_executions{{window=\"{}\"}} {}\n", label, window.total_opportunities_executed));
+            }
+            output.push_str("# HELP mev_bot_window_successful_executions Successful executions in the window, net of reverted bundles\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_successful_executions{{window=\"{}\"}} {}\n", label, window.total_successful_executions));
+            }
+            output.push_str("# HELP mev_bot_window_success_rate Execution success rate in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_success_rate{{window=\"{}\"}} {:.4}\n", label, window.execution_success_rate));
+            }
+            output.push_str("# HELP mev_bot_window_profit_sol Profit in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_profit_sol{{window=\"{}\"}} {:.6}\n", label, window.total_profit));
+            }
+            output.push_str("# HELP mev_bot_window_fees_sol Fees paid in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_fees_sol{{window=\"{}\"}} {:.6}\n", label, window.total_fees_paid));
+            }
+            output.push_str("# HELP mev_bot_window_tips_sol Tips paid in the window\n");
+            for (label, window) in &windows {
+                output.push_str(&format!("mev_bot_window_tips_sol{{window=\"{}\"}} {:.6}\n", label, window.total_tips_paid));
+            }
+            
+            // Which tip sizes land
+            for rate in self.metrics_collector.get_tip_bucket_rates().await {
+                output.push_str(&format!("mev_bot_tip_landing_rate{{bucket=\"{}\"}} {:.4}\n", rate.bucket, rate.landing_rate()));
+                output.push_str(&format!("mev_bot_tip_attempts{{bucket=\"{}\"}} {:.2}\n", rate.bucket, rate.attempts));
+            }
+            
+            // Landing by leader type: Jito leaders should land, the rest shouldn't be sent to
+            for rate in self.metrics_collector.get_leader_landing_rates().await {
+                output.push_str(&format!("mev_bot_bundle_landing_rate{{leader=\"{}\"}} {:.4}\n", rate.leader, rate.landing_rate()));
+                output.push_str(&format!("mev_bot_bundles_by_leader_total{{leader=\"{}\"}} {}\n", rate.leader, rate.attempts));
+            }
+            
+            // Per-endpoint WebSocket delivery race
+            for endpoint in self.metrics_collector.get_ws_endpoint_summaries().await {
+                output.push_str(&format!("mev_bot_ws_first_deliveries_total{{endpoint=\"{}\"}} {}\n", endpoint.endpoint, endpoint.first_deliveries));
+                output.push_str(&format!("mev_bot_ws_late_deliveries_total{{endpoint=\"{}\"}} {}\n", endpoint.endpoint, endpoint.late_deliveries));
+                output.push_str(&format!("mev_bot_ws_behind_ms{{endpoint=\"{}\",quantile=\"0.5\"}} {}\n", endpoint.endpoint, endpoint.p50_behind_ms));
+                output.push_str(&format!("mev_bot_ws_behind_ms{{endpoint=\"{}\",quantile=\"0.95\"}} {}\n", endpoint.endpoint, endpoint.p95_behind_ms));
+            }
+            
+            // Thresholds the executor is actually using
+            if let Some(thresholds) = self.metrics_collector.get_strategy_thresholds().await {
+                output.push_str("# HELP mev_bot_min_profit_sol Minimum net profit required per strategy\n");
+                output.push_str(&format!("mev_bot_min_profit_sol{{strategy=\"arbitrage\"}} {:.6}\n", thresholds.min_arbitrage_profit));
+                output.push_str(&format!("mev_bot_min_profit_sol{{strategy=\"sandwich\"}} {:.6}\n", thresholds.min_sandwich_profit));
+                output.push_str(&format!("mev_bot_min_profit_sol{{strategy=\"frontrun\"}} {:.6}\n", thresholds.min_frontrun_profit));
+                output.push_str(&format!("mev_bot_max_slippage_bps {}\n", thresholds.max_slippage_bps));
+                output.push_str(&format!("mev_bot_max_price_impact_bps {}\n", thresholds.max_price_impact_bps));
+            }
+            
+            // Strategies disabled after repeated failures
+            for strategy in self.metrics_collector.get_disabled_strategies().await {
+                output.push_str(&format!("mev_bot_strategy_disabled{{strategy=\"{}\"}} 1\n", format!("{:?}", strategy).to_lowercase()));
+            }
+            
             // Strategy-specific metrics
             for strategy in strategy_metrics {
                 let strategy_name = format!("{:?}", strategy.strategy_type).to_lowercase();
@@ -509,9 +1756,49 @@ pub mod prometheus_exporter {
                 output.push_str(&format!("mev_bot_strategy_{}_total_profit {:.6}\n", strategy_name, strategy.total_profit));
             }
             
-            output
+            finite_samples(output)
         }
     }
+    
+    fn write_histogram_header(output: &mut String, name: &str, help: &str) {
+        output.push_str(&format!("# HELP {} {}\n", name, help));
+        output.push_str(&format!("# TYPE {} histogram\n", name));
+    }
+    
+    // The _bucket/_sum/_count series of one histogram, followed by its quantile estimates as the
+    // separate {name}_quantile family (a histogram family can't carry a quantile label)
+    fn write_histogram(output: &mut String, name: &str, labels: &str, histogram: &BucketHistogram) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        for (bound, count) in histogram.cumulative() {
+            let le = if bound.is_infinite() { "+Inf".to_string() } else { bound.to_string() };
+            output.push_str(&format!("{}_bucket{{{}{}le=\"{}\"}} {}\n", name, labels, separator, le, count));
+        }
+        let braced = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        output.push_str(&format!("{}_sum{} {:.3}\n", name, braced, histogram.sum()));
+        output.push_str(&format!("{}_count{} {}\n", name, braced, histogram.count()));
+        for quantile in [0.5, 0.9, 0.99] {
+            if let Some(value) = histogram.quantile(quantile) {
+                output.push_str(&format!("{}_quantile{{{}{}quantile=\"{}\"}} {:.3}\n", name, labels, separator, quantile, value));
+            }
+        }
+    }
+    
+    // Rewrites NaN and infinite sample values to 0 so one bad number can't break a scrape; the
+    // value is whatever follows the last space of a non-comment line
+    pub fn finite_samples(output: String) -> String {
+        let mut sanitized = String::with_capacity(output.len());
+        for line in output.lines() {
+            match line.rsplit_once(' ') {
+                Some((series, value)) if !line.starts_with('#') && value.parse::<f64>().is_ok_and(|value| !value.is_finite()) => {
+                    sanitized.push_str(series);
+                    sanitized.push_str(" 0");
+                }
+                _ => sanitized.push_str(line),
+            }
+            sanitized.push('\n');
+        }
+        sanitized
+    }
 }
 
 impl Clone for MetricsCollector {
@@ -522,10 +1809,535 @@ impl Clone for MetricsCollector {
             rpc_metrics: Arc::clone(&self.rpc_metrics),
             opportunity_history: Arc::clone(&self.opportunity_history),
             alert_history: Arc::clone(&self.alert_history),
+            stage_latencies: Arc::clone(&self.stage_latencies),
+            ws_endpoint_stats: Arc::clone(&self.ws_endpoint_stats),
+            cache_stats: Arc::clone(&self.cache_stats),
+            filter_rejections: Arc::clone(&self.filter_rejections),
+            phase_latencies: Arc::clone(&self.phase_latencies),
+            competition: Arc::clone(&self.competition),
+            disabled_strategies: Arc::clone(&self.disabled_strategies),
+            tip_bucket_rates: Arc::clone(&self.tip_bucket_rates),
+            leader_landing_rates: Arc::clone(&self.leader_landing_rates),
+            rpc_rate_budgets: Arc::clone(&self.rpc_rate_budgets),
+            rpc_breakers: Arc::clone(&self.rpc_breakers),
+            token_exposure_rejections: Arc::clone(&self.token_exposure_rejections),
+            blacklisted_pools: Arc::clone(&self.blacklisted_pools),
+            strategy_thresholds: Arc::clone(&self.strategy_thresholds),
+            windowed_metrics: Arc::clone(&self.windowed_metrics),
+            alert_dispatcher: self.alert_dispatcher.clone(),
+            opportunity_outcomes: self.opportunity_outcomes.clone(),
+            filter_decisions: self.filter_decisions.clone(),
+            latency_histograms: Arc::clone(&self.latency_histograms),
+            token_pnl: Arc::clone(&self.token_pnl),
+            profit_calibration: self.profit_calibration.clone(),
+            simulation_accuracy: self.simulation_accuracy.clone(),
             balance_drop_threshold: self.balance_drop_threshold,
             consecutive_failures_threshold: self.consecutive_failures_threshold,
             success_rate_threshold: self.success_rate_threshold,
             max_opportunity_age_ms: self.max_opportunity_age_ms,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new(100);
+        for value in 1..=100 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.percentile(0.50), Some(50));
+        assert_eq!(histogram.percentile(0.95), Some(95));
+
+        // Oldest samples are evicted once the buffer is full
+        histogram.record(1000);
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile(0.0), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_execution_phases_recorded_per_strategy() {
+        use crate::utils::mev_strategies::{ExecutionPhase, PhaseTimer};
+
+        let mut phases = PhaseTimer::start();
+        std::thread::sleep(Duration::from_millis(3));
+        phases.finish(ExecutionPhase::Simulation);
+        std::thread::sleep(Duration::from_millis(2));
+        phases.finish(ExecutionPhase::Submit);
+
+        let result = MevStrategyResult {
+            success: true,
+            profit: 0.01,
+            fees_paid: 0.0,
+            tip_paid: 0.0,
+            execution_time_ms: phases.elapsed_ms(),
+            strategy_type: MevStrategyType::Arbitrage,
+            phases: phases.recorded(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        };
+        assert!(result.execution_time_ms >= 5);
+        assert!(result.phases.iter().all(|(_, duration_ms)| *duration_ms > 0));
+
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        collector.record_strategy_execution(&result).await;
+        let recorded: Vec<(String, String)> = collector.get_phase_latency_summaries().await.into_iter()
+            .map(|summary| (summary.strategy, summary.phase))
+            .collect();
+        assert_eq!(recorded, vec![
+            ("Arbitrage".to_string(), "simulation".to_string()),
+            ("Arbitrage".to_string(), "submit".to_string()),
+            ("Arbitrage".to_string(), "total".to_string()),
+        ]);
+        assert!(collector.get_all_strategy_metrics().await[0].avg_execution_time_ms >= 5.0);
+    }
+
+    #[test]
+    fn test_opportunity_timing_only_reports_completed_stages() {
+        let detected_at = Instant::now() - Duration::from_millis(20);
+        let mut timing = OpportunityTiming::new(detected_at);
+        timing.mark_fetched();
+
+        let stages: Vec<&str> = timing.stage_durations().iter().map(|(name, _)| *name).collect();
+        assert_eq!(stages, vec!["fetch"]);
+        assert!(timing.stage_durations()[0].1 >= 20);
+    }
+
+    #[tokio::test]
+    async fn test_window_excludes_executions_before_it() {
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        let result = MevStrategyResult {
+            success: true,
+            profit: 0.02,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 80,
+            strategy_type: MevStrategyType::Arbitrage,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        };
+        collector.record_opportunity_detected().await;
+        collector.record_strategy_execution(&result).await;
+
+        let now = now_unix();
+        let last_15 = collector.get_performance_in_window_at(15, now).await;
+        assert_eq!(last_15.total_opportunities_detected, 1);
+        assert_eq!(last_15.total_opportunities_executed, 1);
+        assert_eq!(last_15.total_successful_executions, 1);
+        assert!((last_15.total_profit - 0.02).abs() < 1e-9);
+        assert_eq!(last_15.execution_success_rate, 1.0);
+
+        // Seen from two hours on, it's outside the last hour but still inside the last day
+        let later = now + 7200;
+        assert_eq!(collector.get_performance_in_window_at(60, later).await.total_opportunities_executed, 0);
+        assert_eq!(collector.get_performance_in_window_at(1440, later).await.total_opportunities_executed, 1);
+        // The lifetime totals are unaffected
+        assert_eq!(collector.get_system_metrics().await.total_opportunities_executed, 1);
+    }
+
+    fn execution(strategy_type: MevStrategyType, success: bool, profit: f64) -> MevStrategyResult {
+        MevStrategyResult {
+            success,
+            profit,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 40,
+            strategy_type,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }
+    }
+
+    // Accepted by Jito, with its landing left to the bundle tracker
+    fn pending(strategy_type: MevStrategyType, profit: f64, bundle_id: &str) -> MevStrategyResult {
+        MevStrategyResult { pending: true, bundle_id: Some(bundle_id.to_string()), ..execution(strategy_type, false, profit) }
+    }
+
+    // Every record_* path that feeds an average, applied to a fresh collector in the given order
+    async fn record_in_order(collector: &MetricsCollector, order: &[usize]) {
+        for step in order {
+            match step {
+                0 => collector.record_opportunity_result(0.01, 0.0, 0.0, 0.0, 0.5, Vec::new(), 30, false, "arbitrage".to_string()).await,
+                1 => collector.record_strategy_execution(&execution(MevStrategyType::Arbitrage, true, 0.02)).await,
+                2 => collector.record_rpc_call("Primary", true, 12.5, 100, 200).await,
+                3 => collector.record_bundle_outcome(&ResolvedBundle {
+                    bundle_id: "bundle".to_string(),
+                    strategy_type: MevStrategyType::Sandwich,
+                    state: crate::utils::bundle_tracker::BundleState::Expired,
+                    tip: 0.0005,
+                    percentile: None,
+                    leader: crate::utils::leader_schedule::LeaderType::Jito,
+                    profit: 0.01,
+                    fees: 0.001,
+                    mints: Vec::new(),
+                    pools: Vec::new(),
+                    transactions: Vec::new(),
+                    simulation: None,
+                    simulation_accuracy: None,
+                    realized_profit: None,
+                }).await,
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn permutations(items: Vec<usize>) -> Vec<Vec<usize>> {
+        if items.len() <= 1 {
+            return vec![items];
+        }
+        let mut all = Vec::new();
+        for i in 0..items.len() {
+            let mut rest = items.clone();
+            let first = rest.remove(i);
+            for mut tail in permutations(rest) {
+                tail.insert(0, first);
+                all.push(tail);
+            }
+        }
+        all
+    }
+
+    fn assert_all_finite(body: &str, order: &[usize]) {
+        for line in body.lines().filter(|line| !line.starts_with('#')) {
+            let value: f64 = line.rsplit_once(' ').unwrap().1.parse().unwrap();
+            assert!(value.is_finite(), "{} after {:?}", line, order);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_averages_stay_finite_in_any_record_order() {
+        for order in permutations(vec![0, 1, 2, 3]) {
+            let collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+            record_in_order(&collector, &order).await;
+
+            let body = prometheus_exporter::PrometheusMetrics::new(Arc::clone(&collector)).format_prometheus().await;
+            assert_all_finite(&body, &order);
+
+            let system = collector.get_system_metrics().await;
+            for value in [system.execution_success_rate, system.avg_profit_per_success, system.avg_execution_time_ms, system.total_profit] {
+                assert!(value.is_finite(), "{:?}", order);
+            }
+            assert_eq!(system.avg_execution_time_ms, 40.0);
+            for strategy in collector.get_all_strategy_metrics().await {
+                assert!(strategy.avg_profit_per_execution.is_finite() && strategy.avg_execution_time_ms.is_finite(), "{:?}", order);
+            }
+            let rpc = collector.get_rpc_metrics("Primary").await.unwrap();
+            assert_eq!(rpc.avg_response_time_ms, 12.5);
+            assert_eq!(rpc.error_rate, 0.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_non_finite_inputs_never_reach_the_export() {
+        let collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        collector.record_strategy_execution(&execution(MevStrategyType::Arbitrage, true, f64::NAN)).await;
+        collector.record_strategy_execution(&execution(MevStrategyType::Arbitrage, true, 0.03)).await;
+        collector.record_rpc_call("Primary", false, f64::INFINITY, 0, 0).await;
+        collector.record_drawdown(f64::NAN, false).await;
+
+        let system = collector.get_system_metrics().await;
+        assert!((system.total_profit - 0.03).abs() < 1e-9);
+        assert!((system.avg_profit_per_success - 0.015).abs() < 1e-9);
+        assert_eq!(collector.get_rpc_metrics("Primary").await.unwrap().avg_response_time_ms, 0.0);
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::clone(&collector)).format_prometheus().await;
+        assert_all_finite(&body, &[]);
+        assert_eq!(
+            prometheus_exporter::finite_samples("# HELP x NaN\nx{a=\"b c\"} NaN\ny -inf\nz 1.5\n".to_string()),
+            "# HELP x NaN\nx{a=\"b c\"} 0\ny 0\nz 1.5\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_that_never_lands_becomes_a_false_positive() {
+        use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        let opportunity = OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.02,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        let (first, second) = (OpportunityTiming::new(Instant::now()), OpportunityTiming::new(Instant::now()));
+        assert_ne!(first.correlation_id, second.correlation_id);
+
+        for timing in [&first, &second] {
+            collector.record_opportunity_stage(timing.correlation_id, &opportunity, OpportunityStage::Detected).await;
+            collector.record_opportunity_stage(timing.correlation_id, &opportunity, OpportunityStage::Queued).await;
+        }
+        let sys_metrics = collector.get_system_metrics().await;
+        assert_eq!(sys_metrics.total_opportunities_detected, 2);
+        assert_eq!(sys_metrics.total_opportunities_evaluated, 2);
+        for (timing, bundle_id) in [(&first, "landed"), (&second, "dropped")] {
+            let result = pending(MevStrategyType::Arbitrage, 0.02, bundle_id);
+            collector.record_strategy_execution(&result).await;
+            collector.record_execution_outcome(timing.correlation_id, &result).await;
+        }
+        // Nothing is booked as made until a bundle lands
+        let sys_metrics = collector.get_system_metrics().await;
+        assert_eq!((sys_metrics.total_opportunities_executed, sys_metrics.total_successful_executions), (2, 0));
+        assert_eq!(sys_metrics.total_profit, 0.0);
+        assert_eq!(collector.calculate_false_positive_rate().await, 0.0);
+
+        for (bundle_id, state) in [("landed", crate::utils::bundle_tracker::BundleState::Landed), ("dropped", crate::utils::bundle_tracker::BundleState::Expired)] {
+            collector.record_bundle_outcome(&ResolvedBundle {
+                bundle_id: bundle_id.to_string(),
+                strategy_type: MevStrategyType::Arbitrage,
+                state,
+                tip: 0.0005,
+                percentile: None,
+                leader: crate::utils::leader_schedule::LeaderType::Jito,
+                profit: 0.02,
+                fees: 0.001,
+                mints: Vec::new(),
+                pools: Vec::new(),
+                transactions: Vec::new(),
+                simulation: None,
+                simulation_accuracy: None,
+                realized_profit: None,
+            }).await;
+        }
+        let sys_metrics = collector.get_system_metrics().await;
+        assert_eq!((sys_metrics.total_successful_executions, sys_metrics.total_bundles_landed, sys_metrics.total_bundles_failed), (1, 1, 1));
+        assert!((sys_metrics.total_profit - 0.02).abs() < 1e-12);
+        assert_eq!(sys_metrics.execution_success_rate, 0.5);
+        let strategy = collector.get_strategy_metrics(&MevStrategyType::Arbitrage).await.unwrap();
+        assert_eq!((strategy.executions, strategy.successes), (2, 1));
+        assert_eq!(collector.get_performance_in_window(60).await.total_successful_executions, 1);
+        assert_eq!(collector.calculate_false_positive_rate().await, 0.5);
+        assert_eq!(sys_metrics.false_positive_rate, 0.5);
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::new(collector)).format_prometheus().await;
+        assert!(body.contains("mev_bot_false_positive_rate 0.5000"));
+        assert!(body.contains("mev_bot_filter_precision_by_dex{dex=\"Raydium\"} 0.5000"));
+        assert!(body.contains("mev_bot_false_positive_rate_by_type{opportunity_type=\"arbitrage\"} 0.5000"));
+    }
+
+    #[tokio::test]
+    async fn test_latency_histograms_feed_the_averages_and_export() {
+        let collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        for execution_time_ms in [20, 40, 600] {
+            let mut result = execution(MevStrategyType::Sandwich, true, 0.01);
+            result.execution_time_ms = execution_time_ms;
+            collector.record_strategy_execution(&result).await;
+        }
+        collector.record_rpc_call("Primary", true, 3.0, 0, 0).await;
+        collector.record_rpc_call("Primary", true, 9.0, 0, 0).await;
+        let mut timing = OpportunityTiming::new(Instant::now());
+        timing.submitted_at = Some(timing.detected_at + Duration::from_millis(120));
+        collector.record_opportunity_timing(&timing).await;
+
+        let histograms = collector.get_latency_histograms().await;
+        assert_eq!(histograms.execution.count(), 3);
+        assert_eq!(collector.get_system_metrics().await.avg_execution_time_ms, histograms.execution.mean());
+        assert_eq!(collector.get_strategy_metrics(&MevStrategyType::Sandwich).await.unwrap().avg_execution_time_ms, 220.0);
+        assert_eq!(collector.get_rpc_metrics("Primary").await.unwrap().avg_response_time_ms, 6.0);
+        assert_eq!(histograms.detection_to_submission.count(), 1);
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::clone(&collector)).format_prometheus().await;
+        assert!(body.contains("# TYPE mev_bot_execution_ms histogram"));
+        assert!(body.contains("mev_bot_execution_ms_bucket{le=\"25\"} 1\n"));
+        assert!(body.contains("mev_bot_execution_ms_bucket{le=\"50\"} 2\n"));
+        assert!(body.contains("mev_bot_execution_ms_bucket{le=\"+Inf\"} 3\n"));
+        assert!(body.contains("mev_bot_execution_ms_sum 660.000\n"));
+        assert!(body.contains("mev_bot_execution_ms_count 3\n"));
+        assert!(body.contains("mev_bot_strategy_execution_ms_bucket{strategy=\"sandwich\",le=\"750\"} 3\n"));
+        assert!(body.contains("mev_bot_rpc_response_ms_count{endpoint=\"Primary\"} 2\n"));
+        assert!(body.contains("mev_bot_detection_to_submission_ms_bucket{le=\"250\"} 1\n"));
+        assert!(body.contains("mev_bot_execution_ms_quantile{quantile=\"0.99\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_token_pnl_follows_executions_and_reconciliation() {
+        use crate::utils::enhanced_transaction_simulator::OpportunityType;
+        use crate::utils::pool_creation::WSOL_MINT;
+
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        let opportunity = |token: &str| OpportunityDetails {
+            token_a: WSOL_MINT.to_string(),
+            token_b: token.to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.02,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        collector.record_token_outcome(&opportunity("BONK"), &execution(MevStrategyType::Arbitrage, true, 0.03)).await;
+        collector.record_token_outcome(&opportunity("WIF"), &execution(MevStrategyType::Sandwich, true, -0.02)).await;
+        collector.record_token_outcome(&opportunity("WIF"), &execution(MevStrategyType::Sandwich, false, -0.001)).await;
+        collector.record_token_outcome(&opportunity("JUP"), &pending(MevStrategyType::Arbitrage, 0.01, "jup")).await;
+        collector.record_token_outcome(&opportunity("BONK"), &pending(MevStrategyType::Arbitrage, 0.02, "bonk")).await;
+
+        // BONK's second bundle landed; JUP's never did
+        for (bundle_id, state, mint, profit) in [
+            ("bonk", crate::utils::bundle_tracker::BundleState::Landed, "BONK", 0.02),
+            ("jup", crate::utils::bundle_tracker::BundleState::Failed, "JUP", 0.01),
+        ] {
+            collector.record_bundle_outcome(&ResolvedBundle {
+                bundle_id: bundle_id.to_string(),
+                strategy_type: MevStrategyType::Arbitrage,
+                state,
+                tip: 0.0005,
+                percentile: None,
+                leader: crate::utils::leader_schedule::LeaderType::Jito,
+                profit,
+                fees: 0.001,
+                mints: vec![WSOL_MINT.to_string(), mint.to_string()],
+                pools: Vec::new(),
+                transactions: Vec::new(),
+                simulation: None,
+                simulation_accuracy: None,
+                realized_profit: None,
+            }).await;
+        }
+
+        let leaders = collector.get_token_pnl_leaders(5).await;
+        assert_eq!(leaders.winners.len(), 1);
+        assert_eq!(leaders.winners[0].mint, "BONK");
+        assert_eq!(leaders.losers.len(), 1);
+        assert_eq!((leaders.losers[0].mint.as_str(), leaders.losers[0].trades, leaders.losers[0].successes), ("WIF", 2, 1));
+        let jup = collector.get_token_pnl().await.into_iter().find(|token| token.mint == "JUP").unwrap();
+        assert_eq!((jup.trades, jup.successes), (1, 0));
+        assert!(jup.profit.abs() < 1e-12);
+        let bonk = collector.get_token_pnl().await.into_iter().find(|token| token.mint == "BONK").unwrap();
+        assert_eq!((bonk.trades, bonk.successes), (2, 2));
+
+        let json: serde_json::Value = serde_json::from_str(&collector.export_metrics_json().await.unwrap()).unwrap();
+        assert_eq!(json["tokens"]["losers"][0]["mint"], "WIF");
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::new(collector)).format_prometheus().await;
+        assert!(body.contains("mev_bot_token_profit_sol{mint=\"BONK\"} 0.050000"));
+        assert!(body.contains("mev_bot_token_fees_sol{mint=\"WIF\"} 0.003000"));
+        assert!(body.contains("mev_bot_token_trades{mint=\"WIF\"} 2"));
+        assert!(!body.contains(&format!("mint=\"{}\"", WSOL_MINT)));
+    }
+
+    #[tokio::test]
+    async fn test_reconciled_successes_calibrate_estimates() {
+        use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        let min_samples = collector.profit_calibration().config().min_samples;
+        let opportunity = OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.02,
+            dex: "Orca".to_string(),
+            opportunity_type: OpportunityType::Sandwich,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        // Each trade is booked at its 0.02 estimate but realizes 0.006 SOL gross once reconciled:
+        // 0.0045 net plus 0.0015 in fees and tip
+        for i in 0..min_samples + 1 {
+            let timing = OpportunityTiming::new(Instant::now());
+            collector.record_opportunity_stage(timing.correlation_id, &opportunity, OpportunityStage::Queued).await;
+            let result = pending(MevStrategyType::Sandwich, 0.0185, &format!("bundle-{}", i));
+            collector.record_execution_outcome(timing.correlation_id, &result).await;
+            // The last one couldn't be reconciled, so it says nothing about the estimate
+            let realized_profit = (i < min_samples).then_some(0.0045);
+            if let Some(bundle_id) = result.bundle_id {
+                collector.record_bundle_outcome(&ResolvedBundle {
+                    bundle_id,
+                    strategy_type: MevStrategyType::Sandwich,
+                    state: crate::utils::bundle_tracker::BundleState::Landed,
+                    tip: 0.0005,
+                    percentile: None,
+                    leader: crate::utils::leader_schedule::LeaderType::Jito,
+                    profit: 0.0185,
+                    fees: 0.001,
+                    mints: Vec::new(),
+                    pools: Vec::new(),
+                    transactions: Vec::new(),
+                    simulation: None,
+                    simulation_accuracy: None,
+                    realized_profit,
+                }).await;
+            }
+        }
+        // Losses and bundles that never landed aren't estimate errors
+        let timing = OpportunityTiming::new(Instant::now());
+        collector.record_opportunity_stage(timing.correlation_id, &opportunity, OpportunityStage::Queued).await;
+        collector.record_execution_outcome(timing.correlation_id, &execution(MevStrategyType::Sandwich, false, -0.01)).await;
+
+        let calibration = collector.profit_calibration();
+        assert!((calibration.factor("Sandwich", "Orca").await - 0.3).abs() < 1e-9);
+        assert!((calibration.calibrated_profit(&opportunity).await - 0.006).abs() < 1e-9);
+        assert_eq!(calibration.factors().await[0].samples, min_samples);
+        let alerts = collector.get_recent_alerts(10).await;
+        assert_eq!(alerts.iter().filter(|alert| matches!(alert.alert_type, AlertType::ProfitCalibration)).count(), 2);
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::new(collector)).format_prometheus().await;
+        assert!(body.contains("mev_bot_profit_calibration{scope=\"type\",key=\"Sandwich\"} 0.3000"));
+        assert!(body.contains("mev_bot_profit_calibration_samples{scope=\"dex\",key=\"Orca\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_landed_bundles_score_simulation_accuracy() {
+        use crate::utils::enhanced_transaction_simulator::OpportunityType;
+        use crate::utils::mev_simulation_pipeline::simulation_accuracy;
+
+        let collector = MetricsCollector::new(&Config::default()).unwrap();
+        let min_samples = collector.simulation_accuracy().config().min_samples;
+        let opportunity = OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "BONK".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.02,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Backrun,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        // Simulated 0.02 SOL, but every landed bundle lost 0.001
+        for i in 0..min_samples {
+            let timing = OpportunityTiming::new(Instant::now());
+            collector.record_opportunity_stage(timing.correlation_id, &opportunity, OpportunityStage::Queued).await;
+            collector.record_execution_outcome(timing.correlation_id, &pending(MevStrategyType::Backrun, 0.015, &format!("bundle-{}", i))).await;
+            collector.record_bundle_outcome(&ResolvedBundle {
+                bundle_id: format!("bundle-{}", i),
+                strategy_type: MevStrategyType::Backrun,
+                state: crate::utils::bundle_tracker::BundleState::Landed,
+                tip: 0.0005,
+                percentile: None,
+                leader: crate::utils::leader_schedule::LeaderType::Jito,
+                profit: 0.015,
+                fees: 0.001,
+                mints: Vec::new(),
+                pools: Vec::new(),
+                transactions: vec![format!("sig-{}", i)],
+                simulation: None,
+                simulation_accuracy: Some(simulation_accuracy(0.02, -0.001)),
+                realized_profit: None,
+            }).await;
+        }
+
+        let scores = collector.simulation_accuracy().scores().await;
+        assert_eq!(scores.iter().map(|score| (score.scope, score.key.as_str(), score.accuracy)).collect::<Vec<_>>(), vec![
+            ("type", "Backrun", 0.0), ("dex", "Raydium", 0.0),
+        ]);
+        let alerts = collector.get_recent_alerts(10).await;
+        assert_eq!(alerts.iter().filter(|alert| matches!(alert.alert_type, AlertType::SimulationAccuracy)).count(), 2);
+
+        let body = prometheus_exporter::PrometheusMetrics::new(Arc::new(collector)).format_prometheus().await;
+        assert!(body.contains("mev_bot_simulation_accuracy{scope=\"type\",key=\"Backrun\"} 0.0000"));
+        assert!(body.contains(&format!("mev_bot_simulation_accuracy_samples{{scope=\"dex\",key=\"Raydium\"}} {}", min_samples)));
+    }
+}
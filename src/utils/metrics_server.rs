@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use crate::config::MetricsConfig;
+use crate::logging::Logger;
+use crate::utils::metrics_collector::MetricsCollector;
+use crate::utils::metrics_collector::prometheus_exporter::PrometheusMetrics;
+use crate::utils::risk_controls::RiskManager;
+use crate::utils::supervisor::RestartTracker;
+
+#[derive(Debug, Default)]
+struct StreamState {
+    subscribed: HashSet<String>, // Endpoints with a confirmed subscription
+    last_message: Option<Instant>,
+}
+
+// Whether the notification stream is actually delivering: some endpoint holds a confirmed
+// subscription and a notification arrived recently. Cheap to clone: clones share the same state
+#[derive(Debug, Clone)]
+pub struct StreamLiveness {
+    stale_after: Duration,
+    state: Arc<Mutex<StreamState>>,
+}
+
+impl StreamLiveness {
+    pub fn new(stale_after: Duration) -> Self {
+        Self { stale_after, state: Arc::new(Mutex::new(StreamState::default())) }
+    }
+
+    pub fn from_config(config: &MetricsConfig) -> Self {
+        Self::new(Duration::from_secs(config.health_stream_stale_secs))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, StreamState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // The endpoint counts as subscribed until the returned guard is dropped, i.e. on whichever
+    // path its connection ends
+    pub fn subscribed(&self, endpoint: &str) -> StreamSubscription {
+        self.state().subscribed.insert(endpoint.to_string());
+        StreamSubscription { liveness: self.clone(), endpoint: endpoint.to_string() }
+    }
+
+    pub fn record_message(&self) {
+        self.record_message_at(Instant::now());
+    }
+
+    pub fn record_message_at(&self, now: Instant) {
+        self.state().last_message = Some(now);
+    }
+
+    // None while live
+    pub fn unhealthy_reason_at(&self, now: Instant) -> Option<String> {
+        let state = self.state();
+        if state.subscribed.is_empty() {
+            return Some("no WebSocket endpoint is subscribed".to_string());
+        }
+        match state.last_message {
+            Some(last) if now.saturating_duration_since(last) <= self.stale_after => None,
+            Some(last) => Some(format!("no notification for {}s", now.saturating_duration_since(last).as_secs())),
+            None => Some("no notification received yet".to_string()),
+        }
+    }
+}
+
+pub struct StreamSubscription {
+    liveness: StreamLiveness,
+    endpoint: String,
+}
+
+impl Drop for StreamSubscription {
+    fn drop(&mut self) {
+        self.liveness.state().subscribed.remove(&self.endpoint);
+    }
+}
+
+// GET /metrics serves the Prometheus text of the mempool's MetricsCollector; GET /healthz answers
+// 200 while the notification stream is live and the risk layer isn't pausing trading, 503 otherwise
+pub struct MetricsServer {
+    metrics_collector: Arc<MetricsCollector>,
+    stream: StreamLiveness,
+    risk_manager: Option<Arc<RiskManager>>,
+    restarts: Option<RestartTracker>, // The supervisor's, when the mempool runs under one
+}
+
+impl MetricsServer {
+    pub fn new(metrics_collector: Arc<MetricsCollector>, stream: StreamLiveness, risk_manager: Option<Arc<RiskManager>>) -> Self {
+        Self { metrics_collector, stream, risk_manager, restarts: None }
+    }
+
+    // Adds the mempool restart count to /metrics, and it and the last crash to /healthz
+    pub fn with_restarts(mut self, restarts: RestartTracker) -> Self {
+        self.restarts = Some(restarts);
+        self
+    }
+
+    // Binds right away, so a port that's taken fails startup, then serves until `shutdown` flips
+    // to true. Returns the bound address (useful with port 0) and the server task
+    pub fn start(self, addr: SocketAddr, mut shutdown: watch::Receiver<bool>) -> Result<(SocketAddr, JoinHandle<()>), Box<dyn std::error::Error + Send + Sync>> {
+        let builder = Server::try_bind(&addr)
+            .map_err(|e| format!("Failed to bind metrics server to {}: {}", addr, e))?;
+
+        let server = Arc::new(self);
+        let make_service = make_service_fn(move |_| {
+            let server = Arc::clone(&server);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = Arc::clone(&server);
+                    async move { Ok::<_, Infallible>(server.handle(request).await) }
+                }))
+            }
+        });
+
+        let running = builder.serve(make_service);
+        let local_addr = running.local_addr();
+        let graceful = running.with_graceful_shutdown(async move {
+            // A dropped sender means the owner is gone, which is treated as shutdown
+            while !*shutdown.borrow() {
+                if shutdown.changed().await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Logger::status_update(&format!("Serving /metrics and /healthz on {}", local_addr));
+        let handle = tokio::spawn(async move {
+            if let Err(e) = graceful.await {
+                Logger::error_occurred(&format!("Metrics server failed: {}", e));
+            }
+        });
+        Ok((local_addr, handle))
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        match (request.method(), request.uri().path()) {
+            (&Method::GET, "/metrics") => {
+                let mut body = PrometheusMetrics::new(Arc::clone(&self.metrics_collector)).format_prometheus().await;
+                if let Some(ref restarts) = self.restarts {
+                    body.push_str("# HELP mev_bot_mempool_restarts_total Times the supervisor restarted the mempool after a crash\n");
+                    body.push_str("# TYPE mev_bot_mempool_restarts_total counter\n");
+                    body.push_str(&format!("mev_bot_mempool_restarts_total {}\n", restarts.restarts()));
+                }
+                text_response(StatusCode::OK, body)
+            }
+            (&Method::GET, "/healthz") => {
+                let (status, mut body) = match self.unhealthy_reason().await {
+                    None => (StatusCode::OK, "ok\n".to_string()),
+                    Some(reason) => (StatusCode::SERVICE_UNAVAILABLE, format!("{}\n", reason)),
+                };
+                // A restarted mempool that is live again is healthy; the history is informational
+                if let Some(ref restarts) = self.restarts {
+                    body.push_str(&format!("mempool restarts: {}\n", restarts.restarts()));
+                    if let Some(error) = restarts.last_error() {
+                        body.push_str(&format!("last error: {}\n", error));
+                    }
+                }
+                text_response(status, body)
+            }
+            _ => text_response(StatusCode::NOT_FOUND, "not found\n".to_string()),
+        }
+    }
+
+    // None while healthy
+    pub async fn unhealthy_reason(&self) -> Option<String> {
+        if let Some(reason) = self.stream.unhealthy_reason_at(Instant::now()) {
+            return Some(reason);
+        }
+        if let Some(ref risk_manager) = self.risk_manager {
+            if risk_manager.should_pause_operations().await {
+                return Some("risk controls paused trading".to_string());
+            }
+        }
+        None
+    }
+}
+
+fn text_response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    // Prometheus text exposition format
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; version=0.0.4"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mev_strategies::{MevStrategyResult, MevStrategyType};
+    use crate::config::Config;
+    use crate::utils::risk_controls::RiskLimits;
+
+    fn metric_value(body: &str, name: &str) -> Option<f64> {
+        body.lines()
+            .find_map(|line| line.strip_prefix(name).and_then(|rest| rest.strip_prefix(' ')))
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> reqwest::Response {
+        reqwest::get(format!("http://{}{}", addr, path)).await.unwrap()
+    }
+
+    #[test]
+    fn test_stream_goes_stale_and_loses_its_subscriptions() {
+        let liveness = StreamLiveness::new(Duration::from_secs(30));
+        let start = Instant::now();
+        assert!(liveness.unhealthy_reason_at(start).is_some());
+
+        let subscription = liveness.subscribed("helius");
+        assert_eq!(liveness.unhealthy_reason_at(start), Some("no notification received yet".to_string()));
+        liveness.record_message_at(start);
+        assert_eq!(liveness.unhealthy_reason_at(start + Duration::from_secs(30)), None);
+        assert!(liveness.unhealthy_reason_at(start + Duration::from_secs(31)).is_some());
+
+        // The connection ended
+        drop(subscription);
+        assert_eq!(liveness.unhealthy_reason_at(start), Some("no WebSocket endpoint is subscribed".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_follows_recorded_executions_and_health() {
+        let metrics_collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let liveness = StreamLiveness::new(Duration::from_secs(30));
+        let limits = RiskLimits { min_balance_threshold: 0.5, ..RiskLimits::from_config(&Config::default().risk) };
+        let risk_manager = Arc::new(RiskManager::with_limits(limits));
+        risk_manager.initialize_balance(2.0).await;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = MetricsServer::new(Arc::clone(&metrics_collector), liveness.clone(), Some(Arc::clone(&risk_manager)));
+        let (addr, handle) = server.start("127.0.0.1:0".parse().unwrap(), shutdown_rx).unwrap();
+
+        let before = get(addr, "/metrics").await.text().await.unwrap();
+        assert_eq!(metric_value(&before, "mev_bot_total_opportunities_executed"), Some(0.0));
+
+        metrics_collector.record_strategy_execution(&MevStrategyResult {
+            success: true,
+            profit: 0.02,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 120,
+            strategy_type: MevStrategyType::Arbitrage,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }).await;
+        let after = get(addr, "/metrics").await.text().await.unwrap();
+        assert_eq!(metric_value(&after, "mev_bot_total_opportunities_executed"), Some(1.0));
+        assert_eq!(metric_value(&after, "mev_bot_total_successful_executions"), Some(1.0));
+        assert_eq!(metric_value(&after, "mev_bot_strategy_arbitrage_executions_total"), Some(1.0));
+
+        // Unhealthy until the stream delivers, then again once the balance falls under the minimum
+        assert_eq!(get(addr, "/healthz").await.status().as_u16(), 503);
+        let _subscription = liveness.subscribed("helius");
+        liveness.record_message();
+        assert_eq!(get(addr, "/healthz").await.status().as_u16(), 200);
+        let _ = risk_manager.update_balance(0.4).await;
+        assert_eq!(get(addr, "/healthz").await.status().as_u16(), 503);
+
+        let _ = shutdown_tx.send(true);
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restarts_are_reported_on_metrics_and_health() {
+        let metrics_collector = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let liveness = StreamLiveness::new(Duration::from_secs(30));
+        let restarts = RestartTracker::new();
+        restarts.record_failure("Solana mempool panicked: malformed frame");
+        restarts.record_restart();
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let server = MetricsServer::new(metrics_collector, liveness.clone(), None).with_restarts(restarts);
+        let (addr, handle) = server.start("127.0.0.1:0".parse().unwrap(), shutdown_rx).unwrap();
+
+        let metrics = get(addr, "/metrics").await.text().await.unwrap();
+        assert_eq!(metric_value(&metrics, "mev_bot_mempool_restarts_total"), Some(1.0));
+
+        // The restarted mempool is live again
+        let _subscription = liveness.subscribed("helius");
+        liveness.record_message();
+        let health = get(addr, "/healthz").await;
+        assert_eq!(health.status().as_u16(), 200);
+        assert_eq!(
+            health.text().await.unwrap(),
+            "ok\nmempool restarts: 1\nlast error: Solana mempool panicked: malformed frame\n"
+        );
+
+        let _ = shutdown_tx.send(true);
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+    }
+}
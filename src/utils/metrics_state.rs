@@ -0,0 +1,234 @@
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{watch, Mutex};
+use crate::config::MetricsConfig;
+use crate::logging::Logger;
+use crate::utils::analytics::Analytics;
+use crate::utils::metrics_collector::{MetricsCollector, PersistedMetrics};
+use crate::utils::risk_state::quarantine;
+use crate::utils::tip_history::now_unix;
+
+// Bumped on any change that older files can't be read as; those are moved aside, not loaded
+const FILE_VERSION: u32 = 1;
+
+// Session totals written periodically and at shutdown, so dashboards don't drop to zero on
+// every deploy when RESTORE_METRICS_STATE is on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsState {
+    version: u32,
+    pub saved_at: u64, // Unix seconds
+    pub metrics: PersistedMetrics,
+    pub analytics: Analytics,
+}
+
+impl MetricsState {
+    pub fn new(metrics: PersistedMetrics, analytics: Analytics) -> Self {
+        Self { version: FILE_VERSION, saved_at: now_unix(), metrics, analytics }
+    }
+
+    // A missing file is a fresh start; an unreadable one or one from another version is moved
+    // aside and also starts fresh
+    pub fn load(path: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match Self::parse(&contents) {
+            Ok(state) => Some(state),
+            Err(reason) => {
+                quarantine(path, "metrics state", &reason);
+                None
+            }
+        }
+    }
+
+    // For inspecting a file without starting the bot: nothing is moved aside, problems are errors
+    pub fn read(path: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read metrics state from {}: {}", path, e))?;
+        Self::parse(&contents)
+            .map_err(|e| format!("Invalid metrics state in {}: {}", path, e).into())
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        // The version is read on its own first, so a file whose schema changed is reported as such
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+        let versioned = serde_json::from_str::<Versioned>(contents).map_err(|e| e.to_string())?;
+        if versioned.version != FILE_VERSION {
+            return Err(format!("unsupported version {}", versioned.version));
+        }
+        serde_json::from_str::<MetricsState>(contents).map_err(|e| e.to_string())
+    }
+
+        pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize metrics state: {}", e))?;
+        crate::utils::atomic_write(path, json.as_bytes())
+            .map_err(|e| format!("Failed to write metrics state to {}: {}", path, e).into())
+    }
+}
+
+// Flushes MetricsCollector and the executor's Analytics to the metrics state file and, when asked
+// to, continues from it at startup
+pub struct MetricsPersistence {
+    metrics_collector: Arc<MetricsCollector>,
+    path: String,
+    flush_interval: Option<Duration>, // None flushes at shutdown only
+    restore: bool,
+}
+
+impl MetricsPersistence {
+    pub fn new(metrics_collector: Arc<MetricsCollector>, path: String, flush_interval: Option<Duration>, restore: bool) -> Self {
+        Self { metrics_collector, path, flush_interval, restore }
+    }
+
+    pub fn from_config(metrics_collector: Arc<MetricsCollector>, config: &MetricsConfig) -> Self {
+        let flush_secs = config.state_flush_secs; // 0 flushes at shutdown only
+        Self::new(
+            metrics_collector,
+            config.state_file.clone(),
+            (flush_secs > 0).then(|| Duration::from_secs(flush_secs)),
+            config.restore_state,
+        )
+    }
+
+    // Continues the saved counters when RESTORE_METRICS_STATE is on. State from a dry run isn't
+    // mixed into live totals, nor the other way around
+    pub async fn restore(&self, analytics: &Mutex<Analytics>) -> bool {
+        if !self.restore {
+            return false;
+        }
+        let Some(state) = MetricsState::load(&self.path) else {
+            return false;
+        };
+        if state.metrics.system.dry_run != analytics.lock().await.dry_run {
+            Logger::status_update(&format!(
+                "Not restoring metrics state from {}: it was saved with DRY_RUN={}",
+                self.path, state.metrics.system.dry_run
+            ));
+            return false;
+        }
+        Logger::status_update(&format!(
+            "Restored metrics state from {}: {} executions, {:.4} SOL profit since the session started",
+            self.path, state.metrics.system.total_opportunities_executed, state.metrics.system.total_profit
+        ));
+        self.metrics_collector.restore_persisted_metrics(state.metrics).await;
+        *analytics.lock().await = state.analytics;
+        true
+    }
+
+    pub async fn flush(&self, analytics: &Mutex<Analytics>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let metrics = self.metrics_collector.persisted_metrics().await;
+        let analytics = analytics.lock().await.clone();
+        MetricsState::new(metrics, analytics).save(&self.path)
+    }
+
+    // The shutdown flush is left to whoever drains, once in-flight executions are booked
+    pub fn spawn(self: Arc<Self>, analytics: Arc<Mutex<Analytics>>, mut shutdown: watch::Receiver<bool>) {
+        let Some(interval) = self.flush_interval else { return };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if let Err(e) = self.flush(&analytics).await {
+                    Logger::error_occurred(&format!("Failed to flush metrics state: {}", e));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mev_strategies::{MevStrategyResult, MevStrategyType};
+    use crate::config::Config;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("metrics_state_{}_{}.json", name, std::process::id())).to_string_lossy().to_string()
+    }
+
+    fn execution(strategy_type: MevStrategyType, success: bool, profit: f64) -> MevStrategyResult {
+        MevStrategyResult {
+            success,
+            profit,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 40,
+            strategy_type,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }
+    }
+
+    async fn record(collector: &MetricsCollector, analytics: &Mutex<Analytics>, result: &MevStrategyResult) {
+        collector.record_strategy_execution(result).await;
+        analytics.lock().await.record_transaction(&format!("{:?}", result.strategy_type), result.success, result.profit, result.fees_paid);
+    }
+
+    #[tokio::test]
+    async fn test_restored_counters_continue_monotonically() {
+        let path = temp_path("roundtrip");
+        let first = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let first_analytics = Mutex::new(Analytics::new(false));
+        record(&first, &first_analytics, &execution(MevStrategyType::Arbitrage, true, 0.02)).await;
+        record(&first, &first_analytics, &execution(MevStrategyType::Sandwich, false, -0.001)).await;
+        let persistence = MetricsPersistence::new(Arc::clone(&first), path.clone(), None, true);
+        persistence.flush(&first_analytics).await.unwrap();
+        let before = first.get_system_metrics().await;
+
+        // A later process picks up where the first left off
+        let second = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let second_analytics = Mutex::new(Analytics::new(false));
+        let persistence = MetricsPersistence::new(Arc::clone(&second), path.clone(), None, true);
+        assert!(persistence.restore(&second_analytics).await);
+        let restored = second.get_system_metrics().await;
+        assert_eq!(restored.total_opportunities_executed, 2);
+        assert_eq!(restored.start_time, before.start_time);
+        assert!(restored.process_start_time >= before.process_start_time);
+
+        record(&second, &second_analytics, &execution(MevStrategyType::Arbitrage, true, 0.03)).await;
+        let after = second.get_system_metrics().await;
+        assert_eq!(after.total_opportunities_executed, 3);
+        assert_eq!(after.total_successful_executions, 2);
+        assert!((after.total_profit - 0.05).abs() < 1e-9);
+        assert_eq!(after.avg_execution_time_ms, 40.0);
+        let arbitrage = second.get_strategy_metrics(&MevStrategyType::Arbitrage).await.unwrap();
+        assert_eq!((arbitrage.executions, arbitrage.successes), (2, 2));
+        let analytics = second_analytics.lock().await;
+        assert_eq!(analytics.total_transactions, 3);
+        assert_eq!(analytics.strategy_performance["Arbitrage"].executions, 2);
+        drop(analytics);
+
+        // Off by default: nothing is loaded
+        let third = Arc::new(MetricsCollector::new(&Config::default()).unwrap());
+        let persistence = MetricsPersistence::new(Arc::clone(&third), path.clone(), None, false);
+        assert!(!persistence.restore(&Mutex::new(Analytics::new(false))).await);
+        assert_eq!(third.get_system_metrics().await.total_opportunities_executed, 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_other_versions_are_set_aside() {
+        let path = temp_path("version");
+        std::fs::write(&path, r#"{"version": 99, "saved_at": 0}"#).unwrap();
+
+        assert!(MetricsState::load(&path).is_none());
+        assert!(!std::path::Path::new(&path).exists());
+
+        let prefix = format!("{}.corrupt-", std::path::Path::new(&path).file_name().unwrap().to_string_lossy());
+        for entry in std::fs::read_dir(std::env::temp_dir()).unwrap().filter_map(Result::ok) {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
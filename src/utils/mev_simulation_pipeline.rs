@@ -1,17 +1,58 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use base64::Engine;
+use serde_json::Value;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::VersionedTransaction;
 use crate::logging::Logger;
 use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::bounded_cache::{BoundedCache, CacheStats};
+use crate::utils::bundle_simulation::{self, BundleSimulation};
+use crate::utils::dex_swap_instructions::{self, DexSwapInstructions};
+use crate::utils::mev_strategies::RouteLeg;
+use crate::utils::simulation_effects::{self, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::utils::simulation_config::{self, SimulationConfig};
+use crate::utils::simulation_variance::{self, PoolInputs};
+use crate::utils::network_congestion;
 use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
+use crate::utils::jito::JitoClient;
+use crate::utils::opportunity_evaluator::OpportunityEvaluator;
+use crate::utils::pool_creation::WSOL_MINT;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenBalance {
+    pub amount: f64, // UI units
+    pub decimals: u8,
+}
 
 #[derive(Debug, Clone)]
 pub struct BalanceSnapshot {
-    pub token_balances: HashMap<String, f64>, // token_address -> balance
+    pub token_balances: HashMap<String, TokenBalance>, // mint -> balance over all of the wallet's accounts
     pub sol_balance: f64,
     pub timestamp: std::time::SystemTime,
 }
 
+impl BalanceSnapshot {
+    // UI amount of each mint gained (positive) or spent since `before`; mints that didn't move are left out
+    pub fn token_changes_since(&self, before: &BalanceSnapshot) -> HashMap<String, f64> {
+        let mints: HashSet<&String> = self.token_balances.keys().chain(before.token_balances.keys()).collect();
+        let amount = |snapshot: &BalanceSnapshot, mint: &str| snapshot.token_balances.get(mint).map_or(0.0, |balance| balance.amount);
+        mints.into_iter()
+            .map(|mint| (mint.clone(), amount(self, mint) - amount(before, mint)))
+            .filter(|(_, change)| *change != 0.0)
+            .collect()
+    }
+
+    // SOL gained since `before`, with inventory changes valued at `prices_in_sol` (mint -> SOL per
+    // token). Mints without a price are left out
+    pub fn net_change_in_sol(&self, before: &BalanceSnapshot, prices_in_sol: &HashMap<String, f64>) -> f64 {
+        let inventory: f64 = self.token_changes_since(before).iter()
+            .filter_map(|(mint, change)| prices_in_sol.get(mint).map(|price| change * price))
+            .sum();
+        self.sol_balance - before.sol_balance + inventory
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SimulationStep {
     pub step_type: SimulationStepType,
@@ -20,50 +61,262 @@ pub struct SimulationStep {
     pub actual_effects: Option<TransactionEffects>,
 }
 
-#[derive(Debug, Clone)]
+impl SimulationStep {
+    // Whether we send the transaction, i.e. it isn't the victim's
+    pub fn is_own(&self) -> bool {
+        !matches!(self.step_type, SimulationStepType::Target { signature: Some(_) })
+    }
+    
+    fn unbuilt(step_type: SimulationStepType, error: String) -> Self {
+        Self { step_type, transaction_data: String::new(), expected_effects: TransactionEffects::failed(error), actual_effects: None }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum SimulationStepType {
     Frontrun,
-    Target,
+    Target { signature: Option<String> }, // The victim's signature; None when the step is our own transaction
     Backrun,
 }
 
+// The transaction a sandwich wraps, as its sender signed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetTransaction {
+    pub signature: String,
+    pub transaction: Vec<u8>, // Serialized VersionedTransaction
+}
+
+impl TargetTransaction {
+    // None when the bytes aren't a signed transaction
+    pub fn from_bytes(transaction: Vec<u8>) -> Option<Self> {
+        let decoded: VersionedTransaction = bincode::deserialize(&transaction).ok()?;
+        let signature = decoded.signatures.first()?.to_string();
+        Some(Self { signature, transaction })
+    }
+
+    // From a getTransaction result or pending transaction details carrying the signed
+    // transaction as ["<base64>", "base64"]; None when they only have it in JSON form
+    pub fn from_details(details: &Value) -> Option<Self> {
+        let encoded = details["transaction"][0].as_str()
+            .filter(|_| details["transaction"][1] == "base64")?;
+        Self::from_bytes(base64::engine::general_purpose::STANDARD.decode(encoded).ok()?)
+    }
+
+    pub fn to_base58(&self) -> String {
+        bs58::encode(&self.transaction).into_string()
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(&self.transaction)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionEffects {
-    pub token_balance_changes: HashMap<String, f64>, // token_address -> change amount
+    pub token_balance_changes: HashMap<String, f64>, // mint -> change in UI units
     pub sol_balance_change: f64,
     pub fees_paid: f64,
+    pub units_consumed: u64,
     pub success: bool,
+    pub error: Option<String>, // Why the simulation failed, program message included
+}
+
+impl TransactionEffects {
+    pub fn failed(error: String) -> Self {
+        Self {
+            token_balance_changes: HashMap::new(),
+            sol_balance_change: 0.0,
+            fees_paid: 0.0,
+            units_consumed: 0,
+            success: false,
+            error: Some(error),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct MevSimulationResult {
-    pub pre_execution_snapshot: BalanceSnapshot,
-    pub post_execution_snapshot: BalanceSnapshot,
-    pub net_profit: f64,
+    pub pre_execution_snapshot: BalanceSnapshot, // The wallet the simulation ran against
+    pub net_profit: f64, // See steps_net_profit
     pub total_fees_paid: f64,
     pub simulation_steps: Vec<SimulationStep>,
     pub is_profitable: bool,
     pub confidence_score: f64,
+    pub confidence_breakdown: HashMap<String, f64>, // What the confidence score is made of; see SimulationConfig
     pub execution_variance: f64, // How much the result varies under different conditions
 }
 
+impl MevSimulationResult {
+    // Compute units our own transactions consumed, the victim's left out; None when none of them
+    // reported any, e.g. because they couldn't be simulated
+    pub fn compute_units(&self) -> Option<u64> {
+        let units: u64 = self.simulation_steps.iter()
+            .filter(|step| step.is_own())
+            .map(|step| step.expected_effects.units_consumed)
+            .sum();
+        (units > 0).then_some(units)
+    }
+}
+
+// Results reused for near-identical opportunities, which bursty mempools produce several of
+// within a second. Keys start with the pool address, so a reserve change on the pool drops them
+#[derive(Clone)]
+pub struct SimulationCache {
+    results: BoundedCache<MevSimulationResult>,
+}
+
+impl SimulationCache {
+    const CAPACITY: usize = 256;
+    
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self { results: BoundedCache::new(Self::CAPACITY, ttl) }
+    }
+    
+    // Pool, direction, type, size bucket, blockhash and the signature of the target simulated
+    // with it; None without a pool to key on. Sizes within about 19% of each other share a
+    // bucket (quarter octaves)
+    pub fn fingerprint(opportunity: &OpportunityDetails, blockhash: &str, target: Option<&str>) -> Option<String> {
+        let pool_address = opportunity.pool_address.as_deref()?;
+        let size_bucket = match opportunity.trade_size {
+            0 => -1,
+            size => ((size as f64).log2() * 4.0).floor() as i64,
+        };
+        Some(format!(
+            "{}:{}>{}:{:?}:{}:{}:{}",
+            pool_address, opportunity.token_a, opportunity.token_b, opportunity.opportunity_type, size_bucket, blockhash,
+            target.unwrap_or("-")
+        ))
+    }
+    
+    pub async fn get(&self, fingerprint: &str) -> Option<MevSimulationResult> {
+        self.results.get(fingerprint).await
+    }
+    
+    pub async fn insert(&self, fingerprint: String, result: MevSimulationResult) {
+        self.results.insert(fingerprint, result).await;
+    }
+    
+    pub async fn invalidate_pool(&self, pool_address: &str) {
+        let prefix = format!("{}:", pool_address);
+        self.results.retain(|fingerprint, _| !fingerprint.starts_with(&prefix)).await;
+    }
+    
+    pub async fn clear(&self) {
+        self.results.retain(|_, _| false).await;
+    }
+    
+    pub async fn stats(&self) -> CacheStats {
+        self.results.stats().await
+    }
+}
+
 pub struct MevSimulationPipeline {
     rpc_manager: Arc<RpcManager>,
-    max_variance_threshold: f64, // Max acceptable variance (e.g., 0.1 = 10%)
+    opportunity_evaluator: Arc<OpportunityEvaluator>, // Prices inventory changes in SOL
+    signer: OnceLock<Arc<Keypair>>, // The executor's keypair, set once it has loaded
+    swaps: DexSwapInstructions,
+    jito_client: Option<JitoClient>, // For simulateBundle
+    config: SimulationConfig,
+    cache: Option<SimulationCache>, // None with SIMULATION_CACHE_TTL_MS=0
 }
 
 impl MevSimulationPipeline {
-    pub async fn new(rpc_manager: Arc<RpcManager>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn new(
+        rpc_manager: Arc<RpcManager>,
+        opportunity_evaluator: Arc<OpportunityEvaluator>,
+        config: SimulationConfig
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        config.validate()?;
+        let cache = (!config.cache_ttl.is_zero()).then(|| SimulationCache::new(config.cache_ttl));
+        
+        // Results for a pool are stale as soon as the pool watcher sees its reserves move
+        if let Some(cache) = cache.clone() {
+            let mut updates = opportunity_evaluator.pool_watcher().subscribe_updates();
+            tokio::spawn(async move {
+                loop {
+                    match updates.recv().await {
+                        Ok(pool_state) => cache.invalidate_pool(&pool_state.pool_address).await,
+                        // Missed updates could be for any pool
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => cache.clear().await,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                    }
+                }
+            });
+        }
+        
         Ok(Self {
+            swaps: DexSwapInstructions::new(Arc::clone(&rpc_manager)),
             rpc_manager,
-            max_variance_threshold: 0.1, // 10% maximum acceptable variance
+            opportunity_evaluator,
+            signer: OnceLock::new(),
+            jito_client: None,
+            config,
+            cache,
         })
     }
     
+    // The bot's client, for simulateBundle; None simulates transaction by transaction
+    pub fn with_jito_client(mut self, jito_client: Option<JitoClient>) -> Self {
+        self.jito_client = jito_client;
+        self
+    }
+    
+    // Simulated transactions are signed by, and snapshots follow, the executor's keypair; only the
+    // first call takes effect
+    pub fn set_signer(&self, signer: Arc<Keypair>) {
+        let _ = self.signer.set(signer);
+    }
+    
+    pub fn signer(&self) -> Result<Arc<Keypair>, Box<dyn std::error::Error + Send + Sync>> {
+        self.signer.get().cloned().ok_or_else(|| "Wallet not known yet: the executor's keypair hasn't loaded".into())
+    }
+    
+    pub fn wallet(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.signer()?.pubkey().to_string())
+    }
+    
+    // Simulates the exact bundle about to be sent (base58 transactions, tip included) as one
+    // atomic sequence through simulateBundle. Where the endpoint doesn't support it, each
+    // transaction goes through simulateTransaction on its own instead
+    pub async fn simulate_bundle(&self, transactions: &[String]) -> Result<BundleSimulation, Box<dyn std::error::Error + Send + Sync>> {
+        let encoded: Vec<String> = transactions.iter()
+            .map(|tx| bundle_simulation::to_base64(tx).ok_or("Bundle transaction isn't base58-encoded"))
+            .collect::<Result<_, _>>()?;
+        
+        if let Some(ref jito_client) = self.jito_client {
+            match jito_client.simulate_bundle(&encoded).await {
+                Ok(simulation) => return Ok(simulation),
+                Err(e) if bundle_simulation::is_method_unsupported(&e.to_string()) => {
+                    Logger::status_update("simulateBundle not supported by the endpoint; simulating transactions one by one");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        
+        let mut responses = Vec::with_capacity(encoded.len());
+        for tx in &encoded {
+            responses.push(self.rpc_manager.simulate_transaction(tx).await?);
+        }
+        Ok(bundle_simulation::from_transaction_simulations(&responses))
+    }
+    
+    // `target` is the victim a sandwich is built around; other opportunity types ignore it
     pub async fn run_bundle_simulation(
         &self, 
-        opportunity: &OpportunityDetails
+        opportunity: &OpportunityDetails,
+        target: Option<&TargetTransaction>
     ) -> Result<MevSimulationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let fingerprint = match self.cache {
+            Some(_) => self.fingerprint(opportunity, target).await,
+            None => None,
+        };
+        if let (Some(cache), Some(fingerprint)) = (&self.cache, &fingerprint) {
+            if let Some(result) = cache.get(fingerprint).await {
+                Logger::status_update("Reusing the simulation of a near-identical opportunity");
+                return Ok(result);
+            }
+        }
+        
         Logger::status_update("Starting MEV bundle simulation");
         
         // Step 1: Take pre-execution balance snapshot
@@ -72,7 +325,7 @@ impl MevSimulationPipeline {
         // Step 2: Simulate the full bundle sequence
         let simulation_result = match opportunity.opportunity_type {
             OpportunityType::Sandwich => {
-                self.simulate_sandwich_bundle(opportunity).await?
+                self.simulate_sandwich_bundle(opportunity, target).await?
             },
             OpportunityType::Arbitrage => {
                 self.simulate_arbitrage_bundle(opportunity).await?
@@ -86,42 +339,36 @@ impl MevSimulationPipeline {
             }
         };
         
-        // Step 3: Take post-execution balance snapshot
-        let post_snapshot = self.take_balance_snapshot().await?;
-        
-        // Calculate net profit from pre/post snapshots
-        let net_profit = self.calculate_net_profit(&pre_snapshot, &post_snapshot)?;
+        // Nothing lands in a simulation, so the profit comes from what each of our steps did
+        let net_profit = self.steps_net_profit(&simulation_result.simulation_steps).await;
         
         // Run multiple simulation scenarios to assess variance
         let variance = self.assess_simulation_variance(opportunity).await?;
         
-        // Create the result first
+        // A step that failed to simulate would revert the bundle
+        let failed_step = simulation_result.simulation_steps.iter()
+            .find(|step| !step.expected_effects.success);
+        if let Some(step) = failed_step {
+            Logger::status_update(&format!(
+                "{:?} step failed simulation: {}",
+                step.step_type,
+                step.expected_effects.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        let steps_succeeded = failed_step.is_none();
+        
+        let confidence_breakdown = self.config.confidence_breakdown(&opportunity.opportunity_type, net_profit, variance);
         let result = MevSimulationResult {
             pre_execution_snapshot: pre_snapshot,
-            post_execution_snapshot: post_snapshot,
             net_profit,
             total_fees_paid: simulation_result.total_fees_paid,
             simulation_steps: simulation_result.simulation_steps,
-            is_profitable: net_profit > 0.01 && variance <= self.max_variance_threshold, // Require min profit and low variance
-            confidence_score: 0.0, // Will be calculated next
+            is_profitable: steps_succeeded && self.config.is_profitable(&opportunity.opportunity_type, net_profit, variance),
+            confidence_score: simulation_config::confidence_score(&confidence_breakdown),
+            confidence_breakdown,
             execution_variance: variance,
         };
         
-        // Calculate confidence score based on various factors
-        let confidence_score = self.calculate_confidence_score(&result, variance).await?;
-        
-        // Recreate the result with the correct confidence score
-        let result = MevSimulationResult {
-            pre_execution_snapshot: result.pre_execution_snapshot,
-            post_execution_snapshot: result.post_execution_snapshot,
-            net_profit: result.net_profit,
-            total_fees_paid: result.total_fees_paid,
-            simulation_steps: result.simulation_steps,
-            is_profitable: result.is_profitable,
-            confidence_score,
-            execution_variance: result.execution_variance,
-        };
-        
         Logger::status_update(&format!(
             "Bundle simulation completed - net profit: {:.6} SOL, confidence: {:.2}%, variance: {:.2}%", 
             result.net_profit, 
@@ -129,93 +376,74 @@ impl MevSimulationPipeline {
             result.execution_variance * 100.0
         ));
         
+        if let (Some(cache), Some(fingerprint)) = (&self.cache, fingerprint) {
+            cache.insert(fingerprint, result.clone()).await;
+        }
         Ok(result)
     }
     
+    // The cache key under the current blockhash; None when the blockhash can't be read
+    async fn fingerprint(&self, opportunity: &OpportunityDetails, target: Option<&TargetTransaction>) -> Option<String> {
+        opportunity.pool_address.as_ref()?;
+        let blockhash = self.rpc_manager.get_recent_blockhash().await.ok()?.blockhash;
+        SimulationCache::fingerprint(opportunity, &blockhash, target.map(|target| target.signature.as_str()))
+    }
+    
+    pub async fn cache_stats(&self) -> Option<CacheStats> {
+        match &self.cache {
+            Some(cache) => Some(cache.stats().await),
+            None => None,
+        }
+    }
+    
     async fn take_balance_snapshot(&self) -> Result<BalanceSnapshot, Box<dyn std::error::Error + Send + Sync>> {
-        // Get the bot's wallet address
-        let wallet_address = std::env::var("WALLET_ADDRESS")
-            .map_err(|_| "WALLET_ADDRESS environment variable not set")?;
+        let wallet = self.wallet()?;
+        let sol_balance = self.get_sol_balance(&wallet).await?;
         
-        // Get SOL balance
-        let sol_balance = self.get_sol_balance(&wallet_address).await?;
-        
-        // For now, we'll create a basic snapshot
-        // In a full implementation, we would get all token balances too
-        let mut token_balances = HashMap::new();
+        // Every token account the wallet holds, under both token programs
+        let mut token_accounts = Vec::new();
+        for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+            token_accounts.extend(self.rpc_manager.get_token_accounts_by_owner(&wallet, program_id).await?);
+        }
         
-        // This would be expanded to get all token account balances
-        let snapshot = BalanceSnapshot {
-            token_balances,
+        Ok(BalanceSnapshot {
+            token_balances: simulation_effects::token_balances(&token_accounts),
             sol_balance,
             timestamp: std::time::SystemTime::now(),
-        };
-        
-        Ok(snapshot)
+        })
     }
     
     async fn get_sol_balance(&self, wallet_address: &str) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        let request_body = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getBalance",
-            "params": [wallet_address]
-        });
-        
-        let response = self.rpc_manager.make_request(
-            crate::rpc::rpc_manager::RpcEndpointType::Helius,
-            request_body
-        ).await?;
-        
-        if let Some(value) = response["result"]["value"].as_f64() {
-            Ok(value / 1_000_000_000.0) // Convert lamports to SOL
-        } else {
-            Err("Failed to parse balance result".into())
-        }
+        let lamports = self.rpc_manager.get_balance(wallet_address).await?;
+        Ok(lamports as f64 / 1_000_000_000.0) // Convert lamports to SOL
     }
     
     async fn simulate_sandwich_bundle(
         &self,
-        opportunity: &OpportunityDetails
+        opportunity: &OpportunityDetails,
+        target: Option<&TargetTransaction>
     ) -> Result<SimulationBundleResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Simulating sandwich bundle: frontrun + target + backrun");
         
-        // Create simulated transactions for the sandwich
-        let frontrun_tx = self.create_frontrun_transaction(opportunity).await?;
-        let backrun_tx = self.create_backrun_transaction(opportunity).await?;
-        
-        // For the target, we'll use the actual target transaction (passed in)
-        // In this simulation, we'll assume it exists
-        
-        let mut simulation_steps = Vec::new();
-        
-        // Simulate frontrun transaction
-        let frontrun_effects = self.simulate_transaction_effects(&frontrun_tx, &SimulationStepType::Frontrun).await?;
-        simulation_steps.push(SimulationStep {
-            step_type: SimulationStepType::Frontrun,
-            transaction_data: frontrun_tx.clone(),
-            expected_effects: frontrun_effects,
-            actual_effects: None,
-        });
-        
-        // Simulate backrun transaction
-        let backrun_effects = self.simulate_transaction_effects(&backrun_tx, &SimulationStepType::Backrun).await?;
-        simulation_steps.push(SimulationStep {
-            step_type: SimulationStepType::Backrun,
-            transaction_data: backrun_tx,
-            expected_effects: backrun_effects,
-            actual_effects: None,
-        });
-        
-        // Calculate total fees
-        let total_fees: f64 = simulation_steps.iter()
-            .map(|step| step.expected_effects.fees_paid)
-            .sum();
+        // Without the victim there's no price move to capture
+        let Some(target) = target else {
+            return Ok(SimulationBundleResult::unbuilt(
+                SimulationStepType::Target { signature: None },
+                "No signed target transaction to sandwich".to_string(),
+            ));
+        };
         
-        Ok(SimulationBundleResult {
-            simulation_steps,
-            total_fees_paid: total_fees,
-        })
+        let frontrun_tx = match self.create_frontrun_transaction(opportunity).await {
+            Ok(tx) => tx,
+            Err(e) => return Ok(SimulationBundleResult::unbuilt(SimulationStepType::Frontrun, format!("Could not build the frontrun: {}", e))),
+        };
+        let backrun_tx = match self.create_backrun_transaction(opportunity).await {
+            Ok(tx) => tx,
+            Err(e) => return Ok(SimulationBundleResult::unbuilt(SimulationStepType::Backrun, format!("Could not build the backrun: {}", e))),
+        };
+        let transactions = [frontrun_tx, target.to_base58(), backrun_tx];
+        let effects = self.simulate_sequence(&transactions, opportunity).await?;
+        Ok(SimulationBundleResult::new(sandwich_steps(transactions, &target.signature, effects)))
     }
     
     async fn simulate_arbitrage_bundle(
@@ -224,29 +452,11 @@ impl MevSimulationPipeline {
     ) -> Result<SimulationBundleResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Simulating arbitrage bundle");
         
-        // Create simulated arbitrage transaction
-        let arbitrage_tx = self.create_arbitrage_transaction(opportunity).await?;
-        
-        let mut simulation_steps = Vec::new();
-        
-        // Simulate the arbitrage transaction
-        let arbitrage_effects = self.simulate_transaction_effects(&arbitrage_tx, &SimulationStepType::Target).await?;
-        simulation_steps.push(SimulationStep {
-            step_type: SimulationStepType::Target,
-            transaction_data: arbitrage_tx,
-            expected_effects: arbitrage_effects,
-            actual_effects: None,
-        });
-        
-        // Calculate total fees
-        let total_fees: f64 = simulation_steps.iter()
-            .map(|step| step.expected_effects.fees_paid)
-            .sum();
-        
-        Ok(SimulationBundleResult {
-            simulation_steps,
-            total_fees_paid: total_fees,
-        })
+        let step_type = SimulationStepType::Target { signature: None };
+        match self.create_arbitrage_transaction(opportunity).await {
+            Ok(arbitrage_tx) => self.simulate_single(step_type, arbitrage_tx, opportunity).await,
+            Err(e) => Ok(SimulationBundleResult::unbuilt(step_type, format!("Could not build the arbitrage: {}", e))),
+        }
     }
     
     async fn simulate_frontrun_bundle(
@@ -255,29 +465,10 @@ impl MevSimulationPipeline {
     ) -> Result<SimulationBundleResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Simulating frontrun bundle");
         
-        // Create simulated frontrun transaction
-        let frontrun_tx = self.create_frontrun_transaction(opportunity).await?;
-        
-        let mut simulation_steps = Vec::new();
-        
-        // Simulate the frontrun transaction
-        let frontrun_effects = self.simulate_transaction_effects(&frontrun_tx, &SimulationStepType::Frontrun).await?;
-        simulation_steps.push(SimulationStep {
-            step_type: SimulationStepType::Frontrun,
-            transaction_data: frontrun_tx,
-            expected_effects: frontrun_effects,
-            actual_effects: None,
-        });
-        
-        // Calculate total fees
-        let total_fees: f64 = simulation_steps.iter()
-            .map(|step| step.expected_effects.fees_paid)
-            .sum();
-        
-        Ok(SimulationBundleResult {
-            simulation_steps,
-            total_fees_paid: total_fees,
-        })
+        match self.create_frontrun_transaction(opportunity).await {
+            Ok(frontrun_tx) => self.simulate_single(SimulationStepType::Frontrun, frontrun_tx, opportunity).await,
+            Err(e) => Ok(SimulationBundleResult::unbuilt(SimulationStepType::Frontrun, format!("Could not build the frontrun: {}", e))),
+        }
     }
     
     async fn simulate_generic_bundle(
@@ -286,201 +477,328 @@ impl MevSimulationPipeline {
     ) -> Result<SimulationBundleResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Simulating generic bundle");
         
-        // Default simulation for other opportunity types
-        let tx = self.create_generic_transaction(opportunity).await?;
-        
-        let mut simulation_steps = Vec::new();
-        
-        let effects = self.simulate_transaction_effects(&tx, &SimulationStepType::Target).await?;
-        simulation_steps.push(SimulationStep {
-            step_type: SimulationStepType::Target,
-            transaction_data: tx,
-            expected_effects: effects,
-            actual_effects: None,
-        });
-        
-        let total_fees: f64 = simulation_steps.iter()
-            .map(|step| step.expected_effects.fees_paid)
-            .sum();
-        
-        Ok(SimulationBundleResult {
-            simulation_steps,
-            total_fees_paid: total_fees,
-        })
+        let step_type = SimulationStepType::Target { signature: None };
+        match self.create_generic_transaction(opportunity).await {
+            Ok(tx) => self.simulate_single(step_type, tx, opportunity).await,
+            Err(e) => Ok(SimulationBundleResult::unbuilt(step_type, format!("Could not build the transaction: {}", e))),
+        }
     }
     
+    async fn simulate_single(
+        &self,
+        step_type: SimulationStepType,
+        transaction_data: String,
+        opportunity: &OpportunityDetails
+    ) -> Result<SimulationBundleResult, Box<dyn std::error::Error + Send + Sync>> {
+        let expected_effects = self.simulate_transaction_effects(&transaction_data, opportunity).await?;
+        Ok(SimulationBundleResult::new(vec![SimulationStep { step_type, transaction_data, expected_effects, actual_effects: None }]))
+    }
+    
+    // Buys token_b with the opportunity's size of token_a on its pool, ahead of the victim
     async fn create_frontrun_transaction(&self, opportunity: &OpportunityDetails) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a simulated frontrun transaction that mimics the target transaction's behavior
-        // but executes first to capture the MEV opportunity
-        
-        // In a real implementation, this would create actual swap instructions
-        // For now, we'll create a placeholder transaction
-        
-        // This would be created using Solana SDK with actual swap instructions
-        Ok("simulated_frontrun_transaction_data".to_string())
+        let (frontrun, _) = self.sandwich_legs(opportunity).await?;
+        self.create_leg_transaction(&[frontrun]).await
     }
     
+    // Sells what the frontrun is quoted to buy, after the victim has moved the price
     async fn create_backrun_transaction(&self, opportunity: &OpportunityDetails) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a simulated backrun transaction that reverses the position taken in the frontrun
-        
-        // In a real implementation, this would create actual swap instructions
-        // For now, we'll create a placeholder transaction
-        
-        Ok("simulated_backrun_transaction_data".to_string())
+        let (_, backrun) = self.sandwich_legs(opportunity).await?;
+        self.create_leg_transaction(&[backrun]).await
     }
     
+    // Every leg of the route in one transaction, each spending the previous leg's quoted output
     async fn create_arbitrage_transaction(&self, opportunity: &OpportunityDetails) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a simulated arbitrage transaction that exploits price differences
-        
-        // In a real implementation, this would create actual swap instructions across DEXs
-        // For now, we'll create a placeholder transaction
-        
-        Ok("simulated_arbitrage_transaction_data".to_string())
+        if opportunity.route.is_empty() {
+            return Err("Arbitrage opportunity has no route to build".into());
+        }
+        self.create_leg_transaction(&route_legs(opportunity)).await
     }
     
+    // The route when there is one (a backrun's sized swap), otherwise one swap on the pool
     async fn create_generic_transaction(&self, opportunity: &OpportunityDetails) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a generic transaction based on the opportunity type
-        Ok("simulated_generic_transaction_data".to_string())
+        let legs = match opportunity.route.is_empty() {
+            true => vec![self.pool_leg(opportunity)?],
+            false => route_legs(opportunity),
+        };
+        self.create_leg_transaction(&legs).await
+    }
+    
+    fn pool_leg(&self, opportunity: &OpportunityDetails) -> Result<RouteLeg, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_address = opportunity.pool_address.clone().ok_or("Opportunity has no pool to trade on")?;
+        Ok(RouteLeg {
+            dex: opportunity.dex.clone(),
+            pool_address,
+            input_token: opportunity.token_a.clone(),
+            output_token: opportunity.token_b.clone(),
+            amount_in: opportunity.trade_size,
+            expected_out: 0,
+            min_out: 0,
+        })
+    }
+    
+    // Frontrun and backrun on the opportunity's pool. The backrun sells the frontrun's quoted
+    // output less a margin, so a slightly worse fill doesn't leave it short of tokens; neither
+    // sets a minimum, since the simulation is there to find out what they return
+    async fn sandwich_legs(&self, opportunity: &OpportunityDetails) -> Result<(RouteLeg, RouteLeg), Box<dyn std::error::Error + Send + Sync>> {
+        let frontrun = self.pool_leg(opportunity)?;
+        let pool = self.opportunity_evaluator.get_pool_state(&frontrun.pool_address, &frontrun.dex).await?
+            .ok_or_else(|| format!("No state for pool {}", frontrun.pool_address))?;
+        let quote = pool.quote(&frontrun.input_token, frontrun.amount_in)
+            .ok_or_else(|| format!("Pool {} doesn't trade {}", frontrun.pool_address, frontrun.input_token))?;
+        let frontrun = RouteLeg { expected_out: quote.output_amount, ..frontrun };
+        let backrun = RouteLeg {
+            input_token: frontrun.output_token.clone(),
+            output_token: frontrun.input_token.clone(),
+            amount_in: (quote.output_amount as f64 * (1.0 - BACKRUN_INVENTORY_MARGIN)) as u64,
+            expected_out: 0,
+            ..frontrun.clone()
+        };
+        if backrun.amount_in == 0 {
+            return Err(format!("Frontrun of {} buys nothing on pool {}", frontrun.amount_in, frontrun.pool_address).into());
+        }
+        Ok((frontrun, backrun))
     }
     
+    // The legs' swaps in one transaction signed by the executor's keypair under the latest blockhash
+    pub async fn create_leg_transaction(&self, legs: &[RouteLeg]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let signer = self.signer()?;
+        let mut instructions = Vec::new();
+        for leg in legs {
+            instructions.extend(self.swaps.swap_instructions(leg, &signer.pubkey()).await?);
+        }
+        let blockhash = self.rpc_manager.get_recent_blockhash().await?.blockhash;
+        dex_swap_instructions::signed_transaction(&instructions, &signer, &blockhash)
+    }
+    
+    // Runs the signed transaction (base58) through simulateTransaction and reads its effects off
+    // the wallet and its token accounts for the opportunity's mints. A transaction that can't be
+    // decoded or fails to simulate marks the step failed; only an unreachable RPC is an error
     async fn simulate_transaction_effects(
         &self,
         tx_data: &str,
-        step_type: &SimulationStepType
+        opportunity: &OpportunityDetails
     ) -> Result<TransactionEffects, Box<dyn std::error::Error + Send + Sync>> {
-        // Simulate the effects of a transaction on account balances
-        // This would normally involve calling simulateTransaction RPC
-        
-        // For different step types, estimate different effects
-        let (token_balance_change, sol_balance_change) = match step_type {
-            SimulationStepType::Frontrun => {
-                // Frontrun typically has negative SOL impact (cost) but potential positive later
-                (HashMap::new(), -0.001) // Small cost for the transaction
-            },
-            SimulationStepType::Backrun => {
-                // Backrun should have positive SOL impact if the strategy worked
-                (HashMap::new(), 0.01) // Profit from the strategy
-            },
-            SimulationStepType::Target => {
-                // Target transaction effects depend on the specific opportunity
-                (HashMap::new(), -0.001) // Cost of transaction
-            },
+        let Ok(bytes) = bs58::decode(tx_data).into_vec() else {
+            return Ok(TransactionEffects::failed("Transaction isn't base58-encoded".to_string()));
         };
-        
-        // Estimate fees based on transaction complexity
-        let fees_paid = match step_type {
-            SimulationStepType::Frontrun | SimulationStepType::Backrun => 0.0015, // Higher for complex swaps
-            SimulationStepType::Target => 0.001, // Standard transaction fee
+        let Some(fee_parameters) = simulation_effects::fee_parameters(&bytes) else {
+            return Ok(TransactionEffects::failed("Transaction doesn't deserialize".to_string()));
         };
         
-        Ok(TransactionEffects {
-            token_balance_changes: token_balance_change,
-            sol_balance_change,
-            fees_paid,
-            success: true, // Assume success in simulation
-        })
+        let accounts = simulation_effects::tracked_accounts(&self.wallet()?, &opportunity.mints());
+        let pre_accounts = self.rpc_manager.get_multiple_accounts_parsed(&accounts).await?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        let response = self.rpc_manager.simulate_transaction_with_accounts(&encoded, &accounts).await?;
+        Ok(simulation_effects::transaction_effects(&response, &pre_accounts, fee_parameters))
     }
     
-    fn calculate_net_profit(
+    // Runs the transactions (base58) in order against one evolving state through simulateBundle,
+    // so a leg that only fails after the previous ones have run shows up as failed. Without
+    // simulateBundle each leg is simulated on its own against current state, which can't show that
+    async fn simulate_sequence(
         &self,
-        pre_snapshot: &BalanceSnapshot,
-        post_snapshot: &BalanceSnapshot
-    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Calculate the net profit by comparing pre and post execution balances
-        
-        // For now, we just compare SOL balances
-        let sol_profit = post_snapshot.sol_balance - pre_snapshot.sol_balance;
-        
-        // In a full implementation, we would also account for token balance changes
-        // by converting them to SOL equivalent at current prices
-        
-        Ok(sol_profit)
-    }
-    
-    async fn assess_simulation_variance(&self, opportunity: &OpportunityDetails) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Run multiple simulation scenarios with different parameters to assess variance
-        
-        let mut results = Vec::new();
-        
-        // Run simulation with different market conditions
-        for i in 0..5 { // Run 5 different scenarios
-            let scenario_result = self.run_single_variance_scenario(opportunity, i).await?;
-            results.push(scenario_result);
+        transactions: &[String],
+        opportunity: &OpportunityDetails
+    ) -> Result<Vec<TransactionEffects>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut encoded = Vec::with_capacity(transactions.len());
+        let mut fee_parameters = Vec::with_capacity(transactions.len());
+        for (index, tx) in transactions.iter().enumerate() {
+            let decoded = bs58::decode(tx).into_vec().ok()
+                .and_then(|bytes| Some((simulation_effects::fee_parameters(&bytes)?, bytes)));
+            let Some((parameters, bytes)) = decoded else {
+                // Nothing is sent when any leg is unusable
+                return Ok((0..transactions.len())
+                    .map(|leg| TransactionEffects::failed(if leg == index {
+                        "Transaction isn't a base58-encoded signed transaction".to_string()
+                    } else {
+                        format!("Not simulated: transaction {} of the sequence is invalid", index)
+                    }))
+                    .collect());
+            };
+            encoded.push(base64::engine::general_purpose::STANDARD.encode(&bytes));
+            fee_parameters.push(parameters);
         }
         
-        if results.is_empty() {
-            return Ok(0.0);
+        if let Some(ref jito_client) = self.jito_client {
+            let accounts = simulation_effects::tracked_accounts(&self.wallet()?, &opportunity.mints());
+            match jito_client.simulate_bundle_with_accounts(&encoded, &accounts).await {
+                Ok(response) => return Ok(simulation_effects::bundle_effects(&response, &fee_parameters)),
+                Err(e) if bundle_simulation::is_method_unsupported(&e.to_string()) => {}
+                Err(e) => return Err(e),
+            }
         }
         
-        // Calculate variance from the results
-        let avg_net_profit: f64 = results.iter().sum::<f64>() / results.len() as f64;
-        let variance = results.iter().map(|x| (x - avg_net_profit).powi(2)).sum::<f64>() / results.len() as f64;
-        
-        Ok(variance.sqrt()) // Return standard deviation as the variance measure
+        Logger::status_update("simulateBundle not available; simulating each leg against current state, so reverts caused by earlier legs go unseen");
+        let mut effects = Vec::with_capacity(transactions.len());
+        for tx in transactions {
+            effects.push(self.simulate_transaction_effects(tx, opportunity).await?);
+        }
+        Ok(effects)
     }
     
-    async fn run_single_variance_scenario(
-        &self,
-        opportunity: &OpportunityDetails,
-        scenario_id: usize
-    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Run a single simulation scenario with modified parameters
-        // This simulates different market conditions
-        
-        // Apply different slippage, fees, and market conditions based on scenario_id
-        match scenario_id {
-            0 => Ok(opportunity.estimated_profit * 0.95), // 5% worse than expected
-            1 => Ok(opportunity.estimated_profit * 1.05), // 5% better than expected
-            2 => Ok(opportunity.estimated_profit * 0.9),  // 10% worse
-            3 => Ok(opportunity.estimated_profit * 1.1),  // 10% better
-            4 => Ok(opportunity.estimated_profit * 0.8),  // 20% worse
-            _ => Ok(opportunity.estimated_profit),
+    // SOL our simulated steps gained, with their token changes valued at current prices
+    async fn steps_net_profit(&self, steps: &[SimulationStep]) -> f64 {
+        let mints: HashSet<String> = steps.iter()
+            .filter(|step| step.is_own())
+            .flat_map(|step| step.expected_effects.token_balance_changes.keys().cloned())
+            .collect();
+        let prices_in_sol = self.prices_in_sol(mints.into_iter()).await;
+        steps_net_profit(steps, &prices_in_sol)
+    }
+    
+    // SOL per token of each mint; mints that can't be priced are left out, and with them their
+    // balance changes
+    async fn prices_in_sol(&self, mints: impl Iterator<Item = String>) -> HashMap<String, f64> {
+        let mut prices_in_sol = HashMap::new();
+        for mint in mints {
+            if mint == WSOL_MINT {
+                prices_in_sol.insert(mint, 1.0);
+                continue;
+            }
+            match self.opportunity_evaluator.get_token_price(&mint).await {
+                Ok(price) if price.price_in_sol > 0.0 => {
+                    prices_in_sol.insert(mint, price.price_in_sol);
+                }
+                Ok(_) => Logger::status_update(&format!("No SOL price for {}; its balance change is left out of net profit", mint)),
+                Err(e) => Logger::status_update(&format!("Could not price {}: {}; its balance change is left out of net profit", mint, e)),
+            }
         }
+        prices_in_sol
     }
     
-    async fn calculate_confidence_score(
-        &self,
-        result: &MevSimulationResult,
-        variance: f64
-    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Calculate confidence score based on multiple factors:
-        // 1. Net profit (higher profit = higher confidence)
-        let profit_factor = if result.net_profit > 0.05 { 0.4 } else if result.net_profit > 0.01 { 0.2 } else { 0.0 };
-        
-        // 2. Low variance (lower variance = higher confidence)
-        let variance_factor = if variance < 0.01 { 0.3 } else if variance < 0.05 { 0.1 } else { 0.0 };
-        
-        // 3. Positive net profit (binary factor)
-        let profitability_factor = if result.net_profit > 0.0 { 0.3 } else { 0.0 };
+    // Spread of net profit when the inputs it depends on move: the pool's price, the victim's
+    // size and the priority fee at recent percentiles, as a coefficient of variation
+    async fn assess_simulation_variance(&self, opportunity: &OpportunityDetails) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_state = async {
+            let pool_address = opportunity.pool_address.as_deref()?;
+            let pool = self.opportunity_evaluator.get_pool_state(pool_address, &opportunity.dex).await.ok()??;
+            let (reserve_in, reserve_out) = pool.reserves_for_input(&opportunity.token_a)?;
+            Some(PoolInputs { reserve_in, reserve_out, fee_rate: pool.fee_rate })
+        };
+        let (pool, fees, unit_value) = tokio::join!(
+            pool_state,
+            self.rpc_manager.get_recent_prioritization_fees(),
+            self.opportunity_evaluator.value_in_sol(&opportunity.token_a, 1_000_000_000),
+        );
+        let fee_percentiles = fees.ok()
+            .and_then(|fees| network_congestion::fee_percentiles(&fees))
+            .map_or_else(Vec::new, |(p50, p90)| vec![p50, p90]);
+        // Without a price for the input token the pool can't be valued, so only the fee varies
+        let (pool, sol_per_unit) = match unit_value {
+            Ok(value) if value > 0.0 => (pool, value / 1_000_000_000.0),
+            _ => (None, 0.0),
+        };
         
-        let confidence = profit_factor + variance_factor + profitability_factor;
+        let config = self.config.variance;
+        let scenarios = simulation_variance::scenarios(&config, &opportunity.opportunity_type, &fee_percentiles);
+        let profits = simulation_variance::run_scenarios(&scenarios, config.time_budget, |scenario| {
+            simulation_variance::scenario_profit(
+                &opportunity.opportunity_type,
+                opportunity.estimated_profit,
+                opportunity.trade_size,
+                pool.as_ref(),
+                sol_per_unit,
+                config.compute_units,
+                scenario,
+            )
+        }).await;
+        if profits.len() < scenarios.len() {
+            Logger::status_update(&format!(
+                "Variance assessment ran {} of {} scenarios within {}ms",
+                profits.len(), scenarios.len(), config.time_budget.as_millis()
+            ));
+        }
         
-        Ok((confidence as f64).min(1.0))
+        Ok(simulation_variance::coefficient_of_variation(&profits))
     }
     
-    // Method to compare simulation results to actual execution outcomes
+    // SOL an execution's realized effects (see realized_effects) made the wallet, fees and tip
+    // included, with its token changes at current prices
+    pub async fn realized_net_profit(&self, actual_outcome: &TransactionEffects) -> f64 {
+        let prices_in_sol = self.prices_in_sol(actual_outcome.token_balance_changes.keys().cloned()).await;
+        realized_net_profit(actual_outcome, &prices_in_sol)
+    }
+    
+    // How well a simulation predicted what its execution realized (see realized_effects), from
+    // 0 to 1. Both sides are valued from their steps' effects at the same prices, so a price move
+    // since the simulation doesn't count against it
     pub async fn compare_simulation_to_actual(
         &self,
         simulation_result: &MevSimulationResult,
         actual_outcome: &TransactionEffects
     ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Compare the simulated net profit to the actual net profit
-        // Return accuracy score (0.0 to 1.0)
+        let simulated_mints = simulation_result.simulation_steps.iter()
+            .filter(|step| step.is_own())
+            .flat_map(|step| step.expected_effects.token_balance_changes.keys());
+        let mints: HashSet<String> = simulated_mints.chain(actual_outcome.token_balance_changes.keys()).cloned().collect();
+        let prices_in_sol = self.prices_in_sol(mints.into_iter()).await;
         
-        let actual_net_profit = actual_outcome.sol_balance_change;
-        let simulated_net_profit = simulation_result.net_profit;
-        
-        // Calculate accuracy as the ratio of actual to simulated (clipped to [0,1])
-        let accuracy = if simulated_net_profit != 0.0 {
-            (actual_net_profit / simulated_net_profit).abs().min(1.0)
-        } else if actual_net_profit == 0.0 {
-            1.0 // Both are zero, perfect match
-        } else {
-            0.0 // Simulated zero but actual non-zero, poor match
-        };
-        
-        Ok(accuracy)
+        Ok(realized_accuracy(&simulation_result.simulation_steps, actual_outcome, &prices_in_sol))
+    }
+}
+
+// Below this many SOL a profit counts as zero
+const ACCURACY_EPSILON_SOL: f64 = 1e-9;
+
+// Accuracy of a simulated net profit against the realized one: the smaller over the larger, so
+// missing by half either way scores 0.5. A profit that turned into a loss, or the other way round,
+// scores 0 however close the two are; both zero is a perfect match
+pub fn simulation_accuracy(simulated: f64, actual: f64) -> f64 {
+    if !simulated.is_finite() || !actual.is_finite() {
+        return 0.0;
     }
+    let sign = |profit: f64| if profit.abs() < ACCURACY_EPSILON_SOL { 0 } else if profit > 0.0 { 1 } else { -1 };
+    match (sign(simulated), sign(actual)) {
+        (0, 0) => 1.0,
+        (simulated_sign, actual_sign) if simulated_sign != actual_sign => 0.0,
+        _ => simulated.abs().min(actual.abs()) / simulated.abs().max(actual.abs()),
+    }
+}
+
+// simulation_accuracy of the simulated steps' net profit against the realized effects, both at
+// `prices_in_sol`
+pub fn realized_accuracy(steps: &[SimulationStep], actual_outcome: &TransactionEffects, prices_in_sol: &HashMap<String, f64>) -> f64 {
+    simulation_accuracy(steps_net_profit(steps, prices_in_sol), realized_net_profit(actual_outcome, prices_in_sol))
+}
+
+fn realized_net_profit(actual_outcome: &TransactionEffects, prices_in_sol: &HashMap<String, f64>) -> f64 {
+    let inventory: f64 = actual_outcome.token_balance_changes.iter()
+        .filter_map(|(mint, change)| prices_in_sol.get(mint).map(|price| change * price))
+        .sum();
+    actual_outcome.sol_balance_change + inventory
+}
+
+// Share of the frontrun's quoted output the backrun leaves unsold
+const BACKRUN_INVENTORY_MARGIN: f64 = 0.005;
+
+// What our own steps did to the wallet: lamports (fees included) plus token changes at
+// `prices_in_sol`. The victim's step is left out, and so are mints without a price
+pub fn steps_net_profit(steps: &[SimulationStep], prices_in_sol: &HashMap<String, f64>) -> f64 {
+    steps.iter()
+        .filter(|step| step.is_own())
+        .map(|step| {
+            let effects = &step.expected_effects;
+            let inventory: f64 = effects.token_balance_changes.iter()
+                .filter_map(|(mint, change)| prices_in_sol.get(mint).map(|price| change * price))
+                .sum();
+            effects.sol_balance_change + inventory
+        })
+        .sum()
+}
+
+// The opportunity's route as legs to build, each spending the previous one's quoted output
+fn route_legs(opportunity: &OpportunityDetails) -> Vec<RouteLeg> {
+    opportunity.route.iter()
+        .map(|step| RouteLeg {
+            dex: step.dex.clone(),
+            pool_address: step.pool_address.clone(),
+            input_token: step.input_token.clone(),
+            output_token: step.output_token.clone(),
+            amount_in: step.amount_in,
+            expected_out: step.expected_out,
+            min_out: 0,
+        })
+        .collect()
 }
 
 struct SimulationBundleResult {
@@ -488,6 +806,37 @@ struct SimulationBundleResult {
     total_fees_paid: f64,
 }
 
+impl SimulationBundleResult {
+    fn new(simulation_steps: Vec<SimulationStep>) -> Self {
+        let total_fees_paid = simulation_steps.iter().map(|step| step.expected_effects.fees_paid).sum();
+        Self { simulation_steps, total_fees_paid }
+    }
+    
+    // A step that couldn't be built fails the bundle before anything is simulated
+    fn unbuilt(step_type: SimulationStepType, error: String) -> Self {
+        Self::new(vec![SimulationStep::unbuilt(step_type, error)])
+    }
+}
+
+// [frontrun, target, backrun] with their simulated effects; the victim pays its own fee
+fn sandwich_steps(transactions: [String; 3], target_signature: &str, effects: Vec<TransactionEffects>) -> Vec<SimulationStep> {
+    let step_types = [
+        SimulationStepType::Frontrun,
+        SimulationStepType::Target { signature: Some(target_signature.to_string()) },
+        SimulationStepType::Backrun,
+    ];
+    step_types.into_iter()
+        .zip(transactions)
+        .zip(effects)
+        .map(|((step_type, transaction_data), mut expected_effects)| {
+            if matches!(step_type, SimulationStepType::Target { .. }) {
+                expected_effects.fees_paid = 0.0;
+            }
+            SimulationStep { step_type, transaction_data, expected_effects, actual_effects: None }
+        })
+        .collect()
+}
+
 // New module to handle complex MEV operations
 pub mod mev_operations {
     use super::*;
@@ -506,6 +855,8 @@ pub mod mev_operations {
         pub input_token: String,
         pub output_token: String,
         pub pool_address: String,
+        pub amount_in: u64,
+        pub expected_out: u64, // Quoted output, the next leg's input
     }
     
     pub struct MevOperationBuilder {
@@ -551,4 +902,368 @@ pub mod mev_operations {
             Ok(format!("backrun_swap_{}_to_{}", input_token, output_token))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_sdk::system_instruction;
+    use solana_sdk::transaction::Transaction;
+    use solana_sdk::pubkey::Pubkey;
+    use crate::rpc::rpc_manager::{RpcEndpoint, RpcEndpointType};
+    use crate::utils::mock_http::{self, MockResponse};
+    use crate::utils::orca_whirlpool;
+    use crate::utils::pool_creation::USDC_MINT;
+    use crate::utils::simulation_effects::FeeParameters;
+
+    fn snapshot(sol_balance: f64, tokens: &[(&str, f64, u8)]) -> BalanceSnapshot {
+        BalanceSnapshot {
+            token_balances: tokens.iter()
+                .map(|(mint, amount, decimals)| (mint.to_string(), TokenBalance { amount: *amount, decimals: *decimals }))
+                .collect(),
+            sol_balance,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_net_change_values_inventory_moves() {
+        // Spent 1.5 SOL on 300 USDC and sold the whole BONK bag; a new, unpriced mint came in
+        let before = snapshot(10.0, &[("USDC", 200.0, 6), ("BONK", 1_000_000.0, 5)]);
+        let after = snapshot(8.5, &[("USDC", 500.0, 6), ("NEW", 42.0, 9)]);
+
+        let changes = after.token_changes_since(&before);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes["USDC"], 300.0);
+        assert_eq!(changes["BONK"], -1_000_000.0);
+        assert_eq!(changes["NEW"], 42.0);
+        assert!(after.token_changes_since(&after).is_empty());
+
+        let prices: HashMap<String, f64> = [("USDC".to_string(), 0.005), ("BONK".to_string(), 0.0000001)].into_iter().collect();
+        // -1.5 SOL + 300 * 0.005 - 1_000_000 * 0.0000001; NEW has no price
+        assert!((after.net_change_in_sol(&before, &prices) - (-0.1)).abs() < 1e-9);
+        assert!((after.net_change_in_sol(&before, &HashMap::new()) - (-1.5)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_net_profit_sums_our_steps_only() {
+        let effects = |sol: f64, tokens: &[(&str, f64)]| TransactionEffects {
+            token_balance_changes: tokens.iter().map(|(mint, change)| (mint.to_string(), *change)).collect(),
+            sol_balance_change: sol,
+            fees_paid: 0.000005,
+            units_consumed: 60_000,
+            success: true,
+            error: None,
+        };
+        let step = |step_type: SimulationStepType, expected_effects: TransactionEffects| SimulationStep {
+            step_type, transaction_data: String::new(), expected_effects, actual_effects: None,
+        };
+        // Frontrun wraps and spends 1 SOL on 150 USDC, the victim buys, the backrun sells 149.25
+        // USDC for 1.02 SOL and keeps 0.75 USDC; the victim's own moves don't count
+        let steps = vec![
+            step(SimulationStepType::Frontrun, effects(-1.002044, &[(WSOL_MINT, 0.0), ("USDC", 150.0)])),
+            step(SimulationStepType::Target { signature: Some("victim".to_string()) }, effects(-5.0, &[("USDC", 700.0)])),
+            step(SimulationStepType::Backrun, effects(-0.000005, &[(WSOL_MINT, 1.02), ("USDC", -149.25)])),
+        ];
+        let prices: HashMap<String, f64> = [(WSOL_MINT.to_string(), 1.0), ("USDC".to_string(), 0.0066)].into_iter().collect();
+        // -1.002044 - 0.000005 + 1.02 + 0.75 * 0.0066
+        assert!((steps_net_profit(&steps, &prices) - 0.022901).abs() < 1e-9);
+        // An unpriced mint counts as nothing
+        assert!((steps_net_profit(&steps, &HashMap::new()) - (-1.002049)).abs() < 1e-9);
+
+        // Realized exactly as simulated: a perfect match whatever USDC is worth by then
+        let realized = effects(-1.002049, &[(WSOL_MINT, 1.02), ("USDC", 0.75)]);
+        let repriced: HashMap<String, f64> = [(WSOL_MINT.to_string(), 1.0), ("USDC".to_string(), 0.0132)].into_iter().collect();
+        assert!((realized_accuracy(&steps, &realized, &prices) - 1.0).abs() < 1e-9);
+        assert!((realized_accuracy(&steps, &realized, &repriced) - 1.0).abs() < 1e-9);
+    }
+
+    fn signed_transfer() -> Transaction {
+        let payer = Keypair::new();
+        let instruction = system_instruction::transfer(&payer.pubkey(), &Keypair::new().pubkey(), 1_000);
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer], solana_sdk::hash::Hash::default())
+    }
+
+    fn wallet(lamports: u64) -> Value {
+        json!({ "lamports": lamports, "owner": "11111111111111111111111111111111", "data": ["", "base64"] })
+    }
+
+    fn usdc_account(amount: u64) -> Value {
+        json!({ "lamports": 2_039_280, "data": { "parsed": { "info": {
+            "mint": "EPjFWDDh7cF3ovXV1DEYsdqk6gXdVHDVGg3bQeXjDwLS",
+            "tokenAmount": { "amount": amount.to_string(), "decimals": 6 }
+        }}}})
+    }
+
+    #[test]
+    fn test_target_transaction_from_details() {
+        let transaction = signed_transfer();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&transaction).unwrap());
+        let target = TargetTransaction::from_details(&json!({ "transaction": [encoded, "base64"] })).unwrap();
+        assert_eq!(target.signature, transaction.signatures[0].to_string());
+        assert_eq!(TargetTransaction::from_bytes(bs58::decode(target.to_base58()).into_vec().unwrap()), Some(target));
+
+        // JSON-encoded details don't carry the signed bytes
+        assert_eq!(TargetTransaction::from_details(&json!({ "transaction": { "signatures": ["5xyz"] } })), None);
+        assert_eq!(TargetTransaction::from_bytes(vec![1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_victim_slippage_limit_fails_the_middle_step() {
+        // Our frontrun buys 300 USDC for 1.5 SOL; the victim's swap then gets less than its
+        // minimum out and reverts, so the bundle stops before our backrun
+        let response = json!({ "result": { "value": {
+            "summary": { "failed": { "error": { "TransactionFailure": [[], "custom program error: 0x1771"] }, "tx_signature": "victim" } },
+            "transactionResults": [
+                {
+                    "err": null, "logs": [], "unitsConsumed": 60_000,
+                    "preExecutionAccounts": [wallet(10_000_000_000), usdc_account(0)],
+                    "postExecutionAccounts": [wallet(8_499_995_000), usdc_account(300_000_000)]
+                },
+                {
+                    "err": { "InstructionError": [2, { "Custom": 6001 }] },
+                    "logs": [
+                        "Program log: Error: exceeds desired slippage limit",
+                        "Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 failed: custom program error: 0x1771"
+                    ],
+                    "unitsConsumed": 45_000,
+                    "preExecutionAccounts": [wallet(8_499_995_000), usdc_account(300_000_000)],
+                    "postExecutionAccounts": [wallet(8_499_995_000), usdc_account(300_000_000)]
+                }
+            ]
+        }}});
+        let fees = [FeeParameters { signatures: 1, compute_unit_price: 0 }; 3];
+        let effects = simulation_effects::bundle_effects(&response, &fees);
+        let transactions = ["frontrun".to_string(), "target".to_string(), "backrun".to_string()];
+        let steps = sandwich_steps(transactions, "victim", effects);
+
+        assert_eq!(steps.len(), 3);
+        assert!(steps[0].expected_effects.success);
+        assert!((steps[0].expected_effects.sol_balance_change - (-1.500005)).abs() < 1e-9);
+        assert_eq!(steps[0].expected_effects.token_balance_changes["EPjFWDDh7cF3ovXV1DEYsdqk6gXdVHDVGg3bQeXjDwLS"], 300.0);
+
+        let target = &steps[1];
+        assert_eq!(target.step_type, SimulationStepType::Target { signature: Some("victim".to_string()) });
+        assert!(!target.expected_effects.success);
+        assert!(target.expected_effects.error.as_deref().unwrap().contains("custom program error: 0x1771"));
+        assert_eq!(target.expected_effects.fees_paid, 0.0); // The victim's to pay
+
+        assert_eq!(steps[2].step_type, SimulationStepType::Backrun);
+        assert!(!steps[2].expected_effects.success);
+        assert_eq!(steps[2].expected_effects.error.as_deref(), Some("Not executed: transaction 1 of the bundle failed"));
+        assert_eq!(steps.iter().find(|step| !step.expected_effects.success).map(|step| &step.step_type), Some(&target.step_type));
+
+        // Only our frontrun's units count towards the bundle's compute budget
+        let result = MevSimulationResult { simulation_steps: steps, ..simulation_result(0.0) };
+        assert_eq!(result.compute_units(), Some(60_000));
+        assert_eq!(simulation_result(0.0).compute_units(), None);
+    }
+
+    // Whirlpool quoting 200 USDC per SOL, deep enough that 1.5 SOL barely moves it
+    fn whirlpool_account() -> &'static [u8] {
+        static ACCOUNT: OnceLock<Vec<u8>> = OnceLock::new();
+        ACCOUNT.get_or_init(|| {
+            let mut data = vec![0u8; 653];
+            data[..8].copy_from_slice(&[63, 149, 209, 12, 225, 128, 99, 9]);
+            data[41..43].copy_from_slice(&64u16.to_le_bytes());
+            data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+            data[49..65].copy_from_slice(&100_000_000_000_000u128.to_le_bytes());
+            data[65..81].copy_from_slice(&((0.2f64.sqrt() * 2f64.powi(64)) as u128).to_le_bytes());
+            data[81..85].copy_from_slice(&(-16_095i32).to_le_bytes());
+            data[101..133].copy_from_slice(&bs58::decode(WSOL_MINT).into_vec().unwrap());
+            data[133..165].copy_from_slice(&Pubkey::new_unique().to_bytes());
+            data[181..213].copy_from_slice(&bs58::decode(USDC_MINT).into_vec().unwrap());
+            data[213..245].copy_from_slice(&Pubkey::new_unique().to_bytes());
+            data
+        })
+    }
+
+    fn token_account(mint: &str, amount: u64, decimals: u8) -> Value {
+        json!({ "lamports": 2_039_280, "data": { "parsed": { "info": {
+            "mint": mint,
+            "tokenAmount": { "amount": amount.to_string(), "decimals": decimals }
+        }}}})
+    }
+
+    // RPC node and block engine in one: serves the pool account, a blockhash and an empty wallet
+    // of 10 SOL, and answers simulateBundle with the frontrun buying 300 USDC and the victim then
+    // reverting on its slippage limit. The simulateBundle request is kept for inspection
+    async fn mock_node(pool: &'static str, bundle: Arc<std::sync::Mutex<Option<Value>>>) -> String {
+        let (url, _) = mock_http::serve(move |request| {
+            let request = request.json();
+            let result = match request["method"].as_str() {
+                Some("getAccountInfo") if request["params"][0] == pool => json!({ "context": { "slot": 1 }, "value": {
+                    "lamports": 5_000_000, "owner": orca_whirlpool::WHIRLPOOL_PROGRAM_ID,
+                    "data": [base64::engine::general_purpose::STANDARD.encode(whirlpool_account()), "base64"]
+                }}),
+                Some("getAccountInfo") => json!({ "context": { "slot": 1 }, "value": null }),
+                Some("getLatestBlockhash") => json!({ "context": { "slot": 1 }, "value": {
+                    "blockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N", "lastValidBlockHeight": 100
+                }}),
+                Some("getBalance") => json!({ "context": { "slot": 1 }, "value": 10_000_000_000u64 }),
+                Some("getTokenAccountsByOwner") => json!({ "context": { "slot": 1 }, "value": [] }),
+                Some("getRecentPrioritizationFees") => json!([]),
+                Some("simulateBundle") => {
+                    *bundle.lock().unwrap() = Some(request.clone());
+                    let untouched = [wallet(10_000_000_000), token_account(WSOL_MINT, 0, 9), token_account(USDC_MINT, 0, 6)];
+                    let bought = [wallet(8_499_995_000), token_account(WSOL_MINT, 0, 9), token_account(USDC_MINT, 300_000_000, 6)];
+                    json!({ "context": { "slot": 1 }, "value": {
+                        "summary": { "failed": { "error": { "TransactionFailure": [[], "custom program error: 0x1794"] }, "tx_signature": null } },
+                        "transactionResults": [
+                            { "err": null, "logs": [], "unitsConsumed": 60_000,
+                              "preExecutionAccounts": untouched, "postExecutionAccounts": bought },
+                            { "err": { "InstructionError": [0, { "Custom": 6036 }] },
+                              "logs": ["Program log: AnchorError occurred. Error Code: AmountOutBelowMinimum"],
+                              "unitsConsumed": 40_000,
+                              "preExecutionAccounts": bought, "postExecutionAccounts": bought }
+                        ]
+                    }})
+                }
+                _ => Value::Null,
+            };
+            MockResponse::json(&json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+        }).await;
+        url
+    }
+
+    // Amount and direction of the whirlpool swap in a bundle transaction, after checking it's
+    // signed by `signer`
+    fn signed_whirlpool_swap(encoded: &Value, signer: &Pubkey) -> (u64, bool) {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.as_str().unwrap()).unwrap();
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert!(transaction.verify_with_results().iter().all(|verified| *verified));
+        let keys = transaction.message.static_account_keys();
+        assert_eq!(keys[0], *signer);
+        let swap = transaction.message.instructions().iter()
+            .find(|instruction| keys[instruction.program_id_index as usize].to_string() == orca_whirlpool::WHIRLPOOL_PROGRAM_ID)
+            .expect("a whirlpool swap");
+        (u64::from_le_bytes(swap.data[8..16].try_into().unwrap()), swap.data[41] == 1)
+    }
+
+    #[tokio::test]
+    async fn test_sandwich_simulates_signed_swaps_around_the_victim() {
+        let pool = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+        let bundle = Arc::new(std::sync::Mutex::new(None));
+        let url = mock_node(pool, Arc::clone(&bundle)).await;
+        let rpc_manager = Arc::new(RpcManager::with_endpoints(vec![RpcEndpoint::new(url.clone(), RpcEndpointType::Helius, 1.0)]));
+        let evaluator = Arc::new(OpportunityEvaluator::new(Arc::clone(&rpc_manager), &crate::config::Config::default()).await.unwrap());
+        let pipeline = MevSimulationPipeline::new(Arc::clone(&rpc_manager), evaluator, SimulationConfig::default()).await.unwrap()
+            .with_jito_client(Some(JitoClient::with_url(url)));
+        let signer = Arc::new(Keypair::new());
+        pipeline.set_signer(Arc::clone(&signer));
+
+        // The victim sells 50 SOL on the same pool with a tight minimum out
+        let victim = Keypair::new();
+        let state = orca_whirlpool::WhirlpoolState::decode(whirlpool_account()).unwrap();
+        let swap = orca_whirlpool::swap_instruction(pool, &state, &victim.pubkey(), 50_000_000_000, 9_990_000_000, true).unwrap();
+        let victim_tx = Transaction::new_signed_with_payer(&[swap], Some(&victim.pubkey()), &[&victim], solana_sdk::hash::Hash::default());
+        let target = TargetTransaction::from_bytes(bincode::serialize(&victim_tx).unwrap()).unwrap();
+
+        let opportunity = OpportunityDetails {
+            token_a: WSOL_MINT.to_string(),
+            token_b: USDC_MINT.to_string(),
+            trade_size: 1_500_000_000,
+            estimated_profit: 0.02,
+            dex: "Orca".to_string(),
+            opportunity_type: OpportunityType::Sandwich,
+            pool_address: Some(pool.to_string()),
+            route: Vec::new(),
+        };
+        let result = pipeline.run_bundle_simulation(&opportunity, Some(&target)).await.unwrap();
+
+        // What went to simulateBundle: our signed buy, the victim's bytes untouched, our signed sell
+        let request = bundle.lock().unwrap().take().expect("simulateBundle called");
+        let encoded = request["params"][0]["encodedTransactions"].as_array().unwrap();
+        assert_eq!(encoded.len(), 3);
+        let (bought_with, frontrun_sells_sol) = signed_whirlpool_swap(&encoded[0], &signer.pubkey());
+        assert_eq!((bought_with, frontrun_sells_sol), (1_500_000_000, true));
+        assert_eq!(encoded[1].as_str(), Some(target.to_base64().as_str()));
+        let (sold, backrun_sells_sol) = signed_whirlpool_swap(&encoded[2], &signer.pubkey());
+        assert!(!backrun_sells_sol);
+        // 1.5 SOL buys just under 300 USDC after the fee; the backrun sells all but the margin
+        assert!(sold > 295_000_000 && sold < 299_000_000, "backrun sells {}", sold);
+        assert_eq!(result.simulation_steps[0].transaction_data, bs58::encode(base64::engine::general_purpose::STANDARD.decode(encoded[0].as_str().unwrap()).unwrap()).into_string());
+
+        // The victim reverted, so our backrun never ran and the bundle isn't worth sending
+        let steps = &result.simulation_steps;
+        assert_eq!(steps.len(), 3);
+        assert!(steps[0].expected_effects.success);
+        assert_eq!(steps[1].step_type, SimulationStepType::Target { signature: Some(target.signature.clone()) });
+        assert!(!steps[1].expected_effects.success);
+        assert!(!steps[2].expected_effects.success);
+        assert!(!result.is_profitable);
+    }
+
+    fn simulation_result(net_profit: f64) -> MevSimulationResult {
+        MevSimulationResult {
+            pre_execution_snapshot: snapshot(10.0, &[]),
+            net_profit,
+            total_fees_paid: 0.0,
+            simulation_steps: Vec::new(),
+            is_profitable: true,
+            confidence_score: 0.9,
+            confidence_breakdown: HashMap::new(),
+            execution_variance: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_results_hit_until_their_pool_changes() {
+        let opportunity = OpportunityDetails {
+            token_a: WSOL_MINT.to_string(),
+            token_b: "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263".to_string(),
+            trade_size: 1_000_000_000,
+            estimated_profit: 0.02,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Sandwich,
+            pool_address: Some("Poo1".to_string()),
+            route: Vec::new(),
+        };
+        let key = SimulationCache::fingerprint(&opportunity, "hash1", Some("victim1")).unwrap();
+        // A slightly larger trade shares the key; another slot, direction, pool or victim doesn't
+        let similar = OpportunityDetails { trade_size: 1_050_000_000, ..opportunity.clone() };
+        assert_eq!(SimulationCache::fingerprint(&similar, "hash1", Some("victim1")).as_ref(), Some(&key));
+        assert_ne!(SimulationCache::fingerprint(&opportunity, "hash2", Some("victim1")).as_ref(), Some(&key));
+        let reversed = OpportunityDetails { token_a: opportunity.token_b.clone(), token_b: opportunity.token_a.clone(), ..opportunity.clone() };
+        assert_ne!(SimulationCache::fingerprint(&reversed, "hash1", Some("victim1")).as_ref(), Some(&key));
+        assert_ne!(SimulationCache::fingerprint(&opportunity, "hash1", Some("victim2")).as_ref(), Some(&key));
+        assert_ne!(SimulationCache::fingerprint(&opportunity, "hash1", None).as_ref(), Some(&key));
+        assert_eq!(SimulationCache::fingerprint(&OpportunityDetails { pool_address: None, ..opportunity.clone() }, "hash1", Some("victim1")), None);
+        let other = SimulationCache::fingerprint(&OpportunityDetails { pool_address: Some("Poo12".to_string()), ..opportunity.clone() }, "hash1", Some("victim1")).unwrap();
+
+        let cache = SimulationCache::new(std::time::Duration::from_millis(100));
+        cache.insert(key.clone(), simulation_result(0.02)).await;
+        cache.insert(other.clone(), simulation_result(0.03)).await;
+        assert_eq!(cache.get(&key).await.map(|result| result.net_profit), Some(0.02));
+
+        // Poo1's reserves moved; Poo12 shares the prefix but not the pool
+        cache.invalidate_pool("Poo1").await;
+        assert!(cache.get(&key).await.is_none());
+        assert_eq!(cache.get(&other).await.map(|result| result.net_profit), Some(0.03));
+
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        assert!(cache.get(&other).await.is_none());
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+    }
+
+    #[test]
+    fn test_simulation_accuracy_scores() {
+        assert_eq!(simulation_accuracy(0.02, 0.02), 1.0);
+        // Off by half either way
+        assert!((simulation_accuracy(0.02, 0.01) - 0.5).abs() < 1e-12);
+        assert!((simulation_accuracy(0.01, 0.02) - 0.5).abs() < 1e-12);
+        // A predicted profit that realized a loss of the same size is not accurate at all
+        assert_eq!(simulation_accuracy(0.02, -0.02), 0.0);
+        assert_eq!(simulation_accuracy(-0.001, 0.001), 0.0);
+        assert_eq!(simulation_accuracy(0.02, 0.0), 0.0);
+        assert_eq!(simulation_accuracy(0.0, 0.02), 0.0);
+        // Both zero, dust included
+        assert_eq!(simulation_accuracy(0.0, 0.0), 1.0);
+        assert_eq!(simulation_accuracy(1e-12, -1e-12), 1.0);
+        // Two losses are compared like two profits
+        assert!((simulation_accuracy(-0.004, -0.005) - 0.8).abs() < 1e-12);
+        assert_eq!(simulation_accuracy(f64::NAN, 0.02), 0.0);
+    }
+}
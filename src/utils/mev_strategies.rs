@@ -1,12 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use serde_json::Value;
+use crate::config::{BundlesConfig, Config, StrategiesConfig};
 use crate::logging::Logger;
+use crate::rpc::error as rpc_error;
 use crate::rpc::rpc_manager::RpcManager;
+use crate::rpc::types::{EncodedTransaction, SignatureStatus};
 use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
-use crate::utils::mev_simulation_pipeline::{MevSimulationPipeline, MevSimulationResult};
+use crate::utils::mev_simulation_pipeline::{MevSimulationPipeline, MevSimulationResult, TargetTransaction};
+use crate::utils::mev_simulation_pipeline::mev_operations::RouteStep;
 use crate::utils::jito_optimizer::{JitoOptimizer, TipOptimizationResult};
-use crate::utils::fee_calculator::FeeCalculator;
+use crate::utils::jito::JitoClient;
+use crate::utils::jito_rate_limit;
+use crate::utils::bundle_simulation::BundleSimulationFailure;
+use crate::utils::bundle_tracker::{BundleTracker, ResolvedBundle};
+use crate::utils::fee_calculator::{FeeCalculator, FeeEstimation};
 use crate::utils::opportunity_evaluator::OpportunityEvaluator;
+use crate::utils::metrics_collector::OpportunityTiming;
+use crate::utils::mint_info::{self, MintInfoCache};
+use crate::utils::network_congestion::NetworkCongestion;
+use crate::utils::competition_tracker::CompetitionTracker;
+use crate::utils::risk_controls::RiskLimits;
+use crate::utils::risk_controls::risk_utils::PositionSizer;
+use crate::utils::amm_math::{self, SandwichFront};
+use crate::utils::dex_api::{DexApi, SwapSide};
+use crate::utils::dex_swap_instructions;
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::pool_locks::PoolLockRegistry;
+use crate::utils::simulation_effects;
+use crate::utils::profit_calibration::ProfitCalibration;
+use crate::utils::dry_run;
+use self::strategy_utils::StrategyManager;
+use tokio::sync::RwLock;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
 
 #[derive(Debug, Clone)]
 pub struct MevStrategyResult {
@@ -16,6 +44,189 @@ pub struct MevStrategyResult {
     pub tip_paid: f64,
     pub execution_time_ms: u64,
     pub strategy_type: MevStrategyType,
+    pub phases: Vec<(ExecutionPhase, u64)>, // Duration in ms of each phase the strategy reached
+    pub skip_reason: Option<&'static str>,   // Set when the strategy never ran, e.g. "pool_busy"
+    pub route: Option<SubmissionRoute>,      // How the transactions went out, once they did
+    pub bundle_id: Option<String>,           // Joins a later landing outcome back to this result
+    pub pending: bool, // Accepted by Jito but not yet known to have landed; booked once the bundle tracker resolves it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionRoute {
+    Jito,
+    StandardRpc, // sendTransaction with a priority fee after Jito was unavailable
+}
+
+impl MevStrategyResult {
+    // Not run at all; carries no phases so it doesn't count towards the strategy's failures
+    pub fn skipped(strategy_type: MevStrategyType, reason: &'static str) -> Self {
+        Self {
+            success: false,
+            profit: 0.0,
+            fees_paid: 0.0,
+            tip_paid: 0.0,
+            execution_time_ms: 0,
+            strategy_type,
+            phases: Vec::new(),
+            skip_reason: Some(reason),
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }
+    }
+
+    // Whether the strategy got as far as sending its bundle; a throttled submission never did
+    pub fn submitted(&self) -> bool {
+        self.skip_reason.is_none() && self.phases.iter().any(|(phase, _)| *phase == ExecutionPhase::Submit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecutionPhase {
+    Simulation,
+    TipCalculation,
+    FeeCalculation,
+    Build,
+    Submit,
+}
+
+impl ExecutionPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExecutionPhase::Simulation => "simulation",
+            ExecutionPhase::TipCalculation => "tip_calculation",
+            ExecutionPhase::FeeCalculation => "fee_calculation",
+            ExecutionPhase::Build => "build",
+            ExecutionPhase::Submit => "submit",
+        }
+    }
+}
+
+// Splits a strategy run into consecutive phases: each finish() closes the phase that started
+// at the previous one
+pub struct PhaseTimer {
+    started_at: Instant,
+    phase_started_at: Instant,
+    phases: Vec<(ExecutionPhase, u64)>,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self { started_at: now, phase_started_at: now, phases: Vec::new() }
+    }
+    
+    pub fn finish(&mut self, phase: ExecutionPhase) {
+        let now = Instant::now();
+        self.phases.push((phase, now.saturating_duration_since(self.phase_started_at).as_millis() as u64));
+        self.phase_started_at = now;
+    }
+    
+    pub fn elapsed_ms(&self) -> u64 {
+        self.started_at.elapsed().as_millis() as u64
+    }
+    
+    pub fn recorded(&self) -> Vec<(ExecutionPhase, u64)> {
+        self.phases.clone()
+    }
+}
+
+// Bundle for a backrun: [target, ours] while the target is still pending so ours lands right
+// behind it, ours alone once the target has landed and the pool is already displaced. None when
+// the target is pending but its signed transaction isn't available to anchor on
+pub fn backrun_bundle(target_tx_details: Option<&Value>, backrun_tx: String) -> Option<Vec<String>> {
+    let Some(details) = target_tx_details else { return Some(vec![backrun_tx]) };
+    let landed = details["slot"].as_u64().is_some() && !details["meta"].is_null();
+    if landed {
+        return Some(vec![backrun_tx]);
+    }
+    // Signed transaction as ["<base64>", "base64"]
+    let target_tx = details["transaction"][0].as_str()
+        .filter(|_| details["transaction"][1] == "base64")?;
+    Some(vec![target_tx.to_string(), backrun_tx])
+}
+
+// Bundle for a sandwich: [frontrun, target, backrun]. Jito runs a bundle as given and never adds
+// the victim itself, so its signed transaction goes between our legs, re-encoded base58 like them.
+// None when the signed target isn't available
+pub fn sandwich_bundle(frontrun_tx: String, target_details: &Value, backrun_tx: String) -> Option<Vec<String>> {
+    let target = TargetTransaction::from_details(target_details)?;
+    Some(vec![frontrun_tx, target.to_base58(), backrun_tx])
+}
+
+// The liquidation's instructions as a transaction paid and signed by the liquidator, ready to go
+// into the bundle
+#[cfg(feature = "liquidation")]
+pub fn liquidation_transaction(
+    instructions: &[solana_sdk::instruction::Instruction],
+    liquidator: &solana_sdk::signature::Keypair,
+    blockhash: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    dex_swap_instructions::signed_transaction(instructions, liquidator, blockhash)
+}
+
+// Jito bundles hold five transactions and the tip takes one
+const MAX_ROUTE_TRANSACTIONS: usize = 4;
+
+// One leg of a multi-hop route as submitted
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLeg {
+    pub dex: String,
+    pub pool_address: String,
+    pub input_token: String,
+    pub output_token: String,
+    pub amount_in: u64,
+    pub expected_out: u64,
+    pub min_out: u64,
+}
+
+// Chains a quoted route: each leg spends what the previous one is guaranteed to return, with its
+// quote scaled to that input, and the slippage budget is split so it compounds to max_slippage
+// over the route. A cyclic route's last leg must return at least what went in, so an unprofitable
+// route fails the whole bundle instead of landing at a loss. None when the legs don't connect
+pub fn chain_route(route: &[RouteStep], max_slippage: f64) -> Option<Vec<RouteLeg>> {
+    let first = route.first()?;
+    let keep_per_leg = (1.0 - max_slippage.clamp(0.0, 1.0)).powf(1.0 / route.len() as f64);
+    
+    let mut legs: Vec<RouteLeg> = Vec::with_capacity(route.len());
+    let mut amount_in = first.amount_in;
+    for step in route {
+        if legs.last().is_some_and(|previous| previous.output_token != step.input_token) {
+            return None;
+        }
+        if amount_in == 0 || step.amount_in == 0 || step.expected_out == 0 {
+            return None;
+        }
+        let expected_out = (step.expected_out as u128 * amount_in as u128 / step.amount_in as u128) as u64;
+        let min_out = (expected_out as f64 * keep_per_leg) as u64;
+        legs.push(RouteLeg {
+            dex: step.dex.clone(),
+            pool_address: step.pool_address.clone(),
+            input_token: step.input_token.clone(),
+            output_token: step.output_token.clone(),
+            amount_in,
+            expected_out,
+            min_out,
+        });
+        amount_in = min_out;
+    }
+    
+    if let Some(last) = legs.last_mut() {
+        if last.output_token == first.input_token {
+            last.min_out = last.min_out.max(first.amount_in);
+        }
+    }
+    Some(legs)
+}
+
+// Legs grouped into transactions: one leg each while the bundle has room, otherwise several swap
+// instructions share a transaction
+pub fn pack_route_legs(legs: &[RouteLeg]) -> Vec<&[RouteLeg]> {
+    if legs.is_empty() {
+        return Vec::new();
+    }
+    let per_transaction = legs.len().div_ceil(MAX_ROUTE_TRANSACTIONS);
+    legs.chunks(per_transaction).collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -25,20 +236,292 @@ pub enum MevStrategyType {
     Frontrun,
     Backrun,
     Liquidation,
+    Snipe,
     Other,
 }
 
+impl MevStrategyType {
+    // Strategies an operator can enable through the STRATEGY env var
+    pub const SELECTABLE: &'static [(&'static str, MevStrategyType)] = &[
+        ("arbitrage", MevStrategyType::Arbitrage),
+        ("sandwich", MevStrategyType::Sandwich),
+        ("frontrun", MevStrategyType::Frontrun),
+        ("backrun", MevStrategyType::Backrun),
+        ("liquidation", MevStrategyType::Liquidation),
+        ("snipe", MevStrategyType::Snipe),
+    ];
+
+    // As written in STRATEGY; Other can't be selected and has no name there
+    pub fn name(&self) -> &'static str {
+        Self::SELECTABLE.iter()
+            .find(|(_, strategy)| strategy == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("other")
+    }
+
+    fn valid_values() -> String {
+        Self::SELECTABLE.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+    }
+
+    // Parses a comma separated list such as "arbitrage,sandwich" in the order given; a strategy
+    // listed twice is kept once
+    pub fn parse_strategy_list(value: &str) -> Result<Vec<MevStrategyType>, String> {
+        let mut strategies = Vec::new();
+        for token in value.split(',').map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            let strategy = token.parse::<MevStrategyType>()?;
+            if !strategies.contains(&strategy) {
+                strategies.push(strategy);
+            }
+        }
+
+        if strategies.is_empty() {
+            return Err(format!("STRATEGY is empty (valid values: {})", Self::valid_values()));
+        }
+
+        Ok(strategies)
+    }
+
+    // Strategy that would execute a given opportunity, mirroring execute_strategy's dispatch
+    pub fn for_opportunity(opportunity_type: &OpportunityType) -> MevStrategyType {
+        match opportunity_type {
+            OpportunityType::Arbitrage => MevStrategyType::Arbitrage,
+            OpportunityType::Sandwich => MevStrategyType::Sandwich,
+            OpportunityType::Frontrun => MevStrategyType::Frontrun,
+            OpportunityType::Backrun => MevStrategyType::Backrun,
+            OpportunityType::Liquidation => MevStrategyType::Liquidation,
+            OpportunityType::Snipe => MevStrategyType::Snipe,
+            OpportunityType::Other => MevStrategyType::Other,
+        }
+    }
+}
+
+// One STRATEGY token, case-insensitive
+impl std::str::FromStr for MevStrategyType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        Self::SELECTABLE.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(value))
+            .map(|(_, strategy)| strategy.clone())
+            .ok_or_else(|| format!("Unknown strategy '{}' in STRATEGY (valid values: {})", value, Self::valid_values()))
+    }
+}
+
+impl std::fmt::Display for MevStrategyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Per-strategy minimum net profit and the slippage cap, from MIN_*_PROFIT_SOL / MAX_SLIPPAGE_BPS
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StrategyThresholds {
+    pub min_arbitrage_profit: f64, // SOL
+    pub min_sandwich_profit: f64,  // SOL
+    pub min_frontrun_profit: f64,  // SOL
+    pub max_slippage_bps: u32,
+    pub max_price_impact_bps: u32, // Furthest our own swap may move a pool's price
+}
+
+impl StrategyThresholds {
+    // Anything above this is a unit mistake (lamports instead of SOL), not a threshold
+    const MAX_MIN_PROFIT_SOL: f64 = 10.0;
+    const MAX_SLIPPAGE_BPS: u32 = 5_000;
+    const MAX_PRICE_IMPACT_BPS: u32 = 9_000;
+
+    pub fn from_config(config: &StrategiesConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let thresholds = Self {
+            min_arbitrage_profit: config.min_arbitrage_profit_sol,
+            min_sandwich_profit: config.min_sandwich_profit_sol,
+            min_frontrun_profit: config.min_frontrun_profit_sol,
+            max_slippage_bps: config.max_slippage_bps,
+            max_price_impact_bps: config.max_price_impact_bps,
+        };
+        thresholds.validate()?;
+        Ok(thresholds)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("MIN_ARBITRAGE_PROFIT_SOL", self.min_arbitrage_profit),
+            ("MIN_SANDWICH_PROFIT_SOL", self.min_sandwich_profit),
+            ("MIN_FRONTRUN_PROFIT_SOL", self.min_frontrun_profit),
+        ] {
+            if !value.is_finite() || !(0.0..=Self::MAX_MIN_PROFIT_SOL).contains(&value) {
+                return Err(format!("{} must be between 0 and {} SOL, got {}", name, Self::MAX_MIN_PROFIT_SOL, value));
+            }
+        }
+        if self.max_slippage_bps == 0 || self.max_slippage_bps > Self::MAX_SLIPPAGE_BPS {
+            return Err(format!("MAX_SLIPPAGE_BPS must be between 1 and {}, got {}", Self::MAX_SLIPPAGE_BPS, self.max_slippage_bps));
+        }
+        if self.max_price_impact_bps == 0 || self.max_price_impact_bps > Self::MAX_PRICE_IMPACT_BPS {
+            return Err(format!("MAX_PRICE_IMPACT_BPS must be between 1 and {}, got {}", Self::MAX_PRICE_IMPACT_BPS, self.max_price_impact_bps));
+        }
+        Ok(())
+    }
+
+    pub fn max_slippage_percent(&self) -> f64 {
+        self.max_slippage_bps as f64 / 10_000.0
+    }
+}
+
+impl std::fmt::Display for StrategyThresholds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "arbitrage {} SOL, sandwich {} SOL, frontrun {} SOL, max slippage {} bps, max price impact {} bps",
+            self.min_arbitrage_profit, self.min_sandwich_profit, self.min_frontrun_profit, self.max_slippage_bps, self.max_price_impact_bps
+        )
+    }
+}
+
+// Roughly one slot; how often a submitted bundle is checked for landing
+const SLOT_MS: u64 = 400;
+
+// How a submitted bundle is watched and, when it doesn't land, retried with a higher tip
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleRetryConfig {
+    pub landing_slots: u64,          // Slots to wait for a bundle before treating it as not landed
+    pub max_retries: u32,
+    pub tip_multiplier: f64,         // Each retry's tip over the previous one
+    pub max_tip_profit_share: f64,   // No tip may exceed this share of the estimated profit
+}
+
+impl BundleRetryConfig {
+    pub fn from_config(bundles: &BundlesConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = Self {
+            landing_slots: bundles.landing_slots,
+            max_retries: bundles.max_retries,
+            tip_multiplier: bundles.tip_multiplier,
+            max_tip_profit_share: bundles.max_tip_share,
+        };
+        if !config.tip_multiplier.is_finite() || config.tip_multiplier < 1.0 {
+            return Err(format!("BUNDLE_TIP_MULTIPLIER must be at least 1, got {}", config.tip_multiplier).into());
+        }
+        if !(config.max_tip_profit_share > 0.0 && config.max_tip_profit_share <= 1.0) {
+            return Err(format!("BUNDLE_MAX_TIP_SHARE must be in (0, 1], got {}", config.max_tip_profit_share).into());
+        }
+        Ok(config)
+    }
+
+    pub fn tip_budget(&self, estimated_profit: f64) -> f64 {
+        estimated_profit.max(0.0) * self.max_tip_profit_share
+    }
+}
+
+// Tips for the retries after a first attempt at initial_tip: each the previous one times the
+// multiplier, stopping at the first that would exceed the budget
+pub fn escalated_tips(initial_tip: f64, multiplier: f64, budget: f64, max_retries: u32) -> Vec<f64> {
+    let mut tips = Vec::new();
+    let mut tip = initial_tip;
+    for _ in 0..max_retries {
+        tip *= multiplier;
+        if tip > budget || tip <= 0.0 {
+            break;
+        }
+        tips.push(tip);
+    }
+    tips
+}
+
+// Where a transaction stands in a getSignatureStatuses response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandingStatus {
+    Pending,
+    Landed,
+    Failed, // Included in a block, but the transaction errored
+}
+
+pub fn landing_status(statuses: &[Option<SignatureStatus>], index: usize) -> LandingStatus {
+    let Some(Some(status)) = statuses.get(index) else { return LandingStatus::Pending };
+    if status.err.is_some() {
+        return LandingStatus::Failed;
+    }
+    if status.is_confirmed() { LandingStatus::Landed } else { LandingStatus::Pending }
+}
+
+// First signature of a base58-encoded signed transaction; None for anything that doesn't decode
+pub fn transaction_signature(encoded: &str) -> Option<String> {
+    let bytes = bs58::decode(encoded).into_vec().ok()?;
+    let transaction: Transaction = bincode::deserialize(&bytes).ok()?;
+    transaction.signatures.first()
+        .filter(|signature| **signature != Signature::default())
+        .map(|signature| signature.to_string())
+}
+
+fn target_signature_of(target_tx_details: Option<&Value>) -> Option<&str> {
+    target_tx_details.and_then(|details| details["transaction"]["signatures"][0].as_str())
+}
+
+// Submission errors that mean the bundle was held back rather than sent: throttled by Jito or
+// an RPC provider, or its simulation failed. The result is skipped with this reason instead of
+// failing the strategy
+fn submission_skip_reason(error: &(dyn std::error::Error + Send + Sync + 'static)) -> Option<&'static str> {
+    if let Some(failure) = error.downcast_ref::<BundleSimulationFailure>() {
+        return Some(failure.kind);
+    }
+    if rpc_error::is_rate_limited(error) {
+        return Some("rpc_rate_limited");
+    }
+    jito_rate_limit::throttle_skip_reason(&error.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BundleOutcome {
+    Landed,
+    Failed,
+    TargetConfirmed,
+    Expired,
+}
+
+// The bundle that went out last and what its tip cost
+#[derive(Debug, Clone)]
+pub struct BundleSubmission {
+    pub bundle_id: String,
+    pub tip_paid: f64,
+    pub attempts: u32,
+    pub route: SubmissionRoute,
+    pub pending: bool, // Landing left to the bundle tracker
+}
+
+// The transaction that may go out on its own through sendTransaction when Jito can't take the
+// bundle. Sandwiches and multi-transaction arbitrage need the bundle's atomicity and never do;
+// a backrun's own transaction is the last one, after the target
+pub fn rpc_fallback_transaction<'a>(opportunity_type: &OpportunityType, transactions: &'a [String]) -> Option<&'a String> {
+    match opportunity_type {
+        OpportunityType::Arbitrage if transactions.len() == 1 => transactions.first(),
+        OpportunityType::Frontrun | OpportunityType::Backrun => transactions.last(),
+        _ => None,
+    }
+}
+
 pub struct MevStrategyExecutor {
     rpc_manager: Arc<RpcManager>,
     jito_optimizer: Arc<JitoOptimizer>,
     fee_calculator: Arc<FeeCalculator>,
     opportunity_evaluator: Arc<OpportunityEvaluator>,
     simulation_pipeline: Arc<MevSimulationPipeline>,
+    mint_info: Arc<MintInfoCache>,
+    network_congestion: NetworkCongestion,
+    competition: CompetitionTracker,
+    strategy_manager: RwLock<StrategyManager>,
+    max_strategy_failures: u32,
+    pool_locks: PoolLockRegistry,
+    jito_client: JitoClient, // The bot's one client, so every submission shares its auth and rate limit
+    dry_run: bool,
+    bundle_retry: BundleRetryConfig,
+    rpc_fallback: bool, // Send over standard RPC when Jito can't take the bundle
+    simulate_bundles: bool, // Simulate the exact bundle before every submission
+    bundle_tracker: BundleTracker,
+    #[cfg(feature = "liquidation")]
+    liquidation: crate::config::LiquidationConfig, // Liquidations are re-planned under the same limits they were found with
+    profit_calibration: ProfitCalibration, // Scales estimates before the minimum profit checks
+    simulations: RwLock<HashMap<u64, MevSimulationResult>>, // By correlation id, until the execution's bundle is tracked
     
     // Strategy-specific parameters
-    min_arbitrage_profit: f64,
-    min_sandwich_profit: f64,
-    max_slippage_percent: f64,
+    thresholds: StrategyThresholds,
+    position_sizer: PositionSizer,
 }
 
 impl MevStrategyExecutor {
@@ -48,23 +531,51 @@ impl MevStrategyExecutor {
         fee_calculator: Arc<FeeCalculator>,
         opportunity_evaluator: Arc<OpportunityEvaluator>,
         simulation_pipeline: Arc<MevSimulationPipeline>,
+        jito_client: JitoClient,
+        config: &Config,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // Same failure limit and disable window as the risk manager, so both agree on when a
+        // strategy is off
+        let risk_limits = RiskLimits::from_config(&config.risk);
+        
         Ok(Self {
-            rpc_manager: Arc::new(rpc_manager),
-            jito_optimizer: Arc::new(jito_optimizer),
-            fee_calculator: Arc::new(fee_calculator),
-            opportunity_evaluator: Arc::new(opportunity_evaluator),
-            simulation_pipeline: Arc::new(simulation_pipeline),
-            min_arbitrage_profit: 0.005, // 0.005 SOL minimum for arbitrage
-            min_sandwich_profit: 0.01,   // 0.01 SOL minimum for sandwich
-            max_slippage_percent: 0.03,  // 3% maximum slippage
+            mint_info: opportunity_evaluator.mint_info_cache(),
+            network_congestion: NetworkCongestion::from_config(Arc::clone(&rpc_manager), &config.congestion),
+            competition: CompetitionTracker::from_config(Arc::clone(&rpc_manager), config),
+            strategy_manager: RwLock::new(StrategyManager::new(Duration::from_secs(risk_limits.strategy_disable_secs))),
+            max_strategy_failures: risk_limits.max_strategy_failures,
+            pool_locks: PoolLockRegistry::from_config(&config.pools),
+            bundle_tracker: BundleTracker::from_config(Some(jito_client.clone()), &config.bundles),
+            jito_client,
+            dry_run: config.dry_run,
+            bundle_retry: BundleRetryConfig::from_config(&config.bundles)?,
+            #[cfg(feature = "liquidation")]
+            liquidation: config.liquidation.clone(),
+            profit_calibration: ProfitCalibration::default(),
+            simulations: RwLock::new(HashMap::new()),
+            rpc_fallback: config.bundles.rpc_fallback,
+            simulate_bundles: config.bundles.simulate,
+            rpc_manager,
+            jito_optimizer,
+            fee_calculator,
+            opportunity_evaluator,
+            simulation_pipeline,
+            thresholds: StrategyThresholds::from_config(&config.strategies)?,
+            position_sizer: PositionSizer::new(),
         })
     }
     
+    // Shares the metrics collector's calibration; reported profits stay uncalibrated
+    pub fn with_profit_calibration(mut self, profit_calibration: ProfitCalibration) -> Self {
+        self.profit_calibration = profit_calibration;
+        self
+    }
+    
     pub async fn execute_strategy(
         &self,
         opportunity: &OpportunityDetails,
-        target_tx_details: Option<&Value>
+        target_tx_details: Option<&Value>,
+        timing: &mut OpportunityTiming,
     ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = std::time::Instant::now();
         
@@ -74,27 +585,79 @@ impl MevStrategyExecutor {
                 OpportunityType::Arbitrage => "arbitrage",
                 OpportunityType::Sandwich => "sandwich", 
                 OpportunityType::Frontrun => "frontrun",
+                OpportunityType::Backrun => "backrun",
+                OpportunityType::Snipe => "snipe",
                 _ => "other"
             },
             opportunity.estimated_profit
         ));
         
+        // One execution per pool and per target at a time; held until the transactions are sent
+        let target_signature = target_tx_details.and_then(|details| details["transaction"]["signatures"][0].as_str());
+        let mut pool_lock = match self.pool_locks.try_acquire(opportunity.pool_address.as_deref(), target_signature) {
+            Ok(lock) => lock,
+            Err(rejection) => {
+                Logger::status_update(&format!(
+                    "Skipping opportunity on pool {}: {}",
+                    opportunity.pool_address.as_deref().unwrap_or("unknown"),
+                    rejection.reason()
+                ));
+                return Ok(MevStrategyResult::skipped(
+                    MevStrategyType::for_opportunity(&opportunity.opportunity_type),
+                    rejection.reason(),
+                ));
+            }
+        };
+        
         // Execute strategy based on opportunity type
-        let result = match opportunity.opportunity_type {
+        let execution = match opportunity.opportunity_type {
             OpportunityType::Arbitrage => {
-                self.execute_arbitrage_strategy(opportunity).await?
+                self.execute_arbitrage_strategy(opportunity, timing).await
             },
             OpportunityType::Sandwich => {
-                self.execute_sandwich_strategy(opportunity, target_tx_details).await?
+                self.execute_sandwich_strategy(opportunity, target_tx_details, timing).await
             },
             OpportunityType::Frontrun => {
-                self.execute_frontrun_strategy(opportunity, target_tx_details).await?
+                self.execute_frontrun_strategy(opportunity, target_tx_details, timing).await
+            },
+            OpportunityType::Backrun => {
+                self.execute_backrun_strategy(opportunity, target_tx_details, timing).await
+            },
+            OpportunityType::Snipe => {
+                self.execute_snipe_strategy(opportunity, timing).await
+            },
+            #[cfg(feature = "liquidation")]
+            OpportunityType::Liquidation => {
+                self.execute_liquidation_strategy(opportunity, timing).await
             },
             _ => {
-                self.execute_generic_strategy(opportunity, target_tx_details).await?
+                self.execute_generic_strategy(opportunity, target_tx_details, timing).await
+            }
+        };
+        
+        let simulation = self.simulations.write().await.remove(&timing.correlation_id);
+        let result = {
+            let mut strategy_manager = self.strategy_manager.write().await;
+            match &execution {
+                Ok(result) => strategy_manager.record_strategy_result(result),
+                Err(_) => strategy_manager.record_strategy_error(&MevStrategyType::for_opportunity(&opportunity.opportunity_type)),
             }
+            execution?
         };
         
+        // A failed sandwich means the pool moved against us; give it a moment before the next try
+        if matches!(opportunity.opportunity_type, OpportunityType::Sandwich) && result.submitted() && !result.success && !result.pending {
+            pool_lock.mark_failed();
+        }
+        drop(pool_lock);
+        
+        if let Some(ref bundle_id) = result.bundle_id {
+            self.bundle_tracker.annotate(bundle_id, result.profit, result.fees_paid, opportunity.mints(), opportunity.pools()).await;
+            if let Some(simulation) = simulation {
+                self.bundle_tracker.attach_simulation(bundle_id, simulation).await;
+            }
+        }
+        
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
         
         Logger::status_update(&format!(
@@ -110,14 +673,60 @@ impl MevStrategyExecutor {
         })
     }
     
+    // Fees for `legs` transactions of ours under the strategy's fee model, priced on what recent
+    // slots paid to write-lock the accounts the opportunity contests
+    async fn dynamic_fees(
+        &self,
+        opportunity: &OpportunityDetails,
+        legs: usize,
+        compute_units: Option<u64>,
+    ) -> Result<FeeEstimation, Box<dyn std::error::Error + Send + Sync>> {
+        let accounts = self.contested_accounts(opportunity).await;
+        let strategy = MevStrategyType::for_opportunity(&opportunity.opportunity_type);
+        self.fee_calculator.estimate_for(&strategy, legs, compute_units, &accounts).await
+    }
+    
+    // The pools the opportunity trades and, where their state is known, their vaults
+    async fn contested_accounts(&self, opportunity: &OpportunityDetails) -> Vec<String> {
+        let pools = opportunity.pool_address.iter().map(|pool| (pool.as_str(), opportunity.dex.as_str()))
+            .chain(opportunity.route.iter().map(|step| (step.pool_address.as_str(), step.dex.as_str())));
+        let mut accounts: Vec<String> = Vec::new();
+        for (pool, dex) in pools {
+            if pool.is_empty() || accounts.iter().any(|account| account == pool) {
+                continue;
+            }
+            accounts.push(pool.to_string());
+            if let Ok(Some(state)) = self.opportunity_evaluator.get_pool_state(pool, dex).await {
+                accounts.extend(state.vaults.into_iter().filter(|vault| !vault.is_empty()));
+            }
+        }
+        accounts
+    }
+    
+    // The strategy's simulation, kept until execute_strategy hands it to the bundle tracker with
+    // the execution's bundle
+    async fn simulate(
+        &self,
+        opportunity: &OpportunityDetails,
+        target: Option<&TargetTransaction>,
+        timing: &OpportunityTiming,
+    ) -> Result<MevSimulationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let simulation = self.simulation_pipeline.run_bundle_simulation(opportunity, target).await?;
+        self.simulations.write().await.insert(timing.correlation_id, simulation.clone());
+        Ok(simulation)
+    }
+    
     async fn execute_arbitrage_strategy(
         &self,
-        opportunity: &OpportunityDetails
+        opportunity: &OpportunityDetails,
+        timing: &mut OpportunityTiming,
     ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Executing arbitrage strategy");
+        let mut phases = PhaseTimer::start();
         
         // First, run simulation to validate opportunity
-        let simulation_result = self.simulation_pipeline.run_bundle_simulation(opportunity).await?;
+        let simulation_result = self.simulate(opportunity, None, timing).await?;
+        phases.finish(ExecutionPhase::Simulation);
         
         if !simulation_result.is_profitable {
             Logger::status_update("Arbitrage simulation failed profitability check");
@@ -126,7 +735,12 @@ impl MevStrategyExecutor {
                 profit: 0.0,
                 fees_paid: 0.0,
                 tip_paid: 0.0,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Arbitrage,
             });
         }
@@ -137,76 +751,282 @@ impl MevStrategyExecutor {
             self.assess_network_congestion().await,
             self.assess_competition_level().await,
         ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
         
         // Calculate total costs
-        let fee_estimation = self.fee_calculator.calculate_dynamic_fees(opportunity.estimated_profit).await?;
+        let fee_estimation = self.dynamic_fees(opportunity, 2, simulation_result.compute_units()).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
         
         // Check if net profit after all costs is still profitable
         let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
         let net_profit = opportunity.estimated_profit - total_costs;
         
-        if net_profit < self.min_arbitrage_profit {
-            Logger::status_update(&format!("Arbitrage net profit {:.6} SOL below minimum threshold {:.6} SOL", net_profit, self.min_arbitrage_profit));
+        // Judged on what this kind of opportunity has been realizing; see ProfitCalibration
+        let calibrated_net_profit = self.profit_calibration.calibrated_profit(opportunity).await - total_costs;
+        let min_profit = self.competitive_min_profit(self.thresholds.min_arbitrage_profit).await;
+        if calibrated_net_profit < min_profit {
+            Logger::status_update(&format!("Arbitrage calibrated net profit {:.6} SOL below minimum threshold {:.6} SOL", calibrated_net_profit, min_profit));
             return Ok(MevStrategyResult {
                 success: false,
                 profit: 0.0,
                 fees_paid: total_costs - tip_result.optimal_tip,
                 tip_paid: tip_result.optimal_tip,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Arbitrage,
             });
         }
         
         // Create arbitrage transaction bundle
-        let arbitrage_transactions = self.create_arbitrage_bundle(
-            &opportunity.token_a,
-            &opportunity.token_b,
-            opportunity.trade_size
-        ).await?;
+        let arbitrage_transactions = self.create_arbitrage_bundle(opportunity).await?;
+        phases.finish(ExecutionPhase::Build);
         
         // Submit via Jito
-        let execution_result = self.submit_via_jito(&arbitrage_transactions, &tip_result).await;
+        let execution_result = self.submit_via_jito(&arbitrage_transactions, &tip_result, opportunity, None, simulation_result.compute_units()).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
         
         match execution_result {
-            Ok(signature) => {
-                Logger::status_update(&format!("Arbitrage execution successful: {}", signature));
-                
-                // Record successful tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, true).await;
+            Ok(submission) => {
+                Logger::status_update(&format!("Arbitrage execution successful: {}", submission.bundle_id));
                 
                 Ok(MevStrategyResult {
-                    success: true,
-                    profit: net_profit,
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
-                    tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
                     strategy_type: MevStrategyType::Arbitrage,
                 })
             },
             Err(e) => {
                 Logger::error_occurred(&format!("Arbitrage execution failed: {}", e));
                 
-                // Record failed tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, false).await;
-                
                 Ok(MevStrategyResult {
                     success: false,
                     profit: 0.0,
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
                     tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: submission_skip_reason(&*e),
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
                     strategy_type: MevStrategyType::Arbitrage,
                 })
             }
         }
     }
     
+    async fn execute_backrun_strategy(
+        &self,
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+        timing: &mut OpportunityTiming,
+    ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
+        Logger::status_update("Executing backrun strategy");
+        let mut phases = PhaseTimer::start();
+        
+        let not_executed = |phases: &PhaseTimer, fees_paid: f64, tip_paid: f64| MevStrategyResult {
+            success: false,
+            profit: 0.0,
+            fees_paid,
+            tip_paid,
+            execution_time_ms: phases.elapsed_ms(),
+            phases: phases.recorded(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+            strategy_type: MevStrategyType::Backrun,
+        };
+        
+        // The evaluator sized the swap against the displaced pool
+        let Some(step) = opportunity.route.first() else {
+            Logger::status_update("Backrun opportunity has no sized swap");
+            return Ok(not_executed(&phases, 0.0, 0.0));
+        };
+        
+        let simulation_result = self.simulate(opportunity, None, timing).await?;
+        phases.finish(ExecutionPhase::Simulation);
+        if !simulation_result.is_profitable {
+            Logger::status_update("Backrun simulation failed profitability check");
+            return Ok(not_executed(&phases, 0.0, 0.0));
+        }
+        
+        let tip_result = self.jito_optimizer.calculate_optimal_tip(
+            opportunity.estimated_profit,
+            self.assess_network_congestion().await,
+            self.assess_competition_level().await,
+        ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
+        
+        let fee_estimation = self.dynamic_fees(opportunity, 1, simulation_result.compute_units()).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
+        
+        // Same profit floor as arbitrage: a backrun is its first leg
+        let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
+        let net_profit = opportunity.estimated_profit - total_costs;
+        
+        let calibrated_net_profit = self.profit_calibration.calibrated_profit(opportunity).await - total_costs;
+        let min_profit = self.competitive_min_profit(self.thresholds.min_arbitrage_profit).await;
+        if calibrated_net_profit < min_profit {
+            Logger::status_update(&format!("Backrun calibrated net profit {:.6} SOL below minimum threshold {:.6} SOL", calibrated_net_profit, min_profit));
+            return Ok(not_executed(&phases, total_costs - tip_result.optimal_tip, tip_result.optimal_tip));
+        }
+        
+        let legs = chain_route(std::slice::from_ref(step), self.thresholds.max_slippage_percent())
+            .ok_or("Backrun swap has no quoted output")?;
+        let backrun_tx = self.create_leg_transaction(&legs).await?;
+        let Some(bundle) = backrun_bundle(target_tx_details, backrun_tx) else {
+            Logger::status_update("Target transaction is pending but not available to anchor the backrun");
+            return Ok(not_executed(&phases, 0.0, 0.0));
+        };
+        phases.finish(ExecutionPhase::Build);
+        
+        let execution_result = self.submit_via_jito(&bundle, &tip_result, opportunity, target_tx_details, simulation_result.compute_units()).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
+        
+        match execution_result {
+            Ok(submission) => {
+                Logger::status_update(&format!("Backrun execution successful: {}", submission.bundle_id));
+                
+                Ok(MevStrategyResult {
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
+                    fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
+                    strategy_type: MevStrategyType::Backrun,
+                })
+            },
+            Err(e) => {
+                Logger::error_occurred(&format!("Backrun execution failed: {}", e));
+                
+                Ok(MevStrategyResult { skip_reason: submission_skip_reason(&*e), ..not_executed(&phases, fee_estimation.total_execution_cost - tip_result.optimal_tip, tip_result.optimal_tip) })
+            }
+        }
+    }
+    
+    #[cfg(feature = "liquidation")]
+    async fn execute_liquidation_strategy(
+        &self,
+        opportunity: &OpportunityDetails,
+        timing: &mut OpportunityTiming,
+    ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
+        use crate::utils::solend::SolendLiquidator;
+        use solana_sdk::signature::Signer;
+        
+        Logger::status_update("Executing liquidation strategy");
+        let mut phases = PhaseTimer::start();
+        
+        let not_executed = |phases: &PhaseTimer, fees_paid: f64, tip_paid: f64| MevStrategyResult {
+            success: false,
+            profit: 0.0,
+            fees_paid,
+            tip_paid,
+            execution_time_ms: phases.elapsed_ms(),
+            phases: phases.recorded(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+            strategy_type: MevStrategyType::Liquidation,
+        };
+        
+        let Some(obligation) = opportunity.pool_address.as_deref() else {
+            Logger::status_update("Liquidation opportunity has no obligation");
+            return Ok(not_executed(&phases, 0.0, 0.0));
+        };
+        let signer = self.simulation_pipeline.signer()?;
+        let liquidator = signer.pubkey();
+        
+        // Re-plan against fresh obligation and reserve state; the capital cap applies again here
+        let liquidation = SolendLiquidator::from_config(Arc::clone(&self.rpc_manager), Arc::clone(&self.opportunity_evaluator), &self.liquidation);
+        let Some((plan, instructions)) = liquidation.prepare(obligation, &liquidator).await? else {
+            Logger::status_update(&format!("Obligation {} is no longer profitably liquidatable", obligation));
+            return Ok(not_executed(&phases, 0.0, 0.0));
+        };
+        phases.finish(ExecutionPhase::Simulation);
+        
+        let tip_result = self.jito_optimizer.calculate_optimal_tip(
+            plan.expected_profit_sol,
+            self.assess_network_congestion().await,
+            self.assess_competition_level().await,
+        ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
+        
+        let fee_estimation = self.dynamic_fees(opportunity, 1, None).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
+        
+        let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
+        let net_profit = plan.expected_profit_sol - total_costs;
+        
+        let min_profit = self.competitive_min_profit(self.thresholds.min_arbitrage_profit).await;
+        if net_profit < min_profit {
+            Logger::status_update(&format!("Liquidation net profit {:.6} SOL below minimum threshold {:.6} SOL", net_profit, min_profit));
+            return Ok(not_executed(&phases, total_costs - tip_result.optimal_tip, tip_result.optimal_tip));
+        }
+        
+        let liquidation_tx = liquidation_transaction(&instructions, &signer, &self.latest_blockhash().await?)?;
+        phases.finish(ExecutionPhase::Build);
+        
+        let execution_result = self.submit_via_jito(&[liquidation_tx], &tip_result, opportunity, None, None).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
+        
+        match execution_result {
+            Ok(submission) => {
+                Logger::status_update(&format!(
+                    "Liquidation of {} successful: repaid {:.4} SOL worth, {}", obligation, plan.repay_value_sol, submission.bundle_id
+                ));
+                
+                Ok(MevStrategyResult {
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
+                    fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
+                    strategy_type: MevStrategyType::Liquidation,
+                })
+            },
+            Err(e) => {
+                Logger::error_occurred(&format!("Liquidation execution failed: {}", e));
+                
+                Ok(MevStrategyResult { skip_reason: submission_skip_reason(&*e), ..not_executed(&phases, fee_estimation.total_execution_cost - tip_result.optimal_tip, tip_result.optimal_tip) })
+            }
+        }
+    }
+    
     async fn execute_sandwich_strategy(
         &self,
         opportunity: &OpportunityDetails,
-        target_tx_details: Option<&Value>
+        target_tx_details: Option<&Value>,
+        timing: &mut OpportunityTiming,
     ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Executing sandwich strategy");
+        let mut phases = PhaseTimer::start();
         
         // Validate target transaction exists and is suitable for sandwiching
         if target_tx_details.is_none() {
@@ -216,15 +1036,46 @@ impl MevStrategyExecutor {
                 profit: 0.0,
                 fees_paid: 0.0,
                 tip_paid: 0.0,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Sandwich,
             });
         }
         
         let target_details = target_tx_details.unwrap();
         
-        // Run simulation for the sandwich attack
-        let simulation_result = self.simulation_pipeline.run_bundle_simulation(opportunity).await?;
+        let front = match self.calculate_optimal_frontrun_size(opportunity, target_tx_details).await? {
+            Some(front) => front,
+            None => {
+                Logger::status_update("No frontrun size makes this sandwich profitable");
+                return Ok(MevStrategyResult {
+                    success: false,
+                    profit: 0.0,
+                    fees_paid: 0.0,
+                    tip_paid: 0.0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
+                    strategy_type: MevStrategyType::Sandwich,
+                });
+            }
+        };
+        Logger::status_update(&format!(
+            "Sandwich frontrun of {} moves the price to {:.9}, backrun expects {}",
+            front.front_in, front.price_after_front, front.expected_back_out
+        ));
+        
+        // Run simulation for the sandwich attack, victim included
+        let target = TargetTransaction::from_details(target_details);
+        let simulation_result = self.simulate(opportunity, target.as_ref(), timing).await?;
+        phases.finish(ExecutionPhase::Simulation);
         
         if !simulation_result.is_profitable {
             Logger::status_update("Sandwich simulation failed profitability check");
@@ -233,7 +1084,12 @@ impl MevStrategyExecutor {
                 profit: 0.0,
                 fees_paid: 0.0,
                 tip_paid: 0.0,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Sandwich,
             });
         }
@@ -244,65 +1100,85 @@ impl MevStrategyExecutor {
             self.assess_network_congestion().await,
             self.assess_competition_level().await,
         ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
         
         // Calculate total costs
-        let fee_estimation = self.fee_calculator.calculate_dynamic_fees(opportunity.estimated_profit).await?;
+        let fee_estimation = self.dynamic_fees(opportunity, 2, simulation_result.compute_units()).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
         
         // Check if net profit after all costs is still profitable
         let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
         let net_profit = opportunity.estimated_profit - total_costs;
         
-        if net_profit < self.min_sandwich_profit {
-            Logger::status_update(&format!("Sandwich net profit {:.6} SOL below minimum threshold {:.6} SOL", net_profit, self.min_sandwich_profit));
+        let calibrated_net_profit = self.profit_calibration.calibrated_profit(opportunity).await - total_costs;
+        let min_profit = self.competitive_min_profit(self.thresholds.min_sandwich_profit).await;
+        if calibrated_net_profit < min_profit {
+            Logger::status_update(&format!("Sandwich calibrated net profit {:.6} SOL below minimum threshold {:.6} SOL", calibrated_net_profit, min_profit));
             return Ok(MevStrategyResult {
                 success: false,
                 profit: 0.0,
                 fees_paid: total_costs - tip_result.optimal_tip,
                 tip_paid: tip_result.optimal_tip,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Sandwich,
             });
         }
         
         // Create sandwich bundle: [frontrun, target, backrun]
         let sandwich_transactions = self.create_sandwich_bundle(
-            &opportunity.token_a,
-            &opportunity.token_b,
-            opportunity.trade_size,
+            opportunity,
+            &front,
             target_details
         ).await?;
+        phases.finish(ExecutionPhase::Build);
         
         // Submit via Jito with proper timing
-        let execution_result = self.submit_sandwich_bundle(&sandwich_transactions, &tip_result).await;
+        let execution_result = self.submit_sandwich_bundle(&sandwich_transactions, &tip_result, opportunity, target_tx_details, simulation_result.compute_units()).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
         
         match execution_result {
-            Ok(signature) => {
-                Logger::status_update(&format!("Sandwich execution successful: {}", signature));
+            Ok(submission) => {
+                Logger::status_update(&format!("Sandwich execution successful: {}", submission.bundle_id));
                 
-                // Record successful tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, true).await;
+                // Check later whether someone else was wrapped around the same target
+                if let (Some(target_signature), Some(pool_address)) = (target_details["transaction"]["signatures"][0].as_str(), &opportunity.pool_address) {
+                    self.competition.check_target(target_signature.to_string(), pool_address.clone());
+                }
                 
                 Ok(MevStrategyResult {
-                    success: true,
-                    profit: net_profit,
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
-                    tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
                     strategy_type: MevStrategyType::Sandwich,
                 })
             },
             Err(e) => {
                 Logger::error_occurred(&format!("Sandwich execution failed: {}", e));
                 
-                // Record failed tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, false).await;
-                
                 Ok(MevStrategyResult {
                     success: false,
                     profit: 0.0,
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
                     tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: submission_skip_reason(&*e),
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
                     strategy_type: MevStrategyType::Sandwich,
                 })
             }
@@ -312,23 +1188,18 @@ impl MevStrategyExecutor {
     async fn execute_frontrun_strategy(
         &self,
         opportunity: &OpportunityDetails,
-        target_tx_details: Option<&Value>
+        target_tx_details: Option<&Value>,
+        timing: &mut OpportunityTiming,
     ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Executing frontrun strategy");
+        let mut phases = PhaseTimer::start();
         
-        // If target details exist, analyze them to replicate the trade
-        let target_trade_size = if let Some(details) = target_tx_details {
-            self.extract_target_trade_size(details).await?
-        } else {
-            opportunity.trade_size
-        };
-        
-        // Run simulation for the frontrun
+        // Run simulation for the frontrun at the size the evaluator gave the opportunity
         let mut frontrun_opportunity = opportunity.clone();
-        frontrun_opportunity.trade_size = target_trade_size;
         frontrun_opportunity.opportunity_type = OpportunityType::Frontrun;
         
-        let simulation_result = self.simulation_pipeline.run_bundle_simulation(&frontrun_opportunity).await?;
+        let simulation_result = self.simulate(&frontrun_opportunity, None, timing).await?;
+        phases.finish(ExecutionPhase::Simulation);
         
         if !simulation_result.is_profitable {
             Logger::status_update("Frontrun simulation failed profitability check");
@@ -337,7 +1208,12 @@ impl MevStrategyExecutor {
                 profit: 0.0,
                 fees_paid: 0.0,
                 tip_paid: 0.0,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Frontrun,
             });
         }
@@ -348,79 +1224,203 @@ impl MevStrategyExecutor {
             self.assess_network_congestion().await,
             self.assess_competition_level().await,
         ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
         
         // Calculate total costs
-        let fee_estimation = self.fee_calculator.calculate_dynamic_fees(opportunity.estimated_profit).await?;
+        let fee_estimation = self.dynamic_fees(opportunity, 1, simulation_result.compute_units()).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
         
         // Check if net profit after all costs is still profitable
         let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
         let net_profit = opportunity.estimated_profit - total_costs;
         
-        if net_profit < self.min_arbitrage_profit { // Use arbitrage minimum for frontrun
-            Logger::status_update(&format!("Frontrun net profit {:.6} SOL below minimum threshold", self.min_arbitrage_profit));
+        let calibrated_net_profit = self.profit_calibration.calibrated_profit(opportunity).await - total_costs;
+        let min_profit = self.competitive_min_profit(self.thresholds.min_frontrun_profit).await;
+        if calibrated_net_profit < min_profit {
+            Logger::status_update(&format!("Frontrun calibrated net profit {:.6} SOL below minimum threshold {:.6} SOL", calibrated_net_profit, min_profit));
             return Ok(MevStrategyResult {
                 success: false,
                 profit: 0.0,
                 fees_paid: total_costs - tip_result.optimal_tip,
                 tip_paid: tip_result.optimal_tip,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Frontrun,
             });
         }
         
         // Create frontrun transaction
-        let frontrun_transaction = self.create_frontrun_transaction(
-            &opportunity.token_a,
-            &opportunity.token_b,
-            target_trade_size
-        ).await?;
+        let frontrun_leg = self.quoted_pool_leg(opportunity, opportunity.trade_size).await?;
+        let frontrun_transaction = self.create_leg_transaction(&[frontrun_leg]).await?;
+        phases.finish(ExecutionPhase::Build);
         
         // Submit via Jito
-        let execution_result = self.submit_via_jito(&vec![frontrun_transaction], &tip_result).await;
+        let execution_result = self.submit_via_jito(&[frontrun_transaction], &tip_result, opportunity, target_tx_details, simulation_result.compute_units()).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
         
         match execution_result {
-            Ok(signature) => {
-                Logger::status_update(&format!("Frontrun execution successful: {}", signature));
-                
-                // Record successful tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, true).await;
+            Ok(submission) => {
+                Logger::status_update(&format!("Frontrun execution successful: {}", submission.bundle_id));
                 
                 Ok(MevStrategyResult {
-                    success: true,
-                    profit: net_profit,
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
-                    tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
                     strategy_type: MevStrategyType::Frontrun,
                 })
             },
             Err(e) => {
                 Logger::error_occurred(&format!("Frontrun execution failed: {}", e));
                 
-                // Record failed tip result
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, false).await;
-                
                 Ok(MevStrategyResult {
                     success: false,
                     profit: 0.0,
                     fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
                     tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: submission_skip_reason(&*e),
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
                     strategy_type: MevStrategyType::Frontrun,
                 })
             }
         }
     }
     
-    async fn execute_generic_strategy(
+    async fn execute_snipe_strategy(
         &self,
         opportunity: &OpportunityDetails,
-        target_tx_details: Option<&Value>
+        timing: &mut OpportunityTiming,
     ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
-        Logger::status_update("Executing generic strategy");
-        
-        // For other opportunity types, use a generic approach
-        let simulation_result = self.simulation_pipeline.run_bundle_simulation(opportunity).await?;
+        Logger::status_update("Executing snipe strategy");
+        let mut phases = PhaseTimer::start();
+        
+        let pool_address = match opportunity.pool_address {
+            Some(ref address) => address.clone(),
+            None => {
+                Logger::status_update("Snipe opportunity has no pool address");
+                return Ok(MevStrategyResult {
+                    success: false,
+                    profit: 0.0,
+                    fees_paid: 0.0,
+                    tip_paid: 0.0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
+                    strategy_type: MevStrategyType::Snipe,
+                });
+            }
+        };
+        
+        // Snipes are a race for the first block after pool creation, so skip the bundle simulation
+        // (there is no prior pool state to simulate against) and go straight to tip/fee sizing
+        let tip_result = self.jito_optimizer.calculate_optimal_tip(
+            opportunity.estimated_profit,
+            self.assess_network_congestion().await,
+            self.assess_competition_level().await,
+        ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
+        
+        let fee_estimation = self.dynamic_fees(opportunity, 1, None).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
+        let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
+        let net_profit = opportunity.estimated_profit - total_costs;
+        let calibrated_profit = self.profit_calibration.calibrated_profit(opportunity).await;
+        
+        if calibrated_profit - total_costs <= 0.0 {
+            Logger::status_update(&format!("Snipe calibrated profit {:.6} SOL does not cover costs {:.6} SOL", calibrated_profit, total_costs));
+            return Ok(MevStrategyResult {
+                success: false,
+                profit: 0.0,
+                fees_paid: fee_estimation.total_execution_cost,
+                tip_paid: tip_result.optimal_tip,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
+                strategy_type: MevStrategyType::Snipe,
+            });
+        }
+        
+        // Buy the new token with the configured quote amount
+        // No minimum out: the new pool's price is set entirely by the creator's initial deposit
+        let buy_leg = self.pool_leg(opportunity, &opportunity.token_a, &opportunity.token_b, opportunity.trade_size, 0)?;
+        let buy_transaction = self.create_leg_transaction(&[buy_leg]).await?;
+        phases.finish(ExecutionPhase::Build);
+        
+        let execution_result = self.submit_via_jito(&[buy_transaction], &tip_result, opportunity, None, None).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
+        
+        match execution_result {
+            Ok(submission) => {
+                Logger::status_update(&format!("Snipe execution successful on pool {}: {}", pool_address, submission.bundle_id));
+                
+                Ok(MevStrategyResult {
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
+                    fees_paid: fee_estimation.total_execution_cost,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
+                    strategy_type: MevStrategyType::Snipe,
+                })
+            },
+            Err(e) => {
+                Logger::error_occurred(&format!("Snipe execution failed on pool {}: {}", pool_address, e));
+                
+                Ok(MevStrategyResult {
+                    success: false,
+                    profit: 0.0,
+                    fees_paid: fee_estimation.total_execution_cost,
+                    tip_paid: tip_result.optimal_tip,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: submission_skip_reason(&*e),
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
+                    strategy_type: MevStrategyType::Snipe,
+                })
+            }
+        }
+    }
+    
+    async fn execute_generic_strategy(
+        &self,
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+        timing: &mut OpportunityTiming,
+    ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
+        Logger::status_update("Executing generic strategy");
+        let mut phases = PhaseTimer::start();
+        
+        // For other opportunity types, use a generic approach
+        let simulation_result = self.simulate(opportunity, None, timing).await?;
+        phases.finish(ExecutionPhase::Simulation);
         
         if !simulation_result.is_profitable {
             Logger::status_update("Generic strategy simulation failed profitability check");
@@ -429,7 +1429,12 @@ impl MevStrategyExecutor {
                 profit: 0.0,
                 fees_paid: 0.0,
                 tip_paid: 0.0,
-                execution_time_ms: 0,
+                execution_time_ms: phases.elapsed_ms(),
+                phases: phases.recorded(),
+                skip_reason: None,
+                route: None,
+                bundle_id: None,
+                pending: false,
                 strategy_type: MevStrategyType::Other,
             });
         }
@@ -440,44 +1445,59 @@ impl MevStrategyExecutor {
             self.assess_network_congestion().await,
             self.assess_competition_level().await,
         ).await?;
+        phases.finish(ExecutionPhase::TipCalculation);
         
-        let fee_estimation = self.fee_calculator.calculate_dynamic_fees(opportunity.estimated_profit).await?;
+        let fee_estimation = self.dynamic_fees(opportunity, 1, simulation_result.compute_units()).await?;
+        phases.finish(ExecutionPhase::FeeCalculation);
         
         let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
         let net_profit = opportunity.estimated_profit - total_costs;
         
         // Create generic transaction based on opportunity
-        let transaction = self.create_generic_transaction(opportunity).await?;
+        if matches!(opportunity.opportunity_type, OpportunityType::Liquidation) {
+            return Err("Liquidations are only executed by the liquidation build".into());
+        }
+        let leg = self.quoted_pool_leg(opportunity, opportunity.trade_size).await?;
+        let transaction = self.create_leg_transaction(&[leg]).await?;
+        phases.finish(ExecutionPhase::Build);
         
         // Submit via Jito
-        let execution_result = self.submit_via_jito(&vec![transaction], &tip_result).await;
+        let execution_result = self.submit_via_jito(&[transaction], &tip_result, opportunity, target_tx_details, simulation_result.compute_units()).await;
+        timing.mark_submitted();
+        phases.finish(ExecutionPhase::Submit);
         
         match execution_result {
-            Ok(signature) => {
-                Logger::status_update(&format!("Generic strategy execution successful: {}", signature));
-                
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, true).await;
+            Ok(submission) => {
+                Logger::status_update(&format!("Generic strategy execution successful: {}", submission.bundle_id));
                 
                 Ok(MevStrategyResult {
-                    success: true,
-                    profit: net_profit,
-                    fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
-                    tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    success: !submission.pending,
+                    profit: net_profit - (submission.tip_paid - tip_result.optimal_tip),
+                    fees_paid: fee_estimation.total_execution_cost,
+                    tip_paid: submission.tip_paid,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: None,
+                    route: Some(submission.route),
+                    bundle_id: Some(submission.bundle_id),
+                    pending: submission.pending,
                     strategy_type: MevStrategyType::Other,
                 })
             },
             Err(e) => {
                 Logger::error_occurred(&format!("Generic strategy execution failed: {}", e));
                 
-                self.jito_optimizer.record_tip_result(tip_result.optimal_tip, false).await;
-                
                 Ok(MevStrategyResult {
                     success: false,
                     profit: 0.0,
-                    fees_paid: fee_estimation.total_execution_cost - tip_result.optimal_tip,
+                    fees_paid: fee_estimation.total_execution_cost,
                     tip_paid: tip_result.optimal_tip,
-                    execution_time_ms: 0,
+                    execution_time_ms: phases.elapsed_ms(),
+                    phases: phases.recorded(),
+                    skip_reason: submission_skip_reason(&*e),
+                    route: None,
+                    bundle_id: None,
+                    pending: false,
                     strategy_type: MevStrategyType::Other,
                 })
             }
@@ -486,349 +1506,465 @@ impl MevStrategyExecutor {
     
     async fn create_arbitrage_bundle(
         &self,
-        token_a: &str,
-        token_b: &str,
-        trade_size: u64
+        opportunity: &OpportunityDetails
     ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        // Create arbitrage bundle: buy on DEX1, sell on DEX2
-        let mut transactions = Vec::new();
-        
-        // Get best routes on different DEXes
-        let dex1_route = self.opportunity_evaluator.get_best_swap_route(token_a, token_b, trade_size).await?;
-        let dex2_route = self.opportunity_evaluator.get_best_swap_route(token_b, token_a, trade_size).await?;
-        
-        if let (Some(route1), Some(route2)) = (dex1_route, dex2_route) {
-            // Create transactions for the arbitrage
-            let buy_transaction = self.create_swap_transaction(
-                token_a,
-                token_b,
-                route1.input_amount
-            ).await?;
-            
-            let sell_transaction = self.create_swap_transaction(
-                token_b,
-                token_a,
-                route2.input_amount
-            ).await?;
-            
-            transactions.push(buy_transaction);
-            transactions.push(sell_transaction);
+        // Routes from the route finder carry every leg; the bundle lands all of them or none
+        if !opportunity.route.is_empty() {
+            let legs = chain_route(&opportunity.route, self.thresholds.max_slippage_percent())
+                .ok_or("Arbitrage route legs don't connect")?;
+            // Orderbook legs go out as IOC orders, which only the orderbook build sends
+            if !cfg!(feature = "orderbook") && legs.iter().any(|leg| leg.dex == "Phoenix") {
+                return Err("Route has a Phoenix leg; build with the orderbook feature to execute it".into());
+            }
+            let mut transactions = Vec::new();
+            for group in pack_route_legs(&legs) {
+                transactions.push(self.create_leg_transaction(group).await?);
+            }
+            return Ok(transactions);
         }
         
-        Ok(transactions)
+        // A quote alone doesn't say which pools to trade on
+        Err("Arbitrage opportunity has no quoted route to execute".into())
     }
     
     async fn create_sandwich_bundle(
         &self,
-        token_a: &str,
-        token_b: &str,
-        trade_size: u64,
+        opportunity: &OpportunityDetails,
+        front: &SandwichFront,
         target_details: &Value
     ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        // Create sandwich bundle: [frontrun, target, backrun]
-        let mut bundle = Vec::new();
+        let (token_a, token_b) = (&opportunity.token_a, &opportunity.token_b);
         
-        // Create frontrun transaction (same trade as target but larger)
-        let frontrun_tx = self.create_frontrun_transaction(
-            token_a,
-            token_b,
-            trade_size
-        ).await?;
-        
-        // Create backrun transaction (opposite of frontrun)
-        let backrun_tx = self.create_backrun_transaction(
-            token_b,
-            token_a,
-            trade_size
-        ).await?;
+        // Create frontrun transaction (same direction as the target). It must buy at least what
+        // the backrun sells
+        let frontrun_leg = self.pool_leg(opportunity, token_a, token_b, front.front_in, front.front_out)?;
+        let frontrun_tx = self.create_leg_transaction(&[frontrun_leg]).await?;
         
-        bundle.push(frontrun_tx);
-        // Target transaction would be inserted in the middle by the block builder
-        bundle.push(backrun_tx);
+        // Sell what the frontrun bought; accept no worse than our slippage cap off the expected
+        // output at the post-victim price
+        let min_out = (front.expected_back_out as f64 * (1.0 - self.thresholds.max_slippage_percent())) as u64;
+        let backrun_leg = self.pool_leg(opportunity, token_b, token_a, front.front_out, min_out)?;
+        let backrun_tx = self.create_leg_transaction(&[backrun_leg]).await?;
         
-        Ok(bundle)
+        sandwich_bundle(frontrun_tx, target_details, backrun_tx)
+            .ok_or_else(|| "Target transaction isn't available signed to sandwich".into())
     }
     
-    async fn create_frontrun_transaction(
+    // A swap on the opportunity's own pool
+    fn pool_leg(
         &self,
+        opportunity: &OpportunityDetails,
         input_token: &str,
         output_token: &str,
-        trade_size: u64
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a transaction that mimics the target trade but executes first
-        // This would be implemented using Solana SDK in a real implementation
-        
-        // In a real implementation, this would create a proper swap instruction
-        Ok(format!("frontrun_{}_to_{}_{}", input_token, output_token, trade_size))
+        amount_in: u64,
+        min_out: u64
+    ) -> Result<RouteLeg, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_address = opportunity.pool_address.clone().ok_or("Opportunity has no pool to trade on")?;
+        Ok(RouteLeg {
+            dex: opportunity.dex.clone(),
+            pool_address,
+            input_token: input_token.to_string(),
+            output_token: output_token.to_string(),
+            amount_in,
+            expected_out: 0,
+            min_out,
+        })
     }
     
-    async fn create_backrun_transaction(
+    // token_a -> token_b on the opportunity's pool, quoted against its current state and failing
+    // past our slippage cap
+    async fn quoted_pool_leg(
         &self,
-        input_token: &str,
-        output_token: &str,
-        trade_size: u64
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a transaction that reverses the frontrun position
-        // This would be implemented using Solana SDK in a real implementation
-        
-        Ok(format!("backrun_{}_to_{}_{}", input_token, output_token, trade_size))
+        opportunity: &OpportunityDetails,
+        amount_in: u64
+    ) -> Result<RouteLeg, Box<dyn std::error::Error + Send + Sync>> {
+        let leg = self.pool_leg(opportunity, &opportunity.token_a, &opportunity.token_b, amount_in, 0)?;
+        let pool = self.opportunity_evaluator.get_pool_state(&leg.pool_address, &leg.dex).await?
+            .ok_or_else(|| format!("No state for pool {}", leg.pool_address))?;
+        let quote = pool.quote(&leg.input_token, amount_in)
+            .ok_or_else(|| format!("Pool {} doesn't trade {}", leg.pool_address, leg.input_token))?;
+        let min_out = (quote.output_amount as f64 * (1.0 - self.thresholds.max_slippage_percent())) as u64;
+        Ok(RouteLeg { expected_out: quote.output_amount, min_out, ..leg })
     }
     
-    async fn create_swap_transaction(
+    async fn create_priority_transaction(
         &self,
-        input_token: &str,
-        output_token: &str,
-        amount: u64
+        transaction: &str,
+        fee_estimation: &FeeEstimation
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a swap transaction
-        Ok(format!("swap_{}_to_{}_{}", input_token, output_token, amount))
+        // SetComputeUnitLimit and SetComputeUnitPrice replace whatever budget it had, and it's
+        // re-signed under a fresh blockhash
+        let signer = self.simulation_pipeline.signer()?;
+        let blockhash = self.latest_blockhash().await?;
+        dex_swap_instructions::with_compute_budget(
+            transaction,
+            fee_estimation.compute_unit_limit,
+            fee_estimation.compute_unit_price,
+            &signer,
+            &blockhash,
+        )
     }
     
-    async fn create_generic_transaction(
+    // The legs' swap instructions in one transaction signed by our keypair (base58)
+    async fn create_leg_transaction(
         &self,
-        opportunity: &OpportunityDetails
+        legs: &[RouteLeg]
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Create a generic transaction based on opportunity type
-        match opportunity.opportunity_type {
-            OpportunityType::Liquidation => {
-                // Create liquidation transaction
-                Ok(format!("liquidation_{}_{}", opportunity.token_a, opportunity.token_b))
-            },
-            _ => {
-                // Default to a basic swap transaction
-                self.create_swap_transaction(
-                    &opportunity.token_a,
-                    &opportunity.token_b,
-                    opportunity.trade_size
-                ).await
-            }
-        }
+        self.simulation_pipeline.create_leg_transaction(legs).await
     }
     
+    // Sends the bundle and watches for it to land. A bundle that expires unlanded is rebuilt with a
+    // fresh blockhash and a bumped tip while the opportunity still holds up and the tip stays within
+    // budget; every attempt's tip and outcome goes to the tip model. `compute_units` is what the
+    // transactions consumed in simulation, for sizing the RPC fallback's compute budget
     async fn submit_via_jito(
         &self,
         transactions: &[String],
-        tip_result: &TipOptimizationResult
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Prepare bundle with tip transaction
-        let bundle_transactions = self.jito_optimizer.prepare_bundle_for_submission(
-            transactions.to_vec(),
+        tip_result: &TipOptimizationResult,
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+        compute_units: Option<u64>,
+    ) -> Result<BundleSubmission, Box<dyn std::error::Error + Send + Sync>> {
+        let target_signature = target_signature_of(target_tx_details);
+        let mut tips = vec![tip_result.optimal_tip];
+        tips.extend(escalated_tips(
             tip_result.optimal_tip,
-            &tip_result.recommended_tip_account
-        ).await?;
+            self.bundle_retry.tip_multiplier,
+            self.bundle_retry.tip_budget(opportunity.estimated_profit),
+            self.bundle_retry.max_retries,
+        ));
         
-        // Get Jito client and submit bundle
-        if let Ok(jito_client) = self.get_jito_client().await {
+        for (attempt, &tip) in tips.iter().enumerate() {
+            let attempts = attempt as u32 + 1;
+            // Escalated retries are past the floor percentile, so only the first attempt calibrates it
+            let percentile = if attempt == 0 { tip_result.percentile } else { None };
+            if attempt > 0 && !self.opportunity_still_valid(opportunity, target_tx_details).await {
+                return Err(format!("Opportunity no longer valid after {} unlanded attempts", attempt).into());
+            }
+            
+            // Prepare bundle with tip transaction; each attempt gets a fresh blockhash so a retry
+            // isn't dropped as expired
+            let blockhash = self.latest_blockhash().await?;
+            let bundle_transactions = self.jito_optimizer.prepare_bundle_for_submission(
+                transactions.to_vec(),
+                tip,
+                &tip_result.recommended_tip_account,
+                &blockhash
+            ).await?;
+            
+            // A revert anywhere in the sequence sinks the whole bundle; find out before sending it
+            if self.simulate_bundles {
+                let simulation = self.simulation_pipeline.simulate_bundle(&bundle_transactions).await?;
+                if let Some(failure) = simulation.failure {
+                    Logger::status_update(&format!("{}; not submitting", failure));
+                    return Err(failure.into());
+                }
+            }
+            
+            // Stop short of sending; a bundle that never went out says nothing about congestion or competition
+            if self.dry_run {
+                let bundle_id = dry_run::synthetic_signature(&bundle_transactions);
+                Logger::status_update(&format!("DRY RUN: bundle of {} transactions not submitted ({})", bundle_transactions.len(), bundle_id));
+                return Ok(BundleSubmission { bundle_id, tip_paid: tip, attempts, route: SubmissionRoute::Jito, pending: false });
+            }
+            
+            let jito_client = &self.jito_client;
+            
             // Apply bundle timing strategy
             let timing_strategy = self.jito_optimizer.get_bundle_timing_strategy().await;
             
+            // No Jito leader due before the bundle goes stale: a priority-fee transaction can still land.
+            // Sandwiches and other bundle-only strategies have no such fallback and go out regardless
+            if timing_strategy.prefer_rpc && self.rpc_fallback && rpc_fallback_transaction(&opportunity.opportunity_type, transactions).is_some() {
+                return self.submit_via_rpc(transactions, opportunity, target_tx_details, compute_units, "No Jito leader within the submission window".into()).await;
+            }
+            
             // Implement timing delays
             self.jito_optimizer.implement_micro_delay(&timing_strategy).await;
             
-            // Submit the bundle
-            let signature = jito_client.send_bundle(&bundle_transactions).await?;
-            Ok(signature)
-        } else {
-            Err("Could not create Jito client".into())
+            // Submit the bundle; how long acceptance takes feeds the congestion score
+            let sent_at = Instant::now();
+            let result = jito_client.send_bundle(&bundle_transactions).await;
+            self.network_congestion.record_send_latency(sent_at.elapsed().as_millis() as u64).await;
+            let bundle_id = match result {
+                Ok(bundle_id) => bundle_id,
+                Err(e) => {
+                    // Rejected bundles count as lost to competition; throttled ones never reached the auction
+                    if jito_rate_limit::throttle_skip_reason(&e.to_string()).is_none() {
+                        self.jito_optimizer.record_tip_result(tip, percentile, false).await;
+                        self.jito_optimizer.record_leader_outcome(timing_strategy.leader, false).await;
+                        self.competition.record_bundle(false).await;
+                    }
+                    if JitoClient::is_retryable_error(&*e) {
+                        return self.submit_via_rpc(transactions, opportunity, target_tx_details, compute_units, e).await;
+                    }
+                    return Err(e);
+                }
+            };
+            
+            // Without a signature to look up, the block engine's bundle statuses tell us later whether
+            // it landed; the tip model, competition score and the books hear about it then
+            // Out of our hands now; other opportunities on the pool needn't wait for it to land
+            self.pool_locks.release(opportunity.pool_address.as_deref(), target_signature);
+            
+            let Some(signature) = bundle_transactions.first().and_then(|tx| transaction_signature(tx)) else {
+                self.bundle_tracker.track(&bundle_id, MevStrategyType::for_opportunity(&opportunity.opportunity_type), tip, percentile, timing_strategy.leader).await;
+                return Ok(BundleSubmission { bundle_id, tip_paid: tip, attempts, route: SubmissionRoute::Jito, pending: true });
+            };
+            
+            let outcome = self.watch_landing(&signature, target_signature).await;
+            self.jito_optimizer.record_tip_result(tip, percentile, outcome == BundleOutcome::Landed).await;
+            self.jito_optimizer.record_leader_outcome(timing_strategy.leader, outcome == BundleOutcome::Landed).await;
+            self.competition.record_bundle(outcome == BundleOutcome::Landed).await;
+            match outcome {
+                BundleOutcome::Landed => return Ok(BundleSubmission { bundle_id, tip_paid: tip, attempts, route: SubmissionRoute::Jito, pending: false }),
+                BundleOutcome::Failed => return Err(format!("Bundle {} landed but its transaction failed", bundle_id).into()),
+                BundleOutcome::TargetConfirmed => return Err(format!("Target confirmed before bundle {} landed", bundle_id).into()),
+                BundleOutcome::Expired => Logger::status_update(&format!(
+                    "Bundle {} didn't land within {} slots with a {:.6} SOL tip (attempt {}/{})",
+                    bundle_id, self.bundle_retry.landing_slots, tip, attempts, tips.len()
+                )),
+            }
         }
+        
+        Err(format!("Bundle didn't land after {} attempts", tips.len()).into())
     }
     
-    async fn submit_sandwich_bundle(
+    // Fallback when Jito is unconfigured or unreachable: the main transaction gets a competitive
+    // compute-unit price and a limit sized from its simulation, and goes out through
+    // sendTransaction. Returns the Jito error when the fallback is off or the opportunity can't go
+    // without a bundle
+    async fn submit_via_rpc(
         &self,
         transactions: &[String],
-        tip_result: &TipOptimizationResult
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Submit sandwich bundle with special timing considerations
-        self.submit_via_jito(transactions, tip_result).await
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+        compute_units: Option<u64>,
+        jito_error: Box<dyn std::error::Error + Send + Sync>,
+    ) -> Result<BundleSubmission, Box<dyn std::error::Error + Send + Sync>> {
+        if !self.rpc_fallback {
+            return Err(jito_error);
+        }
+        let Some(transaction) = rpc_fallback_transaction(&opportunity.opportunity_type, transactions) else {
+            Logger::status_update(&format!("Not falling back to RPC for {:?}: needs bundle atomicity", opportunity.opportunity_type));
+            return Err(jito_error);
+        };
+        Logger::status_update(&format!("Jito submission failed ({}), falling back to standard RPC", jito_error));
+        
+        let fee_estimation = self.dynamic_fees(opportunity, 1, compute_units).await?;
+        let prioritized = self.create_priority_transaction(transaction, &fee_estimation).await?;
+        let signature = self.rpc_manager.send_transaction(&prioritized).await?;
+        self.pool_locks.release(opportunity.pool_address.as_deref(), target_signature_of(target_tx_details));
+        
+        // Only the first check of the watch matters here: there is no tip to bump. A backrun
+        // wants its target confirmed first, so only its own landing is watched
+        let target_signature = match opportunity.opportunity_type {
+            OpportunityType::Backrun => None,
+            _ => target_signature_of(target_tx_details),
+        };
+        match self.watch_landing(&signature, target_signature).await {
+            BundleOutcome::Landed => Ok(BundleSubmission { bundle_id: signature, tip_paid: 0.0, attempts: 1, route: SubmissionRoute::StandardRpc, pending: false }),
+            outcome => Err(format!("RPC fallback transaction {} didn't land: {:?}", signature, outcome).into()),
+        }
     }
     
-    async fn get_jito_client(&self) -> Result<crate::utils::jito::JitoClient, Box<dyn std::error::Error>> {
-        match crate::utils::jito::JitoClient::new() {
-            Some(client) => Ok(client),
-            None => Err("Jito client not configured".into()),
+    // Polls our first transaction, and the target when there is one, once per slot
+    async fn watch_landing(&self, signature: &str, target_signature: Option<&str>) -> BundleOutcome {
+        let mut signatures = vec![signature.to_string()];
+        signatures.extend(target_signature.map(str::to_string));
+        
+        for _ in 0..self.bundle_retry.landing_slots {
+            tokio::time::sleep(Duration::from_millis(SLOT_MS)).await;
+            let statuses = match self.rpc_manager.get_signature_statuses(&signatures).await {
+                Ok(statuses) => statuses,
+                Err(e) => {
+                    Logger::status_update(&format!("Could not poll bundle status: {}", e));
+                    continue;
+                }
+            };
+            match landing_status(&statuses, 0) {
+                LandingStatus::Landed => return BundleOutcome::Landed,
+                LandingStatus::Failed => return BundleOutcome::Failed,
+                LandingStatus::Pending => {}
+            }
+            // Once the target is in, there is nothing left to land in front of or behind
+            if target_signature.is_some() && landing_status(&statuses, 1) != LandingStatus::Pending {
+                return BundleOutcome::TargetConfirmed;
+            }
         }
+        BundleOutcome::Expired
     }
     
-    async fn extract_target_trade_size(&self, target_details: &Value) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        // Extract the trade size from target transaction details
-        // This would analyze the transaction to determine the amount being swapped
-        
-        // For now, return a placeholder
-        // In a real implementation, this would decode the swap instruction
-        Ok(1_000_000) // 1 million lamports as placeholder
+    // Before paying more for the same opportunity: a target-driven one must still evaluate as an
+    // opportunity, anything else must still simulate profitably
+    async fn opportunity_still_valid(&self, opportunity: &OpportunityDetails, target_tx_details: Option<&Value>) -> bool {
+        match target_tx_details {
+            Some(details) => matches!(self.opportunity_evaluator.evaluate_opportunity(details).await, Ok(Some(_))),
+            None => self.simulation_pipeline.run_bundle_simulation(opportunity, None).await
+                .is_ok_and(|simulation| simulation.is_profitable),
+        }
+    }
+    
+    async fn latest_blockhash(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.rpc_manager.get_recent_blockhash().await?.blockhash)
+    }
+    
+    async fn submit_sandwich_bundle(
+        &self,
+        transactions: &[String],
+        tip_result: &TipOptimizationResult,
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+        compute_units: Option<u64>,
+    ) -> Result<BundleSubmission, Box<dyn std::error::Error + Send + Sync>> {
+        // Submit sandwich bundle with special timing considerations
+        self.submit_via_jito(transactions, tip_result, opportunity, target_tx_details, compute_units).await
     }
     
     async fn assess_network_congestion(&self) -> f64 {
-        // Assess current network congestion level (0.0 to 1.0)
-        // In a real implementation, this would check mempool size, recent block times, etc.
-        0.5 // Return medium congestion as default
+        self.network_congestion.score().await
     }
     
     async fn assess_competition_level(&self) -> f64 {
-        // Assess current MEV competition level (0.0 to 1.0)
-        // In a real implementation, this would check recent bundle activity, etc.
-        0.6 // Return medium-high competition as default
+        self.competition.score().await
     }
     
-    // Method to optimize frontrun size relative to pool elasticity
-    pub async fn calculate_optimal_frontrun_size(
-        &self,
-        opportunity: &OpportunityDetails
-    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        // Calculate the optimal frontrun size to maximize profit vs slippage
-        
-        let pool_state = self.opportunity_evaluator.get_pool_state(
-            &opportunity.token_a,
-            &opportunity.token_b
-        ).await?;
-        
-        if let Some(pool) = pool_state {
-            // Calculate optimal size based on pool reserves and desired slippage
-            // For simplicity, we'll use 10% of reserve A as a reasonable frontrun size
-            // In practice, this would use more sophisticated curve calculations
-            let optimal_size = (pool.reserve_a as f64 * 0.1) as u64;
-            
-            // Cap at the original trade size to avoid over-front-running
-            Ok(optimal_size.min(opportunity.trade_size * 2)) // Don't exceed 2x the target
-        } else {
-            // If no pool data, use the original trade size
-            Ok(opportunity.trade_size)
+    // Raise the bar when we keep losing to other searchers and lower it when we don't;
+    // a neutral score (0.5) leaves the base threshold unchanged
+    async fn competitive_min_profit(&self, base: f64) -> f64 {
+        base * (0.5 + self.competition.score().await)
+    }
+    
+    // Polls the bundles still waiting on a landing verdict and feeds the verdicts to the tip model,
+    // competition score and strategy streaks; the caller books them into metrics, risk and
+    // analytics. What landed bundles paid goes to the fee models, and they come back scored
+    // against their simulation
+    pub async fn poll_bundle_outcomes(&self) -> Vec<ResolvedBundle> {
+        let mut resolved = self.bundle_tracker.poll().await;
+        for bundle in &mut resolved {
+            self.jito_optimizer.record_tip_result(bundle.tip, bundle.percentile, bundle.landed()).await;
+            self.jito_optimizer.record_leader_outcome(bundle.leader, bundle.landed()).await;
+            self.competition.record_bundle(bundle.landed()).await;
+            self.strategy_manager.write().await.record_bundle_outcome(bundle);
+            if bundle.landed() {
+                self.reconcile_landed_bundle(bundle).await;
+            }
         }
+        resolved
     }
     
-    // Multi-DEX arbitrage logic
-    pub async fn execute_multi_dex_arbitrage(
-        &self,
-        opportunity: &OpportunityDetails
-    ) -> Result<MevStrategyResult, Box<dyn std::error::Error + Send + Sync>> {
-        Logger::status_update("Executing multi-DEX arbitrage");
-        
-        // Find best route across multiple DEXes
-        let best_routes = self.find_arbitrage_routes(&opportunity.token_a, &opportunity.token_b).await?;
-        
-        if best_routes.len() < 2 {
-            Logger::status_update("Not enough DEX routes for profitable arbitrage");
-            return Ok(MevStrategyResult {
-                success: false,
-                profit: 0.0,
-                fees_paid: 0.0,
-                tip_paid: 0.0,
-                execution_time_ms: 0,
-                strategy_type: MevStrategyType::Arbitrage,
-            });
+    async fn reconcile_landed_bundle(&self, bundle: &mut ResolvedBundle) {
+        let Ok(wallet) = self.simulation_pipeline.wallet() else { return };
+        let Some(transactions) = self.landed_transactions(bundle).await else { return };
+        let realized = simulation_effects::realized_effects(&transactions, &wallet);
+        
+        // Only the transactions we paid for; a sandwich's victim paid its own
+        let legs = transactions.iter()
+            .filter(|transaction| transaction.transaction.message.account_keys.first() == Some(&wallet))
+            .count();
+        self.fee_calculator.record_realized_fees(&bundle.strategy_type, legs, realized.fees_paid).await;
+        if realized.success {
+            bundle.realized_profit = Some(self.simulation_pipeline.realized_net_profit(&realized).await);
         }
         
-        // Calculate the arbitrage opportunity across routes
-        let mut transactions = Vec::new();
-        let mut total_profit = 0.0;
-        
-        // Execute buy on lowest price DEX and sell on highest price DEX
-        if let (Some(lowest_route), Some(highest_route)) = (best_routes.first(), best_routes.last()) {
-            if lowest_route.output_amount < highest_route.output_amount {
-                // Calculate actual profit considering transaction costs
-                let raw_profit = (highest_route.output_amount as f64 - lowest_route.input_amount as f64) / 1_000_000_000.0;
-                
-                // Calculate costs for this arbitrage
-                let tip_result = self.jito_optimizer.calculate_optimal_tip(
-                    raw_profit,
-                    self.assess_network_congestion().await,
-                    self.assess_competition_level().await,
-                ).await?;
-                
-                let fee_estimation = self.fee_calculator.calculate_dynamic_fees(raw_profit).await?;
-                let total_costs = fee_estimation.total_execution_cost + tip_result.optimal_tip;
-                let net_profit = raw_profit - total_costs;
-                
-                if net_profit > self.min_arbitrage_profit {
-                    // Create transactions for the arbitrage
-                    let buy_tx = self.create_swap_transaction(
-                        &opportunity.token_a,
-                        &opportunity.token_b,
-                        lowest_route.input_amount
-                    ).await?;
-                    
-                    let sell_tx = self.create_swap_transaction(
-                        &opportunity.token_b,
-                        &opportunity.token_a,
-                        highest_route.input_amount
-                    ).await?;
-                    
-                    transactions.push(buy_tx);
-                    transactions.push(sell_tx);
-                    
-                    total_profit = net_profit;
-                }
+        // How closely the bundle's simulation predicted what its transactions did to the wallet
+        if let Some(ref simulation) = bundle.simulation {
+            match self.simulation_pipeline.compare_simulation_to_actual(simulation, &realized).await {
+                Ok(accuracy) => bundle.simulation_accuracy = Some(accuracy),
+                Err(e) => Logger::error_occurred(&format!("Could not score the simulation of bundle {}: {}", bundle.bundle_id, e)),
             }
         }
-        
-        if transactions.is_empty() || total_profit <= 0.0 {
-            return Ok(MevStrategyResult {
-                success: false,
-                profit: 0.0,
-                fees_paid: 0.0,
-                tip_paid: 0.0,
-                execution_time_ms: 0,
-                strategy_type: MevStrategyType::Arbitrage,
-            });
+    }
+    
+    // The bundle's transactions as they landed; None when any of them can't be fetched
+    async fn landed_transactions(&self, bundle: &ResolvedBundle) -> Option<Vec<EncodedTransaction>> {
+        if bundle.transactions.is_empty() {
+            return None;
         }
-        
-        // Submit arbitrage bundle
-        let execution_result = self.submit_via_jito(&transactions, &TipOptimizationResult {
-            optimal_tip: total_profit * 0.1, // Use 10% of profit as tip as a baseline
-            recommended_tip_account: self.jito_optimizer.select_best_tip_account().await,
-            confidence: 0.8,
-            expected_success_rate: 0.85,
-        }).await;
-        
-        match execution_result {
-            Ok(signature) => {
-                Logger::status_update(&format!("Multi-DEX arbitrage successful: {}", signature));
-                
-                Ok(MevStrategyResult {
-                    success: true,
-                    profit: total_profit,
-                    fees_paid: total_profit * 0.9, // Placeholder
-                    tip_paid: total_profit * 0.1, // Placeholder
-                    execution_time_ms: 0,
-                    strategy_type: MevStrategyType::Arbitrage,
-                })
-            },
-            Err(e) => {
-                Logger::error_occurred(&format!("Multi-DEX arbitrage failed: {}", e));
-                
-                Ok(MevStrategyResult {
-                    success: false,
-                    profit: 0.0,
-                    fees_paid: 0.0,
-                    tip_paid: 0.0,
-                    execution_time_ms: 0,
-                    strategy_type: MevStrategyType::Arbitrage,
-                })
+        let mut transactions: Vec<EncodedTransaction> = Vec::with_capacity(bundle.transactions.len());
+        for signature in &bundle.transactions {
+            match self.rpc_manager.get_transaction(signature).await {
+                Ok(Some(transaction)) => transactions.push(transaction),
+                Ok(None) => return None,
+                Err(e) => {
+                    Logger::status_update(&format!("Could not fetch {} of bundle {} to reconcile it: {}", signature, bundle.bundle_id, e));
+                    return None;
+                }
             }
         }
+        Some(transactions)
+    }
+    
+    pub fn bundle_poll_interval(&self) -> Duration {
+        self.bundle_tracker.poll_interval()
+    }
+    
+    // Bundles the Jito rate limiter turned away or the block engine answered with a 429
+    pub fn throttled_bundles(&self) -> u64 {
+        self.jito_client.throttled_count()
+    }
+    
+    // Whether the strategy is sitting out a cooldown after too many failures in a row
+    pub async fn is_strategy_disabled(&self, strategy_type: &MevStrategyType) -> bool {
+        self.strategy_manager.write().await.should_disable_strategy(strategy_type, self.max_strategy_failures)
+    }
+    
+    pub async fn disabled_strategies(&self) -> Vec<MevStrategyType> {
+        self.strategy_manager.read().await.disabled_strategies()
+    }
+    
+    pub fn thresholds(&self) -> &StrategyThresholds {
+        &self.thresholds
+    }
+    
+    pub fn competition_tracker(&self) -> CompetitionTracker {
+        self.competition.clone()
     }
     
-    async fn find_arbitrage_routes(
+    // Frontrun that maximizes the sandwich around the victim's decoded swap, within our slippage
+    // cap, our price impact cap, the position sizer's capital cap and the victim's own minimum
+    // output. None when the pool is unknown or no size profits
+    pub async fn calculate_optimal_frontrun_size(
         &self,
-        token_a: &str,
-        token_b: &str
-    ) -> Result<Vec<crate::utils::opportunity_evaluator::SwapQuote>, Box<dyn std::error::Error + Send + Sync>> {
-        // Find best swap routes across multiple DEXes for arbitrage
-        let mut all_quotes = Vec::new();
-        
-        // Get quotes from various DEXes
-        if let Ok(Some(quote)) = self.opportunity_evaluator.get_best_swap_route(token_a, token_b, 100_000_000).await {
-            all_quotes.push(quote);
+        opportunity: &OpportunityDetails,
+        target_tx_details: Option<&Value>,
+    ) -> Result<Option<SandwichFront>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(pool_address) = &opportunity.pool_address else { return Ok(None) };
+        let Some(pool) = self.opportunity_evaluator.get_pool_state(pool_address, &opportunity.dex).await? else { return Ok(None) };
+        let Some((reserve_in, reserve_out)) = pool.reserves_for_input(&opportunity.token_a) else { return Ok(None) };
+        let Some(side) = SwapSide::selling(&pool, &opportunity.token_a) else { return Ok(None) };
+        
+        let victim_min_out = target_tx_details
+            .and_then(|details| self.opportunity_evaluator.victim_min_amount_out(details, pool_address))
+            .unwrap_or(0);
+        let max_front_in = self.capital_cap(&opportunity.token_a).await?
+            .min(DexApi::max_size_for_impact(&pool, side, self.thresholds.max_price_impact_bps as f64));
+        
+        Ok(amm_math::optimal_sandwich_front(
+            opportunity.trade_size,
+            victim_min_out,
+            reserve_in,
+            reserve_out,
+            pool.fee_rate,
+            max_front_in,
+            self.thresholds.max_slippage_percent(),
+        ))
+    }
+    
+    // Position sizer's cap on our wallet balance, in raw units of the token we'd spend
+    async fn capital_cap(&self, token: &str) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let wallet = self.simulation_pipeline.wallet()?;
+        let balance_sol = mint_info::raw_to_ui(self.rpc_manager.get_balance(&wallet).await?, 9);
+        let cap_sol = self.position_sizer.capital_cap(balance_sol);
+        if token == WSOL_MINT {
+            return Ok(mint_info::ui_to_raw(cap_sol, 9));
         }
         
-        if let Ok(Some(quote)) = self.opportunity_evaluator.get_best_swap_route(token_b, token_a, 100_000_000).await {
-            all_quotes.push(quote);
+        let price = self.opportunity_evaluator.get_token_price(token).await?;
+        if price.price_in_sol.is_nan() || price.price_in_sol <= 0.0 {
+            return Err(format!("No SOL price for {}", token).into());
         }
-        
-        // Sort by output amount to identify best buy/sell opportunities
-        all_quotes.sort_by(|a, b| a.output_amount.cmp(&b.output_amount));
-        
-        Ok(all_quotes)
+        Ok(mint_info::ui_to_raw(cap_sol / price.price_in_sol, self.mint_info.decimals(token).await?))
     }
 }
 
@@ -846,9 +1982,26 @@ pub mod strategy_utils {
         pub avg_fees_paid: f64,
         pub avg_tip_paid: f64,
         pub avg_execution_time_ms: u64,
+        pub consecutive_failures: u32, // Submitted executions that failed (or errored) in a row
+        pub disabled_until: Option<Instant>,
     }
     
     impl StrategyPerformance {
+        fn new(strategy_type: MevStrategyType) -> Self {
+            Self {
+                strategy_type,
+                total_executions: 0,
+                successful_executions: 0,
+                total_profit: 0.0,
+                avg_profit_per_success: 0.0,
+                avg_fees_paid: 0.0,
+                avg_tip_paid: 0.0,
+                avg_execution_time_ms: 0,
+                consecutive_failures: 0,
+                disabled_until: None,
+            }
+        }
+        
         pub fn success_rate(&self) -> f64 {
             if self.total_executions == 0 {
                 0.0
@@ -868,27 +2021,29 @@ pub mod strategy_utils {
     
     pub struct StrategyManager {
         pub performances: std::collections::HashMap<MevStrategyType, StrategyPerformance>,
+        cooldown: Duration, // How long a strategy stays disabled
     }
     
     impl StrategyManager {
-        pub fn new() -> Self {
+        pub fn new(cooldown: Duration) -> Self {
             Self {
                 performances: std::collections::HashMap::new(),
+                cooldown,
             }
         }
         
         pub fn record_strategy_result(&mut self, result: &MevStrategyResult) {
             let entry = self.performances.entry(result.strategy_type.clone())
-                .or_insert_with(|| StrategyPerformance {
-                    strategy_type: result.strategy_type.clone(),
-                    total_executions: 0,
-                    successful_executions: 0,
-                    total_profit: 0.0,
-                    avg_profit_per_success: 0.0,
-                    avg_fees_paid: 0.0,
-                    avg_tip_paid: 0.0,
-                    avg_execution_time_ms: 0,
-                });
+                .or_insert_with(|| StrategyPerformance::new(result.strategy_type.clone()));
+            
+            // Only submissions say anything about the strategy working; opportunities that fell
+            // below a threshold before submitting neither break nor extend a failure streak, and
+            // pending bundles wait for record_bundle_outcome
+            if result.success {
+                entry.consecutive_failures = 0;
+            } else if result.submitted() && !result.pending {
+                entry.consecutive_failures += 1;
+            }
             
             entry.total_executions += 1;
             if result.success {
@@ -906,14 +2061,412 @@ pub mod strategy_utils {
             entry.avg_execution_time_ms = (((entry.avg_execution_time_ms as f64 * (entry.total_executions as f64 - 1.0)) + result.execution_time_ms as f64) / entry.total_executions as f64) as u64;
         }
         
-        pub fn should_disable_strategy(&self, strategy_type: &MevStrategyType, max_failures: u32) -> bool {
-            if let Some(perf) = self.performances.get(strategy_type) {
-                // If there are more than max_failures consecutive failures, disable the strategy
-                // This is a simplified implementation - in reality you'd track consecutive failures specifically
-                perf.total_executions >= 5 && perf.success_rate() < 0.1 // Less than 10% success rate over 5+ attempts
-            } else {
-                false
+        // The verdict on a pending result's bundle, booked as that result would have been
+        pub fn record_bundle_outcome(&mut self, bundle: &ResolvedBundle) {
+            let entry = self.performances.entry(bundle.strategy_type.clone())
+                .or_insert_with(|| StrategyPerformance::new(bundle.strategy_type.clone()));
+            if !bundle.landed() {
+                entry.consecutive_failures += 1;
+                return;
             }
+            entry.consecutive_failures = 0;
+            entry.successful_executions += 1;
+            entry.total_profit += bundle.profit;
+            entry.avg_profit_per_success = entry.total_profit / entry.successful_executions as f64;
+        }
+        
+        // Execution errors count as failures of the strategy that was dispatched
+        pub fn record_strategy_error(&mut self, strategy_type: &MevStrategyType) {
+            self.performances.entry(strategy_type.clone())
+                .or_insert_with(|| StrategyPerformance::new(strategy_type.clone()))
+                .consecutive_failures += 1;
+        }
+        
+        pub fn should_disable_strategy(&mut self, strategy_type: &MevStrategyType, max_failures: u32) -> bool {
+            self.should_disable_strategy_at(strategy_type, max_failures, Instant::now())
+        }
+        
+        // Disables the strategy for the cooldown once max_failures in a row are reached; after the
+        // cooldown it's re-enabled with a fresh streak
+        pub fn should_disable_strategy_at(&mut self, strategy_type: &MevStrategyType, max_failures: u32, now: Instant) -> bool {
+            let cooldown = self.cooldown;
+            let Some(perf) = self.performances.get_mut(strategy_type) else { return false };
+            
+            if let Some(disabled_until) = perf.disabled_until {
+                if now < disabled_until {
+                    return true;
+                }
+                perf.disabled_until = None;
+                perf.consecutive_failures = 0;
+                Logger::status_update(&format!("Re-enabling strategy {:?} after cooldown", strategy_type));
+                return false;
+            }
+            
+            if perf.consecutive_failures >= max_failures {
+                perf.disabled_until = Some(now + cooldown);
+                Logger::error_occurred(&format!(
+                    "Strategy {:?} disabled for {}s after {} consecutive failures",
+                    strategy_type, cooldown.as_secs(), perf.consecutive_failures
+                ));
+                return true;
+            }
+            
+            false
+        }
+        
+        pub fn disabled_strategies(&self) -> Vec<MevStrategyType> {
+            let now = Instant::now();
+            self.performances.values()
+                .filter(|perf| perf.disabled_until.is_some_and(|until| now < until))
+                .map(|perf| perf.strategy_type.clone())
+                .collect()
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::strategy_utils::StrategyManager;
+    use crate::rpc::types::{self, RpcResponse};
+    use crate::utils::bundle_tracker::BundleState;
+    use crate::utils::leader_schedule::LeaderType;
+    use serde_json::json;
+
+    fn submitted_result(strategy_type: MevStrategyType, success: bool) -> MevStrategyResult {
+        MevStrategyResult {
+            success,
+            profit: 0.0,
+            fees_paid: 0.0001,
+            tip_paid: 0.0001,
+            execution_time_ms: 10,
+            strategy_type,
+            phases: vec![(ExecutionPhase::Simulation, 5), (ExecutionPhase::Submit, 5)],
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }
+    }
+
+    #[test]
+    fn test_repeated_failures_disable_strategy_until_cooldown() {
+        let mut manager = StrategyManager::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            manager.record_strategy_result(&submitted_result(MevStrategyType::Sandwich, false));
+        }
+        manager.record_strategy_error(&MevStrategyType::Sandwich);
+        assert!(manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now));
+
+        // Later opportunities of that type stay skipped; other strategies are unaffected
+        assert!(manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now + Duration::from_secs(30)));
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Arbitrage, 3, now));
+        assert_eq!(manager.disabled_strategies(), vec![MevStrategyType::Sandwich]);
+
+        // Re-enabled after the cooldown with a fresh streak
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now + Duration::from_secs(61)));
+        manager.record_strategy_result(&submitted_result(MevStrategyType::Sandwich, false));
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now + Duration::from_secs(62)));
+    }
+
+    #[test]
+    fn test_success_or_unsubmitted_results_dont_extend_streak() {
+        let mut manager = StrategyManager::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            manager.record_strategy_result(&submitted_result(MevStrategyType::Arbitrage, false));
+        }
+        manager.record_strategy_result(&submitted_result(MevStrategyType::Arbitrage, true));
+        manager.record_strategy_result(&submitted_result(MevStrategyType::Arbitrage, false));
+
+        // Below the profit threshold: never submitted
+        let mut skipped = submitted_result(MevStrategyType::Arbitrage, false);
+        skipped.phases = vec![(ExecutionPhase::Simulation, 5)];
+        for _ in 0..5 {
+            manager.record_strategy_result(&skipped);
+        }
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Arbitrage, 3, now));
+    }
+
+    #[test]
+    fn test_throttled_submissions_dont_count_as_failures() {
+        let mut manager = StrategyManager::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        let mut throttled = submitted_result(MevStrategyType::Backrun, false);
+        throttled.skip_reason = jito_rate_limit::throttle_skip_reason("Jito throttled: block engine returned 429");
+        assert!(!throttled.submitted());
+        for _ in 0..5 {
+            manager.record_strategy_result(&throttled);
+        }
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Backrun, 3, now));
+        assert_eq!(jito_rate_limit::throttle_skip_reason("Jito bundle failed: simulation failed"), None);
+    }
+
+    #[test]
+    fn test_pending_bundles_count_once_they_resolve() {
+        let mut manager = StrategyManager::new(Duration::from_secs(60));
+        let now = Instant::now();
+        let bundle = |state| ResolvedBundle {
+            bundle_id: "bundle".to_string(),
+            strategy_type: MevStrategyType::Sandwich,
+            state,
+            tip: 0.0001,
+            percentile: None,
+            leader: LeaderType::Jito,
+            profit: 0.01,
+            fees: 0.0001,
+            mints: Vec::new(),
+            pools: Vec::new(),
+            transactions: Vec::new(),
+            simulation: None,
+            simulation_accuracy: None,
+            realized_profit: None,
+        };
+
+        let mut pending = submitted_result(MevStrategyType::Sandwich, false);
+        pending.pending = true;
+        for _ in 0..5 {
+            manager.record_strategy_result(&pending);
+        }
+        assert!(!manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now));
+
+        manager.record_bundle_outcome(&bundle(BundleState::Landed));
+        let performance = &manager.performances[&MevStrategyType::Sandwich];
+        assert_eq!((performance.total_executions, performance.successful_executions), (5, 1));
+        assert!((performance.total_profit - 0.01).abs() < 1e-12);
+
+        for _ in 0..3 {
+            manager.record_bundle_outcome(&bundle(BundleState::Expired));
+        }
+        assert!(manager.should_disable_strategy_at(&MevStrategyType::Sandwich, 3, now));
+    }
+
+    #[test]
+    fn test_failed_bundle_simulation_is_recorded_as_skip_reason() {
+        let failure: Box<dyn std::error::Error + Send + Sync> = BundleSimulationFailure {
+            kind: "bundle_sim_instruction_error",
+            transaction_index: Some(1),
+            detail: "{\"InstructionError\":[0,{\"Custom\":6001}]}".to_string(),
+        }.into();
+        assert_eq!(submission_skip_reason(&*failure), Some("bundle_sim_instruction_error"));
+
+        let throttled: Box<dyn std::error::Error + Send + Sync> = "Jito throttled: submission queue full".into();
+        assert_eq!(submission_skip_reason(&*throttled), Some("jito_throttled"));
+        let rejected: Box<dyn std::error::Error + Send + Sync> = "Jito bundle failed: bundle rejected".into();
+        assert_eq!(submission_skip_reason(&*rejected), None);
+    }
+
+    #[test]
+    fn test_thresholds_reject_negative_or_absurd_values() {
+        let valid = StrategyThresholds {
+            min_arbitrage_profit: 0.005,
+            min_sandwich_profit: 0.01,
+            min_frontrun_profit: 0.005,
+            max_slippage_bps: 300,
+            max_price_impact_bps: 1_000,
+        };
+        assert!(valid.validate().is_ok());
+        assert!((valid.max_slippage_percent() - 0.03).abs() < 1e-12);
+
+        assert!(StrategyThresholds { min_arbitrage_profit: -0.001, ..valid.clone() }.validate().is_err());
+        assert!(StrategyThresholds { min_sandwich_profit: f64::NAN, ..valid.clone() }.validate().is_err());
+        // Lamports mistaken for SOL
+        assert!(StrategyThresholds { min_frontrun_profit: 5_000_000.0, ..valid.clone() }.validate().is_err());
+        assert!(StrategyThresholds { max_slippage_bps: 0, ..valid.clone() }.validate().is_err());
+        assert!(StrategyThresholds { max_slippage_bps: 10_000, ..valid.clone() }.validate().is_err());
+        assert!(StrategyThresholds { max_price_impact_bps: 0, ..valid.clone() }.validate().is_err());
+        assert!(StrategyThresholds { max_price_impact_bps: 10_000, ..valid }.validate().is_err());
+    }
+
+    #[test]
+    fn test_strategy_names_parse_case_insensitively() {
+        assert_eq!("arbitrage".parse::<MevStrategyType>(), Ok(MevStrategyType::Arbitrage));
+        assert_eq!(" SandWich ".parse::<MevStrategyType>(), Ok(MevStrategyType::Sandwich));
+        assert!("other".parse::<MevStrategyType>().is_err()); // Not selectable
+        for (name, strategy) in MevStrategyType::SELECTABLE {
+            assert_eq!(strategy.to_string(), *name);
+            assert_eq!(name.parse::<MevStrategyType>().as_ref(), Ok(strategy));
+        }
+    }
+
+    #[test]
+    fn test_strategy_list_keeps_order_and_drops_duplicates() {
+        let parsed = MevStrategyType::parse_strategy_list(" Snipe ,arbitrage,, SNIPE,arbitrage ").unwrap();
+        assert_eq!(parsed, vec![MevStrategyType::Snipe, MevStrategyType::Arbitrage]);
+
+        let err = MevStrategyType::parse_strategy_list("arbitrage, arbitrge ,sandwich").unwrap_err();
+        assert_eq!(err, "Unknown strategy 'arbitrge' in STRATEGY (valid values: arbitrage, sandwich, frontrun, backrun, liquidation, snipe)");
+
+        assert!(MevStrategyType::parse_strategy_list(" , ").unwrap_err().starts_with("STRATEGY is empty"));
+    }
+
+    fn step(input_token: &str, output_token: &str, amount_in: u64, expected_out: u64) -> RouteStep {
+        RouteStep {
+            dex: "Raydium".to_string(),
+            input_token: input_token.to_string(),
+            output_token: output_token.to_string(),
+            pool_address: format!("{}-{}", input_token, output_token),
+            amount_in,
+            expected_out,
+        }
+    }
+
+    #[test]
+    fn test_three_leg_route_chains_guaranteed_amounts() {
+        // 1 SOL -> 150 USDC -> 6M BONK -> 1.025 SOL
+        let route = vec![
+            step("SOL", "USDC", 1_000_000_000, 150_000_000),
+            step("USDC", "BONK", 150_000_000, 600_000_000_000),
+            step("BONK", "SOL", 600_000_000_000, 1_025_000_000),
+        ];
+        let legs = chain_route(&route, 0.03).unwrap();
+        assert_eq!(legs.len(), 3);
+        assert_eq!(legs[0].amount_in, 1_000_000_000);
+        for pair in legs.windows(2) {
+            assert_eq!(pair[1].amount_in, pair[0].min_out);
+            assert_eq!(pair[1].input_token, pair[0].output_token);
+        }
+        for leg in &legs {
+            assert!(leg.min_out < leg.expected_out);
+        }
+        // Later quotes scale down with their smaller, guaranteed input
+        assert!(legs[1].expected_out < 600_000_000_000);
+        // Compounded slippage alone would accept ~0.99 SOL back; the route must at least break even
+        assert_eq!(legs[2].min_out, 1_000_000_000);
+        // Without the cycle the budget compounds to no more than 3% over the route
+        let open_legs = chain_route(&route[..2], 0.03).unwrap();
+        let guaranteed = open_legs[1].min_out as f64 / 600_000_000_000.0;
+        assert!((guaranteed - 0.97).abs() < 1e-6);
+        assert_eq!(pack_route_legs(&legs).len(), 3);
+    }
+
+    #[test]
+    fn test_route_rejects_disconnected_legs_and_packs_long_routes() {
+        let broken = vec![
+            step("SOL", "USDC", 1_000_000_000, 150_000_000),
+            step("BONK", "SOL", 600_000_000_000, 1_020_000_000),
+        ];
+        assert!(chain_route(&broken, 0.03).is_none());
+        assert!(chain_route(&[], 0.03).is_none());
+
+        let tokens = ["SOL", "A", "B", "C", "D", "E", "SOL"];
+        let long: Vec<RouteStep> = tokens.windows(2).map(|pair| step(pair[0], pair[1], 1_000_000, 1_000_000)).collect();
+        let legs = chain_route(&long, 0.01).unwrap();
+        let groups = pack_route_legs(&legs);
+        assert!(groups.len() <= MAX_ROUTE_TRANSACTIONS);
+        assert_eq!(groups.iter().map(|group| group.len()).sum::<usize>(), legs.len());
+    }
+
+    #[test]
+    fn test_backrun_bundle_follows_pending_target() {
+        let pending = json!({ "transaction": ["VGFyZ2V0", "base64"] });
+        assert_eq!(backrun_bundle(Some(&pending), "ours".to_string()), Some(vec!["VGFyZ2V0".to_string(), "ours".to_string()]));
+
+        // Already landed: the pool is displaced, nothing to anchor on
+        let landed = json!({ "slot": 250_000_000, "meta": { "err": null }, "transaction": ["VGFyZ2V0", "base64"] });
+        assert_eq!(backrun_bundle(Some(&landed), "ours".to_string()), Some(vec!["ours".to_string()]));
+
+        // Pending but only available parsed
+        let parsed = json!({ "transaction": { "message": {} } });
+        assert_eq!(backrun_bundle(Some(&parsed), "ours".to_string()), None);
+    }
+
+    #[test]
+    fn test_sandwich_bundle_puts_the_victim_between_our_legs() {
+        use base64::Engine;
+        use solana_sdk::signature::{Keypair, Signer};
+        let victim = Keypair::new();
+        let transfer = solana_sdk::system_instruction::transfer(&victim.pubkey(), &solana_sdk::pubkey::Pubkey::new_unique(), 1);
+        let victim_tx = Transaction::new_signed_with_payer(&[transfer], Some(&victim.pubkey()), &[&victim], solana_sdk::hash::Hash::default());
+        let bytes = bincode::serialize(&victim_tx).unwrap();
+        let details = json!({ "transaction": [base64::engine::general_purpose::STANDARD.encode(&bytes), "base64"] });
+
+        let bundle = sandwich_bundle("frontrun".to_string(), &details, "backrun".to_string()).unwrap();
+        assert_eq!(bundle, vec!["frontrun".to_string(), bs58::encode(&bytes).into_string(), "backrun".to_string()]);
+
+        // Without the signed victim there is nothing to sandwich
+        let parsed = json!({ "transaction": { "message": {} } });
+        assert_eq!(sandwich_bundle("frontrun".to_string(), &parsed, "backrun".to_string()), None);
+    }
+
+    #[cfg(feature = "liquidation")]
+    #[test]
+    fn test_liquidation_transaction_is_signed_by_the_liquidator() {
+        use solana_sdk::signature::{Keypair, Signer};
+        let liquidator = Keypair::new();
+        let repay = solana_sdk::system_instruction::transfer(&liquidator.pubkey(), &solana_sdk::pubkey::Pubkey::new_unique(), 1);
+        let encoded = liquidation_transaction(&[repay], &liquidator, "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N").unwrap();
+
+        let transaction: Transaction = bincode::deserialize(&bs58::decode(&encoded).into_vec().unwrap()).unwrap();
+        assert!(transaction.is_signed());
+        assert!(transaction.verify().is_ok());
+        assert_eq!(transaction.message.account_keys[0], liquidator.pubkey());
+    }
+
+    #[test]
+    fn test_retry_tips_escalate_within_budget() {
+        let tips = escalated_tips(0.001, 1.5, 0.01, 5);
+        assert_eq!(tips.len(), 5);
+        assert!(tips.windows(2).all(|pair| (pair[1] / pair[0] - 1.5).abs() < 1e-9));
+
+        // The bump past the budget is where retries stop
+        let capped = escalated_tips(0.004, 2.0, 0.01, 5);
+        assert_eq!(capped, vec![0.008]);
+        assert!(escalated_tips(0.004, 2.0, 0.005, 5).is_empty());
+        assert!(escalated_tips(0.001, 1.5, 0.01, 0).is_empty());
+    }
+
+    #[test]
+    fn test_reads_landing_status_from_signature_statuses() {
+        let statuses: RpcResponse<Vec<Option<SignatureStatus>>> = types::parse_result("getSignatureStatuses", &json!({ "result": {
+            "context": { "slot": 1 },
+            "value": [
+                null,
+                { "slot": 1, "err": null, "confirmationStatus": "processed" },
+                { "slot": 1, "err": null, "confirmationStatus": "confirmed" },
+                { "slot": 1, "err": { "InstructionError": [0, "Custom"] }, "confirmationStatus": "confirmed" },
+            ]
+        }})).unwrap();
+        let statuses = statuses.value;
+        assert_eq!(landing_status(&statuses, 0), LandingStatus::Pending);
+        assert_eq!(landing_status(&statuses, 1), LandingStatus::Pending);
+        assert_eq!(landing_status(&statuses, 2), LandingStatus::Landed);
+        assert_eq!(landing_status(&statuses, 3), LandingStatus::Failed);
+        assert_eq!(landing_status(&statuses, 4), LandingStatus::Pending);
+        assert_eq!(transaction_signature("frontrun_SOL_to_USDC_1000"), None);
+    }
+
+    #[test]
+    fn test_sandwich_never_falls_back_to_rpc() {
+        let bundle = vec!["frontrun".to_string(), "backrun".to_string()];
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Sandwich, &bundle), None);
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Sandwich, &bundle[..1]), None);
+
+        // Two arbitrage legs in separate transactions would go out unhedged
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Arbitrage, &bundle), None);
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Arbitrage, &bundle[..1]), Some(&bundle[0]));
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Frontrun, &bundle[..1]), Some(&bundle[0]));
+
+        let backrun = vec!["target".to_string(), "ours".to_string()];
+        assert_eq!(rpc_fallback_transaction(&OpportunityType::Backrun, &backrun), Some(&backrun[1]));
+    }
+
+    #[test]
+    fn test_only_transport_and_outage_errors_fall_back() {
+        use crate::rpc::error::RpcError;
+        use crate::utils::jito_rate_limit::Throttled;
+        let retryable = |error: Box<dyn std::error::Error + Send + Sync>| JitoClient::is_retryable_error(&*error);
+
+        assert!(retryable(RpcError::Timeout.into()));
+        assert!(retryable(RpcError::Transport("Jito request failed with status: 503 Service Unavailable".to_string()).into()));
+        assert!(retryable(Throttled::BlockEngineBackoff(Duration::from_secs(1)).into()));
+        assert!(!retryable(RpcError::Rpc { code: 400, message: "Jito request failed with status: 400 Bad Request".to_string() }.into()));
+        assert!(!retryable(RpcError::Rpc { code: 401, message: "Jito request failed with status: 401 Unauthorized".to_string() }.into()));
+        // Errors the engine answered with, even when their text looks like an outage
+        assert!(!retryable("Jito bundle failed: {\"code\":-32602,\"message\":\"bundle simulation failed\"}".into()));
+        assert!(!retryable("Jito request failed with status: 503".into()));
+    }
+}
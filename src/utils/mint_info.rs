@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::pool_creation::WSOL_MINT;
+
+const MINT_ACCOUNT_LEN: usize = 82;
+// Token-2022 pads mints with extensions to a token account's length, then writes the account type
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+
+// Token-2022 extension types
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+const EXTENSION_PERMANENT_DELEGATE: u16 = 12;
+const EXTENSION_TRANSFER_HOOK: u16 = 14;
+
+// The Token-2022 extensions that can cost a holder; all empty for SPL Token mints
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MintExtensions {
+    pub transfer_fee_bps: Option<u16>, // Higher of the current and the scheduled fee
+    pub transfer_hook_program: Option<String>, // Runs on every transfer and can refuse it
+    pub permanent_delegate: Option<String>, // Can move or burn any holder's tokens
+}
+
+impl MintExtensions {
+    // TLV entries after the account type: type u16, length u16, value. Unknown types are skipped
+    // and a truncated entry ends the list
+    fn decode(data: &[u8]) -> Self {
+        let mut extensions = Self::default();
+        if data.len() <= TOKEN_ACCOUNT_LEN || data[TOKEN_ACCOUNT_LEN] != ACCOUNT_TYPE_MINT {
+            return extensions;
+        }
+        let u16_at = |value: &[u8], offset: usize| u16::from_le_bytes(value[offset..offset + 2].try_into().expect("2 byte slice"));
+        // OptionalNonZeroPubkey: all zeroes is None
+        let pubkey_at = |value: &[u8], offset: usize| -> Option<String> {
+            let key = &value[offset..offset + 32];
+            key.iter().any(|byte| *byte != 0).then(|| bs58::encode(key).into_string())
+        };
+
+        let mut offset = TOKEN_ACCOUNT_LEN + 1;
+        while offset + 4 <= data.len() {
+            let extension_type = u16_at(data, offset);
+            let length = u16_at(data, offset + 2) as usize;
+            let Some(value) = data.get(offset + 4..offset + 4 + length) else { break };
+            match extension_type {
+                0 => break, // Uninitialized: the rest is padding
+                // Config and withdraw authorities (64), withheld amount (8), then the older and
+                // newer fees: epoch u64, maximum fee u64, basis points u16
+                EXTENSION_TRANSFER_FEE_CONFIG if length >= 108 => {
+                    extensions.transfer_fee_bps = Some(u16_at(value, 88).max(u16_at(value, 106)));
+                }
+                EXTENSION_PERMANENT_DELEGATE if length >= 32 => {
+                    extensions.permanent_delegate = pubkey_at(value, 0);
+                }
+                // Authority, then the hook program
+                EXTENSION_TRANSFER_HOOK if length >= 64 => {
+                    extensions.transfer_hook_program = pubkey_at(value, 32);
+                }
+                _ => {}
+            }
+            offset += 4 + length;
+        }
+        extensions
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintInfo {
+    pub decimals: u8,
+    pub supply: u64,
+    pub mint_authority: Option<String>,   // More tokens can still be minted
+    pub freeze_authority: Option<String>, // Holders' token accounts can be frozen
+    pub extensions: MintExtensions,
+}
+
+impl MintInfo {
+    // Layout: mint authority COption<Pubkey> (36), supply u64, decimals u8, is_initialized bool,
+    // freeze authority COption<Pubkey> (36). Token-2022 mints share it, extensions come after
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < MINT_ACCOUNT_LEN {
+            return Err(format!("Mint account too short: {} bytes", data.len()).into());
+        }
+        if data[45] == 0 {
+            return Err("Mint account is not initialized".into());
+        }
+
+        let optional_pubkey = |offset: usize| -> Option<String> {
+            let tag = u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 byte slice"));
+            (tag == 1).then(|| bs58::encode(&data[offset + 4..offset + 36]).into_string())
+        };
+
+        Ok(Self {
+            decimals: data[44],
+            supply: u64::from_le_bytes(data[36..44].try_into().expect("8 byte slice")),
+            mint_authority: optional_pubkey(0),
+            freeze_authority: optional_pubkey(46),
+            extensions: MintExtensions::decode(data),
+        })
+    }
+
+    pub fn ui_to_raw(&self, ui_amount: f64) -> u64 {
+        ui_to_raw(ui_amount, self.decimals)
+    }
+
+    pub fn raw_to_ui(&self, raw_amount: u64) -> f64 {
+        raw_to_ui(raw_amount, self.decimals)
+    }
+}
+
+pub fn ui_to_raw(ui_amount: f64, decimals: u8) -> u64 {
+    (ui_amount * 10f64.powi(decimals as i32)).round().max(0.0) as u64
+}
+
+pub fn raw_to_ui(raw_amount: u64, decimals: u8) -> f64 {
+    raw_amount as f64 / 10f64.powi(decimals as i32)
+}
+
+// Mint metadata never changes in ways we care about (decimals are fixed and authorities are
+// only ever revoked), so entries are kept for the lifetime of the process
+pub struct MintInfoCache {
+    rpc_manager: Arc<RpcManager>,
+    cache: Arc<RwLock<HashMap<String, MintInfo>>>,
+}
+
+impl MintInfoCache {
+    pub fn new(rpc_manager: Arc<RpcManager>) -> Self {
+        let mut known = HashMap::new();
+        // Native SOL is priced and traded as wrapped SOL
+        known.insert(WSOL_MINT.to_string(), MintInfo {
+            decimals: 9,
+            supply: 0,
+            mint_authority: None,
+            freeze_authority: None,
+            extensions: MintExtensions::default(),
+        });
+
+        Self {
+            rpc_manager,
+            cache: Arc::new(RwLock::new(known)),
+        }
+    }
+
+    pub async fn get(&self, mint: &str) -> Result<MintInfo, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(info) = self.cache.read().await.get(mint) {
+            return Ok(info.clone());
+        }
+
+        let data = self.rpc_manager.get_account_data(mint).await?;
+        let info = MintInfo::decode(&data)
+            .map_err(|e| format!("Invalid mint {}: {}", mint, e))?;
+        self.cache.write().await.insert(mint.to_string(), info.clone());
+        Ok(info)
+    }
+
+    pub async fn decimals(&self, mint: &str) -> Result<u8, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.get(mint).await?.decimals)
+    }
+}
+
+impl Clone for MintInfoCache {
+    fn clone(&self) -> Self {
+        MintInfoCache {
+            rpc_manager: Arc::clone(&self.rpc_manager),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::USDC_MINT;
+
+    // USDC mint account: 6 decimals, mint and freeze authority set
+    fn mint_account(decimals: u8, mint_authority: Option<&str>, freeze_authority: Option<&str>) -> Vec<u8> {
+        let mut data = vec![0u8; MINT_ACCOUNT_LEN];
+        if let Some(authority) = mint_authority {
+            data[0..4].copy_from_slice(&1u32.to_le_bytes());
+            data[4..36].copy_from_slice(&bs58::decode(authority).into_vec().unwrap());
+        }
+        data[36..44].copy_from_slice(&9_000_000_000_000_000u64.to_le_bytes());
+        data[44] = decimals;
+        data[45] = 1;
+        if let Some(authority) = freeze_authority {
+            data[46..50].copy_from_slice(&1u32.to_le_bytes());
+            data[50..82].copy_from_slice(&bs58::decode(authority).into_vec().unwrap());
+        }
+        data
+    }
+
+    #[test]
+    fn test_decodes_decimals_and_authorities() {
+        let authority = "BJE5MMbqXjVwjAF7oxwPYXnTXDyspzZyt4vwenNw5ruG";
+        let info = MintInfo::decode(&mint_account(6, Some(authority), Some(USDC_MINT))).unwrap();
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.supply, 9_000_000_000_000_000);
+        assert_eq!(info.mint_authority.as_deref(), Some(authority));
+        assert_eq!(info.freeze_authority.as_deref(), Some(USDC_MINT));
+
+        let revoked = MintInfo::decode(&mint_account(9, None, None)).unwrap();
+        assert_eq!(revoked.mint_authority, None);
+        assert_eq!(revoked.freeze_authority, None);
+    }
+
+    #[test]
+    fn test_converts_amounts_with_mint_decimals() {
+        let usdc = MintInfo::decode(&mint_account(6, None, None)).unwrap();
+        assert_eq!(usdc.ui_to_raw(1234.5), 1_234_500_000);
+        assert_eq!(usdc.raw_to_ui(1_234_500_000), 1234.5);
+        assert_eq!(ui_to_raw(1.5, 9), 1_500_000_000);
+    }
+
+    #[test]
+    fn test_rejects_uninitialized_and_short_accounts() {
+        let mut data = mint_account(6, None, None);
+        data[45] = 0;
+        assert!(MintInfo::decode(&data).is_err());
+        assert!(MintInfo::decode(&data[..50]).is_err());
+    }
+}
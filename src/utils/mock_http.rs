@@ -0,0 +1,106 @@
+// Minimal HTTP server for tests that need a node, block engine or API to talk to
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// One request as the server got it
+pub struct MockRequest {
+    pub index: usize, // Requests served before this one
+    pub body: String,
+}
+
+impl MockRequest {
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: String) -> Self {
+        Self { status, headers: Vec::new(), body, delay: Duration::ZERO }
+    }
+
+    pub fn json(body: &Value) -> Self {
+        Self::new(200, body.to_string())
+    }
+
+    // A JSON-RPC success answering the request's id
+    pub fn rpc_result(request: &MockRequest, result: Value) -> Self {
+        let id = request.json().get("id").cloned().unwrap_or(Value::from(1));
+        Self::json(&serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+// Answers every request with what `handler` makes of it, each connection on its own task.
+// Returns the base URL and the number of requests served
+pub async fn serve<F>(handler: F) -> (String, Arc<AtomicUsize>)
+where
+    F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&hits);
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let index = counter.fetch_add(1, Ordering::SeqCst);
+            let handler = Arc::clone(&handler);
+            tokio::spawn(async move {
+                let body = read_body(&mut socket).await;
+                let response = handler(&MockRequest { index, body });
+                tokio::time::sleep(response.delay).await;
+                let headers: String = response.headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+                let raw = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.status, headers, response.body.len(), response.body
+                );
+                let _ = socket.write_all(raw.as_bytes()).await;
+            });
+        }
+    });
+    (url, hits)
+}
+
+// Reads until Content-Length bytes of body are in; headers and body may arrive separately
+async fn read_body(socket: &mut tokio::net::TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match socket.read(&mut buf).await {
+            Ok(read) if read > 0 => request.extend_from_slice(&buf[..read]),
+            _ => break,
+        }
+        let text = String::from_utf8_lossy(&request);
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head.lines()
+                .find_map(|line| line.split_once(':').filter(|(name, _)| name.eq_ignore_ascii_case("content-length")))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if body.len() >= length {
+                return body.to_string();
+            }
+        }
+    }
+    String::from_utf8_lossy(&request).split_once("\r\n\r\n").map(|(_, body)| body.to_string()).unwrap_or_default()
+}
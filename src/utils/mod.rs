@@ -1,5 +1,8 @@
 pub mod solana_utils;
 pub mod jito;
+pub mod jito_auth;
+pub mod jito_rate_limit;
+pub mod token_bucket;
 pub mod profit_calculator;
 pub mod profitability_calculator;
 pub mod fee_calculator;
@@ -13,7 +16,71 @@ pub mod false_positive_reducer;
 pub mod jito_optimizer;
 pub mod mev_strategies;
 pub mod metrics_collector;
+pub mod bucket_histogram;
+pub mod opportunity_outcomes;
+pub mod filter_decisions;
+pub mod alert_sinks;
+pub mod windowed_metrics;
+pub mod performance_summary;
+pub mod metrics_state;
+pub mod token_pnl;
+pub mod profit_calibration;
+pub mod metrics_server;
 pub mod risk_controls;
+pub mod kill_switch;
+pub mod supervisor;
+pub mod preflight;
+pub mod balance_poller;
+pub mod risk_state;
+pub mod pool_blacklist;
 pub mod dex_swap_instructions;
-pub mod risk_manager;
-pub mod analytics;
\ No newline at end of file
+pub mod analytics;
+pub mod opportunity_queue;
+pub mod instruction_parser;
+pub mod pool_creation;
+pub mod pumpfun;
+pub mod analysis_limiter;
+pub mod signature_dedup;
+pub mod raydium_amm;
+pub mod orca_whirlpool;
+pub mod meteora_dlmm;
+pub mod phoenix;
+pub mod dex_registry;
+pub mod jupiter_quote;
+pub mod amm_math;
+pub mod pyth;
+pub mod mint_info;
+pub mod token_safety;
+pub mod launch_filters;
+pub mod pool_registry;
+pub mod bounded_cache;
+pub mod triangular_arb;
+pub mod pool_watcher;
+pub mod price_sources;
+pub mod liquidity_filters;
+pub mod network_congestion;
+pub mod competition_tracker;
+pub mod pool_locks;
+pub mod dry_run;
+pub mod bundle_tracker;
+pub mod bundle_simulation;
+pub mod simulation_effects;
+pub mod simulation_variance;
+pub mod simulation_config;
+pub mod simulation_consensus;
+pub mod simulation_accuracy;
+pub mod tip_floor;
+pub mod tip_history;
+pub mod leader_schedule;
+pub mod transaction_fetcher;
+#[cfg(feature = "liquidation")]
+pub mod solend;
+#[cfg(test)]
+pub mod mock_http;
+
+// Written to a temporary file and renamed over the old one, so a crash mid-write can't corrupt it
+pub fn atomic_write(path: &str, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(&tmp_path, path)
+}
@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+
+const PERFORMANCE_SAMPLES: usize = 5; // One per minute
+const MAX_LATENCY_SAMPLES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CongestionConfig {
+    pub tps_weight: f64,
+    pub fee_weight: f64,
+    pub latency_weight: f64,
+    pub tps_capacity: f64,        // Transactions per second treated as a saturated cluster
+    pub fee_high_micro_lamports: u64, // Priority fee per CU treated as fully contested
+    pub latency_high_ms: u64,     // Send latency treated as fully congested
+    pub cache_ttl: Duration,
+}
+
+impl CongestionConfig {
+    pub fn from_config(config: &crate::config::CongestionConfig) -> Self {
+        Self {
+            tps_weight: config.tps_weight,
+            fee_weight: config.fee_weight,
+            latency_weight: config.latency_weight,
+            tps_capacity: config.tps_capacity,
+            fee_high_micro_lamports: config.fee_high_micro_lamports,
+            latency_high_ms: config.latency_high_ms,
+            cache_ttl: Duration::from_millis(config.cache_ms),
+        }
+    }
+}
+
+// Each component is normalized to 0..1; None when its input wasn't available
+#[derive(Debug, Clone, PartialEq)]
+pub struct CongestionAssessment {
+    pub tps_utilization: Option<f64>,
+    pub fee_pressure: Option<f64>,
+    pub latency_pressure: Option<f64>,
+    pub fee_p50: u64,
+    pub fee_p90: u64,
+    pub score: f64,
+}
+
+impl CongestionAssessment {
+    // Weighted mean of the available components; 0.5 when none are
+    pub fn new(config: &CongestionConfig, tps_utilization: Option<f64>, fee_percentiles: Option<(u64, u64)>, latency_pressure: Option<f64>) -> Self {
+        let (fee_p50, fee_p90) = fee_percentiles.unwrap_or((0, 0));
+        let fee_pressure = fee_percentiles.map(|(p50, p90)| fee_pressure(p50, p90, config.fee_high_micro_lamports));
+
+        let components = [
+            (tps_utilization, config.tps_weight),
+            (fee_pressure, config.fee_weight),
+            (latency_pressure, config.latency_weight),
+        ];
+        let (weighted, weights) = components.iter()
+            .filter_map(|(value, weight)| value.map(|value| (value * weight, *weight)))
+            .fold((0.0, 0.0), |(sum, total), (value, weight)| (sum + value, total + weight));
+        let score = if weights > 0.0 { (weighted / weights).clamp(0.0, 1.0) } else { 0.5 };
+
+        Self { tps_utilization, fee_pressure, latency_pressure, fee_p50, fee_p90, score }
+    }
+
+    pub fn level(&self) -> &'static str {
+        if self.score >= 0.7 {
+            "high"
+        } else if self.score >= 0.3 {
+            "medium"
+        } else {
+            "low"
+        }
+    }
+}
+
+// Mean TPS over the samples relative to capacity
+pub fn tps_utilization(samples: &Value, tps_capacity: f64) -> Option<f64> {
+    let (transactions, seconds) = samples["result"].as_array()?
+        .iter()
+        .filter_map(|sample| Some((sample["numTransactions"].as_u64()?, sample["samplePeriodSecs"].as_u64()?)))
+        .fold((0u64, 0u64), |(transactions, seconds), (t, s)| (transactions + t, seconds + s));
+    if seconds == 0 || tps_capacity <= 0.0 {
+        return None;
+    }
+    Some((transactions as f64 / seconds as f64 / tps_capacity).clamp(0.0, 1.0))
+}
+
+// (p50, p90) of the prioritization fees paid in recent slots
+pub fn fee_percentiles(fees: &Value) -> Option<(u64, u64)> {
+    let mut values: Vec<u64> = fees["result"].as_array()?
+        .iter()
+        .filter_map(|entry| entry["prioritizationFee"].as_u64())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = |p: f64| values[((p * values.len() as f64).ceil() as usize).clamp(1, values.len()) - 1];
+    Some((rank(0.5), rank(0.9)))
+}
+
+// Fees span orders of magnitude, so both percentiles are log-scaled against the high mark
+pub fn fee_pressure(p50: u64, p90: u64, high_micro_lamports: u64) -> f64 {
+    let high = ((high_micro_lamports.max(1) as f64) + 1.0).ln();
+    let scaled = |fee: u64| ((fee as f64 + 1.0).ln() / high).clamp(0.0, 1.0);
+    0.5 * scaled(p50) + 0.5 * scaled(p90)
+}
+
+// Median of our recent send latencies relative to the high mark
+pub fn latency_pressure(samples_ms: &VecDeque<u64>, latency_high_ms: u64) -> Option<f64> {
+    if samples_ms.is_empty() || latency_high_ms == 0 {
+        return None;
+    }
+    let mut sorted: Vec<u64> = samples_ms.iter().copied().collect();
+    sorted.sort_unstable();
+    let median = sorted[sorted.len() / 2];
+    Some((median as f64 / latency_high_ms as f64).clamp(0.0, 1.0))
+}
+
+// 0..1 congestion score for tip sizing, refreshed at most once per cache_ttl
+pub struct NetworkCongestion {
+    rpc_manager: Arc<RpcManager>,
+    config: CongestionConfig,
+    send_latencies: Arc<RwLock<VecDeque<u64>>>,
+    cached: Arc<RwLock<Option<(Instant, CongestionAssessment)>>>,
+}
+
+impl NetworkCongestion {
+    pub fn new(rpc_manager: Arc<RpcManager>, config: CongestionConfig) -> Self {
+        Self {
+            rpc_manager,
+            config,
+            send_latencies: Arc::new(RwLock::new(VecDeque::with_capacity(MAX_LATENCY_SAMPLES))),
+            cached: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, config: &crate::config::CongestionConfig) -> Self {
+        Self::new(rpc_manager, CongestionConfig::from_config(config))
+    }
+
+    // Time from handing a bundle to the block engine until it was accepted
+    pub async fn record_send_latency(&self, latency_ms: u64) {
+        let mut latencies = self.send_latencies.write().await;
+        if latencies.len() >= MAX_LATENCY_SAMPLES {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency_ms);
+    }
+
+    pub async fn score(&self) -> f64 {
+        self.assess().await.score
+    }
+
+    pub async fn assess(&self) -> CongestionAssessment {
+        if let Some((assessed_at, assessment)) = self.cached.read().await.as_ref() {
+            if assessed_at.elapsed() < self.config.cache_ttl {
+                return assessment.clone();
+            }
+        }
+
+        let (samples, fees) = tokio::join!(
+            self.rpc_manager.get_recent_performance_samples(PERFORMANCE_SAMPLES),
+            self.rpc_manager.get_recent_prioritization_fees(),
+        );
+        let tps = samples.ok().and_then(|samples| tps_utilization(&samples, self.config.tps_capacity));
+        let fees = fees.ok().and_then(|fees| fee_percentiles(&fees));
+        let latency = latency_pressure(&*self.send_latencies.read().await, self.config.latency_high_ms);
+        let assessment = CongestionAssessment::new(&self.config, tps, fees, latency);
+
+        let mut cached = self.cached.write().await;
+        let previous_level = cached.as_ref().map(|(_, previous)| previous.level());
+        if previous_level != Some(assessment.level()) {
+            let component = |value: Option<f64>| value.map_or_else(|| "n/a".to_string(), |value| format!("{:.2}", value));
+            Logger::status_update(&format!(
+                "Network congestion {} ({:.2}): tps {}, fees {} (p50 {} / p90 {} micro-lamports), send latency {}",
+                assessment.level(), assessment.score, component(assessment.tps_utilization),
+                component(assessment.fee_pressure), assessment.fee_p50, assessment.fee_p90,
+                component(assessment.latency_pressure)
+            ));
+        }
+        *cached = Some((Instant::now(), assessment.clone()));
+
+        assessment
+    }
+}
+
+impl Clone for NetworkCongestion {
+    fn clone(&self) -> Self {
+        Self {
+            rpc_manager: Arc::clone(&self.rpc_manager),
+            config: self.config.clone(),
+            send_latencies: Arc::clone(&self.send_latencies),
+            cached: Arc::clone(&self.cached),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> CongestionConfig {
+        CongestionConfig {
+            tps_weight: 0.4,
+            fee_weight: 0.4,
+            latency_weight: 0.2,
+            tps_capacity: 5000.0,
+            fee_high_micro_lamports: 1_000_000,
+            latency_high_ms: 2000,
+            cache_ttl: Duration::from_secs(3),
+        }
+    }
+
+    #[test]
+    fn test_normalizes_canned_samples() {
+        let samples = json!({ "result": [
+            { "numTransactions": 180_000, "numSlots": 150, "samplePeriodSecs": 60, "slot": 2 },
+            { "numTransactions": 120_000, "numSlots": 150, "samplePeriodSecs": 60, "slot": 1 }
+        ]});
+        // 2,500 TPS against 5,000
+        assert!((tps_utilization(&samples, 5000.0).unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(tps_utilization(&json!({ "result": [] }), 5000.0), None);
+
+        let fees = json!({ "result": (1..=10).map(|i| json!({ "slot": i, "prioritizationFee": i * 1000 })).collect::<Vec<_>>() });
+        assert_eq!(fee_percentiles(&fees), Some((5000, 9000)));
+
+        assert_eq!(fee_pressure(0, 0, 1_000_000), 0.0);
+        assert!((fee_pressure(1_000_000, 1_000_000, 1_000_000) - 1.0).abs() < 1e-9);
+        assert_eq!(fee_pressure(50_000_000, 50_000_000, 1_000_000), 1.0);
+
+        let latencies: VecDeque<u64> = [400, 3000, 500].into_iter().collect();
+        assert!((latency_pressure(&latencies, 2000).unwrap() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_reweights_missing_components() {
+        let full = CongestionAssessment::new(&config(), Some(1.0), Some((1_000_000, 1_000_000)), Some(1.0));
+        assert!((full.score - 1.0).abs() < 1e-9);
+        assert_eq!(full.level(), "high");
+
+        // Without latency samples the other two carry the whole weight
+        let partial = CongestionAssessment::new(&config(), Some(0.2), Some((0, 0)), None);
+        assert!((partial.score - 0.1).abs() < 1e-9);
+        assert_eq!(partial.level(), "low");
+
+        assert_eq!(CongestionAssessment::new(&config(), None, None, None).score, 0.5);
+    }
+}
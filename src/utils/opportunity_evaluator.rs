@@ -1,22 +1,181 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::Mutex;
 use serde_json::{json, Value};
+use crate::config::Config;
 use crate::logging::Logger;
 use crate::rpc::rpc_manager::{RpcManager, RpcTaskType};
-use crate::utils::dex_api::DexApi;
+use crate::utils::pool_creation::{self, SnipeFilters, NewPoolInfo, WSOL_MINT};
+use crate::utils::launch_filters::PoolLaunch;
+use crate::utils::pumpfun::{self, BondingCurveState, PumpFunFilters, PumpFunInstruction};
+use crate::utils::raydium_amm::{self, AmmInfo, RaydiumSwap};
+use crate::utils::orca_whirlpool::{WhirlpoolState, WhirlpoolSwap};
+use crate::utils::meteora_dlmm::{self, BinArray, DlmmQuote, DlmmSwap, LbPairState};
+use crate::utils::phoenix::{OrderBook, Side};
+use crate::utils::dex_registry::{DecodedSwap, DexRegistry};
+use crate::utils::instruction_parser;
+use crate::utils::jupiter_quote::JupiterQuoteClient;
+use crate::utils::amm_math;
+use crate::utils::dex_api::{DexApi, SwapSide};
+use crate::utils::pyth::PythPriceSource;
+use crate::utils::price_sources::{BirdeyePriceSource, CompositePriceSource, PoolPriceSource};
+use crate::utils::liquidity_filters::{FilterRejection, LiquidityFilters};
+use crate::utils::mint_info::{self, MintInfoCache};
+use crate::utils::pool_registry::{PoolEntry, PoolRegistry};
+use crate::utils::bounded_cache::{BoundedCache, CacheStats};
+use crate::utils::pool_watcher::PoolWatcher;
+use crate::utils::pool_blacklist::{PoolBlacklist, PoolBlacklistConfig};
+use crate::utils::profit_calibration::ProfitCalibration;
+use crate::utils::fee_calculator::FeeCalculator;
+use crate::utils::triangular_arb;
+use crate::utils::mev_simulation_pipeline::mev_operations::RouteStep;
+use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
 
 #[derive(Debug, Clone)]
 pub struct PoolState {
+    pub pool_address: String,
+    pub dex: String,
     pub token_a: String,
     pub token_b: String,
     pub reserve_a: u64,
     pub reserve_b: u64,
-    pub liquidity: f64,
+    pub liquidity: f64, // In SOL; 0 when neither side is SOL
     pub fee_rate: f64,
+    pub vaults: Vec<String>, // Token accounts every swap on the pool write-locks
     pub last_updated: std::time::SystemTime,
 }
 
+impl PoolState {
+    pub fn from_raydium(pool_address: &str, amm: &AmmInfo, coin_vault_amount: u64, pc_vault_amount: u64) -> Self {
+        let (reserve_a, reserve_b) = amm.reserves(coin_vault_amount, pc_vault_amount);
+        Self {
+            pool_address: pool_address.to_string(),
+            dex: "Raydium".to_string(),
+            token_a: amm.coin_mint.clone(),
+            token_b: amm.pc_mint.clone(),
+            reserve_a,
+            reserve_b,
+            liquidity: liquidity_in_sol(&amm.coin_mint, reserve_a, &amm.pc_mint, reserve_b),
+            fee_rate: amm.fee_fraction(),
+            vaults: vec![amm.coin_vault.clone(), amm.pc_vault.clone()],
+            last_updated: std::time::SystemTime::now(),
+        }
+    }
+    
+    pub fn from_whirlpool(pool_address: &str, whirlpool: &WhirlpoolState) -> Self {
+        let (reserve_a, reserve_b) = whirlpool.virtual_reserves();
+        Self {
+            pool_address: pool_address.to_string(),
+            dex: "Orca".to_string(),
+            token_a: whirlpool.token_mint_a.clone(),
+            token_b: whirlpool.token_mint_b.clone(),
+            reserve_a,
+            reserve_b,
+            liquidity: liquidity_in_sol(&whirlpool.token_mint_a, reserve_a, &whirlpool.token_mint_b, reserve_b),
+            fee_rate: whirlpool.fee_fraction(),
+            vaults: vec![whirlpool.token_vault_a.clone(), whirlpool.token_vault_b.clone()],
+            last_updated: std::time::SystemTime::now(),
+        }
+    }
+    
+    pub fn from_dlmm(pool_address: &str, pair: &LbPairState, vault_x_amount: u64, vault_y_amount: u64) -> Self {
+        let (amount_x, amount_y) = pair.reserves(vault_x_amount, vault_y_amount);
+        let (reserve_a, reserve_b) = pair.virtual_reserves(amount_x, amount_y);
+        Self {
+            pool_address: pool_address.to_string(),
+            dex: "Meteora".to_string(),
+            token_a: pair.token_x_mint.clone(),
+            token_b: pair.token_y_mint.clone(),
+            reserve_a,
+            reserve_b,
+            liquidity: liquidity_in_sol(&pair.token_x_mint, reserve_a, &pair.token_y_mint, reserve_b),
+            fee_rate: pair.fee_fraction(),
+            vaults: vec![pair.reserve_x.clone(), pair.reserve_y.clone()],
+            last_updated: std::time::SystemTime::now(),
+        }
+    }
+    
+    // (reserve_in, reserve_out) for a swap selling input_mint; None if the pool doesn't hold it
+    pub fn reserves_for_input(&self, input_mint: &str) -> Option<(u64, u64)> {
+        if input_mint == self.token_a {
+            Some((self.reserve_a, self.reserve_b))
+        } else if input_mint == self.token_b {
+            Some((self.reserve_b, self.reserve_a))
+        } else {
+            None
+        }
+    }
+    
+    pub fn quote(&self, input_mint: &str, amount_in: u64) -> Option<SwapQuote> {
+        let (reserve_in, reserve_out) = self.reserves_for_input(input_mint)?;
+        Some(SwapQuote {
+            input_amount: amount_in,
+            output_amount: amm_math::get_amount_out(amount_in, reserve_in, reserve_out, self.fee_rate),
+            slippage: amm_math::effective_slippage(amount_in, reserve_in, reserve_out, self.fee_rate),
+            route: vec![self.dex.clone()],
+            price_impact: amm_math::price_impact(amount_in, reserve_in),
+        })
+    }
+}
+
+// One leg of a two-venue arbitrage: a reserve-priced pool or an orderbook
+#[derive(Debug, Clone)]
+pub enum Venue {
+    Pool(PoolState),
+    Book(OrderBook),
+}
+
+impl Venue {
+    pub fn dex(&self) -> &str {
+        match self {
+            Venue::Pool(pool) => &pool.dex,
+            Venue::Book(_) => "Phoenix",
+        }
+    }
+    
+    pub fn address(&self) -> &str {
+        match self {
+            Venue::Pool(pool) => &pool.pool_address,
+            Venue::Book(book) => &book.market,
+        }
+    }
+    
+    // Output of selling amount_in of input_mint; books fill level by level from the top. Input a
+    // book can't fill is left out of the output, so thin books price conservatively
+    pub fn amount_out(&self, input_mint: &str, amount_in: u64) -> Option<u64> {
+        match self {
+            Venue::Pool(pool) => {
+                let (reserve_in, reserve_out) = pool.reserves_for_input(input_mint)?;
+                Some(amm_math::get_amount_out(amount_in, reserve_in, reserve_out, pool.fee_rate))
+            }
+            Venue::Book(book) => book.fill(input_mint, amount_in).map(|fill| fill.amount_out),
+        }
+    }
+}
+
+// amount_in of token_a through both venues in whichever order returns more: (whether venue_a
+// goes first, token_b bought on the first venue, token_a back from the second)
+pub fn best_round_trip(venue_a: &Venue, venue_b: &Venue, token_a: &str, token_b: &str, amount_in: u64) -> Option<(bool, u64, u64)> {
+    let round_trip = |buy: &Venue, sell: &Venue| -> Option<(u64, u64)> {
+        let bought = buy.amount_out(token_a, amount_in)?;
+        Some((bought, sell.amount_out(token_b, bought)?))
+    };
+    let via_a = round_trip(venue_a, venue_b)?;
+    let via_b = round_trip(venue_b, venue_a)?;
+    Some(if via_a.1 >= via_b.1 { (true, via_a.0, via_a.1) } else { (false, via_b.0, via_b.1) })
+}
+
+// Both sides of a pool are worth the same, so a SOL-paired pool holds twice its SOL reserve
+fn liquidity_in_sol(token_a: &str, reserve_a: u64, token_b: &str, reserve_b: u64) -> f64 {
+    if token_a == WSOL_MINT {
+        2.0 * reserve_a as f64 / 1_000_000_000.0
+    } else if token_b == WSOL_MINT {
+        2.0 * reserve_b as f64 / 1_000_000_000.0
+    } else {
+        0.0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PriceData {
     pub token: String,
@@ -24,6 +183,9 @@ pub struct PriceData {
     pub price_in_usd: f64,
     pub volume_24h: f64,
     pub last_updated: std::time::SystemTime,
+    pub source: &'static str,     // Price source that served it
+    pub confidence_interval: f64, // Relative uncertainty (0.01 = +/-1%); wider margins above a few percent
+    pub staleness_ms: u64,        // Age of the underlying price when it was fetched
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +199,23 @@ pub struct ArbitrageOpportunity {
     pub expected_out_b: u64,
     pub estimated_profit: f64,
     pub confidence_score: f64,
+    pub route: Vec<RouteStep>, // Every leg in execution order
+}
+
+impl ArbitrageOpportunity {
+    // Queue entry for the strategy executor; the route carries the legs to execute
+    pub fn to_opportunity_details(&self) -> OpportunityDetails {
+        OpportunityDetails {
+            token_a: self.input_token.clone(),
+            token_b: self.output_token.clone(),
+            trade_size: self.amount_in,
+            estimated_profit: self.estimated_profit,
+            dex: self.dex_a.clone(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: self.route.first().map(|step| step.pool_address.clone()),
+            route: self.route.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -50,26 +229,150 @@ pub struct SwapQuote {
 
 pub struct OpportunityEvaluator {
     rpc_manager: Arc<RpcManager>,
-    dex_api: Arc<DexApi>,
-    pool_states: Arc<RwLock<HashMap<String, PoolState>>>,
-    price_cache: Arc<RwLock<HashMap<String, PriceData>>>,
+    pool_states: BoundedCache<PoolState>,
+    price_cache: BoundedCache<PriceData>,
+    prices: CompositePriceSource, // Pyth, then Birdeye, then pool-implied prices
+    birdeye: Arc<BirdeyePriceSource>, // Also the 24h volume source for the liquidity filters
+    liquidity_filters: LiquidityFilters,
+    filter_rejections: Arc<Mutex<Vec<(OpportunityType, FilterRejection)>>>, // Drained by metrics
+    mint_info: Arc<MintInfoCache>, // Shared with the strategy executor and false-positive reducer
+    pool_registry: PoolRegistry,
+    pool_watcher: PoolWatcher, // Pushes subscription updates into pool_states
+    pool_blacklist: PoolBlacklist, // Pools no opportunity is emitted on
+    profit_calibration: ProfitCalibration, // Scales estimates before the threshold check
+    pool_state_ages: Arc<Mutex<Vec<u64>>>, // Age in ms of each pool state used, drained by metrics
+    whirlpool_cache: BoundedCache<WhirlpoolState>, // Mints/vaults never change per pool
+    launches: BoundedCache<PoolLaunch>, // Pool creations behind snipes, for the launch filters
+    dex_registry: DexRegistry,
+    jupiter: JupiterQuoteClient,
     opportunity_threshold: f64, // Minimum profit threshold to consider opportunity
+    position_size_sol: f64, // Largest front-run we'd take, used to size profit estimates
+    triangular_min_liquidity_sol: f64, // Pools shallower than this are skipped by the route search
+    snipe_filters: SnipeFilters,
+    pumpfun_filters: PumpFunFilters,
+    fee_calculator: Option<Arc<FeeCalculator>>, // The mempool's, priced with the configured fees
 }
 
 impl OpportunityEvaluator {
-    pub async fn new(rpc_manager: Arc<RpcManager>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn new(rpc_manager: Arc<RpcManager>, config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let pools = &config.pools;
+        let pool_states = BoundedCache::new(pools.cache_capacity, std::time::Duration::from_millis(pools.cache_ttl_ms));
+        
+        let mint_info = Arc::new(MintInfoCache::new(Arc::clone(&rpc_manager)));
+        let birdeye = Arc::new(BirdeyePriceSource::from_config(&config.prices)?);
+        let prices = CompositePriceSource::chain(
+            PythPriceSource::from_config(Arc::clone(&rpc_manager), &config.prices)?,
+            Arc::clone(&birdeye),
+            PoolPriceSource::new(pool_states.clone(), Arc::clone(&mint_info)),
+        );
+        
         Ok(Self {
-            rpc_manager: Arc::new(rpc_manager),
-            dex_api: Arc::new(DexApi::new("".to_string())), // URL will be updated dynamically
-            pool_states: Arc::new(RwLock::new(HashMap::new())),
-            price_cache: Arc::new(RwLock::new(HashMap::new())),
+            prices,
+            birdeye,
+            liquidity_filters: LiquidityFilters::from_config(&config.filters),
+            filter_rejections: Arc::new(Mutex::new(Vec::new())),
+            pool_watcher: PoolWatcher::from_config(Arc::clone(&rpc_manager), pool_states.clone(), pools),
+            pool_state_ages: Arc::new(Mutex::new(Vec::new())),
+            pool_blacklist: PoolBlacklist::new(PoolBlacklistConfig::default()),
+            profit_calibration: ProfitCalibration::default(),
+            mint_info,
+            pool_registry: PoolRegistry::from_config(pools).await?,
+            pool_states,
+            price_cache: BoundedCache::new(pools.price_cache_capacity, std::time::Duration::from_millis(pools.price_cache_ttl_ms)),
+            whirlpool_cache: BoundedCache::new(pools.cache_capacity, std::time::Duration::from_secs(3600)),
+            launches: BoundedCache::new(pools.cache_capacity, std::time::Duration::from_secs(600)),
+            dex_registry: DexRegistry::from_config(pools)?,
+            jupiter: JupiterQuoteClient::from_config(&config.prices)?,
             opportunity_threshold: 0.005, // 0.005 SOL minimum threshold
+            position_size_sol: config.strategies.position_size_limit_sol, // Same limit the risk manager enforces per position
+            triangular_min_liquidity_sol: config.strategies.triangular_min_liquidity_sol,
+            snipe_filters: SnipeFilters::from_config(&config.snipe),
+            pumpfun_filters: PumpFunFilters::from_config(&config.strategies),
+            fee_calculator: None,
+            rpc_manager,
         })
     }
     
+    // Shares the risk manager's blacklist, so pools it lists stop producing opportunities
+    pub fn with_pool_blacklist(mut self, pool_blacklist: PoolBlacklist) -> Self {
+        self.pool_blacklist = pool_blacklist;
+        self
+    }
+    
+    // Shares the metrics collector's calibration, so types and DEXes that keep realizing less
+    // than estimated need a larger estimate to clear the threshold
+    pub fn with_profit_calibration(mut self, profit_calibration: ProfitCalibration) -> Self {
+        self.profit_calibration = profit_calibration;
+        self
+    }
+    
+    pub fn with_fee_calculator(mut self, fee_calculator: Arc<FeeCalculator>) -> Self {
+        self.fee_calculator = Some(fee_calculator);
+        self
+    }
+    
+    pub fn mint_info_cache(&self) -> Arc<MintInfoCache> {
+        Arc::clone(&self.mint_info)
+    }
+    
+    pub fn pool_registry(&self) -> PoolRegistry {
+        self.pool_registry.clone()
+    }
+    
+    pub fn pool_watcher(&self) -> PoolWatcher {
+        self.pool_watcher.clone()
+    }
+    
+    // The creation behind a snipe on this pool, while it's still cached
+    pub async fn pool_launch(&self, pool_address: &str) -> Option<PoolLaunch> {
+        self.launches.get(pool_address).await
+    }
+    
+    // Pool state ages recorded since the last call
+    pub async fn take_pool_state_ages(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.pool_state_ages.lock().await)
+    }
+    
+    async fn record_pool_state_age(&self, pool_state: &PoolState) {
+        let age_ms = pool_state.last_updated.elapsed().map_or(0, |age| age.as_millis() as u64);
+        let mut ages = self.pool_state_ages.lock().await;
+        // Nobody draining (metrics disabled): keep the most recent window only
+        if ages.len() >= 10_000 {
+            ages.drain(..5_000);
+        }
+        ages.push(age_ms);
+    }
+    
+    pub async fn cache_stats(&self) -> Vec<(&'static str, CacheStats)> {
+        vec![
+            ("pool_states", self.pool_states.stats().await),
+            ("prices", self.price_cache.stats().await),
+            ("whirlpools", self.whirlpool_cache.stats().await),
+            ("launches", self.launches.stats().await),
+        ]
+    }
+    
+    // Drops expired entries so idle caches release memory between inserts
+    pub async fn sweep_caches(&self) {
+        self.pool_states.sweep().await;
+        self.price_cache.sweep().await;
+        self.whirlpool_cache.sweep().await;
+        self.launches.sweep().await;
+    }
+    
     pub async fn evaluate_opportunity(&self, transaction_data: &Value) -> Result<Option<crate::utils::enhanced_transaction_simulator::OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
         Logger::status_update("Evaluating MEV opportunity from transaction data");
         
+        // New pool launches are handled separately: there is no existing pool state to verify against
+        if let Some(new_pool) = pool_creation::detect_raydium_pool_creation(transaction_data) {
+            return self.evaluate_snipe_opportunity(new_pool).await;
+        }
+        
+        let pumpfun_instructions = pumpfun::decode_transaction(transaction_data);
+        if !pumpfun_instructions.is_empty() {
+            return self.evaluate_pumpfun_opportunity(pumpfun_instructions, transaction_data["slot"].as_u64()).await;
+        }
+        
         // Analyze the transaction to identify potential MEV opportunities
         let potential_opportunities = self.analyze_transaction_for_mev(transaction_data).await?;
         
@@ -81,7 +384,7 @@ impl OpportunityEvaluator {
         // Evaluate each potential opportunity
         for opportunity in potential_opportunities {
             // Check if the opportunity meets our minimum profitability threshold
-            if opportunity.estimated_profit >= self.opportunity_threshold {
+            if self.meets_profit_threshold(&opportunity).await && !self.on_blacklisted_pool(&opportunity).await {
                 Logger::status_update(&format!(
                     "MEV opportunity detected: type {:?}, estimated profit: {:.6} SOL", 
                     opportunity.opportunity_type, opportunity.estimated_profit
@@ -97,140 +400,405 @@ impl OpportunityEvaluator {
         Ok(None)
     }
     
-    async fn analyze_transaction_for_mev(&self, transaction_data: &Value) -> Result<Vec<crate::utils::enhanced_transaction_simulator::OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut opportunities = Vec::new();
+    async fn evaluate_snipe_opportunity(&self, new_pool: NewPoolInfo) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let usdc_price_in_sol = self.get_token_price("USDC").await?.price_in_sol;
+        let initial_liquidity_sol = new_pool.initial_liquidity_sol(usdc_price_in_sol);
+        
+        Logger::status_update(&format!(
+            "New Raydium pool detected: {} ({} / {}), initial liquidity {:.3} SOL",
+            new_pool.pool_address,
+            new_pool.base_mint,
+            new_pool.quote_mint,
+            initial_liquidity_sol
+        ));
+        
+        if let Some(reason) = self.snipe_filters.rejection_reason(&new_pool) {
+            Logger::status_update(&format!("Skipping new pool {}: {}", new_pool.pool_address, reason));
+            return Ok(None);
+        }
+        
+        self.launches.insert(new_pool.pool_address.clone(), PoolLaunch {
+            pool_address: new_pool.pool_address.clone(),
+            dex: "Raydium".to_string(),
+            creation_slot: new_pool.creation_slot,
+            initial_liquidity_sol: Some(initial_liquidity_sol),
+            lp_mint: Some(new_pool.lp_mint.clone()),
+        }).await;
+        
+        let opportunity = OpportunityDetails {
+            token_a: new_pool.quote_mint,
+            token_b: new_pool.base_mint,
+            trade_size: self.snipe_filters.buy_amount_lamports(),
+            estimated_profit: self.snipe_filters.buy_amount_sol * self.snipe_filters.target_return,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Snipe,
+            pool_address: Some(new_pool.pool_address),
+            route: Vec::new(),
+        };
+        if self.on_blacklisted_pool(&opportunity).await {
+            return Ok(None);
+        }
+        Ok(Some(opportunity))
+    }
+    
+    async fn evaluate_pumpfun_opportunity(&self, instructions: Vec<PumpFunInstruction>, slot: Option<u64>) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut best: Option<OpportunityDetails> = None;
         
-        // Analyze transaction instructions for potential MEV opportunities
-        if let Some(transaction) = transaction_data.get("transaction") {
-            if let Some(message) = transaction.get("message") {
-                if let Some(instructions) = message.get("instructions") {
-                    if let Some(instr_array) = instructions.as_array() {
-                        for instruction in instr_array {
-                            // Look for DEX swap instructions
-                            if let Some(accounts) = instruction.get("accounts").and_then(|v| v.as_array()) {
-                                if accounts.len() >= 4 {
-                                    // This looks like a swap instruction, check if we can arbitrage or frontrun
-                                    if let Some(opportunity) = self.identify_swap_opportunity(instruction, transaction_data).await? {
-                                        opportunities.push(opportunity);
-                                    }
-                                }
-                            }
-                        }
+        for instruction in instructions {
+            let candidate = match instruction {
+                PumpFunInstruction::Create { mint, bonding_curve, symbol, .. } => {
+                    Logger::status_update(&format!("pump.fun launch detected: {} ({})", symbol, mint));
+                    // Every curve starts from the same virtual reserves and has no LP, so only its age is filtered on
+                    self.launches.insert(bonding_curve.clone(), PoolLaunch {
+                        pool_address: bonding_curve.clone(),
+                        dex: "PumpFun".to_string(),
+                        creation_slot: slot,
+                        initial_liquidity_sol: None,
+                        lp_mint: None,
+                    }).await;
+                    Some(OpportunityDetails {
+                        token_a: WSOL_MINT.to_string(),
+                        token_b: mint,
+                        trade_size: self.snipe_filters.buy_amount_lamports(),
+                        estimated_profit: self.snipe_filters.buy_amount_sol * self.snipe_filters.target_return,
+                        dex: "PumpFun".to_string(),
+                        opportunity_type: OpportunityType::Snipe,
+                        pool_address: Some(bonding_curve),
+                        route: Vec::new(),
+                    })
+                },
+                PumpFunInstruction::Buy { mint, bonding_curve, token_amount, max_sol_cost } => {
+                    let buy_sol = max_sol_cost as f64 / 1_000_000_000.0;
+                    if buy_sol < self.pumpfun_filters.min_victim_buy_sol {
+                        continue;
+                    }
+                    
+                    let curve = BondingCurveState::decode(&self.rpc_manager.get_account_data(&bonding_curve).await?)?;
+                    if curve.complete {
+                        // Curve has migrated to Raydium; bonding-curve math no longer applies
+                        continue;
+                    }
+                    
+                    let max_frontrun = (self.pumpfun_filters.max_frontrun_sol * 1_000_000_000.0) as u64;
+                    match pumpfun::estimate_frontrun(&curve, token_amount, max_sol_cost, max_frontrun) {
+                        Some(estimate) if estimate.profit_lamports > 0 => {
+                            Logger::status_update(&format!(
+                                "pump.fun buy of {:.3} SOL on {}: frontrun {:.3} SOL for est. {:.6} SOL",
+                                buy_sol, mint, estimate.frontrun_sol as f64 / 1_000_000_000.0,
+                                estimate.profit_lamports as f64 / 1_000_000_000.0
+                            ));
+                            Some(OpportunityDetails {
+                                token_a: WSOL_MINT.to_string(),
+                                token_b: mint,
+                                trade_size: estimate.frontrun_sol,
+                                estimated_profit: estimate.profit_lamports as f64 / 1_000_000_000.0,
+                                dex: "PumpFun".to_string(),
+                                opportunity_type: OpportunityType::Frontrun,
+                                pool_address: Some(bonding_curve),
+                                route: Vec::new(),
+                            })
+                        },
+                        _ => None,
                     }
+                },
+                PumpFunInstruction::Sell { .. } => None,
+            };
+            
+            if let Some(candidate) = candidate {
+                if self.on_blacklisted_pool(&candidate).await {
+                    continue;
+                }
+                let is_better = best.as_ref().is_none_or(|b| candidate.estimated_profit > b.estimated_profit);
+                if is_better {
+                    best = Some(candidate);
                 }
             }
         }
         
-        // Check for token balance changes that might indicate arbitrage opportunities
-        if let Some(meta) = transaction_data.get("meta") {
-            if let Some(post_balances) = meta.get("postTokenBalances").and_then(|v| v.as_array()) {
-                if let Some(pre_balances) = meta.get("preTokenBalances").and_then(|v| v.as_array()) {
-                    if let Some(arb_opportunity) = self.identify_arbitrage_from_balances(pre_balances, post_balances).await? {
-                        opportunities.push(arb_opportunity);
-                    }
+        let Some(best) = best else { return Ok(None) };
+        Ok(self.meets_profit_threshold(&best).await.then_some(best))
+    }
+    
+    // Judged on the calibrated estimate; the opportunity itself keeps the raw one, which is what
+    // the calibration is measured against
+    async fn meets_profit_threshold(&self, opportunity: &OpportunityDetails) -> bool {
+        self.profit_calibration.calibrated_profit(opportunity).await >= self.opportunity_threshold
+    }
+    
+    // Whether the opportunity trades a blacklisted pool, directly or along its route
+    async fn on_blacklisted_pool(&self, opportunity: &OpportunityDetails) -> bool {
+        let pools = opportunity.pools();
+        match self.pool_blacklist.first_blacklisted(pools.iter().map(String::as_str)).await {
+            Some(pool) => {
+                Logger::status_update(&format!("Skipping {:?} opportunity: pool {} is blacklisted", opportunity.opportunity_type, pool));
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Minimum output the victim's swap on this pool accepts, i.e. how far we can move the price
+    // before its transaction fails; None when no swap on the pool decodes with a minimum output
+    pub fn victim_min_amount_out(&self, transaction_data: &Value, pool_address: &str) -> Option<u64> {
+        instruction_parser::all_instructions(transaction_data).iter()
+            .filter_map(|instruction| self.dex_registry.decode_swap(instruction, transaction_data))
+            .find_map(|swap| match swap {
+                DecodedSwap::Raydium(swap) if swap.pool_address == pool_address => Some(swap.min_amount_out()),
+                DecodedSwap::Whirlpool(swap) if swap.whirlpool == pool_address && swap.amount_specified_is_input => Some(swap.other_amount_threshold),
+                DecodedSwap::Meteora(swap) if swap.lb_pair == pool_address => Some(swap.min_amount_out),
+                _ => None,
+            })
+    }
+    
+    async fn analyze_transaction_for_mev(&self, transaction_data: &Value) -> Result<Vec<crate::utils::enhanced_transaction_simulator::OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut opportunities = Vec::new();
+        
+        // Swaps decoded by the registry's hooks carry the real amounts and mints; a swap on a
+        // known program it can't decode has neither, so it yields no opportunity
+        for instruction in instruction_parser::all_instructions(transaction_data) {
+            let opportunity = match self.dex_registry.decode_swap(&instruction, transaction_data) {
+                Some(DecodedSwap::Raydium(swap)) => self.raydium_swap_opportunity(&swap).await?,
+                Some(DecodedSwap::Whirlpool(swap)) => self.whirlpool_swap_opportunity(&swap).await?,
+                Some(DecodedSwap::Meteora(swap)) => self.dlmm_swap_opportunity(&swap).await?,
+                None => None,
+            };
+            if let Some(opportunity) = opportunity {
+                // A reference pool that can't be read costs the backrun, not the swap's own opportunity
+                match self.backrun_opportunity(&opportunity).await {
+                    Ok(Some(backrun)) => opportunities.push(backrun),
+                    Ok(None) => {}
+                    Err(e) => Logger::error_occurred(&format!("Failed to evaluate a backrun of pool {}: {}", opportunity.pool_address.as_deref().unwrap_or("unknown"), e)),
                 }
+                opportunities.push(opportunity);
             }
         }
         
+        // Check for token balance changes that might indicate arbitrage opportunities
+        if let Some(arb_opportunity) = self.identify_arbitrage_from_balances(transaction_data).await? {
+            opportunities.push(arb_opportunity);
+        }
+        
         Ok(opportunities)
     }
     
-    async fn identify_swap_opportunity(
-        &self, 
-        instruction: &Value, 
-        transaction_data: &Value
-    ) -> Result<Option<crate::utils::enhanced_transaction_simulator::OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
-        // Extract tokens involved in the swap
-        // In practice, this would decode the instruction data to determine input/output tokens
-        
-        // For now, let's simulate detecting a Jupiter swap
-        if let Some(program_id) = instruction.get("programId").and_then(|v| v.as_str()) {
-            // Check for known DEX program IDs (these are placeholders)
-            if program_id.contains("JUP") || program_id.contains("RAY") || program_id.contains("ORCA") {
-                // Extract token information from accounts
-                if let Some(accounts) = instruction.get("accounts").and_then(|v| v.as_array()) {
-                    if accounts.len() >= 4 { // Assume [user, input_token, output_token, dex_vault, ...]
-                        // In a real implementation, we'd decode the instruction data to get exact tokens
-                        // For now, use placeholder values
-                        let opportunity = crate::utils::enhanced_transaction_simulator::OpportunityDetails {
-                            token_a: "TOKEN_A".to_string(),
-                            token_b: "TOKEN_B".to_string(),
-                            trade_size: 1_000_000, // Placeholder
-                            estimated_profit: self.estimate_swap_profitability(transaction_data).await?,
-                            dex: self.get_dex_name_from_program_id(program_id),
-                            opportunity_type: crate::utils::enhanced_transaction_simulator::OpportunityType::Frontrun,
-                        };
-                        
-                        return Ok(Some(opportunity));
-                    }
+    async fn raydium_swap_opportunity(
+        &self,
+        swap: &RaydiumSwap,
+    ) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        // Without both mints there's no pair to quote or trade against
+        let (input_mint, output_mint) = match (&swap.input_mint, &swap.output_mint) {
+            (Some(input), Some(output)) => (input.clone(), output.clone()),
+            _ => {
+                Logger::status_update(&format!("Could not resolve mints for Raydium swap in pool {}", swap.pool_address));
+                return Ok(None);
+            }
+        };
+        
+        self.pool_registry.observe(PoolEntry {
+            dex: "Raydium".to_string(),
+            address: swap.pool_address.clone(),
+            token_a: input_mint.clone(),
+            token_b: output_mint.clone(),
+        }).await;
+        self.pool_watcher.record_activity(&swap.pool_address, "Raydium").await;
+        
+        let estimated_profit = self.estimate_swap_profitability(&swap.pool_address, "Raydium", &input_mint, swap.amount_in()).await;
+        
+        Ok(Some(OpportunityDetails {
+            token_a: input_mint,
+            token_b: output_mint,
+            trade_size: swap.amount_in(),
+            estimated_profit,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Frontrun,
+            pool_address: Some(swap.pool_address.clone()),
+            route: Vec::new(),
+        }))
+    }
+    
+    async fn whirlpool_swap_opportunity(
+        &self,
+        swap: &WhirlpoolSwap,
+    ) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let pool = match self.get_whirlpool_state(&swap.whirlpool).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                Logger::status_update(&format!("Could not load whirlpool {}: {}", swap.whirlpool, e));
+                return Ok(None);
+            }
+        };
+        
+        self.pool_registry.observe(PoolEntry {
+            dex: "Orca".to_string(),
+            address: swap.whirlpool.clone(),
+            token_a: pool.token_mint_a.clone(),
+            token_b: pool.token_mint_b.clone(),
+        }).await;
+        self.pool_watcher.record_activity(&swap.whirlpool, "Orca").await;
+        
+        let estimated_profit = self.estimate_swap_profitability(&swap.whirlpool, "Orca", swap.input_mint(&pool), swap.amount_in()).await;
+        
+        Ok(Some(OpportunityDetails {
+            token_a: swap.input_mint(&pool).to_string(),
+            token_b: swap.output_mint(&pool).to_string(),
+            trade_size: swap.amount_in(),
+            estimated_profit,
+            dex: "Orca".to_string(),
+            opportunity_type: OpportunityType::Frontrun,
+            pool_address: Some(swap.whirlpool.clone()),
+            route: Vec::new(),
+        }))
+    }
+    
+    async fn dlmm_swap_opportunity(
+        &self,
+        swap: &DlmmSwap,
+    ) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(input_mint), Some(output_mint)) = (swap.input_mint(), swap.output_mint()) else {
+            Logger::status_update(&format!("Could not resolve the input side of Meteora swap in pool {}", swap.lb_pair));
+            return Ok(None);
+        };
+        
+        self.pool_registry.observe(PoolEntry {
+            dex: "Meteora".to_string(),
+            address: swap.lb_pair.clone(),
+            token_a: swap.token_x_mint.clone(),
+            token_b: swap.token_y_mint.clone(),
+        }).await;
+        
+        let estimated_profit = self.estimate_swap_profitability(&swap.lb_pair, "Meteora", input_mint, swap.amount_in).await;
+        
+        Ok(Some(OpportunityDetails {
+            token_a: input_mint.to_string(),
+            token_b: output_mint.to_string(),
+            trade_size: swap.amount_in,
+            estimated_profit,
+            dex: "Meteora".to_string(),
+            opportunity_type: OpportunityType::Frontrun,
+            pool_address: Some(swap.lb_pair.clone()),
+            route: Vec::new(),
+        }))
+    }
+    
+    // Backrun of a decoded swap: the target leaves its pool priced away from the deepest other
+    // pool for the pair, and our swap in the opposite direction takes the difference
+    async fn backrun_opportunity(
+        &self,
+        target: &OpportunityDetails,
+    ) -> Result<Option<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(pool_address) = target.pool_address.as_deref() else { return Ok(None) };
+        let Some(pool) = self.get_pool_state(pool_address, &target.dex).await? else { return Ok(None) };
+        
+        let mut reference: Option<PoolState> = None;
+        for entry in self.pool_registry.pools_for_pair(&target.token_a, &target.token_b).await {
+            if entry.address == pool_address {
+                continue;
+            }
+            if let Some(candidate) = self.get_pool_state(&entry.address, &entry.dex).await? {
+                if reference.as_ref().is_none_or(|best| candidate.liquidity > best.liquidity) {
+                    reference = Some(candidate);
                 }
             }
         }
+        let Some(reference) = reference else { return Ok(None) };
         
-        Ok(None)
+        let Some((step, profit)) = plan_backrun(&pool, &reference, &target.token_a, target.trade_size) else { return Ok(None) };
+        let estimated_profit = self.input_amount_in_sol(&reference, &step.input_token, profit as i128).await?;
+        
+        Ok(Some(OpportunityDetails {
+            token_a: step.input_token.clone(),
+            token_b: step.output_token.clone(),
+            trade_size: step.amount_in,
+            estimated_profit,
+            dex: target.dex.clone(),
+            opportunity_type: OpportunityType::Backrun,
+            pool_address: Some(pool_address.to_string()),
+            route: vec![step],
+        }))
     }
     
-    fn get_dex_name_from_program_id(&self, program_id: &str) -> String {
-        match program_id {
-            id if id.contains("JUP") => "Jupiter".to_string(),
-            id if id.contains("RAY") => "Raydium".to_string(),
-            id if id.contains("ORCA") => "Orca".to_string(),
-            id if id.contains("SERUM") => "Serum".to_string(),
-            _ => "Unknown".to_string(),
+    // Whirlpool account, fetched once per pool; only its static fields (mints, vaults) are relied on
+    async fn get_whirlpool_state(&self, whirlpool: &str) -> Result<WhirlpoolState, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(state) = self.whirlpool_cache.get(whirlpool).await {
+            return Ok(state);
         }
+        
+        let data = self.rpc_manager.get_account_data(whirlpool).await?;
+        let state = WhirlpoolState::decode(&data)?;
+        self.whirlpool_cache.insert(whirlpool.to_string(), state.clone()).await;
+        Ok(state)
     }
     
-    async fn estimate_swap_profitability(&self, transaction_data: &Value) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Estimate profit potential from the swap
-        // This would analyze the expected market impact
+    // Profit (in SOL, before transaction fees) of sandwiching the decoded swap with up to our
+    // configured position, from the price movement it causes on the pool's curve. 0 when the
+    // pool state or a way to value the position is unavailable
+    async fn estimate_swap_profitability(&self, pool_address: &str, dex: &str, input_mint: &str, amount_in: u64) -> f64 {
+        let pool_state = match self.get_pool_state(pool_address, dex).await {
+            Ok(Some(pool_state)) => pool_state,
+            Ok(None) => return 0.0,
+            Err(e) => {
+                Logger::status_update(&format!("No pool state for {} pool {}: {}", dex, pool_address, e));
+                return 0.0;
+            }
+        };
+        let (reserve_in, reserve_out) = match pool_state.reserves_for_input(input_mint) {
+            Some(reserves) => reserves,
+            None => return 0.0,
+        };
         
-        // For now, we'll analyze the transaction's compute usage and fee to estimate value
-        if let Some(meta) = transaction_data.get("meta") {
-            // Check if the transaction paid high fees, which might indicate high-value activity
-            if let Some(fee) = meta["fee"].as_u64() {
-                // Convert fee from lamports to SOL and estimate 10% of that as potential MEV
-                let fee_sol = fee as f64 / 1_000_000_000.0;
-                return Ok(fee_sol * 10.0); // 10x the fee as potential profit
+        // Our position in input tokens
+        let pool_has_sol = pool_state.token_a == WSOL_MINT || pool_state.token_b == WSOL_MINT;
+        let max_front_in = if input_mint != WSOL_MINT && pool_has_sol {
+            // Priced through the pool itself: reserve_out is SOL
+            if reserve_out == 0 {
+                return 0.0;
             }
-        }
+            (self.position_size_sol * 1_000_000_000.0 * reserve_in as f64 / reserve_out as f64) as u64
+        } else {
+            match self.sol_equivalent_amount(input_mint, self.position_size_sol).await {
+                Ok(amount) => amount,
+                Err(_) => return 0.0,
+            }
+        };
         
-        Ok(0.001) // Default small estimate
+        let profit = amm_math::capturable_profit(amount_in, max_front_in, reserve_in, reserve_out, pool_state.fee_rate);
+        self.input_amount_in_sol(&pool_state, input_mint, profit as i128).await.unwrap_or(0.0)
     }
     
     async fn identify_arbitrage_from_balances(
         &self, 
-        pre_balances: &[Value], 
-        post_balances: &[Value]
+        transaction_data: &Value
     ) -> Result<Option<crate::utils::enhanced_transaction_simulator::OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
-        // Compare pre and post balances to detect potential arbitrage
+        // Compare the swapping wallet's own pre and post balances to detect potential arbitrage
         let mut opportunities = Vec::new();
         
-        for (pre, post) in pre_balances.iter().zip(post_balances.iter()) {
-            if let (Some(pre_amount), Some(post_amount)) = (
-                pre.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64()),
-                post.get("uiTokenAmount").and_then(|v| v.get("uiAmount")).and_then(|v| v.as_f64())
-            ) {
-                let change = post_amount - pre_amount;
-                if change.abs() > 0.001 { // Significant balance change
-                    // Check if this change represents an arbitrage opportunity
-                    if let Some(mint) = pre.get("mint").and_then(|v| v.as_str()) {
-                        // Get current prices to calculate potential profit
-                        let price_data = self.get_token_price(mint).await?;
-                        let estimated_profit = change.abs() * price_data.price_in_sol;
-                        
-                        if estimated_profit > self.opportunity_threshold {
-                            let opportunity = crate::utils::enhanced_transaction_simulator::OpportunityDetails {
-                                token_a: mint.to_string(),
-                                token_b: "SOL".to_string(), // Example: token to SOL swap
-                                trade_size: (post_amount.abs() * 1_000_000_000.0) as u64, // Convert to lamports
-                                estimated_profit,
-                                dex: "MultiDex".to_string(),
-                                opportunity_type: crate::utils::enhanced_transaction_simulator::OpportunityType::Arbitrage,
-                            };
-                            
-                            opportunities.push(opportunity);
-                        }
+        for balance_change in instruction_parser::fee_payer_balance_changes(transaction_data) {
+            let change = balance_change.ui_change();
+            if change.abs() > 0.001 { // Significant balance change
+                // Get current prices to calculate potential profit
+                let mint = balance_change.mint.as_str();
+                let price_data = match self.get_token_price(mint).await {
+                    Ok(price_data) => price_data,
+                    Err(e) => {
+                        Logger::status_update(&format!("Skipping balance change of {}: {}", mint, e));
+                        continue;
                     }
+                };
+                let estimated_profit = change.abs() * price_data.price_in_sol;
+                
+                if estimated_profit > self.opportunity_threshold {
+                    let opportunity = crate::utils::enhanced_transaction_simulator::OpportunityDetails {
+                        token_a: mint.to_string(),
+                        token_b: "SOL".to_string(), // Example: token to SOL swap
+                        trade_size: balance_change.change.unsigned_abs().min(u64::MAX as u128) as u64, // Raw token units
+                        estimated_profit,
+                        dex: "MultiDex".to_string(),
+                        opportunity_type: crate::utils::enhanced_transaction_simulator::OpportunityType::Arbitrage,
+                        pool_address: None,
+                        route: Vec::new(),
+                    };
+                    
+                    opportunities.push(opportunity);
                 }
             }
         }
@@ -249,99 +817,163 @@ impl OpportunityEvaluator {
         &self, 
         opportunity: &crate::utils::enhanced_transaction_simulator::OpportunityDetails
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // Verify the opportunity against real-time pool states and prices
-        let pool_state = self.get_pool_state(&opportunity.token_a, &opportunity.token_b).await?;
+        // Verify the opportunity against the real-time state of the pool the target swap uses
+        let pool_address = match &opportunity.pool_address {
+            Some(pool_address) => pool_address,
+            None => {
+                Logger::status_update("Cannot verify opportunity without a decoded pool address");
+                return Ok(false);
+            }
+        };
+        let pool_state = self.get_pool_state(pool_address, &opportunity.dex).await?;
         
-        if let Some(pool) = pool_state {
-            // Check if the pool has sufficient liquidity for the trade size
-            let min_liquidity_ratio = 10.0; // Require 10x more liquidity than trade size
-            
-            let trade_size_sol = opportunity.trade_size as f64 / 1_000_000_000.0;
-            let has_sufficient_liquidity = pool.liquidity >= trade_size_sol * min_liquidity_ratio;
-            
-            if has_sufficient_liquidity {
-                // Double-check profitability with current pool state
-                let verified_profit = self.calculate_realistic_profit(&pool, opportunity).await?;
-                
-                // Only approve if verified profit meets threshold
-                return Ok(verified_profit >= self.opportunity_threshold);
+        let Some(pool) = pool_state else { return Ok(false) };
+        
+        // Shallow or inactive pools show the best spreads and the worst fills
+        let liquidity_sol = self.pool_liquidity_in_sol(&pool).await;
+        let volume_24h_usd = self.pool_volume_24h_usd(&pool).await;
+        let trade_size_sol = self.input_amount_in_sol(&pool, &opportunity.token_a, opportunity.trade_size as i128).await
+            .unwrap_or(opportunity.trade_size as f64 / 1_000_000_000.0);
+        if let Err(rejection) = self.liquidity_filters.check(&opportunity.opportunity_type, liquidity_sol, volume_24h_usd, trade_size_sol) {
+            Logger::status_update(&format!("Skipping {:?} opportunity on pool {}: {}", opportunity.opportunity_type, pool.pool_address, rejection));
+            let mut rejections = self.filter_rejections.lock().await;
+            if rejections.len() >= 10_000 {
+                rejections.drain(..5_000); // Nobody draining; keep the most recent
             }
+            rejections.push((opportunity.opportunity_type.clone(), rejection));
+            return Ok(false);
         }
         
-        Ok(false)
+        // Double-check profitability with current pool state
+        let verified_profit = self.calculate_realistic_profit(&pool, opportunity).await?;
+        
+        // Only approve if verified profit meets threshold
+        Ok(verified_profit >= self.opportunity_threshold)
     }
     
-    pub async fn get_pool_state(&self, token_a: &str, token_b: &str) -> Result<Option<PoolState>, Box<dyn std::error::Error + Send + Sync>> {
-        let pool_key = format!("{}_{}", token_a, token_b);
-        
-        {
-            // First, try to get from cache
-            let pool_states = self.pool_states.read().await;
-            if let Some(cached) = pool_states.get(&pool_key) {
-                // Check if cache is still fresh (less than 1 second old)
-                if cached.last_updated.elapsed().unwrap_or_default().as_secs() < 1 {
-                    return Ok(Some(cached.clone()));
-                }
+    // Pools without a SOL side are valued through the price of one of their tokens
+    async fn pool_liquidity_in_sol(&self, pool: &PoolState) -> f64 {
+        if pool.liquidity > 0.0 {
+            return pool.liquidity;
+        }
+        for (token, reserve) in [(&pool.token_a, pool.reserve_a), (&pool.token_b, pool.reserve_b)] {
+            if let Ok(value) = self.value_in_sol(token, reserve as i128).await {
+                return 2.0 * value;
+            }
+        }
+        0.0
+    }
+    
+    // 24h volume of the pool's non-SOL token, when Birdeye is configured
+    async fn pool_volume_24h_usd(&self, pool: &PoolState) -> Option<f64> {
+        let token = if pool.token_a == WSOL_MINT { &pool.token_b } else { &pool.token_a };
+        match self.birdeye.volume_24h_usd(token).await {
+            Ok(volume) => volume,
+            Err(e) => {
+                Logger::status_update(&format!("24h volume for {} unavailable: {}", token, e));
+                None
             }
         }
+    }
+    
+    // Liquidity filter rejections since the last call
+    pub async fn take_filter_rejections(&self) -> Vec<(OpportunityType, FilterRejection)> {
+        std::mem::take(&mut *self.filter_rejections.lock().await)
+    }
+    
+    pub async fn get_pool_state(&self, pool_address: &str, dex: &str) -> Result<Option<PoolState>, Box<dyn std::error::Error + Send + Sync>> {
+        // First, try to get from cache
+        if let Some(cached) = self.pool_states.get(pool_address).await {
+            self.record_pool_state_age(&cached).await;
+            return Ok(Some(cached));
+        }
         
-        // Fetch fresh data from DEX APIs
-        let fresh_pool_state = self.fetch_fresh_pool_state(token_a, token_b).await?;
+        // Fetch fresh data from the pool account; no lock is held during the RPC
+        let fresh_pool_state = self.fetch_fresh_pool_state(pool_address, dex).await?;
         
         // Update cache
-        {
-            let mut pool_states = self.pool_states.write().await;
-            if let Some(state) = &fresh_pool_state {
-                pool_states.insert(pool_key, state.clone());
-            }
+        if let Some(state) = &fresh_pool_state {
+            self.record_pool_state_age(state).await;
+            self.pool_states.insert(pool_address.to_string(), state.clone()).await;
         }
         
         Ok(fresh_pool_state)
     }
     
-    async fn fetch_fresh_pool_state(&self, token_a: &str, token_b: &str) -> Result<Option<PoolState>, Box<dyn std::error::Error + Send + Sync>> {
-        // In a real implementation, this would fetch pool states from DEX APIs
-        // For now, return a simulated pool state
-        
-        // Simulate fetching from multiple DEXes like Jupiter, Raydium, Orca
-        let dexes_to_check = vec!["Jupiter", "Raydium", "Orca"];
-        
-        for dex in dexes_to_check {
-            if let Ok(pool_state) = self.fetch_pool_from_dex(dex, token_a, token_b).await {
-                return Ok(Some(pool_state));
+    async fn fetch_fresh_pool_state(&self, pool_address: &str, dex: &str) -> Result<Option<PoolState>, Box<dyn std::error::Error + Send + Sync>> {
+        match dex {
+            "Raydium" => Ok(Some(self.fetch_raydium_pool_state(pool_address).await?)),
+            "Orca" => Ok(Some(self.fetch_whirlpool_pool_state(pool_address).await?)),
+            "Meteora" => Ok(Some(self.fetch_dlmm_pool_state(pool_address).await?)),
+            // Orderbooks have no reserves to model; see get_order_book
+            "Phoenix" => Ok(None),
+            _ => {
+                Logger::status_update(&format!("No pool account decoder for {} pool {}", dex, pool_address));
+                Ok(None)
             }
         }
+    }
+    
+    async fn fetch_raydium_pool_state(&self, pool_address: &str) -> Result<PoolState, Box<dyn std::error::Error + Send + Sync>> {
+        let amm = AmmInfo::decode(&self.rpc_manager.get_account_data(pool_address).await?)?;
         
-        // If no pools found from direct DEX queries, return placeholder
-        Ok(Some(PoolState {
-            token_a: token_a.to_string(),
-            token_b: token_b.to_string(),
-            reserve_a: 1_000_000_000_000, // 1000 tokens (placeholder)
-            reserve_b: 1_000_000_000_000,
-            liquidity: 1000.0, // 1000 SOL worth of liquidity
-            fee_rate: 0.0025, // 0.25% fee
-            last_updated: std::time::SystemTime::now(),
-        }))
+        let vaults = [amm.coin_vault.clone(), amm.pc_vault.clone()];
+        let vault_data = self.rpc_manager.get_multiple_accounts_data(&vaults).await?;
+        let vault_amount = |index: usize| -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            let data = vault_data[index].as_ref()
+                .ok_or_else(|| format!("Vault {} of Raydium pool {} not found", vaults[index], pool_address))?;
+            raydium_amm::token_account_amount(data)
+        };
+        
+        Ok(PoolState::from_raydium(pool_address, &amm, vault_amount(0)?, vault_amount(1)?))
     }
     
-    async fn fetch_pool_from_dex(&self, dex: &str, token_a: &str, token_b: &str) -> Result<PoolState, Box<dyn std::error::Error + Send + Sync>> {
-        // In a real implementation, this would make actual API calls to DEXes
-        // For now, return a simulated result based on the DEX
-        
-        Ok(PoolState {
-            token_a: token_a.to_string(),
-            token_b: token_b.to_string(),
-            reserve_a: 1_000_000_000_000,
-            reserve_b: 1_000_000_000_000,
-            liquidity: match dex {
-                "Jupiter" => 5000.0,
-                "Raydium" => 3000.0,
-                "Orca" => 2000.0,
-                _ => 1000.0,
-            },
-            fee_rate: 0.0025, // Standard 0.25% fee
-            last_updated: std::time::SystemTime::now(),
-        })
+    async fn fetch_whirlpool_pool_state(&self, pool_address: &str) -> Result<PoolState, Box<dyn std::error::Error + Send + Sync>> {
+        let whirlpool = WhirlpoolState::decode(&self.rpc_manager.get_account_data(pool_address).await?)?;
+        self.whirlpool_cache.insert(pool_address.to_string(), whirlpool.clone()).await;
+        Ok(PoolState::from_whirlpool(pool_address, &whirlpool))
+    }
+    
+    async fn fetch_dlmm_pool_state(&self, pool_address: &str) -> Result<PoolState, Box<dyn std::error::Error + Send + Sync>> {
+        let pair = LbPairState::decode(&self.rpc_manager.get_account_data(pool_address).await?)?;
+        
+        let vaults = [pair.reserve_x.clone(), pair.reserve_y.clone()];
+        let vault_data = self.rpc_manager.get_multiple_accounts_data(&vaults).await?;
+        let vault_amount = |index: usize| -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            let data = vault_data[index].as_ref()
+                .ok_or_else(|| format!("Reserve {} of Meteora pool {} not found", vaults[index], pool_address))?;
+            raydium_amm::token_account_amount(data)
+        };
+        
+        Ok(PoolState::from_dlmm(pool_address, &pair, vault_amount(0)?, vault_amount(1)?))
+    }
+    
+    // Books move with every order, so they're read fresh rather than cached
+    pub async fn get_order_book(&self, market: &str) -> Result<OrderBook, Box<dyn std::error::Error + Send + Sync>> {
+        OrderBook::decode(market, &self.rpc_manager.get_account_data(market).await?)
+    }
+    
+    // Output of selling amount_in of input_mint on a DLMM pool, walking its bins from the active
+    // one across the bin arrays a swap would pass
+    pub async fn quote_dlmm(&self, pool_address: &str, input_mint: &str, amount_in: u64) -> Result<(LbPairState, DlmmQuote), Box<dyn std::error::Error + Send + Sync>> {
+        let pair = LbPairState::decode(&self.rpc_manager.get_account_data(pool_address).await?)?;
+        let swap_for_y = if input_mint == pair.token_x_mint {
+            true
+        } else if input_mint == pair.token_y_mint {
+            false
+        } else {
+            return Err(format!("Meteora pool {} doesn't hold {}", pool_address, input_mint).into());
+        };
+        
+        let arrays = meteora_dlmm::swap_bin_arrays(pool_address, pair.active_id, swap_for_y, meteora_dlmm::SWAP_BIN_ARRAYS)?;
+        let mut bins = BTreeMap::new();
+        // Arrays nobody has initialized hold no liquidity; the walk stops there
+        for data in self.rpc_manager.get_multiple_accounts_data(&arrays).await?.into_iter().flatten() {
+            bins.extend(BinArray::decode(&data)?.bins_by_id());
+        }
+        
+        let quote = meteora_dlmm::quote(&pair, &bins, amount_in, swap_for_y);
+        Ok((pair, quote))
     }
     
     async fn calculate_realistic_profit(
@@ -349,31 +981,53 @@ impl OpportunityEvaluator {
         pool_state: &PoolState, 
         opportunity: &crate::utils::enhanced_transaction_simulator::OpportunityDetails
     ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Calculate realistic profit considering slippage and fees
-        let trade_size = opportunity.trade_size as f64 / 1_000_000_000.0;
+        // A backrun was already sized and valued against its reference pool
+        if matches!(opportunity.opportunity_type, OpportunityType::Backrun) {
+            let total_fees = self.estimate_transaction_fees().await?;
+            return Ok((opportunity.estimated_profit - total_fees).max(0.0));
+        }
         
-        // Calculate slippage based on trade size to pool size ratio
-        let slippage = (trade_size / pool_state.liquidity) * 0.1; // 10% of trade_to_pool ratio as slippage
+        // Replay buying ahead of the target swap and selling right after it on the pool's curve
+        let (reserve_in, reserve_out) = match pool_state.reserves_for_input(&opportunity.token_a) {
+            Some(reserves) => reserves,
+            None => return Ok(0.0),
+        };
+        let front_in = opportunity.trade_size; // Matched to the target until sizing is optimized
+        let profit = amm_math::sandwich_profit(front_in, opportunity.trade_size, reserve_in, reserve_out, pool_state.fee_rate);
         
-        // Calculate expected output considering slippage
-        let expected_output = opportunity.estimated_profit * (1.0 - slippage);
+        let profit_sol = self.input_amount_in_sol(pool_state, &opportunity.token_a, profit).await?;
         
         // Subtract fees
         let total_fees = self.estimate_transaction_fees().await?;
-        let net_profit = expected_output - total_fees;
+        let net_profit = profit_sol - total_fees;
         
         Ok(net_profit.max(0.0)) // Never return negative profit
     }
     
+    // Value an amount of the pool's input token in SOL, through the pool's own price when the
+    // other side is SOL
+    async fn input_amount_in_sol(&self, pool_state: &PoolState, input_mint: &str, amount: i128) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        if input_mint == WSOL_MINT {
+            return Ok(amount as f64 / 1_000_000_000.0);
+        }
+        if let Some((reserve_in, reserve_out)) = pool_state.reserves_for_input(input_mint) {
+            if (pool_state.token_a == WSOL_MINT || pool_state.token_b == WSOL_MINT) && reserve_in > 0 {
+                return Ok(amount as f64 * (reserve_out as f64 / reserve_in as f64) / 1_000_000_000.0);
+            }
+        }
+        // Neither side is SOL: convert to whole tokens and price them
+        self.value_in_sol(input_mint, amount).await
+    }
+    
     async fn estimate_transaction_fees(&self) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-        // Estimate fees using the fee_calculator module
-        use crate::utils::fee_calculator::FeeCalculator;
-        
-        let temp_rpc = self.rpc_manager.as_ref().clone();
-        let fee_calc = FeeCalculator::new(temp_rpc).await?;
+        // Estimate fees using the fee_calculator module; one with default fee settings when none was given
+        let fee_calc = match &self.fee_calculator {
+            Some(fee_calculator) => Arc::clone(fee_calculator),
+            None => Arc::new(FeeCalculator::new(Arc::clone(&self.rpc_manager), &Config::default()).await?),
+        };
         
         // Calculate fees for a typical MEV transaction
-        let fee_estimation = fee_calc.calculate_dynamic_fees(0.01).await?;
+        let fee_estimation = fee_calc.calculate_dynamic_fees(0.01, None, &[], &OpportunityType::Other).await?;
         
         Ok(fee_estimation.total_execution_cost)
     }
@@ -390,7 +1044,7 @@ impl OpportunityEvaluator {
         let mut quotes = Vec::new();
         
         // Get quotes from various DEXes
-        if let Ok(jupiter_quote) = self.get_jupiter_quote(input_token, output_token, amount_in).await {
+        if let Some(jupiter_quote) = self.get_jupiter_quote(input_token, output_token, amount_in).await {
             quotes.push(jupiter_quote);
         }
         
@@ -402,6 +1056,14 @@ impl OpportunityEvaluator {
             quotes.push(quote);
         }
         
+        if let Ok(quote) = self.get_meteora_quote(input_token, output_token, amount_in).await {
+            quotes.push(quote);
+        }
+        
+        if let Ok(quote) = self.get_phoenix_quote(input_token, output_token, amount_in).await {
+            quotes.push(quote);
+        }
+        
         if let Ok(serum_quote) = self.get_serum_quote(input_token, output_token, amount_in).await {
             quotes.push(serum_quote);
         }
@@ -420,40 +1082,94 @@ impl OpportunityEvaluator {
         }
     }
     
-    async fn get_jupiter_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
-        // In a real implementation, this would call Jupiter's API
-        // For now, return a simulated quote
-        Ok(SwapQuote {
-            input_amount: amount_in,
-            output_amount: amount_in, // Placeholder - in reality would be calculated based on reserves
-            slippage: 0.005, // 0.5% slippage
-            route: vec!["Jupiter".to_string()],
-            price_impact: 0.003, // 0.3% price impact
+    // None when Jupiter has no route, rate limits us or doesn't answer in time
+    async fn get_jupiter_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Option<SwapQuote> {
+        let quote = self.jupiter.quote(input_token, output_token, amount_in).await?;
+        Some(SwapQuote {
+            input_amount: quote.in_amount,
+            output_amount: quote.out_amount,
+            slippage: quote.slippage_bps as f64 / 10_000.0,
+            route: quote.route,
+            price_impact: quote.price_impact,
         })
     }
     
     async fn get_raydium_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
-        // Simulated Raydium quote
+        self.get_pool_quote("Raydium", input_token, output_token, amount_in).await
+    }
+    
+    async fn get_orca_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_pool_quote("Orca", input_token, output_token, amount_in).await
+    }
+    
+    // DLMM liquidity is in discrete bins, so the quote walks them rather than using reserves
+    async fn get_meteora_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_address = self.pool_registry.pools_for_pair(input_token, output_token).await
+            .into_iter()
+            .find(|pool| pool.dex == "Meteora")
+            .map(|pool| pool.address)
+            .ok_or_else(|| format!("No known Meteora pool for {} -> {}", input_token, output_token))?;
+        
+        let (pair, quote) = self.quote_dlmm(&pool_address, input_token, amount_in).await?;
+        let spot_price = pair.bin_price(pair.active_id);
+        let at_spot = |amount: u64| if input_token == pair.token_x_mint { amount as f64 * spot_price } else { amount as f64 / spot_price };
+        let shortfall = |expected: f64| if expected > 0.0 { (1.0 - quote.amount_out as f64 / expected).max(0.0) } else { 0.0 };
+        
         Ok(SwapQuote {
-            input_amount: amount_in,
-            output_amount: amount_in,
-            slippage: 0.007, // 0.7% slippage
-            route: vec!["Raydium".to_string()],
-            price_impact: 0.005, // 0.5% price impact
+            input_amount: quote.amount_in,
+            output_amount: quote.amount_out,
+            slippage: shortfall(at_spot(quote.amount_in)),
+            route: vec!["Meteora".to_string()],
+            price_impact: shortfall(at_spot(quote.amount_in - quote.fee)),
         })
     }
     
-    async fn get_orca_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
-        // Simulated Orca quote
+    // Size-aware fill against a Phoenix book, measured against its top of book
+    async fn get_phoenix_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
+        let market = self.pool_registry.pools_for_pair(input_token, output_token).await
+            .into_iter()
+            .find(|pool| pool.dex == "Phoenix")
+            .map(|pool| pool.address)
+            .ok_or_else(|| format!("No known Phoenix market for {} -> {}", input_token, output_token))?;
+        
+        let book = self.get_order_book(&market).await?;
+        let fill = book.fill(input_token, amount_in)
+            .filter(|fill| fill.levels > 0)
+            .ok_or_else(|| format!("Phoenix market {} has no liquidity for {}", market, input_token))?;
+        let top = if fill.side == Side::Ask { book.best_bid() } else { book.best_ask() }
+            .map(|level| book.price(level.price_in_ticks))
+            .ok_or("Empty book side")?;
+        let at_top = |amount: u64| if fill.side == Side::Ask { amount as f64 * top } else { amount as f64 / top };
+        let shortfall = |out: u64, expected: f64| if expected > 0.0 { (1.0 - out as f64 / expected).max(0.0) } else { 0.0 };
+        // Fees come off the proceeds of a sell and out of the budget of a buy
+        let (gross_in, gross_out) = match fill.side {
+            Side::Ask => (fill.amount_in, fill.amount_out + fill.fee),
+            Side::Bid => (fill.amount_in - fill.fee, fill.amount_out),
+        };
+        
         Ok(SwapQuote {
-            input_amount: amount_in,
-            output_amount: amount_in,
-            slippage: 0.006, // 0.6% slippage
-            route: vec!["Orca".to_string()],
-            price_impact: 0.004, // 0.4% price impact
+            input_amount: fill.amount_in,
+            output_amount: fill.amount_out,
+            slippage: shortfall(fill.amount_out, at_top(fill.amount_in)),
+            route: vec!["Phoenix".to_string()],
+            price_impact: shortfall(gross_out, at_top(gross_in)),
         })
     }
     
+    // Quote against the reserves of a known pool of the pair on the given DEX
+    async fn get_pool_quote(&self, dex: &str, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_address = self.pool_registry.pools_for_pair(input_token, output_token).await
+            .into_iter()
+            .find(|pool| pool.dex == dex)
+            .map(|pool| pool.address)
+            .ok_or_else(|| format!("No known {} pool for {} -> {}", dex, input_token, output_token))?;
+        
+        let pool = self.get_pool_state(&pool_address, dex).await?
+            .ok_or_else(|| format!("{} pool {} unavailable", dex, pool_address))?;
+        pool.quote(input_token, amount_in)
+            .ok_or_else(|| format!("{} pool {} doesn't hold {}", dex, pool_address, input_token).into())
+    }
+    
     async fn get_serum_quote(&self, input_token: &str, output_token: &str, amount_in: u64) -> Result<SwapQuote, Box<dyn std::error::Error + Send + Sync>> {
         // Simulated Serum quote
         Ok(SwapQuote {
@@ -470,19 +1186,37 @@ impl OpportunityEvaluator {
         
         let mut opportunities = Vec::new();
         
-        // Get all available token pairs across DEXes
-        let token_pairs = self.get_all_token_pairs().await?;
-        
-        for (token_a, token_b) in token_pairs {
-            // Get quotes from multiple DEXes for the same pair
-            let dexes = vec!["Jupiter", "Raydium", "Orca"];
+        // Pairs with more than one known pool
+        for ((token_a, token_b), pools) in self.pool_registry.arbitrage_pairs().await {
+            // Get current pool states and books for price comparison
+            let mut venues = Vec::new();
+            for pool in &pools {
+                if self.pool_blacklist.is_blacklisted(&pool.address).await {
+                    continue;
+                }
+                let venue = if pool.dex == "Phoenix" {
+                    self.get_order_book(&pool.address).await.map(|book| Some(Venue::Book(book)))
+                } else {
+                    self.get_pool_state(&pool.address, &pool.dex).await.map(|state| state.map(Venue::Pool))
+                };
+                match venue {
+                    Ok(Some(venue)) => venues.push(venue),
+                    Ok(None) => {}
+                    Err(e) => Logger::status_update(&format!("Skipping {} pool {}: {}", pool.dex, pool.address, e)),
+                }
+            }
             
-            // Get current pool states for price comparison
-            if let Ok(pool_a) = self.fetch_pool_from_dex(&dexes[0], &token_a, &token_b).await {
-                if let Ok(pool_b) = self.fetch_pool_from_dex(&dexes[1], &token_a, &token_b).await {
+            for (i, venue_a) in venues.iter().enumerate() {
+                for venue_b in &venues[i + 1..] {
+                    // Two book legs would take two IOC orders; only pool-vs-pool and pool-vs-book trade
+                    if matches!((venue_a, venue_b), (Venue::Book(_), Venue::Book(_))) {
+                        continue;
+                    }
                     // Calculate potential arbitrage profit
-                    if let Some(arb_opportunity) = self.calculate_arbitrage_profit(&pool_a, &pool_b, &dexes[0], &dexes[1], &token_a, &token_b).await? {
-                        opportunities.push(arb_opportunity);
+                    match self.calculate_arbitrage_profit(venue_a, venue_b, &token_a, &token_b).await {
+                        Ok(Some(arb_opportunity)) => opportunities.push(arb_opportunity),
+                        Ok(None) => {}
+                        Err(e) => Logger::status_update(&format!("Could not price {} / {} arbitrage: {}", token_a, token_b, e)),
                     }
                 }
             }
@@ -496,62 +1230,96 @@ impl OpportunityEvaluator {
         Ok(opportunities)
     }
     
-    async fn get_all_token_pairs(&self) -> Result<Vec<(String, String)>, Box<dyn std::error::Error + Send + Sync>> {
-        // In a real implementation, this would fetch all supported token pairs
-        // For now, return some common pairs
-        Ok(vec![
-            ("SOL".to_string(), "USDC".to_string()),
-            ("SOL".to_string(), "USDT".to_string()),
-            ("USDC".to_string(), "USDT".to_string()),
-            ("SOL".to_string(), "BONK".to_string()),
-            ("JUP".to_string(), "SOL".to_string()),
-        ])
+    // SOL -> X -> Y -> SOL cycles over every registry pool, sized at 1 SOL
+    pub async fn find_triangular_opportunities(&self) -> Result<Vec<ArbitrageOpportunity>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut states = Vec::new();
+        for pool in self.pool_registry.pools().await {
+            if self.pool_blacklist.is_blacklisted(&pool.address).await {
+                continue;
+            }
+            if let Ok(Some(state)) = self.get_pool_state(&pool.address, &pool.dex).await {
+                states.push(state);
+            }
+        }
+        
+        let routes = triangular_arb::find_triangular_routes(&states, 1_000_000_000, self.triangular_min_liquidity_sol);
+        if routes.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        // One bundle regardless of the number of legs
+        let fees = self.estimate_transaction_fees().await?;
+        let opportunities = routes.into_iter()
+            .map(|route| {
+                let estimated_profit = route.profit_lamports() as f64 / 1_000_000_000.0 - fees;
+                ArbitrageOpportunity {
+                    input_token: WSOL_MINT.to_string(),
+                    output_token: route.steps[0].output_token.clone(),
+                    dex_a: route.steps[0].dex.clone(),
+                    dex_b: route.steps[route.steps.len() - 1].dex.clone(),
+                    amount_in: route.amount_in,
+                    expected_out_a: route.steps[0].expected_out,
+                    expected_out_b: route.amount_out,
+                    estimated_profit,
+                    confidence_score: 0.7, // Three legs to land instead of two
+                    route: route.steps,
+                }
+            })
+            .filter(|opportunity| opportunity.estimated_profit >= self.opportunity_threshold)
+            .collect();
+        
+        Ok(opportunities)
     }
     
     async fn calculate_arbitrage_profit(
         &self,
-        pool_a: &PoolState,
-        pool_b: &PoolState,
-        dex_a: &str,
-        dex_b: &str,
+        venue_a: &Venue,
+        venue_b: &Venue,
         token_a: &str,
         token_b: &str
     ) -> Result<Option<ArbitrageOpportunity>, Box<dyn std::error::Error + Send + Sync>> {
-        // Calculate potential profit by buying low on one DEX and selling high on another
-        let amount_in = 1_000_000_000u64; // 1 SOL equivalent in lamports
-        
-        // Calculate prices on each DEX
-        let price_a = pool_a.reserve_b as f64 / pool_a.reserve_a as f64;
-        let price_b = pool_b.reserve_b as f64 / pool_b.reserve_a as f64;
+        // Buy token_b on one venue with 1 SOL worth of token_a and sell it back on the other
+        let amount_in = self.sol_equivalent_amount(token_a, 1.0).await?;
         
         // Determine arbitrage direction
-        let (buy_dex, sell_dex, buy_price, sell_price) = if price_a < price_b {
-            (dex_a, dex_b, price_a, price_b)
-        } else {
-            (dex_b, dex_a, price_b, price_a)
-        };
-        
-        // Calculate profit potential
-        let expected_profit = (sell_price - buy_price) * (amount_in as f64 / 1_000_000_000.0);
+        let (a_first, expected_out_a, expected_out_b) = best_round_trip(venue_a, venue_b, token_a, token_b, amount_in)
+            .ok_or("Venue doesn't hold the pair")?;
+        let (buy_venue, sell_venue) = if a_first { (venue_a, venue_b) } else { (venue_b, venue_a) };
+        let has_book_leg = matches!(venue_a, Venue::Book(_)) || matches!(venue_b, Venue::Book(_));
         
-        // Account for fees and slippage
-        let fees_a = pool_a.fee_rate * (amount_in as f64 / 1_000_000_000.0);
-        let fees_b = pool_b.fee_rate * (amount_in as f64 / 1_000_000_000.0);
-        let total_fees = fees_a + fees_b;
-        
-        let net_profit = expected_profit - total_fees;
+        let gross_profit = self.value_in_sol(token_a, expected_out_b as i128 - amount_in as i128).await?;
+        let net_profit = gross_profit - self.estimate_transaction_fees().await?;
         
         if net_profit > self.opportunity_threshold {
             let arb_opp = ArbitrageOpportunity {
                 input_token: token_a.to_string(),
                 output_token: token_b.to_string(),
-                dex_a: buy_dex.to_string(),
-                dex_b: sell_dex.to_string(),
+                dex_a: format!("{}:{}", buy_venue.dex(), buy_venue.address()),
+                dex_b: format!("{}:{}", sell_venue.dex(), sell_venue.address()),
                 amount_in,
-                expected_out_a: (amount_in as f64 * buy_price) as u64,
-                expected_out_b: (amount_in as f64 * sell_price) as u64,
+                expected_out_a,
+                expected_out_b,
                 estimated_profit: net_profit,
-                confidence_score: 0.8, // High confidence for basic arb
+                // High confidence for basic arb; an IOC leg can come back short if the book moves
+                confidence_score: if has_book_leg { 0.7 } else { 0.8 },
+                route: vec![
+                    RouteStep {
+                        dex: buy_venue.dex().to_string(),
+                        input_token: token_a.to_string(),
+                        output_token: token_b.to_string(),
+                        pool_address: buy_venue.address().to_string(),
+                        amount_in,
+                        expected_out: expected_out_a,
+                    },
+                    RouteStep {
+                        dex: sell_venue.dex().to_string(),
+                        input_token: token_b.to_string(),
+                        output_token: token_a.to_string(),
+                        pool_address: sell_venue.address().to_string(),
+                        amount_in: expected_out_a,
+                        expected_out: expected_out_b,
+                    },
+                ],
             };
             
             Ok(Some(arb_opp))
@@ -560,44 +1328,235 @@ impl OpportunityEvaluator {
         }
     }
     
-    async fn get_token_price(&self, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
+    // Raw amount of token worth the given SOL
+    async fn sol_equivalent_amount(&self, token: &str, sol: f64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        if token == WSOL_MINT {
+            return Ok(mint_info::ui_to_raw(sol, 9));
+        }
+        let decimals = self.mint_info.decimals(token).await?;
+        let price = self.get_token_price(token).await?;
+        if price.price_in_sol <= 0.0 {
+            return Err(format!("No usable price for {}", token).into());
+        }
+        Ok(mint_info::ui_to_raw(sol / price.price_in_sol, decimals))
+    }
+    
+    // SOL value of a (possibly negative) raw token amount
+    pub async fn value_in_sol(&self, token: &str, raw_amount: i128) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let decimals = if token == WSOL_MINT { 9 } else { self.mint_info.decimals(token).await? };
+        let price_in_sol = if token == WSOL_MINT { 1.0 } else { self.get_token_price(token).await?.price_in_sol };
+        let ui_amount = mint_info::raw_to_ui(raw_amount.unsigned_abs().min(u64::MAX as u128) as u64, decimals);
+        Ok(ui_amount * price_in_sol * if raw_amount < 0 { -1.0 } else { 1.0 })
+    }
+    
+    pub async fn get_token_price(&self, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
         // Try to get from cache first
-        {
-            let price_cache = self.price_cache.read().await;
-            if let Some(cached) = price_cache.get(token) {
-                // Check if cache is still fresh
-                if cached.last_updated.elapsed().unwrap_or_default().as_secs() < 5 { // 5 seconds
-                    return Ok(cached.clone());
-                }
-            }
+        if let Some(cached) = self.price_cache.get(token).await {
+            return Ok(cached);
         }
         
         // Fetch fresh price data
         let fresh_price = self.fetch_fresh_price(token).await?;
         
         // Update cache
-        {
-            let mut price_cache = self.price_cache.write().await;
-            price_cache.insert(token.to_string(), fresh_price.clone());
-        }
+        self.price_cache.insert(token.to_string(), fresh_price.clone()).await;
         
         Ok(fresh_price)
     }
     
     async fn fetch_fresh_price(&self, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
-        // In a real implementation, this would fetch from price APIs
-        // For now, return simulated prices
-        Ok(PriceData {
-            token: token.to_string(),
-            price_in_sol: match token {
-                "SOL" => 1.0,
-                "USDC" | "USDT" => 0.0004, // ~$0.0004 per token if SOL = $150
-                "JUP" => 0.002, // ~$0.30 per JUP if SOL = $150
-                _ => 0.0001, // Default small amount
-            },
-            price_in_usd: 0.0, // Placeholder
-            volume_24h: 0.0,   // Placeholder
-            last_updated: std::time::SystemTime::now(),
-        })
+        self.prices.get_price(token).await
+    }
+    
+    // Price lookups served by each source since startup
+    pub async fn price_source_counts(&self) -> std::collections::BTreeMap<&'static str, u64> {
+        self.prices.served_counts().await
+    }
+}
+
+// Swap back through a pool right after a target swap of target_amount_in target_input, sized so
+// the pool returns to the reference pool's price. Returns the step and its profit in raw units
+// of the step's input token (the target's output); None when the gap doesn't cover the fee
+pub fn plan_backrun(pool: &PoolState, reference: &PoolState, target_input: &str, target_amount_in: u64) -> Option<(RouteStep, f64)> {
+    let side = SwapSide::selling(pool, target_input)?;
+    let our_input = if target_input == pool.token_a { &pool.token_b } else { &pool.token_a };
+    
+    // Reserves after the target, seen from our side of the swap
+    let displaced = DexApi::price_impact(pool, side, target_amount_in)?.pool_after(pool);
+    let (displaced_in, displaced_out) = displaced.reserves_for_input(our_input)?;
+    
+    // Target's input token priced in our input token on the undisturbed venue
+    let (reference_out, reference_in) = reference.reserves_for_input(target_input)?;
+    if reference_out == 0 {
+        return None;
     }
-}
\ No newline at end of file
+    let output_price = reference_in as f64 / reference_out as f64;
+    
+    let amount_in = amm_math::optimal_backrun_amount(displaced_in, displaced_out, pool.fee_rate, output_price);
+    if amount_in == 0 {
+        return None;
+    }
+    let profit = amm_math::backrun_profit(amount_in, displaced_in, displaced_out, pool.fee_rate, output_price);
+    if profit <= 0.0 {
+        return None;
+    }
+    
+    Some((RouteStep {
+        dex: pool.dex.clone(),
+        input_token: our_input.clone(),
+        output_token: target_input.to_string(),
+        pool_address: pool.pool_address.clone(),
+        amount_in,
+        expected_out: amm_math::get_amount_out(amount_in, displaced_in, displaced_out, pool.fee_rate),
+    }, profit))
+}
+
+// SOL per whole token from the reserves of a SOL-paired pool
+pub fn pool_implied_price_in_sol(pool: &PoolState, token: &str, decimals: u8) -> Option<f64> {
+    if token == WSOL_MINT {
+        return None;
+    }
+    let (reserve_token, reserve_sol) = match pool.reserves_for_input(token) {
+        Some(reserves) if pool.token_a == WSOL_MINT || pool.token_b == WSOL_MINT => reserves,
+        _ => return None,
+    };
+    if reserve_token == 0 {
+        return None;
+    }
+    Some(mint_info::raw_to_ui(reserve_sol, 9) / mint_info::raw_to_ui(reserve_token, decimals))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::phoenix::BookLevel;
+    use crate::utils::pool_creation::USDC_MINT;
+
+    fn amm(coin_mint: &str, pc_mint: &str) -> AmmInfo {
+        AmmInfo {
+            coin_decimals: 6,
+            pc_decimals: 9,
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            coin_need_take_pnl: 0,
+            pc_need_take_pnl: 2_000_000_000,
+            coin_vault: "CoinVau1t".to_string(),
+            pc_vault: "PcVau1t".to_string(),
+            coin_mint: coin_mint.to_string(),
+            pc_mint: pc_mint.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_raydium_pool_state_uses_vault_balances() {
+        let pool = PoolState::from_raydium("Poo1", &amm(USDC_MINT, WSOL_MINT), 15_000_000_000, 102_000_000_000);
+        assert_eq!(pool.pool_address, "Poo1");
+        assert_eq!((pool.reserve_a, pool.reserve_b), (15_000_000_000, 100_000_000_000));
+        assert!((pool.fee_rate - 0.0025).abs() < 1e-12);
+        assert!((pool.liquidity - 200.0).abs() < 1e-9); // 100 SOL on each side
+    }
+
+    #[test]
+    fn test_pool_quote_follows_swap_direction() {
+        let pool = PoolState::from_raydium("Poo1", &amm(USDC_MINT, WSOL_MINT), 15_000_000_000, 102_000_000_000);
+
+        let quote = pool.quote(WSOL_MINT, 1_000_000_000).unwrap();
+        assert_eq!(quote.output_amount, amm_math::get_amount_out(1_000_000_000, 100_000_000_000, 15_000_000_000, 0.0025));
+        assert_eq!(quote.route, vec!["Raydium"]);
+        assert!(quote.slippage > quote.price_impact); // Slippage includes the fee
+
+        let reverse = pool.quote(USDC_MINT, 150_000_000).unwrap();
+        assert_eq!(reverse.output_amount, amm_math::get_amount_out(150_000_000, 15_000_000_000, 100_000_000_000, 0.0025));
+        assert!(pool.quote("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 1).is_none());
+    }
+
+    #[test]
+    fn test_pool_implied_price_needs_sol_pair() {
+        let pool = PoolState::from_raydium("Poo1", &amm(USDC_MINT, WSOL_MINT), 15_000_000_000, 102_000_000_000);
+        // 15,000 USDC (6 decimals) against 100 SOL
+        let price = pool_implied_price_in_sol(&pool, USDC_MINT, 6).unwrap();
+        assert!((price - 100.0 / 15_000.0).abs() < 1e-12);
+
+        let stable_pool = PoolState::from_raydium("Poo2", &amm(USDC_MINT, "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"), 1, 1);
+        assert_eq!(pool_implied_price_in_sol(&stable_pool, USDC_MINT, 6), None);
+    }
+
+    #[test]
+    fn test_backrun_sized_against_reference_pool() {
+        // Same 15,000 USDC / 100 SOL price on both venues; a 10 SOL sell pushes SOL down on one
+        let pool = PoolState::from_raydium("Poo1", &amm(USDC_MINT, WSOL_MINT), 15_000_000_000, 102_000_000_000);
+        let reference = PoolState::from_raydium("Poo2", &amm(USDC_MINT, WSOL_MINT), 150_000_000_000, 1_002_000_000_000);
+
+        let (step, profit) = plan_backrun(&pool, &reference, WSOL_MINT, 10_000_000_000).unwrap();
+        assert_eq!((step.input_token.as_str(), step.output_token.as_str()), (USDC_MINT, WSOL_MINT));
+        assert_eq!(step.pool_address, "Poo1");
+        assert!(profit > 0.0);
+
+        // Our swap takes back close to the SOL the target sold, not the whole displacement
+        let target_out = amm_math::get_amount_out(10_000_000_000, 100_000_000_000, 15_000_000_000, 0.0025);
+        assert!(step.amount_in < target_out);
+        assert!(step.expected_out > 8_000_000_000 && step.expected_out < 10_000_000_000);
+
+        // Larger or smaller swaps leave less
+        let price = 150_000_000_000.0 / 1_000_000_000_000.0; // Raw USDC per raw SOL
+        let (x, y) = (15_000_000_000 - target_out, 110_000_000_000);
+        for amount_in in [step.amount_in / 2, step.amount_in * 3 / 2] {
+            assert!(amm_math::backrun_profit(amount_in, x, y, 0.0025, price) < profit);
+        }
+
+        // An undisturbed pool isn't worth crossing
+        assert!(plan_backrun(&pool, &reference, WSOL_MINT, 1_000).is_none());
+    }
+
+    // SOL/USDC book with 0.001 SOL lots, 0.001 USDC ticks and a 5 bps taker fee; 153,000 ticks is
+    // 153 USDC per SOL
+    fn book(bids: Vec<BookLevel>) -> OrderBook {
+        OrderBook {
+            market: "Market1".to_string(),
+            base_mint: WSOL_MINT.to_string(),
+            quote_mint: USDC_MINT.to_string(),
+            base_vault: "BaseVau1t".to_string(),
+            quote_vault: "QuoteVau1t".to_string(),
+            base_lot_size: 1_000_000,
+            quote_lot_size: 1,
+            tick_size: 1_000,
+            base_lots_per_base_unit: 1_000,
+            taker_fee_bps: 5,
+            bids,
+            asks: vec![BookLevel { price_in_ticks: 153_500, base_lots: 10_000 }],
+        }
+    }
+
+    #[test]
+    fn test_pool_vs_book_round_trip_sells_into_the_bid() {
+        // Pool at 150 USDC per SOL, book bid at 153
+        let pool = Venue::Pool(PoolState::from_raydium("Poo1", &amm(USDC_MINT, WSOL_MINT), 15_000_000_000, 102_000_000_000));
+        let deep = Venue::Book(book(vec![BookLevel { price_in_ticks: 153_000, base_lots: 5_000 }]));
+        assert_eq!((deep.dex(), deep.address()), ("Phoenix", "Market1"));
+
+        // Sell 1 SOL into the bid (153 USDC less 5 bps), buy it back on the pool
+        let (pool_first, bought, sold) = best_round_trip(&pool, &deep, WSOL_MINT, USDC_MINT, 1_000_000_000).unwrap();
+        assert!(!pool_first);
+        assert_eq!(bought, 153_000_000 - 76_500);
+        assert_eq!(sold, amm_math::get_amount_out(bought, 15_000_000_000, 100_000_000_000, 0.0025));
+        assert!(sold > 1_000_000_000);
+
+        // Only 0.3 SOL rests at 153: the rest of the order fills at 150 and the spread is gone
+        let thin = Venue::Book(book(vec![
+            BookLevel { price_in_ticks: 153_000, base_lots: 300 },
+            BookLevel { price_in_ticks: 150_000, base_lots: 5_000 },
+        ]));
+        let (_, bought, sold) = best_round_trip(&pool, &thin, WSOL_MINT, USDC_MINT, 1_000_000_000).unwrap();
+        assert_eq!(bought, 150_900_000 - 75_450);
+        assert!(sold < 1_000_000_000);
+
+        // A pool that doesn't trade the pair has no round trip
+        let other = Venue::Pool(PoolState::from_raydium("Poo2", &amm(USDC_MINT, "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"), 1, 1));
+        assert!(best_round_trip(&other, &deep, WSOL_MINT, USDC_MINT, 1_000_000_000).is_none());
+    }
+
+    #[test]
+    fn test_liquidity_unknown_without_sol_side() {
+        let pool = PoolState::from_raydium("Poo1", &amm(USDC_MINT, "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"), 1, 1);
+        assert_eq!(pool.liquidity, 0.0);
+    }
+}
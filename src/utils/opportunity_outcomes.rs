@@ -0,0 +1,339 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use crate::utils::enhanced_transaction_simulator::OpportunityDetails;
+use crate::utils::mev_strategies::MevStrategyResult;
+
+const MAX_OUTCOMES: usize = 10_000;
+
+// How far an opportunity got; each stage replaces the one before it
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpportunityStage {
+    Detected,         // The evaluator found it
+    Filtered(String), // Rejected by the false positive reducer, with its reason
+    Queued,           // Passed the filters
+    Executed { profit: f64, success: bool },
+    BundlePending { profit: f64 }, // Accepted by Jito; Executed once the bundle lands
+    BundleFailed,     // Accepted by Jito, but the bundle never landed
+}
+
+#[derive(Debug, Clone)]
+pub struct OpportunityOutcome {
+    pub correlation_id: u64,
+    pub opportunity_type: String,
+    pub dex: String,
+    pub estimated_profit: f64,
+    pub stage: OpportunityStage,
+    pub bundle_id: Option<String>,
+}
+
+impl OpportunityOutcome {
+    pub fn passed_filters(&self) -> bool {
+        !matches!(self.stage, OpportunityStage::Detected | OpportunityStage::Filtered(_))
+    }
+
+    // A false positive passed the filters and then made no money: it lost, failed or its bundle
+    // didn't land. None until the execution result is in
+    pub fn is_false_positive(&self) -> Option<bool> {
+        match self.stage {
+            OpportunityStage::Executed { profit, success } => Some(!success || profit <= 0.0),
+            OpportunityStage::BundleFailed => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutcomeStats {
+    pub detected: u64,
+    pub filtered: u64,
+    pub passed_filters: u64,
+    pub true_positives: u64,
+    pub false_positives: u64,
+}
+
+impl OutcomeStats {
+    fn add(&mut self, outcome: &OpportunityOutcome) {
+        self.detected += 1;
+        if matches!(outcome.stage, OpportunityStage::Filtered(_)) {
+            self.filtered += 1;
+        }
+        if outcome.passed_filters() {
+            self.passed_filters += 1;
+        }
+        match outcome.is_false_positive() {
+            Some(true) => self.false_positives += 1,
+            Some(false) => self.true_positives += 1,
+            None => {}
+        }
+    }
+
+    pub fn resolved(&self) -> u64 {
+        self.true_positives + self.false_positives
+    }
+
+    // Passed the filters but never executed (expired, held back by risk controls, errored) or not
+    // back yet
+    pub fn unresolved(&self) -> u64 {
+        self.passed_filters.saturating_sub(self.resolved())
+    }
+
+    // Share of the resolved opportunities that passed the filters and made no money
+    pub fn false_positive_rate(&self) -> f64 {
+        if self.resolved() > 0 { self.false_positives as f64 / self.resolved() as f64 } else { 0.0 }
+    }
+
+    // Share of the resolved opportunities that passed the filters and paid
+    pub fn filter_precision(&self) -> f64 {
+        if self.resolved() > 0 { self.true_positives as f64 / self.resolved() as f64 } else { 0.0 }
+    }
+
+    // Share of the filtered opportunities the false positive reducer turned away
+    pub fn filter_rejection_rate(&self) -> f64 {
+        let judged = self.filtered + self.passed_filters;
+        if judged > 0 { self.filtered as f64 / judged as f64 } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OutcomeLog {
+    outcomes: HashMap<u64, OpportunityOutcome>,
+    order: VecDeque<u64>, // Oldest first, for eviction
+    by_bundle: HashMap<String, u64>,
+}
+
+impl OutcomeLog {
+    fn evict_oldest(&mut self) {
+        while self.outcomes.len() > MAX_OUTCOMES {
+            let Some(id) = self.order.pop_front() else { break };
+            if let Some(outcome) = self.outcomes.remove(&id) {
+                if let Some(bundle_id) = outcome.bundle_id {
+                    self.by_bundle.remove(&bundle_id);
+                }
+            }
+        }
+    }
+}
+
+// Each opportunity's way through detection, filtering, execution and bundle reconciliation,
+// keyed by the correlation id its OpportunityTiming carries. Bounded to the most recent
+// MAX_OUTCOMES opportunities. Cheap to clone: clones share the same log
+#[derive(Debug, Clone, Default)]
+pub struct OpportunityOutcomes {
+    log: Arc<RwLock<OutcomeLog>>,
+}
+
+impl OpportunityOutcomes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Opportunities that skip detection (triangular routes, liquidations) start at whichever
+    // stage is recorded first; returns true for that first stage
+    pub async fn record_stage(&self, correlation_id: u64, opportunity: &OpportunityDetails, stage: OpportunityStage) -> bool {
+        let mut log = self.log.write().await;
+        if let Some(outcome) = log.outcomes.get_mut(&correlation_id) {
+            outcome.stage = stage;
+            return false;
+        }
+        log.outcomes.insert(correlation_id, OpportunityOutcome {
+            correlation_id,
+            opportunity_type: format!("{:?}", opportunity.opportunity_type),
+            dex: opportunity.dex.clone(),
+            estimated_profit: opportunity.estimated_profit,
+            stage,
+            bundle_id: None,
+        });
+        log.order.push_back(correlation_id);
+        log.evict_oldest();
+        true
+    }
+
+    // A pending result waits on its bundle; see record_bundle_resolved. Returns the outcome when
+    // it's final, i.e. there's no bundle left to wait on
+    pub async fn record_execution(&self, correlation_id: u64, result: &MevStrategyResult) -> Option<OpportunityOutcome> {
+        let mut log = self.log.write().await;
+        let outcome = log.outcomes.get_mut(&correlation_id)?;
+        outcome.bundle_id = result.bundle_id.clone();
+        let (true, Some(bundle_id)) = (result.pending, &result.bundle_id) else {
+            outcome.stage = OpportunityStage::Executed { profit: result.profit, success: result.success };
+            return Some(outcome.clone());
+        };
+        outcome.stage = OpportunityStage::BundlePending { profit: result.profit };
+        log.by_bundle.insert(bundle_id.clone(), correlation_id);
+        None
+    }
+
+    // Returns the reconciled outcome when the bundle belonged to a tracked opportunity
+    pub async fn record_bundle_resolved(&self, bundle_id: &str, landed: bool) -> Option<OpportunityOutcome> {
+        let mut log = self.log.write().await;
+        let correlation_id = log.by_bundle.remove(bundle_id)?;
+        let outcome = log.outcomes.get_mut(&correlation_id)?;
+        if let OpportunityStage::BundlePending { profit } = outcome.stage {
+            outcome.stage = if landed { OpportunityStage::Executed { profit, success: true } } else { OpportunityStage::BundleFailed };
+        }
+        Some(outcome.clone())
+    }
+
+    pub async fn get(&self, correlation_id: u64) -> Option<OpportunityOutcome> {
+        self.log.read().await.outcomes.get(&correlation_id).cloned()
+    }
+
+    pub async fn stats(&self) -> OutcomeStats {
+        let log = self.log.read().await;
+        let mut stats = OutcomeStats::default();
+        for outcome in log.outcomes.values() {
+            stats.add(outcome);
+        }
+        stats
+    }
+
+    pub async fn stats_by_type(&self) -> Vec<(String, OutcomeStats)> {
+        self.stats_by(|outcome| outcome.opportunity_type.clone()).await
+    }
+
+    pub async fn stats_by_dex(&self) -> Vec<(String, OutcomeStats)> {
+        self.stats_by(|outcome| outcome.dex.clone()).await
+    }
+
+    async fn stats_by(&self, key: impl Fn(&OpportunityOutcome) -> String) -> Vec<(String, OutcomeStats)> {
+        let log = self.log.read().await;
+        let mut groups: HashMap<String, OutcomeStats> = HashMap::new();
+        for outcome in log.outcomes.values() {
+            groups.entry(key(outcome)).or_default().add(outcome);
+        }
+        let mut groups: Vec<(String, OutcomeStats)> = groups.into_iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        groups
+    }
+
+    // Whether each of the last `limit` resolved opportunities of a type paid, oldest first; what
+    // FalsePositiveReducer tunes itself with
+    pub async fn recent_results(&self, opportunity_type: &str, limit: usize) -> Vec<bool> {
+        let log = self.log.read().await;
+        let mut results: Vec<bool> = log.order.iter().rev()
+            .filter_map(|id| log.outcomes.get(id))
+            .filter(|outcome| outcome.opportunity_type == opportunity_type)
+            .filter_map(|outcome| outcome.is_false_positive().map(|false_positive| !false_positive))
+            .take(limit)
+            .collect();
+        results.reverse();
+        results
+    }
+
+    pub async fn len(&self) -> usize {
+        self.log.read().await.outcomes.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.log.read().await.outcomes.is_empty()
+    }
+
+    pub async fn clear(&self) {
+        *self.log.write().await = OutcomeLog::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::enhanced_transaction_simulator::OpportunityType;
+    use crate::utils::mev_strategies::MevStrategyType;
+
+    fn opportunity(opportunity_type: OpportunityType, dex: &str) -> OpportunityDetails {
+        OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.01,
+            dex: dex.to_string(),
+            opportunity_type,
+            pool_address: None,
+            route: Vec::new(),
+        }
+    }
+
+    fn result(success: bool, profit: f64, bundle_id: Option<&str>) -> MevStrategyResult {
+        MevStrategyResult {
+            success,
+            profit,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 50,
+            strategy_type: MevStrategyType::Arbitrage,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: bundle_id.map(str::to_string),
+            pending: false,
+        }
+    }
+
+    // Accepted by Jito, with its landing left to the bundle tracker
+    fn pending(profit: f64, bundle_id: &str) -> MevStrategyResult {
+        MevStrategyResult { pending: true, ..result(false, profit, Some(bundle_id)) }
+    }
+
+    #[tokio::test]
+    async fn test_false_positives_follow_the_opportunity_to_its_bundle() {
+        let outcomes = OpportunityOutcomes::new();
+        let arbitrage = opportunity(OpportunityType::Arbitrage, "Raydium");
+        let sandwich = opportunity(OpportunityType::Sandwich, "Orca");
+
+        // 1 filtered out; 2 paid once its bundle landed; 3 lost money; 4's bundle never landed; 5 never ran
+        for id in 1..=5 {
+            let opportunity = if id == 3 { &sandwich } else { &arbitrage };
+            outcomes.record_stage(id, opportunity, OpportunityStage::Detected).await;
+        }
+        outcomes.record_stage(1, &arbitrage, OpportunityStage::Filtered("Slippage".to_string())).await;
+        for id in 2..=5 {
+            let opportunity = if id == 3 { &sandwich } else { &arbitrage };
+            outcomes.record_stage(id, opportunity, OpportunityStage::Queued).await;
+        }
+        assert!(outcomes.record_execution(2, &pending(0.02, "bundle-2")).await.is_none());
+        assert!(outcomes.record_execution(3, &result(false, -0.001, None)).await.is_some());
+        assert!(outcomes.record_execution(4, &pending(0.03, "bundle-4")).await.is_none());
+        // Neither counts as a positive of either kind while its bundle is pending
+        assert_eq!(outcomes.stats().await.resolved(), 1);
+        assert_eq!(
+            outcomes.record_bundle_resolved("bundle-2", true).await.unwrap().stage,
+            OpportunityStage::Executed { profit: 0.02, success: true }
+        );
+        assert_eq!(outcomes.record_bundle_resolved("bundle-4", false).await.unwrap().stage, OpportunityStage::BundleFailed);
+        assert!(outcomes.record_bundle_resolved("someone-else", false).await.is_none());
+
+        let stats = outcomes.stats().await;
+        assert_eq!(stats, OutcomeStats { detected: 5, filtered: 1, passed_filters: 4, true_positives: 1, false_positives: 2 });
+        assert_eq!(stats.unresolved(), 1);
+        assert!((stats.false_positive_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((stats.filter_precision() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.filter_rejection_rate(), 0.2);
+        assert_eq!(outcomes.get(4).await.unwrap().stage, OpportunityStage::BundleFailed);
+
+        let by_type = outcomes.stats_by_type().await;
+        assert_eq!(by_type[0].0, "Arbitrage");
+        assert_eq!((by_type[0].1.true_positives, by_type[0].1.false_positives), (1, 1));
+        assert_eq!((by_type[1].0.as_str(), by_type[1].1.false_positives), ("Sandwich", 1));
+        let by_dex = outcomes.stats_by_dex().await;
+        assert_eq!(by_dex.iter().map(|(dex, _)| dex.as_str()).collect::<Vec<_>>(), vec!["Orca", "Raydium"]);
+
+        assert_eq!(outcomes.recent_results("Arbitrage", 10).await, vec![true, false]);
+        assert_eq!(outcomes.recent_results("Arbitrage", 1).await, vec![false]);
+    }
+
+    #[tokio::test]
+    async fn test_log_keeps_only_the_most_recent_opportunities() {
+        let outcomes = OpportunityOutcomes::new();
+        let arbitrage = opportunity(OpportunityType::Arbitrage, "Raydium");
+        for id in 0..(MAX_OUTCOMES as u64 + 10) {
+            outcomes.record_stage(id, &arbitrage, OpportunityStage::Queued).await;
+        }
+        assert_eq!(outcomes.len().await, MAX_OUTCOMES);
+        assert!(outcomes.get(9).await.is_none());
+        assert!(outcomes.get(10).await.is_some());
+
+        // A result for an evicted opportunity is ignored
+        assert!(outcomes.record_execution(0, &pending(0.01, "late")).await.is_none());
+        assert!(outcomes.record_bundle_resolved("late", false).await.is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use tokio::sync::{Mutex, Notify};
+use crate::config::StrategiesConfig;
+use crate::utils::enhanced_transaction_simulator::OpportunityDetails;
+use crate::utils::metrics_collector::OpportunityTiming;
+
+#[derive(Debug, Clone)]
+pub struct QueuedOpportunity {
+    pub signature: String,
+    pub opportunity: OpportunityDetails,
+    pub target_tx_details: Value,
+    pub estimated_profit: f64,
+    pub detected_at: Instant,
+    pub deadline: Instant,
+    pub timing: OpportunityTiming,
+}
+
+impl QueuedOpportunity {
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now >= self.deadline
+    }
+}
+
+// Heap ordering: highest profit first, earliest deadline breaks ties
+impl Ord for QueuedOpportunity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.estimated_profit
+            .total_cmp(&other.estimated_profit)
+            .then_with(|| other.deadline.cmp(&self.deadline))
+    }
+}
+
+impl PartialOrd for QueuedOpportunity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for QueuedOpportunity {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedOpportunity {}
+
+// What a push into a full queue displaced
+#[derive(Debug, Default)]
+pub struct PushOutcome {
+    pub expired: u64, // Past their deadline, purged to make room
+    pub dropped: Option<QueuedOpportunity>, // Least profitable entry, maybe the pushed one, when purging wasn't enough
+}
+
+pub struct OpportunityQueue {
+    heap: Arc<Mutex<BinaryHeap<QueuedOpportunity>>>,
+    notify: Arc<Notify>,
+    expired_count: Arc<AtomicU64>,
+    pub ttl: Duration,
+    pub capacity: usize,
+}
+
+impl OpportunityQueue {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            heap: Arc::new(Mutex::new(BinaryHeap::new())),
+            notify: Arc::new(Notify::new()),
+            expired_count: Arc::new(AtomicU64::new(0)),
+            ttl,
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn from_config(config: &StrategiesConfig) -> Self {
+        Self::new(Duration::from_millis(config.opportunity_ttl_ms), config.opportunity_queue_capacity)
+    }
+
+    pub async fn push(
+        &self,
+        signature: String,
+        opportunity: OpportunityDetails,
+        target_tx_details: Value,
+        timing: OpportunityTiming,
+    ) -> PushOutcome {
+        let detected_at = timing.detected_at;
+        let entry = QueuedOpportunity {
+            signature,
+            estimated_profit: opportunity.estimated_profit,
+            opportunity,
+            target_tx_details,
+            detected_at,
+            deadline: detected_at + self.ttl,
+            timing,
+        };
+
+        let now = Instant::now();
+        let mut outcome = PushOutcome::default();
+        let mut heap = self.heap.lock().await;
+        // With every worker busy nothing pops, so stale entries would otherwise hold the space
+        if heap.len() >= self.capacity {
+            let before = heap.len();
+            heap.retain(|queued| !queued.is_expired(now));
+            outcome.expired = (before - heap.len()) as u64;
+            self.expired_count.fetch_add(outcome.expired, AtomicOrdering::Relaxed);
+        }
+        heap.push(entry);
+        if heap.len() > self.capacity {
+            let mut entries = std::mem::take(&mut *heap).into_vec();
+            if let Some(lowest) = entries.iter().enumerate().min_by(|(_, a), (_, b)| a.cmp(b)).map(|(index, _)| index) {
+                outcome.dropped = Some(entries.swap_remove(lowest));
+            }
+            *heap = BinaryHeap::from(entries);
+        }
+        drop(heap);
+
+        self.notify.notify_one();
+        outcome
+    }
+
+    // Pops the most profitable entry that is still within its deadline, discarding
+    // any expired entries found along the way. Returns the entry and how many were dropped.
+    pub async fn try_pop(&self) -> (Option<QueuedOpportunity>, u64) {
+        let now = Instant::now();
+        let mut heap = self.heap.lock().await;
+        let mut dropped = 0u64;
+
+        while let Some(entry) = heap.pop() {
+            if entry.is_expired(now) {
+                dropped += 1;
+                continue;
+            }
+            self.expired_count.fetch_add(dropped, AtomicOrdering::Relaxed);
+            return (Some(entry), dropped);
+        }
+
+        self.expired_count.fetch_add(dropped, AtomicOrdering::Relaxed);
+        (None, dropped)
+    }
+
+    // Waits until a non-expired entry is available
+    pub async fn pop(&self) -> (QueuedOpportunity, u64) {
+        let mut total_dropped = 0u64;
+        loop {
+            let notified = self.notify.notified();
+            let (entry, dropped) = self.try_pop().await;
+            total_dropped += dropped;
+            if let Some(entry) = entry {
+                return (entry, total_dropped);
+            }
+            notified.await;
+        }
+    }
+
+    pub async fn len(&self) -> usize {
+        self.heap.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.heap.lock().await.is_empty()
+    }
+
+    pub fn expired_count(&self) -> u64 {
+        self.expired_count.load(AtomicOrdering::Relaxed)
+    }
+}
+
+impl Clone for OpportunityQueue {
+    fn clone(&self) -> Self {
+        OpportunityQueue {
+            heap: Arc::clone(&self.heap),
+            notify: Arc::clone(&self.notify),
+            expired_count: Arc::clone(&self.expired_count),
+            ttl: self.ttl,
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+    fn opportunity(profit: f64) -> OpportunityDetails {
+        OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: profit,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: None,
+            route: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pops_highest_profit_first() {
+        let queue = OpportunityQueue::new(Duration::from_secs(5), 1_000);
+        let now = Instant::now();
+        queue.push("low".to_string(), opportunity(0.01), json!({}), OpportunityTiming::new(now)).await;
+        queue.push("high".to_string(), opportunity(0.1), json!({}), OpportunityTiming::new(now)).await;
+        queue.push("mid".to_string(), opportunity(0.05), json!({}), OpportunityTiming::new(now)).await;
+
+        assert_eq!(queue.pop().await.0.signature, "high");
+        assert_eq!(queue.pop().await.0.signature, "mid");
+        assert_eq!(queue.pop().await.0.signature, "low");
+        assert!(queue.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_dropped() {
+        let queue = OpportunityQueue::new(Duration::from_millis(50), 1_000);
+        let stale = Instant::now() - Duration::from_millis(100);
+        queue.push("stale".to_string(), opportunity(1.0), json!({}), OpportunityTiming::new(stale)).await;
+        queue.push("fresh".to_string(), opportunity(0.01), json!({}), OpportunityTiming::new(Instant::now())).await;
+
+        let (entry, dropped) = queue.try_pop().await;
+        assert_eq!(entry.unwrap().signature, "fresh");
+        assert_eq!(dropped, 1);
+        assert_eq!(queue.expired_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_purges_stale_entries_before_dropping_fresh_ones() {
+        let queue = OpportunityQueue::new(Duration::from_millis(50), 3);
+        let stale = Instant::now() - Duration::from_millis(100);
+        for i in 0..3 {
+            queue.push(format!("stale_{}", i), opportunity(1.0), json!({}), OpportunityTiming::new(stale)).await;
+        }
+
+        // The stale entries make way, however profitable they looked
+        let outcome = queue.push("fresh_a".to_string(), opportunity(0.01), json!({}), OpportunityTiming::new(Instant::now())).await;
+        assert_eq!(outcome.expired, 3);
+        assert!(outcome.dropped.is_none());
+        assert_eq!(queue.len().await, 1);
+        assert_eq!(queue.expired_count(), 3);
+
+        // Full of fresh entries, the least profitable one goes, whether queued or just pushed
+        queue.push("fresh_b".to_string(), opportunity(0.05), json!({}), OpportunityTiming::new(Instant::now())).await;
+        queue.push("fresh_c".to_string(), opportunity(0.03), json!({}), OpportunityTiming::new(Instant::now())).await;
+        let outcome = queue.push("fresh_d".to_string(), opportunity(0.1), json!({}), OpportunityTiming::new(Instant::now())).await;
+        assert_eq!(outcome.expired, 0);
+        assert_eq!(outcome.dropped.unwrap().signature, "fresh_a");
+        let outcome = queue.push("fresh_e".to_string(), opportunity(0.001), json!({}), OpportunityTiming::new(Instant::now())).await;
+        assert_eq!(outcome.dropped.unwrap().signature, "fresh_e");
+
+        assert_eq!(queue.pop().await.0.signature, "fresh_d");
+        assert_eq!(queue.pop().await.0.signature, "fresh_b");
+        assert_eq!(queue.pop().await.0.signature, "fresh_c");
+        assert!(queue.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_workers_each_get_unique_entries() {
+        let queue = OpportunityQueue::new(Duration::from_secs(5), 1_000);
+        let mut workers = Vec::new();
+        for _ in 0..4 {
+            let worker_queue = queue.clone();
+            workers.push(tokio::spawn(async move {
+                let mut seen = Vec::new();
+                for _ in 0..25 {
+                    seen.push(worker_queue.pop().await.0.signature);
+                }
+                seen
+            }));
+        }
+
+        for i in 0..100 {
+            queue.push(format!("sig_{}", i), opportunity(i as f64 * 0.001), json!({}), OpportunityTiming::new(Instant::now())).await;
+        }
+
+        let mut all = Vec::new();
+        for worker in workers {
+            all.extend(worker.await.unwrap());
+        }
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 100);
+        assert!(queue.is_empty().await);
+    }
+}
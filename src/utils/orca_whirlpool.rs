@@ -0,0 +1,376 @@
+use std::str::FromStr;
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::utils::instruction_parser::{self, ParsedInstruction};
+use crate::utils::simulation_effects::TOKEN_PROGRAM_ID;
+
+pub const WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+// Anchor discriminator: first 8 bytes of sha256("global:swap")
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// Anchor account discriminator for Whirlpool
+const WHIRLPOOL_ACCOUNT_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+
+const SWAP_MIN_ACCOUNTS: usize = 11;
+const WHIRLPOOL_ACCOUNT_LEN: usize = 653;
+
+// Ticks per tick array, and the price bounds a swap may run to
+const TICK_ARRAY_SIZE: i32 = 88;
+const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhirlpoolSwap {
+    pub whirlpool: String,
+    pub amount: u64,
+    pub other_amount_threshold: u64,
+    pub sqrt_price_limit: u128,
+    pub amount_specified_is_input: bool,
+    pub a_to_b: bool,
+    pub token_owner_account_a: String,
+    pub token_owner_account_b: String,
+    pub token_vault_a: String,
+    pub token_vault_b: String,
+}
+
+impl WhirlpoolSwap {
+    // Input amount: exact when amount_specified_is_input, otherwise the user's maximum
+    pub fn amount_in(&self) -> u64 {
+        if self.amount_specified_is_input { self.amount } else { self.other_amount_threshold }
+    }
+
+    // The user's token account being debited
+    pub fn user_input_account(&self) -> &str {
+        if self.a_to_b { &self.token_owner_account_a } else { &self.token_owner_account_b }
+    }
+
+    pub fn input_mint<'a>(&self, pool: &'a WhirlpoolState) -> &'a str {
+        if self.a_to_b { &pool.token_mint_a } else { &pool.token_mint_b }
+    }
+
+    pub fn output_mint<'a>(&self, pool: &'a WhirlpoolState) -> &'a str {
+        if self.a_to_b { &pool.token_mint_b } else { &pool.token_mint_a }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhirlpoolState {
+    pub tick_spacing: u16,
+    pub fee_rate: u16, // Hundredths of a basis point (3000 = 0.3%)
+    pub liquidity: u128,
+    pub sqrt_price: u128, // Q64.64
+    pub tick_current_index: i32,
+    pub token_mint_a: String,
+    pub token_vault_a: String,
+    pub token_mint_b: String,
+    pub token_vault_b: String,
+}
+
+impl WhirlpoolState {
+    // Layout (after the 8 byte discriminator): config 32, bump 1, tick_spacing u16, seed 2,
+    // fee_rate u16, protocol_fee_rate u16, liquidity u128, sqrt_price u128, tick i32,
+    // protocol fees 2x u64, mint_a, vault_a, fee_growth_a u128, mint_b, vault_b, ...
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < WHIRLPOOL_ACCOUNT_LEN {
+            return Err(format!("Whirlpool account too short: {} bytes", data.len()).into());
+        }
+        if data[..8] != WHIRLPOOL_ACCOUNT_DISCRIMINATOR {
+            return Err("Account is not an Orca whirlpool".into());
+        }
+
+        let pubkey = |offset: usize| bs58::encode(&data[offset..offset + 32]).into_string();
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let u128_at = |offset: usize| {
+            u128::from_le_bytes(data[offset..offset + 16].try_into().expect("16 byte slice"))
+        };
+
+        Ok(Self {
+            tick_spacing: u16_at(41),
+            fee_rate: u16_at(45),
+            liquidity: u128_at(49),
+            sqrt_price: u128_at(65),
+            tick_current_index: i32::from_le_bytes(data[81..85].try_into().expect("4 byte slice")),
+            token_mint_a: pubkey(101),
+            token_vault_a: pubkey(133),
+            token_mint_b: pubkey(181),
+            token_vault_b: pubkey(213),
+        })
+    }
+
+    // Fee as a fraction (0.003 = 0.3%)
+    pub fn fee_fraction(&self) -> f64 {
+        self.fee_rate as f64 / 1_000_000.0
+    }
+
+    // Price of token A in token B, in raw (non decimal-adjusted) units
+    pub fn price_a_in_b(&self) -> f64 {
+        let sqrt_price = self.sqrt_price as f64 / (1u128 << 64) as f64;
+        sqrt_price * sqrt_price
+    }
+
+    // Constant-product equivalent of the active liquidity: x = L / sqrt(P), y = L * sqrt(P).
+    // Accurate for trades that stay within the current tick range
+    pub fn virtual_reserves(&self) -> (u64, u64) {
+        let sqrt_price = self.sqrt_price as f64 / (1u128 << 64) as f64;
+        if sqrt_price == 0.0 {
+            return (0, 0);
+        }
+        let liquidity = self.liquidity as f64;
+        ((liquidity / sqrt_price) as u64, (liquidity * sqrt_price) as u64)
+    }
+}
+
+// Layout: [discriminator 8][amount u64][other_amount_threshold u64][sqrt_price_limit u128]
+//         [amount_specified_is_input bool][a_to_b bool]
+pub fn decode_instruction(instruction: &ParsedInstruction) -> Option<WhirlpoolSwap> {
+    if instruction.program_id != WHIRLPOOL_PROGRAM_ID || instruction.accounts.len() < SWAP_MIN_ACCOUNTS {
+        return None;
+    }
+
+    let data = instruction_parser::decode_instruction_data(instruction)?;
+    if data.len() < 42 || data[..8] != SWAP_DISCRIMINATOR {
+        return None;
+    }
+
+    let accounts = &instruction.accounts;
+    Some(WhirlpoolSwap {
+        whirlpool: accounts[2].clone(),
+        amount: u64::from_le_bytes(data[8..16].try_into().ok()?),
+        other_amount_threshold: u64::from_le_bytes(data[16..24].try_into().ok()?),
+        sqrt_price_limit: u128::from_le_bytes(data[24..40].try_into().ok()?),
+        amount_specified_is_input: data[40] != 0,
+        a_to_b: data[41] != 0,
+        token_owner_account_a: accounts[3].clone(),
+        token_vault_a: accounts[4].clone(),
+        token_owner_account_b: accounts[5].clone(),
+        token_vault_b: accounts[6].clone(),
+    })
+}
+
+pub fn decode_transaction(tx_details: &Value) -> Vec<WhirlpoolSwap> {
+    instruction_parser::all_instructions(tx_details)
+        .iter()
+        .filter_map(decode_instruction)
+        .collect()
+}
+
+// PDA of ["tick_array", whirlpool, start tick index as a decimal string]
+pub fn tick_array_address(whirlpool: &str, start_tick_index: i32) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"tick_array", pubkey(whirlpool)?.as_ref(), start_tick_index.to_string().as_bytes()],
+        &pubkey(WHIRLPOOL_PROGRAM_ID)?,
+    );
+    Ok(address.to_string())
+}
+
+// The array holding the current tick and the next two in the swap's direction: A in pushes the
+// price (and tick) down, B in pushes it up
+pub fn swap_tick_arrays(whirlpool: &str, pool: &WhirlpoolState, a_to_b: bool) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let span = TICK_ARRAY_SIZE * pool.tick_spacing.max(1) as i32;
+    let start = pool.tick_current_index.div_euclid(span) * span;
+    let step = if a_to_b { -span } else { span };
+    (0..3).map(|i| tick_array_address(whirlpool, start + i * step)).collect()
+}
+
+// swap: [token program, token authority (s), whirlpool (w), owner account a (w), vault a (w),
+// owner account b (w), vault b (w), tick arrays 0-2 (w), oracle]. Exact input, with the price
+// limit left at the bound so only min_amount_out stops it. The wallet's associated token
+// accounts are the owner accounts
+pub fn swap_instruction(
+    whirlpool: &str,
+    pool: &WhirlpoolState,
+    user: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    a_to_b: bool,
+) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let program_id = pubkey(WHIRLPOOL_PROGRAM_ID)?;
+    let whirlpool_key = pubkey(whirlpool)?;
+    let (oracle, _) = Pubkey::find_program_address(&[b"oracle", whirlpool_key.as_ref()], &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(pubkey(TOKEN_PROGRAM_ID)?, false),
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(whirlpool_key, false),
+        AccountMeta::new(associated_token_address(user, &pubkey(&pool.token_mint_a)?)?, false),
+        AccountMeta::new(pubkey(&pool.token_vault_a)?, false),
+        AccountMeta::new(associated_token_address(user, &pubkey(&pool.token_mint_b)?)?, false),
+        AccountMeta::new(pubkey(&pool.token_vault_b)?, false),
+    ];
+    for tick_array in swap_tick_arrays(whirlpool, pool, a_to_b)? {
+        accounts.push(AccountMeta::new(pubkey(&tick_array)?, false));
+    }
+    accounts.push(AccountMeta::new_readonly(oracle, false));
+
+    let mut data = SWAP_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+    data.extend_from_slice(&(if a_to_b { MIN_SQRT_PRICE } else { MAX_SQRT_PRICE }).to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+
+    Ok(Instruction { program_id, accounts, data })
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), pubkey(TOKEN_PROGRAM_ID)?.as_ref(), mint.as_ref()],
+        &pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")?,
+    );
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    const POOL: &str = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+
+    fn swap_data(amount: u64, threshold: u64, is_input: bool, a_to_b: bool) -> String {
+        let mut data = SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&threshold.to_le_bytes());
+        data.extend_from_slice(&4_295_048_016u128.to_le_bytes()); // MIN_SQRT_PRICE
+        data.push(is_input as u8);
+        data.push(a_to_b as u8);
+        bs58::encode(&data).into_string()
+    }
+
+    // Trimmed getTransaction ("json" encoding) of a SOL -> USDC whirlpool swap of 5 SOL
+    fn swap_fixture() -> Value {
+        let mut keys: Vec<String> = vec![
+            "UserWa11et111111111111111111111111111111111".to_string(), // 0 token authority
+            "UserWsoLAccount1111111111111111111111111111".to_string(),  // 1 owner account a
+            "UserUsdcAccount1111111111111111111111111111".to_string(),  // 2 owner account b
+            POOL.to_string(),                                           // 3 whirlpool
+            "VaultA111111111111111111111111111111111111".to_string(),   // 4 vault a
+            "VaultB111111111111111111111111111111111111".to_string(),   // 5 vault b
+        ];
+        keys.extend((0..4).map(|i| format!("TickOrOracle{}", i)));      // 6..9
+        keys.push("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string()); // 10
+        keys.push(WHIRLPOOL_PROGRAM_ID.to_string());                       // 11
+
+        json!({
+            "transaction": {
+                "message": {
+                    "accountKeys": keys,
+                    "instructions": [{
+                        "programIdIndex": 11,
+                        "accounts": [10, 0, 3, 1, 4, 2, 5, 6, 7, 8, 9],
+                        "data": swap_data(5_000_000_000, 740_000_000, true, true)
+                    }]
+                }
+            },
+            "meta": {
+                "preTokenBalances": [
+                    { "accountIndex": 1, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "7500000000", "decimals": 9 } },
+                    { "accountIndex": 2, "mint": USDC_MINT, "uiTokenAmount": { "amount": "12000000", "decimals": 6 } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 1, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "2500000000", "decimals": 9 } },
+                    { "accountIndex": 2, "mint": USDC_MINT, "uiTokenAmount": { "amount": "759812345", "decimals": 6 } }
+                ],
+                "logMessages": []
+            }
+        })
+    }
+
+    fn whirlpool_account(mint_a: &str, mint_b: &str) -> Vec<u8> {
+        let mut data = vec![0u8; WHIRLPOOL_ACCOUNT_LEN];
+        data[..8].copy_from_slice(&WHIRLPOOL_ACCOUNT_DISCRIMINATOR);
+        data[41..43].copy_from_slice(&64u16.to_le_bytes());
+        data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+        data[49..65].copy_from_slice(&1_000_000_000u128.to_le_bytes());
+        data[65..81].copy_from_slice(&(1u128 << 64).to_le_bytes()); // price 1.0
+        data[81..85].copy_from_slice(&(-12i32).to_le_bytes());
+        data[101..133].copy_from_slice(&bs58::decode(mint_a).into_vec().unwrap());
+        data[181..213].copy_from_slice(&bs58::decode(mint_b).into_vec().unwrap());
+        data
+    }
+
+    #[test]
+    fn test_decoded_amount_matches_balance_meta() {
+        let tx = swap_fixture();
+        let swap = decode_transaction(&tx).pop().expect("whirlpool swap decoded");
+
+        assert_eq!(swap.whirlpool, POOL);
+        assert!(swap.a_to_b);
+        assert!(swap.amount_specified_is_input);
+
+        let spent = -instruction_parser::token_balance_change(&tx, swap.user_input_account()).unwrap();
+        assert_eq!(spent, swap.amount_in() as i128);
+    }
+
+    #[test]
+    fn test_decodes_whirlpool_account_and_resolves_mints() {
+        let pool = WhirlpoolState::decode(&whirlpool_account(WSOL_MINT, USDC_MINT)).unwrap();
+        assert_eq!(pool.token_mint_a, WSOL_MINT);
+        assert_eq!(pool.token_mint_b, USDC_MINT);
+        assert_eq!(pool.tick_spacing, 64);
+        assert_eq!(pool.tick_current_index, -12);
+        assert!((pool.fee_fraction() - 0.003).abs() < 1e-12);
+        assert!((pool.price_a_in_b() - 1.0).abs() < 1e-12);
+        assert_eq!(pool.virtual_reserves(), (1_000_000_000, 1_000_000_000));
+
+        let swap = decode_transaction(&swap_fixture()).pop().unwrap();
+        assert_eq!(swap.input_mint(&pool), WSOL_MINT);
+        assert_eq!(swap.output_mint(&pool), USDC_MINT);
+    }
+
+    #[test]
+    fn test_virtual_reserves_follow_price() {
+        let mut data = whirlpool_account(WSOL_MINT, USDC_MINT);
+        // sqrt_price = 2.0 -> 4 B per A
+        data[65..81].copy_from_slice(&(2u128 << 64).to_le_bytes());
+        let pool = WhirlpoolState::decode(&data).unwrap();
+
+        let (reserve_a, reserve_b) = pool.virtual_reserves();
+        assert_eq!((reserve_a, reserve_b), (500_000_000, 2_000_000_000));
+        assert!((reserve_b as f64 / reserve_a as f64 - pool.price_a_in_b()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_built_swap_decodes_back() {
+        let pool = WhirlpoolState::decode(&whirlpool_account(WSOL_MINT, USDC_MINT)).unwrap();
+        let user = Pubkey::new_unique();
+        let instruction = swap_instruction(POOL, &pool, &user, 2_000_000_000, 290_000_000, true).unwrap();
+        assert!(instruction.accounts[1].is_signer);
+
+        let parsed = ParsedInstruction {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction.accounts.iter().map(|meta| meta.pubkey.to_string()).collect(),
+            data: Some(bs58::encode(&instruction.data).into_string()),
+        };
+        let swap = decode_instruction(&parsed).expect("built swap decodes");
+        assert_eq!(swap.whirlpool, POOL);
+        assert_eq!((swap.amount, swap.other_amount_threshold), (2_000_000_000, 290_000_000));
+        assert!(swap.a_to_b && swap.amount_specified_is_input);
+        assert_eq!(swap.sqrt_price_limit, MIN_SQRT_PRICE);
+        assert_eq!(
+            swap.user_input_account(),
+            associated_token_address(&user, &pubkey(WSOL_MINT).unwrap()).unwrap().to_string()
+        );
+
+        // Tick -12 at spacing 64 sits in the array starting at -5632; A in walks down from it
+        let arrays = swap_tick_arrays(POOL, &pool, true).unwrap();
+        assert_eq!(arrays[0], tick_array_address(POOL, -5632).unwrap());
+        assert_eq!(arrays[1], tick_array_address(POOL, -11264).unwrap());
+        assert_eq!(swap_tick_arrays(POOL, &pool, false).unwrap()[1], tick_array_address(POOL, 0).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_non_whirlpool_accounts() {
+        let mut data = whirlpool_account(WSOL_MINT, USDC_MINT);
+        data[0] ^= 0xff;
+        assert!(WhirlpoolState::decode(&data).is_err());
+        assert!(WhirlpoolState::decode(&data[..100]).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use colored::*;
+use tokio::sync::watch;
+use crate::config::MetricsConfig;
+use crate::utils::metrics_collector::{MetricsCollector, StrategyMetrics, SystemMetrics};
+use crate::utils::risk_controls::RiskManager;
+use crate::utils::token_pnl::{TokenPnl, TokenPnlLeaders};
+
+// What one summary block reports: the last window, the whole session, the wallet and the risk state
+#[derive(Debug, Clone)]
+pub struct PerformanceSummary {
+    pub window_minutes: u64,
+    pub window: SystemMetrics,
+    pub session: SystemMetrics,
+    pub current_balance: Option<f64>, // None without risk controls
+    pub top_strategies: Vec<StrategyMetrics>, // Most profitable first, at most 3
+    pub tokens: TokenPnlLeaders, // At most 3 each
+    pub active_pauses: Vec<String>,
+}
+
+impl PerformanceSummary {
+    pub async fn collect(metrics_collector: &MetricsCollector, risk_manager: Option<&RiskManager>, window_minutes: u64) -> Self {
+        let mut top_strategies: Vec<StrategyMetrics> = metrics_collector.get_all_strategy_metrics().await
+            .into_iter()
+            .filter(|strategy| strategy.executions > 0)
+            .collect();
+        top_strategies.sort_by(|a, b| b.total_profit.total_cmp(&a.total_profit));
+        top_strategies.truncate(3);
+
+        let (current_balance, active_pauses) = match risk_manager {
+            Some(risk_manager) => (Some(risk_manager.get_risk_metrics().await.current_balance), risk_manager.active_pauses().await),
+            None => (None, Vec::new()),
+        };
+
+        Self {
+            window_minutes,
+            window: metrics_collector.get_performance_in_window(window_minutes).await,
+            session: metrics_collector.get_system_metrics().await,
+            current_balance,
+            top_strategies,
+            tokens: metrics_collector.get_token_pnl_leaders(3).await,
+            active_pauses,
+        }
+    }
+
+    // The block as printed; `color` off leaves plain text for NO_COLOR and log files
+    pub fn render(&self, color: bool) -> String {
+        let session_secs = SystemTime::now().duration_since(self.session.start_time).unwrap_or_default().as_secs();
+        let mut lines = vec![
+            paint(format!("{:=^60}", " Performance summary "), color, |text| text.blue().bold()),
+            format!("{} {}", paint(format!("Last {}m:", self.window_minutes), color, |text| text.bold().yellow()), totals_line(&self.window, color)),
+            format!("{} {}", paint(format!("Session ({}):", format_duration(session_secs)), color, |text| text.bold().yellow()), totals_line(&self.session, color)),
+        ];
+
+        if let Some(balance) = self.current_balance {
+            lines.push(format!("{} {:.4} SOL", paint("Balance:".to_string(), color, |text| text.bold().yellow()), balance));
+        }
+
+        let strategies = if self.top_strategies.is_empty() {
+            "none executed yet".to_string()
+        } else {
+            self.top_strategies.iter()
+                .map(|strategy| format!(
+                    "{:?} {} ({}/{})",
+                    strategy.strategy_type, signed_sol(strategy.total_profit, color), strategy.successes, strategy.executions
+                ))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        lines.push(format!("{} {}", paint("Top strategies:".to_string(), color, |text| text.bold().yellow()), strategies));
+        lines.push(format!("{} {}", paint("Top tokens:".to_string(), color, |text| text.bold().yellow()), tokens_line(&self.tokens.winners, color)));
+        lines.push(format!("{} {}", paint("Worst tokens:".to_string(), color, |text| text.bold().yellow()), tokens_line(&self.tokens.losers, color)));
+
+        let pauses = if self.active_pauses.is_empty() {
+            paint("none".to_string(), color, |text| text.green())
+        } else {
+            paint(self.active_pauses.join(" | "), color, |text| text.red().bold())
+        };
+        lines.push(format!("{} {}", paint("Risk pauses:".to_string(), color, |text| text.bold().yellow()), pauses));
+        lines.push(paint("=".repeat(60), color, |text| text.blue()));
+        lines.join("\n")
+    }
+}
+
+// Logs a PerformanceSummary on a fixed interval, and once more at shutdown
+pub struct PerformanceSummarizer {
+    metrics_collector: Arc<MetricsCollector>,
+    risk_manager: Option<Arc<RiskManager>>,
+    interval: Option<Duration>, // None logs the shutdown summary only
+    color: bool,
+}
+
+impl PerformanceSummarizer {
+    pub fn new(metrics_collector: Arc<MetricsCollector>, risk_manager: Option<Arc<RiskManager>>, interval: Option<Duration>, color: bool) -> Self {
+        Self { metrics_collector, risk_manager, interval, color }
+    }
+
+    // PERFORMANCE_SUMMARY_MINS=0 turns the periodic summary off; NO_COLOR turns colors off
+    pub fn from_config(metrics_collector: Arc<MetricsCollector>, risk_manager: Option<Arc<RiskManager>>, config: &MetricsConfig) -> Self {
+        let minutes = config.performance_summary_mins;
+        Self::new(
+            metrics_collector,
+            risk_manager,
+            (minutes > 0).then(|| Duration::from_secs(minutes * 60)),
+            config.summary_color,
+        )
+    }
+
+    // Window for the "Last Nm" line: one interval, at least a minute; 15 without one
+    fn window_minutes(&self) -> u64 {
+        self.interval.map_or(15, |interval| (interval.as_secs() / 60).max(1))
+    }
+
+    pub async fn log_summary(&self) {
+        let summary = PerformanceSummary::collect(&self.metrics_collector, self.risk_manager.as_deref(), self.window_minutes()).await;
+        println!("{}", summary.render(self.color));
+    }
+
+    pub fn spawn(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let Some(interval) = self.interval else { return };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return, // The shutdown summary is logged by whoever drains
+                }
+                self.log_summary().await;
+            }
+        });
+    }
+}
+
+fn paint(text: String, color: bool, style: impl FnOnce(ColoredString) -> ColoredString) -> String {
+    if color { style(text.normal()).to_string() } else { text }
+}
+
+fn signed_sol(amount: f64, color: bool) -> String {
+    let text = format!("{:+.4} SOL", amount);
+    if amount < 0.0 {
+        paint(text, color, |text| text.red())
+    } else {
+        paint(text, color, |text| text.green())
+    }
+}
+
+fn totals_line(metrics: &SystemMetrics, color: bool) -> String {
+    format!(
+        "detected {} | evaluated {} | executed {} | success {:.1}% | PnL {} | fees {:.4} SOL | tips {:.4} SOL",
+        metrics.total_opportunities_detected,
+        metrics.total_opportunities_evaluated,
+        metrics.total_opportunities_executed,
+        metrics.execution_success_rate * 100.0,
+        signed_sol(metrics.total_profit, color),
+        metrics.total_fees_paid,
+        metrics.total_tips_paid,
+    )
+}
+
+fn tokens_line(tokens: &[TokenPnl], color: bool) -> String {
+    if tokens.is_empty() {
+        return "none".to_string();
+    }
+    tokens.iter()
+        .map(|token| format!("{} {} ({}/{})", short_mint(&token.mint), signed_sol(token.profit, color), token.successes, token.trades))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// First and last four characters of a mint address; shorter names as they are
+fn short_mint(mint: &str) -> String {
+    if mint.len() <= 12 || !mint.is_ascii() {
+        return mint.to_string();
+    }
+    format!("{}…{}", &mint[..4], &mint[mint.len() - 4..])
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mev_strategies::{MevStrategyResult, MevStrategyType};
+    use crate::config::Config;
+    use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
+    use crate::utils::pool_creation::WSOL_MINT;
+    use crate::utils::risk_controls::RiskLimits;
+
+    fn execution(strategy_type: MevStrategyType, success: bool, profit: f64) -> MevStrategyResult {
+        MevStrategyResult {
+            success,
+            profit,
+            fees_paid: 0.001,
+            tip_paid: 0.0005,
+            execution_time_ms: 40,
+            strategy_type,
+            phases: Vec::new(),
+            skip_reason: None,
+            route: None,
+            bundle_id: None,
+            pending: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summary_reports_window_session_strategies_and_pauses() {
+        let metrics_collector = MetricsCollector::new(&Config::default()).unwrap();
+        for (strategy_type, success, profit) in [
+            (MevStrategyType::Arbitrage, true, 0.02),
+            (MevStrategyType::Arbitrage, false, -0.001),
+            (MevStrategyType::Sandwich, true, 0.05),
+            (MevStrategyType::Backrun, true, 0.001),
+            (MevStrategyType::Frontrun, false, -0.002),
+        ] {
+            metrics_collector.record_strategy_execution(&execution(strategy_type, success, profit)).await;
+        }
+        // PnL counts successful executions only; failures show up in fees and tips
+        let opportunity = OpportunityDetails {
+            token_a: WSOL_MINT.to_string(),
+            token_b: "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.02,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        metrics_collector.record_token_outcome(&opportunity, &execution(MevStrategyType::Arbitrage, true, 0.02)).await;
+        let limits = RiskLimits { max_drawdown_percent: 0.0, ..RiskLimits::from_config(&Config::default().risk) };
+        let risk_manager = RiskManager::with_limits(limits);
+        risk_manager.initialize_balance(0.1).await; // Below the 0.5 SOL default minimum
+
+        let summary = PerformanceSummary::collect(&metrics_collector, Some(&risk_manager), 15).await;
+        let strategies: Vec<_> = summary.top_strategies.iter().map(|strategy| strategy.strategy_type.clone()).collect();
+        assert_eq!(strategies, vec![MevStrategyType::Sandwich, MevStrategyType::Arbitrage, MevStrategyType::Backrun]);
+        assert_eq!(summary.window.total_opportunities_executed, 5);
+        assert_eq!(summary.current_balance, Some(0.1));
+        assert_eq!(summary.active_pauses.len(), 1);
+
+        let text = summary.render(false);
+        assert!(!text.contains('\u{1b}'));
+        assert!(text.contains("Last 15m: detected 0 | evaluated 0 | executed 5 | success 60.0% | PnL +0.0710 SOL | fees 0.0050 SOL | tips 0.0025 SOL"));
+        assert!(text.contains("Balance: 0.1000 SOL"));
+        assert!(text.contains("Top strategies: Sandwich +0.0500 SOL (1/1) | Arbitrage +0.0200 SOL (1/2) | Backrun +0.0010 SOL (1/1)"));
+        assert!(text.contains("Top tokens: DezX…B263 +0.0200 SOL (1/1)"));
+        assert!(text.contains("Worst tokens: none"));
+        assert!(text.contains("Risk pauses: balance 0.1000 SOL below the 0.5000 SOL minimum"));
+    }
+
+    #[test]
+    fn test_session_duration_format() {
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(3599), "59m");
+        assert_eq!(format_duration(3600 * 26 + 300), "26h 05m");
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "orderbook")]
+use std::str::FromStr;
+#[cfg(feature = "orderbook")]
+use solana_sdk::instruction::{AccountMeta, Instruction};
+#[cfg(feature = "orderbook")]
+use solana_sdk::pubkey::Pubkey;
+#[cfg(feature = "orderbook")]
+use crate::utils::simulation_effects::TOKEN_PROGRAM_ID;
+
+pub const PHOENIX_PROGRAM_ID: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY";
+
+const MARKET_HEADER_LEN: usize = 576;
+// FIFOMarket after the header: 256 bytes of padding, six u64s, then the bid and ask trees
+const BASE_LOTS_PER_BASE_UNIT_OFFSET: usize = MARKET_HEADER_LEN + 256;
+const TAKER_FEE_BPS_OFFSET: usize = BASE_LOTS_PER_BASE_UNIT_OFFSET + 24;
+const BIDS_OFFSET: usize = BASE_LOTS_PER_BASE_UNIT_OFFSET + 48;
+// Tree: root u32, 12 bytes padding, allocator size u64, bump index u32, free list head u32
+const TREE_HEADER_LEN: usize = 32;
+// Node: left, right, parent, color u32 registers; order id (price in ticks u64, sequence number
+// u64); resting order (trader index u64, base lots u64, last valid slot u64, last valid time u64)
+const TREE_NODE_LEN: usize = 64;
+const SENTINEL: u32 = 0;
+
+#[cfg(feature = "orderbook")]
+const SWAP_INSTRUCTION_TAG: u8 = 0;
+#[cfg(feature = "orderbook")]
+const IMMEDIATE_OR_CANCEL_PACKET: u8 = 2;
+#[cfg(feature = "orderbook")]
+const SELF_TRADE_CANCEL_PROVIDE: u8 = 1;
+
+// Taker side: a bid buys base with quote, an ask sells base for quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+// Resting size at one price, orders at the same price merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookLevel {
+    pub price_in_ticks: u64,
+    pub base_lots: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookFill {
+    pub side: Side,
+    pub amount_in: u64, // Input consumed, fees included; less than asked when the book runs out
+    pub amount_out: u64,
+    pub fee: u64, // Quote atoms
+    pub levels: usize, // Price levels crossed
+    pub worst_price_in_ticks: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBook {
+    pub market: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub base_vault: String,
+    pub quote_vault: String,
+    pub base_lot_size: u64, // Base atoms per lot
+    pub quote_lot_size: u64, // Quote atoms per lot
+    pub tick_size: u64, // Quote atoms per base unit per tick
+    pub base_lots_per_base_unit: u64,
+    pub taker_fee_bps: u64,
+    pub bids: Vec<BookLevel>, // Best (highest) first
+    pub asks: Vec<BookLevel>, // Best (lowest) first
+}
+
+impl OrderBook {
+    // Header layout: discriminant u64, status u64, bids size u64 at 16, asks size u64 at 24, seats
+    // u64, base params (decimals u32, vault bump u32, mint 48, vault 80), base lot size u64 at 112,
+    // quote params (mint 128, vault 160), quote lot size u64 at 192, tick size u64 at 200, ...
+    // Orders with a time in force are kept: the program drops expired ones as it meets them, so a
+    // book holding many of them quotes slightly optimistic
+    pub fn decode(market: &str, data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < BIDS_OFFSET {
+            return Err(format!("Phoenix market account too short: {} bytes", data.len()).into());
+        }
+
+        let pubkey = |offset: usize| bs58::encode(&data[offset..offset + 32]).into_string();
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+
+        let (bids_size, asks_size) = (u64_at(16) as usize, u64_at(24) as usize);
+        let asks_offset = BIDS_OFFSET + TREE_HEADER_LEN + bids_size.saturating_mul(TREE_NODE_LEN);
+        let trees_end = asks_offset.saturating_add(TREE_HEADER_LEN + asks_size.saturating_mul(TREE_NODE_LEN));
+        if data.len() < trees_end {
+            return Err(format!("Phoenix market {} too short for {} bids and {} asks", market, bids_size, asks_size).into());
+        }
+
+        let base_lots_per_base_unit = u64_at(BASE_LOTS_PER_BASE_UNIT_OFFSET);
+        if base_lots_per_base_unit == 0 || u64_at(112) == 0 || u64_at(192) == 0 {
+            return Err(format!("Phoenix market {} has no lot sizes", market).into());
+        }
+
+        let mut bids: Vec<BookLevel> = read_levels(data, BIDS_OFFSET, bids_size);
+        bids.reverse();
+
+        Ok(Self {
+            market: market.to_string(),
+            base_mint: pubkey(48),
+            quote_mint: pubkey(128),
+            base_vault: pubkey(80),
+            quote_vault: pubkey(160),
+            base_lot_size: u64_at(112),
+            quote_lot_size: u64_at(192),
+            tick_size: u64_at(200),
+            base_lots_per_base_unit,
+            taker_fee_bps: u64_at(TAKER_FEE_BPS_OFFSET),
+            bids,
+            asks: read_levels(data, asks_offset, asks_size),
+        })
+    }
+
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids.first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks.first().copied()
+    }
+
+    // Quote atoms per base atom at a price
+    pub fn price(&self, price_in_ticks: u64) -> f64 {
+        price_in_ticks as f64 * self.tick_size as f64 / (self.base_lots_per_base_unit as f64 * self.base_lot_size as f64)
+    }
+
+    // Between the best ask and best bid, as a fraction of the mid price
+    pub fn spread(&self) -> Option<f64> {
+        let (bid, ask) = (self.price(self.best_bid()?.price_in_ticks), self.price(self.best_ask()?.price_in_ticks));
+        let mid = (bid + ask) / 2.0;
+        if mid > 0.0 { Some((ask - bid) / mid) } else { None }
+    }
+
+    // Resting base atoms on one side
+    pub fn depth(&self, side: Side) -> u64 {
+        let levels = if side == Side::Bid { &self.bids } else { &self.asks };
+        levels.iter().map(|level| level.base_lots.saturating_mul(self.base_lot_size)).sum()
+    }
+
+    // Quote lots for base lots at a price, as the matching engine rounds them
+    fn quote_lots(&self, base_lots: u64, price_in_ticks: u64, round_up: bool) -> u128 {
+        let tick_lots = (self.tick_size / self.quote_lot_size) as u128;
+        let numerator = base_lots as u128 * price_in_ticks as u128 * tick_lots;
+        let denominator = self.base_lots_per_base_unit as u128;
+        if round_up { numerator.div_ceil(denominator) } else { numerator / denominator }
+    }
+
+    // Market order of amount_in input_mint taking liquidity from the top of book down. Taker fees
+    // are charged in quote lots, rounded up: deducted from the proceeds when selling base, and
+    // reserved out of the budget when buying it. None when the market doesn't trade the mint
+    pub fn fill(&self, input_mint: &str, amount_in: u64) -> Option<BookFill> {
+        if input_mint == self.base_mint {
+            Some(self.fill_ask(amount_in))
+        } else if input_mint == self.quote_mint {
+            Some(self.fill_bid(amount_in))
+        } else {
+            None
+        }
+    }
+
+    fn fill_ask(&self, amount_in: u64) -> BookFill {
+        let mut left = amount_in / self.base_lot_size;
+        let (mut quote_lots, mut levels, mut worst_price_in_ticks) = (0u128, 0, 0);
+        for level in &self.bids {
+            if left == 0 {
+                break;
+            }
+            let taken = left.min(level.base_lots);
+            quote_lots += self.quote_lots(taken, level.price_in_ticks, false);
+            left -= taken;
+            levels += 1;
+            worst_price_in_ticks = level.price_in_ticks;
+        }
+
+        let fee_lots = (quote_lots * self.taker_fee_bps as u128).div_ceil(10_000);
+        let sold_lots = amount_in / self.base_lot_size - left;
+        BookFill {
+            side: Side::Ask,
+            amount_in: sold_lots * self.base_lot_size,
+            amount_out: ((quote_lots - fee_lots) * self.quote_lot_size as u128).min(u64::MAX as u128) as u64,
+            fee: (fee_lots * self.quote_lot_size as u128).min(u64::MAX as u128) as u64,
+            levels,
+            worst_price_in_ticks,
+        }
+    }
+
+    fn fill_bid(&self, amount_in: u64) -> BookFill {
+        // Budget left for matching once the fee on it is set aside
+        let mut left = (amount_in / self.quote_lot_size) as u128 * 10_000 / (10_000 + self.taker_fee_bps as u128);
+        let (mut base_lots, mut quote_lots, mut levels, mut worst_price_in_ticks) = (0u64, 0u128, 0, 0);
+        for level in &self.asks {
+            let lot_cost = self.quote_lots(1, level.price_in_ticks, false);
+            if lot_cost == 0 {
+                continue;
+            }
+            let whole_level = self.quote_lots(level.base_lots, level.price_in_ticks, true);
+            let taken = if left >= whole_level {
+                level.base_lots
+            } else {
+                (left * self.base_lots_per_base_unit as u128 / (level.price_in_ticks as u128 * (self.tick_size / self.quote_lot_size) as u128)) as u64
+            };
+            if taken == 0 {
+                break;
+            }
+            let cost = self.quote_lots(taken, level.price_in_ticks, true);
+            base_lots += taken;
+            quote_lots += cost;
+            left -= cost.min(left);
+            levels += 1;
+            worst_price_in_ticks = level.price_in_ticks;
+            if taken < level.base_lots {
+                break;
+            }
+        }
+
+        let fee_lots = (quote_lots * self.taker_fee_bps as u128).div_ceil(10_000);
+        BookFill {
+            side: Side::Bid,
+            amount_in: ((quote_lots + fee_lots) * self.quote_lot_size as u128).min(u64::MAX as u128) as u64,
+            amount_out: base_lots.saturating_mul(self.base_lot_size),
+            fee: (fee_lots * self.quote_lot_size as u128).min(u64::MAX as u128) as u64,
+            levels,
+            worst_price_in_ticks,
+        }
+    }
+}
+
+// In-order walk of a side's tree from its root, merging orders at the same price; ascending by
+// price. Free-listed nodes are never reached, and the walk stops after `capacity` nodes so a
+// corrupt tree can't loop
+fn read_levels(data: &[u8], tree_offset: usize, capacity: usize) -> Vec<BookLevel> {
+    let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 byte slice"));
+    let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+    let node_offset = |node: u32| tree_offset + TREE_HEADER_LEN + (node as usize - 1) * TREE_NODE_LEN;
+    let valid = |node: u32| node != SENTINEL && (node as usize) <= capacity;
+
+    let mut levels: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut stack = Vec::new();
+    let mut node = u32_at(tree_offset);
+    let mut visited = 0;
+    while (valid(node) || !stack.is_empty()) && visited < capacity {
+        while valid(node) && stack.len() < capacity {
+            stack.push(node);
+            node = u32_at(node_offset(node));
+        }
+        let Some(top) = stack.pop() else { break };
+        let offset = node_offset(top);
+        *levels.entry(u64_at(offset + 16)).or_default() += u64_at(offset + 40);
+        visited += 1;
+        node = u32_at(offset + 4);
+    }
+
+    levels.into_iter()
+        .filter(|(_, base_lots)| *base_lots > 0)
+        .map(|(price_in_ticks, base_lots)| BookLevel { price_in_ticks, base_lots })
+        .collect()
+}
+
+#[cfg(feature = "orderbook")]
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+#[cfg(feature = "orderbook")]
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), pubkey(TOKEN_PROGRAM_ID)?.as_ref(), mint.as_ref()],
+        &pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")?,
+    );
+    Ok(address)
+}
+
+// Swap: [program, log authority, market (w), trader (s), trader base (w), trader quote (w), base
+// vault (w), quote vault (w), token program], data [tag u8][borsh OrderPacket::ImmediateOrCancel].
+// No limit price: the order takes what the book has and fails unless min_amount_out fills. The
+// wallet's associated token accounts settle the trade
+#[cfg(feature = "orderbook")]
+pub fn ioc_instruction(
+    book: &OrderBook,
+    trader: &Pubkey,
+    input_mint: &str,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let side = if input_mint == book.base_mint {
+        Side::Ask
+    } else if input_mint == book.quote_mint {
+        Side::Bid
+    } else {
+        return Err(format!("Phoenix market {} doesn't trade {}", book.market, input_mint).into());
+    };
+
+    let program_id = pubkey(PHOENIX_PROGRAM_ID)?;
+    let base_mint = pubkey(&book.base_mint)?;
+    let quote_mint = pubkey(&book.quote_mint)?;
+    let (log_authority, _) = Pubkey::find_program_address(&[b"log"], &program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(log_authority, false),
+        AccountMeta::new(pubkey(&book.market)?, false),
+        AccountMeta::new_readonly(*trader, true),
+        AccountMeta::new(associated_token_address(trader, &base_mint)?, false),
+        AccountMeta::new(associated_token_address(trader, &quote_mint)?, false),
+        AccountMeta::new(pubkey(&book.base_vault)?, false),
+        AccountMeta::new(pubkey(&book.quote_vault)?, false),
+        AccountMeta::new_readonly(pubkey(TOKEN_PROGRAM_ID)?, false),
+    ];
+
+    let (num_base_lots, num_quote_lots, min_base_lots, min_quote_lots) = match side {
+        Side::Ask => (amount_in / book.base_lot_size, 0, 0, min_amount_out.div_ceil(book.quote_lot_size)),
+        Side::Bid => (0, amount_in / book.quote_lot_size, min_amount_out.div_ceil(book.base_lot_size), 0),
+    };
+
+    let mut data = vec![SWAP_INSTRUCTION_TAG, IMMEDIATE_OR_CANCEL_PACKET, if side == Side::Bid { 0 } else { 1 }];
+    data.push(0); // price_in_ticks: None
+    for value in [num_base_lots, num_quote_lots, min_base_lots, min_quote_lots] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(SELF_TRADE_CANCEL_PROVIDE);
+    data.push(0); // match_limit: None
+    data.extend_from_slice(&0u128.to_le_bytes()); // client_order_id
+    data.push(0); // use_only_deposited_funds
+    data.push(0); // last_valid_slot: None
+    data.push(0); // last_valid_unix_timestamp_in_seconds: None
+
+    Ok(Instruction { program_id, accounts, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    // Pubkeys from the registry tests, standing in for the market's vaults
+    const BASE_VAULT: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+    const QUOTE_VAULT: &str = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+    const MARKET: &str = "BJE5MMbqXjVwjAF7oxwPYXnTXDyspzZyt4vwenNw5ruG";
+    const TREE_SIZE: usize = 4;
+
+    fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    // Tree of (node, left, right, price in ticks, base lots)
+    fn put_tree(data: &mut [u8], offset: usize, root: u32, nodes: &[(u32, u32, u32, u64, u64)]) {
+        put(data, offset, &root.to_le_bytes());
+        put(data, offset + 16, &(nodes.len() as u64).to_le_bytes());
+        for (node, left, right, price_in_ticks, base_lots) in nodes {
+            let node_offset = offset + TREE_HEADER_LEN + (*node as usize - 1) * TREE_NODE_LEN;
+            put(data, node_offset, &left.to_le_bytes());
+            put(data, node_offset + 4, &right.to_le_bytes());
+            put(data, node_offset + 16, &price_in_ticks.to_le_bytes());
+            put(data, node_offset + 24, &(*node as u64).to_le_bytes());
+            put(data, node_offset + 40, &base_lots.to_le_bytes());
+        }
+    }
+
+    // SOL/USDC market laid out as the program stores it: 0.001 SOL lots, 1 micro-USDC quote lots,
+    // 0.001 USDC ticks and a 5 bps taker fee; 150,000 ticks is 150 USDC per SOL
+    fn market_account() -> Vec<u8> {
+        let asks_offset = BIDS_OFFSET + TREE_HEADER_LEN + TREE_SIZE * TREE_NODE_LEN;
+        let mut data = vec![0u8; asks_offset + TREE_HEADER_LEN + TREE_SIZE * TREE_NODE_LEN];
+        put(&mut data, 16, &(TREE_SIZE as u64).to_le_bytes());
+        put(&mut data, 24, &(TREE_SIZE as u64).to_le_bytes());
+        put(&mut data, 40, &9u32.to_le_bytes());
+        put(&mut data, 48, &bs58::decode(WSOL_MINT).into_vec().unwrap());
+        put(&mut data, 80, &bs58::decode(BASE_VAULT).into_vec().unwrap());
+        put(&mut data, 112, &1_000_000u64.to_le_bytes());
+        put(&mut data, 120, &6u32.to_le_bytes());
+        put(&mut data, 128, &bs58::decode(USDC_MINT).into_vec().unwrap());
+        put(&mut data, 160, &bs58::decode(QUOTE_VAULT).into_vec().unwrap());
+        put(&mut data, 192, &1u64.to_le_bytes());
+        put(&mut data, 200, &1_000u64.to_le_bytes());
+        put(&mut data, BASE_LOTS_PER_BASE_UNIT_OFFSET, &1_000u64.to_le_bytes());
+        put(&mut data, BASE_LOTS_PER_BASE_UNIT_OFFSET + 8, &1_000u64.to_le_bytes());
+        put(&mut data, TAKER_FEE_BPS_OFFSET, &5u64.to_le_bytes());
+        // Two bids at 150, one at 149.9; node 4 is unlinked, as a filled order's node would be
+        put_tree(&mut data, BIDS_OFFSET, 2, &[(1, 0, 0, 149_900, 5_000), (2, 1, 3, 150_000, 2_000), (3, 0, 0, 150_000, 1_000), (4, 0, 0, 151_000, 9_999)]);
+        put_tree(&mut data, asks_offset, 1, &[(1, 0, 2, 150_100, 3_000), (2, 0, 0, 150_300, 10_000)]);
+        data
+    }
+
+    fn book() -> OrderBook {
+        OrderBook::decode(MARKET, &market_account()).unwrap()
+    }
+
+    #[test]
+    fn test_decodes_top_of_book_and_depth() {
+        let book = book();
+        assert_eq!((book.base_mint.as_str(), book.quote_mint.as_str()), (WSOL_MINT, USDC_MINT));
+        assert_eq!((book.base_vault.as_str(), book.quote_vault.as_str()), (BASE_VAULT, QUOTE_VAULT));
+        assert_eq!(book.taker_fee_bps, 5);
+
+        // Orders at the same price merge; the free-listed node never shows up
+        assert_eq!(book.bids, vec![
+            BookLevel { price_in_ticks: 150_000, base_lots: 3_000 },
+            BookLevel { price_in_ticks: 149_900, base_lots: 5_000 },
+        ]);
+        assert_eq!(book.best_ask(), Some(BookLevel { price_in_ticks: 150_100, base_lots: 3_000 }));
+        assert_eq!((book.depth(Side::Bid), book.depth(Side::Ask)), (8_000_000_000, 13_000_000_000));
+
+        // Raw USDC per lamport, and 0.1 USDC between 150 and 150.1
+        assert!((book.price(150_000) - 0.15).abs() < 1e-12);
+        assert!((book.spread().unwrap() - 0.1 / 150.05).abs() < 1e-12);
+
+        assert!(OrderBook::decode(MARKET, &market_account()[..BIDS_OFFSET + 100]).is_err());
+    }
+
+    #[test]
+    fn test_fills_walk_the_book_from_the_top() {
+        let book = book();
+
+        // Selling 4 SOL: 3 at 150, 1 at 149.9, then 5 bps off the 599.9 USDC
+        let fill = book.fill(WSOL_MINT, 4_000_000_000).unwrap();
+        assert_eq!((fill.side, fill.amount_in, fill.levels, fill.worst_price_in_ticks), (Side::Ask, 4_000_000_000, 2, 149_900));
+        assert_eq!((fill.amount_out, fill.fee), (599_600_050, 299_950));
+
+        // Buying with 300 USDC: 299.85 left after the fee reserve buys 1.997 SOL at 150.1
+        let fill = book.fill(USDC_MINT, 300_000_000).unwrap();
+        assert_eq!((fill.side, fill.amount_out, fill.levels), (Side::Bid, 1_997_000_000, 1));
+        assert_eq!((fill.amount_in, fill.fee), (299_899_575, 149_875));
+
+        // More than the book holds: everything on the side, the rest unspent
+        let fill = book.fill(WSOL_MINT, 10_000_000_000).unwrap();
+        assert_eq!((fill.amount_in, fill.levels), (8_000_000_000, 2));
+        assert!(book.fill(BASE_VAULT, 1_000).is_none());
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_builds_ioc_swap_instruction() {
+        let trader = Pubkey::new_unique();
+        let instruction = ioc_instruction(&book(), &trader, WSOL_MINT, 4_000_000_000, 599_000_000).unwrap();
+
+        assert_eq!(instruction.program_id.to_string(), PHOENIX_PROGRAM_ID);
+        assert_eq!(instruction.accounts[2].pubkey.to_string(), MARKET);
+        assert!(instruction.accounts[3].is_signer && instruction.accounts[3].pubkey == trader);
+        assert_eq!(instruction.accounts[6].pubkey.to_string(), BASE_VAULT);
+        // Swap, IOC, ask, no price, 4,000 base lots in and at least 599 USDC out
+        assert_eq!(&instruction.data[..4], &[0, 2, 1, 0]);
+        assert_eq!(u64::from_le_bytes(instruction.data[4..12].try_into().unwrap()), 4_000);
+        assert_eq!(u64::from_le_bytes(instruction.data[28..36].try_into().unwrap()), 599_000_000);
+        assert_eq!(instruction.data.len(), 4 + 32 + 2 + 16 + 3);
+
+        assert!(ioc_instruction(&book(), &trader, BASE_VAULT, 1, 0).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+use crate::config::PoolsConfig;
+use crate::logging::Logger;
+use crate::utils::tip_history::now_unix;
+
+const FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlacklistReason {
+    NetLoss,
+    FailureRate,
+    Manual, // Listed in POOL_BLACKLIST_CONFIG
+}
+
+impl BlacklistReason {
+    // Stable label for metrics
+    pub fn label(&self) -> &'static str {
+        match self {
+            BlacklistReason::NetLoss => "net_loss",
+            BlacklistReason::FailureRate => "failure_rate",
+            BlacklistReason::Manual => "manual",
+        }
+    }
+}
+
+impl fmt::Display for BlacklistReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlacklistReason::NetLoss => write!(f, "net loss over the limit"),
+            BlacklistReason::FailureRate => write!(f, "failure rate over the limit"),
+            BlacklistReason::Manual => write!(f, "listed in the blacklist config"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlacklistEntry {
+    pub pool: String,
+    pub reason: BlacklistReason,
+    pub net_loss: f64, // SOL lost on the pool up to the listing
+    pub listed_at: u64, // Unix seconds
+    pub expires_at: Option<u64>, // None for manual entries: they stay until removed from the config
+}
+
+impl BlacklistEntry {
+    pub fn is_active_at(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolBlacklistConfig {
+    pub max_net_loss: f64, // SOL lost on one pool that lists it; 0 disables
+    pub failure_window: usize, // Last attempts the failure rate is measured over; 0 disables
+    pub max_failure_rate: f64, // Share of failures in a full window above which the pool is listed
+    pub duration: Duration, // How long an automatic entry lasts
+}
+
+impl Default for PoolBlacklistConfig {
+    fn default() -> Self {
+        Self { max_net_loss: 0.05, failure_window: 10, max_failure_rate: 0.8, duration: Duration::from_secs(86_400) }
+    }
+}
+
+impl PoolBlacklistConfig {
+    pub fn from_config(config: &PoolsConfig) -> Self {
+        Self {
+            max_net_loss: config.blacklist_max_loss_sol,
+            failure_window: config.blacklist_failure_window,
+            max_failure_rate: config.blacklist_max_failure_rate,
+            duration: Duration::from_secs(config.blacklist_secs),
+        }
+    }
+}
+
+// Realized results on one pool since it was last listed (or first traded)
+#[derive(Debug, Default)]
+struct PoolStats {
+    realized_pnl: f64,
+    recent_failures: VecDeque<bool>, // Last `failure_window` attempts, oldest first; true = failed
+}
+
+#[derive(Debug, Default)]
+struct BlacklistState {
+    stats: HashMap<String, PoolStats>,
+    entries: HashMap<String, BlacklistEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BlacklistFile {
+    version: u32,
+    entries: Vec<BlacklistEntry>,
+}
+
+// Pools that keep losing money or keep failing, which the evaluator stops emitting opportunities
+// on for a while. Automatic entries are persisted across restarts; manual ones are reloaded from
+// POOL_BLACKLIST_CONFIG on every start. Cheap to clone: clones share the same state
+#[derive(Clone)]
+pub struct PoolBlacklist {
+    config: PoolBlacklistConfig,
+    state: Arc<RwLock<BlacklistState>>,
+    file: Option<String>, // None keeps the blacklist in memory only
+    file_write: Arc<Mutex<()>>, // Serializes saves so an older snapshot can't land after a newer one
+}
+
+impl PoolBlacklist {
+    pub fn new(config: PoolBlacklistConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(RwLock::new(BlacklistState::default())),
+            file: None,
+            file_write: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn from_config(config: &PoolsConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut blacklist = Self::new(PoolBlacklistConfig::from_config(config)).with_file(&config.blacklist_file, now_unix());
+
+        // Only read when configured, and then it must exist
+        if let Some(config_path) = &config.blacklist_config {
+            let contents = std::fs::read_to_string(config_path)
+                .map_err(|e| format!("Failed to read POOL_BLACKLIST_CONFIG {}: {}", config_path, e))?;
+            blacklist = blacklist.with_manual_entries(&contents, now_unix())
+                .map_err(|e| format!("Invalid pool blacklist config {}: {}", config_path, e))?;
+        }
+        Ok(blacklist)
+    }
+
+    // Restores the still running entries from `path` and persists every new one there
+    pub fn with_file(mut self, path: &str, now: u64) -> Self {
+        let restored = load_entries(path).into_iter()
+            .filter(|entry| entry.reason != BlacklistReason::Manual && entry.is_active_at(now))
+            .collect::<Vec<_>>();
+        if !restored.is_empty() {
+            Logger::status_update(&format!("Restored {} blacklisted pools from {}", restored.len(), path));
+        }
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.get_mut().entries.extend(restored.into_iter().map(|entry| (entry.pool.clone(), entry)));
+        }
+        self.file = Some(path.to_string());
+        self
+    }
+
+    // Format: ["<pool address>", ...]
+    pub fn with_manual_entries(mut self, contents: &str, now: u64) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let pools: Vec<String> = serde_json::from_str(contents)
+            .map_err(|e| format!("malformed pool list: {}", e))?;
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            let entries = &mut state.get_mut().entries;
+            for pool in pools.into_iter().map(|pool| pool.trim().to_string()).filter(|pool| !pool.is_empty()) {
+                entries.insert(pool.clone(), BlacklistEntry {
+                    pool,
+                    reason: BlacklistReason::Manual,
+                    net_loss: 0.0,
+                    listed_at: now,
+                    expires_at: None,
+                });
+            }
+        }
+        Ok(self)
+    }
+
+    pub async fn is_blacklisted(&self, pool: &str) -> bool {
+        self.is_blacklisted_at(pool, now_unix()).await
+    }
+
+    pub async fn is_blacklisted_at(&self, pool: &str, now: u64) -> bool {
+        self.state.read().await.entries.get(pool).is_some_and(|entry| entry.is_active_at(now))
+    }
+
+    // First listed pool among `pools`, if any
+    pub async fn first_blacklisted<'a>(&self, pools: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        let now = now_unix();
+        let state = self.state.read().await;
+        pools.into_iter().find(|pool| state.entries.get(*pool).is_some_and(|entry| entry.is_active_at(now)))
+    }
+
+    pub async fn record_outcome(&self, pool: &str, pnl: f64, failed: bool) -> Option<BlacklistEntry> {
+        self.record_outcome_at(pool, pnl, failed, now_unix()).await
+    }
+
+    // Books one realized result on the pool: net PnL in SOL and whether the attempt failed. Returns
+    // the entry when this result gets the pool listed; its stats then start over, so a pool coming
+    // off the list isn't put straight back by the results that listed it
+    pub async fn record_outcome_at(&self, pool: &str, pnl: f64, failed: bool, now: u64) -> Option<BlacklistEntry> {
+        let entry = {
+            let mut state = self.state.write().await;
+            // Executions already in flight when the pool was listed
+            if state.entries.get(pool).is_some_and(|entry| entry.is_active_at(now)) {
+                return None;
+            }
+
+            let stats = state.stats.entry(pool.to_string()).or_default();
+            stats.realized_pnl += pnl;
+            if self.config.failure_window > 0 {
+                stats.recent_failures.push_back(failed);
+                while stats.recent_failures.len() > self.config.failure_window {
+                    stats.recent_failures.pop_front();
+                }
+            }
+
+            let net_loss = (-stats.realized_pnl).max(0.0);
+            let failure_rate = (stats.recent_failures.len() == self.config.failure_window && self.config.failure_window > 0)
+                .then(|| stats.recent_failures.iter().filter(|failed| **failed).count() as f64 / self.config.failure_window as f64);
+            let reason = if self.config.max_net_loss > 0.0 && net_loss > self.config.max_net_loss {
+                BlacklistReason::NetLoss
+            } else if failure_rate.is_some_and(|rate| rate > self.config.max_failure_rate) {
+                BlacklistReason::FailureRate
+            } else {
+                return None;
+            };
+
+            state.stats.remove(pool);
+            let entry = BlacklistEntry {
+                pool: pool.to_string(),
+                reason,
+                net_loss,
+                listed_at: now,
+                expires_at: Some(now + self.config.duration.as_secs()),
+            };
+            state.entries.insert(pool.to_string(), entry.clone());
+            entry
+        };
+
+        Logger::status_update(&format!(
+            "Blacklisting pool {} for {}s: {} (net loss {:.6} SOL)",
+            entry.pool, self.config.duration.as_secs(), entry.reason, entry.net_loss
+        ));
+        self.save().await;
+        Some(entry)
+    }
+
+    pub async fn entries(&self) -> Vec<BlacklistEntry> {
+        self.entries_at(now_unix()).await
+    }
+
+    // Active entries, largest loss first. Expired ones are dropped (and the file rewritten) here
+    pub async fn entries_at(&self, now: u64) -> Vec<BlacklistEntry> {
+        let (mut entries, expired) = {
+            let mut state = self.state.write().await;
+            let expired = state.entries.values()
+                .filter(|entry| !entry.is_active_at(now))
+                .map(|entry| entry.pool.clone())
+                .collect::<Vec<_>>();
+            for pool in &expired {
+                state.entries.remove(pool);
+            }
+            (state.entries.values().cloned().collect::<Vec<_>>(), expired)
+        };
+
+        if !expired.is_empty() {
+            Logger::status_update(&format!("Pool blacklist expired for {}", expired.join(", ")));
+            self.save().await;
+        }
+        entries.sort_by(|a, b| b.net_loss.total_cmp(&a.net_loss).then_with(|| a.pool.cmp(&b.pool)));
+        entries
+    }
+
+    // Writes the automatic entries out now; used on shutdown
+    pub async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(ref path) = self.file else {
+            return Ok(());
+        };
+        let _write = self.file_write.lock().await;
+        let entries = self.state.read().await.entries.values()
+            .filter(|entry| entry.reason != BlacklistReason::Manual)
+            .cloned()
+            .collect();
+        save_entries(path, entries)
+    }
+
+    // A failed write is logged and retried with the next change
+    async fn save(&self) {
+        if let Err(e) = self.flush().await {
+            Logger::error_occurred(&format!("Failed to persist pool blacklist: {}", e));
+        }
+    }
+}
+
+// A missing file is an empty blacklist. An unreadable one is moved aside so it can be looked at
+fn load_entries(path: &str) -> Vec<BlacklistEntry> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let reason = match serde_json::from_str::<BlacklistFile>(&contents) {
+        Ok(file) if file.version == FILE_VERSION => return file.entries,
+        Ok(file) => format!("unsupported version {}", file.version),
+        Err(e) => e.to_string(),
+    };
+    let quarantined = format!("{}.corrupt-{}", path, now_unix());
+    match std::fs::rename(path, &quarantined) {
+        Ok(_) => Logger::error_occurred(&format!("Ignoring corrupt pool blacklist {} ({}), moved it to {}", path, reason, quarantined)),
+        Err(e) => Logger::error_occurred(&format!("Ignoring corrupt pool blacklist {} ({}), could not move it aside: {}", path, reason, e)),
+    }
+    Vec::new()
+}
+
+fn save_entries(path: &str, entries: Vec<BlacklistEntry>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let json = serde_json::to_string_pretty(&BlacklistFile { version: FILE_VERSION, entries })
+        .map_err(|e| format!("Failed to serialize pool blacklist: {}", e))?;
+    crate::utils::atomic_write(path, json.as_bytes())
+        .map_err(|e| format!("Failed to write pool blacklist to {}: {}", path, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+    const START: u64 = 1_700_000_000;
+
+    fn config() -> PoolBlacklistConfig {
+        PoolBlacklistConfig { max_net_loss: 0.05, failure_window: 4, max_failure_rate: 0.5, duration: Duration::from_secs(3600) }
+    }
+
+    #[tokio::test]
+    async fn test_net_loss_lists_the_pool_until_it_expires() {
+        let blacklist = PoolBlacklist::new(config());
+
+        // Wins offset losses: only the net result counts
+        assert!(blacklist.record_outcome_at(POOL, 0.03, false, START).await.is_none());
+        assert!(blacklist.record_outcome_at(POOL, -0.06, false, START).await.is_none());
+        let entry = blacklist.record_outcome_at(POOL, -0.03, false, START + 10).await.unwrap();
+        assert_eq!(entry.reason, BlacklistReason::NetLoss);
+        assert!((entry.net_loss - 0.06).abs() < 1e-9);
+        assert_eq!(entry.expires_at, Some(START + 3610));
+        assert!(blacklist.is_blacklisted_at(POOL, START + 3609).await);
+
+        // Results of executions still in flight don't extend the listing
+        assert!(blacklist.record_outcome_at(POOL, -1.0, false, START + 20).await.is_none());
+        assert_eq!(blacklist.entries_at(START + 20).await.len(), 1);
+
+        // Expired: off the list, with a clean slate
+        assert!(!blacklist.is_blacklisted_at(POOL, START + 3610).await);
+        assert!(blacklist.entries_at(START + 3610).await.is_empty());
+        assert!(blacklist.record_outcome_at(POOL, -0.04, false, START + 3620).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failure_rate_is_measured_over_a_full_window() {
+        let blacklist = PoolBlacklist::new(PoolBlacklistConfig { max_net_loss: 0.0, ..config() });
+
+        // Three failures out of three: the window isn't full yet
+        for _ in 0..3 {
+            assert!(blacklist.record_outcome_at(POOL, -0.001, true, START).await.is_none());
+        }
+        // 3 of 4 failed, over the 50% limit
+        let entry = blacklist.record_outcome_at(POOL, 0.0, false, START).await.unwrap();
+        assert_eq!(entry.reason, BlacklistReason::FailureRate);
+
+        // Exactly at the limit is allowed; older attempts slide out of the window
+        let other = "7XawhbbxtsRcQA8KTkHT9f9nc6d69UwqCDh6U5EEbEmX";
+        for failed in [true, true, false, false, true, false] {
+            assert!(blacklist.record_outcome_at(other, 0.0, failed, START).await.is_none());
+        }
+        assert!(!blacklist.is_blacklisted_at(other, START).await);
+    }
+
+    #[tokio::test]
+    async fn test_entries_survive_a_restart_and_manual_ones_never_expire() {
+        let path = std::env::temp_dir().join(format!("pool_blacklist_{}.json", std::process::id())).to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&path);
+        let manual = "7XawhbbxtsRcQA8KTkHT9f9nc6d69UwqCDh6U5EEbEmX";
+
+        let blacklist = PoolBlacklist::new(config()).with_file(&path, START)
+            .with_manual_entries(&format!("[\"{}\"]", manual), START).unwrap();
+        blacklist.record_outcome_at(POOL, -0.1, false, START).await.unwrap();
+
+        // Manual entries come from the config, not the file
+        let restarted = PoolBlacklist::new(config()).with_file(&path, START + 60);
+        assert!(restarted.is_blacklisted_at(POOL, START + 60).await);
+        assert!(!restarted.is_blacklisted_at(manual, START + 60).await);
+
+        // Expired entries aren't restored
+        let much_later = PoolBlacklist::new(config()).with_file(&path, START + 3600);
+        assert!(much_later.entries_at(START + 3600).await.is_empty());
+        assert!(blacklist.is_blacklisted_at(manual, START + 365 * 86_400).await);
+        let _ = std::fs::remove_file(&path);
+    }
+}
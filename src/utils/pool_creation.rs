@@ -0,0 +1,218 @@
+use serde_json::Value;
+use crate::config::SnipeConfig;
+use crate::utils::instruction_parser::{self, ParsedInstruction};
+
+pub const RAYDIUM_AMM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+const INITIALIZE2_DISCRIMINATOR: u8 = 1;
+const INITIALIZE2_MIN_ACCOUNTS: usize = 21;
+
+#[derive(Debug, Clone)]
+pub struct NewPoolInfo {
+    pub pool_address: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub initial_base_amount: u64,
+    pub initial_quote_amount: u64,
+    pub open_time: u64,
+    pub lp_mint: String,
+    pub creation_slot: Option<u64>, // Slot the initialize2 landed in, when the fetch carries it
+}
+
+impl NewPoolInfo {
+    // Initial quote-side liquidity in SOL; USDC pools need the SOL price of USDC
+    pub fn initial_liquidity_sol(&self, usdc_price_in_sol: f64) -> f64 {
+        match self.quote_mint.as_str() {
+            WSOL_MINT => self.initial_quote_amount as f64 / 1_000_000_000.0,
+            USDC_MINT => self.initial_quote_amount as f64 / 1_000_000.0 * usdc_price_in_sol,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnipeFilters {
+    pub allowed_quote_mints: Vec<String>,
+    pub buy_amount_sol: f64,
+    pub target_return: f64, // Expected take-profit used to rank snipes against other opportunities
+}
+
+impl SnipeFilters {
+    pub fn from_config(config: &SnipeConfig) -> Self {
+        Self {
+            allowed_quote_mints: config.quote_mints.clone(),
+            buy_amount_sol: config.buy_amount_sol,
+            target_return: config.target_return,
+        }
+    }
+
+    // Returns the rejection reason, or None if the pool's quote mint is one we trade. Liquidity,
+    // age and LP rules are launch filters applied by the false positive reducer
+    pub fn rejection_reason(&self, pool: &NewPoolInfo) -> Option<String> {
+        if !self.allowed_quote_mints.iter().any(|mint| mint == &pool.quote_mint) {
+            return Some(format!("quote mint {} not allowed", pool.quote_mint));
+        }
+
+        None
+    }
+
+    pub fn buy_amount_lamports(&self) -> u64 {
+        (self.buy_amount_sol * 1_000_000_000.0) as u64
+    }
+}
+
+// Detects a Raydium AMM v4 initialize2 (pool creation) in a fetched transaction
+pub fn detect_raydium_pool_creation(tx_details: &Value) -> Option<NewPoolInfo> {
+    let logs = instruction_parser::log_messages(tx_details);
+    let has_initialize_log = logs.iter().any(|log| log.contains("initialize2"));
+
+    let instruction = instruction_parser::all_instructions(tx_details)
+        .into_iter()
+        .find(|ix| ix.program_id == RAYDIUM_AMM_V4_PROGRAM_ID && is_initialize2(ix))?;
+
+    if instruction.accounts.len() < INITIALIZE2_MIN_ACCOUNTS {
+        return None;
+    }
+
+    let (open_time, pc_amount, coin_amount) = decode_initialize2_amounts(&instruction)
+        .or_else(|| if has_initialize_log { parse_initialize2_log(&logs) } else { None })?;
+
+    let coin_mint = instruction.accounts[8].clone();
+    let pc_mint = instruction.accounts[9].clone();
+
+    // Raydium's "pc" side is normally the quote, but some launches pair the new token as pc
+    let (base_mint, quote_mint, base_amount, quote_amount) =
+        if is_known_quote(&coin_mint) && !is_known_quote(&pc_mint) {
+            (pc_mint, coin_mint, pc_amount, coin_amount)
+        } else {
+            (coin_mint, pc_mint, coin_amount, pc_amount)
+        };
+
+    Some(NewPoolInfo {
+        pool_address: instruction.accounts[4].clone(),
+        base_mint,
+        quote_mint,
+        initial_base_amount: base_amount,
+        initial_quote_amount: quote_amount,
+        open_time,
+        lp_mint: instruction.accounts[7].clone(),
+        creation_slot: tx_details["slot"].as_u64(),
+    })
+}
+
+fn is_known_quote(mint: &str) -> bool {
+    mint == WSOL_MINT || mint == USDC_MINT
+}
+
+fn is_initialize2(instruction: &ParsedInstruction) -> bool {
+    match instruction_parser::decode_instruction_data(instruction) {
+        Some(data) => data.first() == Some(&INITIALIZE2_DISCRIMINATOR),
+        // Without data we can only rely on the account count
+        None => instruction.accounts.len() >= INITIALIZE2_MIN_ACCOUNTS,
+    }
+}
+
+// Layout: [discriminator u8, nonce u8, open_time u64, init_pc_amount u64, init_coin_amount u64]
+fn decode_initialize2_amounts(instruction: &ParsedInstruction) -> Option<(u64, u64, u64)> {
+    let data = instruction_parser::decode_instruction_data(instruction)?;
+    if data.len() < 26 || data[0] != INITIALIZE2_DISCRIMINATOR {
+        return None;
+    }
+
+    let read_u64 = |offset: usize| -> Option<u64> {
+        data.get(offset..offset + 8)?.try_into().ok().map(u64::from_le_bytes)
+    };
+
+    Some((read_u64(2)?, read_u64(10)?, read_u64(18)?))
+}
+
+// "initialize2: InitializeInstruction2 { nonce: 254, open_time: 0, init_pc_amount: 1, init_coin_amount: 2 }"
+fn parse_initialize2_log(logs: &[&str]) -> Option<(u64, u64, u64)> {
+    let line = logs.iter().find(|log| log.contains("initialize2"))?;
+
+    let field = |name: &str| -> Option<u64> {
+        let start = line.find(&format!("{}: ", name))? + name.len() + 2;
+        line[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .ok()
+    };
+
+    Some((field("open_time").unwrap_or(0), field("init_pc_amount")?, field("init_coin_amount")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // getTransaction ("json" encoding) result of a Raydium initialize2, laid out as on mainnet:
+    // compute budget instructions first, keys in message order (signer, writable, read-only) and
+    // the real token/ATA/system programs, rent sysvar, AMM authority, create-pool config, fee
+    // account and OpenBook program in their initialize2 slots. The new pool's own accounts
+    // (pool, mints, vaults, market, creator) are stand-ins
+    const POOL: &str = "EHrH4HPq6iNnaUhzRW3KZ3nTLWh5WFCwD5p6xQxEyv4X";
+    const LP_MINT: &str = "HApbMe93yKq3jsJijdxG2364bXbYrR96d6cJH4RJY7wQ";
+    const NEW_TOKEN: &str = "5bDDb6UhCevDxkcNK3MJuuCTUNomb6pjX65FtRe9iknY";
+    const RAYDIUM_INSTRUCTION: usize = 2;
+
+    fn pool_creation_fixture() -> Value {
+        serde_json::from_str(include_str!("../../tests/fixtures/raydium_initialize2.json")).unwrap()
+    }
+
+    fn filters() -> SnipeFilters {
+        SnipeFilters {
+            allowed_quote_mints: vec![WSOL_MINT.to_string(), USDC_MINT.to_string()],
+            buy_amount_sol: 0.1,
+            target_return: 0.2,
+        }
+    }
+
+    #[test]
+    fn test_detects_pool_creation_from_fixture() {
+        let pool = detect_raydium_pool_creation(&pool_creation_fixture()).expect("pool creation detected");
+        assert_eq!(pool.pool_address, POOL);
+        assert_eq!(pool.base_mint, NEW_TOKEN);
+        assert_eq!(pool.quote_mint, WSOL_MINT);
+        assert_eq!(pool.initial_base_amount, 1_000_000_000_000_000);
+        assert_eq!(pool.initial_quote_amount, 50_000_000_000);
+        assert_eq!(pool.open_time, 1_700_000_000);
+        assert_eq!(pool.lp_mint, LP_MINT);
+        assert_eq!(pool.creation_slot, Some(250_000_000));
+        assert!((pool.initial_liquidity_sol(0.0) - 50.0).abs() < 1e-9);
+        assert!(filters().rejection_reason(&pool).is_none());
+    }
+
+    #[test]
+    fn test_falls_back_to_log_amounts() {
+        let mut fixture = pool_creation_fixture();
+        fixture["transaction"]["message"]["instructions"][RAYDIUM_INSTRUCTION]
+            .as_object_mut()
+            .unwrap()
+            .remove("data");
+
+        let pool = detect_raydium_pool_creation(&fixture).expect("pool creation detected");
+        assert_eq!(pool.initial_quote_amount, 50_000_000_000);
+        assert_eq!(pool.open_time, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_filters_reject_unknown_quote() {
+        let mut pool = detect_raydium_pool_creation(&pool_creation_fixture()).unwrap();
+        pool.quote_mint = "SomeOtherMint".to_string();
+        assert!(filters().rejection_reason(&pool).unwrap().contains("not allowed"));
+    }
+
+    #[test]
+    fn test_ignores_regular_transactions() {
+        let tx = json!({
+            "transaction": { "message": { "accountKeys": ["A", "B"], "instructions": [{ "programIdIndex": 1, "accounts": [0], "data": "" }] } },
+            "meta": { "logMessages": [] }
+        });
+        assert!(detect_raydium_pool_creation(&tx).is_none());
+    }
+}
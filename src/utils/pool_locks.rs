@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::config::PoolsConfig;
+
+// Why an execution couldn't take its locks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockRejection {
+    PoolBusy,
+    TargetBusy,
+    PoolCoolingDown,
+}
+
+impl LockRejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            LockRejection::PoolBusy => "pool_busy",
+            LockRejection::TargetBusy => "target_busy",
+            LockRejection::PoolCoolingDown => "pool_cooldown",
+        }
+    }
+}
+
+#[derive(Default)]
+struct LockState {
+    pools: HashMap<String, Instant>,     // pool -> acquired at
+    targets: HashMap<String, Instant>,   // target signature -> acquired at
+    cooldowns: HashMap<String, Instant>, // pool -> off-limits until
+}
+
+// In-flight executions keyed by pool and target signature, so concurrent workers don't end up
+// competing with our own bundles or spending the risk budget twice on one pool. Locks are held
+// until the execution's transactions are out (see release) or the guard drops; one older than
+// the hold timeout counts as abandoned
+pub struct PoolLockRegistry {
+    state: Arc<Mutex<LockState>>,
+    hold_timeout: Duration,
+    failure_cooldown: Duration,
+}
+
+// Releases the pool and target when dropped
+pub struct PoolLockGuard {
+    state: Arc<Mutex<LockState>>,
+    pool: Option<String>,
+    target: Option<String>,
+    acquired_at: Instant,
+    cooldown: Option<Duration>,
+    failure_cooldown: Duration,
+}
+
+impl PoolLockGuard {
+    // Keep the pool off-limits for the failure cooldown after release, e.g. after a failed sandwich
+    pub fn mark_failed(&mut self) {
+        self.cooldown = Some(self.failure_cooldown);
+    }
+}
+
+impl Drop for PoolLockGuard {
+    fn drop(&mut self) {
+        let Ok(mut state) = self.state.lock() else { return };
+        // A lock that timed out may have been taken over since; leave the new holder's entry
+        if let Some(pool) = &self.pool {
+            if state.pools.get(pool) == Some(&self.acquired_at) {
+                state.pools.remove(pool);
+            }
+            if let Some(cooldown) = self.cooldown {
+                state.cooldowns.insert(pool.clone(), Instant::now() + cooldown);
+            }
+        }
+        if let Some(target) = &self.target {
+            if state.targets.get(target) == Some(&self.acquired_at) {
+                state.targets.remove(target);
+            }
+        }
+    }
+}
+
+impl PoolLockRegistry {
+    pub fn new(hold_timeout: Duration, failure_cooldown: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LockState::default())),
+            hold_timeout,
+            failure_cooldown,
+        }
+    }
+
+    pub fn from_config(config: &PoolsConfig) -> Self {
+        Self::new(Duration::from_millis(config.lock_timeout_ms), Duration::from_millis(config.failure_cooldown_ms))
+    }
+
+    // Takes the pool and target together or neither
+    pub fn try_acquire(&self, pool: Option<&str>, target: Option<&str>) -> Result<PoolLockGuard, LockRejection> {
+        self.try_acquire_at(pool, target, Instant::now())
+    }
+
+    fn try_acquire_at(&self, pool: Option<&str>, target: Option<&str>, now: Instant) -> Result<PoolLockGuard, LockRejection> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let held = |acquired_at: &Instant| now.saturating_duration_since(*acquired_at) < self.hold_timeout;
+
+        if let Some(pool) = pool {
+            if state.cooldowns.get(pool).is_some_and(|until| now < *until) {
+                return Err(LockRejection::PoolCoolingDown);
+            }
+            if state.pools.get(pool).is_some_and(held) {
+                return Err(LockRejection::PoolBusy);
+            }
+        }
+        if let Some(target) = target {
+            if state.targets.get(target).is_some_and(held) {
+                return Err(LockRejection::TargetBusy);
+            }
+        }
+
+        if let Some(pool) = pool {
+            state.cooldowns.remove(pool);
+            state.pools.insert(pool.to_string(), now);
+        }
+        if let Some(target) = target {
+            state.targets.insert(target.to_string(), now);
+        }
+        Ok(PoolLockGuard {
+            state: Arc::clone(&self.state),
+            pool: pool.map(str::to_string),
+            target: target.map(str::to_string),
+            acquired_at: now,
+            cooldown: None,
+            failure_cooldown: self.failure_cooldown,
+        })
+    }
+
+    // Frees the pool and target ahead of the guard's drop, once the execution has sent its
+    // transactions and only their landing is left to watch. The guard's failure cooldown still
+    // applies when it drops
+    pub fn release(&self, pool: Option<&str>, target: Option<&str>) {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(pool) = pool {
+            state.pools.remove(pool);
+        }
+        if let Some(target) = target {
+            state.targets.remove(target);
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().map_or(0, |state| state.pools.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_concurrent_opportunities_on_one_pool_submit_once() {
+        let registry = Arc::new(PoolLockRegistry::new(Duration::from_secs(5), Duration::from_secs(2)));
+        let submissions = Arc::new(AtomicUsize::new(0));
+
+        let workers: Vec<_> = ["sig-a", "sig-b"].into_iter()
+            .map(|target| {
+                let registry = Arc::clone(&registry);
+                let submissions = Arc::clone(&submissions);
+                tokio::spawn(async move {
+                    if let Ok(_lock) = registry.try_acquire(Some("Pool1"), Some(target)) {
+                        submissions.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.await.unwrap();
+        }
+
+        assert_eq!(submissions.load(Ordering::SeqCst), 1);
+        assert_eq!(registry.in_flight(), 0);
+        // Released: the next opportunity on the pool goes through
+        assert!(registry.try_acquire(Some("Pool1"), Some("sig-c")).is_ok());
+    }
+
+    #[test]
+    fn test_failed_sandwich_cools_pool_down() {
+        let registry = PoolLockRegistry::new(Duration::from_secs(5), Duration::from_secs(2));
+        {
+            let mut lock = registry.try_acquire(Some("Pool1"), Some("sig-a")).unwrap();
+            assert_eq!(registry.try_acquire(Some("Pool2"), Some("sig-a")).err(), Some(LockRejection::TargetBusy));
+            lock.mark_failed();
+        }
+        assert_eq!(registry.try_acquire(Some("Pool1"), None).err(), Some(LockRejection::PoolCoolingDown));
+        assert!(registry.try_acquire(Some("Pool2"), None).is_ok());
+        assert!(registry.try_acquire_at(Some("Pool1"), None, Instant::now() + Duration::from_secs(3)).is_ok());
+    }
+
+    #[test]
+    fn test_released_pool_is_free_while_the_guard_lives() {
+        let registry = PoolLockRegistry::new(Duration::from_secs(5), Duration::from_secs(2));
+        let mut lock = registry.try_acquire(Some("Pool1"), Some("sig-a")).unwrap();
+        registry.release(Some("Pool1"), Some("sig-a"));
+        assert_eq!(registry.in_flight(), 0);
+
+        // Another execution takes the pool while the first watches its bundle land; the first
+        // finishing doesn't free it, but its failure still cools the pool down
+        let next = registry.try_acquire_at(Some("Pool1"), Some("sig-b"), Instant::now() + Duration::from_millis(1)).unwrap();
+        lock.mark_failed();
+        drop(lock);
+        assert_eq!(registry.in_flight(), 1);
+        drop(next);
+        assert_eq!(registry.try_acquire(Some("Pool1"), None).err(), Some(LockRejection::PoolCoolingDown));
+    }
+
+    #[test]
+    fn test_abandoned_lock_expires() {
+        let registry = PoolLockRegistry::new(Duration::from_secs(5), Duration::from_secs(2));
+        let stale = registry.try_acquire(Some("Pool1"), None).unwrap();
+        assert_eq!(registry.try_acquire(Some("Pool1"), None).err(), Some(LockRejection::PoolBusy));
+
+        let later = Instant::now() + Duration::from_secs(6);
+        let fresh = registry.try_acquire_at(Some("Pool1"), None, later).unwrap();
+        // The stale holder finishing late doesn't free the pool under the new one
+        drop(stale);
+        assert_eq!(registry.try_acquire_at(Some("Pool1"), None, later).err(), Some(LockRejection::PoolBusy));
+        drop(fresh);
+    }
+}
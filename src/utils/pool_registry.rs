@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use crate::config::PoolsConfig;
+use crate::logging::Logger;
+use crate::utils::dex_monitor::PoolInfo;
+use crate::utils::pool_creation::WSOL_MINT;
+
+// DEXes whose pool (or, for Phoenix, market) accounts the evaluator can decode
+pub const SUPPORTED_POOL_DEXES: &[&str] = &["Raydium", "Orca", "Meteora", "Phoenix"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolEntry {
+    pub dex: String,
+    pub address: String,
+    pub token_a: String,
+    pub token_b: String,
+}
+
+impl PoolEntry {
+    pub fn has_pair(&self, token_a: &str, token_b: &str) -> bool {
+        (self.token_a == token_a && self.token_b == token_b) || (self.token_a == token_b && self.token_b == token_a)
+    }
+
+    // Pair key with SOL first when present, otherwise ordered, so both orientations match
+    pub fn pair(&self) -> (String, String) {
+        if self.token_b == WSOL_MINT || (self.token_a != WSOL_MINT && self.token_b < self.token_a) {
+            (self.token_b.clone(), self.token_a.clone())
+        } else {
+            (self.token_a.clone(), self.token_b.clone())
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !SUPPORTED_POOL_DEXES.contains(&self.dex.as_str()) {
+            return Err(format!("unsupported dex '{}' (supported: {})", self.dex, SUPPORTED_POOL_DEXES.join(", ")));
+        }
+        for (field, value) in [("address", &self.address), ("token_a", &self.token_a), ("token_b", &self.token_b)] {
+            if bs58::decode(value).into_vec().map_or(true, |key| key.len() != 32) {
+                return Err(format!("{} '{}' is not a valid public key", field, value));
+            }
+        }
+        if self.token_a == self.token_b {
+            return Err(format!("pool {} has the same mint on both sides", self.address));
+        }
+        Ok(())
+    }
+}
+
+// Known pools per token pair and DEX, loaded from POOLS_FILE at startup and optionally
+// extended with pools seen in the mempool
+pub struct PoolRegistry {
+    pools: Arc<RwLock<HashMap<String, PoolEntry>>>, // address -> pool
+    auto_add: bool,
+}
+
+impl PoolRegistry {
+    pub fn new(auto_add: bool) -> Self {
+        Self {
+            pools: Arc::new(RwLock::new(HashMap::new())),
+            auto_add,
+        }
+    }
+
+    pub async fn from_config(config: &PoolsConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let registry = Self::new(config.registry_auto_add);
+
+        // An explicitly configured file must exist; the default one is optional
+        let (path, required) = match &config.registry_file {
+            Some(path) => (path.clone(), true),
+            None => ("pools.json".to_string(), false),
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => registry.load_config(&contents).await
+                .map_err(|e| format!("Invalid pool registry {}: {}", path, e))?,
+            Err(e) if required => return Err(format!("Failed to read POOLS_FILE {}: {}", path, e).into()),
+            Err(_) => {}
+        }
+
+        let counts = registry.counts_by_dex().await;
+        let summary: Vec<String> = counts.iter().map(|(dex, count)| format!("{} {}", dex, count)).collect();
+        Logger::status_update(&format!(
+            "Loaded {} pools from {} ({})",
+            counts.values().sum::<usize>(),
+            path,
+            if summary.is_empty() { "none".to_string() } else { summary.join(", ") }
+        ));
+
+        Ok(registry)
+    }
+
+    // Format: [{"dex": "Raydium", "address": "...", "token_a": "<mint>", "token_b": "<mint>"}]
+    pub async fn load_config(&self, contents: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let entries: Vec<PoolEntry> = serde_json::from_str(contents)
+            .map_err(|e| format!("malformed pool list: {}", e))?;
+
+        let mut pools = self.pools.write().await;
+        for (index, entry) in entries.into_iter().enumerate() {
+            entry.validate().map_err(|e| format!("entry {}: {}", index, e))?;
+            pools.insert(entry.address.clone(), entry);
+        }
+        Ok(())
+    }
+
+    pub async fn insert(&self, entry: PoolEntry) -> bool {
+        if entry.validate().is_err() {
+            return false;
+        }
+        self.pools.write().await.insert(entry.address.clone(), entry).is_none()
+    }
+
+    // Adds a pool seen in the mempool when POOL_REGISTRY_AUTO_ADD is enabled; true if it was new
+    pub async fn observe(&self, entry: PoolEntry) -> bool {
+        if !self.auto_add || self.contains(&entry.address).await {
+            return false;
+        }
+        let (address, dex) = (entry.address.clone(), entry.dex.clone());
+        let added = self.insert(entry).await;
+        if added {
+            Logger::status_update(&format!("Added {} pool {} to the pool registry", dex, address));
+        }
+        added
+    }
+
+    pub async fn contains(&self, address: &str) -> bool {
+        self.pools.read().await.contains_key(address)
+    }
+
+    pub async fn len(&self) -> usize {
+        self.pools.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.pools.read().await.is_empty()
+    }
+
+    pub async fn pools(&self) -> Vec<PoolEntry> {
+        self.pools.read().await.values().cloned().collect()
+    }
+
+    pub async fn pools_for_pair(&self, token_a: &str, token_b: &str) -> Vec<PoolEntry> {
+        self.pools.read().await
+            .values()
+            .filter(|pool| pool.has_pair(token_a, token_b))
+            .cloned()
+            .collect()
+    }
+
+    // Pairs with pools on at least two DEXes or pool addresses, i.e. arbitrage candidates
+    pub async fn arbitrage_pairs(&self) -> Vec<((String, String), Vec<PoolEntry>)> {
+        let mut by_pair: BTreeMap<(String, String), Vec<PoolEntry>> = BTreeMap::new();
+        for pool in self.pools.read().await.values() {
+            by_pair.entry(pool.pair()).or_default().push(pool.clone());
+        }
+        by_pair.into_iter().filter(|(_, pools)| pools.len() >= 2).collect()
+    }
+
+    pub async fn counts_by_dex(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for pool in self.pools.read().await.values() {
+            *counts.entry(pool.dex.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // Pools in the DEXMonitor's format; reserves are filled in when pool states are fetched
+    pub async fn pool_infos(&self) -> Vec<PoolInfo> {
+        self.pools.read().await
+            .values()
+            .map(|pool| PoolInfo {
+                address: pool.address.clone(),
+                token_a: pool.token_a.clone(),
+                token_b: pool.token_b.clone(),
+                reserve_a: 0,
+                reserve_b: 0,
+                pool_type: pool.dex.to_lowercase(),
+            })
+            .collect()
+    }
+}
+
+impl Clone for PoolRegistry {
+    fn clone(&self) -> Self {
+        PoolRegistry {
+            pools: Arc::clone(&self.pools),
+            auto_add: self.auto_add,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::USDC_MINT;
+
+    const RAYDIUM_SOL_USDC: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+    const ORCA_SOL_USDC: &str = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+
+    fn config() -> String {
+        format!(r#"[
+            {{"dex": "Raydium", "address": "{}", "token_a": "{}", "token_b": "{}"}},
+            {{"dex": "Orca", "address": "{}", "token_a": "{}", "token_b": "{}"}}
+        ]"#, RAYDIUM_SOL_USDC, WSOL_MINT, USDC_MINT, ORCA_SOL_USDC, USDC_MINT, WSOL_MINT)
+    }
+
+    #[tokio::test]
+    async fn test_loads_pools_and_groups_pairs_across_orientations() {
+        let registry = PoolRegistry::new(false);
+        registry.load_config(&config()).await.unwrap();
+
+        let counts = registry.counts_by_dex().await;
+        assert_eq!(counts.get("Raydium"), Some(&1));
+        assert_eq!(counts.get("Orca"), Some(&1));
+
+        let pairs = registry.arbitrage_pairs().await;
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].0, (WSOL_MINT.to_string(), USDC_MINT.to_string()));
+        assert_eq!(registry.pools_for_pair(USDC_MINT, WSOL_MINT).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_config_fails() {
+        let registry = PoolRegistry::new(false);
+        assert!(registry.load_config("{not json").await.is_err());
+
+        let unknown_dex = format!(r#"[{{"dex": "Serum", "address": "{}", "token_a": "{}", "token_b": "{}"}}]"#, RAYDIUM_SOL_USDC, WSOL_MINT, USDC_MINT);
+        assert!(registry.load_config(&unknown_dex).await.is_err());
+
+        let bad_mint = format!(r#"[{{"dex": "Raydium", "address": "{}", "token_a": "SOL", "token_b": "USDC"}}]"#, RAYDIUM_SOL_USDC);
+        assert!(registry.load_config(&bad_mint).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_observed_pools_are_added_only_when_enabled() {
+        let entry = PoolEntry {
+            dex: "Raydium".to_string(),
+            address: RAYDIUM_SOL_USDC.to_string(),
+            token_a: WSOL_MINT.to_string(),
+            token_b: USDC_MINT.to_string(),
+        };
+
+        let disabled = PoolRegistry::new(false);
+        assert!(!disabled.observe(entry.clone()).await);
+        assert_eq!(disabled.len().await, 0);
+
+        let enabled = PoolRegistry::new(true);
+        assert!(enabled.observe(entry.clone()).await);
+        assert!(!enabled.observe(entry).await);
+        assert_eq!(enabled.len().await, 1);
+    }
+}
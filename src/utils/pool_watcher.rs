@@ -0,0 +1,448 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use base64::Engine;
+use futures::SinkExt;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use crate::config::PoolsConfig;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::bounded_cache::BoundedCache;
+use crate::utils::opportunity_evaluator::PoolState;
+use crate::utils::orca_whirlpool::WhirlpoolState;
+use crate::utils::raydium_amm::{self, AmmInfo};
+
+// Activity is remembered for this many pools; the least active are forgotten first
+const MAX_TRACKED_ACTIVITY: usize = 1_000;
+
+#[derive(Debug, Clone)]
+enum WatchedSource {
+    // Reserves live in the vault token accounts; the AMM account is read once
+    Raydium { amm: AmmInfo, coin_amount: u64, pc_amount: u64 },
+    // Price and liquidity live in the pool account itself
+    Whirlpool,
+}
+
+#[derive(Debug, Clone)]
+struct WatchedPool {
+    source: WatchedSource,
+    last_update: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct PoolActivity {
+    count: u64,
+    last_seen: Instant,
+}
+
+#[derive(Default)]
+struct WatcherState {
+    activity: HashMap<String, PoolActivity>,
+    watched: HashMap<String, WatchedPool>,
+    accounts: HashMap<String, String>, // Subscribed account -> pool
+    loading: HashSet<String>,
+}
+
+impl WatcherState {
+    fn record_activity(&mut self, pool_address: &str, now: Instant) {
+        let entry = self.activity.entry(pool_address.to_string()).or_insert_with(|| PoolActivity {
+            count: 0,
+            last_seen: now,
+        });
+        entry.count += 1;
+        entry.last_seen = now;
+
+        if self.activity.len() > MAX_TRACKED_ACTIVITY {
+            let watched = &self.watched;
+            if let Some(coldest) = self.activity.iter()
+                .filter(|(pool, _)| !watched.contains_key(*pool) && pool.as_str() != pool_address)
+                .min_by_key(|(_, activity)| (activity.count, activity.last_seen))
+                .map(|(pool, _)| pool.clone())
+            {
+                self.activity.remove(&coldest);
+            }
+        }
+    }
+
+    // Least active watched pool, ties going to the one seen longest ago
+    fn least_active_watched(&self) -> Option<(String, u64)> {
+        self.watched.keys()
+            .map(|pool| (pool, self.activity.get(pool)))
+            .min_by_key(|(_, activity)| activity.map(|a| (a.count, a.last_seen)))
+            .map(|(pool, activity)| (pool.clone(), activity.map_or(0, |a| a.count)))
+    }
+
+    // Whether the pool should be watched and, when full, which watched pool it replaces.
+    // A newcomer must be strictly more active than the pool it evicts, which bounds churn
+    fn admission(&self, pool_address: &str, max_pools: usize) -> Option<Option<String>> {
+        if max_pools == 0 || self.watched.contains_key(pool_address) || self.loading.contains(pool_address) {
+            return None;
+        }
+        if self.watched.len() + self.loading.len() < max_pools {
+            return Some(None);
+        }
+        let count = self.activity.get(pool_address).map_or(0, |a| a.count);
+        match self.least_active_watched() {
+            Some((evicted, evicted_count)) if count > evicted_count => Some(Some(evicted)),
+            _ => None,
+        }
+    }
+
+    fn unwatch(&mut self, pool_address: &str) {
+        self.watched.remove(pool_address);
+        self.accounts.retain(|_, pool| pool != pool_address);
+    }
+
+    fn watch(&mut self, pool_address: &str, source: WatchedSource, accounts: Vec<String>) {
+        self.loading.remove(pool_address);
+        for account in accounts {
+            self.accounts.insert(account, pool_address.to_string());
+        }
+        self.watched.insert(pool_address.to_string(), WatchedPool {
+            source,
+            last_update: Instant::now(),
+        });
+    }
+
+    // Applies new account data and returns the pool's updated state
+    fn apply_update(&mut self, account: &str, data: &[u8]) -> Result<Option<PoolState>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(pool_address) = self.accounts.get(account).cloned() else { return Ok(None) };
+        let Some(watched) = self.watched.get_mut(&pool_address) else { return Ok(None) };
+
+        let state = match &mut watched.source {
+            WatchedSource::Raydium { amm, coin_amount, pc_amount } => {
+                let amount = raydium_amm::token_account_amount(data)?;
+                if account == amm.coin_vault {
+                    *coin_amount = amount;
+                } else {
+                    *pc_amount = amount;
+                }
+                PoolState::from_raydium(&pool_address, amm, *coin_amount, *pc_amount)
+            }
+            WatchedSource::Whirlpool => PoolState::from_whirlpool(&pool_address, &WhirlpoolState::decode(data)?),
+        };
+        watched.last_update = Instant::now();
+        Ok(Some(state))
+    }
+}
+
+// Keeps the reserves of the most active pools current through accountSubscribe, so the
+// evaluator's pool cache isn't only refreshed when an opportunity needs it
+pub struct PoolWatcher {
+    rpc_manager: Arc<RpcManager>,
+    pool_states: BoundedCache<PoolState>,
+    max_pools: usize,
+    state: Arc<Mutex<WatcherState>>,
+    updates: broadcast::Sender<PoolState>, // Each pool state as soon as its reserves change
+}
+
+impl PoolWatcher {
+    pub fn new(rpc_manager: Arc<RpcManager>, pool_states: BoundedCache<PoolState>, max_pools: usize) -> Self {
+        Self {
+            rpc_manager,
+            pool_states,
+            max_pools,
+            state: Arc::new(Mutex::new(WatcherState::default())),
+            updates: broadcast::channel(256).0,
+        }
+    }
+
+    // Every pool state whose reserves change from here on. A receiver that falls behind gets
+    // RecvError::Lagged and should treat every pool as changed
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<PoolState> {
+        self.updates.subscribe()
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, pool_states: BoundedCache<PoolState>, config: &PoolsConfig) -> Self {
+        Self::new(rpc_manager, pool_states, config.watcher_max_pools) // 0 disables the watcher
+    }
+
+    // Counts an opportunity seen on the pool and starts watching it when it's among the most active
+    pub async fn record_activity(&self, pool_address: &str, dex: &str) {
+        if self.max_pools == 0 || !matches!(dex, "Raydium" | "Orca") {
+            return;
+        }
+
+        let evicted = {
+            let mut state = self.state.lock().await;
+            state.record_activity(pool_address, Instant::now());
+            match state.admission(pool_address, self.max_pools) {
+                Some(evicted) => {
+                    if let Some(ref evicted) = evicted {
+                        state.unwatch(evicted);
+                    }
+                    state.loading.insert(pool_address.to_string());
+                    evicted
+                }
+                None => return,
+            }
+        };
+        if let Some(evicted) = evicted {
+            Logger::status_update(&format!("Pool watcher replacing {} with more active pool {}", evicted, pool_address));
+        }
+
+        // Loading the pool takes RPC calls; don't hold up the evaluation that triggered it
+        let watcher = self.clone();
+        let (pool_address, dex) = (pool_address.to_string(), dex.to_string());
+        tokio::spawn(async move {
+            if let Err(e) = watcher.load_pool(&pool_address, &dex).await {
+                Logger::status_update(&format!("Pool watcher could not load {} pool {}: {}", dex, pool_address, e));
+                watcher.state.lock().await.loading.remove(&pool_address);
+            }
+        });
+    }
+
+    async fn load_pool(&self, pool_address: &str, dex: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let data = self.rpc_manager.get_account_data(pool_address).await?;
+        let (source, accounts, pool_state) = if dex == "Raydium" {
+            let amm = AmmInfo::decode(&data)?;
+            let vaults = vec![amm.coin_vault.clone(), amm.pc_vault.clone()];
+            let vault_data = self.rpc_manager.get_multiple_accounts_data(&vaults).await?;
+            let amount = |index: usize| -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+                raydium_amm::token_account_amount(vault_data[index].as_deref().ok_or("Vault account not found")?)
+            };
+            let (coin_amount, pc_amount) = (amount(0)?, amount(1)?);
+            let pool_state = PoolState::from_raydium(pool_address, &amm, coin_amount, pc_amount);
+            (WatchedSource::Raydium { amm, coin_amount, pc_amount }, vaults, pool_state)
+        } else {
+            let pool_state = PoolState::from_whirlpool(pool_address, &WhirlpoolState::decode(&data)?);
+            (WatchedSource::Whirlpool, vec![pool_address.to_string()], pool_state)
+        };
+
+        self.state.lock().await.watch(pool_address, source, accounts);
+        self.pool_states.insert(pool_address.to_string(), pool_state).await;
+        Ok(())
+    }
+
+    // Milliseconds since each watched pool last received an update
+    pub async fn update_ages(&self) -> Vec<(String, u64)> {
+        self.state.lock().await.watched.iter()
+            .map(|(pool, watched)| (pool.clone(), watched.last_update.elapsed().as_millis() as u64))
+            .collect()
+    }
+
+    pub async fn watched_count(&self) -> usize {
+        self.state.lock().await.watched.len()
+    }
+
+    // Keeps one subscription connection open until shutdown, reconnecting with backoff
+    pub fn start(&self, ws_url: String, shutdown: watch::Receiver<bool>) {
+        if self.max_pools == 0 {
+            return;
+        }
+        let watcher = self.clone();
+        tokio::spawn(async move {
+            let mut backoff = Duration::from_secs(1);
+            loop {
+                if *shutdown.borrow() {
+                    return;
+                }
+                match watcher.run_connection(&ws_url, shutdown.clone()).await {
+                    Ok(()) => return,
+                    Err(e) => Logger::error_occurred(&format!("Pool watcher connection failed: {}, reconnecting in {}s", e, backoff.as_secs())),
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(30));
+            }
+        });
+    }
+
+    async fn run_connection(&self, ws_url: &str, mut shutdown: watch::Receiver<bool>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (ws_stream, _) = connect_async(ws_url).await
+            .map_err(|e| format!("WebSocket connection failed: {}", e))?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+        // Subscriptions are per connection, so all of them start over on reconnect
+        let mut next_id = 1u64;
+        let mut pending: HashMap<u64, String> = HashMap::new();     // request id -> account
+        let mut subscribed: HashMap<String, u64> = HashMap::new();  // account -> subscription
+        let mut by_subscription: HashMap<u64, String> = HashMap::new();
+        let mut reconcile = tokio::time::interval(Duration::from_millis(500));
+
+        loop {
+            tokio::select! {
+                _ = reconcile.tick() => {
+                    let wanted: HashSet<String> = self.state.lock().await.accounts.keys().cloned().collect();
+
+                    let missing: Vec<String> = wanted.iter()
+                        .filter(|a| !subscribed.contains_key(*a) && !pending.values().any(|p| p == *a))
+                        .cloned()
+                        .collect();
+                    for account in missing {
+                        let request = json!({
+                            "jsonrpc": "2.0",
+                            "id": next_id,
+                            "method": "accountSubscribe",
+                            "params": [account, { "encoding": "base64", "commitment": "processed" }]
+                        });
+                        ws_sender.send(Message::Text(request.to_string())).await
+                            .map_err(|e| format!("Failed to subscribe to {}: {}", account, e))?;
+                        pending.insert(next_id, account);
+                        next_id += 1;
+                    }
+
+                    let stale: Vec<String> = subscribed.keys().filter(|a| !wanted.contains(*a)).cloned().collect();
+                    for account in stale {
+                        if let Some(subscription) = subscribed.remove(&account) {
+                            by_subscription.remove(&subscription);
+                            let request = json!({
+                                "jsonrpc": "2.0",
+                                "id": next_id,
+                                "method": "accountUnsubscribe",
+                                "params": [subscription]
+                            });
+                            next_id += 1;
+                            ws_sender.send(Message::Text(request.to_string())).await
+                                .map_err(|e| format!("Failed to unsubscribe from {}: {}", account, e))?;
+                        }
+                    }
+                }
+                message = ws_receiver.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Ping(payload))) => {
+                            let _ = ws_sender.send(Message::Pong(payload)).await;
+                            continue;
+                        }
+                        Some(Ok(Message::Close(_))) | None => return Err("connection closed".into()),
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(format!("WebSocket error: {}", e).into()),
+                    };
+                    let Ok(value) = serde_json::from_str::<Value>(&text) else { continue };
+
+                    // Subscribe confirmation: {"jsonrpc":"2.0","result":<subscription>,"id":<request>}
+                    if let Some(account) = value["id"].as_u64().and_then(|id| pending.remove(&id)) {
+                        if let Some(subscription) = value["result"].as_u64() {
+                            subscribed.insert(account.clone(), subscription);
+                            by_subscription.insert(subscription, account);
+                        }
+                        continue;
+                    }
+
+                    if value["method"] == "accountNotification" {
+                        let params = &value["params"];
+                        let Some(account) = params["subscription"].as_u64().and_then(|s| by_subscription.get(&s)) else { continue };
+                        let Some(encoded) = params["result"]["value"]["data"][0].as_str() else { continue };
+                        let Ok(data) = base64::engine::general_purpose::STANDARD.decode(encoded) else { continue };
+
+                        let update = self.state.lock().await.apply_update(account, &data);
+                        match update {
+                            Ok(Some(pool_state)) => {
+                                self.pool_states.insert(pool_state.pool_address.clone(), pool_state.clone()).await;
+                                // Nobody listening isn't an error
+                                let _ = self.updates.send(pool_state);
+                            }
+                            Ok(None) => {}
+                            Err(e) => Logger::status_update(&format!("Pool watcher could not decode {}: {}", account, e)),
+                        }
+                    }
+                }
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        let _ = ws_sender.close().await;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Clone for PoolWatcher {
+    fn clone(&self) -> Self {
+        PoolWatcher {
+            rpc_manager: Arc::clone(&self.rpc_manager),
+            pool_states: self.pool_states.clone(),
+            max_pools: self.max_pools,
+            state: Arc::clone(&self.state),
+            updates: self.updates.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+    fn amm() -> AmmInfo {
+        AmmInfo {
+            coin_decimals: 9,
+            pc_decimals: 6,
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            coin_need_take_pnl: 0,
+            pc_need_take_pnl: 0,
+            coin_vault: "CoinVau1t".to_string(),
+            pc_vault: "PcVau1t".to_string(),
+            coin_mint: WSOL_MINT.to_string(),
+            pc_mint: USDC_MINT.to_string(),
+        }
+    }
+
+    fn token_account(amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    fn active_state(pools: &[(&str, u64)]) -> WatcherState {
+        let mut state = WatcherState::default();
+        let now = Instant::now();
+        for (pool, count) in pools {
+            for _ in 0..*count {
+                state.record_activity(pool, now);
+            }
+            state.watch(pool, WatchedSource::Whirlpool, vec![format!("{}-account", pool)]);
+        }
+        state
+    }
+
+    #[test]
+    fn test_vault_updates_refresh_reserves() {
+        let mut state = WatcherState::default();
+        let source = WatchedSource::Raydium { amm: amm(), coin_amount: 100_000_000_000, pc_amount: 15_000_000_000 };
+        state.watch("Poo1", source, vec!["CoinVau1t".to_string(), "PcVau1t".to_string()]);
+
+        let updated = state.apply_update("CoinVau1t", &token_account(101_000_000_000)).unwrap().unwrap();
+        assert_eq!((updated.reserve_a, updated.reserve_b), (101_000_000_000, 15_000_000_000));
+        let updated = state.apply_update("PcVau1t", &token_account(14_852_000_000)).unwrap().unwrap();
+        assert_eq!((updated.reserve_a, updated.reserve_b), (101_000_000_000, 14_852_000_000));
+
+        assert!(state.apply_update("Unknown", &token_account(1)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replaces_least_active_pool_only_when_outranked() {
+        let mut state = active_state(&[("Hot", 10), ("Cold", 2)]);
+        let now = Instant::now();
+
+        // Full at two pools: a newcomer seen once doesn't displace anything
+        state.record_activity("New", now);
+        assert_eq!(state.admission("New", 2), None);
+
+        for _ in 0..2 {
+            state.record_activity("New", now);
+        }
+        assert_eq!(state.admission("New", 2), Some(Some("Cold".to_string())));
+        assert_eq!(state.admission("Hot", 2), None); // Already watched
+
+        state.unwatch("Cold");
+        assert!(!state.accounts.contains_key("Cold-account"));
+        assert_eq!(state.admission("New", 2), Some(None));
+    }
+
+    #[test]
+    fn test_activity_tracking_is_bounded() {
+        let mut state = active_state(&[("Watched", 1)]);
+        let now = Instant::now();
+        for i in 0..(MAX_TRACKED_ACTIVITY * 2) {
+            state.record_activity(&format!("Pool{}", i), now);
+        }
+        assert!(state.activity.len() <= MAX_TRACKED_ACTIVITY);
+        assert!(state.activity.contains_key("Watched"));
+    }
+}
@@ -0,0 +1,367 @@
+use std::future::Future;
+use std::time::Duration;
+use futures::SinkExt;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use solana_sdk::signature::{read_keypair_file, Signer};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use crate::config::{Config, Network};
+use crate::mempool::solana::ws_endpoint_label;
+use crate::rpc::error::RpcError;
+use crate::rpc::rpc_manager::{RpcEndpointType, RpcManager};
+use crate::utils::jito::JitoClient;
+
+// Per check; a hung endpoint fails its check instead of stalling startup
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Why a check failed, and what to change so it passes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflightFailure {
+    pub error: String,
+    pub hint: String,
+}
+
+impl PreflightFailure {
+    fn new(error: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { error: error.into(), hint: hint.into() }
+    }
+}
+
+// One probe: what it found when it passed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub outcome: Result<String, PreflightFailure>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+    pub wallet: Option<String>,
+    pub balance_sol: Option<f64>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.outcome.is_ok())
+    }
+
+    pub fn failures(&self) -> Vec<&PreflightCheck> {
+        self.checks.iter().filter(|check| check.outcome.is_err()).collect()
+    }
+
+    fn push(&mut self, name: impl Into<String>, outcome: Result<String, PreflightFailure>) {
+        self.checks.push(PreflightCheck { name: name.into(), outcome });
+    }
+}
+
+// Every RPC endpoint, every WebSocket endpoint, the Jito block engine when it's configured, the
+// keypair and its balance. Nothing stops at the first failure, so one run shows everything to fix
+pub async fn run(config: &Config) -> PreflightReport {
+    let rpc_manager = match RpcManager::from_config(config).await {
+        Ok(rpc_manager) => rpc_manager,
+        Err(e) => {
+            let mut report = PreflightReport::default();
+            report.push("RPC", Err(PreflightFailure::new(e.to_string(), "Check HELIUS, DRPC and SOLANA_RPC_URL")));
+            return report;
+        }
+    };
+    // USE_JITO requires JITO_RPC_URL, so a configured URL covers both
+    let jito_client = config.jito.rpc_url.clone().map(JitoClient::with_url);
+    run_with(config, &rpc_manager, jito_client.as_ref()).await
+}
+
+pub async fn run_with(config: &Config, rpc_manager: &RpcManager, jito_client: Option<&JitoClient>) -> PreflightReport {
+    let mut report = PreflightReport::default();
+    let keypair_path = &config.keypair_path;
+
+    // HELIUS falls back to SOLANA_RPC_URL, so the hint names whichever one is in use
+    let helius_var = if config.rpc.helius.is_some() { "HELIUS" } else { "SOLANA_RPC_URL" };
+    let mut endpoints = vec![(RpcEndpointType::Helius, helius_var)];
+    if config.rpc.drpc.is_some() {
+        endpoints.push((RpcEndpointType::Drpc, "DRPC"));
+    }
+    for (endpoint_type, var) in endpoints {
+        report.push(format!("RPC {:?}", endpoint_type), check_rpc(rpc_manager, endpoint_type, var).await);
+    }
+
+    for ws_url in config.ws_urls() {
+        report.push(format!("WebSocket {}", ws_endpoint_label(&ws_url)), check_ws(&ws_url).await);
+    }
+
+    if let Some(jito_client) = jito_client {
+        report.push("Jito", check_jito(jito_client).await);
+    }
+
+    let wallet = match read_keypair_file(keypair_path) {
+        Ok(keypair) => keypair.pubkey().to_string(),
+        Err(e) => {
+            let hint = format!("Create it with `solana-keygen new -o {}` or set KEYPAIR_PATH to a readable keypair file", keypair_path);
+            report.push("Keypair", Err(PreflightFailure::new(format!("Failed to read keypair {}: {}", keypair_path, e), hint)));
+            return report;
+        }
+    };
+    report.push("Keypair", Ok(wallet.clone()));
+    let balance = check_balance(config, rpc_manager, &wallet).await;
+    report.wallet = Some(wallet);
+    report.balance_sol = balance.as_ref().ok().map(|(balance_sol, _)| *balance_sol);
+    report.push("Balance", balance.map(|(_, detail)| detail));
+    report
+}
+
+async fn within<T>(future: impl Future<Output = Result<T, PreflightFailure>>, hint: &str) -> Result<T, PreflightFailure> {
+    match tokio::time::timeout(CHECK_TIMEOUT, future).await {
+        Ok(result) => result,
+        Err(_) => Err(PreflightFailure::new(format!("No answer within {}s", CHECK_TIMEOUT.as_secs()), hint)),
+    }
+}
+
+// getVersion, then getSlot, on that endpoint alone
+async fn check_rpc(rpc_manager: &RpcManager, endpoint_type: RpcEndpointType, var: &str) -> Result<String, PreflightFailure> {
+    let unreachable = format!("Check that {} is reachable from this host", var);
+    within(async {
+        let request = |method: &str| json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [] });
+        let version = rpc_manager.make_request_to(endpoint_type, request("getVersion")).await
+            .map_err(|e| PreflightFailure::new(format!("getVersion failed: {}", e), rpc_hint(var, &e)))?;
+        let version = version["result"]["solana-core"].as_str().unwrap_or("unknown version").to_string();
+        let slot = rpc_manager.make_request_to(endpoint_type, request("getSlot")).await
+            .map_err(|e| PreflightFailure::new(format!("getSlot failed: {}", e), rpc_hint(var, &e)))?;
+        let slot = slot["result"].as_u64()
+            .ok_or_else(|| PreflightFailure::new("getSlot returned no slot", format!("{} answered, but not like a Solana RPC node; check the URL", var)))?;
+        Ok(format!("solana-core {}, slot {}", version, slot))
+    }, &unreachable).await
+}
+
+// What to change for a failed RPC call. A bad or expired API key comes back as a JSON-RPC
+// auth error or as a 401/403 page that isn't JSON at all
+pub fn rpc_hint(var: &str, error: &RpcError) -> String {
+    let rejected_key = match error {
+        RpcError::Transport(detail) => detail.contains("Failed to parse response"),
+        RpcError::Rpc { code, message } => {
+            let message = message.to_lowercase();
+            matches!(*code, -32401 | -32403 | 401 | 403) || ["api key", "unauthorized", "forbidden"].iter().any(|needle| message.contains(needle))
+        }
+        _ => false,
+    };
+    if error.is_rate_limited() {
+        format!("{} is rate limiting requests already; check the plan behind its API key", var)
+    } else if rejected_key {
+        format!("Check the API key in {}; the provider rejected the request", var)
+    } else if matches!(error, RpcError::Rpc { .. } | RpcError::InvalidResponse(_)) {
+        format!("{} answered, but not like a Solana RPC node; check the URL", var)
+    } else {
+        format!("Check that {} is correct and reachable from this host", var)
+    }
+}
+
+// Opens a connection, subscribes to logs as the mempool does, then unsubscribes and closes
+async fn check_ws(ws_url: &str) -> Result<String, PreflightFailure> {
+    let unreachable = "Check SOLANA_WS_URL / SOLANA_WS_URLS: ws:// or wss://, usually with the same API key as the RPC URL";
+    let rejected = "The endpoint doesn't allow logsSubscribe for all transactions; use a provider WebSocket URL that does";
+    within(async {
+        let (mut ws_stream, _) = connect_async(ws_url).await
+            .map_err(|e| PreflightFailure::new(format!("WebSocket connection failed: {}", e), unreachable))?;
+        let subscribe = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "logsSubscribe",
+            "params": ["all", { "commitment": "processed" }]
+        });
+        ws_stream.send(Message::Text(subscribe.to_string())).await
+            .map_err(|e| PreflightFailure::new(format!("Failed to send subscription: {}", e), unreachable))?;
+
+        let subscription = loop {
+            let value = match ws_stream.next().await {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<Value>(&text) {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                },
+                Some(Ok(Message::Close(frame))) => {
+                    let reason = frame.map(|frame| frame.reason.to_string()).unwrap_or_default();
+                    return Err(PreflightFailure::new(format!("Closed before confirming the subscription: {}", reason), rejected));
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(PreflightFailure::new(format!("WebSocket error: {}", e), unreachable)),
+                None => return Err(PreflightFailure::new("Closed before confirming the subscription", rejected)),
+            };
+            if value["id"].as_u64() != Some(1) {
+                continue;
+            }
+            match value["result"].as_u64() {
+                Some(id) => break id,
+                None => return Err(PreflightFailure::new(format!("logsSubscribe rejected: {}", value.get("error").unwrap_or(&value)), rejected)),
+            }
+        };
+
+        let unsubscribe = json!({ "jsonrpc": "2.0", "id": 2, "method": "logsUnsubscribe", "params": [subscription] });
+        let _ = ws_stream.send(Message::Text(unsubscribe.to_string())).await;
+        let _ = ws_stream.close(None).await;
+        Ok(format!("logsSubscribe confirmed (subscription {})", subscription))
+    }, unreachable).await
+}
+
+async fn check_jito(jito_client: &JitoClient) -> Result<String, PreflightFailure> {
+    let hint = "Check JITO_RPC_URL points at a block engine (e.g. https://mainnet.block-engine.jito.wtf) and JITO_AUTH_HEADER if it needs one";
+    within(async {
+        match jito_client.fetch_tip_accounts().await {
+            Ok(accounts) => Ok(format!("{} tip accounts", accounts.len())),
+            Err(e) => Err(PreflightFailure::new(e.to_string(), hint)),
+        }
+    }, hint).await
+}
+
+// The balance in SOL and how it reads against MIN_BALANCE_THRESHOLD. Below it fails, except in a
+// dry run, which never spends anything
+async fn check_balance(config: &Config, rpc_manager: &RpcManager, wallet: &str) -> Result<(f64, String), PreflightFailure> {
+    let unreachable = "Check that HELIUS (or SOLANA_RPC_URL) is reachable from this host";
+    let lamports = within(async {
+        rpc_manager.get_balance(wallet).await
+            .map_err(|e| PreflightFailure::new(format!("Failed to fetch the SOL balance: {}", e), rpc_hint("HELIUS", &e)))
+    }, unreachable).await?;
+
+    let balance_sol = lamports as f64 / 1_000_000_000.0;
+    let min_balance = config.risk.min_balance;
+    if balance_sol >= min_balance {
+        return Ok((balance_sol, format!("{:.4} SOL (minimum {} SOL)", balance_sol, min_balance)));
+    }
+    if config.dry_run {
+        return Ok((balance_sol, format!("{:.4} SOL, below the {} SOL minimum; ignored in a dry run", balance_sol, min_balance)));
+    }
+    let mut hint = format!("Fund {} with at least {} SOL, or lower MIN_BALANCE_THRESHOLD", wallet, min_balance);
+    if !matches!(config.network(), Network::Mainnet) {
+        hint.push_str(&format!(" (or `solana airdrop 1 {} --url {}`)", wallet, config.network().label().to_lowercase()));
+    }
+    Err(PreflightFailure::new(format!("{:.4} SOL is below the {} SOL minimum", balance_sol, min_balance), hint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use tokio::net::TcpListener;
+    use crate::rpc::rpc_manager::RpcEndpoint;
+    use crate::utils::mock_http::{self, MockResponse};
+
+    // RPC node answering getVersion, getSlot and getBalance
+    async fn mock_rpc(lamports: u64) -> String {
+        let (url, _) = mock_http::serve(move |request| {
+            let result = match request.json()["method"].as_str() {
+                Some("getVersion") => json!({ "solana-core": "1.17.20", "feature-set": 1 }),
+                Some("getSlot") => json!(250_000_000u64),
+                Some("getBalance") => json!({ "context": { "slot": 1 }, "value": lamports }),
+                _ => Value::Null,
+            };
+            MockResponse::rpc_result(request, result)
+        }).await;
+        url
+    }
+
+    // WebSocket endpoint answering the first subscribe request with `answer`
+    async fn mock_ws(answer: Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let answer = answer.clone();
+                tokio::spawn(async move {
+                    let mut ws_stream = tokio_tungstenite::accept_async(socket).await.unwrap();
+                    if let Some(Ok(Message::Text(_))) = ws_stream.next().await {
+                        let _ = ws_stream.send(Message::Text(answer.to_string())).await;
+                    }
+                    while let Some(Ok(_)) = ws_stream.next().await {}
+                });
+            }
+        });
+        url
+    }
+
+    fn keypair_file(name: &str) -> (String, String) {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("preflight_{}_{}.json", name, std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+        (path.to_string_lossy().to_string(), keypair.pubkey().to_string())
+    }
+
+    fn config(ws_url: String, keypair_path: &str) -> Config {
+        let mut config = Config::default();
+        config.keypair_path = keypair_path.to_string();
+        config.rpc.helius = Some("http://helius.example".to_string());
+        config.rpc.solana_ws_url = Some(ws_url);
+        config.risk.min_balance = 0.5;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_everything_reachable_and_funded_passes() {
+        let rpc_manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(mock_rpc(2_000_000_000).await, RpcEndpointType::Helius, 1.0)]);
+        let ws_url = mock_ws(json!({ "jsonrpc": "2.0", "result": 7, "id": 1 })).await;
+        let (keypair_path, wallet) = keypair_file("funded");
+
+        let report = run_with(&config(ws_url, &keypair_path), &rpc_manager, None).await;
+        assert_eq!(report.checks.len(), 4, "{:?}", report.checks);
+        assert!(report.passed(), "{:?}", report.failures());
+        assert_eq!(report.checks[0].outcome, Ok("solana-core 1.17.20, slot 250000000".to_string()));
+        assert_eq!(report.checks[1].outcome, Ok("logsSubscribe confirmed (subscription 7)".to_string()));
+        assert_eq!(report.checks[2].outcome, Ok(wallet.clone()));
+        assert_eq!(report.wallet, Some(wallet));
+        assert_eq!(report.balance_sol, Some(2.0));
+        let _ = std::fs::remove_file(keypair_path);
+    }
+
+    #[tokio::test]
+    async fn test_failures_come_with_specific_hints() {
+        let rpc_manager = RpcManager::with_endpoints(vec![RpcEndpoint::new(mock_rpc(100_000_000).await, RpcEndpointType::Helius, 1.0)]);
+        let ws_url = mock_ws(json!({ "jsonrpc": "2.0", "error": { "code": -32601, "message": "Method not found" }, "id": 1 })).await;
+        let (keypair_path, wallet) = keypair_file("underfunded");
+        let mut config = config(ws_url, &keypair_path);
+
+        let report = run_with(&config, &rpc_manager, None).await;
+        let failures = report.failures();
+        assert_eq!(failures.len(), 2, "{:?}", report.checks);
+        let ws_failure = failures[0].outcome.clone().unwrap_err();
+        assert!(ws_failure.error.contains("Method not found"));
+        assert!(ws_failure.hint.contains("logsSubscribe"));
+        assert_eq!(failures[1].name, "Balance");
+        let balance_failure = failures[1].outcome.clone().unwrap_err();
+        assert_eq!(balance_failure.error, "0.1000 SOL is below the 0.5 SOL minimum");
+        assert!(balance_failure.hint.contains(&format!("solana airdrop 1 {} --url devnet", wallet)));
+
+        // A dry run only reports the low balance
+        config.dry_run = true;
+        let report = run_with(&config, &rpc_manager, None).await;
+        assert!(report.checks.last().unwrap().outcome.as_ref().unwrap().contains("ignored in a dry run"));
+
+        // Without a keypair there's no wallet to check the balance of
+        config.keypair_path = "missing-keypair.json".to_string();
+        let report = run_with(&config, &rpc_manager, None).await;
+        let keypair = report.checks.last().unwrap();
+        assert_eq!(keypair.name, "Keypair");
+        assert!(keypair.outcome.clone().unwrap_err().hint.contains("solana-keygen new -o missing-keypair.json"));
+        assert_eq!(report.wallet, None);
+        let _ = std::fs::remove_file(keypair_path);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_jito_and_rpc_fail() {
+        let rpc_manager = RpcManager::with_endpoints(vec![RpcEndpoint::new("http://127.0.0.1:9".to_string(), RpcEndpointType::Helius, 1.0)]);
+        let jito_client = JitoClient::with_url("http://127.0.0.1:9".to_string());
+        let config = config("ws://127.0.0.1:9".to_string(), "missing-keypair.json");
+        let report = run_with(&config, &rpc_manager, Some(&jito_client)).await;
+
+        let names: Vec<&str> = report.failures().iter().map(|check| check.name.as_str()).collect();
+        assert_eq!(names, vec!["RPC Helius", "WebSocket 127.0.0.1:9", "Jito", "Keypair"]);
+        assert!(report.failures()[0].outcome.clone().unwrap_err().hint.contains("HELIUS"));
+    }
+
+    #[test]
+    fn test_rpc_hints() {
+        assert!(rpc_hint("HELIUS", &RpcError::RateLimited { retry_after: None }).contains("rate limiting"));
+        let unauthorized = RpcError::Transport("Failed to parse response as JSON: expected value".to_string());
+        assert!(rpc_hint("HELIUS", &unauthorized).contains("API key in HELIUS"));
+        let invalid_key = RpcError::Rpc { code: -32401, message: "invalid api key provided".to_string() };
+        assert!(rpc_hint("HELIUS", &invalid_key).contains("API key in HELIUS"));
+        let node_error = RpcError::Rpc { code: -32601, message: "Method not found".to_string() };
+        assert!(rpc_hint("DRPC", &node_error).contains("not like a Solana RPC node"));
+        assert!(rpc_hint("DRPC", &RpcError::Timeout).contains("reachable"));
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use crate::config::PricesConfig;
+use crate::logging::Logger;
+use crate::utils::amm_math;
+use crate::utils::bounded_cache::BoundedCache;
+use crate::utils::mint_info::MintInfoCache;
+use crate::utils::opportunity_evaluator::{pool_implied_price_in_sol, PoolState, PriceData};
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::pyth::PythPriceSource;
+
+const SLOT_DURATION_MS: u64 = 400;
+
+// Birdeye aggregates across venues but doesn't publish an interval
+const BIRDEYE_CONFIDENCE_INTERVAL: f64 = 0.01;
+
+type PriceResult<'a> = BoxFuture<'a, Result<Option<PriceData>, Box<dyn std::error::Error + Send + Sync>>>;
+
+// A source of token prices. Ok(None) means the source has nothing for the token (no feed, no
+// API key, no pool) and the next source should be asked; Err is a failed lookup
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn price<'a>(&'a self, token: &'a str) -> PriceResult<'a>;
+}
+
+impl PriceSource for PythPriceSource {
+    fn name(&self) -> &'static str {
+        "pyth"
+    }
+
+    fn price<'a>(&'a self, token: &'a str) -> PriceResult<'a> {
+        Box::pin(async move {
+            if !self.has_feed(token) {
+                return Ok(None);
+            }
+            let price = self.get_price(token).await?;
+            Ok(Some(PriceData {
+                token: token.to_string(),
+                price_in_sol: price.price_in_sol,
+                price_in_usd: price.price_in_usd,
+                volume_24h: 0.0, // Not provided by Pyth
+                last_updated: SystemTime::now(),
+                source: self.name(),
+                confidence_interval: price.confidence_usd / price.price_in_usd,
+                staleness_ms: price.slot_age * SLOT_DURATION_MS,
+            }))
+        })
+    }
+}
+
+// Birdeye /defi/multi_price, used for long-tail mints without a Pyth feed. Skipped when no
+// BIRDEYE_API_KEY is configured
+pub struct BirdeyePriceSource {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: Option<String>,
+    volumes: BoundedCache<f64>, // 24h USD volume per mint; changes slowly
+}
+
+impl BirdeyePriceSource {
+    pub fn new(api_url: &str, api_key: Option<String>, timeout: Duration) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to build Birdeye HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            volumes: BoundedCache::new(1_000, Duration::from_secs(60)),
+        })
+    }
+
+    pub fn from_config(config: &PricesConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new(&config.birdeye_api_url, config.birdeye_api_key.clone(), Duration::from_millis(config.birdeye_timeout_ms))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    // 24h USD volume of the mint; None without an API key
+    pub async fn volume_24h_usd(&self, token: &str) -> Result<Option<f64>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(api_key) = self.api_key.as_deref() else { return Ok(None) };
+        if let Some(volume) = self.volumes.get(token).await {
+            return Ok(Some(volume));
+        }
+
+        let response = self.client
+            .get(format!("{}/defi/price_volume/single", self.api_url))
+            .query(&[("address", token), ("type", "24h")])
+            .header("X-API-KEY", api_key)
+            .header("x-chain", "solana")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!("Birdeye volume request failed: {}", response.status()).into());
+        }
+
+        // Response: {"success": true, "data": {"price": <usd>, "volumeUSD": <usd>, ...}}
+        let body: Value = response.json().await
+            .map_err(|e| format!("Failed to parse Birdeye response: {}", e))?;
+        let volume = body["data"]["volumeUSD"].as_f64()
+            .ok_or_else(|| format!("Birdeye has no volume for {}", token))?;
+        self.volumes.insert(token.to_string(), volume).await;
+        Ok(Some(volume))
+    }
+
+    async fn fetch_price(&self, api_key: &str, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.client
+            .get(format!("{}/defi/multi_price", self.api_url))
+            .query(&[("list_address", format!("{},{}", token, WSOL_MINT))])
+            .header("X-API-KEY", api_key)
+            .header("x-chain", "solana")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!("Birdeye price request failed: {}", response.status()).into());
+        }
+
+        let body: Value = response.json().await
+            .map_err(|e| format!("Failed to parse Birdeye response: {}", e))?;
+        parse_multi_price(&body, token)
+    }
+}
+
+// Response: {"success": true, "data": {"<mint>": {"value": <usd>, "updateUnixTime": <secs>}, ...}}
+fn parse_multi_price(body: &Value, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
+    let entry = |mint: &str| -> Result<(f64, u64), Box<dyn std::error::Error + Send + Sync>> {
+        let data = &body["data"][mint];
+        let usd = data["value"].as_f64()
+            .filter(|usd| *usd > 0.0)
+            .ok_or_else(|| format!("Birdeye has no price for {}", mint))?;
+        Ok((usd, data["updateUnixTime"].as_u64().unwrap_or(0)))
+    };
+
+    let (token_usd, token_updated) = entry(token)?;
+    let (sol_usd, sol_updated) = entry(WSOL_MINT)?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    Ok(PriceData {
+        token: token.to_string(),
+        price_in_sol: token_usd / sol_usd,
+        price_in_usd: token_usd,
+        volume_24h: 0.0,
+        last_updated: SystemTime::now(),
+        source: "birdeye",
+        confidence_interval: BIRDEYE_CONFIDENCE_INTERVAL,
+        staleness_ms: now.saturating_sub(token_updated.min(sol_updated)) * 1000,
+    })
+}
+
+impl PriceSource for BirdeyePriceSource {
+    fn name(&self) -> &'static str {
+        "birdeye"
+    }
+
+    fn price<'a>(&'a self, token: &'a str) -> PriceResult<'a> {
+        Box::pin(async move {
+            let Some(api_key) = self.api_key.as_deref() else { return Ok(None) };
+            // Birdeye is queried by mint; symbols are left to Pyth
+            if bs58::decode(token).into_vec().map_or(true, |key| key.len() != 32) {
+                return Ok(None);
+            }
+            self.fetch_price(api_key, token).await.map(Some)
+        })
+    }
+}
+
+// Price implied by the deepest cached SOL pool of the token. Registry pools stay cached through
+// the pool watcher and arbitrage scans, so this covers every token we have pools for
+pub struct PoolPriceSource {
+    pool_states: BoundedCache<PoolState>,
+    mint_info: Arc<MintInfoCache>,
+}
+
+impl PoolPriceSource {
+    pub fn new(pool_states: BoundedCache<PoolState>, mint_info: Arc<MintInfoCache>) -> Self {
+        Self { pool_states, mint_info }
+    }
+}
+
+// Best price among the token's SOL pools, with the price move a 1 SOL trade would cause
+// as its confidence interval
+pub fn deepest_pool_price<'a>(pools: &'a [PoolState], token: &str, decimals: u8) -> Option<(f64, f64, &'a PoolState)> {
+    pools.iter()
+        .filter_map(|pool| pool_implied_price_in_sol(pool, token, decimals).map(|price| (price, pool)))
+        .max_by(|a, b| a.1.liquidity.total_cmp(&b.1.liquidity))
+        .map(|(price, pool)| {
+            let reserve_sol = (pool.liquidity / 2.0 * 1_000_000_000.0) as u64;
+            (price, amm_math::price_impact(1_000_000_000, reserve_sol), pool)
+        })
+}
+
+impl PriceSource for PoolPriceSource {
+    fn name(&self) -> &'static str {
+        "pool"
+    }
+
+    fn price<'a>(&'a self, token: &'a str) -> PriceResult<'a> {
+        Box::pin(async move {
+            let price_data = |price_in_sol: f64, confidence_interval: f64, staleness_ms: u64| PriceData {
+                token: token.to_string(),
+                price_in_sol,
+                price_in_usd: 0.0, // Unknown without a USD feed
+                volume_24h: 0.0,
+                last_updated: SystemTime::now(),
+                source: "pool",
+                confidence_interval,
+                staleness_ms,
+            };
+            if token == "SOL" || token == WSOL_MINT {
+                return Ok(Some(price_data(1.0, 0.0, 0)));
+            }
+
+            let pools = self.pool_states
+                .filter_map(|pool| (pool.reserves_for_input(token).is_some() && pool.liquidity > 0.0).then(|| pool.clone()))
+                .await;
+            if pools.is_empty() {
+                return Ok(None);
+            }
+            let decimals = self.mint_info.decimals(token).await?;
+            Ok(deepest_pool_price(&pools, token, decimals).map(|(price, confidence_interval, pool)| {
+                let staleness_ms = pool.last_updated.elapsed().map_or(0, |age| age.as_millis() as u64);
+                price_data(price, confidence_interval, staleness_ms)
+            }))
+        })
+    }
+}
+
+// Asks each source in order until one has a price, counting which source served each lookup
+pub struct CompositePriceSource {
+    sources: Vec<Arc<dyn PriceSource>>,
+    served: Arc<RwLock<BTreeMap<&'static str, u64>>>,
+}
+
+impl CompositePriceSource {
+    pub fn new(sources: Vec<Arc<dyn PriceSource>>) -> Self {
+        Self {
+            sources,
+            served: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    // Pyth, then Birdeye, then the pool-implied price
+    pub fn chain(pyth: PythPriceSource, birdeye: Arc<BirdeyePriceSource>, pool: PoolPriceSource) -> Self {
+        Self::new(vec![Arc::new(pyth), birdeye, Arc::new(pool)])
+    }
+
+    pub async fn get_price(&self, token: &str) -> Result<PriceData, Box<dyn std::error::Error + Send + Sync>> {
+        for source in &self.sources {
+            match source.price(token).await {
+                Ok(Some(price)) => {
+                    *self.served.write().await.entry(source.name()).or_insert(0) += 1;
+                    return Ok(price);
+                }
+                Ok(None) => {}
+                Err(e) => Logger::status_update(&format!("{} price for {} rejected, falling back: {}", source.name(), token, e)),
+            }
+        }
+        Err(format!("No price source available for {}", token).into())
+    }
+
+    // Lookups served per source since startup
+    pub async fn served_counts(&self) -> BTreeMap<&'static str, u64> {
+        self.served.read().await.clone()
+    }
+}
+
+impl Clone for CompositePriceSource {
+    fn clone(&self) -> Self {
+        CompositePriceSource {
+            sources: self.sources.clone(),
+            served: Arc::clone(&self.served),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    enum Fixed {
+        Price(f64),
+        Nothing,
+        Fails,
+    }
+
+    struct FixedSource(&'static str, Fixed);
+
+    impl PriceSource for FixedSource {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn price<'a>(&'a self, token: &'a str) -> PriceResult<'a> {
+            Box::pin(async move {
+                match self.1 {
+                    Fixed::Price(price_in_sol) => Ok(Some(PriceData {
+                        token: token.to_string(),
+                        price_in_sol,
+                        price_in_usd: 0.0,
+                        volume_24h: 0.0,
+                        last_updated: SystemTime::now(),
+                        source: self.0,
+                        confidence_interval: 0.0,
+                        staleness_ms: 0,
+                    })),
+                    Fixed::Nothing => Ok(None),
+                    Fixed::Fails => Err("upstream timeout".into()),
+                }
+            })
+        }
+    }
+
+    fn composite(sources: Vec<FixedSource>) -> CompositePriceSource {
+        CompositePriceSource::new(sources.into_iter().map(|s| Arc::new(s) as Arc<dyn PriceSource>).collect())
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_in_order_and_records_source() {
+        let prices = composite(vec![
+            FixedSource("pyth", Fixed::Nothing),
+            FixedSource("birdeye", Fixed::Fails),
+            FixedSource("pool", Fixed::Price(0.0000002)),
+        ]);
+        let price = prices.get_price(BONK_MINT).await.unwrap();
+        assert_eq!(price.source, "pool");
+
+        let preferred = composite(vec![
+            FixedSource("pyth", Fixed::Price(1.0)),
+            FixedSource("pool", Fixed::Price(2.0)),
+        ]);
+        assert_eq!(preferred.get_price(WSOL_MINT).await.unwrap().source, "pyth");
+
+        assert_eq!(prices.served_counts().await.into_iter().collect::<Vec<_>>(), vec![("pool", 1)]);
+        assert!(composite(vec![FixedSource("pyth", Fixed::Nothing)]).get_price(BONK_MINT).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_birdeye_without_api_key_is_skipped() {
+        for key in [None, Some("  ".to_string())] {
+            let birdeye = BirdeyePriceSource::new("http://127.0.0.1:9", key, Duration::from_millis(50)).unwrap();
+            assert!(birdeye.price(BONK_MINT).await.unwrap().is_none());
+            assert!(birdeye.volume_24h_usd(BONK_MINT).await.unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_parses_birdeye_prices_into_sol() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let body = json!({
+            "success": true,
+            "data": {
+                BONK_MINT: { "value": 0.00003, "updateUnixTime": now - 4 },
+                WSOL_MINT: { "value": 150.0, "updateUnixTime": now - 1 }
+            }
+        });
+        let price = parse_multi_price(&body, BONK_MINT).unwrap();
+        assert!((price.price_in_sol - 0.0000002).abs() < 1e-15);
+        assert!(price.staleness_ms >= 4_000);
+
+        let missing = json!({ "success": true, "data": { WSOL_MINT: { "value": 150.0 } } });
+        assert!(parse_multi_price(&missing, BONK_MINT).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use crate::config::MetricsConfig;
+use crate::utils::enhanced_transaction_simulator::OpportunityDetails;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfitCalibrationConfig {
+    pub window: usize, // Last reconciled trades the factor is the median over, per key
+    pub min_samples: usize, // Below this many the estimates are taken at face value
+    pub alert_floor: f64, // Factor below which an alert is raised; 0 disables
+    pub half_life: Duration, // A key's factor halves its distance to 1 per this long without trades
+}
+
+impl Default for ProfitCalibrationConfig {
+    fn default() -> Self {
+        Self { window: 100, min_samples: 20, alert_floor: 0.5, half_life: Duration::from_secs(6 * 3600) }
+    }
+}
+
+impl ProfitCalibrationConfig {
+    pub fn from_config(config: &MetricsConfig) -> Self {
+        Self {
+            window: config.profit_calibration_window,
+            min_samples: config.profit_calibration_min_samples,
+            alert_floor: config.profit_calibration_alert_floor,
+            half_life: Duration::from_secs_f64(config.profit_calibration_half_life_hours.max(0.0) * 3600.0),
+        }
+    }
+}
+
+// One key's rolling factor; scope is "type" or "dex"
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationFactor {
+    pub scope: &'static str,
+    pub key: String,
+    pub factor: f64, // Median realized/estimated ratio, unclamped
+    pub samples: usize,
+}
+
+#[derive(Debug, Default)]
+struct CalibrationState {
+    by_type: HashMap<String, VecDeque<f64>>, // Ratios, oldest first
+    by_dex: HashMap<String, VecDeque<f64>>,
+    last_trade: HashMap<(&'static str, String), Instant>,
+    below_floor: HashSet<(&'static str, String)>, // Keys already alerted on, until they recover
+}
+
+fn median(ratios: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<f64> = ratios.iter().copied().collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) / 2.0 } else { sorted[middle] }
+}
+
+// `factor` moved towards 1 by half its distance per half-life idle; a zero half-life doesn't decay
+fn decayed(factor: f64, idle: Duration, half_life: Duration) -> f64 {
+    if half_life.is_zero() {
+        return factor;
+    }
+    1.0 - (1.0 - factor) * 0.5f64.powf(idle.as_secs_f64() / half_life.as_secs_f64())
+}
+
+// How much of its estimated profit each opportunity type and DEX actually realizes, as the
+// rolling median of realized/estimated over reconciled successes. The evaluator and the strategy
+// executor scale estimates by it before their threshold checks; it never scales one up, and
+// estimates are taken at face value until a key has min_samples trades. A key that stops trading
+// drifts back to face value, so a bad spell doesn't hold its estimates down indefinitely. Cheap
+// to clone: clones share the same state
+#[derive(Debug, Clone)]
+pub struct ProfitCalibration {
+    config: ProfitCalibrationConfig,
+    state: Arc<RwLock<CalibrationState>>,
+}
+
+impl Default for ProfitCalibration {
+    fn default() -> Self {
+        Self::new(ProfitCalibrationConfig::default())
+    }
+}
+
+impl ProfitCalibration {
+    pub fn new(config: ProfitCalibrationConfig) -> Self {
+        Self { config, state: Arc::new(RwLock::new(CalibrationState::default())) }
+    }
+
+    pub fn from_config(config: &MetricsConfig) -> Self {
+        Self::new(ProfitCalibrationConfig::from_config(config))
+    }
+
+    pub fn config(&self) -> ProfitCalibrationConfig {
+        self.config
+    }
+
+    // Books one reconciled trade; returns the keys whose factor just dropped below the alert floor
+    pub async fn record(&self, opportunity_type: &str, dex: &str, estimated: f64, realized: f64) -> Vec<CalibrationFactor> {
+        self.record_at(opportunity_type, dex, estimated, realized, Instant::now()).await
+    }
+
+    async fn record_at(&self, opportunity_type: &str, dex: &str, estimated: f64, realized: f64, now: Instant) -> Vec<CalibrationFactor> {
+        // A ratio against a non-positive estimate means nothing
+        if estimated <= 0.0 || !estimated.is_finite() || !realized.is_finite() {
+            return Vec::new();
+        }
+        let ratio = realized / estimated;
+        let mut state = self.state.write().await;
+        let mut crossed = Vec::new();
+        for (scope, key) in [("type", opportunity_type), ("dex", dex)] {
+            let windows = if scope == "type" { &mut state.by_type } else { &mut state.by_dex };
+            let ratios = windows.entry(key.to_string()).or_default();
+            ratios.push_back(ratio);
+            while ratios.len() > self.config.window.max(1) {
+                ratios.pop_front();
+            }
+            let samples = ratios.len();
+            let factor = median(ratios);
+            state.last_trade.insert((scope, key.to_string()), now);
+            if samples < self.config.min_samples {
+                continue;
+            }
+            let factor = CalibrationFactor { scope, key: key.to_string(), factor, samples };
+            if factor.factor < self.config.alert_floor {
+                if state.below_floor.insert((scope, key.to_string())) {
+                    crossed.push(factor);
+                }
+            } else {
+                state.below_floor.remove(&(scope, key.to_string()));
+            }
+        }
+        crossed
+    }
+
+    // The multiplier for an estimate of this type on this DEX: the more pessimistic of the two
+    // factors, within [0, 1], each decayed towards 1 for the time since its last trade; 1 while
+    // neither has min_samples trades
+    pub async fn factor(&self, opportunity_type: &str, dex: &str) -> f64 {
+        self.factor_at(opportunity_type, dex, Instant::now()).await
+    }
+
+    async fn factor_at(&self, opportunity_type: &str, dex: &str, now: Instant) -> f64 {
+        let state = self.state.read().await;
+        [("type", state.by_type.get(opportunity_type), opportunity_type), ("dex", state.by_dex.get(dex), dex)]
+            .into_iter()
+            .filter_map(|(scope, ratios, key)| {
+                let ratios = ratios.filter(|ratios| !ratios.is_empty() && ratios.len() >= self.config.min_samples)?;
+                let idle = state.last_trade.get(&(scope, key.to_string())).map_or(Duration::ZERO, |at| now.saturating_duration_since(*at));
+                Some(decayed(median(ratios).clamp(0.0, 1.0), idle, self.config.half_life))
+            })
+            .fold(1.0_f64, f64::min)
+    }
+
+    // The opportunity's estimated profit as it is likely to be realized. Losses aren't scaled
+    pub async fn calibrated_profit(&self, opportunity: &OpportunityDetails) -> f64 {
+        if opportunity.estimated_profit <= 0.0 {
+            return opportunity.estimated_profit;
+        }
+        let factor = self.factor(&format!("{:?}", opportunity.opportunity_type), &opportunity.dex).await;
+        opportunity.estimated_profit * factor
+    }
+
+    // Every key with at least one trade, types first, for metrics
+    pub async fn factors(&self) -> Vec<CalibrationFactor> {
+        let state = self.state.read().await;
+        let mut factors = Vec::new();
+        for (scope, windows) in [("type", &state.by_type), ("dex", &state.by_dex)] {
+            let mut keys: Vec<&String> = windows.keys().collect();
+            keys.sort();
+            for key in keys {
+                let ratios = &windows[key];
+                if !ratios.is_empty() {
+                    factors.push(CalibrationFactor { scope, key: key.clone(), factor: median(ratios), samples: ratios.len() });
+                }
+            }
+        }
+        factors
+    }
+
+    pub async fn clear(&self) {
+        *self.state.write().await = CalibrationState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+    fn config() -> ProfitCalibrationConfig {
+        ProfitCalibrationConfig { window: 20, min_samples: 5, alert_floor: 0.6, half_life: Duration::from_secs(3600) }
+    }
+
+    #[tokio::test]
+    async fn test_factor_converges_to_the_realized_share() {
+        let calibration = ProfitCalibration::new(config());
+        // Arbitrage on Raydium realizes about half its estimate, with the odd outlier
+        for i in 0..4 {
+            assert!(calibration.record("Arbitrage", "Raydium", 0.02, 0.01 + 0.0001 * i as f64).await.is_empty());
+        }
+        assert_eq!(calibration.factor("Arbitrage", "Raydium").await, 1.0);
+
+        let crossed = calibration.record("Arbitrage", "Raydium", 0.02, 0.5).await;
+        assert_eq!(crossed.iter().map(|factor| factor.scope).collect::<Vec<_>>(), vec!["type", "dex"]);
+        for _ in 0..30 {
+            assert!(calibration.record("Arbitrage", "Raydium", 0.04, 0.02).await.is_empty());
+        }
+        assert!((calibration.factor("Arbitrage", "Raydium").await - 0.5).abs() < 1e-9);
+
+        // Orca realizes a quarter; its factor wins over the type's for Orca trades only
+        for _ in 0..5 {
+            calibration.record("Arbitrage", "Orca", 0.04, 0.01).await;
+        }
+        assert!((calibration.factor("Arbitrage", "Orca").await - 0.25).abs() < 1e-9);
+        assert!((calibration.factor("Arbitrage", "Raydium").await - 0.5).abs() < 1e-9);
+        // Never above 1, and untouched keys are taken at face value
+        for _ in 0..5 {
+            calibration.record("Backrun", "Phoenix", 0.01, 0.03).await;
+        }
+        assert_eq!(calibration.factor("Backrun", "Phoenix").await, 1.0);
+        assert_eq!(calibration.factor("Sandwich", "Meteora").await, 1.0);
+
+        let opportunity = OpportunityDetails {
+            token_a: "SOL".to_string(),
+            token_b: "USDC".to_string(),
+            trade_size: 1_000_000,
+            estimated_profit: 0.04,
+            dex: "Raydium".to_string(),
+            opportunity_type: OpportunityType::Arbitrage,
+            pool_address: None,
+            route: Vec::new(),
+        };
+        assert!((calibration.calibrated_profit(&opportunity).await - 0.02).abs() < 1e-9);
+
+        let factors = calibration.factors().await;
+        assert_eq!(factors.iter().map(|factor| (factor.scope, factor.key.as_str())).collect::<Vec<_>>(), vec![
+            ("type", "Arbitrage"), ("type", "Backrun"), ("dex", "Orca"), ("dex", "Phoenix"), ("dex", "Raydium"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_alert_fires_once_per_drop_below_the_floor() {
+        let calibration = ProfitCalibration::new(config());
+        let mut alerts = 0;
+        for _ in 0..10 {
+            alerts += calibration.record("Sandwich", "Orca", 0.01, 0.003).await.len();
+        }
+        assert_eq!(alerts, 2); // Once for the type, once for the DEX
+
+        // Estimates improve; the window fills with accurate trades and the factor recovers
+        for _ in 0..20 {
+            assert!(calibration.record("Sandwich", "Orca", 0.01, 0.009).await.is_empty());
+        }
+        assert!((calibration.factor("Sandwich", "Orca").await - 0.9).abs() < 1e-9);
+
+        // Skewed again: a fresh alert
+        let mut alerts = 0;
+        for _ in 0..20 {
+            alerts += calibration.record("Sandwich", "Orca", 0.01, 0.001).await.len();
+        }
+        assert_eq!(alerts, 2);
+
+        // Non-positive estimates are ignored
+        assert!(calibration.record("Sandwich", "Orca", 0.0, 0.01).await.is_empty());
+        assert!(calibration.record("Sandwich", "Orca", -0.01, 0.01).await.is_empty());
+        assert_eq!(calibration.factors().await[0].samples, 20);
+    }
+
+    #[tokio::test]
+    async fn test_idle_factor_drifts_back_to_face_value() {
+        let calibration = ProfitCalibration::new(config());
+        let start = Instant::now();
+        for _ in 0..5 {
+            calibration.record_at("Arbitrage", "Raydium", 0.04, 0.01, start).await;
+        }
+        assert!((calibration.factor_at("Arbitrage", "Raydium", start).await - 0.25).abs() < 1e-9);
+
+        // Half the way back to 1 per idle hour
+        let hour = Duration::from_secs(3600);
+        assert!((calibration.factor_at("Arbitrage", "Raydium", start + hour).await - 0.625).abs() < 1e-9);
+        assert!((calibration.factor_at("Arbitrage", "Raydium", start + hour * 2).await - 0.8125).abs() < 1e-9);
+
+        // A new trade restarts the clock for its keys
+        calibration.record_at("Arbitrage", "Raydium", 0.04, 0.01, start + hour * 2).await;
+        assert!((calibration.factor_at("Arbitrage", "Raydium", start + hour * 2).await - 0.25).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,434 @@
+use std::str::FromStr;
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::config::StrategiesConfig;
+use crate::utils::instruction_parser::{self, ParsedInstruction};
+use crate::utils::simulation_effects::TOKEN_PROGRAM_ID;
+
+pub const PUMPFUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+// Protocol fee destination every buy and sell passes
+const FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+// Anchor discriminators: first 8 bytes of sha256("global:<name>")
+const CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// Anchor account discriminator for BondingCurve
+const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+const FEE_BPS: u64 = 100; // 1% protocol fee on both buys and sells
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PumpFunInstruction {
+    Create {
+        mint: String,
+        bonding_curve: String,
+        name: String,
+        symbol: String,
+    },
+    Buy {
+        mint: String,
+        bonding_curve: String,
+        token_amount: u64,
+        max_sol_cost: u64,
+    },
+    Sell {
+        mint: String,
+        bonding_curve: String,
+        token_amount: u64,
+        min_sol_output: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BondingCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+}
+
+impl BondingCurveState {
+    // Layout: [discriminator 8][virtual_token u64][virtual_sol u64][real_token u64][real_sol u64][supply u64][complete u8]
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < 49 {
+            return Err(format!("Bonding curve account too short: {} bytes", data.len()).into());
+        }
+        if data[..8] != BONDING_CURVE_DISCRIMINATOR {
+            return Err("Account is not a pump.fun bonding curve".into());
+        }
+
+        Ok(Self {
+            virtual_token_reserves: read_u64(data, 8).ok_or("Invalid virtual_token_reserves")?,
+            virtual_sol_reserves: read_u64(data, 16).ok_or("Invalid virtual_sol_reserves")?,
+            real_token_reserves: read_u64(data, 24).ok_or("Invalid real_token_reserves")?,
+            real_sol_reserves: read_u64(data, 32).ok_or("Invalid real_sol_reserves")?,
+            token_total_supply: read_u64(data, 40).ok_or("Invalid token_total_supply")?,
+            complete: data[48] != 0,
+        })
+    }
+
+    // Tokens received for a SOL amount (lamports), after the 1% fee
+    pub fn tokens_out_for_sol(&self, sol_in: u64) -> u64 {
+        let sol_after_fee = sol_in as u128 * (10_000 - FEE_BPS) as u128 / 10_000;
+        let tokens = self.virtual_token_reserves as u128 * sol_after_fee
+            / (self.virtual_sol_reserves as u128 + sol_after_fee);
+        (tokens as u64).min(self.real_token_reserves)
+    }
+
+    // SOL (lamports) needed to buy an exact token amount, including the 1% fee
+    pub fn sol_cost_for_tokens(&self, token_amount: u64) -> Option<u64> {
+        if token_amount >= self.virtual_token_reserves {
+            return None;
+        }
+        let cost = self.virtual_sol_reserves as u128 * token_amount as u128
+            / (self.virtual_token_reserves - token_amount) as u128
+            + 1;
+        Some((cost * (10_000 + FEE_BPS) as u128 / 10_000) as u64)
+    }
+
+    // SOL (lamports) received for selling tokens, after the 1% fee
+    pub fn sol_out_for_tokens(&self, token_amount: u64) -> u64 {
+        let sol = self.virtual_sol_reserves as u128 * token_amount as u128
+            / (self.virtual_token_reserves as u128 + token_amount as u128);
+        (sol * (10_000 - FEE_BPS) as u128 / 10_000) as u64
+    }
+
+    pub fn apply_buy(&self, sol_in: u64) -> (Self, u64) {
+        let tokens = self.tokens_out_for_sol(sol_in);
+        let sol_after_fee = sol_in * (10_000 - FEE_BPS) / 10_000;
+        let next = Self {
+            virtual_token_reserves: self.virtual_token_reserves - tokens,
+            virtual_sol_reserves: self.virtual_sol_reserves + sol_after_fee,
+            real_token_reserves: self.real_token_reserves - tokens,
+            real_sol_reserves: self.real_sol_reserves + sol_after_fee,
+            ..*self
+        };
+        (next, tokens)
+    }
+
+    pub fn apply_token_buy(&self, token_amount: u64) -> Option<(Self, u64)> {
+        let cost = self.sol_cost_for_tokens(token_amount)?;
+        let sol_after_fee = cost * (10_000 - FEE_BPS) / 10_000;
+        Some((Self {
+            virtual_token_reserves: self.virtual_token_reserves - token_amount,
+            virtual_sol_reserves: self.virtual_sol_reserves + sol_after_fee,
+            real_token_reserves: self.real_token_reserves.saturating_sub(token_amount),
+            real_sol_reserves: self.real_sol_reserves + sol_after_fee,
+            ..*self
+        }, cost))
+    }
+
+    pub fn price_in_sol(&self) -> f64 {
+        // Both sides use their base units; pump.fun tokens have 6 decimals, SOL has 9
+        (self.virtual_sol_reserves as f64 / 1_000_000_000.0) / (self.virtual_token_reserves as f64 / 1_000_000.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PumpFunFilters {
+    pub min_victim_buy_sol: f64,  // Only consider buys at least this large
+    pub max_frontrun_sol: f64,    // Upper bound for our own frontrun buy
+}
+
+impl PumpFunFilters {
+    pub fn from_config(config: &StrategiesConfig) -> Self {
+        Self {
+            min_victim_buy_sol: config.pumpfun_min_buy_sol,
+            max_frontrun_sol: config.pumpfun_max_frontrun_sol,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrontrunEstimate {
+    pub frontrun_sol: u64,
+    pub tokens_bought: u64,
+    pub sol_returned: u64,
+    pub profit_lamports: i64,
+}
+
+// Simulates buy(frontrun) -> victim buys `victim_tokens` -> sell(frontrun tokens) on the curve.
+// Halves the frontrun until the victim's cost stays within their max_sol_cost slippage limit.
+pub fn estimate_frontrun(
+    curve: &BondingCurveState,
+    victim_tokens: u64,
+    victim_max_sol_cost: u64,
+    max_frontrun_sol: u64,
+) -> Option<FrontrunEstimate> {
+    let mut frontrun_sol = max_frontrun_sol;
+
+    while frontrun_sol >= 1_000_000 { // Don't bother below 0.001 SOL
+        let (after_frontrun, tokens_bought) = curve.apply_buy(frontrun_sol);
+        if let Some((after_victim, victim_cost)) = after_frontrun.apply_token_buy(victim_tokens) {
+            if victim_cost <= victim_max_sol_cost {
+                let sol_returned = after_victim.sol_out_for_tokens(tokens_bought);
+                return Some(FrontrunEstimate {
+                    frontrun_sol,
+                    tokens_bought,
+                    sol_returned,
+                    profit_lamports: sol_returned as i64 - frontrun_sol as i64,
+                });
+            }
+        }
+        frontrun_sol /= 2;
+    }
+
+    None
+}
+
+pub fn decode_instruction(instruction: &ParsedInstruction) -> Option<PumpFunInstruction> {
+    if instruction.program_id != PUMPFUN_PROGRAM_ID {
+        return None;
+    }
+
+    let data = instruction_parser::decode_instruction_data(instruction)?;
+    if data.len() < 8 {
+        return None;
+    }
+    let (discriminator, args) = data.split_at(8);
+
+    // Buy/sell accounts: [global, fee_recipient, mint, bonding_curve, associated_bonding_curve, user_ata, user, ...]
+    // Create accounts: [mint, mint_authority, bonding_curve, associated_bonding_curve, global, ...]
+    if discriminator == BUY_DISCRIMINATOR {
+        Some(PumpFunInstruction::Buy {
+            mint: instruction.accounts.get(2)?.clone(),
+            bonding_curve: instruction.accounts.get(3)?.clone(),
+            token_amount: read_u64(args, 0)?,
+            max_sol_cost: read_u64(args, 8)?,
+        })
+    } else if discriminator == SELL_DISCRIMINATOR {
+        Some(PumpFunInstruction::Sell {
+            mint: instruction.accounts.get(2)?.clone(),
+            bonding_curve: instruction.accounts.get(3)?.clone(),
+            token_amount: read_u64(args, 0)?,
+            min_sol_output: read_u64(args, 8)?,
+        })
+    } else if discriminator == CREATE_DISCRIMINATOR {
+        let (name, offset) = read_borsh_string(args, 0)?;
+        let (symbol, _) = read_borsh_string(args, offset)?;
+        Some(PumpFunInstruction::Create {
+            mint: instruction.accounts.first()?.clone(),
+            bonding_curve: instruction.accounts.get(2)?.clone(),
+            name,
+            symbol,
+        })
+    } else {
+        None
+    }
+}
+
+// All pump.fun instructions in a fetched transaction, including ones invoked via CPI
+pub fn decode_transaction(tx_details: &Value) -> Vec<PumpFunInstruction> {
+    instruction_parser::all_instructions(tx_details)
+        .iter()
+        .filter_map(decode_instruction)
+        .collect()
+}
+
+// PDA of ["bonding-curve", mint]
+pub fn bonding_curve_address(mint: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(&[b"bonding-curve", pubkey(mint)?.as_ref()], &pubkey(PUMPFUN_PROGRAM_ID)?);
+    Ok(address.to_string())
+}
+
+// Buy an exact token amount, paying at most max_sol_cost lamports. The wallet's associated token
+// account for the mint receives the tokens and has to exist already
+pub fn buy_instruction(mint: &str, user: &Pubkey, token_amount: u64, max_sol_cost: u64) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    trade_instruction(BUY_DISCRIMINATOR, mint, user, token_amount, max_sol_cost)
+}
+
+// Sell a token amount for at least min_sol_output lamports
+pub fn sell_instruction(mint: &str, user: &Pubkey, token_amount: u64, min_sol_output: u64) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    trade_instruction(SELL_DISCRIMINATOR, mint, user, token_amount, min_sol_output)
+}
+
+// Buy: [global, fee recipient (w), mint, bonding curve (w), associated bonding curve (w), user
+// token account (w), user (s, w), system program, token program, rent, event authority, program].
+// Sell swaps the token program and rent for the associated token program and token program.
+// Data: [discriminator 8][token amount u64][SOL limit u64]
+fn trade_instruction(
+    discriminator: [u8; 8],
+    mint: &str,
+    user: &Pubkey,
+    token_amount: u64,
+    sol_limit: u64,
+) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let program_id = pubkey(PUMPFUN_PROGRAM_ID)?;
+    let mint = pubkey(mint)?;
+    let token_program = pubkey(TOKEN_PROGRAM_ID)?;
+    let (global, _) = Pubkey::find_program_address(&[b"global"], &program_id);
+    let (bonding_curve, _) = Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program_id);
+    let (event_authority, _) = Pubkey::find_program_address(&[b"__event_authority"], &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(global, false),
+        AccountMeta::new(pubkey(FEE_RECIPIENT)?, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new(bonding_curve, false),
+        AccountMeta::new(associated_token_address(&bonding_curve, &mint)?, false),
+        AccountMeta::new(associated_token_address(user, &mint)?, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+    ];
+    if discriminator == BUY_DISCRIMINATOR {
+        accounts.push(AccountMeta::new_readonly(token_program, false));
+        accounts.push(AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false));
+    } else {
+        accounts.push(AccountMeta::new_readonly(pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?, false));
+        accounts.push(AccountMeta::new_readonly(token_program, false));
+    }
+    accounts.push(AccountMeta::new_readonly(event_authority, false));
+    accounts.push(AccountMeta::new_readonly(program_id, false));
+
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&sol_limit.to_le_bytes());
+
+    Ok(Instruction { program_id, accounts, data })
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), pubkey(TOKEN_PROGRAM_ID)?.as_ref(), mint.as_ref()],
+        &pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+    );
+    Ok(address)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)?.try_into().ok().map(u64::from_le_bytes)
+}
+
+fn read_borsh_string(data: &[u8], offset: usize) -> Option<(String, usize)> {
+    let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+    let bytes = data.get(offset + 4..offset + 4 + len)?;
+    Some((String::from_utf8_lossy(bytes).to_string(), offset + 4 + len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Initial state of every pump.fun curve at launch
+    fn fresh_curve() -> BondingCurveState {
+        BondingCurveState {
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+        }
+    }
+
+    fn instruction(data: Vec<u8>, accounts: usize) -> ParsedInstruction {
+        ParsedInstruction {
+            program_id: PUMPFUN_PROGRAM_ID.to_string(),
+            accounts: (0..accounts).map(|i| format!("Account{}", i)).collect(),
+            data: Some(bs58::encode(data).into_string()),
+        }
+    }
+
+    #[test]
+    fn test_decode_bonding_curve_account() {
+        let curve = fresh_curve();
+        let mut data = BONDING_CURVE_DISCRIMINATOR.to_vec();
+        for value in [curve.virtual_token_reserves, curve.virtual_sol_reserves, curve.real_token_reserves,
+                      curve.real_sol_reserves, curve.token_total_supply] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(0);
+
+        assert_eq!(BondingCurveState::decode(&data).unwrap(), curve);
+        assert!(BondingCurveState::decode(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn test_decode_buy_and_create_instructions() {
+        let mut buy = BUY_DISCRIMINATOR.to_vec();
+        buy.extend_from_slice(&5_000_000_000u64.to_le_bytes());
+        buy.extend_from_slice(&200_000_000u64.to_le_bytes());
+        assert_eq!(decode_instruction(&instruction(buy, 12)), Some(PumpFunInstruction::Buy {
+            mint: "Account2".to_string(),
+            bonding_curve: "Account3".to_string(),
+            token_amount: 5_000_000_000,
+            max_sol_cost: 200_000_000,
+        }));
+
+        let mut create = CREATE_DISCRIMINATOR.to_vec();
+        for field in ["Test Coin", "TEST", "https://example.com/meta.json"] {
+            create.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            create.extend_from_slice(field.as_bytes());
+        }
+        match decode_instruction(&instruction(create, 14)) {
+            Some(PumpFunInstruction::Create { mint, name, symbol, .. }) => {
+                assert_eq!(mint, "Account0");
+                assert_eq!(name, "Test Coin");
+                assert_eq!(symbol, "TEST");
+            }
+            other => panic!("unexpected decode result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_built_trades_decode_back() {
+        let mint = Pubkey::new_unique().to_string();
+        let user = Pubkey::new_unique();
+        let parsed = |instruction: Instruction| ParsedInstruction {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction.accounts.iter().map(|meta| meta.pubkey.to_string()).collect(),
+            data: Some(bs58::encode(&instruction.data).into_string()),
+        };
+
+        let buy = buy_instruction(&mint, &user, 5_000_000_000, 200_000_000).unwrap();
+        assert!(buy.accounts[6].is_signer);
+        assert_eq!(decode_instruction(&parsed(buy)), Some(PumpFunInstruction::Buy {
+            mint: mint.clone(),
+            bonding_curve: bonding_curve_address(&mint).unwrap(),
+            token_amount: 5_000_000_000,
+            max_sol_cost: 200_000_000,
+        }));
+
+        let sell = sell_instruction(&mint, &user, 5_000_000_000, 190_000_000).unwrap();
+        assert_eq!(sell.accounts[9].pubkey.to_string(), TOKEN_PROGRAM_ID);
+        assert!(matches!(
+            decode_instruction(&parsed(sell)),
+            Some(PumpFunInstruction::Sell { token_amount: 5_000_000_000, min_sol_output: 190_000_000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_bonding_curve_round_trip_loses_only_fees() {
+        let curve = fresh_curve();
+        let (after_buy, tokens) = curve.apply_buy(1_000_000_000); // 1 SOL
+        let sol_back = after_buy.sol_out_for_tokens(tokens);
+        // ~2% lost to the two fees, never a profit on a plain round trip
+        assert!(sol_back < 1_000_000_000);
+        assert!(sol_back > 970_000_000);
+    }
+
+    #[test]
+    fn test_frontrun_respects_victim_slippage() {
+        let curve = fresh_curve();
+        let victim_tokens = curve.tokens_out_for_sol(5_000_000_000); // victim buys ~5 SOL worth
+        let victim_cost = curve.sol_cost_for_tokens(victim_tokens).unwrap();
+        let max_cost = victim_cost * 110 / 100; // 10% slippage tolerance
+
+        let estimate = estimate_frontrun(&curve, victim_tokens, max_cost, 2_000_000_000).unwrap();
+        assert!(estimate.profit_lamports > 0);
+
+        // With zero slippage tolerance there is no room to frontrun
+        assert!(estimate_frontrun(&curve, victim_tokens, victim_cost, 2_000_000_000).is_none());
+    }
+}
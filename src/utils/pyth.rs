@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::config::PricesConfig;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+const PYTH_PRICE_HEADER_LEN: usize = 240;
+
+// Mainnet Pyth push-oracle price accounts, listed under both symbol and mint
+const DEFAULT_FEEDS: &[(&str, &str, &str)] = &[
+    ("SOL", WSOL_MINT, "H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG"),
+    ("USDC", USDC_MINT, "Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD"),
+    ("USDT", "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "3vxLXJqLqF3JG5TCbYycbKWRBbCJQLxQmBGCkyqEEefL"),
+    ("BONK", "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", "8ihFLu5FimgTQ1Unh4dVyEHUGodJ5gJQCrQf4KUVB9bN"),
+    ("JUP", "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", "g6eRCbboSwK4tSWngn773RCMexr1APQr4uA9bGZBYfo"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub status: u32,
+    pub publish_slot: u64,
+}
+
+impl PythPrice {
+    // Layout: magic u32, version u32, account type u32, size u32, price type u32, expo i32 (20),
+    // ..., aggregate price i64 (208), conf u64 (216), status u32 (224), corp act u32,
+    // publish slot u64 (232)
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < PYTH_PRICE_HEADER_LEN {
+            return Err(format!("Pyth price account too short: {} bytes", data.len()).into());
+        }
+
+        let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 byte slice"));
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+
+        if u32_at(0) != PYTH_MAGIC {
+            return Err("Account is not a Pyth account".into());
+        }
+        if u32_at(8) != PYTH_PRICE_ACCOUNT_TYPE {
+            return Err(format!("Pyth account type {} is not a price account", u32_at(8)).into());
+        }
+
+        Ok(Self {
+            price: u64_at(208) as i64,
+            conf: u64_at(216),
+            expo: u32_at(20) as i32,
+            status: u32_at(224),
+            publish_slot: u64_at(232),
+        })
+    }
+
+    pub fn price_f64(&self) -> f64 {
+        self.price as f64 * 10f64.powi(self.expo)
+    }
+
+    pub fn conf_f64(&self) -> f64 {
+        self.conf as f64 * 10f64.powi(self.expo)
+    }
+
+    // Rejects prices that aren't trading, aren't positive or were published too long ago
+    pub fn validate(&self, current_slot: u64, max_slot_age: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.status != PYTH_STATUS_TRADING {
+            return Err(format!("Pyth price status is {} (not trading)", self.status).into());
+        }
+        if self.price <= 0 {
+            return Err(format!("Pyth price is not positive: {}", self.price).into());
+        }
+        let age = current_slot.saturating_sub(self.publish_slot);
+        if age > max_slot_age {
+            return Err(format!("Pyth price is stale: published {} slots ago (max {})", age, max_slot_age).into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PythTokenPrice {
+    pub price_in_usd: f64,
+    pub price_in_sol: f64,
+    pub confidence_usd: f64,
+    pub slot_age: u64, // Slots since the older of the two feeds was published
+}
+
+// Converts the USD feeds of a token and of SOL into the token's prices
+pub fn token_price(token: &PythPrice, sol: &PythPrice, current_slot: u64) -> PythTokenPrice {
+    let price_in_usd = token.price_f64();
+    PythTokenPrice {
+        price_in_usd,
+        price_in_sol: price_in_usd / sol.price_f64(),
+        confidence_usd: token.conf_f64(),
+        slot_age: current_slot.saturating_sub(token.publish_slot.min(sol.publish_slot)),
+    }
+}
+
+pub struct PythPriceSource {
+    rpc_manager: Arc<RpcManager>,
+    feeds: HashMap<String, String>, // symbol or mint -> price account
+    max_slot_age: u64,
+}
+
+impl PythPriceSource {
+    pub fn from_config(rpc_manager: Arc<RpcManager>, config: &PricesConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut feeds = HashMap::new();
+        for (symbol, mint, account) in DEFAULT_FEEDS {
+            feeds.insert(symbol.to_string(), account.to_string());
+            feeds.insert(mint.to_string(), account.to_string());
+        }
+
+        // Extra or overriding feeds: PYTH_PRICE_FEEDS=<symbol or mint>=<price account>,...
+        for entry in &config.pyth_price_feeds {
+            let (token, account) = entry.split_once('=')
+                .ok_or_else(|| format!("Invalid PYTH_PRICE_FEEDS entry '{}' (expected TOKEN=ACCOUNT)", entry))?;
+            feeds.insert(token.trim().to_string(), account.trim().to_string());
+        }
+
+        Ok(Self { rpc_manager, feeds, max_slot_age: config.pyth_max_slot_age })
+    }
+
+    pub fn has_feed(&self, token: &str) -> bool {
+        self.feeds.contains_key(token)
+    }
+
+    pub async fn get_price(&self, token: &str) -> Result<PythTokenPrice, Box<dyn std::error::Error + Send + Sync>> {
+        let token_feed = self.feeds.get(token)
+            .ok_or_else(|| format!("No Pyth feed configured for {}", token))?;
+        let sol_feed = self.feeds.get("SOL")
+            .ok_or("No Pyth feed configured for SOL")?;
+
+        let accounts = [token_feed.clone(), sol_feed.clone()];
+        let (data, current_slot) = tokio::try_join!(
+            self.rpc_manager.get_multiple_accounts_data(&accounts),
+            self.rpc_manager.get_slot()
+        )?;
+
+        let mut prices = Vec::with_capacity(2);
+        for (account, data) in accounts.iter().zip(data) {
+            let data = data.ok_or_else(|| format!("Pyth price account {} not found", account))?;
+            let price = PythPrice::decode(&data)?;
+            price.validate(current_slot, self.max_slot_age)
+                .map_err(|e| format!("{} ({})", e, account))?;
+            prices.push(price);
+        }
+
+        Ok(token_price(&prices[0], &prices[1], current_slot))
+    }
+}
+
+impl Clone for PythPriceSource {
+    fn clone(&self) -> Self {
+        PythPriceSource {
+            rpc_manager: Arc::clone(&self.rpc_manager),
+            feeds: self.feeds.clone(),
+            max_slot_age: self.max_slot_age,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Price account header as published on mainnet: SOL/USD at $148.25 +/- $0.07
+    fn price_account(price: i64, conf: u64, expo: i32, status: u32, publish_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 3312];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+        data[12..16].copy_from_slice(&3312u32.to_le_bytes());
+        data[16..20].copy_from_slice(&1u32.to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&conf.to_le_bytes());
+        data[224..228].copy_from_slice(&status.to_le_bytes());
+        data[232..240].copy_from_slice(&publish_slot.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_decodes_price_with_exponent_and_confidence() {
+        let price = PythPrice::decode(&price_account(14_825_000_000, 7_000_000, -8, PYTH_STATUS_TRADING, 250_000_000)).unwrap();
+        assert_eq!(price.publish_slot, 250_000_000);
+        assert!((price.price_f64() - 148.25).abs() < 1e-9);
+        assert!((price.conf_f64() - 0.07).abs() < 1e-9);
+        assert!(price.validate(250_000_010, 25).is_ok());
+    }
+
+    #[test]
+    fn test_converts_usd_feeds_to_sol_price() {
+        let sol = PythPrice::decode(&price_account(15_000_000_000, 0, -8, PYTH_STATUS_TRADING, 1)).unwrap();
+        let usdc = PythPrice::decode(&price_account(99_990_000, 10_000, -8, PYTH_STATUS_TRADING, 1)).unwrap();
+
+        let price = token_price(&usdc, &sol, 5);
+        assert!((price.price_in_usd - 0.9999).abs() < 1e-9);
+        assert_eq!(price.slot_age, 4);
+        assert!((price.price_in_sol - 0.9999 / 150.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rejects_stale_halted_and_foreign_accounts() {
+        let stale = PythPrice::decode(&price_account(14_825_000_000, 0, -8, PYTH_STATUS_TRADING, 100)).unwrap();
+        assert!(stale.validate(200, 25).is_err());
+
+        let halted = PythPrice::decode(&price_account(14_825_000_000, 0, -8, 2, 100)).unwrap();
+        assert!(halted.validate(100, 25).is_err());
+
+        let mut not_pyth = price_account(1, 0, -8, PYTH_STATUS_TRADING, 100);
+        not_pyth[0] = 0;
+        assert!(PythPrice::decode(&not_pyth).is_err());
+        assert!(PythPrice::decode(&[0u8; 100]).is_err());
+    }
+}
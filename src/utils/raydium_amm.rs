@@ -0,0 +1,519 @@
+use std::str::FromStr;
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::utils::instruction_parser::{self, ParsedInstruction};
+use crate::utils::pool_creation::RAYDIUM_AMM_V4_PROGRAM_ID;
+use crate::utils::simulation_effects::TOKEN_PROGRAM_ID;
+
+// PDA every v4 pool's vaults are owned by
+const AMM_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+
+const SWAP_BASE_IN_DISCRIMINATOR: u8 = 9;
+const SWAP_BASE_OUT_DISCRIMINATOR: u8 = 11;
+
+// swapBaseIn/swapBaseOut take 18 accounts, or 17 when the amm target orders account is omitted
+const SWAP_ACCOUNTS_WITH_TARGET_ORDERS: usize = 18;
+const SWAP_MIN_ACCOUNTS: usize = 17;
+
+const AMM_INFO_LEN: usize = 752;
+const TOKEN_ACCOUNT_MIN_LEN: usize = 72;
+const MARKET_ACCOUNT_MIN_LEN: usize = 349;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwapDirection {
+    BaseIn,  // Exact input: amount is the input, other_amount the minimum output
+    BaseOut, // Exact output: amount is the maximum input, other_amount the output
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaydiumSwap {
+    pub pool_address: String,
+    pub direction: SwapDirection,
+    pub amount: u64,
+    pub other_amount: u64,
+    pub input_mint: Option<String>,
+    pub output_mint: Option<String>,
+    pub pool_coin_vault: String,
+    pub pool_pc_vault: String,
+    pub user_source: String,
+    pub user_destination: String,
+    pub user_owner: String,
+}
+
+impl RaydiumSwap {
+    // Input amount of the swap; for swapBaseOut this is the user's maximum
+    pub fn amount_in(&self) -> u64 {
+        self.amount
+    }
+
+    // Minimum output the user accepts; for swapBaseOut this is the exact output
+    pub fn min_amount_out(&self) -> u64 {
+        self.other_amount
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmmInfo {
+    pub coin_decimals: u8,
+    pub pc_decimals: u8,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub coin_need_take_pnl: u64, // Protocol fees still sitting in the vaults
+    pub pc_need_take_pnl: u64,
+    pub coin_vault: String,
+    pub pc_vault: String,
+    pub coin_mint: String,
+    pub pc_mint: String,
+}
+
+impl AmmInfo {
+    // Layout: 32 u64 parameters (status, nonce, ..., coin_decimals at 32, pc_decimals at 40,
+    // trade fee at 144/152, need_take_pnl at 192/200, ...), swap accounting up to 336, then
+    // coin vault, pc vault, coin mint, pc mint, ...
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() != AMM_INFO_LEN {
+            return Err(format!("Raydium AMM account has {} bytes, expected {}", data.len(), AMM_INFO_LEN).into());
+        }
+
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"));
+        let pubkey = |offset: usize| bs58::encode(&data[offset..offset + 32]).into_string();
+
+        Ok(Self {
+            coin_decimals: u64_at(32) as u8,
+            pc_decimals: u64_at(40) as u8,
+            trade_fee_numerator: u64_at(144),
+            trade_fee_denominator: u64_at(152),
+            coin_need_take_pnl: u64_at(192),
+            pc_need_take_pnl: u64_at(200),
+            coin_vault: pubkey(336),
+            pc_vault: pubkey(368),
+            coin_mint: pubkey(400),
+            pc_mint: pubkey(432),
+        })
+    }
+
+    // Fee as a fraction (0.0025 = 0.25%)
+    pub fn fee_fraction(&self) -> f64 {
+        if self.trade_fee_denominator == 0 {
+            return 0.0;
+        }
+        self.trade_fee_numerator as f64 / self.trade_fee_denominator as f64
+    }
+
+    // Tradable reserves: vault balances minus the protocol fees not yet withdrawn
+    pub fn reserves(&self, coin_vault_amount: u64, pc_vault_amount: u64) -> (u64, u64) {
+        (
+            coin_vault_amount.saturating_sub(self.coin_need_take_pnl),
+            pc_vault_amount.saturating_sub(self.pc_need_take_pnl),
+        )
+    }
+}
+
+// The accounts a swap passes besides the vaults: the pool's open orders and target orders, and
+// the OpenBook market it was created on
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmmSwapKeys {
+    pub open_orders: String,
+    pub target_orders: String,
+    pub market_program: String,
+    pub market: String,
+    pub market_bids: String,
+    pub market_asks: String,
+    pub market_event_queue: String,
+    pub market_coin_vault: String,
+    pub market_pc_vault: String,
+    pub market_vault_signer: String,
+}
+
+impl AmmSwapKeys {
+    // AmmInfo continues after pc mint with lp mint 464, open orders 496, market 528, market
+    // program 560, target orders 592. The market (after 5 bytes of padding): flags u64, own
+    // address, vault signer nonce u64 at 45, mints, coin vault 117, pc vault 165, request queue,
+    // event queue 253, bids 285, asks 317
+    pub fn decode(amm_data: &[u8], market_data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if amm_data.len() != AMM_INFO_LEN {
+            return Err(format!("Raydium AMM account has {} bytes, expected {}", amm_data.len(), AMM_INFO_LEN).into());
+        }
+        if market_data.len() < MARKET_ACCOUNT_MIN_LEN {
+            return Err(format!("Market account too short: {} bytes", market_data.len()).into());
+        }
+
+        let amm_key = |offset: usize| bs58::encode(&amm_data[offset..offset + 32]).into_string();
+        let market_key = |offset: usize| bs58::encode(&market_data[offset..offset + 32]).into_string();
+        let (market, market_program) = (amm_key(528), amm_key(560));
+        let nonce = u64::from_le_bytes(market_data[45..53].try_into().expect("8 byte slice"));
+        let vault_signer = Pubkey::create_program_address(
+            &[pubkey(&market)?.as_ref(), &nonce.to_le_bytes()],
+            &pubkey(&market_program)?,
+        ).map_err(|e| format!("Invalid vault signer nonce for market {}: {}", market, e))?;
+
+        Ok(Self {
+            open_orders: amm_key(496),
+            target_orders: amm_key(592),
+            market_program,
+            market,
+            market_bids: market_key(285),
+            market_asks: market_key(317),
+            market_event_queue: market_key(253),
+            market_coin_vault: market_key(117),
+            market_pc_vault: market_key(165),
+            market_vault_signer: vault_signer.to_string(),
+        })
+    }
+}
+
+// swapBaseIn with all 18 accounts (see decode_instruction's layout). `coin_to_pc` sells the
+// coin side; the wallet's associated token accounts are the source and destination
+pub fn swap_instruction(
+    pool_address: &str,
+    amm: &AmmInfo,
+    keys: &AmmSwapKeys,
+    user: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    coin_to_pc: bool,
+) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let user_coin = associated_token_address(user, &pubkey(&amm.coin_mint)?)?;
+    let user_pc = associated_token_address(user, &pubkey(&amm.pc_mint)?)?;
+    let (user_source, user_destination) = if coin_to_pc { (user_coin, user_pc) } else { (user_pc, user_coin) };
+
+    let accounts = vec![
+        AccountMeta::new_readonly(pubkey(TOKEN_PROGRAM_ID)?, false),
+        AccountMeta::new(pubkey(pool_address)?, false),
+        AccountMeta::new_readonly(pubkey(AMM_AUTHORITY)?, false),
+        AccountMeta::new(pubkey(&keys.open_orders)?, false),
+        AccountMeta::new(pubkey(&keys.target_orders)?, false),
+        AccountMeta::new(pubkey(&amm.coin_vault)?, false),
+        AccountMeta::new(pubkey(&amm.pc_vault)?, false),
+        AccountMeta::new_readonly(pubkey(&keys.market_program)?, false),
+        AccountMeta::new(pubkey(&keys.market)?, false),
+        AccountMeta::new(pubkey(&keys.market_bids)?, false),
+        AccountMeta::new(pubkey(&keys.market_asks)?, false),
+        AccountMeta::new(pubkey(&keys.market_event_queue)?, false),
+        AccountMeta::new(pubkey(&keys.market_coin_vault)?, false),
+        AccountMeta::new(pubkey(&keys.market_pc_vault)?, false),
+        AccountMeta::new_readonly(pubkey(&keys.market_vault_signer)?, false),
+        AccountMeta::new(user_source, false),
+        AccountMeta::new(user_destination, false),
+        AccountMeta::new_readonly(*user, true),
+    ];
+
+    let mut data = vec![SWAP_BASE_IN_DISCRIMINATOR];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    Ok(Instruction { program_id: pubkey(RAYDIUM_AMM_V4_PROGRAM_ID)?, accounts, data })
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), pubkey(TOKEN_PROGRAM_ID)?.as_ref(), mint.as_ref()],
+        &pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")?,
+    );
+    Ok(address)
+}
+
+// Amount field of an SPL token account (mint 32, owner 32, amount u64, ...)
+pub fn token_account_amount(data: &[u8]) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    if data.len() < TOKEN_ACCOUNT_MIN_LEN {
+        return Err(format!("Token account too short: {} bytes", data.len()).into());
+    }
+    Ok(u64::from_le_bytes(data[64..72].try_into().expect("8 byte slice")))
+}
+
+// Layout: [discriminator u8][amount u64][other_amount u64]
+pub fn decode_instruction(instruction: &ParsedInstruction, tx_details: &Value) -> Option<RaydiumSwap> {
+    if instruction.program_id != RAYDIUM_AMM_V4_PROGRAM_ID || instruction.accounts.len() < SWAP_MIN_ACCOUNTS {
+        return None;
+    }
+
+    let data = instruction_parser::decode_instruction_data(instruction)?;
+    let direction = match *data.first()? {
+        SWAP_BASE_IN_DISCRIMINATOR => SwapDirection::BaseIn,
+        SWAP_BASE_OUT_DISCRIMINATOR => SwapDirection::BaseOut,
+        _ => return None,
+    };
+
+    let read_u64 = |offset: usize| -> Option<u64> {
+        data.get(offset..offset + 8)?.try_into().ok().map(u64::from_le_bytes)
+    };
+    let amount = read_u64(1)?;
+    let other_amount = read_u64(9)?;
+
+    let accounts = &instruction.accounts;
+    let vault_offset = if accounts.len() >= SWAP_ACCOUNTS_WITH_TARGET_ORDERS { 5 } else { 4 };
+    let user_source = accounts[accounts.len() - 3].clone();
+    let user_destination = accounts[accounts.len() - 2].clone();
+    let pool_coin_vault = accounts[vault_offset].clone();
+    let pool_pc_vault = accounts[vault_offset + 1].clone();
+
+    let (input_mint, output_mint) = resolve_mints(
+        tx_details, &user_source, &user_destination, &pool_coin_vault, &pool_pc_vault,
+    );
+
+    Some(RaydiumSwap {
+        pool_address: accounts[1].clone(),
+        direction,
+        amount,
+        other_amount,
+        input_mint,
+        output_mint,
+        pool_coin_vault,
+        pool_pc_vault,
+        user_source,
+        user_destination,
+        user_owner: accounts[accounts.len() - 1].clone(),
+    })
+}
+
+// Top-level and CPI (e.g. routed through Jupiter) Raydium swaps in the transaction
+pub fn decode_transaction(tx_details: &Value) -> Vec<RaydiumSwap> {
+    instruction_parser::all_instructions(tx_details)
+        .iter()
+        .filter_map(|ix| decode_instruction(ix, tx_details))
+        .collect()
+}
+
+// The user's token accounts usually appear in the token balance metadata; when one side is
+// missing (e.g. a wrapped SOL account closed in the same transaction) it's inferred from the
+// pool vaults, whose two mints are the pair
+fn resolve_mints(
+    tx_details: &Value,
+    user_source: &str,
+    user_destination: &str,
+    pool_coin_vault: &str,
+    pool_pc_vault: &str,
+) -> (Option<String>, Option<String>) {
+    let input = instruction_parser::token_account_mint(tx_details, user_source);
+    let output = instruction_parser::token_account_mint(tx_details, user_destination);
+
+    let coin_mint = instruction_parser::token_account_mint(tx_details, pool_coin_vault);
+    let pc_mint = instruction_parser::token_account_mint(tx_details, pool_pc_vault);
+    let other_side = |known: &Option<String>| -> Option<String> {
+        match (known.as_deref(), coin_mint.as_deref(), pc_mint.as_deref()) {
+            (Some(known), Some(coin), Some(pc)) if known == coin => Some(pc.to_string()),
+            (Some(known), Some(coin), Some(pc)) if known == pc => Some(coin.to_string()),
+            _ => None,
+        }
+    };
+
+    match (input, output) {
+        (Some(input), Some(output)) => (Some(input), Some(output)),
+        (Some(input), None) => {
+            let output = other_side(&Some(input.clone()));
+            (Some(input), output)
+        }
+        (None, Some(output)) => {
+            let input = other_side(&Some(output.clone()));
+            (input, Some(output))
+        }
+        (None, None) => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::pool_creation::WSOL_MINT;
+
+    const TOKEN_MINT: &str = "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R";
+    const POOL: &str = "DSUvc5qf5LJHHV5e2tD184ixotSnCnwj7i4jJa4Xsrmt";
+
+    fn swap_data(discriminator: u8, amount: u64, other_amount: u64) -> String {
+        let mut data = vec![discriminator];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&other_amount.to_le_bytes());
+        bs58::encode(&data).into_string()
+    }
+
+    // Trimmed getTransaction ("json" encoding) of an 18-account swapBaseIn selling 2 SOL
+    // for a token, with the same account layout and balance metadata mainnet returns
+    fn swap_fixture(data: String) -> Value {
+        let mut keys: Vec<String> = vec![
+            "UserWa11et111111111111111111111111111111111".to_string(), // 0 owner / fee payer
+            "UserWsoLAccount1111111111111111111111111111".to_string(),  // 1 user source
+            "UserTokenAccount111111111111111111111111111".to_string(),  // 2 user destination
+            POOL.to_string(),                                           // 3 amm
+            "PoolCoinVau1t111111111111111111111111111111".to_string(),  // 4 coin vault
+            "PoolPcVau1t11111111111111111111111111111111".to_string(),  // 5 pc vault
+        ];
+        keys.extend((0..12).map(|i| format!("Other{}", i)));
+        keys.push("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string()); // 18
+        keys.push(RAYDIUM_AMM_V4_PROGRAM_ID.to_string()); // 19
+
+        // token program, amm, authority, open orders, target orders, coin vault, pc vault,
+        // serum program, market, bids, asks, event queue, coin vault, pc vault, vault signer,
+        // user source, user destination, user owner
+        let accounts: Vec<u64> = vec![18, 3, 6, 7, 8, 4, 5, 9, 10, 11, 12, 13, 14, 15, 16, 1, 2, 0];
+
+        json!({
+            "transaction": {
+                "message": {
+                    "accountKeys": keys,
+                    "instructions": [{ "programIdIndex": 19, "accounts": accounts, "data": data }]
+                }
+            },
+            "meta": {
+                "preTokenBalances": [
+                    { "accountIndex": 1, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "2000000000", "decimals": 9 } },
+                    { "accountIndex": 4, "mint": TOKEN_MINT, "uiTokenAmount": { "amount": "500000000000", "decimals": 6 } },
+                    { "accountIndex": 5, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "100000000000", "decimals": 9 } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 2, "mint": TOKEN_MINT, "uiTokenAmount": { "amount": "9746560776", "decimals": 6 } },
+                    { "accountIndex": 4, "mint": TOKEN_MINT, "uiTokenAmount": { "amount": "490253439224", "decimals": 6 } },
+                    { "accountIndex": 5, "mint": WSOL_MINT, "uiTokenAmount": { "amount": "102000000000", "decimals": 9 } }
+                ],
+                "logMessages": []
+            }
+        })
+    }
+
+    #[test]
+    fn test_decodes_swap_base_in() {
+        let tx = swap_fixture(swap_data(SWAP_BASE_IN_DISCRIMINATOR, 2_000_000_000, 9_500_000_000));
+        let swaps = decode_transaction(&tx);
+        assert_eq!(swaps.len(), 1);
+
+        let swap = &swaps[0];
+        assert_eq!(swap.direction, SwapDirection::BaseIn);
+        assert_eq!(swap.pool_address, POOL);
+        assert_eq!(swap.amount_in(), 2_000_000_000);
+        assert_eq!(swap.min_amount_out(), 9_500_000_000);
+        assert_eq!(swap.input_mint.as_deref(), Some(WSOL_MINT));
+        assert_eq!(swap.output_mint.as_deref(), Some(TOKEN_MINT));
+
+        // The user received what the pool's coin vault lost
+        let received = instruction_parser::token_balance_change(&tx, &swap.user_destination).unwrap();
+        let vault_change = instruction_parser::token_balance_change(&tx, &swap.pool_coin_vault).unwrap();
+        assert_eq!(received, -vault_change);
+        assert!(received >= swap.min_amount_out() as i128);
+    }
+
+    #[test]
+    fn test_decodes_swap_base_out() {
+        let tx = swap_fixture(swap_data(SWAP_BASE_OUT_DISCRIMINATOR, 2_100_000_000, 9_746_560_776));
+        let swap = decode_transaction(&tx).pop().unwrap();
+        assert_eq!(swap.direction, SwapDirection::BaseOut);
+        assert_eq!(swap.amount_in(), 2_100_000_000);
+        assert_eq!(swap.min_amount_out(), 9_746_560_776);
+    }
+
+    #[test]
+    fn test_infers_missing_mint_from_pool_vaults() {
+        let mut tx = swap_fixture(swap_data(SWAP_BASE_IN_DISCRIMINATOR, 2_000_000_000, 1));
+        // Wrapped SOL account created and closed within the transaction has no balance entry
+        tx["meta"]["preTokenBalances"].as_array_mut().unwrap().remove(0);
+
+        let swap = decode_transaction(&tx).pop().unwrap();
+        assert_eq!(swap.input_mint.as_deref(), Some(WSOL_MINT));
+        assert_eq!(swap.output_mint.as_deref(), Some(TOKEN_MINT));
+    }
+
+    // AmmInfo of a SOL (pc) / token (coin) pool with 0.25% fees
+    fn amm_info_fixture() -> Vec<u8> {
+        let mut data = vec![0u8; AMM_INFO_LEN];
+        let mut put_u64 = |offset: usize, value: u64| data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        put_u64(0, 6); // status: swap only
+        put_u64(32, 6);
+        put_u64(40, 9);
+        put_u64(144, 25);
+        put_u64(152, 10_000);
+        put_u64(192, 1_000_000);
+        put_u64(200, 50_000);
+        let mut put_key = |offset: usize, key: &str| {
+            data[offset..offset + 32].copy_from_slice(&bs58::decode(key).into_vec().unwrap())
+        };
+        put_key(336, "EyRFm8xA7BLqMdSZ5V7g9wFTtXgDz3HHfR7k3DqnVdjT");
+        put_key(368, "3pDT4qCFVMx1D9CFjkGk9AUBHKRjnf7CtZiNKtSjfhKN");
+        put_key(400, TOKEN_MINT);
+        put_key(432, WSOL_MINT);
+        data
+    }
+
+    fn token_account_fixture(mint: &str, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[..32].copy_from_slice(&bs58::decode(mint).into_vec().unwrap());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_decodes_amm_info_and_reserves() {
+        let amm = AmmInfo::decode(&amm_info_fixture()).unwrap();
+        assert_eq!(amm.coin_mint, TOKEN_MINT);
+        assert_eq!(amm.pc_mint, WSOL_MINT);
+        assert_eq!(amm.coin_vault, "EyRFm8xA7BLqMdSZ5V7g9wFTtXgDz3HHfR7k3DqnVdjT");
+        assert_eq!(amm.pc_vault, "3pDT4qCFVMx1D9CFjkGk9AUBHKRjnf7CtZiNKtSjfhKN");
+        assert_eq!((amm.coin_decimals, amm.pc_decimals), (6, 9));
+        assert!((amm.fee_fraction() - 0.0025).abs() < 1e-12);
+
+        let coin_amount = token_account_amount(&token_account_fixture(TOKEN_MINT, 490_253_439_224)).unwrap();
+        let pc_amount = token_account_amount(&token_account_fixture(WSOL_MINT, 102_000_000_000)).unwrap();
+        assert_eq!(amm.reserves(coin_amount, pc_amount), (490_252_439_224, 101_999_950_000));
+    }
+
+    #[test]
+    fn test_rejects_malformed_pool_accounts() {
+        assert!(AmmInfo::decode(&amm_info_fixture()[..700]).is_err());
+        assert!(token_account_amount(&[0u8; 40]).is_err());
+    }
+
+    // OpenBook market for the fixture pool, with the first nonce that derives a vault signer
+    fn market_fixture(market: &str, market_program: &str) -> Vec<u8> {
+        let mut data = vec![0u8; 388];
+        let nonce = (0u64..)
+            .find(|nonce| Pubkey::create_program_address(
+                &[pubkey(market).unwrap().as_ref(), &nonce.to_le_bytes()],
+                &pubkey(market_program).unwrap(),
+            ).is_ok())
+            .unwrap();
+        data[45..53].copy_from_slice(&nonce.to_le_bytes());
+        for (offset, seed) in [(117, 1u8), (165, 2), (253, 3), (285, 4), (317, 5)] {
+            data[offset..offset + 32].copy_from_slice(&[seed; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_built_swap_decodes_back() {
+        let market = Pubkey::new_unique().to_string();
+        let market_program = "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX";
+        let mut amm_data = amm_info_fixture();
+        amm_data[528..560].copy_from_slice(&bs58::decode(&market).into_vec().unwrap());
+        amm_data[560..592].copy_from_slice(&bs58::decode(market_program).into_vec().unwrap());
+        let amm = AmmInfo::decode(&amm_data).unwrap();
+        let keys = AmmSwapKeys::decode(&amm_data, &market_fixture(&market, market_program)).unwrap();
+        assert_eq!(keys.market, market);
+        assert_eq!(keys.market_bids, Pubkey::new_from_array([4; 32]).to_string());
+
+        let user = Pubkey::new_unique();
+        let instruction = swap_instruction(POOL, &amm, &keys, &user, 2_000_000_000, 9_500_000_000, false).unwrap();
+        let parsed = ParsedInstruction {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction.accounts.iter().map(|meta| meta.pubkey.to_string()).collect(),
+            data: Some(bs58::encode(&instruction.data).into_string()),
+        };
+        let swap = decode_instruction(&parsed, &Value::Null).expect("built swap decodes");
+        assert_eq!(swap.pool_address, POOL);
+        assert_eq!(swap.direction, SwapDirection::BaseIn);
+        assert_eq!((swap.amount_in(), swap.min_amount_out()), (2_000_000_000, 9_500_000_000));
+        assert_eq!((swap.pool_coin_vault.as_str(), swap.pool_pc_vault.as_str()), (amm.coin_vault.as_str(), amm.pc_vault.as_str()));
+        // pc (SOL) in, coin out
+        assert_eq!(swap.user_source, associated_token_address(&user, &pubkey(WSOL_MINT).unwrap()).unwrap().to_string());
+        assert_eq!(swap.user_owner, user.to_string());
+        assert!(instruction.accounts[17].is_signer);
+    }
+
+    #[test]
+    fn test_ignores_other_raydium_instructions() {
+        let tx = swap_fixture(swap_data(3, 1, 1)); // Deposit
+        assert!(decode_transaction(&tx).is_empty());
+    }
+}
@@ -1,8 +1,19 @@
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{watch, Mutex, RwLock};
+use crate::config::{Config, RiskConfig};
 use crate::logging::Logger;
+use crate::utils::kill_switch::KillSwitch;
 use crate::utils::mev_strategies::MevStrategyType;
+use crate::utils::pool_blacklist::{PoolBlacklist, PoolBlacklistConfig};
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::risk_state::{self, PersistedStrategyFailure, RiskState};
+use self::risk_utils::{CircuitBreaker, CircuitState, CircuitTransition};
+
+// mint -> SOL commitments, oldest first
+type TokenExposure = HashMap<String, VecDeque<(SystemTime, f64)>>;
 
 #[derive(Debug, Clone)]
 pub struct RiskLimits {
@@ -11,7 +22,16 @@ pub struct RiskLimits {
     pub max_consecutive_failures: u32,    // Max consecutive failures before pause
     pub min_balance_threshold: f64,       // Min balance to continue operations
     pub max_strategy_failures: u32,       // Max failures per strategy before disabling
+    pub strategy_disable_secs: u64,       // How long a strategy stays disabled after reaching it
     pub session_timeout_minutes: u64,     // Session timeout (0 = no timeout)
+    pub max_exposure_per_token_sol: f64,  // Max SOL committed to a single high-variance token
+    pub daily_reset_utc_offset_minutes: i32, // Where the spending day starts: 0 is UTC midnight, 120 is UTC+2
+    pub token_exposure_window_secs: u64,  // How long a commitment counts toward its token's exposure
+    pub max_drawdown_percent: f64,        // Drop from the balance high-water mark that halts trading (0 = off)
+    pub drawdown_cooloff_secs: u64,       // Minimum halt before trading may resume on its own
+    pub drawdown_resume_percent: f64,     // Drawdown the balance must recover to before resuming
+    pub circuit_breaker_cooldown_secs: u64, // Executions skipped after max_consecutive_failures before a probe
+    pub high_water_mark_max_age_secs: u64, // A persisted high-water mark older than this isn't restored
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +41,25 @@ pub struct BalanceTracker {
     pub balance_history: VecDeque<(std::time::SystemTime, f64)>,
     pub total_spent: f64,
     pub total_earned: f64,
+    pub high_water_mark: f64, // Highest balance seen, kept across restarts
+    pub high_water_mark_at: Option<SystemTime>, // When the balance last reached it
+}
+
+impl BalanceTracker {
+    // How far the balance sits below the high-water mark, in percent
+    pub fn drawdown_percent(&self) -> f64 {
+        if self.high_water_mark > 0.0 {
+            ((self.high_water_mark - self.current_balance) / self.high_water_mark * 100.0).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrategyState {
+    Enabled,
+    Disabled { until: SystemTime }, // Too many failures; back to Enabled once `until` has passed
 }
 
 #[derive(Debug, Clone)]
@@ -28,8 +67,37 @@ pub struct StrategyFailureTracker {
     pub strategy_type: MevStrategyType,
     pub failure_count: u32,
     pub last_failure_time: Option<std::time::SystemTime>,
-    pub is_disabled: bool,
-    pub disabled_until: Option<std::time::SystemTime>,
+    pub state: StrategyState,
+}
+
+impl StrategyFailureTracker {
+    pub fn new(strategy_type: MevStrategyType) -> Self {
+        Self { strategy_type, failure_count: 0, last_failure_time: None, state: StrategyState::Enabled }
+    }
+    
+    pub fn is_disabled_at(&self, now: SystemTime) -> bool {
+        matches!(self.state, StrategyState::Disabled { until } if now < until)
+    }
+    
+    pub fn disabled_until(&self) -> Option<SystemTime> {
+        match self.state {
+            StrategyState::Disabled { until } => Some(until),
+            StrategyState::Enabled => None,
+        }
+    }
+    
+    // Disabled -> Enabled once the window is over, with a fresh failure count. Returns whether
+    // it re-enabled; callers hold the write lock, so the check and the change are one step
+    fn expire_at(&mut self, now: SystemTime) -> bool {
+        match self.state {
+            StrategyState::Disabled { until } if now >= until => {
+                self.state = StrategyState::Enabled;
+                self.failure_count = 0;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +116,18 @@ pub enum RiskEventType {
     StrategyDisabled,
     LossLimitExceeded,
     SessionTimeout,
+    DailySummary, // Totals of a spending day that just ended
+    DrawdownHalt,
+    PoolBlacklisted,
+}
+
+// Spending counters for one budget day, reset when the day rolls over
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DailySpending {
+    day: u64, // Days since the epoch in the configured reset offset
+    spent: f64,
+    operations: u32,
+    failures: u32,
 }
 
 pub struct RiskManager {
@@ -56,46 +136,75 @@ pub struct RiskManager {
     strategy_failures: Arc<RwLock<HashMap<String, StrategyFailureTracker>>>,
     risk_events: Arc<RwLock<Vec<RiskEvent>>>,
     session_start_time: std::time::SystemTime,
-    global_daily_spent: Arc<RwLock<f64>>,
+    global_daily_spent: Arc<RwLock<DailySpending>>,
     consecutive_failure_count: Arc<RwLock<u32>>,
     last_operation_time: Arc<RwLock<std::time::SystemTime>>,
+    token_exposure: Arc<RwLock<TokenExposure>>,
+    last_pause_state: Arc<RwLock<bool>>, // Last state reported by check_pause_state
+    state_file: Option<String>, // Where the risk state is persisted; None keeps it in memory only
+    state_write: Mutex<()>, // Serializes saves so an older snapshot can't land after a newer one
+    state_dirty: AtomicBool, // Changed since the last save; persist_state or spawn_state_flush writes it out
+    kill_switch: Arc<KillSwitch>,
+    drawdown_halted_since: Arc<RwLock<Option<SystemTime>>>,
+    circuit_breaker: Arc<RwLock<CircuitBreaker>>, // Opened by consecutive_failure_count
+    pool_blacklist: PoolBlacklist, // Shared with the evaluator, which skips listed pools
+}
+
+impl RiskLimits {
+    pub fn from_config(config: &RiskConfig) -> Self {
+        Self {
+            global_loss_per_bundle: config.global_loss_per_bundle,
+            global_daily_spending_limit: config.global_daily_spending_limit,
+            max_consecutive_failures: config.max_consecutive_failures,
+            min_balance_threshold: config.min_balance,
+            max_strategy_failures: config.max_strategy_failures,
+            strategy_disable_secs: config.strategy_disable_secs,
+            session_timeout_minutes: config.session_timeout_minutes,
+            max_exposure_per_token_sol: config.max_exposure_per_token_sol,
+            daily_reset_utc_offset_minutes: config.daily_reset_utc_offset_minutes,
+            token_exposure_window_secs: config.token_exposure_window_secs,
+            max_drawdown_percent: config.max_drawdown_percent,
+            drawdown_cooloff_secs: config.drawdown_cooloff_secs,
+            drawdown_resume_percent: config.drawdown_resume_percent,
+            circuit_breaker_cooldown_secs: config.circuit_breaker_cooldown_secs,
+            high_water_mark_max_age_secs: config.high_water_mark_max_age_secs,
+        }
+    }
+    
+    // Budget day `now` falls in, per the configured reset offset
+    pub fn budget_day(&self, now: SystemTime) -> u64 {
+        risk_state::budget_day(unix_secs(now), self.daily_reset_utc_offset_minutes as i64 * 60)
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0)
+}
+
+// SOL committed to `mint` within `window` of `now`; older commitments are dropped on the way
+fn exposure_within_window(exposure: &mut TokenExposure, mint: &str, now: SystemTime, window: Duration) -> f64 {
+    let Some(commitments) = exposure.get_mut(mint) else {
+        return 0.0;
+    };
+    while commitments.front().is_some_and(|(at, _)| now.duration_since(*at).unwrap_or_default() >= window) {
+        commitments.pop_front();
+    }
+    let total = commitments.iter().map(|(_, amount)| amount).sum();
+    if commitments.is_empty() {
+        exposure.remove(mint);
+    }
+    total
 }
 
 impl RiskManager {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let limits = RiskLimits {
-            global_loss_per_bundle: std::env::var("GLOBAL_LOSS_PER_BUNDLE")
-                .unwrap_or_else(|_| "0.01".to_string())
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid GLOBAL_LOSS_PER_BUNDLE: {}", e))?,
-            
-            global_daily_spending_limit: std::env::var("GLOBAL_DAILY_SPENDING_LIMIT")
-                .unwrap_or_else(|_| "10.0".to_string())
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid GLOBAL_DAILY_SPENDING_LIMIT: {}", e))?,
-            
-            max_consecutive_failures: std::env::var("MAX_CONSECUTIVE_FAILURES")
-                .unwrap_or_else(|_| "5".to_string())
-                .parse::<u32>()
-                .map_err(|e| format!("Invalid MAX_CONSECUTIVE_FAILURES: {}", e))?,
-                
-            min_balance_threshold: std::env::var("MIN_BALANCE_THRESHOLD")
-                .unwrap_or_else(|_| "0.5".to_string())
-                .parse::<f64>()
-                .map_err(|e| format!("Invalid MIN_BALANCE_THRESHOLD: {}", e))?,
-                
-            max_strategy_failures: std::env::var("MAX_STRATEGY_FAILURES")
-                .unwrap_or_else(|_| "3".to_string())
-                .parse::<u32>()
-                .map_err(|e| format!("Invalid MAX_STRATEGY_FAILURES: {}", e))?,
-                
-            session_timeout_minutes: std::env::var("SESSION_TIMEOUT_MINUTES")
-                .unwrap_or_else(|_| "0".to_string()) // 0 means no timeout
-                .parse::<u64>()
-                .map_err(|e| format!("Invalid SESSION_TIMEOUT_MINUTES: {}", e))?,
-        };
-        
-        Ok(Self {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self::with_limits(RiskLimits::from_config(&config.risk)).with_kill_switch(Arc::new(KillSwitch::from_config(&config.risk))))
+    }
+    
+    pub fn with_limits(limits: RiskLimits) -> Self {
+        let today = limits.budget_day(SystemTime::now());
+        let circuit_breaker = CircuitBreaker::new(&limits);
+        Self {
             limits,
             balance_tracker: Arc::new(RwLock::new(BalanceTracker {
                 initial_balance: 0.0,
@@ -103,38 +212,237 @@ impl RiskManager {
                 balance_history: VecDeque::new(),
                 total_spent: 0.0,
                 total_earned: 0.0,
+                high_water_mark: 0.0,
+                high_water_mark_at: None,
             })),
             strategy_failures: Arc::new(RwLock::new(HashMap::new())),
             risk_events: Arc::new(RwLock::new(Vec::new())),
             session_start_time: std::time::SystemTime::now(),
-            global_daily_spent: Arc::new(RwLock::new(0.0)),
+            global_daily_spent: Arc::new(RwLock::new(DailySpending { day: today, ..DailySpending::default() })),
             consecutive_failure_count: Arc::new(RwLock::new(0)),
             last_operation_time: Arc::new(RwLock::new(std::time::SystemTime::now())),
-        })
+            token_exposure: Arc::new(RwLock::new(HashMap::new())),
+            last_pause_state: Arc::new(RwLock::new(false)),
+            state_file: None,
+            state_write: Mutex::new(()),
+            state_dirty: AtomicBool::new(false),
+            kill_switch: Arc::new(KillSwitch::disabled()),
+            drawdown_halted_since: Arc::new(RwLock::new(None)),
+            circuit_breaker: Arc::new(RwLock::new(circuit_breaker)),
+            pool_blacklist: PoolBlacklist::new(PoolBlacklistConfig::default()),
+        }
+    }
+    
+    pub fn with_kill_switch(mut self, kill_switch: Arc<KillSwitch>) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+    
+    pub fn kill_switch(&self) -> &Arc<KillSwitch> {
+        &self.kill_switch
+    }
+    
+    pub fn with_pool_blacklist(mut self, pool_blacklist: PoolBlacklist) -> Self {
+        self.pool_blacklist = pool_blacklist;
+        self
+    }
+    
+    pub fn pool_blacklist(&self) -> &PoolBlacklist {
+        &self.pool_blacklist
+    }
+    
+    // Persists the risk state to `path` (see spawn_state_flush) and restores what the file holds
+    // for today, so a restart doesn't hand the bot a fresh daily budget. The high-water mark is
+    // restored only while younger than high_water_mark_max_age_secs, so a balance from long ago
+    // can't hold the bot in drawdown for good
+    pub fn with_state_file(mut self, path: &str) -> Self {
+        let now = SystemTime::now();
+        if let Some(state) = RiskState::load(path) {
+            let state = state.for_day(self.limits.budget_day(now), unix_secs(now));
+            let strategy_failures = state.strategy_failures.iter()
+                .map(|failure| (format!("{:?}", failure.strategy), StrategyFailureTracker {
+                    strategy_type: failure.strategy.clone(),
+                    failure_count: failure.failure_count,
+                    last_failure_time: None,
+                    state: match failure.disabled_until {
+                        Some(until) => StrategyState::Disabled { until: UNIX_EPOCH + Duration::from_secs(until) },
+                        None => StrategyState::Enabled,
+                    },
+                }))
+                .collect::<HashMap<_, _>>();
+            Logger::status_update(&format!(
+                "Restored risk state from {}: {:.4} SOL spent today, {} consecutive failures, {} disabled strategies",
+                path, state.daily_spent, state.consecutive_failures,
+                strategy_failures.values().filter(|tracker| tracker.is_disabled_at(now)).count()
+            ));
+            
+            self.global_daily_spent = Arc::new(RwLock::new(DailySpending {
+                day: state.day,
+                spent: state.daily_spent,
+                operations: state.daily_operations,
+                failures: state.daily_failures,
+            }));
+            self.consecutive_failure_count = Arc::new(RwLock::new(state.consecutive_failures));
+            // Restarting mid-streak doesn't buy a fresh run of failures
+            if let Some(circuit_breaker) = Arc::get_mut(&mut self.circuit_breaker) {
+                circuit_breaker.get_mut().record_failure_at(state.consecutive_failures, now);
+            }
+            self.strategy_failures = Arc::new(RwLock::new(strategy_failures));
+            // A mark without a timestamp predates it being recorded and counts as expired
+            let high_water_mark_age = state.balance_high_water_mark_at.map(|at| unix_secs(now).saturating_sub(at));
+            if high_water_mark_age.is_some_and(|age| age < self.limits.high_water_mark_max_age_secs) {
+                if let Some(tracker) = Arc::get_mut(&mut self.balance_tracker) {
+                    let tracker = tracker.get_mut();
+                    tracker.high_water_mark = state.balance_high_water_mark;
+                    tracker.high_water_mark_at = state.balance_high_water_mark_at.map(|at| UNIX_EPOCH + Duration::from_secs(at));
+                }
+            } else if state.balance_high_water_mark > 0.0 {
+                Logger::status_update(&format!(
+                    "Not restoring the {:.4} SOL balance high-water mark: older than {}s",
+                    state.balance_high_water_mark, self.limits.high_water_mark_max_age_secs
+                ));
+            }
+            self.drawdown_halted_since = Arc::new(RwLock::new(
+                state.drawdown_halted_since.map(|since| UNIX_EPOCH + Duration::from_secs(since))
+            ));
+        }
+        self.state_file = Some(path.to_string());
+        self
+    }
+    
+    async fn snapshot_state(&self) -> RiskState {
+        let daily = self.global_daily_spent.read().await.clone();
+        let mut state = RiskState::new(daily.day);
+        state.daily_spent = daily.spent;
+        state.daily_operations = daily.operations;
+        state.daily_failures = daily.failures;
+        state.consecutive_failures = *self.consecutive_failure_count.read().await;
+        {
+            let tracker = self.balance_tracker.read().await;
+            state.balance_high_water_mark = tracker.high_water_mark;
+            state.balance_high_water_mark_at = tracker.high_water_mark_at.map(unix_secs);
+        }
+        state.drawdown_halted_since = self.drawdown_halted_since.read().await.map(unix_secs);
+        state.strategy_failures = self.strategy_failures.read().await.values()
+            .filter(|tracker| tracker.failure_count > 0 || tracker.disabled_until().is_some())
+            .map(|tracker| PersistedStrategyFailure {
+                strategy: tracker.strategy_type.clone(),
+                failure_count: tracker.failure_count,
+                disabled_until: tracker.disabled_until().map(unix_secs),
+            })
+            .collect();
+        state
+    }
+    
+    // Writes the risk state out now, on the blocking pool; used on shutdown
+    pub async fn flush_state(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(ref path) = self.state_file else {
+            return Ok(());
+        };
+        let _write = self.state_write.lock().await;
+        self.state_dirty.store(false, Ordering::SeqCst);
+        let state = self.snapshot_state().await;
+        let path = path.clone();
+        let saved = tokio::task::spawn_blocking(move || state.save(&path)).await
+            .map_err(|e| format!("Risk state write did not finish: {}", e))
+            .and_then(|saved| saved.map_err(|e| e.to_string()));
+        if saved.is_err() {
+            self.state_dirty.store(true, Ordering::SeqCst);
+        }
+        Ok(saved?)
+    }
+    
+    // One tick of spawn_state_flush: writes the state out if anything changed since the last save
+    pub async fn flush_state_if_dirty(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.state_dirty.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.flush_state().await
+    }
+    
+    // Writes changes out every `interval` until `shutdown` flips to true. Spend and failure counters
+    // are saved as they change (see persist_state), so this only carries the high-water mark,
+    // strategy re-enables and manual resets, which a crash can set back by at most one interval; a
+    // failed write is retried on the next tick
+    pub fn spawn_state_flush(self: &Arc<Self>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        if self.state_file.is_none() {
+            return;
+        }
+        let risk_manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {},
+                    _ = shutdown.changed() => return,
+                }
+                if let Err(e) = risk_manager.flush_state_if_dirty().await {
+                    Logger::error_occurred(&format!("Failed to persist risk state: {}", e));
+                }
+            }
+        });
+    }
+    
+    // Called after changes that can wait for the next spawn_state_flush tick
+    fn mark_state_dirty(&self) {
+        self.state_dirty.store(true, Ordering::SeqCst);
+    }
+    
+    // Saves before returning, for what a crash must not roll back: the day's spend, the failure
+    // counts behind the circuit breaker and strategy disables, and drawdown halts. A failed write
+    // stays dirty for the next tick
+    async fn persist_state(&self) {
+        self.mark_state_dirty();
+        if let Err(e) = self.flush_state().await {
+            Logger::error_occurred(&format!("Failed to persist risk state: {}", e));
+        }
     }
     
     pub async fn initialize_balance(&self, balance: f64) {
-        let mut tracker = self.balance_tracker.write().await;
-        tracker.initial_balance = balance;
-        tracker.current_balance = balance;
-        
-        // Add to balance history
-        tracker.balance_history.push_back((std::time::SystemTime::now(), balance));
-        
-        // Keep only recent history (last 1000 entries)
-        if tracker.balance_history.len() > 1000 {
-            let to_remove = tracker.balance_history.len() - 1000;
-            tracker.balance_history.drain(0..to_remove);
+        // A restart below the restored high-water mark can be in drawdown already
+        let drawdown = {
+            let mut tracker = self.balance_tracker.write().await;
+            tracker.initial_balance = balance;
+            tracker.current_balance = balance;
+            if balance > tracker.high_water_mark {
+                tracker.high_water_mark = balance;
+                tracker.high_water_mark_at = Some(SystemTime::now());
+            }
+            
+            // Add to balance history
+            tracker.balance_history.push_back((std::time::SystemTime::now(), balance));
+            
+            // Keep only recent history (last 1000 entries)
+            if tracker.balance_history.len() > 1000 {
+                let to_remove = tracker.balance_history.len() - 1000;
+                tracker.balance_history.drain(0..to_remove);
+            }
+            tracker.drawdown_percent()
+        };
+        if self.update_drawdown_halt_at(drawdown, SystemTime::now()).await {
+            self.persist_state().await;
+        } else {
+            self.mark_state_dirty();
         }
     }
     
     pub async fn update_balance(&self, new_balance: f64) -> Result<bool, RiskError> {
+        self.update_balance_at(new_balance, SystemTime::now()).await
+    }
+    
+    pub async fn update_balance_at(&self, new_balance: f64, now: SystemTime) -> Result<bool, RiskError> {
         let mut tracker = self.balance_tracker.write().await;
         let old_balance = tracker.current_balance;
         tracker.current_balance = new_balance;
         
+        let raised_high_water_mark = new_balance > tracker.high_water_mark;
+        if raised_high_water_mark {
+            tracker.high_water_mark = new_balance;
+            tracker.high_water_mark_at = Some(now);
+        }
+        
         // Add to history
-        tracker.balance_history.push_back((std::time::SystemTime::now(), new_balance));
+        tracker.balance_history.push_back((now, new_balance));
         
         // Keep only recent history
         if tracker.balance_history.len() > 1000 {
@@ -142,12 +450,18 @@ impl RiskManager {
             tracker.balance_history.drain(0..to_remove);
         }
         
-        // Check if balance dropped below minimum threshold
+        let drawdown = tracker.drawdown_percent();
+        let halt_changed = self.update_drawdown_halt_at(drawdown, now).await;
+        
+        // Check if balance dropped below minimum threshold; the event marks the crossing, not
+        // every update while the balance stays below
         if new_balance < self.limits.min_balance_threshold {
-            let drop_percentage = (old_balance - new_balance) / old_balance;
-            self.record_risk_event(RiskEventType::BalanceThresholdBreached, 
-                                 format!("Balance dropped below minimum threshold: {:.4} SOL", new_balance),
-                                 Some(drop_percentage)).await;
+            if old_balance >= self.limits.min_balance_threshold {
+                let drop_percentage = (old_balance - new_balance) / old_balance;
+                self.record_risk_event(RiskEventType::BalanceThresholdBreached, 
+                                     format!("Balance dropped below minimum threshold: {:.4} SOL", new_balance),
+                                     Some(drop_percentage)).await;
+            }
             return Err(RiskError::BalanceTooLow(new_balance));
         }
         
@@ -157,15 +471,89 @@ impl RiskManager {
         } else {
             tracker.total_earned += (new_balance - old_balance);
         }
+        drop(tracker);
+        
+        if halt_changed {
+            self.persist_state().await;
+        } else if raised_high_water_mark {
+            self.mark_state_dirty();
+        }
+        
+        if self.drawdown_halted_since.read().await.is_some() {
+            return Err(RiskError::DrawdownHalt(drawdown));
+        }
         
         Ok(true)
     }
     
-    pub async fn should_allow_operation(
-        &self,
-        expected_profit: f64,
-        costs: f64
-    ) -> Result<(), RiskError> {
+    // Halts once the drawdown passes the limit. The halt lifts on its own only after the cool-off
+    // and once the balance has recovered to the resume level; otherwise reset_risk_state lifts it.
+    // Returns whether the halt state changed
+    async fn update_drawdown_halt_at(&self, drawdown: f64, now: SystemTime) -> bool {
+        if self.limits.max_drawdown_percent <= 0.0 {
+            return false;
+        }
+        
+        let mut halted_since = self.drawdown_halted_since.write().await;
+        match *halted_since {
+            None if drawdown > self.limits.max_drawdown_percent => {
+                *halted_since = Some(now);
+                drop(halted_since);
+                let details = format!(
+                    "Drawdown {:.2}% from the balance high-water mark exceeds {:.2}%, halting trading",
+                    drawdown, self.limits.max_drawdown_percent
+                );
+                Logger::error_occurred(&details);
+                self.record_risk_event(RiskEventType::DrawdownHalt, details, Some(drawdown)).await;
+                true
+            }
+            Some(since) if now.duration_since(since).unwrap_or_default() >= Duration::from_secs(self.limits.drawdown_cooloff_secs)
+                && drawdown <= self.limits.drawdown_resume_percent => {
+                *halted_since = None;
+                Logger::status_update(&format!("Drawdown recovered to {:.2}%, lifting the trading halt", drawdown));
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    pub async fn is_drawdown_halted(&self) -> bool {
+        self.drawdown_halted_since.read().await.is_some()
+    }
+    
+    pub async fn drawdown_percent(&self) -> f64 {
+        self.balance_tracker.read().await.drawdown_percent()
+    }
+    
+    // Starts a new spending day once `now` is past the reset boundary, reporting the day that
+    // ended as a DailySummary risk event
+    async fn roll_daily_spending_at(&self, now: SystemTime) {
+        let today = self.limits.budget_day(now);
+        let finished = {
+            let mut daily = self.global_daily_spent.write().await;
+            if daily.day >= today {
+                return;
+            }
+            std::mem::replace(&mut *daily, DailySpending { day: today, ..DailySpending::default() })
+        };
+        
+        let summary = format!(
+            "Daily summary for {}: spent {:.4} SOL of {:.4} SOL over {} operations, {} failed",
+            risk_state::day_label(finished.day), finished.spent, self.limits.global_daily_spending_limit,
+            finished.operations, finished.failures
+        );
+        Logger::status_update(&summary);
+        self.record_risk_event(RiskEventType::DailySummary, summary, Some(finished.spent)).await;
+        self.mark_state_dirty();
+    }
+    
+    pub async fn should_allow_operation(&self, costs: f64) -> Result<(), RiskError> {
+        self.should_allow_operation_at(costs, SystemTime::now()).await
+    }
+    
+    pub async fn should_allow_operation_at(&self, costs: f64, now: SystemTime) -> Result<(), RiskError> {
+        self.roll_daily_spending_at(now).await;
+        
         // Check all risk conditions before allowing operation
         
         // 1. Check session timeout
@@ -182,7 +570,7 @@ impl RiskManager {
         }
         
         // 2. Check daily spending limit
-        let daily_spent = { *self.global_daily_spent.read().await };
+        let daily_spent = { self.global_daily_spent.read().await.spent };
         let potential_total = daily_spent + costs;
         
         if potential_total > self.limits.global_daily_spending_limit {
@@ -193,84 +581,106 @@ impl RiskManager {
             return Err(RiskError::DailySpendingLimitExceeded);
         }
         
-        // 3. Check balance is sufficient for operation
+        // 3. Check balance is sufficient for operation and no drawdown halt is in force
+        if self.is_drawdown_halted().await {
+            return Err(RiskError::DrawdownHalt(self.drawdown_percent().await));
+        }
         let current_balance = { self.balance_tracker.read().await.current_balance };
         if current_balance < costs {
             return Err(RiskError::InsufficientBalance);
         }
         
-        // 4. Check consecutive failures
-        let consecutive_failures = { *self.consecutive_failure_count.read().await };
-        if consecutive_failures >= self.limits.max_consecutive_failures {
-            self.record_risk_event(RiskEventType::ConsecutiveFailures,
-                                 format!("Maximum consecutive failures reached: {}", consecutive_failures),
-                                 Some(consecutive_failures as f64)).await;
+        // 4. Check the consecutive-failure circuit breaker; past its cool-down this call may be the probe
+        let (allowed, transition) = self.circuit_breaker.write().await.allow_at(now);
+        self.log_circuit_transition(transition).await;
+        if !allowed {
             return Err(RiskError::MaxConsecutiveFailures);
         }
         
         Ok(())
     }
     
-    pub async fn should_allow_strategy(
-        &self,
-        strategy_type: &MevStrategyType,
-        expected_profit: f64,
-        costs: f64
-    ) -> Result<(), RiskError> {
+    // Token exposure is reserved separately, with try_reserve_exposure
+    pub async fn should_allow_strategy(&self, strategy_type: &MevStrategyType, costs: f64) -> Result<(), RiskError> {
         // First check general operation allowance
-        self.should_allow_operation(expected_profit, costs).await?;
+        self.should_allow_operation(costs).await?;
         
         // Check if this specific strategy is disabled due to failures
+        self.strategy_allowed_at(strategy_type, SystemTime::now()).await
+    }
+    
+    // Whether the strategy may run at `now`, re-enabling it if its disable window is over. One
+    // write-lock pass, so a failure recorded concurrently can't be lost between check and update
+    pub async fn strategy_allowed_at(&self, strategy_type: &MevStrategyType, now: SystemTime) -> Result<(), RiskError> {
         let strategy_key = format!("{:?}", strategy_type);
-        let failures = self.strategy_failures.read().await;
-        
-        if let Some(tracker) = failures.get(&strategy_key) {
-            if tracker.is_disabled {
-                if let Some(disabled_until) = tracker.disabled_until {
-                    if std::time::SystemTime::now() < disabled_until {
-                        return Err(RiskError::StrategyDisabled(strategy_key));
-                    } else {
-                        // Re-enable the strategy after timeout
-                        drop(failures);
-                        let mut failures = self.strategy_failures.write().await;
-                        if let Some(mut tracker) = failures.get_mut(&strategy_key) {
-                            tracker.is_disabled = false;
-                            tracker.disabled_until = None;
-                            Logger::status_update(&format!("Re-enabling strategy: {}", strategy_key));
-                        }
-                    }
-                } else {
-                    return Err(RiskError::StrategyDisabled(strategy_key));
-                }
+        let re_enabled = {
+            let mut failures = self.strategy_failures.write().await;
+            let Some(tracker) = failures.get_mut(&strategy_key) else {
+                return Ok(());
+            };
+            let re_enabled = tracker.expire_at(now);
+            if tracker.is_disabled_at(now) {
+                return Err(RiskError::StrategyDisabled(strategy_key));
             }
-        }
+            re_enabled
+        };
         
+        if re_enabled {
+            Logger::status_update(&format!("Re-enabling strategy: {}", strategy_key));
+            self.mark_state_dirty();
+        }
         Ok(())
     }
     
     pub async fn record_successful_operation(&self, profit: f64) {
-        // Reset consecutive failure counter
+        self.record_successful_operation_at(profit, SystemTime::now()).await;
+    }
+    
+    pub async fn record_successful_operation_at(&self, profit: f64, now: SystemTime) {
+        // Reset consecutive failure counter; a success (normally the half-open probe) closes the breaker
         *self.consecutive_failure_count.write().await = 0;
+        let transition = self.circuit_breaker.write().await.record_success();
+        self.log_circuit_transition(transition).await;
         
-        // Add to daily spent if this was a cost (negative profit)
-        if profit < 0.0 {
-            let mut daily_spent = self.global_daily_spent.write().await;
-            *daily_spent += profit.abs();
+        // Costs count against the day they were paid in
+        self.roll_daily_spending_at(now).await;
+        {
+            let mut daily = self.global_daily_spent.write().await;
+            daily.operations += 1;
+            // Add to daily spent if this was a cost (negative profit)
+            if profit < 0.0 {
+                daily.spent += profit.abs();
+            }
         }
         
         // Update last operation time
-        *self.last_operation_time.write().await = std::time::SystemTime::now();
+        *self.last_operation_time.write().await = now;
+        
+        self.persist_state().await;
     }
     
     pub async fn record_failed_operation(&self) -> Result<(), RiskError> {
+        self.record_failed_operation_at(SystemTime::now()).await
+    }
+    
+    pub async fn record_failed_operation_at(&self, now: SystemTime) -> Result<(), RiskError> {
+        self.roll_daily_spending_at(now).await;
+        self.global_daily_spent.write().await.failures += 1;
+        
         // Increment consecutive failure counter
-        let mut failure_count = self.consecutive_failure_count.write().await;
-        *failure_count += 1;
+        let failure_count = {
+            let mut failure_count = self.consecutive_failure_count.write().await;
+            *failure_count += 1;
+            *failure_count
+        };
+        let transition = self.circuit_breaker.write().await.record_failure_at(failure_count, now);
+        self.log_circuit_transition(transition).await;
+        self.persist_state().await;
         
-        if *failure_count >= self.limits.max_consecutive_failures {
+        if failure_count >= self.limits.max_consecutive_failures {
             self.record_risk_event(RiskEventType::ConsecutiveFailures,
-                                 format!("Reached maximum consecutive failures: {}", *failure_count),
-                                 Some(*failure_count as f64)).await;
+                                 format!("Reached maximum consecutive failures: {}", failure_count),
+                                 Some(failure_count as f64)).await;
             return Err(RiskError::MaxConsecutiveFailures);
         }
         
@@ -278,26 +688,24 @@ impl RiskManager {
     }
     
     pub async fn record_strategy_failure(&self, strategy_type: &MevStrategyType) {
+        self.record_strategy_failure_at(strategy_type, SystemTime::now()).await;
+    }
+    
+    pub async fn record_strategy_failure_at(&self, strategy_type: &MevStrategyType, now: SystemTime) {
         let strategy_key = format!("{:?}", strategy_type);
         let mut failures = self.strategy_failures.write().await;
         
-        let tracker = failures.entry(strategy_key.clone()).or_insert_with(|| StrategyFailureTracker {
-            strategy_type: strategy_type.clone(),
-            failure_count: 0,
-            last_failure_time: None,
-            is_disabled: false,
-            disabled_until: None,
-        });
+        let tracker = failures.entry(strategy_key.clone())
+            .or_insert_with(|| StrategyFailureTracker::new(strategy_type.clone()));
         
+        // A failure after the window ran out counts toward a new streak
+        tracker.expire_at(now);
         tracker.failure_count += 1;
-        tracker.last_failure_time = Some(std::time::SystemTime::now());
+        tracker.last_failure_time = Some(now);
         
         // Check if we should disable this strategy
-        if tracker.failure_count >= self.limits.max_strategy_failures && !tracker.is_disabled {
-            tracker.is_disabled = true;
-            // Disable for 1 hour (can be configured)
-            let disable_until = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
-            tracker.disabled_until = Some(disable_until);
+        if tracker.failure_count >= self.limits.max_strategy_failures && tracker.state == StrategyState::Enabled {
+            tracker.state = StrategyState::Disabled { until: now + Duration::from_secs(self.limits.strategy_disable_secs) };
             
             self.record_risk_event(RiskEventType::StrategyDisabled,
                                  format!("Strategy disabled due to too many failures: {}", strategy_key),
@@ -306,6 +714,23 @@ impl RiskManager {
             Logger::error_occurred(&format!("Strategy {} has been disabled due to {} consecutive failures", 
                                           strategy_key, tracker.failure_count));
         }
+        drop(failures);
+        
+        self.persist_state().await;
+    }
+    
+    // A landed execution ends the strategy's failure streak
+    pub async fn record_strategy_success(&self, strategy_type: &MevStrategyType) {
+        let ended_streak = match self.strategy_failures.write().await.get_mut(&format!("{:?}", strategy_type)) {
+            Some(tracker) if tracker.failure_count > 0 => {
+                tracker.failure_count = 0;
+                true
+            }
+            _ => false,
+        };
+        if ended_streak {
+            self.mark_state_dirty();
+        }
     }
     
     pub async fn check_bundle_risk(
@@ -343,23 +768,112 @@ impl RiskManager {
         }
     }
     
-    // Check if the bot should pause operations
+    // Check if the bot should pause operations. Consecutive failures don't pause: the circuit
+    // breaker holds back executions while notifications are still evaluated
     pub async fn should_pause_operations(&self) -> bool {
+        if self.kill_switch.is_engaged() || self.is_drawdown_halted().await {
+            return true;
+        }
+        
         let current_balance = { self.balance_tracker.read().await.current_balance };
-        let consecutive_failures = { *self.consecutive_failure_count.read().await };
         
-        // Pause if balance is too low or too many consecutive failures
-        current_balance < self.limits.min_balance_threshold || 
-        consecutive_failures >= self.limits.max_consecutive_failures
+        // Pause if balance is too low
+        current_balance < self.limits.min_balance_threshold
+    }
+    
+    pub async fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker.read().await.state()
+    }
+    
+    async fn log_circuit_transition(&self, transition: Option<CircuitTransition>) {
+        match transition {
+            Some((_, CircuitState::Open)) => {
+                let consecutive_failures = { *self.consecutive_failure_count.read().await };
+                Logger::status_update(&format!(
+                    "Circuit breaker open after {} consecutive failures, skipping executions for {}s",
+                    consecutive_failures, self.limits.circuit_breaker_cooldown_secs
+                ));
+            }
+            Some((_, CircuitState::HalfOpen)) => Logger::status_update("Circuit breaker half-open, letting one probe execution through"),
+            Some((_, CircuitState::Closed)) => Logger::status_update("Circuit breaker closed, executions resumed"),
+            None => {}
+        }
+    }
+    
+    // Same as should_pause_operations, but logs when the pause state changes instead of
+    // on every call, so hot paths can check it per opportunity
+    pub async fn check_pause_state(&self) -> bool {
+        let paused = self.should_pause_operations().await;
+        if self.update_pause_state(paused).await {
+            let kill_switch_reason = if paused { self.kill_switch.reason() } else { None };
+            if let Some(reason) = kill_switch_reason {
+                Logger::status_update(&format!("Kill switch engaged ({}), paused new opportunities", reason));
+            } else if paused && self.is_drawdown_halted().await {
+                Logger::status_update(&format!(
+                    "Risk controls paused operations: drawdown {:.2}% (max {:.2}%)",
+                    self.drawdown_percent().await, self.limits.max_drawdown_percent
+                ));
+            } else if paused {
+                let current_balance = { self.balance_tracker.read().await.current_balance };
+                Logger::status_update(&format!(
+                    "Risk controls paused operations: balance {:.4} SOL (min {:.4})",
+                    current_balance, self.limits.min_balance_threshold
+                ));
+            } else {
+                Logger::status_update("Risk controls resumed operations");
+            }
+        }
+        paused
+    }
+    
+    // Returns true when the state differs from the last one reported
+    async fn update_pause_state(&self, paused: bool) -> bool {
+        let mut last_pause_state = self.last_pause_state.write().await;
+        let changed = *last_pause_state != paused;
+        *last_pause_state = paused;
+        changed
+    }
+    
+    // Why trading is currently held back, one line per reason; empty while nothing is
+    pub async fn active_pauses(&self) -> Vec<String> {
+        self.active_pauses_at(SystemTime::now()).await
+    }
+    
+    pub async fn active_pauses_at(&self, now: SystemTime) -> Vec<String> {
+        let mut pauses = Vec::new();
+        if let Some(reason) = self.kill_switch.reason() {
+            pauses.push(format!("kill switch engaged ({})", reason));
+        }
+        if self.is_drawdown_halted().await {
+            pauses.push(format!("drawdown halt at {:.2}% (max {:.2}%)", self.drawdown_percent().await, self.limits.max_drawdown_percent));
+        }
+        let current_balance = { self.balance_tracker.read().await.current_balance };
+        if current_balance < self.limits.min_balance_threshold {
+            pauses.push(format!("balance {:.4} SOL below the {:.4} SOL minimum", current_balance, self.limits.min_balance_threshold));
+        }
+        match self.circuit_state().await {
+            CircuitState::Open => pauses.push("circuit breaker open".to_string()),
+            CircuitState::HalfOpen => pauses.push("circuit breaker half-open".to_string()),
+            CircuitState::Closed => {}
+        }
+        let mut disabled: Vec<String> = self.strategy_failures.read().await.iter()
+            .filter(|(_, tracker)| tracker.is_disabled_at(now))
+            .map(|(strategy, _)| format!("{} disabled", strategy))
+            .collect();
+        disabled.sort();
+        pauses.extend(disabled);
+        pauses
     }
     
     // Get current risk metrics
     pub async fn get_risk_metrics(&self) -> RiskMetrics {
         let tracker = self.balance_tracker.read().await;
-        let daily_spent = *self.global_daily_spent.read().await;
+        let daily_spent = self.global_daily_spent.read().await.spent;
         let consecutive_failures = *self.consecutive_failure_count.read().await;
         
         RiskMetrics {
+            drawdown_percent: tracker.drawdown_percent(),
+            drawdown_halted: self.is_drawdown_halted().await,
             current_balance: tracker.current_balance,
             initial_balance: tracker.initial_balance,
             balance_change: tracker.current_balance - tracker.initial_balance,
@@ -370,26 +884,40 @@ impl RiskManager {
             consecutive_failures,
             max_consecutive_failures: self.limits.max_consecutive_failures,
             active_strategy_failures: self.count_active_strategy_failures().await,
+            circuit_state: self.circuit_state().await,
         }
     }
     
     async fn count_active_strategy_failures(&self) -> usize {
         let failures = self.strategy_failures.read().await;
-        failures.values().filter(|tracker| tracker.is_disabled).count()
+        let now = SystemTime::now();
+        failures.values().filter(|tracker| tracker.is_disabled_at(now)).count()
     }
     
     // Reset risk state (for testing or manual override)
     pub async fn reset_risk_state(&self) {
         *self.consecutive_failure_count.write().await = 0;
+        self.circuit_breaker.write().await.reset();
         *self.last_operation_time.write().await = std::time::SystemTime::now();
         
+        // Lift a drawdown halt; the drawdown is measured from here on
+        *self.drawdown_halted_since.write().await = None;
+        {
+            let mut tracker = self.balance_tracker.write().await;
+            tracker.high_water_mark = tracker.current_balance;
+            tracker.high_water_mark_at = Some(SystemTime::now());
+        }
+        
         // Reset strategy failures
-        let mut failures = self.strategy_failures.write().await;
-        for tracker in failures.values_mut() {
-            tracker.failure_count = 0;
-            tracker.is_disabled = false;
-            tracker.disabled_until = None;
+        {
+            let mut failures = self.strategy_failures.write().await;
+            for tracker in failures.values_mut() {
+                tracker.failure_count = 0;
+                tracker.state = StrategyState::Enabled;
+            }
         }
+        
+        self.mark_state_dirty();
     }
     
     // Manual override to enable a disabled strategy
@@ -397,13 +925,15 @@ impl RiskManager {
         let strategy_key = format!("{:?}", strategy_type);
         let mut failures = self.strategy_failures.write().await;
         
-        if let Some(mut tracker) = failures.get_mut(&strategy_key) {
-            tracker.is_disabled = false;
-            tracker.disabled_until = None;
+        if let Some(tracker) = failures.get_mut(&strategy_key) {
+            tracker.state = StrategyState::Enabled;
             tracker.failure_count = 0; // Reset failure count when manually enabled
             
             Logger::status_update(&format!("Manually re-enabled strategy: {}", strategy_key));
         }
+        drop(failures);
+        
+        self.mark_state_dirty();
     }
     
     // Get risk events in the last N minutes
@@ -418,9 +948,140 @@ impl RiskManager {
             .collect()
     }
     
+    // Check that committing `amount` SOL to `mint` stays under the per-token cap
+    pub async fn check_token_exposure(&self, mint: &str, amount: f64) -> Result<(), RiskError> {
+        self.check_token_exposure_at(mint, amount, SystemTime::now()).await
+    }
+    
+    // Same for every mint of an opportunity; SOL itself is what's being committed, not a position
+    pub async fn check_tokens_exposure(&self, tokens: &[&str], amount: f64) -> Result<(), RiskError> {
+        for mint in tokens.iter().filter(|mint| **mint != WSOL_MINT) {
+            self.check_token_exposure(mint, amount).await?;
+        }
+        Ok(())
+    }
+    
+    pub async fn check_token_exposure_at(&self, mint: &str, amount: f64, now: SystemTime) -> Result<(), RiskError> {
+        let current = self.get_token_exposure_at(mint, now).await;
+        
+        if current + amount > self.limits.max_exposure_per_token_sol {
+            return Err(self.token_exposure_exceeded(mint, current + amount).await);
+        }
+        
+        Ok(())
+    }
+    
+    // Checks `amount` against every non-SOL mint and books it under one lock, so workers running
+    // concurrently can't all pass the check before any of them records. Nothing is booked on Err;
+    // release_tokens_exposure gives the reservation back
+    pub async fn try_reserve_exposure(&self, tokens: &[&str], amount: f64) -> Result<(), RiskError> {
+        self.try_reserve_exposure_at(tokens, amount, SystemTime::now()).await
+    }
+    
+    pub async fn try_reserve_exposure_at(&self, tokens: &[&str], amount: f64, now: SystemTime) -> Result<(), RiskError> {
+        let window = Duration::from_secs(self.limits.token_exposure_window_secs);
+        let mut exposure = self.token_exposure.write().await;
+        let mints: Vec<&str> = tokens.iter().copied().filter(|mint| *mint != WSOL_MINT).collect();
+        for mint in &mints {
+            let current = exposure_within_window(&mut exposure, mint, now, window);
+            if current + amount > self.limits.max_exposure_per_token_sol {
+                drop(exposure);
+                return Err(self.token_exposure_exceeded(mint, current + amount).await);
+            }
+        }
+        if amount > 0.0 {
+            for mint in mints {
+                exposure.entry(mint.to_string()).or_default().push_back((now, amount));
+            }
+        }
+        Ok(())
+    }
+    
+    async fn token_exposure_exceeded(&self, mint: &str, total: f64) -> RiskError {
+        self.record_risk_event(RiskEventType::LossLimitExceeded,
+                             format!("Token exposure cap would be exceeded for {}: {:.4} SOL > {:.4} SOL",
+                                    mint, total, self.limits.max_exposure_per_token_sol),
+                             Some(total)).await;
+        RiskError::TokenExposureExceeded(mint.to_string())
+    }
+    
+    // Books a submitted execution's net result against the pools it traded; a multi-pool route
+    // splits it evenly, since there's no telling which leg lost
+    pub async fn record_pool_outcome(&self, pools: &[String], pnl: f64, failed: bool) {
+        if pools.is_empty() {
+            return;
+        }
+        let share = pnl / pools.len() as f64;
+        for pool in pools {
+            if let Some(entry) = self.pool_blacklist.record_outcome(pool, share, failed).await {
+                self.record_risk_event(
+                    RiskEventType::PoolBlacklisted,
+                    format!("Pool {} blacklisted: {}", entry.pool, entry.reason),
+                    Some(entry.net_loss),
+                ).await;
+            }
+        }
+    }
+    
+    pub async fn record_token_exposure(&self, mint: &str, amount: f64) {
+        self.record_token_exposure_at(mint, amount, SystemTime::now()).await;
+    }
+    
+    pub async fn record_token_exposure_at(&self, mint: &str, amount: f64, now: SystemTime) {
+        if amount <= 0.0 {
+            return;
+        }
+        self.token_exposure.write().await.entry(mint.to_string()).or_default().push_back((now, amount));
+    }
+    
+    // A position was closed or the trade turned out to be a round-trip: `amount` SOL no longer
+    // sits in `mint`. Taken off the oldest commitments first
+    pub async fn release_token_exposure(&self, mint: &str, amount: f64) {
+        let mut exposure = self.token_exposure.write().await;
+        let Some(commitments) = exposure.get_mut(mint) else {
+            return;
+        };
+        let mut remaining = amount;
+        while remaining > 0.0 {
+            let Some(oldest) = commitments.front_mut() else {
+                break;
+            };
+            if oldest.1 > remaining {
+                oldest.1 -= remaining;
+                break;
+            }
+            remaining -= oldest.1;
+            commitments.pop_front();
+        }
+        if commitments.is_empty() {
+            exposure.remove(mint);
+        }
+    }
+    
+    pub async fn release_tokens_exposure(&self, tokens: &[&str], amount: f64) {
+        for mint in tokens.iter().filter(|mint| **mint != WSOL_MINT) {
+            self.release_token_exposure(mint, amount).await;
+        }
+    }
+    
+    pub async fn get_token_exposure(&self, mint: &str) -> f64 {
+        self.get_token_exposure_at(mint, SystemTime::now()).await
+    }
+    
+    // SOL committed to `mint` within the exposure window; older commitments have aged out
+    pub async fn get_token_exposure_at(&self, mint: &str, now: SystemTime) -> f64 {
+        let window = Duration::from_secs(self.limits.token_exposure_window_secs);
+        exposure_within_window(&mut *self.token_exposure.write().await, mint, now, window)
+    }
+    
     // Check if we're within daily limits
     pub async fn check_daily_limits(&self, amount: f64) -> Result<(), RiskError> {
-        let daily_spent = *self.global_daily_spent.read().await;
+        self.check_daily_limits_at(amount, SystemTime::now()).await
+    }
+    
+    pub async fn check_daily_limits_at(&self, amount: f64, now: SystemTime) -> Result<(), RiskError> {
+        self.roll_daily_spending_at(now).await;
+        let daily_spent = self.global_daily_spent.read().await.spent;
         let total_with_new_amount = daily_spent + amount;
         
         if total_with_new_amount > self.limits.global_daily_spending_limit {
@@ -433,6 +1094,8 @@ impl RiskManager {
 
 #[derive(Debug, Clone)]
 pub struct RiskMetrics {
+    pub drawdown_percent: f64,
+    pub drawdown_halted: bool,
     pub current_balance: f64,
     pub initial_balance: f64,
     pub balance_change: f64,
@@ -443,6 +1106,7 @@ pub struct RiskMetrics {
     pub consecutive_failures: u32,
     pub max_consecutive_failures: u32,
     pub active_strategy_failures: usize,
+    pub circuit_state: CircuitState,
 }
 
 #[derive(Debug)]
@@ -454,6 +1118,8 @@ pub enum RiskError {
     StrategyDisabled(String),
     SessionTimeout,
     InsufficientBalance,
+    TokenExposureExceeded(String),
+    DrawdownHalt(f64),
     InternalError(String),
 }
 
@@ -467,6 +1133,8 @@ impl std::fmt::Display for RiskError {
             RiskError::StrategyDisabled(strategy) => write!(f, "Strategy disabled: {}", strategy),
             RiskError::SessionTimeout => write!(f, "Session timeout"),
             RiskError::InsufficientBalance => write!(f, "Insufficient balance"),
+            RiskError::TokenExposureExceeded(mint) => write!(f, "Token exposure cap exceeded: {}", mint),
+            RiskError::DrawdownHalt(drawdown) => write!(f, "Trading halted at a {:.2}% drawdown", drawdown),
             RiskError::InternalError(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -494,6 +1162,11 @@ pub mod risk_utils {
             }
         }
         
+        // Most we commit to a single position, in SOL
+        pub fn capital_cap(&self, current_balance: f64) -> f64 {
+            current_balance * self.max_position_size
+        }
+        
         pub async fn calculate_position_size(
             &self,
             current_balance: f64,
@@ -516,23 +1189,57 @@ pub mod risk_utils {
         }
     }
     
-    // Circuit breaker to pause operations if conditions are unfavorable
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CircuitState {
+        Closed,   // Executions flow
+        Open,     // Too many consecutive failures: executions skipped until the cool-down is over
+        HalfOpen, // Cool-down over: one probe execution decides whether to close again
+    }
+    
+    impl CircuitState {
+        // Gauge value: 0 closed, 1 half-open, 2 open
+        pub fn as_gauge(&self) -> u8 {
+            match self {
+                CircuitState::Closed => 0,
+                CircuitState::HalfOpen => 1,
+                CircuitState::Open => 2,
+            }
+        }
+    }
+    
+    pub type CircuitTransition = (CircuitState, CircuitState);
+    
+    // Circuit breaker to pause executions after a run of consecutive failures. Past the cool-down
+    // the first caller becomes the probe; a probe that never reports back (nothing was submitted)
+    // is replaced after another cool-down. Methods take the current time so tests can use a fake clock
+    #[derive(Debug, Clone)]
     pub struct CircuitBreaker {
         pub enabled: bool,
         pub consecutive_failure_threshold: u32,
-        pub cooldown_period_minutes: u64,
+        pub cooldown: Duration,
+        state: CircuitState,
+        opened_at: SystemTime,
+        probe_started: Option<SystemTime>, // While half-open and the probe is out
     }
     
     impl CircuitBreaker {
-        pub fn new() -> Self {
+        // Threshold from MAX_CONSECUTIVE_FAILURES, cool-down from CIRCUIT_BREAKER_COOLDOWN_SECS
+        pub fn new(limits: &RiskLimits) -> Self {
             Self {
-                enabled: true,
-                consecutive_failure_threshold: 5,
-                cooldown_period_minutes: 10,
+                enabled: limits.max_consecutive_failures > 0,
+                consecutive_failure_threshold: limits.max_consecutive_failures,
+                cooldown: Duration::from_secs(limits.circuit_breaker_cooldown_secs),
+                state: CircuitState::Closed,
+                opened_at: UNIX_EPOCH,
+                probe_started: None,
             }
         }
         
-        pub async fn should_break_circuit(&self, consecutive_failures: u32) -> bool {
+        pub fn state(&self) -> CircuitState {
+            self.state
+        }
+        
+        pub fn should_break_circuit(&self, consecutive_failures: u32) -> bool {
             if !self.enabled {
                 return false;
             }
@@ -540,19 +1247,522 @@ pub mod risk_utils {
             consecutive_failures >= self.consecutive_failure_threshold
         }
         
-        pub async fn get_cooldown_remaining(&self, last_failure_time: Option<std::time::SystemTime>) -> Option<u64> {
-            if let Some(failure_time) = last_failure_time {
-                let elapsed = failure_time.elapsed().ok()?;
-                let cooldown_seconds = self.cooldown_period_minutes * 60;
-                
-                if elapsed.as_secs() < cooldown_seconds {
-                    Some(cooldown_seconds - elapsed.as_secs())
-                } else {
-                    None // Cooldown period has passed
+        // Left of the cool-down while open
+        pub fn cooldown_remaining_at(&self, now: SystemTime) -> Option<Duration> {
+            if self.state != CircuitState::Open {
+                return None;
+            }
+            let elapsed = now.duration_since(self.opened_at).unwrap_or_default();
+            self.cooldown.checked_sub(elapsed).filter(|remaining| !remaining.is_zero())
+        }
+        
+        pub fn allow_at(&mut self, now: SystemTime) -> (bool, Option<CircuitTransition>) {
+            match self.state {
+                CircuitState::Closed => (true, None),
+                CircuitState::Open if self.cooldown_remaining_at(now).is_none() => {
+                    self.state = CircuitState::HalfOpen;
+                    self.probe_started = Some(now);
+                    (true, Some((CircuitState::Open, CircuitState::HalfOpen)))
                 }
-            } else {
-                None
+                CircuitState::Open => (false, None),
+                CircuitState::HalfOpen => match self.probe_started {
+                    Some(started) if now.duration_since(started).unwrap_or_default() < self.cooldown => (false, None),
+                    _ => {
+                        self.probe_started = Some(now);
+                        (true, None)
+                    }
+                },
+            }
+        }
+        
+        // `consecutive_failures` includes this failure. A failed probe reopens the breaker
+        pub fn record_failure_at(&mut self, consecutive_failures: u32, now: SystemTime) -> Option<CircuitTransition> {
+            match self.state {
+                CircuitState::Closed if self.should_break_circuit(consecutive_failures) => {
+                    self.open(now);
+                    Some((CircuitState::Closed, CircuitState::Open))
+                }
+                CircuitState::HalfOpen => {
+                    self.open(now);
+                    Some((CircuitState::HalfOpen, CircuitState::Open))
+                }
+                _ => None,
+            }
+        }
+        
+        // Any success ends the streak, so the breaker closes whether it was probing or not
+        pub fn record_success(&mut self) -> Option<CircuitTransition> {
+            let previous = self.state;
+            self.reset();
+            (previous != CircuitState::Closed).then_some((previous, CircuitState::Closed))
+        }
+        
+        pub fn reset(&mut self) {
+            self.state = CircuitState::Closed;
+            self.probe_started = None;
+        }
+        
+        fn open(&mut self, now: SystemTime) {
+            self.state = CircuitState::Open;
+            self.opened_at = now;
+            self.probe_started = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_low_balance_pauses_operations() {
+        let risk_manager = RiskManager::new(&Config::default()).unwrap();
+        risk_manager.initialize_balance(0.1).await; // Below the 0.5 SOL default threshold
+
+        assert!(risk_manager.check_pause_state().await);
+
+        risk_manager.initialize_balance(2.0).await;
+        assert!(!risk_manager.check_pause_state().await);
+    }
+
+    #[tokio::test]
+    async fn test_active_pauses_list_every_reason() {
+        // Drawdown halt off so only the minimum balance reacts to the drop
+        let risk_manager = RiskManager::with_limits(RiskLimits { max_drawdown_percent: 0.0, ..limits(1.0, 0) });
+        risk_manager.initialize_balance(2.0).await;
+        let now = SystemTime::now();
+        assert!(risk_manager.active_pauses_at(now).await.is_empty());
+
+        risk_manager.kill_switch().engage();
+        for _ in 0..3 {
+            risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, now).await;
+        }
+        risk_manager.initialize_balance(0.1).await;
+        let pauses = risk_manager.active_pauses_at(now).await;
+        assert_eq!(pauses.len(), 3);
+        assert!(pauses[0].starts_with("kill switch engaged"));
+        assert!(pauses[1].starts_with("balance 0.1000 SOL"));
+        assert_eq!(pauses[2], "Sandwich disabled");
+
+        // The strategy cooldown is over an hour later
+        risk_manager.kill_switch().release();
+        risk_manager.initialize_balance(2.0).await;
+        assert!(risk_manager.active_pauses_at(now + Duration::from_secs(3601)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pause_state_change_reported_once() {
+        let risk_manager = RiskManager::new(&Config::default()).unwrap();
+
+        assert!(risk_manager.update_pause_state(true).await);
+        assert!(!risk_manager.update_pause_state(true).await);
+        assert!(risk_manager.update_pause_state(false).await);
+        assert!(!risk_manager.update_pause_state(false).await);
+    }
+
+    fn limits(daily_spending_limit: f64, daily_reset_utc_offset_minutes: i32) -> RiskLimits {
+        RiskLimits {
+            global_loss_per_bundle: 0.01,
+            global_daily_spending_limit: daily_spending_limit,
+            max_consecutive_failures: 5,
+            min_balance_threshold: 0.5,
+            max_strategy_failures: 3,
+            strategy_disable_secs: 3600,
+            session_timeout_minutes: 0,
+            max_exposure_per_token_sol: 0.5,
+            daily_reset_utc_offset_minutes,
+            token_exposure_window_secs: 3600,
+            max_drawdown_percent: 20.0,
+            drawdown_cooloff_secs: 3600,
+            drawdown_resume_percent: 10.0,
+            circuit_breaker_cooldown_secs: 600,
+            high_water_mark_max_age_secs: 86_400,
+        }
+    }
+
+    // Next midnight in a zone `offset_minutes` east of UTC, as a clock reading
+    fn next_midnight(offset_minutes: i32) -> SystemTime {
+        let offset = offset_minutes as i64 * 60;
+        let now = unix_secs(SystemTime::now()) as i64;
+        let midnight = ((now + offset).div_euclid(86_400) + 1) * 86_400 - offset;
+        UNIX_EPOCH + Duration::from_secs(midnight as u64)
+    }
+
+    #[tokio::test]
+    async fn test_daily_spending_resets_at_midnight() {
+        let risk_manager = RiskManager::with_limits(limits(1.0, 0));
+        risk_manager.initialize_balance(5.0).await;
+        let midnight = next_midnight(0);
+        let hour = Duration::from_secs(3600);
+
+        risk_manager.record_successful_operation_at(-0.9, midnight - hour).await;
+        let _ = risk_manager.record_failed_operation().await;
+        assert!(matches!(
+            risk_manager.should_allow_operation_at(0.2, midnight - hour / 2).await,
+            Err(RiskError::DailySpendingLimitExceeded)
+        ));
+        assert!(risk_manager.check_daily_limits_at(0.2, midnight - Duration::from_secs(1)).await.is_err());
+
+        // Past midnight the budget is whole again and the day that ended is summarized
+        assert!(risk_manager.should_allow_operation_at(0.2, midnight + hour).await.is_ok());
+        assert_eq!(risk_manager.get_risk_metrics().await.daily_spending, 0.0);
+        let summaries: Vec<RiskEvent> = risk_manager.get_recent_risk_events(60).await.into_iter()
+            .filter(|event| matches!(event.event_type, RiskEventType::DailySummary))
+            .collect();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].value, Some(0.9));
+        assert!(summaries[0].details.contains("over 1 operations, 1 failed"), "{}", summaries[0].details);
+
+        // Later checks on the same day don't summarize again
+        assert!(risk_manager.check_daily_limits_at(0.2, midnight + hour * 2).await.is_ok());
+        let summary_count = risk_manager.get_recent_risk_events(60).await.iter()
+            .filter(|event| matches!(event.event_type, RiskEventType::DailySummary))
+            .count();
+        assert_eq!(summary_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reset_boundary_follows_the_configured_offset() {
+        // UTC+2: the day turns over at 22:00 UTC
+        let midnight = next_midnight(120);
+        assert_eq!(unix_secs(midnight) % 86_400, 22 * 3600);
+        let hour = Duration::from_secs(3600);
+
+        let local = RiskManager::with_limits(limits(1.0, 120));
+        let utc = RiskManager::with_limits(limits(1.0, 0));
+        for risk_manager in [&local, &utc] {
+            risk_manager.initialize_balance(5.0).await;
+            risk_manager.record_successful_operation_at(-0.9, midnight - hour).await;
+        }
+
+        // 23:00 UTC is already tomorrow in UTC+2 but still today in UTC
+        assert!(local.check_daily_limits_at(0.2, midnight + hour).await.is_ok());
+        assert!(utc.check_daily_limits_at(0.2, midnight + hour).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_exposure_accumulates_and_ages_out() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0)); // 0.5 SOL per token, 1h window
+        let start = SystemTime::now();
+        let minute = Duration::from_secs(60);
+
+        risk_manager.record_token_exposure_at("MintA", 0.2, start).await;
+        risk_manager.record_token_exposure_at("MintA", 0.2, start + minute * 30).await;
+        assert!((risk_manager.get_token_exposure_at("MintA", start + minute * 30).await - 0.4).abs() < 1e-9);
+        assert!(risk_manager.check_token_exposure_at("MintA", 0.1, start + minute * 30).await.is_ok());
+        assert!(matches!(
+            risk_manager.check_token_exposure_at("MintA", 0.2, start + minute * 30).await,
+            Err(RiskError::TokenExposureExceeded(mint)) if mint == "MintA"
+        ));
+        // Other mints have their own budget
+        assert!(risk_manager.check_token_exposure_at("MintB", 0.5, start + minute * 30).await.is_ok());
+
+        // The first commitment leaves the window after an hour, the second half an hour later
+        assert!((risk_manager.get_token_exposure_at("MintA", start + minute * 60).await - 0.2).abs() < 1e-9);
+        assert!(risk_manager.check_token_exposure_at("MintA", 0.2, start + minute * 60).await.is_ok());
+        assert_eq!(risk_manager.get_token_exposure_at("MintA", start + minute * 90).await, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_round_trips_release_exposure_and_reservations_check_it() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0));
+        risk_manager.initialize_balance(5.0).await;
+        risk_manager.record_token_exposure("MintA", 0.3).await;
+        risk_manager.record_token_exposure("MintA", 0.15).await;
+
+        assert!(matches!(
+            risk_manager.try_reserve_exposure(&[WSOL_MINT, "MintA"], 0.1).await,
+            Err(RiskError::TokenExposureExceeded(_))
+        ));
+        assert!((risk_manager.get_token_exposure("MintA").await - 0.45).abs() < 1e-9);
+        // SOL itself is never capped
+        assert!(risk_manager.try_reserve_exposure(&[WSOL_MINT, "MintB"], 0.4).await.is_ok());
+        risk_manager.release_tokens_exposure(&[WSOL_MINT, "MintB"], 0.4).await;
+
+        // Released oldest first: the 0.3 commitment goes and 0.05 of the next one
+        risk_manager.release_token_exposure("MintA", 0.35).await;
+        assert!((risk_manager.get_token_exposure("MintA").await - 0.1).abs() < 1e-9);
+        assert!(risk_manager.try_reserve_exposure(&[WSOL_MINT, "MintA"], 0.1).await.is_ok());
+        assert!((risk_manager.get_token_exposure("MintA").await - 0.2).abs() < 1e-9);
+        risk_manager.release_token_exposure("MintA", 1.0).await;
+        assert_eq!(risk_manager.get_token_exposure("MintA").await, 0.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_reservations_never_exceed_the_cap() {
+        let risk_manager = Arc::new(RiskManager::with_limits(limits(10.0, 0))); // 0.5 SOL per token
+        let reservations = (0..16).map(|_| {
+            let risk_manager = Arc::clone(&risk_manager);
+            tokio::spawn(async move { risk_manager.try_reserve_exposure(&[WSOL_MINT, "MintA"], 0.125).await.is_ok() })
+        });
+        let granted = futures::future::join_all(reservations).await.into_iter().filter(|granted| *granted.as_ref().unwrap()).count();
+
+        assert_eq!(granted, 4);
+        assert_eq!(risk_manager.get_token_exposure("MintA").await, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_drawdown_halts_trading_until_cooled_off_and_recovered() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0)); // Halt past 20%, resume at 10% after 1h
+        risk_manager.initialize_balance(10.0).await;
+        let start = SystemTime::now();
+        let minute = Duration::from_secs(60);
+
+        // Peak at 12 SOL; 9.7 is a 19.2% drawdown, 9.5 is 20.8%
+        for (i, balance) in [11.0, 12.0, 10.5, 9.7].into_iter().enumerate() {
+            assert!(risk_manager.update_balance_at(balance, start + minute * i as u32).await.is_ok(), "{}", balance);
+        }
+        assert!(!risk_manager.should_pause_operations().await);
+        assert!(matches!(risk_manager.update_balance_at(9.5, start + minute * 4).await, Err(RiskError::DrawdownHalt(_))));
+        assert!(risk_manager.should_pause_operations().await);
+        assert!(matches!(risk_manager.should_allow_operation(0.01).await, Err(RiskError::DrawdownHalt(_))));
+        let halts = risk_manager.get_recent_risk_events(60).await.into_iter()
+            .filter(|event| matches!(event.event_type, RiskEventType::DrawdownHalt))
+            .count();
+        assert_eq!(halts, 1);
+
+        // Recovered but still cooling off
+        assert!(risk_manager.update_balance_at(11.5, start + minute * 30).await.is_err());
+        // Cooled off but not recovered enough (12.5% down)
+        assert!(risk_manager.update_balance_at(10.5, start + minute * 65).await.is_err());
+        // Both
+        assert!(risk_manager.update_balance_at(11.0, start + minute * 70).await.is_ok());
+        assert!(!risk_manager.should_pause_operations().await);
+        assert!(!risk_manager.get_risk_metrics().await.drawdown_halted);
+    }
+
+    #[tokio::test]
+    async fn test_reset_lifts_a_drawdown_halt() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0));
+        risk_manager.initialize_balance(10.0).await;
+        assert!(risk_manager.update_balance(7.0).await.is_err());
+        assert!(risk_manager.is_drawdown_halted().await);
+
+        risk_manager.reset_risk_state().await;
+        assert!(!risk_manager.is_drawdown_halted().await);
+        // Measured from the balance at the reset
+        assert_eq!(risk_manager.drawdown_percent().await, 0.0);
+        assert!(risk_manager.update_balance(6.0).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_failures_trip_the_circuit_breaker() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0)); // 5 failures, 600s cool-down
+        risk_manager.initialize_balance(5.0).await;
+        let start = SystemTime::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        for _ in 0..4 {
+            assert!(risk_manager.record_failed_operation_at(start).await.is_ok());
+        }
+        assert_eq!(risk_manager.circuit_state().await, CircuitState::Closed);
+        assert!(risk_manager.record_failed_operation_at(start).await.is_err());
+        assert_eq!(risk_manager.circuit_state().await, CircuitState::Open);
+
+        // Executions are held back, but notifications keep being evaluated
+        assert!(matches!(risk_manager.should_allow_operation_at(0.01, at(599)).await, Err(RiskError::MaxConsecutiveFailures)));
+        assert!(!risk_manager.should_pause_operations().await);
+
+        // Past the cool-down one probe goes through; it fails and the breaker reopens
+        assert!(risk_manager.should_allow_operation_at(0.01, at(600)).await.is_ok());
+        assert!(risk_manager.should_allow_operation_at(0.01, at(600)).await.is_err());
+        assert_eq!(risk_manager.get_risk_metrics().await.circuit_state, CircuitState::HalfOpen);
+        let _ = risk_manager.record_failed_operation_at(at(601)).await;
+        assert!(risk_manager.should_allow_operation_at(0.01, at(1200)).await.is_err());
+
+        // The next probe lands and executions resume with a clean streak
+        assert!(risk_manager.should_allow_operation_at(0.01, at(1201)).await.is_ok());
+        risk_manager.record_successful_operation_at(-0.01, at(1202)).await;
+        assert_eq!(risk_manager.circuit_state().await, CircuitState::Closed);
+        assert!(risk_manager.should_allow_operation_at(0.01, at(1202)).await.is_ok());
+        assert!(risk_manager.should_allow_operation_at(0.01, at(1202)).await.is_ok());
+    }
+
+    #[test]
+    fn test_lost_circuit_probe_is_replaced_after_another_cool_down() {
+        let start = SystemTime::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut breaker = CircuitBreaker::new(&limits(10.0, 0));
+        assert_eq!(breaker.record_failure_at(5, start), Some((CircuitState::Closed, CircuitState::Open)));
+        assert_eq!(breaker.cooldown_remaining_at(at(100)), Some(Duration::from_secs(500)));
+
+        assert_eq!(breaker.allow_at(at(600)), (true, Some((CircuitState::Open, CircuitState::HalfOpen))));
+        // The probe was held back by another check and never submitted
+        assert_eq!(breaker.allow_at(at(1199)), (false, None));
+        assert_eq!(breaker.allow_at(at(1200)), (true, None));
+
+        // A zero threshold turns the breaker off
+        let mut disabled = CircuitBreaker::new(&RiskLimits { max_consecutive_failures: 0, ..limits(10.0, 0) });
+        assert_eq!(disabled.record_failure_at(100, start), None);
+        assert!(disabled.allow_at(start).0);
+    }
+
+    #[tokio::test]
+    async fn test_disable_window_expires_into_a_fresh_streak() {
+        let risk_manager = RiskManager::with_limits(limits(10.0, 0)); // 3 failures disable for an hour
+        let start = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+
+        for _ in 0..2 {
+            risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, start).await;
+        }
+        // A landed execution ends the streak, so two more failures don't disable it
+        risk_manager.record_strategy_success(&MevStrategyType::Sandwich).await;
+        for _ in 0..2 {
+            risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, start).await;
+        }
+        assert!(risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, start).await.is_ok());
+
+        risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, start).await;
+        assert!(matches!(
+            risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, start + hour - Duration::from_secs(1)).await,
+            Err(RiskError::StrategyDisabled(_))
+        ));
+        assert_eq!(risk_manager.get_risk_metrics().await.active_strategy_failures, 1);
+
+        // Re-enabled at the boundary with a clean count: one more failure doesn't disable it again
+        assert!(risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, start + hour).await.is_ok());
+        risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, start + hour).await;
+        assert!(risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, start + hour).await.is_ok());
+        // Other strategies were never affected
+        assert!(risk_manager.strategy_allowed_at(&MevStrategyType::Arbitrage, start).await.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_racing_the_expiry_boundary_loses_no_updates() {
+        for _ in 0..200 {
+            let risk_manager = Arc::new(RiskManager::with_limits(limits(10.0, 0)));
+            let start = SystemTime::now();
+            for _ in 0..3 {
+                risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, start).await;
             }
+            let expired = start + Duration::from_secs(3600);
+
+            // One task finds the window over and re-enables while the other records a new streak
+            let checker = {
+                let risk_manager = Arc::clone(&risk_manager);
+                tokio::spawn(async move { risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, expired).await })
+            };
+            let failer = {
+                let risk_manager = Arc::clone(&risk_manager);
+                tokio::spawn(async move {
+                    for _ in 0..3 {
+                        risk_manager.record_strategy_failure_at(&MevStrategyType::Sandwich, expired).await;
+                    }
+                })
+            };
+            let _ = checker.await.unwrap();
+            failer.await.unwrap();
+
+            // Whichever ran first, all three failures count and the new disable stands
+            assert_eq!(risk_manager.strategy_failures.read().await["Sandwich"].failure_count, 3);
+            assert!(matches!(
+                risk_manager.strategy_allowed_at(&MevStrategyType::Sandwich, expired).await,
+                Err(RiskError::StrategyDisabled(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_risk_state_survives_a_restart_mid_day() {
+        let path = std::env::temp_dir().join(format!("risk_controls_restart_{}.json", std::process::id())).to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let risk_manager = RiskManager::new(&Config::default()).unwrap().with_state_file(&path);
+        risk_manager.initialize_balance(3.0).await;
+        risk_manager.update_balance(4.0).await.unwrap();
+        risk_manager.update_balance(3.5).await.unwrap();
+        risk_manager.record_successful_operation(-1.25).await;
+        let _ = risk_manager.record_failed_operation().await;
+        for _ in 0..3 {
+            risk_manager.record_strategy_failure(&MevStrategyType::Sandwich).await;
+        }
+        drop(risk_manager); // Killed before any periodic or shutdown flush
+
+        let restarted = RiskManager::new(&Config::default()).unwrap().with_state_file(&path);
+        restarted.initialize_balance(3.5).await;
+        let metrics = restarted.get_risk_metrics().await;
+        assert_eq!(metrics.daily_spending, 1.25);
+        assert_eq!(metrics.consecutive_failures, 1);
+        assert_eq!(metrics.active_strategy_failures, 1);
+        assert_eq!(restarted.balance_tracker.read().await.high_water_mark, 4.0);
+        assert!(matches!(
+            restarted.should_allow_strategy(&MevStrategyType::Sandwich, 0.01).await,
+            Err(RiskError::StrategyDisabled(_))
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_counters_are_saved_as_they_change_and_the_rest_by_the_flush() {
+        let path = std::env::temp_dir().join(format!("risk_controls_dirty_{}.json", std::process::id())).to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&path);
+
+        // A new high-water mark waits for the flush
+        let risk_manager = RiskManager::new(&Config::default()).unwrap().with_state_file(&path);
+        risk_manager.initialize_balance(3.0).await;
+        assert!(!std::path::Path::new(&path).exists());
+        risk_manager.flush_state_if_dirty().await.unwrap();
+        assert_eq!(RiskState::load(&path).unwrap().balance_high_water_mark, 3.0);
+
+        // Spend and failures are on disk as soon as they're recorded
+        risk_manager.record_successful_operation(-0.5).await;
+        assert_eq!(RiskState::load(&path).unwrap().daily_spent, 0.5);
+        let _ = risk_manager.record_failed_operation().await;
+        assert_eq!(RiskState::load(&path).unwrap().consecutive_failures, 1);
+        risk_manager.record_strategy_failure(&MevStrategyType::Sandwich).await;
+        assert_eq!(RiskState::load(&path).unwrap().strategy_failures[0].failure_count, 1);
+
+        // Nothing changed since: no write
+        std::fs::remove_file(&path).unwrap();
+        risk_manager.flush_state_if_dirty().await.unwrap();
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_stale_high_water_mark_is_not_restored() {
+        let path = std::env::temp_dir().join(format!("risk_controls_hwm_{}.json", std::process::id())).to_string_lossy().to_string();
+        let now = unix_secs(SystemTime::now());
+        let mut config = Config::default();
+        config.risk.high_water_mark_max_age_secs = 3600;
+        let mut state = RiskState::new(RiskLimits::from_config(&config.risk).budget_day(SystemTime::now()));
+        state.balance_high_water_mark = 10.0;
+
+        // Set half an hour ago: restored, and a 6 SOL balance is in drawdown
+        state.balance_high_water_mark_at = Some(now - 1800);
+        state.save(&path).unwrap();
+        let restarted = RiskManager::new(&config).unwrap().with_state_file(&path);
+        restarted.initialize_balance(6.0).await;
+        assert_eq!(restarted.balance_tracker.read().await.high_water_mark, 10.0);
+        assert!(restarted.is_drawdown_halted().await);
+
+        // Set two hours ago, or never stamped: the drawdown is measured from the current balance
+        for set_at in [Some(now - 7200), None] {
+            state.balance_high_water_mark_at = set_at;
+            state.save(&path).unwrap();
+            let restarted = RiskManager::new(&config).unwrap().with_state_file(&path);
+            restarted.initialize_balance(6.0).await;
+            assert_eq!(restarted.balance_tracker.read().await.high_water_mark, 6.0);
+            assert!(!restarted.is_drawdown_halted().await);
         }
+        let _ = std::fs::remove_file(&path);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_route_losses_are_split_across_its_pools() {
+        let risk_manager = RiskManager::new(&Config::default()).unwrap()
+            .with_pool_blacklist(PoolBlacklist::new(PoolBlacklistConfig { max_net_loss: 0.05, failure_window: 0, ..PoolBlacklistConfig::default() }));
+        let route = vec!["pool_a".to_string(), "pool_b".to_string()];
+
+        // 0.08 SOL lost on a two-leg route is 0.04 per pool: under the limit
+        risk_manager.record_pool_outcome(&route, -0.08, true).await;
+        assert!(!risk_manager.pool_blacklist().is_blacklisted("pool_a").await);
+
+        // Another 0.02 on pool_a alone puts it over
+        risk_manager.record_pool_outcome(&route[..1], -0.02, true).await;
+        assert!(risk_manager.pool_blacklist().is_blacklisted("pool_a").await);
+        assert!(!risk_manager.pool_blacklist().is_blacklisted("pool_b").await);
+        let events = risk_manager.get_recent_risk_events(60).await;
+        assert_eq!(events.iter().filter(|event| matches!(event.event_type, RiskEventType::PoolBlacklisted)).count(), 1);
+    }
+}
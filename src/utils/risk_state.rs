@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use crate::logging::Logger;
+use crate::utils::mev_strategies::MevStrategyType;
+use crate::utils::tip_history::now_unix;
+
+const FILE_VERSION: u32 = 1;
+const SECS_PER_DAY: u64 = 86_400;
+
+// Days since the Unix epoch in a zone `offset_secs` east of UTC, so the day boundary is that
+// zone's midnight; an offset of 0 resets at UTC midnight
+pub fn budget_day(unix_secs: u64, offset_secs: i64) -> u64 {
+    (unix_secs as i64 + offset_secs).div_euclid(SECS_PER_DAY as i64).max(0) as u64
+}
+
+// Calendar date (YYYY-MM-DD) of a budget day, for logs and risk events
+pub fn day_label(day: u64) -> String {
+    // Civil-from-days: days since 1970-01-01 to a proleptic Gregorian date
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedStrategyFailure {
+    pub strategy: MevStrategyType,
+    pub failure_count: u32,
+    pub disabled_until: Option<u64>, // Unix seconds
+}
+
+// RiskManager's safety accounting, so a restart (or a crash loop) picks up where the last process
+// left off instead of with a fresh daily budget. Spend, failure counters and drawdown halts are
+// written as they change; the high-water mark can lag by one flush interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskState {
+    version: u32,
+    #[serde(alias = "utc_day")]
+    pub day: u64, // Budget day the daily counters below belong to
+    pub daily_spent: f64,
+    #[serde(default)]
+    pub daily_operations: u32,
+    #[serde(default)]
+    pub daily_failures: u32,
+    pub consecutive_failures: u32,
+    pub strategy_failures: Vec<PersistedStrategyFailure>,
+    pub balance_high_water_mark: f64,
+    #[serde(default)]
+    pub balance_high_water_mark_at: Option<u64>, // Unix seconds the mark was set
+    #[serde(default)]
+    pub drawdown_halted_since: Option<u64>, // Unix seconds; the halt outlives restarts
+}
+
+impl RiskState {
+    pub fn new(day: u64) -> Self {
+        Self {
+            version: FILE_VERSION,
+            day,
+            daily_spent: 0.0,
+            daily_operations: 0,
+            daily_failures: 0,
+            consecutive_failures: 0,
+            strategy_failures: Vec::new(),
+            balance_high_water_mark: 0.0,
+            balance_high_water_mark_at: None,
+            drawdown_halted_since: None,
+        }
+    }
+
+    // A missing file is a fresh start. An unreadable one is moved aside (so it can be looked at
+    // and isn't overwritten) and also starts fresh; neither stops startup
+    pub fn load(path: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<RiskState>(&contents) {
+            Ok(state) if state.version == FILE_VERSION => Some(state),
+            Ok(state) => {
+                quarantine(path, "risk state", &format!("unsupported version {}", state.version));
+                None
+            }
+            Err(e) => {
+                quarantine(path, "risk state", &e.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize risk state: {}", e))?;
+        crate::utils::atomic_write(path, json.as_bytes())
+            .map_err(|e| format!("Failed to write risk state to {}: {}", path, e).into())
+    }
+
+    // Drops what belongs to an earlier day: the spend and the failure counters start over, while
+    // disable windows that are still running and the high-water mark carry on
+    pub fn for_day(mut self, today: u64, now: u64) -> Self {
+        if self.day != today {
+            self.day = today;
+            self.daily_spent = 0.0;
+            self.daily_operations = 0;
+            self.daily_failures = 0;
+            self.consecutive_failures = 0;
+            for failure in &mut self.strategy_failures {
+                failure.failure_count = 0;
+            }
+        }
+        self.strategy_failures.retain(|failure| failure.failure_count > 0 || failure.disabled_until.is_some_and(|until| until > now));
+        self
+    }
+}
+
+// Moves an unreadable state file aside; `what` names it in the log line
+pub fn quarantine(path: &str, what: &str, reason: &str) {
+    let quarantined = format!("{}.corrupt-{}", path, now_unix());
+    match std::fs::rename(path, &quarantined) {
+        Ok(_) => Logger::error_occurred(&format!("Ignoring corrupt {} {} ({}), moved it to {}", what, path, reason, quarantined)),
+        Err(e) => Logger::error_occurred(&format!("Ignoring corrupt {} {} ({}), could not move it aside: {}", what, path, reason, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("risk_state_{}_{}.json", name, std::process::id())).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_corrupt_file_is_quarantined() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        assert!(RiskState::load(&path).is_none());
+        assert!(!std::path::Path::new(&path).exists());
+
+        let dir = std::env::temp_dir();
+        let prefix = format!("{}.corrupt-", std::path::Path::new(&path).file_name().unwrap().to_string_lossy());
+        let quarantined: Vec<_> = std::fs::read_dir(&dir).unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        let _ = std::fs::remove_file(quarantined[0].path());
+    }
+
+    #[test]
+    fn test_previous_day_counters_are_discarded() {
+        let day = 20_000;
+        let now = day * SECS_PER_DAY + 60;
+        let mut state = RiskState::new(day - 1);
+        state.daily_spent = 4.0;
+        state.consecutive_failures = 2;
+        state.balance_high_water_mark = 12.0;
+        state.strategy_failures = vec![
+            PersistedStrategyFailure { strategy: MevStrategyType::Sandwich, failure_count: 3, disabled_until: Some(now + 600) },
+            PersistedStrategyFailure { strategy: MevStrategyType::Snipe, failure_count: 1, disabled_until: None },
+        ];
+
+        // Same day: kept as is
+        assert_eq!(state.clone().for_day(day - 1, now).daily_spent, 4.0);
+
+        let today = state.for_day(day, now);
+        assert_eq!(today.day, day);
+        assert_eq!(today.daily_spent, 0.0);
+        assert_eq!(today.consecutive_failures, 0);
+        assert_eq!(today.balance_high_water_mark, 12.0);
+        // The running disable window survives, the stale counter doesn't
+        assert_eq!(today.strategy_failures, vec![
+            PersistedStrategyFailure { strategy: MevStrategyType::Sandwich, failure_count: 0, disabled_until: Some(now + 600) },
+        ]);
+    }
+
+    #[test]
+    fn test_budget_day_follows_the_configured_offset() {
+        // 2024-03-01 23:30 UTC
+        let late_evening = 1_709_335_800;
+        assert_eq!(day_label(budget_day(late_evening, 0)), "2024-03-01");
+        // Already the next day an hour east of UTC, still the same day five hours west
+        assert_eq!(day_label(budget_day(late_evening, 3600)), "2024-03-02");
+        assert_eq!(day_label(budget_day(late_evening, -5 * 3600)), "2024-03-01");
+        assert_eq!(day_label(0), "1970-01-01");
+        assert_eq!(day_label(budget_day(951_782_400, 0)), "2000-02-29");
+    }
+}
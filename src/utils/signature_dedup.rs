@@ -0,0 +1,112 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery {
+    First,
+    Duplicate {
+        first_endpoint: String,
+        behind: Duration, // How long after the first delivery this one arrived
+    },
+}
+
+struct DedupState {
+    seen: HashMap<String, (Instant, String)>, // signature -> (first seen, endpoint)
+    order: VecDeque<String>,
+}
+
+// Remembers recently seen signatures so the same transaction delivered by several
+// WebSocket endpoints is only analyzed once
+pub struct SignatureDedupCache {
+    state: Arc<Mutex<DedupState>>,
+    capacity: usize,
+}
+
+impl SignatureDedupCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(DedupState {
+                seen: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub async fn observe(&self, signature: &str, endpoint: &str) -> Delivery {
+        self.observe_at(signature, endpoint, Instant::now()).await
+    }
+
+    pub async fn observe_at(&self, signature: &str, endpoint: &str, now: Instant) -> Delivery {
+        let mut state = self.state.lock().await;
+
+        if let Some((first_seen, first_endpoint)) = state.seen.get(signature) {
+            return Delivery::Duplicate {
+                first_endpoint: first_endpoint.clone(),
+                behind: now.saturating_duration_since(*first_seen),
+            };
+        }
+
+        state.seen.insert(signature.to_string(), (now, endpoint.to_string()));
+        state.order.push_back(signature.to_string());
+
+        // Evict oldest signatures once over capacity
+        while state.order.len() > self.capacity {
+            if let Some(oldest) = state.order.pop_front() {
+                state.seen.remove(&oldest);
+            }
+        }
+
+        Delivery::First
+    }
+
+    pub async fn len(&self) -> usize {
+        self.state.lock().await.seen.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.state.lock().await.seen.is_empty()
+    }
+}
+
+impl Clone for SignatureDedupCache {
+    fn clone(&self) -> Self {
+        SignatureDedupCache {
+            state: Arc::clone(&self.state),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reports_first_and_duplicate_deliveries() {
+        let cache = SignatureDedupCache::new(100);
+        let start = Instant::now();
+
+        assert_eq!(cache.observe_at("sig1", "helius", start).await, Delivery::First);
+        assert_eq!(
+            cache.observe_at("sig1", "triton", start + Duration::from_millis(40)).await,
+            Delivery::Duplicate {
+                first_endpoint: "helius".to_string(),
+                behind: Duration::from_millis(40),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evicts_oldest_signatures_over_capacity() {
+        let cache = SignatureDedupCache::new(2);
+        cache.observe("sig1", "a").await;
+        cache.observe("sig2", "a").await;
+        cache.observe("sig3", "a").await;
+
+        assert_eq!(cache.len().await, 2);
+        assert_eq!(cache.observe("sig1", "b").await, Delivery::First);
+    }
+}
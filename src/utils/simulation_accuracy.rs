@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use crate::config::SimulationConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationAccuracyConfig {
+    pub window: usize, // Last scored executions the accuracy is averaged over, per key
+    pub min_samples: usize, // No alert before a key has this many
+    pub alert_floor: f64, // Rolling accuracy below which an alert is raised; 0 disables
+}
+
+impl Default for SimulationAccuracyConfig {
+    fn default() -> Self {
+        Self { window: 50, min_samples: 10, alert_floor: 0.7 }
+    }
+}
+
+impl SimulationAccuracyConfig {
+    pub fn from_config(config: &SimulationConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if !(0.0..=1.0).contains(&config.accuracy_alert_floor) {
+            return Err("SIMULATION_ACCURACY_ALERT_FLOOR must be in [0, 1]".into());
+        }
+
+        Ok(Self {
+            window: config.accuracy_window,
+            min_samples: config.accuracy_min_samples,
+            alert_floor: config.accuracy_alert_floor,
+        })
+    }
+}
+
+// One key's rolling accuracy; scope is "type" or "dex"
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccuracyScore {
+    pub scope: &'static str,
+    pub key: String,
+    pub accuracy: f64, // Mean over the window, 0 to 1
+    pub samples: usize,
+}
+
+#[derive(Debug, Default)]
+struct AccuracyState {
+    by_type: HashMap<String, VecDeque<f64>>, // Scores, oldest first
+    by_dex: HashMap<String, VecDeque<f64>>,
+    below_floor: HashSet<(&'static str, String)>, // Keys already alerted on, until they recover
+}
+
+fn mean(scores: &VecDeque<f64>) -> f64 {
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+// How well simulations predict what landed executions realize, per opportunity type and DEX, as
+// the rolling mean of their accuracy scores (see mev_simulation_pipeline::simulation_accuracy).
+// Cheap to clone: clones share the same state
+#[derive(Debug, Clone)]
+pub struct SimulationAccuracy {
+    config: SimulationAccuracyConfig,
+    state: Arc<RwLock<AccuracyState>>,
+}
+
+impl Default for SimulationAccuracy {
+    fn default() -> Self {
+        Self::new(SimulationAccuracyConfig::default())
+    }
+}
+
+impl SimulationAccuracy {
+    pub fn new(config: SimulationAccuracyConfig) -> Self {
+        Self { config, state: Arc::new(RwLock::new(AccuracyState::default())) }
+    }
+
+    pub fn from_config(config: &SimulationConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self::new(SimulationAccuracyConfig::from_config(config)?))
+    }
+
+    pub fn config(&self) -> SimulationAccuracyConfig {
+        self.config
+    }
+
+    // Books one scored execution; returns the keys whose accuracy just dropped below the alert floor
+    pub async fn record(&self, opportunity_type: &str, dex: &str, accuracy: f64) -> Vec<AccuracyScore> {
+        if !accuracy.is_finite() {
+            return Vec::new();
+        }
+        let accuracy = accuracy.clamp(0.0, 1.0);
+        let mut state = self.state.write().await;
+        let mut crossed = Vec::new();
+        for (scope, key) in [("type", opportunity_type), ("dex", dex)] {
+            let windows = if scope == "type" { &mut state.by_type } else { &mut state.by_dex };
+            let scores = windows.entry(key.to_string()).or_default();
+            scores.push_back(accuracy);
+            while scores.len() > self.config.window.max(1) {
+                scores.pop_front();
+            }
+            if scores.len() < self.config.min_samples {
+                continue;
+            }
+            let score = AccuracyScore { scope, key: key.to_string(), accuracy: mean(scores), samples: scores.len() };
+            if score.accuracy < self.config.alert_floor {
+                if state.below_floor.insert((scope, key.to_string())) {
+                    crossed.push(score);
+                }
+            } else {
+                state.below_floor.remove(&(scope, key.to_string()));
+            }
+        }
+        crossed
+    }
+
+    // Every key with at least one score, types first, for metrics
+    pub async fn scores(&self) -> Vec<AccuracyScore> {
+        let state = self.state.read().await;
+        let mut scores = Vec::new();
+        for (scope, windows) in [("type", &state.by_type), ("dex", &state.by_dex)] {
+            let mut keys: Vec<&String> = windows.keys().collect();
+            keys.sort();
+            for key in keys {
+                let window = &windows[key];
+                if !window.is_empty() {
+                    scores.push(AccuracyScore { scope, key: key.clone(), accuracy: mean(window), samples: window.len() });
+                }
+            }
+        }
+        scores
+    }
+
+    pub async fn clear(&self) {
+        *self.state.write().await = AccuracyState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_alert_fires_once_per_drop_below_the_floor() {
+        let accuracy = SimulationAccuracy::new(SimulationAccuracyConfig { window: 10, min_samples: 4, alert_floor: 0.7 });
+        for _ in 0..3 {
+            assert!(accuracy.record("Sandwich", "Raydium", 0.0).await.is_empty());
+        }
+        // The fourth score fills the minimum: both keys cross at once
+        let crossed = accuracy.record("Sandwich", "Raydium", 0.0).await;
+        assert_eq!(crossed.iter().map(|score| score.scope).collect::<Vec<_>>(), vec!["type", "dex"]);
+        assert!(accuracy.record("Sandwich", "Raydium", 0.1).await.is_empty());
+
+        // Simulations get it right again; the window fills with accurate executions
+        for _ in 0..10 {
+            assert!(accuracy.record("Sandwich", "Raydium", 0.95).await.is_empty());
+        }
+        let scores = accuracy.scores().await;
+        assert!((scores[0].accuracy - 0.95).abs() < 1e-9);
+        assert_eq!(scores[0].samples, 10);
+
+        // Off again: a fresh alert, and only for the DEX another type keeps accurate
+        for _ in 0..10 {
+            accuracy.record("Arbitrage", "Orca", 0.9).await;
+        }
+        let mut alerts = Vec::new();
+        for _ in 0..10 {
+            alerts.extend(accuracy.record("Sandwich", "Raydium", 0.2).await);
+        }
+        assert_eq!(alerts.iter().map(|score| (score.scope, score.key.as_str())).collect::<Vec<_>>(), vec![
+            ("type", "Sandwich"), ("dex", "Raydium"),
+        ]);
+        assert!(accuracy.record("Sandwich", "Raydium", f64::NAN).await.is_empty());
+        assert_eq!(accuracy.scores().await.len(), 4);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::utils::enhanced_transaction_simulator::OpportunityType;
+use crate::utils::simulation_variance::VarianceConfig;
+
+// How much each component adds to a simulation's confidence score; at most 1 together
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceWeights {
+    pub profit: f64, // Full above strong_profit, half above the type's minimum
+    pub variance: f64, // Full below low_variance, a third up to max_variance
+    pub profitability: f64, // Any positive net profit
+}
+
+impl Default for ConfidenceWeights {
+    fn default() -> Self {
+        Self { profit: 0.4, variance: 0.3, profitability: 0.3 }
+    }
+}
+
+// What MevSimulationPipeline approves: the net profit a simulation has to show per opportunity
+// type, the spread it tolerates and how its confidence score is put together
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    pub min_arbitrage_profit: f64, // SOL
+    pub min_sandwich_profit: f64, // SOL
+    pub min_frontrun_profit: f64, // SOL
+    pub min_backrun_profit: f64, // SOL
+    pub min_other_profit: f64, // SOL; liquidations, snipes and anything else
+    pub max_variance: f64, // Coefficient of variation over the variance scenarios
+    pub strong_profit: f64, // SOL
+    pub low_variance: f64,
+    pub weights: ConfidenceWeights,
+    pub variance: VarianceConfig,
+    pub cache_ttl: Duration, // How long a result is reused for a near-identical opportunity; 0 disables
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            min_arbitrage_profit: 0.01,
+            min_sandwich_profit: 0.01,
+            min_frontrun_profit: 0.01,
+            min_backrun_profit: 0.01,
+            min_other_profit: 0.01,
+            max_variance: 0.1,
+            strong_profit: 0.05,
+            low_variance: 0.01,
+            weights: ConfidenceWeights::default(),
+            variance: VarianceConfig::default(),
+            cache_ttl: Duration::from_millis(500),
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub fn from_config(config: &crate::config::SimulationConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let simulation = Self {
+            min_arbitrage_profit: config.min_arbitrage_profit_sol,
+            min_sandwich_profit: config.min_sandwich_profit_sol,
+            min_frontrun_profit: config.min_frontrun_profit_sol,
+            min_backrun_profit: config.min_backrun_profit_sol,
+            min_other_profit: config.min_other_profit_sol,
+            max_variance: config.max_variance,
+            strong_profit: config.strong_profit_sol,
+            low_variance: config.low_variance,
+            weights: ConfidenceWeights {
+                profit: config.confidence_profit_weight,
+                variance: config.confidence_variance_weight,
+                profitability: config.confidence_profitability_weight,
+            },
+            variance: VarianceConfig::from_config(config)?,
+            cache_ttl: Duration::from_millis(config.cache_ttl_ms),
+        };
+        simulation.validate()?;
+        Ok(simulation)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("SIMULATION_MIN_ARBITRAGE_PROFIT_SOL", self.min_arbitrage_profit),
+            ("SIMULATION_MIN_SANDWICH_PROFIT_SOL", self.min_sandwich_profit),
+            ("SIMULATION_MIN_FRONTRUN_PROFIT_SOL", self.min_frontrun_profit),
+            ("SIMULATION_MIN_BACKRUN_PROFIT_SOL", self.min_backrun_profit),
+            ("SIMULATION_MIN_OTHER_PROFIT_SOL", self.min_other_profit),
+            ("SIMULATION_MAX_VARIANCE", self.max_variance),
+            ("SIMULATION_STRONG_PROFIT_SOL", self.strong_profit),
+            ("SIMULATION_LOW_VARIANCE", self.low_variance),
+            ("SIMULATION_CONFIDENCE_PROFIT_WEIGHT", self.weights.profit),
+            ("SIMULATION_CONFIDENCE_VARIANCE_WEIGHT", self.weights.variance),
+            ("SIMULATION_CONFIDENCE_PROFITABILITY_WEIGHT", self.weights.profitability),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a non-negative number, got {}", name, value));
+            }
+        }
+        if self.low_variance > self.max_variance {
+            return Err(format!(
+                "SIMULATION_LOW_VARIANCE ({}) can't exceed SIMULATION_MAX_VARIANCE ({})",
+                self.low_variance, self.max_variance
+            ));
+        }
+        let total_weight = self.weights.profit + self.weights.variance + self.weights.profitability;
+        if total_weight > 1.0 + 1e-9 {
+            return Err(format!("Simulation confidence weights must sum to at most 1, got {}", total_weight));
+        }
+        Ok(())
+    }
+
+    pub fn min_net_profit(&self, opportunity_type: &OpportunityType) -> f64 {
+        match opportunity_type {
+            OpportunityType::Arbitrage => self.min_arbitrage_profit,
+            OpportunityType::Sandwich => self.min_sandwich_profit,
+            OpportunityType::Frontrun => self.min_frontrun_profit,
+            OpportunityType::Backrun => self.min_backrun_profit,
+            OpportunityType::Liquidation | OpportunityType::Snipe | OpportunityType::Other => self.min_other_profit,
+        }
+    }
+
+    // Clears the type's minimum with a spread no wider than max_variance
+    pub fn is_profitable(&self, opportunity_type: &OpportunityType, net_profit: f64, variance: f64) -> bool {
+        net_profit > self.min_net_profit(opportunity_type) && variance <= self.max_variance
+    }
+
+    // Each component's contribution to the confidence score, keyed "profit", "variance" and
+    // "profitability"; the score is their sum
+    pub fn confidence_breakdown(&self, opportunity_type: &OpportunityType, net_profit: f64, variance: f64) -> HashMap<String, f64> {
+        let profit = if net_profit > self.strong_profit {
+            self.weights.profit
+        } else if net_profit > self.min_net_profit(opportunity_type) {
+            self.weights.profit / 2.0
+        } else {
+            0.0
+        };
+        let variance = if variance < self.low_variance {
+            self.weights.variance
+        } else if variance <= self.max_variance {
+            self.weights.variance / 3.0
+        } else {
+            0.0
+        };
+        let profitability = if net_profit > 0.0 { self.weights.profitability } else { 0.0 };
+        [("profit", profit), ("variance", variance), ("profitability", profitability)]
+            .into_iter()
+            .map(|(component, value)| (component.to_string(), value))
+            .collect()
+    }
+}
+
+pub fn confidence_score(breakdown: &HashMap<String, f64>) -> f64 {
+    breakdown.values().sum::<f64>().min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds_follow_the_config() {
+        let config = SimulationConfig::default();
+        assert!(config.is_profitable(&OpportunityType::Sandwich, 0.02, 0.05));
+        assert!(!config.is_profitable(&OpportunityType::Sandwich, 0.02, 0.2));
+
+        // A stricter sandwich minimum and a looser variance cap
+        let config = SimulationConfig { min_sandwich_profit: 0.03, max_variance: 0.5, ..SimulationConfig::default() };
+        assert!(!config.is_profitable(&OpportunityType::Sandwich, 0.02, 0.05));
+        assert!(config.is_profitable(&OpportunityType::Arbitrage, 0.02, 0.2));
+        assert!(config.is_profitable(&OpportunityType::Sandwich, 0.04, 0.2));
+    }
+
+    #[test]
+    fn test_confidence_breakdown_follows_the_weights() {
+        let config = SimulationConfig::default();
+        let breakdown = config.confidence_breakdown(&OpportunityType::Arbitrage, 0.02, 0.005);
+        assert_eq!(breakdown["profit"], 0.2);
+        assert_eq!(breakdown["variance"], 0.3);
+        assert_eq!(breakdown["profitability"], 0.3);
+        assert!((confidence_score(&breakdown) - 0.8).abs() < 1e-12);
+
+        let config = SimulationConfig {
+            weights: ConfidenceWeights { profit: 0.8, variance: 0.1, profitability: 0.1 },
+            strong_profit: 0.01,
+            ..SimulationConfig::default()
+        };
+        let breakdown = config.confidence_breakdown(&OpportunityType::Arbitrage, 0.02, 0.05);
+        assert_eq!(breakdown["profit"], 0.8);
+        assert!((breakdown["variance"] - 0.1 / 3.0).abs() < 1e-12);
+        assert!((confidence_score(&breakdown) - (0.9 + 0.1 / 3.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_impossible_configs_are_rejected() {
+        assert!(SimulationConfig::default().validate().is_ok());
+        let negative = SimulationConfig { min_backrun_profit: -0.01, ..SimulationConfig::default() };
+        assert!(negative.validate().unwrap_err().contains("SIMULATION_MIN_BACKRUN_PROFIT_SOL"));
+        let heavy = SimulationConfig {
+            weights: ConfidenceWeights { profit: 0.5, variance: 0.3, profitability: 0.3 },
+            ..SimulationConfig::default()
+        };
+        assert!(heavy.validate().unwrap_err().contains("sum to at most 1"));
+        let inverted = SimulationConfig { low_variance: 0.2, max_variance: 0.1, ..SimulationConfig::default() };
+        assert!(inverted.validate().is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::future::Future;
+use std::time::Duration;
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::Value;
+use crate::utils::amm_math;
+use crate::utils::opportunity_evaluator::PoolState;
+use crate::utils::pool_creation::WSOL_MINT;
+use crate::utils::simulation_effects;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConsensusConfig {
+    pub endpoints: usize, // Endpoints running the same simulation; below 2 turns the check off
+    pub tolerance: f64, // Net profits within this fraction of the larger one agree
+    pub deadline: Duration, // Endpoints that haven't answered by then are left out
+    pub disagreement_penalty: f64, // Confidence scores are multiplied by this when endpoints disagree
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: 3,
+            tolerance: 0.1,
+            deadline: Duration::from_millis(150),
+            disagreement_penalty: 0.5,
+        }
+    }
+}
+
+impl ConsensusConfig {
+    pub fn from_config(config: &crate::config::SimulationConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (tolerance, disagreement_penalty) = (config.consensus_tolerance, config.consensus_penalty);
+        if !(0.0..1.0).contains(&tolerance) || !(0.0..=1.0).contains(&disagreement_penalty) {
+            return Err("SIMULATION_CONSENSUS_TOLERANCE must be in [0, 1) and SIMULATION_CONSENSUS_PENALTY in [0, 1]".into());
+        }
+
+        Ok(Self {
+            endpoints: config.consensus_endpoints,
+            tolerance,
+            deadline: Duration::from_millis(config.consensus_deadline_ms),
+            disagreement_penalty,
+        })
+    }
+}
+
+// What one endpoint made of the simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointSimulation {
+    pub endpoint: String,
+    pub net_profit: f64, // SOL
+    pub error: Option<String>, // The program error, when the transaction failed there
+}
+
+// One endpoint's simulateTransaction response for the transaction the opportunity hinges on. Where
+// the transaction goes through it is worth what `net_profit` makes of the account states this
+// endpoint reports after it, in the order they were requested; where it fails it is worth
+// nothing: the bundle built around it wouldn't land
+pub fn endpoint_simulation(endpoint: &str, response: &Value, net_profit: impl FnOnce(&[Value]) -> f64) -> EndpointSimulation {
+    let value = &response["result"]["value"];
+    let error = (!value["err"].is_null()).then(|| simulation_effects::program_error(value));
+    let accounts = value["accounts"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    EndpointSimulation {
+        endpoint: endpoint.to_string(),
+        net_profit: if error.is_none() { net_profit(accounts) } else { 0.0 },
+        error,
+    }
+}
+
+// Raw balance of a jsonParsed token account
+fn token_amount(account: &Value) -> Option<u64> {
+    account["data"]["parsed"]["info"]["tokenAmount"]["amount"].as_str()?.parse().ok()
+}
+
+// What a swap through `pool` leaves to backrun, in SOL. `pool` and `vaults_before` (its vault
+// balances) are read before the swap, `vaults_after` are the vault accounts after it as one
+// endpoint simulated it. The backrun takes the pool back to its price before the swap.
+// None when the vaults aren't token accounts or neither side is wrapped SOL
+pub fn backrun_value(pool: &PoolState, vaults_before: &[u64], vaults_after: &[Value]) -> Option<f64> {
+    if pool.token_a != WSOL_MINT && pool.token_b != WSOL_MINT {
+        return None;
+    }
+    let after = vaults_after.iter().map(token_amount).collect::<Option<Vec<u64>>>()?;
+    let (&[before_a, before_b], &[after_a, after_b]) = (vaults_before, after.as_slice()) else {
+        return None;
+    };
+
+    // The side the swap paid into and what it took out of the other one
+    let (swap_input, paid, received) = if after_a > before_a {
+        (&pool.token_a, after_a - before_a, before_b.saturating_sub(after_b))
+    } else if after_b > before_b {
+        (&pool.token_b, after_b - before_b, before_a.saturating_sub(after_a))
+    } else {
+        return Some(0.0);
+    };
+    let (reserve_paid, reserve_other) = pool.reserves_for_input(swap_input)?;
+    if reserve_paid == 0 {
+        return None;
+    }
+    // The swap's input token priced in ours before the swap
+    let output_price = reserve_other as f64 / reserve_paid as f64;
+    let (displaced_in, displaced_out) = (reserve_other.saturating_sub(received), reserve_paid.saturating_add(paid));
+
+    let amount_in = amm_math::optimal_backrun_amount(displaced_in, displaced_out, pool.fee_rate, output_price);
+    let profit = amm_math::backrun_profit(amount_in, displaced_in, displaced_out, pool.fee_rate, output_price).max(0.0);
+    if swap_input == WSOL_MINT {
+        Some(profit / output_price / LAMPORTS_PER_SOL)
+    } else {
+        Some(profit / LAMPORTS_PER_SOL)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConsensus {
+    pub simulations: Vec<EndpointSimulation>, // The endpoints that answered in time
+    pub disagreement: Option<String>, // What the endpoints differ on; None when they agree
+}
+
+impl SimulationConsensus {
+    pub fn agrees(&self) -> bool {
+        self.disagreement.is_none()
+    }
+}
+
+// Every endpoint has to agree with the first: the same program error or none, and a net profit
+// within `tolerance` of the larger of the two. A single answer agrees with itself
+pub fn compare(simulations: Vec<EndpointSimulation>, tolerance: f64) -> SimulationConsensus {
+    let disagreement = simulations.split_first().and_then(|(first, rest)| {
+        rest.iter().find_map(|other| {
+            if first.error != other.error {
+                Some(format!(
+                    "{} {} but {} {}",
+                    first.endpoint, first.error.as_deref().unwrap_or("succeeded"),
+                    other.endpoint, other.error.as_deref().unwrap_or("succeeded")
+                ))
+            } else if (first.net_profit - other.net_profit).abs() > tolerance * first.net_profit.abs().max(other.net_profit.abs()) {
+                Some(format!(
+                    "{} nets {:.6} SOL but {} nets {:.6} SOL",
+                    first.endpoint, first.net_profit, other.endpoint, other.net_profit
+                ))
+            } else {
+                None
+            }
+        })
+    });
+    SimulationConsensus { simulations, disagreement }
+}
+
+// Runs the endpoints' simulations concurrently and keeps those answered within the deadline;
+// a None is an endpoint that failed to answer at all
+pub async fn race<F>(simulations: Vec<F>, deadline: Duration) -> Vec<EndpointSimulation>
+where
+    F: Future<Output = Option<EndpointSimulation>>,
+{
+    let mut pending: FuturesUnordered<F> = simulations.into_iter().collect();
+    let deadline = tokio::time::Instant::now() + deadline;
+    let mut answered = Vec::with_capacity(pending.len());
+    while let Ok(Some(result)) = tokio::time::timeout_at(deadline, pending.next()).await {
+        answered.extend(result);
+    }
+    answered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ok() -> Value {
+        json!({ "result": { "value": { "err": null, "logs": [], "unitsConsumed": 52_000 } } })
+    }
+
+    #[test]
+    fn test_endpoints_disagree_on_errors_and_profit() {
+        let slippage = json!({ "result": { "value": {
+            "err": { "InstructionError": [2, { "Custom": 6001 }] },
+            "logs": ["Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 failed: custom program error: 0x1771"]
+        }}});
+        let fresh = endpoint_simulation("Helius", &ok(), |_| 0.02);
+        let stale = endpoint_simulation("Drpc", &slippage, |_| 0.02);
+        assert_eq!(stale.net_profit, 0.0);
+        assert!(stale.error.as_deref().unwrap().contains("0x1771"));
+
+        let consensus = compare(vec![fresh.clone(), stale], 0.1);
+        assert!(!consensus.agrees());
+        assert!(consensus.disagreement.unwrap().starts_with("Helius succeeded but Drpc"));
+
+        // Both succeed: 5% apart is within tolerance, 50% isn't
+        let close = EndpointSimulation { endpoint: "Jito".to_string(), net_profit: 0.019, error: None };
+        assert!(compare(vec![fresh.clone(), close], 0.1).agrees());
+        let far = EndpointSimulation { endpoint: "Jito".to_string(), net_profit: 0.01, error: None };
+        assert!(!compare(vec![fresh.clone(), far], 0.1).agrees());
+        assert!(compare(vec![fresh], 0.1).agrees());
+    }
+
+    #[tokio::test]
+    async fn test_slow_endpoints_miss_the_deadline() {
+        let answer = |endpoint: &'static str, delay_ms: u64, response: Option<Value>| async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            response.map(|response| endpoint_simulation(endpoint, &response, |_| 0.02))
+        };
+        let simulations = vec![
+            answer("Helius", 5, Some(ok())),
+            answer("Drpc", 10, None),
+            answer("Jito", 500, Some(json!({ "result": { "value": { "err": "AccountNotFound", "logs": [] } } }))),
+        ];
+        let started = tokio::time::Instant::now();
+        let answered = race(simulations, Duration::from_millis(100)).await;
+        assert!(started.elapsed() < Duration::from_millis(400));
+        // The disagreeing endpoint was too slow to count
+        assert_eq!(answered.iter().map(|simulation| simulation.endpoint.as_str()).collect::<Vec<_>>(), vec!["Helius"]);
+        assert!(compare(answered, 0.1).agrees());
+    }
+
+    fn vault(amount: u64) -> Value {
+        json!({ "data": { "parsed": { "info": { "tokenAmount": { "amount": amount.to_string() } } } } })
+    }
+
+    #[test]
+    fn test_each_endpoint_prices_its_own_pool_state() {
+        // 1,000 SOL against 200,000 USDC
+        let pool = PoolState {
+            pool_address: "Pool".to_string(),
+            dex: "Raydium".to_string(),
+            token_a: WSOL_MINT.to_string(),
+            token_b: "EPjFWDDh7cF3ovXV1DEYsdqk6gXdVHDVGg3bQeXjDwLS".to_string(),
+            reserve_a: 1_000_000_000_000,
+            reserve_b: 200_000_000_000,
+            liquidity: 2_000.0,
+            fee_rate: 0.0025,
+            vaults: vec!["SolVault".to_string(), "UsdcVault".to_string()],
+            last_updated: std::time::SystemTime::now(),
+        };
+        let before = [1_000_000_000_000, 200_000_000_000];
+        let simulated = |sol_in: u64, usdc_out: u64| json!({ "result": { "value": {
+            "err": null, "logs": [],
+            "accounts": [vault(before[0] + sol_in), vault(before[1] - usdc_out)]
+        }}});
+        let value = |accounts: &[Value]| backrun_value(&pool, &before, accounts).unwrap();
+
+        // A victim selling 20 SOL leaves USDC cheap to buy back; untouched vaults leave nothing
+        let untouched = endpoint_simulation("Jito", &simulated(0, 0), value);
+        assert_eq!(untouched.net_profit, 0.0);
+        let fresh = endpoint_simulation("Helius", &simulated(20_000_000_000, 3_912_000_000), value);
+        assert!(fresh.net_profit > 0.0);
+
+        // A node that hands the victim less USDC leaves the pool less displaced and less to backrun
+        let stale = endpoint_simulation("Drpc", &simulated(20_000_000_000, 3_300_000_000), value);
+        assert!(stale.net_profit < fresh.net_profit);
+        assert!(compare(vec![fresh.clone(), endpoint_simulation("Quicknode", &simulated(20_000_000_000, 3_912_000_000), value)], 0.1).agrees());
+        assert!(!compare(vec![fresh, stale], 0.1).agrees());
+
+        // Neither side priced in SOL
+        let unpriced = PoolState { token_a: "Bonk".to_string(), ..pool.clone() };
+        assert_eq!(backrun_value(&unpriced, &before, &[vault(before[0]), vault(before[1])]), None);
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use crate::rpc::types::EncodedTransaction;
+use crate::utils::mev_simulation_pipeline::{TokenBalance, TransactionEffects};
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+const SET_COMPUTE_UNIT_PRICE: u8 = 3; // ComputeBudgetInstruction discriminator
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+// The wallet's associated token account for a mint; None for anything that isn't a pubkey
+pub fn associated_token_address(wallet: &str, mint: &str) -> Option<String> {
+    let wallet = Pubkey::from_str(wallet).ok()?;
+    let mint = Pubkey::from_str(mint).ok()?;
+    let token_program = Pubkey::from_str(TOKEN_PROGRAM_ID).ok()?;
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).ok()?,
+    );
+    Some(address.to_string())
+}
+
+// The accounts a simulation reports on: the wallet first, then its token account for every mint
+// the opportunity trades, wrapped SOL included
+pub fn tracked_accounts(wallet: &str, mints: &[String]) -> Vec<String> {
+    let mut accounts = vec![wallet.to_string()];
+    for token_account in mints.iter().filter_map(|mint| associated_token_address(wallet, mint)) {
+        if !accounts.contains(&token_account) {
+            accounts.push(token_account);
+        }
+    }
+    accounts
+}
+
+// What a signed transaction pays besides its instructions' own transfers
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FeeParameters {
+    pub signatures: u64,
+    pub compute_unit_price: u64, // Micro-lamports per compute unit; 0 without SetComputeUnitPrice
+}
+
+impl FeeParameters {
+    // Base fee plus the priority fee on the units the simulation consumed. The priority fee is
+    // charged on the requested limit, so with a loose limit the real fee is higher
+    pub fn fee_lamports(&self, units_consumed: u64) -> u64 {
+        let priority = (units_consumed as u128 * self.compute_unit_price as u128).div_ceil(1_000_000);
+        self.signatures * LAMPORTS_PER_SIGNATURE + priority as u64
+    }
+}
+
+// None when the bytes aren't a serialized transaction
+pub fn fee_parameters(transaction: &[u8]) -> Option<FeeParameters> {
+    let transaction: VersionedTransaction = bincode::deserialize(transaction).ok()?;
+    let compute_budget = Pubkey::from_str(COMPUTE_BUDGET_PROGRAM_ID).ok()?;
+    let keys = transaction.message.static_account_keys();
+    let compute_unit_price = transaction.message.instructions().iter()
+        .filter(|instruction| keys.get(instruction.program_id_index as usize) == Some(&compute_budget))
+        .find_map(|instruction| match instruction.data.split_first() {
+            Some((&SET_COMPUTE_UNIT_PRICE, price)) => <[u8; 8]>::try_from(price).ok().map(u64::from_le_bytes),
+            _ => None,
+        })
+        .unwrap_or(0);
+    Some(FeeParameters { signatures: transaction.signatures.len() as u64, compute_unit_price })
+}
+
+// Mint and balance of a jsonParsed token account
+fn token_balance(account: &Value) -> Option<(String, TokenBalance)> {
+    let info = &account["data"]["parsed"]["info"];
+    let mint = info["mint"].as_str()?;
+    let raw = info["tokenAmount"]["amount"].as_str()?.parse::<u64>().ok()?;
+    let decimals = u8::try_from(info["tokenAmount"]["decimals"].as_u64()?).ok()?;
+    Some((mint.to_string(), TokenBalance { amount: raw as f64 / 10f64.powi(decimals as i32), decimals }))
+}
+
+// Balance per mint over a wallet's jsonParsed token accounts; a mint held in several accounts is summed
+pub fn token_balances(accounts: &[Value]) -> HashMap<String, TokenBalance> {
+    let mut balances: HashMap<String, TokenBalance> = HashMap::new();
+    for (mint, balance) in accounts.iter().filter_map(token_balance) {
+        balances.entry(mint)
+            .and_modify(|total| total.amount += balance.amount)
+            .or_insert(balance);
+    }
+    balances
+}
+
+// The transaction error, with the failing program's own message from the logs when there is one
+pub fn program_error(value: &Value) -> String {
+    let error = value["err"].to_string();
+    let failure = value["logs"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .rev()
+        .find(|log| log.contains(" failed: "));
+    match failure {
+        Some(log) => format!("{} ({})", error, log),
+        None => error,
+    }
+}
+
+// Effects of one simulated transaction on the tracked accounts: their state before
+// (get_multiple_accounts_parsed) against the state simulateTransaction returns after, both in
+// tracked_accounts order. A failed transaction still pays its fee
+pub fn transaction_effects(response: &Value, pre_accounts: &[Value], fee_parameters: FeeParameters) -> TransactionEffects {
+    let value = &response["result"]["value"];
+    let units_consumed = value["unitsConsumed"].as_u64().unwrap_or(0);
+    // Newer nodes report the fee themselves
+    let fee = value["fee"].as_u64().unwrap_or_else(|| fee_parameters.fee_lamports(units_consumed));
+    let failed = |error: String| TransactionEffects {
+        units_consumed,
+        fees_paid: fee as f64 / LAMPORTS_PER_SOL,
+        ..TransactionEffects::failed(error)
+    };
+
+    if !value["err"].is_null() {
+        return failed(program_error(value));
+    }
+    let post_accounts = value["accounts"].as_array().map(Vec::as_slice).unwrap_or_default();
+    if post_accounts.len() != pre_accounts.len() || pre_accounts.is_empty() {
+        return failed(format!("simulateTransaction returned {} accounts, expected {}", post_accounts.len(), pre_accounts.len()));
+    }
+
+    let lamports = |account: &Value| account["lamports"].as_u64().unwrap_or(0) as f64;
+    let sol_balance_change = (lamports(&post_accounts[0]) - lamports(&pre_accounts[0])) / LAMPORTS_PER_SOL;
+
+    // A token account that doesn't exist on one side holds nothing there
+    let mut token_balance_changes: HashMap<String, f64> = HashMap::new();
+    for (pre, post) in pre_accounts.iter().zip(post_accounts).skip(1) {
+        let (pre, post) = (token_balance(pre), token_balance(post));
+        let Some(mint) = post.as_ref().or(pre.as_ref()).map(|(mint, _)| mint.clone()) else { continue };
+        let change = post.map_or(0.0, |(_, balance)| balance.amount) - pre.map_or(0.0, |(_, balance)| balance.amount);
+        if change != 0.0 {
+            *token_balance_changes.entry(mint).or_insert(0.0) += change;
+        }
+    }
+
+    TransactionEffects {
+        token_balance_changes,
+        sol_balance_change,
+        fees_paid: fee as f64 / LAMPORTS_PER_SOL,
+        units_consumed,
+        success: true,
+        error: None,
+    }
+}
+
+// Effects of each transaction of a simulateBundle run with accounts (see
+// JitoClient::simulate_bundle_with_accounts), in bundle order: each one's tracked accounts just
+// before it against just after it. Legs after a failed one never ran and are failed too
+pub fn bundle_effects(response: &Value, fee_parameters: &[FeeParameters]) -> Vec<TransactionEffects> {
+    let results = response["result"]["value"]["transactionResults"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut effects: Vec<TransactionEffects> = Vec::with_capacity(fee_parameters.len());
+    for (index, fee_parameters) in fee_parameters.iter().enumerate() {
+        let Some(result) = results.get(index) else {
+            let error = match effects.iter().position(|step| !step.success) {
+                Some(failed) => format!("Not executed: transaction {} of the bundle failed", failed),
+                None => "Not executed: the bundle stopped before it".to_string(),
+            };
+            effects.push(TransactionEffects::failed(error));
+            continue;
+        };
+        let pre_accounts = result["preExecutionAccounts"].as_array().map(Vec::as_slice).unwrap_or_default();
+        // Shaped as a simulateTransaction response so it reads like one
+        let simulated = json!({ "result": { "value": {
+            "err": result["err"],
+            "logs": result["logs"],
+            "unitsConsumed": result["unitsConsumed"],
+            "accounts": result["postExecutionAccounts"],
+        }}});
+        effects.push(transaction_effects(&simulated, pre_accounts, *fee_parameters));
+    }
+    effects
+}
+
+// What a landed bundle's transactions (getTransaction) actually did to the wallet, summed over
+// all of them: its lamports, the token balances it owns and the fees it paid as fee payer.
+// Transactions that don't touch the wallet, like a sandwich's victim, add nothing
+pub fn realized_effects(transactions: &[EncodedTransaction], wallet: &str) -> TransactionEffects {
+    let mut realized = TransactionEffects {
+        token_balance_changes: HashMap::new(),
+        sol_balance_change: 0.0,
+        fees_paid: 0.0,
+        units_consumed: 0,
+        success: true,
+        error: None,
+    };
+    for transaction in transactions {
+        let Some(ref meta) = transaction.meta else {
+            return TransactionEffects::failed(format!(
+                "No status metadata for {}", transaction.signature().unwrap_or("transaction")
+            ));
+        };
+        if let Some(ref err) = meta.err {
+            realized.success = false;
+            realized.error.get_or_insert_with(|| err.to_string());
+        }
+        // Lookup-table keys follow the static ones in the balance arrays
+        let keys = transaction.transaction.message.account_keys.iter()
+            .chain(meta.loaded_addresses.iter().flat_map(|loaded| loaded.writable.iter().chain(&loaded.readonly)));
+        if let Some(index) = keys.into_iter().position(|key| key == wallet) {
+            let lamports = |balances: &[u64]| balances.get(index).copied().unwrap_or(0) as f64;
+            realized.sol_balance_change += (lamports(&meta.post_balances) - lamports(&meta.pre_balances)) / LAMPORTS_PER_SOL;
+            if index == 0 {
+                realized.fees_paid += meta.fee as f64 / LAMPORTS_PER_SOL;
+                realized.units_consumed += meta.compute_units_consumed.unwrap_or(0);
+            }
+        }
+        let owned = |balance: &&crate::rpc::types::TokenBalance| balance.owner.as_deref() == Some(wallet);
+        for (balances, sign) in [(&meta.pre_token_balances, -1.0), (&meta.post_token_balances, 1.0)] {
+            for balance in balances.iter().filter(owned) {
+                *realized.token_balance_changes.entry(balance.mint.clone()).or_insert(0.0) += sign * balance.ui_token_amount.ui_amount();
+            }
+        }
+    }
+    realized.token_balance_changes.retain(|_, change| *change != 0.0);
+    realized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::message::Message;
+    use solana_sdk::system_instruction;
+    use solana_sdk::transaction::Transaction;
+
+    const WALLET: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const USDC_MINT: &str = "EPjFWDDh7cF3ovXV1DEYsdqk6gXdVHDVGg3bQeXjDwLS";
+
+    fn token_account(mint: &str, amount: u64, decimals: u64) -> Value {
+        json!({
+            "lamports": 2_039_280,
+            "owner": TOKEN_PROGRAM_ID,
+            "data": { "program": "spl-token", "space": 165, "parsed": { "type": "account", "info": {
+                "mint": mint,
+                "owner": WALLET,
+                "tokenAmount": { "amount": amount.to_string(), "decimals": decimals, "uiAmountString": "" }
+            }}}
+        })
+    }
+
+    #[test]
+    fn test_decodes_a_successful_simulation() {
+        let pre = vec![json!({ "lamports": 2_000_000_000u64, "owner": "11111111111111111111111111111111" }), token_account(USDC_MINT, 100_500_000, 6)];
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": { "context": { "slot": 312_000_000 }, "value": {
+            "err": null,
+            "logs": ["Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 invoke [1]", "Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 success"],
+            "accounts": [
+                { "lamports": 2_012_340_000u64, "owner": "11111111111111111111111111111111", "data": ["", "base64"] },
+                token_account(USDC_MINT, 90_000_000, 6)
+            ],
+            "unitsConsumed": 84_000,
+            "innerInstructions": [],
+            "returnData": null
+        }}});
+
+        let effects = transaction_effects(&response, &pre, FeeParameters { signatures: 1, compute_unit_price: 10_000 });
+        assert!(effects.success);
+        assert_eq!(effects.error, None);
+        assert_eq!(effects.units_consumed, 84_000);
+        assert!((effects.sol_balance_change - 0.01234).abs() < 1e-12);
+        assert!((effects.token_balance_changes[USDC_MINT] + 10.5).abs() < 1e-9);
+        // 5000 base + 84_000 CU at 10_000 micro-lamports
+        assert!((effects.fees_paid - 0.00000584).abs() < 1e-15);
+
+        // A fee the node reports wins over the estimate
+        let mut reported = response.clone();
+        reported["result"]["value"]["fee"] = json!(7_000);
+        assert!((transaction_effects(&reported, &pre, FeeParameters::default()).fees_paid - 0.000007).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_simulation_error_keeps_the_program_message() {
+        let pre = vec![json!({ "lamports": 1_000_000_000u64 })];
+        let response = json!({ "result": { "value": {
+            "err": { "InstructionError": [2, { "Custom": 6001 }] },
+            "logs": [
+                "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc invoke [1]",
+                "Program log: AnchorError occurred. Error Code: SlippageExceeded.",
+                "Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc failed: custom program error: 0x1771"
+            ],
+            "accounts": null,
+            "unitsConsumed": 31_000
+        }}});
+
+        let effects = transaction_effects(&response, &pre, FeeParameters { signatures: 2, compute_unit_price: 0 });
+        assert!(!effects.success);
+        assert_eq!(effects.error.as_deref(), Some(
+            r#"{"InstructionError":[2,{"Custom":6001}]} (Program whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc failed: custom program error: 0x1771)"#
+        ));
+        assert_eq!(effects.sol_balance_change, 0.0);
+        assert!((effects.fees_paid - 0.00001).abs() < 1e-15);
+
+        // Accounts missing from an otherwise clean response fail the step instead of reading as a loss
+        let response = json!({ "result": { "value": { "err": null, "logs": [], "accounts": null, "unitsConsumed": 0 } } });
+        let effects = transaction_effects(&response, &pre, FeeParameters::default());
+        assert!(!effects.success);
+        assert_eq!(effects.error.as_deref(), Some("simulateTransaction returned 0 accounts, expected 1"));
+    }
+
+    #[test]
+    fn test_fee_parameters_and_tracked_accounts() {
+        let payer = Pubkey::from_str(WALLET).unwrap();
+        let message = Message::new(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstruction::set_compute_unit_price(25_000),
+                system_instruction::transfer(&payer, &Pubkey::new_unique(), 1_000),
+            ],
+            Some(&payer),
+        );
+        let bytes = bincode::serialize(&Transaction::new_unsigned(message)).unwrap();
+        assert_eq!(fee_parameters(&bytes), Some(FeeParameters { signatures: 1, compute_unit_price: 25_000 }));
+        assert_eq!(fee_parameters(b"frontrun_placeholder"), None);
+
+        let accounts = tracked_accounts(WALLET, &[
+            crate::utils::pool_creation::WSOL_MINT.to_string(),
+            USDC_MINT.to_string(),
+            USDC_MINT.to_string(),
+            "SOL".to_string(),
+        ]);
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0], WALLET);
+        assert_eq!(accounts[2], associated_token_address(WALLET, USDC_MINT).unwrap());
+    }
+
+    #[test]
+    fn test_token_balances_sum_per_mint() {
+        let accounts = vec![
+            token_account(USDC_MINT, 1_500_000, 6),
+            token_account(USDC_MINT, 500_000, 6),
+            token_account(crate::utils::pool_creation::WSOL_MINT, 250_000_000, 9),
+            json!({ "lamports": 1, "data": ["", "base64"] }),
+        ];
+        let balances = token_balances(&accounts);
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[USDC_MINT], TokenBalance { amount: 2.0, decimals: 6 });
+        assert_eq!(balances[crate::utils::pool_creation::WSOL_MINT].amount, 0.25);
+    }
+
+    #[test]
+    fn test_realized_effects_of_a_landed_bundle() {
+        let transaction = |signer: &str, pre: u64, post: u64, usdc: Option<(&str, &str)>| {
+            let token_balance = |amount: &str| json!([{
+                "accountIndex": 1, "mint": USDC_MINT, "owner": signer,
+                "uiTokenAmount": { "amount": amount, "decimals": 6, "uiAmount": null }
+            }]);
+            let (pre_usdc, post_usdc) = usdc.map_or((json!([]), json!([])), |(pre, post)| (token_balance(pre), token_balance(post)));
+            serde_json::from_value::<EncodedTransaction>(json!({
+                "slot": 1,
+                "transaction": {
+                    "signatures": [format!("{}Sig", signer)],
+                    "message": { "accountKeys": [signer, "Pool2222"], "recentBlockhash": "Hash3333", "instructions": [] }
+                },
+                "meta": {
+                    "err": null, "fee": 10_000, "preBalances": [pre, 1], "postBalances": [post, 1],
+                    "preTokenBalances": pre_usdc, "postTokenBalances": post_usdc, "computeUnitsConsumed": 60_000
+                }
+            })).unwrap()
+        };
+        // Frontrun spends 1 SOL on 150 USDC, the victim's swap is not ours, the backrun sells it all for 1.02 SOL
+        let bundle = vec![
+            transaction(WALLET, 5_000_000_000, 3_999_990_000, Some(("0", "150000000"))),
+            transaction("Victim11", 9_000_000_000, 7_999_990_000, None),
+            transaction(WALLET, 3_999_990_000, 5_019_980_000, Some(("150000000", "0"))),
+        ];
+        let realized = realized_effects(&bundle, WALLET);
+        assert!(realized.success);
+        assert!((realized.sol_balance_change - 0.01998).abs() < 1e-12);
+        assert!((realized.fees_paid - 0.00002).abs() < 1e-12);
+        assert_eq!(realized.units_consumed, 120_000);
+        assert!(realized.token_balance_changes.is_empty());
+
+        let realized = realized_effects(&bundle[..1], WALLET);
+        assert_eq!(realized.token_balance_changes[USDC_MINT], 150.0);
+    }
+}
@@ -0,0 +1,208 @@
+use std::time::Duration;
+use futures::stream::{FuturesUnordered, StreamExt};
+use crate::utils::amm_math;
+use crate::utils::enhanced_transaction_simulator::OpportunityType;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VarianceConfig {
+    pub reserve_perturbation: f64, // Pool price moved by ± this fraction before we land
+    pub trade_size_perturbation: f64, // Victim trade size ± this fraction
+    pub compute_units: u64, // Priced at each fee percentile
+    pub time_budget: Duration, // Scenarios not done by then are left out
+}
+
+impl Default for VarianceConfig {
+    fn default() -> Self {
+        Self {
+            reserve_perturbation: 0.02,
+            trade_size_perturbation: 0.1,
+            compute_units: 200_000,
+            time_budget: Duration::from_millis(200),
+        }
+    }
+}
+
+impl VarianceConfig {
+    pub fn from_config(config: &crate::config::SimulationConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (reserve_perturbation, trade_size_perturbation) = (config.reserve_perturbation, config.trade_size_perturbation);
+        if !(0.0..1.0).contains(&reserve_perturbation) || !(0.0..1.0).contains(&trade_size_perturbation) {
+            return Err("Simulation perturbations must be in [0, 1)".into());
+        }
+
+        Ok(Self {
+            reserve_perturbation,
+            trade_size_perturbation,
+            time_budget: Duration::from_millis(config.variance_budget_ms),
+            ..Self::default()
+        })
+    }
+}
+
+// What the profit math runs on, in raw units of the input token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolInputs {
+    pub reserve_in: u64,
+    pub reserve_out: u64,
+    pub fee_rate: f64,
+}
+
+// One set of perturbations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scenario {
+    pub reserve_shift: f64, // Positive: the input side grew, our input buys less
+    pub trade_size_shift: f64,
+    pub priority_fee: u64, // Micro-lamports per compute unit
+}
+
+// The grid: price moved down, not at all and up, times victim size (victim-driven types only),
+// times every fee percentile. No percentiles prices the fee at 0
+pub fn scenarios(config: &VarianceConfig, opportunity_type: &OpportunityType, fee_percentiles: &[u64]) -> Vec<Scenario> {
+    let shifts = |width: f64| if width > 0.0 { vec![-width, 0.0, width] } else { vec![0.0] };
+    let trade_size_shifts = match opportunity_type {
+        OpportunityType::Sandwich => shifts(config.trade_size_perturbation),
+        _ => vec![0.0],
+    };
+    let priority_fees = if fee_percentiles.is_empty() { vec![0] } else { fee_percentiles.to_vec() };
+
+    let mut grid = Vec::new();
+    for reserve_shift in shifts(config.reserve_perturbation) {
+        for &trade_size_shift in &trade_size_shifts {
+            for &priority_fee in &priority_fees {
+                grid.push(Scenario { reserve_shift, trade_size_shift, priority_fee });
+            }
+        }
+    }
+    grid
+}
+
+fn scale(amount: u64, shift: f64) -> u64 {
+    (amount as f64 * (1.0 + shift)).max(0.0) as u64
+}
+
+// Net profit in SOL of the opportunity under one scenario. The pool's price moves with k held,
+// our transaction keeps the size it was built with at the unperturbed state, and the victim of a
+// sandwich trades its perturbed size. Other types are our own swap of trade_size, whose estimate
+// moves by what the perturbed pool pays out against the unperturbed one. Without pool state only
+// the fee varies. sol_per_unit values one raw input unit
+pub fn scenario_profit(
+    opportunity_type: &OpportunityType,
+    estimated_profit: f64,
+    trade_size: u64,
+    pool: Option<&PoolInputs>,
+    sol_per_unit: f64,
+    compute_units: u64,
+    scenario: &Scenario,
+) -> f64 {
+    let priority_fee = (compute_units as f64 * scenario.priority_fee as f64 / 1_000_000.0) / 1_000_000_000.0;
+    let Some(pool) = pool else {
+        return estimated_profit - priority_fee;
+    };
+    let reserve_in = scale(pool.reserve_in, scenario.reserve_shift);
+    let reserve_out = (pool.reserve_out as f64 / (1.0 + scenario.reserve_shift)) as u64;
+
+    let gross = match opportunity_type {
+        OpportunityType::Sandwich => {
+            let front_in = amm_math::optimal_sandwich_front(
+                trade_size, 0, pool.reserve_in, pool.reserve_out, pool.fee_rate, trade_size, 1.0,
+            ).map_or(0, |front| front.front_in);
+            if front_in == 0 {
+                0.0
+            } else {
+                let victim_in = scale(trade_size, scenario.trade_size_shift);
+                amm_math::sandwich_profit(front_in, victim_in, reserve_in, reserve_out, pool.fee_rate) as f64 * sol_per_unit
+            }
+        }
+        _ => {
+            let base_out = amm_math::get_amount_out(trade_size, pool.reserve_in, pool.reserve_out, pool.fee_rate) as f64;
+            let out = amm_math::get_amount_out(trade_size, reserve_in, reserve_out, pool.fee_rate) as f64;
+            // Output shortfall valued at the unperturbed price, in input units
+            let spot = pool.reserve_in as f64 / pool.reserve_out as f64;
+            estimated_profit + (out - base_out) * spot * sol_per_unit
+        }
+    };
+    gross - priority_fee
+}
+
+// Standard deviation over the absolute mean, so spreads compare across opportunity sizes.
+// Infinite when the profits spread around zero
+pub fn coefficient_of_variation(profits: &[f64]) -> f64 {
+    if profits.is_empty() {
+        return 0.0;
+    }
+    let mean = profits.iter().sum::<f64>() / profits.len() as f64;
+    let std_dev = (profits.iter().map(|profit| (profit - mean).powi(2)).sum::<f64>() / profits.len() as f64).sqrt();
+    if std_dev == 0.0 {
+        0.0
+    } else if mean == 0.0 {
+        f64::INFINITY
+    } else {
+        std_dev / mean.abs()
+    }
+}
+
+// Evaluates the scenarios concurrently and returns the profits of those done within the budget
+pub async fn run_scenarios<F>(scenarios: &[Scenario], time_budget: Duration, profit: F) -> Vec<f64>
+where
+    F: Fn(&Scenario) -> f64 + Sync,
+{
+    let mut pending: FuturesUnordered<_> = scenarios.iter()
+        .map(|scenario| {
+            let profit = &profit;
+            async move { profit(scenario) }
+        })
+        .collect();
+
+    let deadline = tokio::time::Instant::now() + time_budget;
+    let mut profits = Vec::with_capacity(scenarios.len());
+    while let Ok(Some(result)) = tokio::time::timeout_at(deadline, pending.next()).await {
+        profits.push(result);
+    }
+    profits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1,000 SOL against 150,000 USDC, 0.25% fee; input is SOL
+    const POOL: PoolInputs = PoolInputs { reserve_in: 1_000_000_000_000, reserve_out: 150_000_000_000, fee_rate: 0.0025 };
+    const SOL_PER_LAMPORT: f64 = 1e-9;
+
+    async fn variance(opportunity_type: OpportunityType, reserve_perturbation: f64, trade_size_perturbation: f64) -> f64 {
+        let config = VarianceConfig { reserve_perturbation, trade_size_perturbation, ..VarianceConfig::default() };
+        let grid = scenarios(&config, &opportunity_type, &[1_000, 50_000]);
+        let profits = run_scenarios(&grid, config.time_budget, |scenario| {
+            scenario_profit(&opportunity_type, 0.05, 20_000_000_000, Some(&POOL), SOL_PER_LAMPORT, config.compute_units, scenario)
+        }).await;
+        assert_eq!(profits.len(), grid.len());
+        coefficient_of_variation(&profits)
+    }
+
+    #[tokio::test]
+    async fn test_wider_perturbation_means_larger_variance() {
+        for opportunity_type in [OpportunityType::Sandwich, OpportunityType::Arbitrage] {
+            let none = variance(opportunity_type.clone(), 0.0, 0.0).await;
+            let narrow = variance(opportunity_type.clone(), 0.005, 0.05).await;
+            let wide = variance(opportunity_type.clone(), 0.02, 0.2).await;
+            assert!(none < narrow, "{:?}: {} !< {}", opportunity_type, none, narrow);
+            assert!(narrow < wide, "{:?}: {} !< {}", opportunity_type, narrow, wide);
+        }
+        // Only the fee varies without pool state
+        let grid = scenarios(&VarianceConfig::default(), &OpportunityType::Sandwich, &[0, 100_000]);
+        assert_eq!(grid.len(), 18);
+        let profits: Vec<f64> = grid.iter()
+            .map(|scenario| scenario_profit(&OpportunityType::Sandwich, 0.05, 1, None, SOL_PER_LAMPORT, 200_000, scenario))
+            .collect();
+        assert!(profits.iter().all(|profit| *profit == 0.05 || (*profit - 0.04998).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_coefficient_of_variation_is_scale_free() {
+        let small = [0.009, 0.01, 0.011];
+        let large = [0.9, 1.0, 1.1];
+        assert!((coefficient_of_variation(&small) - coefficient_of_variation(&large)).abs() < 1e-9);
+        assert_eq!(coefficient_of_variation(&[0.02, 0.02]), 0.0);
+        assert_eq!(coefficient_of_variation(&[-0.01, 0.01]), f64::INFINITY);
+        assert_eq!(coefficient_of_variation(&[]), 0.0);
+    }
+}
@@ -0,0 +1,657 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use serde_json::json;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::logging::Logger;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::enhanced_transaction_simulator::{OpportunityDetails, OpportunityType};
+use crate::utils::mint_info;
+use crate::utils::opportunity_evaluator::OpportunityEvaluator;
+use crate::utils::pool_creation::WSOL_MINT;
+
+pub const SOLEND_PROGRAM_ID: &str = "So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo";
+pub const SOLEND_MAIN_MARKET: &str = "4UpD2fh7xH3VP9QQaXtsS1YY3bxzWhtfpks7FatyKvdY";
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+const OBLIGATION_LEN: usize = 1300;
+const RESERVE_LEN: usize = 619;
+const OBLIGATION_COLLATERAL_LEN: usize = 88;
+const OBLIGATION_LIQUIDITY_LEN: usize = 112;
+const OBLIGATION_LENDING_MARKET_OFFSET: usize = 10;
+
+const REFRESH_RESERVE_TAG: u8 = 3;
+const REFRESH_OBLIGATION_TAG: u8 = 7;
+const LIQUIDATE_OBLIGATION_TAG: u8 = 12;
+
+// Share of a borrow a single liquidation may repay
+const LIQUIDATION_CLOSE_FACTOR: f64 = 0.2;
+
+// Solend Decimal: u128 scaled by 1e18
+fn wad_at(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().expect("16 byte slice"))
+}
+
+fn wad_to_f64(wad: u128) -> f64 {
+    wad as f64 / 1e18
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 byte slice"))
+}
+
+fn pubkey_at(data: &[u8], offset: usize) -> String {
+    bs58::encode(&data[offset..offset + 32]).into_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObligationCollateral {
+    pub deposit_reserve: String,
+    pub deposited_amount: u64, // Reserve collateral (cToken) units
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObligationLiquidity {
+    pub borrow_reserve: String,
+    pub borrowed_amount_wads: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Obligation {
+    pub lending_market: String,
+    pub owner: String,
+    pub deposited_value: f64, // USD, as of the last on-chain refresh
+    pub borrowed_value: f64,
+    pub unhealthy_borrow_value: f64,
+    pub deposits: Vec<ObligationCollateral>,
+    pub borrows: Vec<ObligationLiquidity>,
+}
+
+impl Obligation {
+    // Layout: version, last_update (slot u64, stale u8), lending_market, owner, deposited_value,
+    // borrowed_value, allowed_borrow_value, unhealthy_borrow_value (Decimals), 64 bytes of newer
+    // fields, deposits_len, borrows_len, then deposits (88 bytes each) followed by borrows (112)
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() != OBLIGATION_LEN {
+            return Err(format!("Solend obligation has {} bytes, expected {}", data.len(), OBLIGATION_LEN).into());
+        }
+
+        let deposits_len = data[202] as usize;
+        let borrows_len = data[203] as usize;
+        let flat_len = deposits_len * OBLIGATION_COLLATERAL_LEN + borrows_len * OBLIGATION_LIQUIDITY_LEN;
+        if 204 + flat_len > OBLIGATION_LEN {
+            return Err(format!("Solend obligation lists {} deposits and {} borrows", deposits_len, borrows_len).into());
+        }
+
+        let deposits = (0..deposits_len)
+            .map(|i| {
+                let offset = 204 + i * OBLIGATION_COLLATERAL_LEN;
+                ObligationCollateral {
+                    deposit_reserve: pubkey_at(data, offset),
+                    deposited_amount: u64_at(data, offset + 32),
+                }
+            })
+            .collect();
+        let borrows_start = 204 + deposits_len * OBLIGATION_COLLATERAL_LEN;
+        let borrows = (0..borrows_len)
+            .map(|i| {
+                let offset = borrows_start + i * OBLIGATION_LIQUIDITY_LEN;
+                ObligationLiquidity {
+                    borrow_reserve: pubkey_at(data, offset),
+                    borrowed_amount_wads: wad_at(data, offset + 48),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            lending_market: pubkey_at(data, OBLIGATION_LENDING_MARKET_OFFSET),
+            owner: pubkey_at(data, 42),
+            deposited_value: wad_to_f64(wad_at(data, 74)),
+            borrowed_value: wad_to_f64(wad_at(data, 90)),
+            unhealthy_borrow_value: wad_to_f64(wad_at(data, 122)),
+            deposits,
+            borrows,
+        })
+    }
+
+    // Borrowed value relative to the liquidation threshold at the last refresh; 1.0 or more
+    // was liquidatable then
+    pub fn last_refresh_utilization(&self) -> f64 {
+        if self.unhealthy_borrow_value <= 0.0 {
+            return 0.0;
+        }
+        self.borrowed_value / self.unhealthy_borrow_value
+    }
+
+    fn reserve_addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self.deposits.iter().map(|d| d.deposit_reserve.clone())
+            .chain(self.borrows.iter().map(|b| b.borrow_reserve.clone()))
+            .collect();
+        addresses.sort();
+        addresses.dedup();
+        addresses
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reserve {
+    pub lending_market: String,
+    pub liquidity_mint: String,
+    pub liquidity_decimals: u8,
+    pub liquidity_supply: String,
+    pub pyth_oracle: String,
+    pub switchboard_oracle: String,
+    pub available_amount: u64,
+    pub borrowed_amount_wads: u128,
+    pub collateral_mint: String,
+    pub collateral_mint_supply: u64,
+    pub collateral_supply: String,
+    pub liquidation_bonus: u8,     // Percent
+    pub liquidation_threshold: u8, // Percent
+}
+
+impl Reserve {
+    // Layout: version, last_update, lending_market, liquidity (mint, decimals, supply, pyth,
+    // switchboard, available u64, borrowed Decimal, cumulative rate, market price), collateral
+    // (mint, total supply u64, supply), config (optimal utilization, ltv, bonus, threshold, ...)
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() != RESERVE_LEN {
+            return Err(format!("Solend reserve has {} bytes, expected {}", data.len(), RESERVE_LEN).into());
+        }
+
+        Ok(Self {
+            lending_market: pubkey_at(data, 10),
+            liquidity_mint: pubkey_at(data, 42),
+            liquidity_decimals: data[74],
+            liquidity_supply: pubkey_at(data, 75),
+            pyth_oracle: pubkey_at(data, 107),
+            switchboard_oracle: pubkey_at(data, 139),
+            available_amount: u64_at(data, 171),
+            borrowed_amount_wads: wad_at(data, 179),
+            collateral_mint: pubkey_at(data, 227),
+            collateral_mint_supply: u64_at(data, 259),
+            collateral_supply: pubkey_at(data, 267),
+            liquidation_bonus: data[301],
+            liquidation_threshold: data[302],
+        })
+    }
+
+    // Liquidity tokens one collateral token redeems for
+    pub fn collateral_exchange_rate(&self) -> f64 {
+        if self.collateral_mint_supply == 0 {
+            return 1.0;
+        }
+        let total_liquidity = self.available_amount as f64 + wad_to_f64(self.borrowed_amount_wads);
+        total_liquidity / self.collateral_mint_supply as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObligationHealth {
+    pub deposited_value_sol: f64,
+    pub weighted_collateral_sol: f64, // Deposits weighted by each reserve's liquidation threshold
+    pub borrowed_value_sol: f64,
+}
+
+impl ObligationHealth {
+    // Below 1.0 the obligation can be liquidated
+    pub fn health_factor(&self) -> f64 {
+        if self.borrowed_value_sol <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.weighted_collateral_sol / self.borrowed_value_sol
+    }
+}
+
+// Value every position at current oracle prices (SOL per whole liquidity token, by mint);
+// None when a reserve or price is missing
+pub fn obligation_health(
+    obligation: &Obligation,
+    reserves: &HashMap<String, Reserve>,
+    prices_in_sol: &HashMap<String, f64>,
+) -> Option<ObligationHealth> {
+    let mut health = ObligationHealth { deposited_value_sol: 0.0, weighted_collateral_sol: 0.0, borrowed_value_sol: 0.0 };
+
+    for deposit in &obligation.deposits {
+        let value = deposit_value_sol(deposit, reserves, prices_in_sol)?;
+        let reserve = reserves.get(&deposit.deposit_reserve)?;
+        health.deposited_value_sol += value;
+        health.weighted_collateral_sol += value * reserve.liquidation_threshold as f64 / 100.0;
+    }
+    for borrow in &obligation.borrows {
+        health.borrowed_value_sol += borrow_value_sol(borrow, reserves, prices_in_sol)?;
+    }
+
+    Some(health)
+}
+
+fn deposit_value_sol(deposit: &ObligationCollateral, reserves: &HashMap<String, Reserve>, prices_in_sol: &HashMap<String, f64>) -> Option<f64> {
+    let reserve = reserves.get(&deposit.deposit_reserve)?;
+    let price = prices_in_sol.get(&reserve.liquidity_mint)?;
+    let liquidity = deposit.deposited_amount as f64 * reserve.collateral_exchange_rate();
+    Some(liquidity / 10f64.powi(reserve.liquidity_decimals as i32) * price)
+}
+
+fn borrow_value_sol(borrow: &ObligationLiquidity, reserves: &HashMap<String, Reserve>, prices_in_sol: &HashMap<String, f64>) -> Option<f64> {
+    let reserve = reserves.get(&borrow.borrow_reserve)?;
+    let price = prices_in_sol.get(&reserve.liquidity_mint)?;
+    Some(wad_to_f64(borrow.borrowed_amount_wads) / 10f64.powi(reserve.liquidity_decimals as i32) * price)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidationPlan {
+    pub repay_reserve: String,
+    pub withdraw_reserve: String,
+    pub repay_mint: String,
+    pub withdraw_mint: String,
+    pub repay_amount: u64, // Raw units of the repay mint
+    pub repay_value_sol: f64,
+    pub expected_profit_sol: f64, // Liquidation bonus on the repaid value, before fees
+}
+
+// Repay the largest borrow against the largest deposit, up to the close factor, the collateral
+// the bonus can be paid from, and max_capital_sol
+pub fn plan_liquidation(
+    obligation: &Obligation,
+    reserves: &HashMap<String, Reserve>,
+    prices_in_sol: &HashMap<String, f64>,
+    max_capital_sol: f64,
+) -> Option<LiquidationPlan> {
+    let health = obligation_health(obligation, reserves, prices_in_sol)?;
+    if health.health_factor() >= 1.0 {
+        return None;
+    }
+
+    let (borrow, borrow_value) = obligation.borrows.iter()
+        .filter_map(|borrow| Some((borrow, borrow_value_sol(borrow, reserves, prices_in_sol)?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let (deposit, deposit_value) = obligation.deposits.iter()
+        .filter_map(|deposit| Some((deposit, deposit_value_sol(deposit, reserves, prices_in_sol)?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let repay_reserve = reserves.get(&borrow.borrow_reserve)?;
+    let withdraw_reserve = reserves.get(&deposit.deposit_reserve)?;
+    let bonus = withdraw_reserve.liquidation_bonus as f64 / 100.0;
+
+    let repay_value_sol = (borrow_value * LIQUIDATION_CLOSE_FACTOR)
+        .min(deposit_value / (1.0 + bonus))
+        .min(max_capital_sol);
+    let repay_price = *prices_in_sol.get(&repay_reserve.liquidity_mint)?;
+    if repay_value_sol <= 0.0 || repay_price <= 0.0 {
+        return None;
+    }
+    let repay_amount = mint_info::ui_to_raw(repay_value_sol / repay_price, repay_reserve.liquidity_decimals);
+
+    Some(LiquidationPlan {
+        repay_reserve: borrow.borrow_reserve.clone(),
+        withdraw_reserve: deposit.deposit_reserve.clone(),
+        repay_mint: repay_reserve.liquidity_mint.clone(),
+        withdraw_mint: withdraw_reserve.liquidity_mint.clone(),
+        repay_amount,
+        repay_value_sol,
+        expected_profit_sol: repay_value_sol * bonus,
+    })
+}
+
+fn pubkey(address: &str) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    Pubkey::from_str(address).map_err(|e| format!("Invalid pubkey {}: {}", address, e).into())
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Result<Pubkey, Box<dyn std::error::Error + Send + Sync>> {
+    let token_program = pubkey(TOKEN_PROGRAM_ID)?;
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &pubkey(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+    );
+    Ok(address)
+}
+
+// Accounts: [reserve (w), pyth oracle, switchboard oracle]
+pub fn refresh_reserve_instruction(reserve_address: &str, reserve: &Reserve) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(Instruction {
+        program_id: pubkey(SOLEND_PROGRAM_ID)?,
+        accounts: vec![
+            AccountMeta::new(pubkey(reserve_address)?, false),
+            AccountMeta::new_readonly(pubkey(&reserve.pyth_oracle)?, false),
+            AccountMeta::new_readonly(pubkey(&reserve.switchboard_oracle)?, false),
+        ],
+        data: vec![REFRESH_RESERVE_TAG],
+    })
+}
+
+// Accounts: [obligation (w), deposit reserves..., borrow reserves...]
+pub fn refresh_obligation_instruction(obligation_address: &str, obligation: &Obligation) -> Result<Instruction, Box<dyn std::error::Error + Send + Sync>> {
+    let mut accounts = vec![AccountMeta::new(pubkey(obligation_address)?, false)];
+    for deposit in &obligation.deposits {
+        accounts.push(AccountMeta::new_readonly(pubkey(&deposit.deposit_reserve)?, false));
+    }
+    for borrow in &obligation.borrows {
+        accounts.push(AccountMeta::new_readonly(pubkey(&borrow.borrow_reserve)?, false));
+    }
+    Ok(Instruction {
+        program_id: pubkey(SOLEND_PROGRAM_ID)?,
+        accounts,
+        data: vec![REFRESH_OBLIGATION_TAG],
+    })
+}
+
+// Refresh every reserve the obligation touches and the obligation itself (liquidate rejects
+// stale state), then LiquidateObligation: [source liquidity (w), destination collateral (w),
+// repay reserve (w), repay liquidity supply (w), withdraw reserve, withdraw collateral supply (w),
+// obligation (w), lending market, market authority, transfer authority (s), token program].
+// The seized collateral stays as the withdraw reserve's cTokens in the liquidator's account
+pub fn liquidation_instructions(
+    obligation_address: &str,
+    obligation: &Obligation,
+    reserves: &HashMap<String, Reserve>,
+    plan: &LiquidationPlan,
+    liquidator: &Pubkey,
+) -> Result<Vec<Instruction>, Box<dyn std::error::Error + Send + Sync>> {
+    let program_id = pubkey(SOLEND_PROGRAM_ID)?;
+    let reserve = |address: &str| reserves.get(address).ok_or_else(|| format!("Reserve {} not loaded", address));
+    let repay_reserve = reserve(&plan.repay_reserve)?;
+    let withdraw_reserve = reserve(&plan.withdraw_reserve)?;
+
+    let mut instructions = Vec::new();
+    for address in obligation.reserve_addresses() {
+        instructions.push(refresh_reserve_instruction(&address, reserve(&address)?)?);
+    }
+    instructions.push(refresh_obligation_instruction(obligation_address, obligation)?);
+
+    let lending_market = pubkey(&obligation.lending_market)?;
+    let (market_authority, _) = Pubkey::find_program_address(&[lending_market.as_ref()], &program_id);
+    let mut data = vec![LIQUIDATE_OBLIGATION_TAG];
+    data.extend_from_slice(&plan.repay_amount.to_le_bytes());
+
+    instructions.push(Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(associated_token_address(liquidator, &pubkey(&repay_reserve.liquidity_mint)?)?, false),
+            AccountMeta::new(associated_token_address(liquidator, &pubkey(&withdraw_reserve.collateral_mint)?)?, false),
+            AccountMeta::new(pubkey(&plan.repay_reserve)?, false),
+            AccountMeta::new(pubkey(&repay_reserve.liquidity_supply)?, false),
+            AccountMeta::new_readonly(pubkey(&plan.withdraw_reserve)?, false),
+            AccountMeta::new(pubkey(&withdraw_reserve.collateral_supply)?, false),
+            AccountMeta::new(pubkey(obligation_address)?, false),
+            AccountMeta::new_readonly(lending_market, false),
+            AccountMeta::new_readonly(market_authority, false),
+            AccountMeta::new_readonly(*liquidator, true),
+            AccountMeta::new_readonly(pubkey(TOKEN_PROGRAM_ID)?, false),
+        ],
+        data,
+    });
+
+    Ok(instructions)
+}
+
+#[derive(Debug, Clone)]
+pub struct LiquidationConfig {
+    pub lending_market: String,
+    pub max_capital_sol: f64,       // Most SOL-equivalent repaid in a single liquidation
+    pub min_profit_sol: f64,
+    pub near_threshold_ratio: f64, // Obligations at this share of their threshold get re-priced
+}
+
+impl LiquidationConfig {
+    pub fn from_config(config: &crate::config::LiquidationConfig) -> Self {
+        Self {
+            lending_market: config.lending_market.clone().unwrap_or_else(|| SOLEND_MAIN_MARKET.to_string()),
+            max_capital_sol: config.max_capital_sol,
+            min_profit_sol: config.min_profit_sol,
+            near_threshold_ratio: config.near_threshold, // Prices move between on-chain refreshes
+        }
+    }
+}
+
+// Finds and re-plans Solend liquidations for one lending market
+#[derive(Clone)]
+pub struct SolendLiquidator {
+    rpc_manager: Arc<RpcManager>,
+    evaluator: Arc<OpportunityEvaluator>,
+    config: LiquidationConfig,
+}
+
+impl SolendLiquidator {
+    pub fn new(rpc_manager: Arc<RpcManager>, evaluator: Arc<OpportunityEvaluator>, config: LiquidationConfig) -> Self {
+        Self { rpc_manager, evaluator, config }
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, evaluator: Arc<OpportunityEvaluator>, config: &crate::config::LiquidationConfig) -> Self {
+        Self::new(rpc_manager, evaluator, LiquidationConfig::from_config(config))
+    }
+
+    pub fn config(&self) -> &LiquidationConfig {
+        &self.config
+    }
+
+    // Obligations of the market whose last refresh put them close to (or past) their threshold
+    async fn candidate_obligations(&self) -> Result<Vec<(String, Obligation)>, Box<dyn std::error::Error + Send + Sync>> {
+        let filters = json!([
+            { "dataSize": OBLIGATION_LEN },
+            { "memcmp": { "offset": OBLIGATION_LENDING_MARKET_OFFSET, "bytes": self.config.lending_market } }
+        ]);
+        let accounts = self.rpc_manager.get_program_accounts_data(SOLEND_PROGRAM_ID, filters).await?;
+
+        Ok(accounts.into_iter()
+            .filter_map(|(address, data)| Obligation::decode(&data).ok().map(|obligation| (address, obligation)))
+            .filter(|(_, obligation)| {
+                !obligation.borrows.is_empty()
+                    && obligation.last_refresh_utilization() >= self.config.near_threshold_ratio
+            })
+            .collect())
+    }
+
+    async fn load_reserves(&self, addresses: &[String]) -> Result<HashMap<String, Reserve>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut reserves = HashMap::new();
+        for chunk in addresses.chunks(100) { // getMultipleAccounts limit
+            let data = self.rpc_manager.get_multiple_accounts_data(chunk).await?;
+            for (address, data) in chunk.iter().zip(data) {
+                if let Some(reserve) = data.and_then(|data| Reserve::decode(&data).ok()) {
+                    reserves.insert(address.clone(), reserve);
+                }
+            }
+        }
+        Ok(reserves)
+    }
+
+    // SOL per whole token for each reserve's liquidity mint; mints without a price are left out
+    async fn reserve_prices(&self, reserves: &HashMap<String, Reserve>) -> HashMap<String, f64> {
+        let mut prices = HashMap::new();
+        for reserve in reserves.values() {
+            if prices.contains_key(&reserve.liquidity_mint) {
+                continue;
+            }
+            if reserve.liquidity_mint == WSOL_MINT {
+                prices.insert(reserve.liquidity_mint.clone(), 1.0);
+                continue;
+            }
+            match self.evaluator.get_token_price(&reserve.liquidity_mint).await {
+                Ok(price) if price.price_in_sol > 0.0 => {
+                    prices.insert(reserve.liquidity_mint.clone(), price.price_in_sol);
+                }
+                Ok(_) => {}
+                Err(e) => Logger::status_update(&format!("No price for Solend reserve mint {}: {}", reserve.liquidity_mint, e)),
+            }
+        }
+        prices
+    }
+
+    fn opportunity(&self, obligation_address: &str, plan: &LiquidationPlan) -> OpportunityDetails {
+        OpportunityDetails {
+            token_a: plan.repay_mint.clone(),
+            token_b: plan.withdraw_mint.clone(),
+            trade_size: plan.repay_amount,
+            estimated_profit: plan.expected_profit_sol,
+            dex: "Solend".to_string(),
+            opportunity_type: OpportunityType::Liquidation,
+            pool_address: Some(obligation_address.to_string()),
+            route: Vec::new(),
+        }
+    }
+
+    // Liquidatable obligations at current oracle prices, most profitable first
+    pub async fn scan(&self) -> Result<Vec<OpportunityDetails>, Box<dyn std::error::Error + Send + Sync>> {
+        let candidates = self.candidate_obligations().await?;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut reserve_addresses: Vec<String> = candidates.iter()
+            .flat_map(|(_, obligation)| obligation.reserve_addresses())
+            .collect();
+        reserve_addresses.sort();
+        reserve_addresses.dedup();
+        let reserves = self.load_reserves(&reserve_addresses).await?;
+        let prices = self.reserve_prices(&reserves).await;
+
+        let mut plans: Vec<(String, LiquidationPlan)> = candidates.iter()
+            .filter_map(|(address, obligation)| {
+                plan_liquidation(obligation, &reserves, &prices, self.config.max_capital_sol)
+                    .map(|plan| (address.clone(), plan))
+            })
+            .filter(|(_, plan)| plan.expected_profit_sol >= self.config.min_profit_sol)
+            .collect();
+        plans.sort_by(|a, b| b.1.expected_profit_sol.total_cmp(&a.1.expected_profit_sol));
+
+        Ok(plans.iter().map(|(address, plan)| self.opportunity(address, plan)).collect())
+    }
+
+    // Re-plan from fresh on-chain state right before execution; None once the obligation is
+    // healthy again or no longer worth the minimum profit
+    pub async fn prepare(
+        &self,
+        obligation_address: &str,
+        liquidator: &Pubkey,
+    ) -> Result<Option<(LiquidationPlan, Vec<Instruction>)>, Box<dyn std::error::Error + Send + Sync>> {
+        let obligation = Obligation::decode(&self.rpc_manager.get_account_data(obligation_address).await?)?;
+        let reserves = self.load_reserves(&obligation.reserve_addresses()).await?;
+        let prices = self.reserve_prices(&reserves).await;
+
+        let plan = match plan_liquidation(&obligation, &reserves, &prices, self.config.max_capital_sol) {
+            Some(plan) if plan.expected_profit_sol >= self.config.min_profit_sol => plan,
+            _ => return Ok(None),
+        };
+        let instructions = liquidation_instructions(obligation_address, &obligation, &reserves, &plan, liquidator)?;
+        Ok(Some((plan, instructions)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::USDC_MINT;
+
+    const SOL_RESERVE: &str = "8PbodeaosQP19SjYFx855UMqWxH2HynZLdBXmsrbac36";
+    const USDC_RESERVE: &str = "BgxfHJDzm44T7XG68MYKx7YisTjZu73tVovyZSjJMpmw";
+
+    fn reserve(liquidity_mint: &str, decimals: u8) -> Reserve {
+        Reserve {
+            lending_market: SOLEND_MAIN_MARKET.to_string(),
+            liquidity_mint: liquidity_mint.to_string(),
+            liquidity_decimals: decimals,
+            liquidity_supply: SOL_RESERVE.to_string(),
+            pyth_oracle: SOL_RESERVE.to_string(),
+            switchboard_oracle: SOL_RESERVE.to_string(),
+            available_amount: 1_000,
+            borrowed_amount_wads: 1_000 * 1_000_000_000_000_000_000u128, // 1,000 lent out
+            collateral_mint: USDC_RESERVE.to_string(),
+            collateral_mint_supply: 1_000, // 2 liquidity per collateral token
+            collateral_supply: USDC_RESERVE.to_string(),
+            liquidation_bonus: 5,
+            liquidation_threshold: 80,
+        }
+    }
+
+    // 10 SOL deposited (as cTokens) against a USDC borrow
+    fn obligation(borrowed_usdc: u64) -> Obligation {
+        Obligation {
+            lending_market: SOLEND_MAIN_MARKET.to_string(),
+            owner: SOL_RESERVE.to_string(),
+            deposited_value: 0.0,
+            borrowed_value: 0.0,
+            unhealthy_borrow_value: 0.0,
+            deposits: vec![ObligationCollateral { deposit_reserve: SOL_RESERVE.to_string(), deposited_amount: 5_000_000_000 }],
+            borrows: vec![ObligationLiquidity {
+                borrow_reserve: USDC_RESERVE.to_string(),
+                borrowed_amount_wads: borrowed_usdc as u128 * 1_000_000 * 1_000_000_000_000_000_000,
+            }],
+        }
+    }
+
+    fn market() -> (HashMap<String, Reserve>, HashMap<String, f64>) {
+        let reserves = HashMap::from([
+            (SOL_RESERVE.to_string(), reserve(WSOL_MINT, 9)),
+            (USDC_RESERVE.to_string(), reserve(USDC_MINT, 6)),
+        ]);
+        let prices = HashMap::from([(WSOL_MINT.to_string(), 1.0), (USDC_MINT.to_string(), 1.0 / 150.0)]);
+        (reserves, prices)
+    }
+
+    #[test]
+    fn test_decode_obligation_positions() {
+        let mut data = vec![0u8; OBLIGATION_LEN];
+        data[0] = 1;
+        data[122..138].copy_from_slice(&(2_000u128 * 1_000_000_000_000_000_000).to_le_bytes());
+        data[90..106].copy_from_slice(&(1_900u128 * 1_000_000_000_000_000_000).to_le_bytes());
+        data[202] = 1;
+        data[203] = 1;
+        data[204 + 32..204 + 40].copy_from_slice(&42u64.to_le_bytes());
+        let borrow = 204 + OBLIGATION_COLLATERAL_LEN;
+        data[borrow + 48..borrow + 64].copy_from_slice(&7u128.to_le_bytes());
+
+        let obligation = Obligation::decode(&data).unwrap();
+        assert_eq!(obligation.deposits[0].deposited_amount, 42);
+        assert_eq!(obligation.borrows[0].borrowed_amount_wads, 7);
+        assert!((obligation.last_refresh_utilization() - 0.95).abs() < 1e-9);
+        assert!(Obligation::decode(&data[..OBLIGATION_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn test_health_uses_oracle_prices_and_thresholds() {
+        let (reserves, prices) = market();
+        // 10 SOL of collateral at an 80% threshold covers 8 SOL of debt
+        let health = obligation_health(&obligation(1_050), &reserves, &prices).unwrap();
+        assert!((health.deposited_value_sol - 10.0).abs() < 1e-9);
+        assert!((health.borrowed_value_sol - 7.0).abs() < 1e-9);
+        assert!(health.health_factor() > 1.0);
+        assert!(plan_liquidation(&obligation(1_050), &reserves, &prices, 10.0).is_none());
+
+        let mut missing = prices.clone();
+        missing.remove(USDC_MINT);
+        assert!(obligation_health(&obligation(1_050), &reserves, &missing).is_none());
+    }
+
+    #[test]
+    fn test_liquidation_capped_by_close_factor_and_capital() {
+        let (reserves, prices) = market();
+        // 9 SOL of debt against 8 SOL of borrowing power
+        let plan = plan_liquidation(&obligation(1_350), &reserves, &prices, 10.0).unwrap();
+        assert_eq!((plan.repay_mint.as_str(), plan.withdraw_mint.as_str()), (USDC_MINT, WSOL_MINT));
+        assert!((plan.repay_value_sol - 1.8).abs() < 1e-9); // 20% close factor
+        assert!((plan.expected_profit_sol - 0.09).abs() < 1e-9); // 5% bonus
+        assert_eq!(plan.repay_amount, 270_000_000); // 270 USDC
+
+        let capped = plan_liquidation(&obligation(1_350), &reserves, &prices, 0.5).unwrap();
+        assert!((capped.repay_value_sol - 0.5).abs() < 1e-9);
+        assert_eq!(capped.repay_amount, 75_000_000);
+    }
+
+    #[test]
+    fn test_liquidation_instructions_refresh_first() {
+        let (reserves, prices) = market();
+        let obligation = obligation(1_350);
+        let plan = plan_liquidation(&obligation, &reserves, &prices, 10.0).unwrap();
+        let liquidator = Pubkey::new_unique();
+
+        let instructions = liquidation_instructions(USDC_RESERVE, &obligation, &reserves, &plan, &liquidator).unwrap();
+        let tags: Vec<u8> = instructions.iter().map(|instruction| instruction.data[0]).collect();
+        assert_eq!(tags, vec![REFRESH_RESERVE_TAG, REFRESH_RESERVE_TAG, REFRESH_OBLIGATION_TAG, LIQUIDATE_OBLIGATION_TAG]);
+
+        let liquidate = instructions.last().unwrap();
+        assert_eq!(liquidate.accounts.len(), 11);
+        assert_eq!(liquidate.data[1..], 270_000_000u64.to_le_bytes());
+        assert!(liquidate.accounts[9].is_signer);
+    }
+}
@@ -0,0 +1,228 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinError;
+use crate::config::SupervisorConfig;
+use crate::logging::Logger;
+
+// How often a crashed task is restarted, and how long to wait in between: the backoff doubles
+// with each restart up to max_backoff
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    pub max_restarts: u32, // Failures past this many restarts stop the bot
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_config(config: &SupervisorConfig) -> Self {
+        Self {
+            max_restarts: config.max_restarts,
+            initial_backoff: Duration::from_millis(config.restart_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_restart_backoff_ms),
+        }
+    }
+
+    // Wait before restart number `restart` (0 for the first)
+    pub fn backoff(&self, restart: u32) -> Duration {
+        self.initial_backoff.saturating_mul(1 << restart.min(16)).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Default)]
+struct RestartState {
+    restarts: u64,
+    last_error: Option<String>,
+}
+
+// Restarts so far and the failure behind the latest one, for /healthz and /metrics. Cheap to
+// clone: clones share the same state
+#[derive(Debug, Clone, Default)]
+pub struct RestartTracker {
+    state: Arc<Mutex<RestartState>>,
+}
+
+impl RestartTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RestartState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn restarts(&self) -> u64 {
+        self.state().restarts
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.state().last_error.clone()
+    }
+
+    pub(crate) fn record_failure(&self, error: &str) {
+        self.state().last_error = Some(error.to_string());
+    }
+
+    pub(crate) fn record_restart(&self) {
+        self.state().restarts += 1;
+    }
+}
+
+// Runs `task` until `shutdown` flips to true, restarting it whenever it panics or returns on its
+// own. Each attempt gets its own stop signal, which is sent once the attempt fails so whatever it
+// spawned stops before the next one starts. Err once the policy's restarts are used up
+pub async fn supervise<F, Fut>(
+    name: &str,
+    policy: &RestartPolicy,
+    tracker: &RestartTracker,
+    mut shutdown: watch::Receiver<bool>,
+    mut task: F,
+) -> Result<(), String>
+where
+    F: FnMut(watch::Receiver<bool>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    loop {
+        let (attempt_tx, attempt_rx) = watch::channel(false);
+        let mut handle = tokio::spawn(task(attempt_rx));
+        let result = tokio::select! {
+            result = &mut handle => result,
+            _ = stopped(&mut shutdown) => {
+                // Let the attempt drain as it would have without the supervisor
+                let _ = attempt_tx.send(true);
+                if let Err(e) = handle.await {
+                    Logger::error_occurred(&format!("{} {} while stopping", name, describe(e)));
+                }
+                return Ok(());
+            }
+        };
+        if *shutdown.borrow() {
+            return Ok(());
+        }
+        let _ = attempt_tx.send(true);
+
+        let error = match result {
+            Ok(()) => format!("{} stopped without being asked to", name),
+            Err(e) => format!("{} {}", name, describe(e)),
+        };
+        tracker.record_failure(&error);
+        let restarts = tracker.restarts();
+        if restarts >= policy.max_restarts as u64 {
+            return Err(format!("{}; giving up after {} restart(s)", error, restarts));
+        }
+
+        let backoff = policy.backoff(restarts as u32);
+        Logger::error_occurred(&format!(
+            "{}; restarting in {}ms (restart {}/{})",
+            error, backoff.as_millis(), restarts + 1, policy.max_restarts
+        ));
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {},
+            _ = stopped(&mut shutdown) => return Ok(()),
+        }
+        tracker.record_restart();
+    }
+}
+
+// Resolves once `shutdown` is true; a dropped sender means the owner is gone, which counts too
+async fn stopped(shutdown: &mut watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+fn describe(error: JoinError) -> String {
+    if !error.is_panic() {
+        return format!("was cancelled: {}", error);
+    }
+    let payload = error.into_panic();
+    let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string());
+    format!("panicked: {}", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy { max_restarts, initial_backoff: Duration::from_millis(1), max_backoff: Duration::from_millis(4) }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RestartPolicy { max_restarts: 10, initial_backoff: Duration::from_secs(1), max_backoff: Duration::from_secs(5) };
+        let waits: Vec<u64> = (0..5).map(|restart| policy.backoff(restart).as_secs()).collect();
+        assert_eq!(waits, vec![1, 2, 4, 5, 5]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_crashing_task_is_restarted_until_it_runs() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let orphans_stopped = Arc::new(AtomicU32::new(0));
+        let tracker = RestartTracker::new();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let supervisor = {
+            let attempts = Arc::clone(&attempts);
+            let orphans_stopped = Arc::clone(&orphans_stopped);
+            let tracker = tracker.clone();
+            tokio::spawn(async move {
+                supervise("stub", &policy(5), &tracker, shutdown_rx, move |mut attempt_shutdown| {
+                    let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                    let orphans_stopped = Arc::clone(&orphans_stopped);
+                    async move {
+                        match attempt {
+                            0 => {
+                                // Something the crashed attempt spawned must be stopped with it
+                                let mut orphan_shutdown = attempt_shutdown.clone();
+                                tokio::spawn(async move {
+                                    stopped(&mut orphan_shutdown).await;
+                                    orphans_stopped.fetch_add(1, Ordering::SeqCst);
+                                });
+                                panic!("malformed frame");
+                            }
+                            1 => {} // Returned without being asked to stop
+                            _ => stopped(&mut attempt_shutdown).await,
+                        }
+                    }
+                }).await
+            })
+        };
+
+        while attempts.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(tracker.restarts(), 2);
+        assert_eq!(tracker.last_error(), Some("stub stopped without being asked to".to_string()));
+        assert_eq!(orphans_stopped.load(Ordering::SeqCst), 1);
+
+        let _ = shutdown_tx.send(true);
+        assert_eq!(supervisor.await.unwrap(), Ok(()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_gives_up_after_max_restarts() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let tracker = RestartTracker::new();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let counter = Arc::clone(&attempts);
+        let result = supervise("stub", &policy(2), &tracker, shutdown_rx, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { panic!("always fails"); }
+        }).await;
+
+        assert_eq!(result, Err("stub panicked: always fails; giving up after 2 restart(s)".to_string()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(tracker.restarts(), 2);
+        assert_eq!(tracker.last_error(), Some("stub panicked: always fails".to_string()));
+    }
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{watch, RwLock};
+use crate::config::JitoConfig;
+use crate::logging::Logger;
+
+// Outcomes (by decayed weight) a percentile needs before its landing rate is trusted
+const MIN_CALIBRATION_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TipPercentile {
+    P25,
+    P50,
+    P75,
+    P95,
+}
+
+impl TipPercentile {
+    pub const ALL: [TipPercentile; 4] = [TipPercentile::P25, TipPercentile::P50, TipPercentile::P75, TipPercentile::P95];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_start_matches(['p', 'P']) {
+            "25" => Some(TipPercentile::P25),
+            "50" => Some(TipPercentile::P50),
+            "75" => Some(TipPercentile::P75),
+            "95" => Some(TipPercentile::P95),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TipPercentile::P25 => "p25",
+            TipPercentile::P50 => "p50",
+            TipPercentile::P75 => "p75",
+            TipPercentile::P95 => "p95",
+        }
+    }
+
+    // Next percentile up; P95 stays where it is
+    pub fn higher(&self) -> Self {
+        match self {
+            TipPercentile::P25 => TipPercentile::P50,
+            TipPercentile::P50 => TipPercentile::P75,
+            TipPercentile::P75 | TipPercentile::P95 => TipPercentile::P95,
+        }
+    }
+}
+
+// Landed-tip percentiles (SOL) from the block engine's tip floor
+#[derive(Debug, Clone, PartialEq)]
+pub struct TipFloor {
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+impl TipFloor {
+    pub fn tip_at(&self, percentile: TipPercentile) -> f64 {
+        match percentile {
+            TipPercentile::P25 => self.p25,
+            TipPercentile::P50 => self.p50,
+            TipPercentile::P75 => self.p75,
+            TipPercentile::P95 => self.p95,
+        }
+    }
+}
+
+// The tip floor endpoint answers with a one-element array of the latest window
+pub fn parse_tip_floor(response: &Value) -> Option<TipFloor> {
+    let latest = response.as_array().and_then(|entries| entries.last()).unwrap_or(response);
+    let field = |percentile: &str| {
+        latest[format!("landed_tips_{}_percentile", percentile)].as_f64().filter(|tip| tip.is_finite() && *tip >= 0.0)
+    };
+    Some(TipFloor {
+        p25: field("25th")?,
+        p50: field("50th")?,
+        p75: field("75th")?,
+        p95: field("95th")?,
+    })
+}
+
+// The percentile's landed tip, capped at a share of the estimated profit so a spike in the
+// floor can't turn a profitable bundle into a loss
+pub fn bounded_tip(floor: &TipFloor, percentile: TipPercentile, estimated_profit: f64, max_profit_share: f64) -> f64 {
+    let cap = (estimated_profit * max_profit_share).max(0.0);
+    floor.tip_at(percentile).min(cap)
+}
+
+// Picks the percentile to tip at from past (percentile, landed, weight) outcomes: the cheapest one
+// with enough samples landing at the target rate, else the configured one, stepped up once it has
+// proven too low
+pub fn calibrated_percentile(
+    outcomes: &[(TipPercentile, bool, f64)],
+    configured: TipPercentile,
+    target_landing_rate: f64,
+) -> TipPercentile {
+    let landing_rate = |percentile: TipPercentile| {
+        let (total, landed) = outcomes.iter()
+            .filter(|(p, _, _)| *p == percentile)
+            .fold((0.0, 0.0), |(total, landed), (_, was_landed, weight)| {
+                (total + weight, if *was_landed { landed + weight } else { landed })
+            });
+        if total < MIN_CALIBRATION_SAMPLES as f64 {
+            return None;
+        }
+        Some(landed / total)
+    };
+
+    if let Some(percentile) = TipPercentile::ALL.into_iter()
+        .find(|p| landing_rate(*p).is_some_and(|rate| rate >= target_landing_rate))
+    {
+        return percentile;
+    }
+    match landing_rate(configured) {
+        Some(rate) if rate < target_landing_rate => configured.higher(),
+        _ => configured,
+    }
+}
+
+// Latest tip floor, polled from the block engine's REST endpoint. A floor older than the max
+// age counts as unavailable so tips fall back to the heuristic
+pub struct TipFloorCache {
+    client: reqwest::Client,
+    url: String,
+    floor: Arc<RwLock<Option<(Instant, TipFloor)>>>,
+    poll_interval: Duration,
+    max_age: Duration,
+}
+
+impl TipFloorCache {
+    pub fn new(url: String, poll_interval: Duration, max_age: Duration) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            url,
+            floor: Arc::new(RwLock::new(None)),
+            poll_interval,
+            max_age,
+        }
+    }
+
+    pub fn from_config(config: &JitoConfig) -> Self {
+        Self::new(
+            config.tip_floor_url.clone(),
+            Duration::from_millis(config.tip_floor_poll_ms.max(1)),
+            Duration::from_secs(config.tip_floor_max_age_secs),
+        )
+    }
+
+    pub async fn refresh(&self) -> Result<TipFloor, Box<dyn std::error::Error + Send + Sync>> {
+        let response: Value = self.client.get(&self.url).send().await?.error_for_status()?.json().await?;
+        let floor = parse_tip_floor(&response).ok_or("Tip floor response is missing percentiles")?;
+        *self.floor.write().await = Some((Instant::now(), floor.clone()));
+        Ok(floor)
+    }
+
+    // None when the floor was never fetched or has gone stale
+    pub async fn current(&self) -> Option<TipFloor> {
+        self.floor.read().await.as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() <= self.max_age)
+            .map(|(_, floor)| floor.clone())
+    }
+
+    pub fn start_polling(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = cache.refresh().await {
+                    Logger::status_update(&format!("Could not refresh Jito tip floor: {}", e));
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(cache.poll_interval) => {},
+                    _ = shutdown.changed() => return,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::utils::mock_http::{self, MockResponse};
+
+    fn floor() -> TipFloor {
+        TipFloor { p25: 0.00001, p50: 0.00005, p75: 0.0002, p95: 0.004 }
+    }
+
+    #[test]
+    fn test_tip_never_exceeds_profit_share() {
+        // Small opportunity: the p95 tip would eat the whole profit, so it's capped at half
+        assert!((bounded_tip(&floor(), TipPercentile::P95, 0.005, 0.5) - 0.0025).abs() < 1e-12);
+        // Large opportunity: the floor itself is the tip
+        assert!((bounded_tip(&floor(), TipPercentile::P50, 1.0, 0.5) - 0.00005).abs() < 1e-12);
+        assert!((bounded_tip(&floor(), TipPercentile::P75, 1.0, 0.5) - 0.0002).abs() < 1e-12);
+        // No profit, no tip
+        assert_eq!(bounded_tip(&floor(), TipPercentile::P25, -0.01, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_parses_tip_floor_response() {
+        let response = json!([{
+            "time": "2024-09-01T12:58:00Z",
+            "landed_tips_25th_percentile": 0.00001,
+            "landed_tips_50th_percentile": 0.00005,
+            "landed_tips_75th_percentile": 0.0002,
+            "landed_tips_95th_percentile": 0.004,
+            "landed_tips_99th_percentile": 0.02,
+            "ema_landed_tips_50th_percentile": 0.00004
+        }]);
+        assert_eq!(parse_tip_floor(&response), Some(floor()));
+        assert_eq!(parse_tip_floor(&json!([{ "landed_tips_25th_percentile": 0.00001 }])), None);
+        assert_eq!(TipPercentile::parse("75"), Some(TipPercentile::P75));
+        assert_eq!(TipPercentile::parse("p95"), Some(TipPercentile::P95));
+        assert_eq!(TipPercentile::parse("90"), None);
+    }
+
+    #[test]
+    fn test_calibration_steps_up_from_a_percentile_that_keeps_missing() {
+        // Too few outcomes to judge: keep the configured percentile
+        let few = vec![(TipPercentile::P50, false, 1.0); 5];
+        assert_eq!(calibrated_percentile(&few, TipPercentile::P50, 0.8), TipPercentile::P50);
+
+        let mut outcomes: Vec<(TipPercentile, bool, f64)> = (0..10).map(|i| (TipPercentile::P50, i < 4, 1.0)).collect();
+        assert_eq!(calibrated_percentile(&outcomes, TipPercentile::P50, 0.8), TipPercentile::P75);
+
+        // Once p75 proves itself it's used even with p50 configured
+        outcomes.extend((0..10).map(|i| (TipPercentile::P75, i < 9, 1.0)));
+        assert_eq!(calibrated_percentile(&outcomes, TipPercentile::P50, 0.8), TipPercentile::P75);
+
+        // And a cheaper percentile that lands often enough wins over it
+        outcomes.extend((0..10).map(|_| (TipPercentile::P25, true, 1.0)));
+        assert_eq!(calibrated_percentile(&outcomes, TipPercentile::P50, 0.8), TipPercentile::P25);
+
+        // Decayed outcomes count for less: half-weight p25 results no longer make the cut
+        let decayed: Vec<(TipPercentile, bool, f64)> = (0..10).map(|_| (TipPercentile::P25, true, 0.5)).collect();
+        assert_eq!(calibrated_percentile(&decayed, TipPercentile::P50, 0.8), TipPercentile::P50);
+    }
+
+    #[tokio::test]
+    async fn test_floor_unavailable_until_fetched_and_after_going_stale() {
+        let (url, _) = mock_http::serve(|_| MockResponse::json(&json!([{
+            "landed_tips_25th_percentile": 0.00001,
+            "landed_tips_50th_percentile": 0.00005,
+            "landed_tips_75th_percentile": 0.0002,
+            "landed_tips_95th_percentile": 0.004
+        }]))).await;
+
+        let cache = TipFloorCache::new(url.clone(), Duration::from_secs(10), Duration::from_secs(60));
+        assert_eq!(cache.current().await, None);
+        cache.refresh().await.unwrap();
+        assert_eq!(cache.current().await, Some(floor()));
+
+        let stale = TipFloorCache::new(url, Duration::from_secs(10), Duration::ZERO);
+        stale.refresh().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(stale.current().await, None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::logging::Logger;
+use crate::utils::tip_floor::TipPercentile;
+
+const MAX_OUTCOMES: usize = 500;
+const FILE_VERSION: u32 = 1;
+
+// Upper bounds (SOL) of the tip size buckets landing rates are reported for; the last is open
+const BUCKET_BOUNDS: [f64; 6] = [0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TipOutcome {
+    pub timestamp: u64, // Unix seconds, so age survives restarts
+    pub tip: f64,
+    pub percentile: Option<TipPercentile>,
+    pub landed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TipBucketRate {
+    pub bucket: String,
+    pub attempts: f64, // Decay-weighted
+    pub landed: f64,
+}
+
+impl TipBucketRate {
+    pub fn landing_rate(&self) -> f64 {
+        if self.attempts > 0.0 { self.landed / self.attempts } else { 0.0 }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TipHistoryFile {
+    version: u32,
+    outcomes: Vec<TipOutcome>,
+}
+
+pub fn now_unix() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn bucket_label(tip: f64) -> String {
+    match BUCKET_BOUNDS.iter().position(|bound| tip < *bound) {
+        Some(0) => format!("<{}", BUCKET_BOUNDS[0]),
+        Some(index) => format!("{}-{}", BUCKET_BOUNDS[index - 1], BUCKET_BOUNDS[index]),
+        None => format!(">={}", BUCKET_BOUNDS[BUCKET_BOUNDS.len() - 1]),
+    }
+}
+
+// Tip outcomes the optimizer learns from, persisted across restarts. Each outcome counts with a
+// weight that halves every half-life, so last week's market says less than this morning's
+#[derive(Debug, Clone)]
+pub struct TipHistory {
+    outcomes: Vec<TipOutcome>,
+    half_life: Duration,
+}
+
+impl TipHistory {
+    pub fn new(half_life: Duration) -> Self {
+        Self { outcomes: Vec::new(), half_life }
+    }
+
+    // A missing or unreadable file starts an empty history; it never stops startup
+    pub fn load(path: &str, half_life: Duration) -> Self {
+        let mut history = Self::new(half_life);
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return history,
+        };
+        match serde_json::from_str::<TipHistoryFile>(&contents) {
+            Ok(file) if file.version == FILE_VERSION => {
+                history.outcomes = file.outcomes;
+                history.trim();
+                Logger::status_update(&format!("Loaded {} tip outcomes from {}", history.outcomes.len(), path));
+            }
+            Ok(file) => Logger::error_occurred(&format!("Ignoring tip history {}: unsupported version {}", path, file.version)),
+            Err(e) => Logger::error_occurred(&format!("Ignoring corrupt tip history {}: {}", path, e)),
+        }
+        history
+    }
+
+        pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(&TipHistoryFile { version: FILE_VERSION, outcomes: self.outcomes.clone() })
+            .map_err(|e| format!("Failed to serialize tip history: {}", e))?;
+        crate::utils::atomic_write(path, json.as_bytes())
+            .map_err(|e| format!("Failed to write tip history to {}: {}", path, e).into())
+    }
+
+    pub fn record(&mut self, tip: f64, percentile: Option<TipPercentile>, landed: bool) {
+        self.record_at(tip, percentile, landed, now_unix());
+    }
+
+    pub fn record_at(&mut self, tip: f64, percentile: Option<TipPercentile>, landed: bool, timestamp: u64) {
+        self.outcomes.push(TipOutcome { timestamp, tip, percentile, landed });
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        if self.outcomes.len() > MAX_OUTCOMES {
+            let excess = self.outcomes.len() - MAX_OUTCOMES;
+            self.outcomes.drain(0..excess);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    fn weight(&self, outcome: &TipOutcome, now: u64) -> f64 {
+        let age_secs = now.saturating_sub(outcome.timestamp) as f64;
+        0.5f64.powf(age_secs / self.half_life.as_secs_f64().max(1.0))
+    }
+
+    // Decay-weighted share of outcomes that landed
+    pub fn success_rate_at(&self, now: u64) -> Option<f64> {
+        let (total, landed) = self.outcomes.iter().fold((0.0, 0.0), |(total, landed), outcome| {
+            let weight = self.weight(outcome, now);
+            (total + weight, if outcome.landed { landed + weight } else { landed })
+        });
+        (total > 0.0).then(|| landed / total)
+    }
+
+    // (percentile, landed, weight) for the outcomes whose tip came from the tip floor
+    pub fn percentile_outcomes_at(&self, now: u64) -> Vec<(TipPercentile, bool, f64)> {
+        self.outcomes.iter()
+            .filter_map(|outcome| outcome.percentile.map(|p| (p, outcome.landed, self.weight(outcome, now))))
+            .collect()
+    }
+
+    // Landing rate per tip size bucket, smallest tips first
+    pub fn bucket_rates_at(&self, now: u64) -> Vec<TipBucketRate> {
+        let mut rates: Vec<(usize, TipBucketRate)> = Vec::new();
+        for outcome in &self.outcomes {
+            let index = BUCKET_BOUNDS.iter().position(|bound| outcome.tip < *bound).unwrap_or(BUCKET_BOUNDS.len());
+            let weight = self.weight(outcome, now);
+            let position = match rates.iter().position(|(i, _)| *i == index) {
+                Some(position) => position,
+                None => {
+                    rates.push((index, TipBucketRate { bucket: bucket_label(outcome.tip), attempts: 0.0, landed: 0.0 }));
+                    rates.len() - 1
+                }
+            };
+            let rate = &mut rates[position].1;
+            rate.attempts += weight;
+            if outcome.landed {
+                rate.landed += weight;
+            }
+        }
+        rates.sort_by_key(|(index, _)| *index);
+        rates.into_iter().map(|(_, rate)| rate).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("tip_history_{}_{}.json", name, std::process::id())).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_history_round_trips_through_file() {
+        let path = temp_path("round_trip");
+        let mut history = TipHistory::new(Duration::from_secs(86_400));
+        history.record(0.00002, Some(TipPercentile::P50), true);
+        history.record(0.002, None, false);
+        history.save(&path).unwrap();
+
+        let loaded = TipHistory::load(&path, Duration::from_secs(86_400));
+        assert_eq!(loaded.outcomes, history.outcomes);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_missing_or_corrupt_file_starts_empty() {
+        assert!(TipHistory::load(&temp_path("missing"), Duration::from_secs(60)).is_empty());
+
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(TipHistory::load(&path, Duration::from_secs(60)).is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_old_outcomes_weigh_less() {
+        let day = 86_400;
+        let now = 10 * day;
+        let mut history = TipHistory::new(Duration::from_secs(day));
+        // A week ago tips failed; today they land
+        for _ in 0..10 {
+            history.record_at(0.0002, None, false, now - 7 * day);
+        }
+        for _ in 0..10 {
+            history.record_at(0.0002, None, true, now);
+        }
+
+        let rate = history.success_rate_at(now).unwrap();
+        assert!(rate > 0.99, "{}", rate);
+
+        let buckets = history.bucket_rates_at(now);
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].bucket, "0.0001-0.0005");
+        assert!((buckets[0].attempts - (10.0 + 10.0 / 128.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_buckets_are_ordered_by_tip_size() {
+        let mut history = TipHistory::new(Duration::from_secs(3600));
+        history.record_at(0.01, None, true, 0);
+        history.record_at(0.000001, None, false, 0);
+        let labels: Vec<String> = history.bucket_rates_at(0).into_iter().map(|rate| rate.bucket).collect();
+        assert_eq!(labels, vec!["<0.00001".to_string(), ">=0.005".to_string()]);
+    }
+}
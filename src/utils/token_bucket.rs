@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+// Token bucket that hands out reservations: a caller that takes a token before one is available
+// goes into debt and is told how long to wait, so waiting callers are served in order. All
+// methods take the current time so tests can drive it with a fake clock
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    burst: f64,
+    tokens: f64, // Negative while reservations are waiting on tokens
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_sec: f64, burst: f64, now: Instant) -> Self {
+        let burst = burst.max(1.0);
+        Self {
+            rate_per_sec: rate_per_sec.max(f64::MIN_POSITIVE),
+            burst,
+            tokens: burst,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate_per_sec).min(self.burst);
+        self.last_refill = self.last_refill.max(now);
+    }
+
+    // How long a reservation made now would have to wait
+    pub fn wait_at(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.rate_per_sec)
+        }
+    }
+
+    // Takes a token, returning how long to wait before using it
+    pub fn reserve_at(&mut self, now: Instant) -> Duration {
+        let wait = self.wait_at(now);
+        self.tokens -= 1.0;
+        wait
+    }
+
+    // Takes a token only if one is available right away
+    pub fn try_take_at(&mut self, now: Instant) -> bool {
+        if self.wait_at(now) > Duration::ZERO {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    // Whole tokens available right now
+    pub fn available_at(&mut self, now: Instant) -> u64 {
+        self.refill(now);
+        self.tokens.max(0.0).floor() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservations_queue_behind_the_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 2.0, start);
+
+        assert_eq!(bucket.reserve_at(start), Duration::ZERO);
+        assert_eq!(bucket.reserve_at(start), Duration::ZERO);
+        // Out of burst: each further reservation waits another half second
+        assert_eq!(bucket.reserve_at(start), Duration::from_millis(500));
+        assert_eq!(bucket.reserve_at(start), Duration::from_millis(1000));
+        assert!(!bucket.try_take_at(start));
+
+        // Time pays the debt back, and the bucket never refills past its burst
+        assert_eq!(bucket.wait_at(start + Duration::from_secs(1)), Duration::from_millis(500));
+        assert_eq!(bucket.available_at(start + Duration::from_secs(60)), 2);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::utils::pool_creation::WSOL_MINT;
+
+// Where mints past the cardinality cap are pooled
+pub const OTHER_MINTS: &str = "other";
+
+// Realized results attributed to one mint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPnl {
+    pub mint: String,
+    pub trades: u64,
+    pub successes: u64,
+    pub profit: f64, // Net; this mint's share of each trade
+    pub fees: f64,
+    pub tips: f64,
+}
+
+impl TokenPnl {
+    pub fn new(mint: &str) -> Self {
+        Self { mint: mint.to_string(), trades: 0, successes: 0, profit: 0.0, fees: 0.0, tips: 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenPnlLeaders {
+    pub winners: Vec<TokenPnl>, // Most profitable first
+    pub losers: Vec<TokenPnl>,  // Biggest loss first
+}
+
+fn is_sol(mint: &str) -> bool {
+    mint == WSOL_MINT || mint == "SOL"
+}
+
+// SOL is one side of nearly every trade, so a trade's PnL goes to the other tokens it involves;
+// SOL only gets it when nothing else is involved
+pub fn attributed_mints(mints: &[String]) -> Vec<String> {
+    let tokens: Vec<String> = mints.iter().filter(|mint| !is_sol(mint)).cloned().collect();
+    if tokens.is_empty() {
+        return mints.iter().take(1).cloned().collect();
+    }
+    tokens
+}
+
+// Per-mint realized PnL. A trade's profit, fees and tips are split evenly across its attributed
+// mints, so the per-mint figures add up to the totals. At most `max_mints` mints get their own
+// entry; later ones share OTHER_MINTS, which keeps the Prometheus label set bounded
+#[derive(Debug, Clone)]
+pub struct TokenPnlBook {
+    tokens: HashMap<String, TokenPnl>,
+    max_mints: usize,
+}
+
+impl TokenPnlBook {
+    pub fn new(max_mints: usize) -> Self {
+        Self { tokens: HashMap::new(), max_mints }
+    }
+
+    fn entry(&mut self, mint: &str) -> &mut TokenPnl {
+        let own_entries = self.tokens.len() - usize::from(self.tokens.contains_key(OTHER_MINTS));
+        let key = if self.tokens.contains_key(mint) || own_entries < self.max_mints { mint } else { OTHER_MINTS };
+        self.tokens.entry(key.to_string()).or_insert_with(|| TokenPnl::new(key))
+    }
+
+    // Profit is booked for successes only, as in the system totals; fees and tips always
+    pub fn record(&mut self, mints: &[String], success: bool, profit: f64, fees: f64, tips: f64) {
+        let mints = attributed_mints(mints);
+        let share = 1.0 / mints.len().max(1) as f64;
+        for mint in &mints {
+            let token = self.entry(mint);
+            token.trades += 1;
+            if success {
+                token.successes += 1;
+                token.profit += profit * share;
+            }
+            token.fees += fees * share;
+            token.tips += tips * share;
+        }
+    }
+
+    // Books the success of a trade recorded while its bundle was pending, once the bundle lands
+    pub fn confirm(&mut self, mints: &[String], profit: f64) {
+        let mints = attributed_mints(mints);
+        let share = 1.0 / mints.len().max(1) as f64;
+        for mint in &mints {
+            let token = self.entry(mint);
+            token.successes += 1;
+            token.profit += profit * share;
+        }
+    }
+
+    // Most profitable first
+    pub fn tokens(&self) -> Vec<TokenPnl> {
+        let mut tokens: Vec<TokenPnl> = self.tokens.values().cloned().collect();
+        tokens.sort_by(|a, b| b.profit.total_cmp(&a.profit).then_with(|| a.mint.cmp(&b.mint)));
+        tokens
+    }
+
+    pub fn leaders(&self, limit: usize) -> TokenPnlLeaders {
+        let tokens = self.tokens();
+        TokenPnlLeaders {
+            winners: tokens.iter().filter(|token| token.profit > 0.0).take(limit).cloned().collect(),
+            losers: tokens.iter().rev().filter(|token| token.profit < 0.0).take(limit).cloned().collect(),
+        }
+    }
+
+    // Continues a previous process's figures; see metrics_state
+    pub fn restore(&mut self, tokens: Vec<TokenPnl>) {
+        self.tokens = tokens.into_iter().map(|token| (token.mint.clone(), token)).collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mints(mints: &[&str]) -> Vec<String> {
+        mints.iter().map(|mint| mint.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pnl_goes_to_the_non_sol_mints_of_each_trade() {
+        let mut book = TokenPnlBook::new(10);
+        book.record(&mints(&[WSOL_MINT, "BONK"]), true, 0.04, 0.002, 0.001);
+        book.record(&mints(&[WSOL_MINT, "BONK"]), false, -0.01, 0.002, 0.0);
+        // A triangular route splits evenly between its two tokens
+        book.record(&mints(&[WSOL_MINT, "USDC", "WIF", WSOL_MINT]), true, 0.02, 0.002, 0.0);
+        book.record(&mints(&["SOL", "SOL"]), true, 0.001, 0.0, 0.0);
+        book.record(&mints(&[WSOL_MINT, "WIF"]), true, -0.05, 0.001, 0.0);
+
+        let tokens: HashMap<String, TokenPnl> = book.tokens().into_iter().map(|token| (token.mint.clone(), token)).collect();
+        assert_eq!(tokens["BONK"].trades, 2);
+        assert_eq!(tokens["BONK"].successes, 1);
+        assert!((tokens["BONK"].profit - 0.04).abs() < 1e-12);
+        assert!((tokens["BONK"].fees - 0.004).abs() < 1e-12);
+        assert!((tokens["USDC"].profit - 0.01).abs() < 1e-12);
+        assert!((tokens["WIF"].profit - (0.01 - 0.05)).abs() < 1e-12);
+        assert_eq!(tokens["SOL"].trades, 1);
+        assert!(!tokens.contains_key(WSOL_MINT));
+
+        let leaders = book.leaders(2);
+        assert_eq!(leaders.winners.iter().map(|token| token.mint.as_str()).collect::<Vec<_>>(), vec!["BONK", "USDC"]);
+        assert_eq!(leaders.losers.iter().map(|token| token.mint.as_str()).collect::<Vec<_>>(), vec!["WIF"]);
+
+        // Another BONK trade, booked while its bundle was pending and confirmed once it landed
+        book.record(&mints(&[WSOL_MINT, "BONK"]), false, 0.03, 0.002, 0.001);
+        book.confirm(&mints(&[WSOL_MINT, "BONK"]), 0.03);
+        let bonk = book.tokens().into_iter().find(|token| token.mint == "BONK").unwrap();
+        assert_eq!((bonk.trades, bonk.successes), (3, 2));
+        assert!((bonk.profit - 0.07).abs() < 1e-12);
+        assert!((bonk.fees - 0.006).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mints_past_the_cap_share_the_other_entry() {
+        let mut book = TokenPnlBook::new(2);
+        for mint in ["A", "B", "C", "D", "A"] {
+            book.record(&mints(&[WSOL_MINT, mint]), true, 0.01, 0.0, 0.0);
+        }
+        let tokens: HashMap<String, TokenPnl> = book.tokens().into_iter().map(|token| (token.mint.clone(), token)).collect();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens["A"].trades, 2);
+        assert_eq!(tokens["B"].trades, 1);
+        assert_eq!(tokens[OTHER_MINTS].trades, 2);
+    }
+}
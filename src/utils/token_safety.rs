@@ -0,0 +1,286 @@
+use std::sync::Arc;
+use std::time::Duration;
+use crate::config::TokenSafetyConfig;
+use crate::rpc::rpc_manager::RpcManager;
+use crate::utils::bounded_cache::BoundedCache;
+use crate::utils::mint_info::{MintInfo, MintInfoCache};
+use crate::utils::pool_creation::{USDC_MINT, WSOL_MINT};
+
+// Authorities held by these issuers are accepted
+const TRUSTED_MINTS: &[&str] = &[
+    WSOL_MINT,
+    USDC_MINT,
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+];
+
+const MAX_CACHED_FAILURES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenSafetyPolicy {
+    pub allow_mint_authority: bool, // The issuer can still print supply
+    pub allow_freeze_authority: bool, // The issuer can freeze our token account
+    pub max_transfer_fee_bps: u16, // Token-2022 transfer fee
+    pub allow_transfer_hook: bool,
+    pub allow_permanent_delegate: bool,
+    pub max_top_holder_share: Option<f64>, // None skips the holder check, an extra RPC call per mint
+    pub failure_ttl: Duration, // How long a failing mint is rejected without being looked at again
+}
+
+impl Default for TokenSafetyPolicy {
+    fn default() -> Self {
+        Self {
+            allow_mint_authority: false,
+            allow_freeze_authority: false,
+            max_transfer_fee_bps: 0,
+            allow_transfer_hook: false,
+            allow_permanent_delegate: false,
+            max_top_holder_share: None,
+            failure_ttl: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl TokenSafetyPolicy {
+    pub fn from_config(config: &TokenSafetyConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let max_top_holder_share = config.max_top_holder_share; // 0 skips the holder check
+        if !(0.0..=1.0).contains(&max_top_holder_share) {
+            return Err("TOKEN_SAFETY_MAX_TOP_HOLDER_SHARE must be in [0, 1]".into());
+        }
+
+        Ok(Self {
+            allow_mint_authority: config.allow_mint_authority,
+            allow_freeze_authority: config.allow_freeze_authority,
+            max_transfer_fee_bps: config.max_transfer_fee_bps,
+            allow_transfer_hook: config.allow_transfer_hook,
+            allow_permanent_delegate: config.allow_permanent_delegate,
+            max_top_holder_share: (max_top_holder_share > 0.0).then_some(max_top_holder_share),
+            failure_ttl: Duration::from_secs(config.failure_ttl_secs),
+        })
+    }
+
+    // What about the mint breaks the policy, if anything; the holder check needs the RPC and is
+    // left to TokenSafetyChecker
+    pub fn violation(&self, info: &MintInfo) -> Option<String> {
+        if !self.allow_mint_authority && info.mint_authority.is_some() {
+            return Some("has an active mint authority".to_string());
+        }
+        if !self.allow_freeze_authority && info.freeze_authority.is_some() {
+            return Some("has an active freeze authority".to_string());
+        }
+        if let Some(bps) = info.extensions.transfer_fee_bps {
+            if bps > self.max_transfer_fee_bps {
+                return Some(format!("charges a {} bps transfer fee", bps));
+            }
+        }
+        if let (false, Some(program)) = (self.allow_transfer_hook, &info.extensions.transfer_hook_program) {
+            return Some(format!("has a transfer hook ({})", program));
+        }
+        if let (false, Some(delegate)) = (self.allow_permanent_delegate, &info.extensions.permanent_delegate) {
+            return Some(format!("has a permanent delegate ({})", delegate));
+        }
+        None
+    }
+}
+
+// Rejects tokens we could buy but not get out of at a profit: printable supply, freezable
+// accounts, Token-2022 fees, hooks and delegates, and optionally a concentrated holder base.
+// Failures are remembered per mint for the policy's failure_ttl
+pub struct TokenSafetyChecker {
+    mint_info: Arc<MintInfoCache>,
+    rpc_manager: Arc<RpcManager>,
+    policy: TokenSafetyPolicy,
+    failures: BoundedCache<String>, // Mint -> why it failed
+}
+
+impl TokenSafetyChecker {
+    pub fn new(mint_info: Arc<MintInfoCache>, rpc_manager: Arc<RpcManager>, policy: TokenSafetyPolicy) -> Self {
+        Self {
+            mint_info,
+            rpc_manager,
+            policy,
+            failures: BoundedCache::new(MAX_CACHED_FAILURES, policy.failure_ttl),
+        }
+    }
+
+    pub fn policy(&self) -> TokenSafetyPolicy {
+        self.policy
+    }
+
+    // Why the mint fails the policy; None when it passes or can't be read (symbolic names, RPC
+    // errors), which are left to the other filters
+    pub async fn check(&self, mint: &str) -> Option<String> {
+        if TRUSTED_MINTS.contains(&mint) {
+            return None;
+        }
+        if let Some(reason) = self.failures.get(mint).await {
+            return Some(reason);
+        }
+
+        let info = self.mint_info.get(mint).await.ok()?;
+        let mut issue = self.policy.violation(&info);
+        if issue.is_none() {
+            if let Some(max_share) = self.policy.max_top_holder_share {
+                issue = self.check_holders(mint, &info, max_share).await;
+            }
+        }
+        let reason = format!("Token {} {}", mint, issue?);
+        self.failures.insert(mint.to_string(), reason.clone()).await;
+        Some(reason)
+    }
+
+    // The largest account is normally the pool's own vault, so the next one is what's measured
+    async fn check_holders(&self, mint: &str, info: &MintInfo, max_share: f64) -> Option<String> {
+        if info.supply == 0 {
+            return None;
+        }
+        let holders = self.rpc_manager.get_token_largest_accounts(mint).await.ok()?;
+        let share = holders.get(1)?.1 as f64 / info.supply as f64;
+        (share > max_share).then(|| format!("has a holder with {:.1}% of the supply", share * 100.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use serde_json::json;
+    use crate::utils::mock_http::{self, MockResponse};
+    use crate::rpc::rpc_manager::{RpcEndpoint, RpcEndpointType};
+
+    const AUTHORITY: &str = "BJE5MMbqXjVwjAF7oxwPYXnTXDyspzZyt4vwenNw5ruG";
+    const HOOK_PROGRAM: &str = "HooKvRbjkPWqaCJ4kx2cbD9zqTb1QHzmfDuprWQzFjsY";
+
+    // SPL Token mint account: 6 decimals, a supply of 1,000,000 tokens
+    fn spl_mint(mint_authority: bool, freeze_authority: bool) -> Vec<u8> {
+        let mut data = vec![0u8; 82];
+        let authority = bs58::decode(AUTHORITY).into_vec().unwrap();
+        if mint_authority {
+            data[0..4].copy_from_slice(&1u32.to_le_bytes());
+            data[4..36].copy_from_slice(&authority);
+        }
+        data[36..44].copy_from_slice(&1_000_000_000_000u64.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+        if freeze_authority {
+            data[46..50].copy_from_slice(&1u32.to_le_bytes());
+            data[50..82].copy_from_slice(&authority);
+        }
+        data
+    }
+
+    // Token-2022 mint account: the SPL layout padded to 165 bytes, the mint account type, then
+    // the extensions as (type, value)
+    fn token_2022_mint(extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = spl_mint(false, false);
+        data.resize(165, 0);
+        data.push(1);
+        for (extension_type, value) in extensions {
+            data.extend_from_slice(&extension_type.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    fn transfer_fee_config(older_bps: u16, newer_bps: u16) -> (u16, Vec<u8>) {
+        let mut value = vec![0u8; 108];
+        value[88..90].copy_from_slice(&older_bps.to_le_bytes());
+        value[106..108].copy_from_slice(&newer_bps.to_le_bytes());
+        (1, value)
+    }
+
+    fn transfer_hook(program: &str) -> (u16, Vec<u8>) {
+        let mut value = vec![0u8; 64];
+        value[32..64].copy_from_slice(&bs58::decode(program).into_vec().unwrap());
+        (14, value)
+    }
+
+    fn permanent_delegate(delegate: Option<&str>) -> (u16, Vec<u8>) {
+        let value = delegate.map_or(vec![0u8; 32], |delegate| bs58::decode(delegate).into_vec().unwrap());
+        (12, value)
+    }
+
+    #[test]
+    fn test_policy_rejects_authorities_and_token_2022_extensions() {
+        let policy = TokenSafetyPolicy::default();
+        let violation = |data: Vec<u8>| policy.violation(&MintInfo::decode(&data).unwrap());
+
+        assert_eq!(violation(spl_mint(false, false)), None);
+        assert_eq!(violation(spl_mint(true, false)).as_deref(), Some("has an active mint authority"));
+        assert_eq!(violation(spl_mint(false, true)).as_deref(), Some("has an active freeze authority"));
+
+        // An unrelated extension (metadata pointer) and an unset delegate are fine
+        assert_eq!(violation(token_2022_mint(&[(18, vec![0u8; 64]), permanent_delegate(None)])), None);
+        let fee = MintInfo::decode(&token_2022_mint(&[transfer_fee_config(50, 150)])).unwrap();
+        assert_eq!(fee.extensions.transfer_fee_bps, Some(150));
+        assert_eq!(policy.violation(&fee).as_deref(), Some("charges a 150 bps transfer fee"));
+        assert_eq!(TokenSafetyPolicy { max_transfer_fee_bps: 200, ..policy }.violation(&fee), None);
+        assert_eq!(
+            violation(token_2022_mint(&[transfer_fee_config(0, 0), transfer_hook(HOOK_PROGRAM)])),
+            Some(format!("has a transfer hook ({})", HOOK_PROGRAM))
+        );
+        assert_eq!(
+            violation(token_2022_mint(&[permanent_delegate(Some(AUTHORITY))])),
+            Some(format!("has a permanent delegate ({})", AUTHORITY))
+        );
+
+        let lenient = TokenSafetyPolicy {
+            allow_mint_authority: true,
+            allow_freeze_authority: true,
+            allow_transfer_hook: true,
+            allow_permanent_delegate: true,
+            ..policy
+        };
+        assert_eq!(lenient.violation(&MintInfo::decode(&spl_mint(true, true)).unwrap()), None);
+
+        // A truncated extension ends the list rather than failing the mint
+        let mut truncated = token_2022_mint(&[permanent_delegate(Some(AUTHORITY))]);
+        truncated.truncate(truncated.len() - 10);
+        assert_eq!(MintInfo::decode(&truncated).unwrap().extensions.permanent_delegate, None);
+    }
+
+    // Node serving `accounts` to getAccountInfo and `largest` to getTokenLargestAccounts; counts
+    // the requests
+    async fn mock_mint_node(accounts: Vec<(&'static str, Vec<u8>)>, largest: Vec<u64>) -> (String, Arc<AtomicUsize>) {
+        use base64::Engine;
+        mock_http::serve(move |request| {
+            let result = if request.body.contains("getTokenLargestAccounts") {
+                json!({ "value": largest.iter().map(|amount| json!({ "amount": amount.to_string() })).collect::<Vec<_>>() })
+            } else {
+                let data = accounts.iter().find(|(account, _)| request.body.contains(account)).map(|(_, data)| data);
+                json!({ "value": data.map(|data| json!({
+                    "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"]
+                })) })
+            };
+            MockResponse::rpc_result(request, result)
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_cached_per_mint() {
+        let (url, requests) = mock_mint_node(vec![
+            ("FreezeMint111111111111111111111111111111111", spl_mint(false, true)),
+            ("SafeMint11111111111111111111111111111111111", spl_mint(false, false)),
+        ], vec![900_000_000_000, 300_000_000_000]).await;
+        let rpc_manager = Arc::new(RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]));
+        let mint_info = Arc::new(MintInfoCache::new(Arc::clone(&rpc_manager)));
+        let checker = TokenSafetyChecker::new(Arc::clone(&mint_info), Arc::clone(&rpc_manager), TokenSafetyPolicy::default());
+
+        let reason = checker.check("FreezeMint111111111111111111111111111111111").await.unwrap();
+        assert!(reason.contains("freeze authority"));
+        let served = requests.load(Ordering::SeqCst);
+        assert_eq!(checker.check("FreezeMint111111111111111111111111111111111").await, Some(reason));
+        assert_eq!(requests.load(Ordering::SeqCst), served);
+
+        // Trusted issuers and unreadable mints pass; the second largest holder has 30%
+        assert_eq!(checker.check(USDC_MINT).await, None);
+        assert_eq!(checker.check("SOL").await, None);
+        assert_eq!(checker.check("SafeMint11111111111111111111111111111111111").await, None);
+        let concentrated = TokenSafetyChecker::new(mint_info, rpc_manager, TokenSafetyPolicy {
+            max_top_holder_share: Some(0.2),
+            ..TokenSafetyPolicy::default()
+        });
+        let reason = concentrated.check("SafeMint11111111111111111111111111111111111").await.unwrap();
+        assert!(reason.ends_with("has a holder with 30.0% of the supply"));
+    }
+}
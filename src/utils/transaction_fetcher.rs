@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use crate::config::RpcConfig;
+use crate::rpc::error::RpcError;
+use crate::rpc::rpc_manager::{RpcManager, RpcTaskType};
+
+type PendingFetch = (String, oneshot::Sender<Result<Value, RpcError>>);
+
+pub fn get_transaction_request(signature: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getTransaction",
+        "params": [
+            signature,
+            {
+                "encoding": "json",
+                "maxSupportedTransactionVersion": 0
+            }
+        ]
+    })
+}
+
+// Collects the mempool's getTransaction lookups that arrive within a short window into one
+// JSON-RPC batch, so a burst of notifications costs one round trip instead of one per signature
+pub struct TransactionFetcher {
+    sender: mpsc::Sender<PendingFetch>,
+}
+
+impl TransactionFetcher {
+    pub fn new(rpc_manager: Arc<RpcManager>, window: Duration, max_batch: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(1024);
+        tokio::spawn(Self::run(rpc_manager, receiver, window, max_batch.max(1)));
+        Self { sender }
+    }
+
+    pub fn from_config(rpc_manager: Arc<RpcManager>, config: &RpcConfig) -> Self {
+        Self::new(rpc_manager, Duration::from_millis(config.tx_fetch_batch_window_ms), config.tx_fetch_batch_max)
+    }
+
+    // The node's full getTransaction answer for the signature
+    pub async fn fetch(&self, signature: &str) -> Result<Value, RpcError> {
+        let (reply, response) = oneshot::channel();
+        self.sender.send((signature.to_string(), reply)).await
+            .map_err(|_| RpcError::Transport("Transaction fetcher stopped".to_string()))?;
+        response.await
+            .map_err(|_| RpcError::Transport("Transaction fetcher dropped the request".to_string()))?
+    }
+
+    async fn run(rpc_manager: Arc<RpcManager>, mut receiver: mpsc::Receiver<PendingFetch>, window: Duration, max_batch: usize) {
+        while let Some(first) = receiver.recv().await {
+            // The window opens with the first lookup, so a lone one waits at most that long
+            let mut pending = vec![first];
+            let deadline = tokio::time::Instant::now() + window;
+            while pending.len() < max_batch {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(fetch)) => pending.push(fetch),
+                    _ => break,
+                }
+            }
+
+            // Sent in the background so the next window doesn't wait on this round trip
+            let rpc_manager = Arc::clone(&rpc_manager);
+            tokio::spawn(async move {
+                let requests = pending.iter().map(|(signature, _)| get_transaction_request(signature)).collect();
+                let results = rpc_manager.make_batch_request(RpcTaskType::Read, requests).await;
+                for ((_, reply), result) in pending.into_iter().zip(results) {
+                    let _ = reply.send(result);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::rpc::rpc_manager::{RpcEndpoint, RpcEndpointType};
+    use crate::utils::mock_http::{self, MockResponse};
+
+    // Node answering each batch in reverse order, with the signature as the result; counts requests
+    async fn mock_batch_rpc() -> (String, Arc<AtomicUsize>) {
+        mock_http::serve(|request| {
+            let requests = request.json().as_array().cloned().unwrap_or_default();
+            let answers: Vec<Value> = requests.iter().rev()
+                .map(|request| json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "signature": request["params"][0] } }))
+                .collect();
+            MockResponse::json(&Value::Array(answers))
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_lookups_share_one_batch() {
+        let (url, hits) = mock_batch_rpc().await;
+        let rpc_manager = Arc::new(RpcManager::with_endpoints(vec![RpcEndpoint::new(url, RpcEndpointType::Helius, 1.0)]));
+        let fetcher = TransactionFetcher::new(rpc_manager, Duration::from_millis(50), 20);
+
+        let (a, b, c) = tokio::join!(fetcher.fetch("sigA"), fetcher.fetch("sigB"), fetcher.fetch("sigC"));
+
+        // Each caller gets its own transaction back although the answers came in reverse
+        assert_eq!(a.unwrap()["result"]["signature"], json!("sigA"));
+        assert_eq!(b.unwrap()["result"]["signature"], json!("sigB"));
+        assert_eq!(c.unwrap()["result"]["signature"], json!("sigC"));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use crate::utils::amm_math;
+use crate::utils::mev_simulation_pipeline::mev_operations::RouteStep;
+use crate::utils::opportunity_evaluator::PoolState;
+use crate::utils::pool_creation::WSOL_MINT;
+
+#[derive(Debug, Clone)]
+pub struct TriangularRoute {
+    pub steps: Vec<RouteStep>, // Three legs starting and ending in SOL
+    pub amount_in: u64,        // Lamports
+    pub amount_out: u64,       // Lamports
+}
+
+impl TriangularRoute {
+    pub fn profit_lamports(&self) -> i128 {
+        self.amount_out as i128 - self.amount_in as i128
+    }
+}
+
+// Lamports per raw unit of each token with a SOL pool, from the deepest such pool
+fn raw_prices_in_lamports<'a>(pools: &[&'a PoolState]) -> HashMap<&'a str, f64> {
+    let mut best: HashMap<&'a str, (u64, f64)> = HashMap::new();
+    for pool in pools {
+        let (token, reserve_token, reserve_sol) = if pool.token_a == WSOL_MINT {
+            (pool.token_b.as_str(), pool.reserve_b, pool.reserve_a)
+        } else if pool.token_b == WSOL_MINT {
+            (pool.token_a.as_str(), pool.reserve_a, pool.reserve_b)
+        } else {
+            continue;
+        };
+        if reserve_token == 0 {
+            continue;
+        }
+        let entry = best.entry(token).or_insert((0, 0.0));
+        if reserve_sol > entry.0 {
+            *entry = (reserve_sol, reserve_sol as f64 / reserve_token as f64);
+        }
+    }
+    best.into_iter().map(|(token, (_, price))| (token, price)).collect()
+}
+
+// Pool liquidity in SOL; pools without a SOL side are valued through the tokens' SOL pools
+fn pool_liquidity_sol(pool: &PoolState, prices: &HashMap<&str, f64>) -> f64 {
+    if pool.liquidity > 0.0 {
+        return pool.liquidity;
+    }
+    let side_value = |token: &str, reserve: u64| prices.get(token).map(|price| 2.0 * reserve as f64 * price / 1_000_000_000.0);
+    side_value(&pool.token_a, pool.reserve_a)
+        .or_else(|| side_value(&pool.token_b, pool.reserve_b))
+        .unwrap_or(0.0)
+}
+
+fn step(pool: &PoolState, input_mint: &str, amount_in: u64) -> Option<(RouteStep, u64)> {
+    let (reserve_in, reserve_out) = pool.reserves_for_input(input_mint)?;
+    let output_mint = if pool.token_a == input_mint { &pool.token_b } else { &pool.token_a };
+    let amount_out = amm_math::get_amount_out(amount_in, reserve_in, reserve_out, pool.fee_rate);
+    let route_step = RouteStep {
+        dex: pool.dex.clone(),
+        input_token: input_mint.to_string(),
+        output_token: output_mint.clone(),
+        pool_address: pool.pool_address.clone(),
+        amount_in,
+        expected_out: amount_out,
+    };
+    Some((route_step, amount_out))
+}
+
+// Profitable SOL -> X -> Y -> SOL cycles through three distinct pools, most profitable first.
+// Pools below min_liquidity_sol are pruned before the search
+pub fn find_triangular_routes(pools: &[PoolState], amount_in: u64, min_liquidity_sol: f64) -> Vec<TriangularRoute> {
+    search_routes(pools, amount_in, min_liquidity_sol).0
+}
+
+// The routes and how many swaps were quoted to find them. Each leg only walks the pools holding
+// its input mint, so the quotes grow with how the pools connect rather than with their cube
+fn search_routes(pools: &[PoolState], amount_in: u64, min_liquidity_sol: f64) -> (Vec<TriangularRoute>, usize) {
+    let all: Vec<&PoolState> = pools.iter()
+        .filter(|pool| pool.reserve_a > 0 && pool.reserve_b > 0)
+        .collect();
+    let prices = raw_prices_in_lamports(&all);
+    let liquid: Vec<&PoolState> = all.into_iter()
+        .filter(|pool| pool_liquidity_sol(pool, &prices) >= min_liquidity_sol)
+        .collect();
+
+    // mint -> pools holding it
+    let mut by_mint: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, pool) in liquid.iter().enumerate() {
+        by_mint.entry(pool.token_a.as_str()).or_default().push(index);
+        by_mint.entry(pool.token_b.as_str()).or_default().push(index);
+    }
+
+    let mut routes = Vec::new();
+    let mut quotes = 0;
+    for &first in by_mint.get(WSOL_MINT).map(Vec::as_slice).unwrap_or(&[]) {
+        quotes += 1;
+        let Some((leg_1, out_1)) = step(liquid[first], WSOL_MINT, amount_in) else { continue };
+        let token_x = leg_1.output_token.clone();
+
+        for &second in &by_mint[token_x.as_str()] {
+            let pool = liquid[second];
+            if second == first || pool.token_a == WSOL_MINT || pool.token_b == WSOL_MINT {
+                continue;
+            }
+            quotes += 1;
+            let Some((leg_2, out_2)) = step(pool, &token_x, out_1) else { continue };
+
+            for &third in by_mint.get(leg_2.output_token.as_str()).map(Vec::as_slice).unwrap_or(&[]) {
+                let pool = liquid[third];
+                if pool.token_a != WSOL_MINT && pool.token_b != WSOL_MINT {
+                    continue;
+                }
+                quotes += 1;
+                let Some((leg_3, out_3)) = step(pool, &leg_2.output_token, out_2) else { continue };
+                if out_3 > amount_in {
+                    routes.push(TriangularRoute {
+                        steps: vec![leg_1.clone(), leg_2.clone(), leg_3],
+                        amount_in,
+                        amount_out: out_3,
+                    });
+                }
+            }
+        }
+    }
+
+    routes.sort_by_key(|route| std::cmp::Reverse(route.profit_lamports()));
+    (routes, quotes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pool_creation::USDC_MINT;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn pool(address: &str, token_a: &str, reserve_a: u64, token_b: &str, reserve_b: u64) -> PoolState {
+        let liquidity = if token_a == WSOL_MINT {
+            2.0 * reserve_a as f64 / 1e9
+        } else if token_b == WSOL_MINT {
+            2.0 * reserve_b as f64 / 1e9
+        } else {
+            0.0
+        };
+        PoolState {
+            pool_address: address.to_string(),
+            dex: "Raydium".to_string(),
+            token_a: token_a.to_string(),
+            token_b: token_b.to_string(),
+            reserve_a,
+            reserve_b,
+            liquidity,
+            fee_rate: 0.0025,
+            vaults: Vec::new(),
+            last_updated: std::time::SystemTime::now(),
+        }
+    }
+
+    // SOL at 150 USDC, BONK at 0.00002 USDC, but the SOL/BONK pool prices BONK 5% cheaper
+    fn mispriced_pools() -> Vec<PoolState> {
+        vec![
+            pool("SolUsdc", WSOL_MINT, 10_000_000_000_000, USDC_MINT, 1_500_000_000_000),
+            pool("UsdcBonk", USDC_MINT, 1_000_000_000_000, BONK_MINT, 5_000_000_000_000_000),
+            pool("SolBonk", WSOL_MINT, 1_000_000_000_000, BONK_MINT, 787_500_000_000_000),
+        ]
+    }
+
+    #[test]
+    fn test_finds_profitable_cycle_with_chained_amounts() {
+        let routes = find_triangular_routes(&mispriced_pools(), 1_000_000_000, 10.0);
+        let best = &routes[0];
+
+        assert!(best.profit_lamports() > 0);
+        assert_eq!(best.steps.len(), 3);
+        assert_eq!(best.steps[0].input_token, WSOL_MINT);
+        assert_eq!(best.steps[2].output_token, WSOL_MINT);
+        for legs in best.steps.windows(2) {
+            assert_eq!(legs[0].output_token, legs[1].input_token);
+            assert_eq!(legs[0].expected_out, legs[1].amount_in);
+        }
+        assert_eq!(best.steps[2].expected_out, best.amount_out);
+        // Buying BONK where it's cheap: SOL -> BONK -> USDC -> SOL
+        assert_eq!(best.steps[0].pool_address, "SolBonk");
+    }
+
+    #[test]
+    fn test_prunes_shallow_pools_and_balanced_markets() {
+        assert!(find_triangular_routes(&mispriced_pools(), 1_000_000_000, 1_000_000.0).is_empty());
+
+        let balanced = vec![
+            pool("SolUsdc", WSOL_MINT, 10_000_000_000_000, USDC_MINT, 1_500_000_000_000),
+            pool("UsdcBonk", USDC_MINT, 1_000_000_000_000, BONK_MINT, 5_000_000_000_000_000),
+            pool("SolBonk", WSOL_MINT, 1_000_000_000_000, BONK_MINT, 750_000_000_000_000),
+        ];
+        assert!(find_triangular_routes(&balanced, 1_000_000_000, 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_search_only_quotes_connected_pools() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mints: Vec<String> = (0..40).map(|i| bs58::encode([i as u8; 32]).into_string()).collect();
+        let mut pools = Vec::new();
+        for (i, mint) in mints.iter().enumerate() {
+            pools.push(pool(&format!("Sol{}", i), WSOL_MINT, rng.gen_range(100..10_000) * 1_000_000_000, mint, rng.gen_range(1..1_000) * 1_000_000_000_000));
+        }
+        while pools.len() < 400 {
+            let (a, b) = (rng.gen_range(0..mints.len()), rng.gen_range(0..mints.len()));
+            if a != b {
+                pools.push(pool(&format!("Pair{}", pools.len()), &mints[a], rng.gen_range(1..1_000) * 1_000_000_000_000, &mints[b], rng.gen_range(1..1_000) * 1_000_000_000_000));
+            }
+        }
+
+        // One quote per SOL pool, plus for each pair pool on its output mint one more and one per
+        // SOL pool closing the cycle; counted instead of timed, so a loaded runner can't fail it
+        let has_sol = |pool: &PoolState| pool.token_a == WSOL_MINT || pool.token_b == WSOL_MINT;
+        let holds = |pool: &PoolState, mint: &str| pool.token_a == mint || pool.token_b == mint;
+        let other = |pool: &PoolState, mint: &str| if pool.token_a == mint { pool.token_b.clone() } else { pool.token_a.clone() };
+        let bound: usize = pools.iter().filter(|pool| has_sol(pool)).map(|sol_pool| {
+            let token_x = other(sol_pool, WSOL_MINT);
+            1 + pools.iter().filter(|pair| !has_sol(pair) && holds(pair, &token_x)).map(|pair| {
+                let token_y = other(pair, &token_x);
+                1 + pools.iter().filter(|closing| has_sol(closing) && holds(closing, &token_y)).count()
+            }).sum::<usize>()
+        }).sum();
+
+        let (routes, quotes) = search_routes(&pools, 1_000_000_000, 10.0);
+        assert!(quotes <= bound, "{} quotes, expected at most {}", quotes, bound);
+        assert!(quotes < pools.len() * pools.len(), "{} quotes for {} pools", quotes, pools.len());
+        assert_eq!(routes.len(), find_triangular_routes(&pools, 1_000_000_000, 10.0).len());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+
+pub const BUCKET_SECS: u64 = 60;
+pub const MAX_BUCKETS: usize = 24 * 60; // A day of minutes
+
+// Counts and sums over some span of minutes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowTotals {
+    pub opportunities_detected: u64,
+    pub opportunities_evaluated: u64,
+    pub opportunities_expired: u64,
+    pub executions: u64,
+    pub successes: u64, // Including pending bundles that landed, in the minute they did
+    pub profit: f64,
+    pub fees: f64,
+    pub tips: f64,
+    pub execution_time_ms: u64, // Sum over executions
+    pub bundles_landed: u64,
+    pub bundles_failed: u64,
+}
+
+impl WindowTotals {
+    fn add(&mut self, other: &WindowTotals) {
+        self.opportunities_detected += other.opportunities_detected;
+        self.opportunities_evaluated += other.opportunities_evaluated;
+        self.opportunities_expired += other.opportunities_expired;
+        self.executions += other.executions;
+        self.successes += other.successes;
+        self.profit += other.profit;
+        self.fees += other.fees;
+        self.tips += other.tips;
+        self.execution_time_ms += other.execution_time_ms;
+        self.bundles_landed += other.bundles_landed;
+        self.bundles_failed += other.bundles_failed;
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.executions > 0 { self.successes as f64 / self.executions as f64 } else { 0.0 }
+    }
+
+    pub fn avg_profit_per_success(&self) -> f64 {
+        if self.successes > 0 { self.profit / self.successes as f64 } else { 0.0 }
+    }
+
+    pub fn avg_execution_time_ms(&self) -> f64 {
+        if self.executions > 0 { self.execution_time_ms as f64 / self.executions as f64 } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MinuteBucket {
+    minute: u64, // Minutes since the Unix epoch
+    totals: WindowTotals,
+}
+
+// Per-minute totals for the last day, oldest first, so "last 15 minutes" or "last hour" can be
+// answered without keeping every event. Timestamps are Unix seconds
+#[derive(Debug, Clone, Default)]
+pub struct WindowedMetrics {
+    buckets: VecDeque<MinuteBucket>,
+}
+
+impl WindowedMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Bucket of the minute `now` falls in, created in order if missing. Buckets a day behind the
+    // newest are dropped; None for an event that old
+    pub fn bucket_at(&mut self, now: u64) -> Option<&mut WindowTotals> {
+        let minute = now / BUCKET_SECS;
+        let newest = self.buckets.back().map_or(minute, |bucket| bucket.minute.max(minute));
+        while self.buckets.front().is_some_and(|bucket| bucket.minute + MAX_BUCKETS as u64 <= newest) {
+            self.buckets.pop_front();
+        }
+        if minute + MAX_BUCKETS as u64 <= newest {
+            return None;
+        }
+
+        // Events nearly always land in the newest bucket; a late one is slotted in by minute
+        let index = match self.buckets.iter().rposition(|bucket| bucket.minute <= minute) {
+            Some(index) if self.buckets[index].minute == minute => index,
+            Some(index) => {
+                self.buckets.insert(index + 1, MinuteBucket { minute, totals: WindowTotals::default() });
+                index + 1
+            }
+            None => {
+                self.buckets.push_front(MinuteBucket { minute, totals: WindowTotals::default() });
+                0
+            }
+        };
+        Some(&mut self.buckets[index].totals)
+    }
+
+    // Totals of the last `minutes` minutes up to `now`, the current (partial) minute included.
+    // Precision is one bucket
+    pub fn totals_at(&self, now: u64, minutes: u64) -> WindowTotals {
+        let current = now / BUCKET_SECS;
+        let first = (current + 1).saturating_sub(minutes);
+        let mut totals = WindowTotals::default();
+        for bucket in self.buckets.iter().rev().take_while(|bucket| bucket.minute >= first) {
+            if bucket.minute <= current {
+                totals.add(&bucket.totals);
+            }
+        }
+        totals
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 1_700_000_040; // On a minute boundary
+
+    fn execute(windowed: &mut WindowedMetrics, at: u64, success: bool, profit: f64) {
+        let bucket = windowed.bucket_at(at).unwrap();
+        bucket.executions += 1;
+        bucket.execution_time_ms += 100;
+        bucket.fees += 0.001;
+        if success {
+            bucket.successes += 1;
+            bucket.profit += profit;
+        }
+    }
+
+    #[test]
+    fn test_windows_only_count_their_minutes() {
+        let mut windowed = WindowedMetrics::new();
+        // Two hours ago, 40 minutes ago, 10 minutes ago and now
+        execute(&mut windowed, START - 7200, true, 1.0);
+        execute(&mut windowed, START - 2400, false, 0.0);
+        execute(&mut windowed, START - 600, true, 0.02);
+        execute(&mut windowed, START + 30, true, 0.04);
+
+        let last_15 = windowed.totals_at(START + 30, 15);
+        assert_eq!(last_15.executions, 2);
+        assert_eq!(last_15.successes, 2);
+        assert!((last_15.profit - 0.06).abs() < 1e-9);
+        assert!((last_15.avg_profit_per_success() - 0.03).abs() < 1e-9);
+        assert_eq!(last_15.avg_execution_time_ms(), 100.0);
+
+        let last_hour = windowed.totals_at(START + 30, 60);
+        assert_eq!(last_hour.executions, 3);
+        assert!((last_hour.success_rate() - 2.0 / 3.0).abs() < 1e-9);
+
+        let last_day = windowed.totals_at(START + 30, 1440);
+        assert_eq!(last_day.executions, 4);
+        assert!((last_day.fees - 0.004).abs() < 1e-9);
+
+        // Looking back from an hour later, the 10-minutes-ago execution has left the last hour
+        assert_eq!(windowed.totals_at(START + 3000, 60).executions, 1);
+    }
+
+    #[test]
+    fn test_late_events_are_slotted_in_by_minute() {
+        let mut windowed = WindowedMetrics::new();
+        execute(&mut windowed, START, true, 0.01);
+        execute(&mut windowed, START - 300, true, 0.01);
+        execute(&mut windowed, START + 60, true, 0.01);
+        assert_eq!(windowed.len(), 3);
+
+        // 1 minute back from START + 60 is only that minute; 2 minutes back also reaches START
+        assert_eq!(windowed.totals_at(START + 60, 1).executions, 1);
+        assert_eq!(windowed.totals_at(START + 60, 2).executions, 2);
+        assert_eq!(windowed.totals_at(START + 60, 10).executions, 3);
+    }
+
+    #[test]
+    fn test_memory_is_bounded_to_a_day_of_buckets() {
+        let mut windowed = WindowedMetrics::new();
+        for minute in 0..(2 * MAX_BUCKETS as u64) {
+            execute(&mut windowed, START + minute * BUCKET_SECS, true, 0.001);
+        }
+        assert_eq!(windowed.len(), MAX_BUCKETS);
+
+        let now = START + (2 * MAX_BUCKETS as u64 - 1) * BUCKET_SECS;
+        assert_eq!(windowed.totals_at(now, 1440).executions, MAX_BUCKETS as u64);
+        // Older than anything kept
+        assert!(windowed.bucket_at(START).is_none());
+    }
+}
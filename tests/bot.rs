@@ -0,0 +1,189 @@
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use futures::SinkExt;
+use futures_util::StreamExt;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use serde_json::{json, Value};
+use solana_sdk::signature::Keypair;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+use rust_mev_hybrid_bot::config::{Config, Network};
+use rust_mev_hybrid_bot::utils::mev_strategies::MevStrategyType;
+use rust_mev_hybrid_bot::Bot;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mev_bot_lib_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn answer(request: &Value) -> Value {
+    let result = match request["method"].as_str() {
+        Some("getHealth") => json!("ok"),
+        Some("getVersion") => json!({ "solana-core": "1.17.20", "feature-set": 1 }),
+        Some("getSlot") => json!(250_000_000u64),
+        Some("getBalance") => json!({ "context": { "slot": 1 }, "value": 5_000_000_000u64 }),
+        Some("getLatestBlockhash") => json!({
+            "context": { "slot": 1 },
+            "value": { "blockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N", "lastValidBlockHeight": 1 }
+        }),
+        Some("getRecentPrioritizationFees") | Some("getRecentPerformanceSamples") => json!([]),
+        Some("getEpochInfo") => json!({
+            "absoluteSlot": 250_000_000u64, "blockHeight": 1, "epoch": 1, "slotIndex": 0, "slotsInEpoch": 432_000
+        }),
+        Some("getMultipleAccounts") | Some("getTokenAccountsByOwner") => json!({ "context": { "slot": 1 }, "value": [] }),
+        _ => Value::Null,
+    };
+    json!({ "jsonrpc": "2.0", "id": request["id"].clone(), "result": result })
+}
+
+// RPC node with a funded wallet; batches are answered element by element
+fn mock_rpc() -> String {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+            let response = match &request {
+                Value::Array(requests) => Value::Array(requests.iter().map(answer).collect()),
+                request => answer(request),
+            };
+            Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+        }))
+    });
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+// WebSocket endpoint confirming every subscription and counting logsSubscribe requests
+async fn mock_ws(log_subscriptions: Arc<AtomicUsize>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let log_subscriptions = Arc::clone(&log_subscriptions);
+            tokio::spawn(async move {
+                let Ok(mut ws_stream) = tokio_tungstenite::accept_async(socket).await else { return };
+                let mut next_subscription = 1u64;
+                while let Some(Ok(message)) = ws_stream.next().await {
+                    let Message::Text(text) = message else { continue };
+                    let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+                    let method = request["method"].as_str().unwrap_or_default();
+                    if method == "logsSubscribe" {
+                        log_subscriptions.fetch_add(1, Ordering::SeqCst);
+                    }
+                    if method.ends_with("Subscribe") {
+                        let confirmation = json!({ "jsonrpc": "2.0", "result": next_subscription, "id": request["id"].clone() });
+                        next_subscription += 1;
+                        let _ = ws_stream.send(Message::Text(confirmation.to_string())).await;
+                    }
+                }
+            });
+        }
+    });
+    url
+}
+
+// A dry-run devnet bot against the mocks, with a wallet and every state file in `dir`
+fn mock_bot(dir: &Path, rpc_url: &str, ws_url: &str) -> Bot {
+    let keypair_path = dir.join("wallet.json");
+    if !keypair_path.exists() {
+        std::fs::write(&keypair_path, serde_json::to_string(&Keypair::new().to_bytes().to_vec()).unwrap()).unwrap();
+    }
+    let mut config = Config::default();
+    config.rpc.helius = Some(rpc_url.to_string());
+    config.rpc.solana_rpc_url = Some(rpc_url.to_string());
+    config.rpc.solana_ws_url = Some(ws_url.to_string());
+    config.shutdown_timeout_ms = 1000;
+    config.strategies.triangular_arb_interval_ms = 0;
+    config.metrics.state_file = dir.join("metrics_state.json").to_string_lossy().to_string();
+    config.metrics.metrics_file = dir.join("mev_metrics.json").to_string_lossy().to_string();
+    config.metrics.analytics_file = dir.join("mev_analytics.json").to_string_lossy().to_string();
+    config.risk.state_file = dir.join("risk_state.json").to_string_lossy().to_string();
+    config.jito.tip_history_file = dir.join("tip_history.json").to_string_lossy().to_string();
+    config.pools.blacklist_file = dir.join("pool_blacklist.json").to_string_lossy().to_string();
+
+    Bot::builder()
+        .with_config(config)
+        .with_network(Network::Devnet)
+        .with_strategies(vec![MevStrategyType::Arbitrage])
+        .with_dry_run(true)
+        .with_keypair_path(&keypair_path.to_string_lossy())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bot_starts_against_mocks_and_stops_cleanly() {
+    let dir = scratch_dir("start_stop");
+    let rpc_url = mock_rpc();
+    let log_subscriptions = Arc::new(AtomicUsize::new(0));
+    let ws_url = mock_ws(Arc::clone(&log_subscriptions)).await;
+
+    let state_file = dir.join("metrics_state.json");
+    let bot = mock_bot(&dir, &rpc_url, &ws_url);
+    let report = bot.preflight().await;
+    assert!(report.passed(), "{:?}", report.failures());
+
+    let handle = bot.start().await.unwrap();
+    let risk = handle.risk();
+    // The preflight subscribed once; the running mempool subscribes again and seeds its balance
+    let started = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if log_subscriptions.load(Ordering::SeqCst) >= 2 && risk.get_risk_metrics().await.current_balance == 5.0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await;
+    assert!(started.is_ok(), "the mempool never subscribed or seeded its balance");
+    assert!(handle.metrics().persisted_metrics().await.system.dry_run);
+    assert!(handle.metrics_addr().is_none());
+
+    let stopped = tokio::time::timeout(Duration::from_secs(30), handle.stop()).await;
+    assert_eq!(stopped, Ok(Ok(())));
+    assert!(state_file.exists(), "metrics state was not flushed at shutdown");
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+// Every loop a run spawns stops with it, so starting the bot again doesn't pile up tasks
+#[tokio::test(flavor = "multi_thread")]
+async fn test_restarted_bot_leaves_no_tasks_behind() {
+    let dir = scratch_dir("restart");
+    let rpc_url = mock_rpc();
+    let log_subscriptions = Arc::new(AtomicUsize::new(0));
+    let ws_url = mock_ws(Arc::clone(&log_subscriptions)).await;
+    let runtime = tokio::runtime::Handle::current().metrics();
+
+    let mut after_first_run = None;
+    for run in 1..=3 {
+        let handle = mock_bot(&dir, &rpc_url, &ws_url).start().await.unwrap();
+        let subscribed = tokio::time::timeout(Duration::from_secs(10), async {
+            while log_subscriptions.load(Ordering::SeqCst) < run {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await;
+        assert!(subscribed.is_ok(), "run {} never subscribed", run);
+        let stopped = tokio::time::timeout(Duration::from_secs(30), handle.stop()).await;
+        assert_eq!(stopped, Ok(Ok(())));
+
+        // Connections to the mocks close a moment after the bot lets go of its clients
+        let baseline = *after_first_run.get_or_insert_with(|| runtime.num_alive_tasks());
+        let settled = tokio::time::timeout(Duration::from_secs(5), async {
+            while runtime.num_alive_tasks() > baseline {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await;
+        assert!(settled.is_ok(), "{} tasks alive after run {}, {} after the first", runtime.num_alive_tasks(), run, baseline);
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
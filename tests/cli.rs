@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+use assert_cmd::Command;
+use predicates::str::contains;
+use solana_sdk::signature::{Keypair, Signer};
+
+use rust_mev_hybrid_bot::config::Config;
+use rust_mev_hybrid_bot::utils::analytics::Analytics;
+use rust_mev_hybrid_bot::utils::metrics_collector::MetricsCollector;
+use rust_mev_hybrid_bot::utils::metrics_state::MetricsState;
+
+// Nothing listens on the discard port, so every connectivity check fails fast and offline
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+const UNREACHABLE_WS: &str = "ws://127.0.0.1:9";
+
+// Each test runs the binary in its own directory with an empty environment, so neither a
+// developer's .env nor the repo's keypair leaks in
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mev_bot_cli_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn bot(dir: &Path) -> Command {
+    let mut command = Command::cargo_bin("rust-mev-hybrid-bot").unwrap();
+    command.current_dir(dir).env_clear().env("NO_COLOR", "1");
+    command
+}
+
+fn with_unreachable_endpoints(command: &mut Command) -> &mut Command {
+    command
+        .env("HELIUS", UNREACHABLE)
+        .env("DRPC", UNREACHABLE)
+        .env("SOLANA_WS_URL", UNREACHABLE_WS)
+        .env("JITO_RPC_URL", UNREACHABLE)
+        .env("JITO_TIP_ACCOUNT", "96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx")
+}
+
+#[test]
+fn test_run_validates_the_environment_and_flags_override_it() {
+    let dir = scratch_dir("run");
+
+    bot(&dir).arg("run")
+        .assert()
+        .failure()
+        .stderr(contains("HELIUS is not set"));
+
+    // USE_JITO without its endpoint, and a malformed URL, are reported together
+    bot(&dir)
+        .env("HELIUS", "helius.example")
+        .env("USE_JITO", "true")
+        .env("JITO_TIP_ACCOUNT", "96gYZGLnJYVFvJJvLL1JUH6ZVx5AZPfC4DW4wxPqZDAx")
+        .arg("run")
+        .assert()
+        .failure()
+        .stderr(contains("HELIUS is not a valid URL"))
+        .stderr(contains("JITO_RPC_URL is not set"));
+
+    // A valid STRATEGY in the environment loses to the misspelled flag
+    with_unreachable_endpoints(&mut bot(&dir))
+        .env("STRATEGY", "arbitrage")
+        .args(["run", "--strategy", "arbitrge"])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown strategy 'arbitrge'"));
+
+    // A valid configuration still has to pass the preflight before the mempool starts
+    with_unreachable_endpoints(&mut bot(&dir))
+        .args(["run", "--dry-run"])
+        .assert()
+        .failure()
+        .stdout(contains("RPC Helius: FAILED"))
+        .stderr(contains("preflight check(s) failed"))
+        .stderr(contains("RPC Helius: Check that HELIUS is correct and reachable"));
+}
+
+#[test]
+fn test_run_refuses_unconfirmed_mainnet_and_jito_off_mainnet() {
+    let dir = scratch_dir("interlock");
+
+    with_unreachable_endpoints(&mut bot(&dir))
+        .args(["run", "--network", "mainnet"])
+        .assert()
+        .failure()
+        .stderr(contains("Refusing to trade real funds on MAINNET"))
+        .stderr(contains("CONFIRM_MAINNET=yes"));
+
+    // Confirming with anything but "yes" doesn't count
+    with_unreachable_endpoints(&mut bot(&dir))
+        .env("NETWORK", "mainnet")
+        .env("CONFIRM_MAINNET", "true")
+        .arg("run")
+        .assert()
+        .failure()
+        .stderr(contains("Refusing to trade real funds on MAINNET"));
+
+    with_unreachable_endpoints(&mut bot(&dir))
+        .env("USE_JITO", "true")
+        .args(["run", "--network", "devnet", "--i-understand-mainnet"])
+        .assert()
+        .failure()
+        .stderr(contains("USE_JITO=true doesn't work on DEVNET"));
+}
+
+#[test]
+fn test_check_prints_resolved_settings_and_fails_on_unreachable_endpoints() {
+    let dir = scratch_dir("check");
+
+    with_unreachable_endpoints(&mut bot(&dir))
+        .env("NETWORK", "devnet")
+        .env("STRATEGY", "sandwich")
+        .args(["check", "--network", "testnet", "--strategy", " Arbitrage,snipe,arbitrage", "--dry-run"])
+        .assert()
+        .failure()
+        .stdout(contains("Network: TESTNET"))
+        .stdout(contains("Strategies: arbitrage, snipe"))
+        .stdout(contains("Dry run: yes"))
+        .stdout(contains("HELIUS: http://127.0.0.1:9"))
+        .stdout(contains("RPC Helius: FAILED"))
+        .stdout(contains("RPC Drpc: FAILED"))
+        .stdout(contains("WebSocket 127.0.0.1:9: FAILED"))
+        .stdout(contains("Jito: FAILED"))
+        .stdout(contains("Keypair: FAILED"))
+        .stderr(contains("5 preflight check(s) failed"))
+        .stderr(contains("Keypair: Create it with `solana-keygen new -o solana-keypair.json`"));
+
+    bot(&dir).args(["check", "--network", "localnet"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid value 'localnet'"));
+}
+
+#[test]
+fn test_balance_reads_the_keypair_before_querying_rpc() {
+    let dir = scratch_dir("balance");
+    let keypair = Keypair::new();
+    let keypair_path = dir.join("wallet.json");
+    std::fs::write(&keypair_path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+
+    with_unreachable_endpoints(&mut bot(&dir))
+        .args(["balance", "--keypair", keypair_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout(contains(format!("Wallet: {}", keypair.pubkey())))
+        .stderr(contains("Failed to fetch the SOL balance"));
+
+    bot(&dir).arg("balance")
+        .assert()
+        .failure()
+        .stderr(contains("Failed to read keypair solana-keypair.json"));
+}
+
+#[tokio::test]
+async fn test_metrics_dumps_the_persisted_state() {
+    let dir = scratch_dir("metrics");
+    let state_path = dir.join("metrics_state.json");
+    let collector = MetricsCollector::new(&Config::default()).unwrap();
+    MetricsState::new(collector.persisted_metrics().await, Analytics::new(false))
+        .save(state_path.to_str().unwrap())
+        .unwrap();
+
+    // The path comes from METRICS_STATE_FILE when --file isn't given
+    let output = bot(&dir)
+        .env("METRICS_STATE_FILE", &state_path)
+        .arg("metrics")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let dumped: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dumped["system"]["total_opportunities_executed"], 0);
+    assert!(dumped["strategies"].is_array());
+
+    bot(&dir).args(["metrics", "--file", "missing.json"])
+        .assert()
+        .failure()
+        .stderr(contains("Failed to read metrics state from missing.json"));
+}